@@ -0,0 +1,310 @@
+//! Regenerates the CSV test vectors of the token math (`top_block_reports` and `bottom_block_reports`) from the crate itself,
+//! so the vectors always cover exactly the range of blocks the math is defined for and they can be reproduced by anyone.
+//!
+//! The blocks, requests and wallet balances are sampled with a fixed seed, so running the generator again produces the same files.
+//! A diff in the regenerated vectors means the token math changed.
+//! The generated vectors are not committed to the repository, they are ignored by git.
+//! The originally published vectors in the `published` subdirectories are a fixed oracle and they are not touched by the generator.
+//!
+//! Usage: `cargo run -p sallar-math --bin generate_test_vectors [output directory]`,
+//! the vectors are written to the crate directory by default.
+
+use std::{
+    env,
+    error::Error,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use sallar_math::{
+    calculate_bottom_bp_with_boost, calculate_bottom_bp_without_boost, calculate_dust_per_bp,
+    calculate_max_bp, calculate_top_bp_with_boost, calculate_user_reward_bottom_block,
+    calculate_user_reward_top_block, MathError, MAX_BLOCK_INDEX, TOKEN_AMOUNT_SCALING_FACTOR,
+};
+
+/// By default the program rewards bottom block solutions of users with the balance of at least 20_000 Sallar.
+const DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 = 2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+/// The highest wallet balance of the sampled bottom block users (20_000_000 Sallar).
+const MAX_SAMPLED_WALLET_BALANCE: u64 = 1_000 * DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE;
+
+/// The highest number of requests with or without boost of a sampled user.
+const MAX_SAMPLED_REQUESTS: u64 = 5;
+
+/// Every n-th range of blocks with the same BP and boost is reported in the top block report.
+const TOP_REPORT_RANGE_INTERVAL: usize = 3;
+
+const TOP_TRANSFER_ROWS: usize = 150_000;
+const BOTTOM_REPORT_ROWS: usize = 60_000;
+const BOTTOM_TRANSFER_ROWS: usize = 90_000;
+
+const SEED: u64 = 0x5a11_a2d5_eed0_0001;
+
+type GeneratorResult<T> = Result<T, Box<dyn Error>>;
+
+/// SplitMix64, a minimal deterministic generator, so the vectors do not depend on an external random number generator.
+struct Sampler {
+    state: u64,
+}
+
+impl Sampler {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        value ^ (value >> 31)
+    }
+
+    /// Samples a value from the inclusive range.
+    fn sample(&mut self, min: u64, max: u64) -> u64 {
+        min + self.next_u64() % (max - min + 1)
+    }
+
+    fn sample_block_index(&mut self) -> u64 {
+        self.sample(1, MAX_BLOCK_INDEX)
+    }
+
+    /// Samples the requests without and with boost, a user always sends at least one request.
+    fn sample_requests(&mut self) -> (u8, u8) {
+        loop {
+            let requests = (
+                self.sample(0, MAX_SAMPLED_REQUESTS) as u8,
+                self.sample(0, MAX_SAMPLED_REQUESTS) as u8,
+            );
+            if requests != (0, 0) {
+                return requests;
+            }
+        }
+    }
+
+    /// Samples the wallet balance of a user, a part of the balances are below the minimum required stake.
+    fn sample_wallet_balance(&mut self) -> u64 {
+        self.sample(
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE / 2,
+            MAX_SAMPLED_WALLET_BALANCE,
+        )
+    }
+}
+
+fn math<T>(result: Result<T, MathError>) -> GeneratorResult<T> {
+    result.map_err(|error| error.to_string().into())
+}
+
+fn create_report(
+    output_dir: &Path,
+    directory: &str,
+    file_name: &str,
+    header: &str,
+) -> GeneratorResult<BufWriter<File>> {
+    let directory = output_dir.join(directory);
+    fs::create_dir_all(&directory)?;
+    let mut writer = BufWriter::new(File::create(directory.join(file_name))?);
+    writeln!(writer, "{header}")?;
+
+    Ok(writer)
+}
+
+/// The rewards of a single request without boost and a single request with boost of the top block, and the dust per BP.
+/// The values are the same for all blocks of a range reported in a single row.
+fn top_block_report_values(block_index: u64) -> GeneratorResult<(u64, u64, u64, f64)> {
+    let dust_per_bp = math(calculate_dust_per_bp(block_index))?;
+    let top_bp_with_boost = math(calculate_top_bp_with_boost(block_index))?;
+    let (_, dust_without_boost) = math(calculate_user_reward_top_block(
+        1,
+        0,
+        top_bp_with_boost,
+        dust_per_bp,
+    ))?;
+    let (_, dust_with_boost) = math(calculate_user_reward_top_block(
+        0,
+        1,
+        top_bp_with_boost,
+        dust_per_bp,
+    ))?;
+
+    Ok((
+        dust_without_boost,
+        dust_with_boost,
+        top_bp_with_boost,
+        dust_per_bp.to_f64(),
+    ))
+}
+
+fn generate_top_block_report(output_dir: &Path) -> GeneratorResult<()> {
+    let mut writer = create_report(
+        output_dir,
+        "top_block_reports",
+        "dustAndBlockPartReportTop.csv",
+        "block index,dust without boost,dust with boost,block parts with boost,dustPerBlockPart,until block index",
+    )?;
+
+    // the ranges of blocks with the same BP and boost
+    let mut ranges = Vec::new();
+    let mut range_start = 1;
+    let mut range_key = (
+        math(calculate_max_bp(1))?,
+        math(calculate_top_bp_with_boost(1))?,
+    );
+    for block_index in 2..=MAX_BLOCK_INDEX {
+        let key = (
+            math(calculate_max_bp(block_index))?,
+            math(calculate_top_bp_with_boost(block_index))?,
+        );
+        if key != range_key {
+            ranges.push((range_start, block_index - 1));
+            range_start = block_index;
+            range_key = key;
+        }
+    }
+    ranges.push((range_start, MAX_BLOCK_INDEX));
+
+    for (block_index, until_block_index) in ranges.into_iter().step_by(TOP_REPORT_RANGE_INTERVAL) {
+        let (dust_without_boost, dust_with_boost, bp_with_boost, dust_per_bp) =
+            top_block_report_values(block_index)?;
+        writeln!(
+            writer,
+            "{block_index},{dust_without_boost},{dust_with_boost},{bp_with_boost},{dust_per_bp:?},{until_block_index}"
+        )?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn generate_top_block_transfers(output_dir: &Path, sampler: &mut Sampler) -> GeneratorResult<()> {
+    let mut writer = create_report(
+        output_dir,
+        "top_block_reports",
+        "topBlockTransferTestData.csv",
+        "block_index,req_without_boost,req_with_boost,reward_dust,bp",
+    )?;
+
+    let mut block_indexes = (0..TOP_TRANSFER_ROWS)
+        .map(|_| sampler.sample_block_index())
+        .collect::<Vec<u64>>();
+    block_indexes.sort_unstable();
+    for block_index in block_indexes {
+        let (request_without_boost, request_with_boost) = sampler.sample_requests();
+        let (bp, reward_dust) = math(calculate_user_reward_top_block(
+            request_without_boost,
+            request_with_boost,
+            math(calculate_top_bp_with_boost(block_index))?,
+            math(calculate_dust_per_bp(block_index))?,
+        ))?;
+        writeln!(
+            writer,
+            "{block_index},{request_without_boost},{request_with_boost},{reward_dust},{bp}"
+        )?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn generate_bottom_block_report(output_dir: &Path, sampler: &mut Sampler) -> GeneratorResult<()> {
+    let mut writer = create_report(
+        output_dir,
+        "bottom_block_reports",
+        "dustAndBlockPartReportBottom.csv",
+        "number,walletBalance,dust without boost,block parts without boost,dust with boost,block parts with boost,dustPerBlockPart",
+    )?;
+
+    let mut block_indexes = (0..BOTTOM_REPORT_ROWS)
+        .map(|_| sampler.sample_block_index())
+        .collect::<Vec<u64>>();
+    block_indexes.sort_unstable_by(|a, b| b.cmp(a));
+    for block_index in block_indexes {
+        let wallet_balance = sampler.sample_wallet_balance();
+        let dust_per_bp = math(calculate_dust_per_bp(block_index))?;
+        let bp_without_boost = math(calculate_bottom_bp_without_boost(wallet_balance))?;
+        let bp_with_boost = math(calculate_bottom_bp_with_boost(block_index, wallet_balance))?;
+        let (_, dust_without_boost) = math(calculate_user_reward_bottom_block(
+            1,
+            0,
+            bp_without_boost,
+            bp_with_boost,
+            dust_per_bp,
+            wallet_balance,
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+        ))?;
+        let (_, dust_with_boost) = math(calculate_user_reward_bottom_block(
+            0,
+            1,
+            bp_without_boost,
+            bp_with_boost,
+            dust_per_bp,
+            wallet_balance,
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+        ))?;
+        writeln!(
+            writer,
+            "{block_index},{wallet_balance},{dust_without_boost},{bp_without_boost},{dust_with_boost},{bp_with_boost},{:?}",
+            dust_per_bp.to_f64()
+        )?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn generate_bottom_block_transfers(
+    output_dir: &Path,
+    sampler: &mut Sampler,
+) -> GeneratorResult<()> {
+    let mut writer = create_report(
+        output_dir,
+        "bottom_block_reports",
+        "bottomBlockTransferTestData.csv",
+        "block_index,req_without_boost,req_with_boost,wallet_balance,reward_dust,bp",
+    )?;
+
+    let mut block_indexes = (0..BOTTOM_TRANSFER_ROWS)
+        .map(|_| sampler.sample_block_index())
+        .collect::<Vec<u64>>();
+    block_indexes.sort_unstable_by(|a, b| b.cmp(a));
+    for block_index in block_indexes {
+        let (request_without_boost, request_with_boost) = sampler.sample_requests();
+        let wallet_balance = sampler.sample_wallet_balance();
+        let bp_without_boost = math(calculate_bottom_bp_without_boost(wallet_balance))?;
+        let bp_with_boost = math(calculate_bottom_bp_with_boost(block_index, wallet_balance))?;
+        let (_, reward_dust) = math(calculate_user_reward_bottom_block(
+            request_without_boost,
+            request_with_boost,
+            bp_without_boost,
+            bp_with_boost,
+            math(calculate_dust_per_bp(block_index))?,
+            wallet_balance,
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+        ))?;
+        // the BP of the requests are reported even if the wallet balance is below the minimum required stake
+        let bp = bp_without_boost * request_without_boost as u64
+            + bp_with_boost * request_with_boost as u64;
+        writeln!(
+            writer,
+            "{block_index},{request_without_boost},{request_with_boost},{wallet_balance},{reward_dust},{bp}"
+        )?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+fn main() -> GeneratorResult<()> {
+    let output_dir = env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    let mut sampler = Sampler::new(SEED);
+
+    generate_top_block_report(&output_dir)?;
+    generate_top_block_transfers(&output_dir, &mut sampler)?;
+    generate_bottom_block_report(&output_dir, &mut sampler)?;
+    generate_bottom_block_transfers(&output_dir, &mut sampler)?;
+
+    println!("Test vectors written to {}", output_dir.display());
+
+    Ok(())
+}
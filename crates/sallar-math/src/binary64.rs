@@ -0,0 +1,186 @@
+use crate::MathError;
+
+/// The number of bits of the significand of an IEEE 754 binary64 (`f64`) number, including the implicit leading bit.
+const SIGNIFICAND_BITS: u32 = 53;
+
+/// A positive IEEE 754 binary64 (`f64`) number represented with integers, i.e. `significand * 2^exponent`.
+/// It allows to reproduce bit-exactly the rounding of the f64 operations the published reward formulas were defined with,
+/// without using floating point on-chain. Only the normal numbers needed by the reward formulas are supported
+/// (no subnormal numbers, infinities nor NaN).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Binary64 {
+    significand: u64,
+    exponent: i32,
+}
+
+impl Binary64 {
+    /// Rounds the exact quotient `numerator / denominator` to the nearest binary64 number (ties to even),
+    /// i.e. the result of the f64 division of the numbers if both of them are exactly representable in f64.
+    ///
+    /// ### Arguments
+    ///
+    /// * `numerator` - the numerator, lower than 2^118,
+    /// * `denominator` - the denominator, lower than 2^65.
+    ///
+    /// ### Returns
+    /// The rounded quotient, or an error if the denominator is 0 or the arguments are out of the supported range.
+    pub(crate) fn from_quotient(numerator: u128, denominator: u128) -> Result<Self, MathError> {
+        ensure!(denominator > 0, MathError::ArithmeticOverflow);
+        ensure!(
+            numerator < 1 << 118 && denominator < 1 << 65,
+            MathError::ArithmeticOverflow
+        );
+        if numerator == 0 {
+            return Ok(Self {
+                significand: 0,
+                exponent: 0,
+            });
+        }
+
+        // the quotient scaled by 2^shift is within [2^52, 2^54), it is scaled down once more if it is not lower than 2^53
+        let mut shift = SIGNIFICAND_BITS as i32 + bit_length(denominator) - bit_length(numerator);
+        let (mut quotient, mut remainder, mut divisor) =
+            scaled_division(numerator, denominator, shift);
+        if quotient >= 1 << SIGNIFICAND_BITS {
+            shift -= 1;
+            (quotient, remainder, divisor) = scaled_division(numerator, denominator, shift);
+        }
+
+        // round to nearest, ties to even
+        if remainder * 2 > divisor || (remainder * 2 == divisor && quotient % 2 == 1) {
+            quotient += 1;
+            if quotient == 1 << SIGNIFICAND_BITS {
+                quotient /= 2;
+                shift -= 1;
+            }
+        }
+
+        Ok(Self {
+            significand: quotient as u64,
+            exponent: -shift,
+        })
+    }
+
+    /// Multiplies the number by an integer with the rounding of the f64 multiplication (to nearest, ties to even),
+    /// i.e. the result of the f64 product of the numbers if the integer is exactly representable in f64.
+    ///
+    /// ### Arguments
+    ///
+    /// * `multiplier` - the integer multiplier.
+    ///
+    /// ### Returns
+    /// The rounded product.
+    pub(crate) fn mul_integer(self, multiplier: u64) -> Result<Self, MathError> {
+        let product = Self::from_quotient(self.significand as u128 * multiplier as u128, 1)?;
+
+        Ok(Self {
+            significand: product.significand,
+            exponent: product.exponent + self.exponent,
+        })
+    }
+
+    /// Rounds the number to the nearest integer, with the halfway cases rounded away from zero (as `f64::round`).
+    ///
+    /// ### Returns
+    /// The rounded number, or an error if it does not fit in u64.
+    pub(crate) fn round_to_u64(self) -> Result<u64, MathError> {
+        let significand = self.significand as u128;
+        let rounded = if significand == 0 {
+            0
+        } else if self.exponent >= 0 {
+            ensure!(self.exponent < 64, MathError::U64ConversionError);
+            significand << self.exponent
+        } else if self.exponent > -(SIGNIFICAND_BITS as i32 + 2) {
+            let shift = -self.exponent;
+            (significand + (1 << (shift - 1))) >> shift
+        } else {
+            // lower than a half
+            0
+        };
+
+        u64::try_from(rounded).map_err(|_| MathError::U64ConversionError)
+    }
+}
+
+/// The number of significant bits of the value.
+fn bit_length(value: u128) -> i32 {
+    (u128::BITS - value.leading_zeros()) as i32
+}
+
+/// Divides `numerator * 2^shift` by the denominator.
+///
+/// ### Returns
+/// The quotient, the remainder and the divisor the remainder is relative to.
+fn scaled_division(numerator: u128, denominator: u128, shift: i32) -> (u128, u128, u128) {
+    let (dividend, divisor) = if shift >= 0 {
+        (numerator << shift, denominator)
+    } else {
+        (numerator, denominator << -shift)
+    };
+
+    (dividend / divisor, dividend % divisor, divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(value: Binary64) -> f64 {
+        value.significand as f64 * 2f64.powi(value.exponent)
+    }
+
+    #[test]
+    fn test_binary64_matches_f64() {
+        for (numerator, denominator) in [
+            (2_000_000_000_000u128, 25_767u128),
+            (2_000_000_000_000, 3),
+            (2_000_000_000_000, 290_698),
+            (1, 3),
+            (7, 1),
+            (1 << 60, 1),
+            ((1 << 53) + 1, 1),
+            ((1 << 53) + 3, 1),
+            (2_000_000_000_000, u64::MAX as u128),
+        ] {
+            let quotient = Binary64::from_quotient(numerator, denominator).unwrap();
+            assert_eq!(
+                to_f64(quotient),
+                numerator as f64 / denominator as f64,
+                "{} / {}",
+                numerator,
+                denominator
+            );
+            for multiplier in [0, 1, 2, 1_000, 2_000, 4_000, 25_767, 1 << 40] {
+                let product = quotient.mul_integer(multiplier).unwrap();
+                let expected = numerator as f64 / denominator as f64 * multiplier as f64;
+                assert_eq!(to_f64(product), expected);
+                if expected < u64::MAX as f64 {
+                    assert_eq!(product.round_to_u64().unwrap(), expected.round() as u64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary64_rounding() {
+        // exactly a half is rounded away from zero
+        let half = Binary64::from_quotient(1, 2).unwrap();
+        assert_eq!(half.round_to_u64().unwrap(), 1);
+        assert_eq!(half.mul_integer(5).unwrap().round_to_u64().unwrap(), 3);
+        assert_eq!(
+            Binary64::from_quotient(1, 3)
+                .unwrap()
+                .round_to_u64()
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            Binary64::from_quotient(1, 0),
+            Err(MathError::ArithmeticOverflow)
+        );
+        assert_eq!(
+            Binary64::from_quotient(1 << 64, 1).unwrap().round_to_u64(),
+            Err(MathError::U64ConversionError)
+        );
+    }
+}
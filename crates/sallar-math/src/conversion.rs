@@ -0,0 +1,120 @@
+use crate::MathError;
+
+/// Converts a given `f64` value to an `u64` value and returns it as a result.
+/// Performs various checks to ensure that the conversion can be performed,
+/// i.e. provided `f64` number is a finite number in the range of `u64`.
+///
+/// The fractional part of the value is truncated and `2^64` (`u64::MAX as f64`) saturates to `u64::MAX`,
+/// use [`convert_integral_f64_to_u64`] if the value must not lose its fractional part.
+///
+/// ### Arguments
+///
+/// * value - the f64 value to be converted to u64
+///
+/// ### Returns
+///
+/// The result of the conversion if the input value is a finite number in the scope of `u64`, or an error distinguishing
+/// NaN (`F64IsNaN`), infinite values (`F64IsInfinite`) and values out of the scope of `u64` (`U64ConversionError`).
+pub fn convert_f64_to_u64(value: f64) -> Result<u64, MathError> {
+    ensure!(!value.is_nan(), MathError::F64IsNaN);
+    ensure!(value.is_finite(), MathError::F64IsInfinite);
+    ensure!(
+        (u64::MIN as f64..=u64::MAX as f64).contains(&value),
+        MathError::U64ConversionError
+    );
+
+    Ok(value as u64)
+}
+
+/// Converts a given integral `f64` value to an `u64` value and returns it as a result.
+/// Performs the same checks as [`convert_f64_to_u64`] and rejects the values with a fractional part,
+/// so the conversion never loses the sub-unit precision silently.
+///
+/// ### Arguments
+///
+/// * value - the integral f64 value to be converted to u64
+///
+/// ### Returns
+///
+/// The result of the conversion, or an error of [`convert_f64_to_u64`] or `F64NotIntegral` if the value has a fractional part.
+pub fn convert_integral_f64_to_u64(value: f64) -> Result<u64, MathError> {
+    let converted = convert_f64_to_u64(value)?;
+    // a saturated u64::MAX converts back to 2^64, which is integral
+    ensure!(converted as f64 == value, MathError::F64NotIntegral);
+
+    Ok(converted)
+}
+
+/// Converts a given `u64` value to an `f64` value.
+///
+/// ### Arguments
+///
+/// * value - the u64 value to be converted to f64
+///
+/// ### Returns
+///
+/// The result of the conversion, the nearest `f64` number for the values above 2^53.
+pub fn convert_u64_to_f64(value: u64) -> f64 {
+    value as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_f64_to_u64_test() {
+        assert_eq!(convert_f64_to_u64(0.0), Ok(0));
+        assert_eq!(convert_f64_to_u64(-0.0), Ok(0));
+        assert_eq!(convert_f64_to_u64(123.75), Ok(123));
+        assert_eq!(convert_f64_to_u64(u64::MAX as f64), Ok(u64::MAX));
+
+        assert_eq!(convert_f64_to_u64(f64::NAN), Err(MathError::F64IsNaN));
+        assert_eq!(convert_f64_to_u64(-f64::NAN), Err(MathError::F64IsNaN));
+        assert_eq!(
+            convert_f64_to_u64(f64::INFINITY),
+            Err(MathError::F64IsInfinite)
+        );
+        assert_eq!(
+            convert_f64_to_u64(f64::NEG_INFINITY),
+            Err(MathError::F64IsInfinite)
+        );
+        assert_eq!(convert_f64_to_u64(-0.1), Err(MathError::U64ConversionError));
+        assert_eq!(
+            convert_f64_to_u64(f64::MAX),
+            Err(MathError::U64ConversionError)
+        );
+    }
+
+    #[test]
+    fn convert_integral_f64_to_u64_test() {
+        assert_eq!(convert_integral_f64_to_u64(0.0), Ok(0));
+        assert_eq!(convert_integral_f64_to_u64(123.0), Ok(123));
+        assert_eq!(
+            convert_integral_f64_to_u64((1u64 << 53) as f64),
+            Ok(1u64 << 53)
+        );
+        assert_eq!(convert_integral_f64_to_u64(u64::MAX as f64), Ok(u64::MAX));
+
+        assert_eq!(
+            convert_integral_f64_to_u64(123.75),
+            Err(MathError::F64NotIntegral)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(0.5),
+            Err(MathError::F64NotIntegral)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(f64::NAN),
+            Err(MathError::F64IsNaN)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(f64::INFINITY),
+            Err(MathError::F64IsInfinite)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(-1.0),
+            Err(MathError::U64ConversionError)
+        );
+    }
+}
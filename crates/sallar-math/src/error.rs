@@ -0,0 +1,42 @@
+use core::fmt;
+
+/// The enum defining all errors of the token math.
+/// The Sallar program maps them to the corresponding program errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// Block number is out of the range of blocks.
+    BlockIndexOutOfBounds,
+    /// The value does not fit in u64.
+    U64ConversionError,
+    /// The f64 value to be converted is NaN.
+    F64IsNaN,
+    /// The f64 value to be converted is infinite.
+    F64IsInfinite,
+    /// The f64 value to be converted has a fractional part that would be lost.
+    F64NotIntegral,
+    /// Arithmetic overflow in the token amount accounting.
+    ArithmeticOverflow,
+    /// The balance snapshot exceeds the registered balance or no balance is registered.
+    InvalidRegisteredBalance,
+    /// No balance is registered, so no share of the prize pool can be calculated.
+    ZeroRegisteredBalance,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MathError::BlockIndexOutOfBounds => "Block number is out of the range of blocks",
+            MathError::U64ConversionError => "U64 conversion error occurred",
+            MathError::F64IsNaN => "F64 value to be converted is NaN",
+            MathError::F64IsInfinite => "F64 value to be converted is infinite",
+            MathError::F64NotIntegral => "F64 value to be converted is not integral",
+            MathError::ArithmeticOverflow => "Arithmetic overflow in the token amount accounting",
+            MathError::InvalidRegisteredBalance => {
+                "The balance snapshot exceeds the registered balance"
+            }
+            MathError::ZeroRegisteredBalance => "No balance is registered",
+        };
+
+        f.write_str(message)
+    }
+}
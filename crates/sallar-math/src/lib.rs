@@ -0,0 +1,1404 @@
+//! Set of functions and constants defining the most important math functions of Sallar token, used by the Sallar program.
+//!
+//! The crate does not depend on Anchor nor Solana and it does not use the standard library, so pools, wallets and explorers
+//! can compute the expected rewards off-chain with exactly the same code the program uses.
+//!
+//! All the calculations determining token amounts are done on integers, so they are deterministic and can be reproduced bit-exactly off-chain.
+//! The reductions of BP and boosts from block to block are exponential, they are not calculated on-chain from the formulas but looked up
+//! in the precomputed tables of the `tables` module. The boosts change only every few thousand blocks, so they are stored as steps.
+//! The BP reduction is stored as checkpoints every `MAX_BP_CHECKPOINT_INTERVAL` blocks, the reduction between a checkpoint and the block
+//! is a product of a few entries of the table of the reduction factor raised to the powers of two, in u128 fixed-point arithmetic
+//! (values scaled by `FIXED_POINT_SCALING_FACTOR`). Every fixed-point multiplication and division is rounded to the nearest scaled integer,
+//! so the accumulated error is far below the precision of the rounded results.
+//!
+//! The amount of dust per BP is kept as the fraction `DUSTS_PER_BLOCK / block_bp`. A reward for the given BP follows the published formula
+//! `round(bp * (DUSTS_PER_BLOCK / block_bp))` evaluated in f64, whose intermediate roundings are reproduced bit-exactly on integers
+//! by the `binary64` module, so the rewards match the published test vectors and the deployed program.
+//! f64 is used only for estimates and values reported to the clients (e.g. the staking APR), they never determine token amounts.
+//!
+//! The accuracy and compliance of the calculations were thoroughly verified using Python scripts. The test vectors are generated
+//! by the `generate_test_vectors` binary of the crate and the results are compared with the f64 formulas by the property-based tests
+//! (the `proptest` feature), so the vectors cannot silently diverge from the implementation. The originally published vectors
+//! (the `published` subdirectories of the reports) are kept as a fixed oracle, independent of the crate, and they are never regenerated.
+//!
+//! Total supply for SPL Token cannot exceed u64 range in Solana.
+//! There are no operations in Sallar that would exceed total supply so this is why usage of u64 is safe here.
+
+#![no_std]
+
+#[cfg(test)]
+extern crate std;
+
+/// Returns the error if the condition is not met.
+macro_rules! ensure {
+    ($condition:expr, $error:expr) => {
+        if !$condition {
+            return Err($error);
+        }
+    };
+}
+
+mod binary64;
+mod conversion;
+mod error;
+#[cfg(all(test, feature = "proptest"))]
+mod proptests;
+mod tables;
+
+use binary64::Binary64;
+pub use conversion::{convert_f64_to_u64, convert_integral_f64_to_u64, convert_u64_to_f64};
+pub use error::MathError;
+
+use tables::{
+    BOTTOM_BLOCK_BOOST_STEPS, MAX_BP_CHECKPOINT_INTERVAL, REDUCTION_INVERSE_CHECKPOINTS,
+    REDUCTION_INVERSE_POWERS, REDUCTION_TABLE_SIZE, TOP_BLOCK_BOOST_STEPS,
+};
+
+pub const TOKEN_AMOUNT_SCALING_FACTOR: u64 = 1_000;
+pub const DUSTS_PER_BLOCK: u64 = 2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+pub const MAX_BLOCK_INDEX: u64 = 470_000;
+
+const FIRST_BP: u64 = 20 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+const TOP_BP_WITHOUT_BOOST: u64 = TOKEN_AMOUNT_SCALING_FACTOR;
+
+/// 1.0 in the fixed-point arithmetic of the reduction factors.
+pub const FIXED_POINT_SCALING_FACTOR: u128 = 1_000_000_000_000_000_000;
+
+/// By default final staking pool in a round is 0.1% of the final staking account balance at the beginning of the round.
+pub const FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR: u64 = 1000;
+
+/// Reward parts are migrated from f64 to integers, 1.0 reward part is represented as 1_000_000_000 scaled reward parts.
+pub const REWARD_PARTS_SCALING_FACTOR: u64 = 1_000_000_000;
+
+/// The number of basis points in 100%.
+pub const BASIS_POINTS_DIVISOR: u64 = 10_000;
+
+/// The amount of tokens minted to the organization account by the initial token distribution.
+pub const INITIAL_TOKEN_DISTRIBUTION_AMOUNT: u64 =
+    260_000_000_000_000_u64 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+fn dust_to_staking_sallar(dusts: u64) -> u64 {
+    // 1 dust = 1e-8 sallar, only the whole sallar will be staked
+    // truncation of the decimal part is intentional
+    dusts / (100_000_000)
+}
+
+/// Asserts that the block index is within the range of blocks, i.e. from 1 to `MAX_BLOCK_INDEX`.
+/// The token math is not defined outside of the range, such a block index can result only from corrupted state.
+pub fn valid_block_index(block_index: u64) -> Result<(), MathError> {
+    ensure!(
+        (1..=MAX_BLOCK_INDEX).contains(&block_index),
+        MathError::BlockIndexOutOfBounds
+    );
+
+    Ok(())
+}
+
+/// Multiplies two fixed-point values, the result is rounded to the nearest scaled integer.
+fn fixed_point_mul(a: u128, b: u128) -> Result<u128, MathError> {
+    let product = a.checked_mul(b).ok_or(MathError::ArithmeticOverflow)?;
+
+    Ok((product + FIXED_POINT_SCALING_FACTOR / 2) / FIXED_POINT_SCALING_FACTOR)
+}
+
+/// Divides an integer by a fixed-point value, the result is an integer rounded to the nearest one (half up, as `f64::round`).
+fn fixed_point_div_round(numerator: u128, divisor: u128) -> Result<u128, MathError> {
+    ensure!(divisor > 0, MathError::ArithmeticOverflow);
+    let scaled_numerator = numerator
+        .checked_mul(FIXED_POINT_SCALING_FACTOR)
+        .ok_or(MathError::ArithmeticOverflow)?;
+
+    Ok((scaled_numerator + divisor / 2) / divisor)
+}
+
+/// Raises a reduction factor to the given power using its table of powers of two.
+///
+/// ### Arguments
+///
+/// * `powers` - the reduction table of the factor,
+/// * `exponent` - the number of blocks the factor is applied for.
+///
+/// ### Returns
+/// The factor raised to the power in fixed-point, or an error if the exponent is not covered by the table.
+fn calculate_reduction(
+    powers: &[u128; REDUCTION_TABLE_SIZE],
+    exponent: u64,
+) -> Result<u128, MathError> {
+    ensure!(
+        exponent < 1 << REDUCTION_TABLE_SIZE,
+        MathError::BlockIndexOutOfBounds
+    );
+
+    let mut reduction = FIXED_POINT_SCALING_FACTOR;
+    for (bit, power) in powers.iter().enumerate() {
+        if exponent & (1 << bit) != 0 {
+            reduction = fixed_point_mul(reduction, *power)?;
+        }
+    }
+
+    Ok(reduction)
+}
+
+fn calculate_bp_reduction_factor(block_index: u64) -> Result<u128, MathError> {
+    valid_block_index(block_index)?;
+    let exponent = block_index - 1;
+    let checkpoint = REDUCTION_INVERSE_CHECKPOINTS
+        .get((exponent / MAX_BP_CHECKPOINT_INTERVAL) as usize)
+        .ok_or(MathError::BlockIndexOutOfBounds)?;
+
+    fixed_point_mul(
+        *checkpoint,
+        calculate_reduction(
+            &REDUCTION_INVERSE_POWERS,
+            exponent % MAX_BP_CHECKPOINT_INTERVAL,
+        )?,
+    )
+}
+
+pub fn calculate_max_bp(block_index: u64) -> Result<u64, MathError> {
+    let bp_reduction_factor = calculate_bp_reduction_factor(block_index)?;
+    let max_bp = fixed_point_div_round(FIRST_BP as u128, bp_reduction_factor)?;
+
+    u64::try_from(max_bp).map_err(|_| MathError::U64ConversionError)
+}
+
+/// The amount of dust per BP of a block, i.e. `DUSTS_PER_BLOCK / block_bp`.
+/// It is kept as the fraction, the rewards calculated from it reproduce the roundings of the f64 formula (see `calculate_single_reward`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DustPerBp {
+    block_bp: u64,
+}
+
+impl DustPerBp {
+    /// Creates the fraction from the number of BP of the whole block balance, e.g. the max BP of the block cached by the caller.
+    ///
+    /// ### Returns
+    /// The dust per BP, or an error if the number of BP is 0.
+    pub fn from_block_bp(block_bp: u64) -> Result<Self, MathError> {
+        ensure!(block_bp > 0, MathError::BlockIndexOutOfBounds);
+
+        Ok(Self { block_bp })
+    }
+
+    /// The number of BP of the whole block balance (the denominator of the fraction).
+    pub fn block_bp(&self) -> u64 {
+        self.block_bp
+    }
+
+    /// Converts the fraction to f64, the value is meant only for reporting, it is never used to calculate token amounts.
+    pub fn to_f64(&self) -> f64 {
+        convert_u64_to_f64(DUSTS_PER_BLOCK) / convert_u64_to_f64(self.block_bp)
+    }
+}
+
+pub fn calculate_dust_per_bp(block_index: u64) -> Result<DustPerBp, MathError> {
+    DustPerBp::from_block_bp(calculate_max_bp(block_index)?)
+}
+
+/// Looks up the boost of the block in the boost steps of `token_math_tables`.
+fn lookup_block_boost(steps: &[(u64, u64)], block_index: u64) -> Result<u64, MathError> {
+    valid_block_index(block_index)?;
+    let step = steps.partition_point(|(first_block_index, _)| *first_block_index <= block_index);
+
+    step.checked_sub(1)
+        .and_then(|step| steps.get(step))
+        .map(|(_, boost)| *boost)
+        .ok_or(MathError::BlockIndexOutOfBounds)
+}
+
+fn calculate_top_block_max_boost(block_index: u64) -> Result<u64, MathError> {
+    lookup_block_boost(&TOP_BLOCK_BOOST_STEPS, block_index)
+}
+
+fn calculate_base_bp_for_given_boost(boost: u64) -> Result<u64, MathError> {
+    boost.checked_add(1).ok_or(MathError::ArithmeticOverflow)
+}
+
+/// Multiplies two u64 values with the u128 intermediate product, the product is downcast to u64 with a check.
+fn checked_u64_product(a: u64, b: u64) -> Result<u64, MathError> {
+    u64::try_from(a as u128 * b as u128).map_err(|_| MathError::U64ConversionError)
+}
+
+fn calculate_top_bp(boost: u64) -> Result<u64, MathError> {
+    checked_u64_product(
+        TOKEN_AMOUNT_SCALING_FACTOR,
+        calculate_base_bp_for_given_boost(boost)?,
+    )
+}
+
+pub fn calculate_top_bp_with_boost(block_index: u64) -> Result<u64, MathError> {
+    let boost = calculate_top_block_max_boost(block_index)?;
+
+    calculate_top_bp(boost)
+}
+
+/// The boost depends only on the block index, so it can be calculated once for all users solving the block.
+pub fn calculate_bottom_block_max_boost(block_index: u64) -> Result<u64, MathError> {
+    lookup_block_boost(&BOTTOM_BLOCK_BOOST_STEPS, block_index)
+}
+
+fn calculate_bottom_bp(user_wallet_balance: u64, boost: u64) -> Result<u64, MathError> {
+    checked_u64_product(
+        calculate_base_bp_for_given_boost(boost)?,
+        dust_to_staking_sallar(user_wallet_balance),
+    )
+}
+
+pub fn calculate_bottom_bp_without_boost(user_wallet_balance: u64) -> Result<u64, MathError> {
+    calculate_bottom_bp(user_wallet_balance, 0)
+}
+
+/// Calculates the boosted BP of the user for the boost calculated upfront with `calculate_bottom_block_max_boost`.
+pub fn calculate_bottom_bp_with_given_boost(
+    user_wallet_balance: u64,
+    boost: u64,
+) -> Result<u64, MathError> {
+    calculate_bottom_bp(user_wallet_balance, boost)
+}
+
+pub fn calculate_bottom_bp_with_boost(
+    block_index: u64,
+    user_wallet_balance: u64,
+) -> Result<u64, MathError> {
+    let boost = calculate_bottom_block_max_boost(block_index)?;
+
+    calculate_bottom_bp(user_wallet_balance, boost)
+}
+
+/// Calculates the reward for the given number of BP with the published f64 formula `round(bp * (DUSTS_PER_BLOCK / block_bp))`:
+/// the dust per BP and its product with BP are rounded to the nearest f64 value and the product is rounded to the nearest dust
+/// (half away from zero). The f64 roundings are reproduced on integers, so the reward is deterministic and matches the published test vectors.
+pub fn calculate_single_reward(bp: u64, dust_per_bp: DustPerBp) -> Result<u64, MathError> {
+    Binary64::from_quotient(DUSTS_PER_BLOCK as u128, dust_per_bp.block_bp as u128)?
+        .mul_integer(bp)?
+        .round_to_u64()
+}
+
+/// Allocates the reward for the given number of BP paid from a block, following the rounding policy of the blocks:
+/// the reward is rounded down to the whole dust and the fraction of a dust not paid is carried as the rounding remainder
+/// (in units of `1 / block_bp` dust) to the following reward of the block, i.e. the reward is `(bp * DUSTS_PER_BLOCK + rounding_remainder) / block_bp`.
+/// As the remainders add up, the rewards for all BP of a block sum up exactly to `DUSTS_PER_BLOCK` and the remainder after the last BP is 0,
+/// regardless of the order and the sizes of the rewards.
+///
+/// ### Arguments
+///
+/// * `bp` - the number of BP the reward is allocated for,
+/// * `dust_per_bp` - the dust per BP of the block,
+/// * `rounding_remainder` - the rounding remainder carried from the previous rewards of the block (0 for the first reward), lower than the block's BP.
+///
+/// ### Returns
+/// The reward and the rounding remainder to carry to the following reward of the block.
+pub fn allocate_block_reward(
+    bp: u64,
+    dust_per_bp: DustPerBp,
+    rounding_remainder: u64,
+) -> Result<(u64, u64), MathError> {
+    let block_bp = dust_per_bp.block_bp as u128;
+    let scaled_reward = (bp as u128 * DUSTS_PER_BLOCK as u128)
+        .checked_add(rounding_remainder as u128)
+        .ok_or(MathError::ArithmeticOverflow)?;
+    let reward =
+        u64::try_from(scaled_reward / block_bp).map_err(|_| MathError::U64ConversionError)?;
+
+    Ok((reward, (scaled_reward % block_bp) as u64))
+}
+
+/// The reward of a single user split into the part for the requests without boost and the part for the requests with boost.
+/// The parts are calculated separately, as they are semantically separated, and summed up only to consolidate them into a single transfer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewardBreakdown {
+    pub bp_no_boost: u64,
+    pub bp_boost: u64,
+    pub amount_no_boost: u64,
+    pub amount_boost: u64,
+}
+
+impl RewardBreakdown {
+    /// The total number of BP of the reward.
+    pub fn bp(&self) -> Result<u64, MathError> {
+        self.bp_no_boost
+            .checked_add(self.bp_boost)
+            .ok_or(MathError::ArithmeticOverflow)
+    }
+
+    /// The total amount of tokens of the reward.
+    pub fn amount(&self) -> Result<u64, MathError> {
+        self.amount_no_boost
+            .checked_add(self.amount_boost)
+            .ok_or(MathError::ArithmeticOverflow)
+    }
+}
+
+/// The function calculates parts of the reward separately for requests with boost and without boost.
+/// They are kept separate from each other, and the reason they are summed up in the end (see `RewardBreakdown`)
+/// is to consolidate them into a single transfer, instead of two separate transfers for each reward part.
+/// However, the calculation is intentionally done this way, as the parts are semantically separated.
+/// The products of the requests and the BP or rewards are calculated in u128 and downcast to u64 with a check.
+fn calculate_user_reward(
+    user_request_without_boost: u8,
+    user_request_with_boost: u8,
+    parts_without_boost: u64,
+    parts_with_boost: u64,
+    dust_per_bp: DustPerBp,
+) -> Result<RewardBreakdown, MathError> {
+    Ok(RewardBreakdown {
+        bp_no_boost: checked_u64_product(user_request_without_boost as u64, parts_without_boost)?,
+        bp_boost: checked_u64_product(user_request_with_boost as u64, parts_with_boost)?,
+        amount_no_boost: checked_u64_product(
+            user_request_without_boost as u64,
+            calculate_single_reward(parts_without_boost, dust_per_bp)?,
+        )?,
+        amount_boost: checked_u64_product(
+            user_request_with_boost as u64,
+            calculate_single_reward(parts_with_boost, dust_per_bp)?,
+        )?,
+    })
+}
+
+/// Calculates the breakdown of the bottom block reward of a user, the reward is empty if the user's wallet balance is lower than the minimum required stake.
+pub fn calculate_user_reward_breakdown_bottom_block(
+    user_request_without_boost: u8,
+    user_request_with_boost: u8,
+    parts_without_boost: u64,
+    parts_with_boost: u64,
+    dust_per_bp: DustPerBp,
+    user_wallet_balance: u64,
+    min_required_stake: u64,
+) -> Result<RewardBreakdown, MathError> {
+    if user_wallet_balance < min_required_stake {
+        return Ok(RewardBreakdown::default());
+    }
+
+    calculate_user_reward(
+        user_request_without_boost,
+        user_request_with_boost,
+        parts_without_boost,
+        parts_with_boost,
+        dust_per_bp,
+    )
+}
+
+/// Calculates the breakdown of the top block reward of a user.
+pub fn calculate_user_reward_breakdown_top_block(
+    user_request_without_boost: u8,
+    user_request_with_boost: u8,
+    parts_with_boost: u64,
+    dust_per_bp: DustPerBp,
+) -> Result<RewardBreakdown, MathError> {
+    calculate_user_reward(
+        user_request_without_boost,
+        user_request_with_boost,
+        TOP_BP_WITHOUT_BOOST,
+        parts_with_boost,
+        dust_per_bp,
+    )
+}
+
+pub fn calculate_user_reward_bottom_block(
+    user_request_without_boost: u8,
+    user_request_with_boost: u8,
+    parts_without_boost: u64,
+    parts_with_boost: u64,
+    dust_per_bp: DustPerBp,
+    user_wallet_balance: u64,
+    min_required_stake: u64,
+) -> Result<(u64, u64), MathError> {
+    let breakdown = calculate_user_reward_breakdown_bottom_block(
+        user_request_without_boost,
+        user_request_with_boost,
+        parts_without_boost,
+        parts_with_boost,
+        dust_per_bp,
+        user_wallet_balance,
+        min_required_stake,
+    )?;
+
+    Ok((breakdown.bp()?, breakdown.amount()?))
+}
+
+pub fn calculate_user_reward_top_block(
+    user_request_without_boost: u8,
+    user_request_with_boost: u8,
+    parts_with_boost: u64,
+    dust_per_bp: DustPerBp,
+) -> Result<(u64, u64), MathError> {
+    let breakdown = calculate_user_reward_breakdown_top_block(
+        user_request_without_boost,
+        user_request_with_boost,
+        parts_with_boost,
+        dust_per_bp,
+    )?;
+
+    Ok((breakdown.bp()?, breakdown.amount()?))
+}
+
+/// Calculates the reward the carried-over user receives at the start of the given block for the BP not received in the previous block
+/// (due to too low amount of remaining BP on the previous block). It follows the same rules as the block solution process:
+/// if the rest BP is lower than the block's BP, the user receives the reward for the rest BP, otherwise the user receives the whole block balance
+/// and the remaining rest BP is carried over again.
+///
+/// ### Arguments
+///
+/// * `user_rest_bp` - the number of BP not received by the user in the previous block,
+/// * `block_index` - the number of the block the rest BP is paid from.
+///
+/// ### Returns
+/// The number of rest BP paid in the given block and the amount of tokens transferred to the user.
+pub fn calculate_user_rest_reward(
+    user_rest_bp: u64,
+    block_index: u64,
+) -> Result<(u64, u64), MathError> {
+    let block_bp = calculate_max_bp(block_index)?;
+    let paid_rest_bp = user_rest_bp.min(block_bp);
+
+    if paid_rest_bp < block_bp {
+        let dust_per_bp = calculate_dust_per_bp(block_index)?;
+        Ok((
+            paid_rest_bp,
+            calculate_single_reward(paid_rest_bp, dust_per_bp)?,
+        ))
+    } else {
+        Ok((paid_rest_bp, DUSTS_PER_BLOCK))
+    }
+}
+
+/// Estimates the annual percentage rate of final staking, returned as a fraction (e.g. 0.05 for 5%).
+/// It follows the on-chain round mechanics: every round distributes 0.1% of the final staking account balance (truncated to whole dusts),
+/// so the pool shrinks from round to round. Reward parts are proportional to staked balances, so every staker gets the same rate.
+///
+/// ### Arguments
+///
+/// * `pool_balance` - the final staking account balance,
+/// * `user_balance` - the staked balance sharing the rewards (the total balance of the stakers participating in the rounds),
+/// * `rounds_per_year` - the number of final staking rounds in a year.
+///
+/// ### Returns
+/// The estimated APR, 0 if there is no staked balance.
+pub fn estimate_staking_apr(pool_balance: u64, user_balance: u64, rounds_per_year: u64) -> f64 {
+    if user_balance == 0 {
+        return 0.0;
+    }
+
+    let mut left_pool_balance = pool_balance;
+    let mut yearly_reward: u64 = 0;
+    for _ in 0..rounds_per_year {
+        let pool_in_round =
+            left_pool_balance / FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR;
+        if pool_in_round == 0 {
+            break;
+        }
+
+        yearly_reward += pool_in_round;
+        left_pool_balance -= pool_in_round;
+    }
+
+    convert_u64_to_f64(yearly_reward) / convert_u64_to_f64(user_balance)
+}
+
+/// Converts scaled (integer) reward parts to the f64 reward parts of the deprecated state field.
+///
+/// ### Arguments
+///
+/// * `scaled_reward_parts` - the number of scaled reward parts.
+///
+/// ### Returns
+/// The reward parts (1.0 is the whole prize pool of the round).
+pub fn convert_scaled_reward_parts_to_f64(scaled_reward_parts: u64) -> f64 {
+    convert_u64_to_f64(scaled_reward_parts) / convert_u64_to_f64(REWARD_PARTS_SCALING_FACTOR)
+}
+
+/// Calculates the final staking reward of a user requesting the given part of the round's prize pool, rounded down.
+///
+/// ### Arguments
+///
+/// * `reward_part` - the part of the prize pool requested by the user in scaled reward parts,
+/// * `pool_in_round` - the prize pool of the current final staking round.
+///
+/// ### Returns
+/// The reward of the user.
+pub fn calculate_final_staking_reward(
+    reward_part: u64,
+    pool_in_round: u64,
+) -> Result<u64, MathError> {
+    u64::try_from(pool_in_round as u128 * reward_part as u128 / REWARD_PARTS_SCALING_FACTOR as u128)
+        .map_err(|_| MathError::U64ConversionError)
+}
+
+/// Calculates the part of the round's prize pool of a user from the user's balance snapshot registered for the round, rounded down.
+///
+/// ### Arguments
+///
+/// * `balance` - the balance snapshot of the user,
+/// * `registered_balance` - the total balance of the snapshots registered for the round.
+///
+/// ### Returns
+/// The part of the prize pool of the user in scaled reward parts, or an error if the balance exceeds the registered balance.
+pub fn calculate_snapshot_reward_part(
+    balance: u64,
+    registered_balance: u64,
+) -> Result<u64, MathError> {
+    ensure!(
+        balance <= registered_balance && registered_balance > 0,
+        MathError::InvalidRegisteredBalance
+    );
+
+    Ok((balance as u128 * REWARD_PARTS_SCALING_FACTOR as u128 / registered_balance as u128) as u64)
+}
+
+/// Calculates the maximum part of the round's prize pool a user can be given, i.e. the user's share of the registered balance of the round increased by the tolerance, rounded down.
+///
+/// ### Arguments
+///
+/// * `balance` - the balance of the user's token account,
+/// * `registered_balance` - the total balance of the snapshots registered for the round,
+/// * `tolerance_bps` - the tolerance (in basis points) by which the reward part may exceed the user's share.
+///
+/// ### Returns
+/// The maximum part of the prize pool of the user in scaled reward parts, or an error if no balance is registered for the round.
+pub fn calculate_max_reward_part(
+    balance: u64,
+    registered_balance: u64,
+    tolerance_bps: u32,
+) -> Result<u64, MathError> {
+    ensure!(registered_balance > 0, MathError::ZeroRegisteredBalance);
+
+    let max_reward_part = balance as u128
+        * REWARD_PARTS_SCALING_FACTOR as u128
+        * (BASIS_POINTS_DIVISOR as u128 + tolerance_bps as u128)
+        / (registered_balance as u128 * BASIS_POINTS_DIVISOR as u128);
+
+    Ok(u64::try_from(max_reward_part).unwrap_or(u64::MAX))
+}
+
+/// Calculates the balance of a user weighted by the multiplier of the user's final staking tier, rounded down.
+///
+/// ### Arguments
+///
+/// * `balance` - the balance of the user,
+/// * `multiplier_bps` - the multiplier of the user's tier in basis points (10_000 is 1x).
+///
+/// ### Returns
+/// The weighted balance of the user.
+pub fn calculate_weighted_balance(balance: u64, multiplier_bps: u32) -> Result<u64, MathError> {
+    u64::try_from(balance as u128 * multiplier_bps as u128 / 10_000)
+        .map_err(|_| MathError::U64ConversionError)
+}
+
+/// Calculates the fee taken from the reward, rounded down so the fee never exceeds the configured rate.
+///
+/// ### Arguments
+///
+/// * `reward_amount` - the reward calculated for the user,
+/// * `reward_fee_bps` - the fee rate in basis points.
+///
+/// ### Returns
+/// The fee amount.
+pub fn calculate_reward_fee(reward_amount: u64, reward_fee_bps: u16) -> Result<u64, MathError> {
+    Ok((reward_amount as u128 * reward_fee_bps as u128 / BASIS_POINTS_DIVISOR as u128) as u64)
+}
+
+/// Calculates the amount of tokens minted to the top block distribution account so far.
+/// Every top block, from the first one up to the current one, is funded with `DUSTS_PER_BLOCK` tokens.
+///
+/// ### Arguments
+///
+/// * `first_top_block_number` - the top block number the contract was initialized with (1 on mainnet),
+/// * `top_block_number` - current top block number.
+///
+/// ### Returns
+/// The amount of tokens minted for top blocks.
+pub fn calculate_top_blocks_minted_amount(
+    first_top_block_number: u64,
+    top_block_number: u64,
+) -> u64 {
+    (top_block_number - first_top_block_number + 1) * DUSTS_PER_BLOCK
+}
+
+/// Calculates the amount of tokens minted to the bottom block distribution account so far.
+/// Every bottom block, from the first one down to the current one, is funded with `DUSTS_PER_BLOCK` tokens.
+///
+/// ### Arguments
+///
+/// * `first_bottom_block_number` - the bottom block number the contract was initialized with (`MAX_BLOCK_INDEX` on mainnet),
+/// * `bottom_block_number` - current bottom block number.
+///
+/// ### Returns
+/// The amount of tokens minted for bottom blocks.
+pub fn calculate_bottom_blocks_minted_amount(
+    first_bottom_block_number: u64,
+    bottom_block_number: u64,
+) -> u64 {
+    (first_bottom_block_number - bottom_block_number + 1) * DUSTS_PER_BLOCK
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{boxed::Box, error::Error as standardError, fs::File, string::ToString, vec};
+
+    use super::*;
+    use crate::tables::{BOTTOM_BOOST_REDUCTION_POWERS, TOP_BOOST_REDUCTION_POWERS};
+
+    /// By default the program rewards bottom block solutions of users with the balance of at least 20_000 Sallar.
+    const DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 =
+        2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+    const MAX_BOTTOM_BOOST: u128 = 60;
+
+    /// 0.5 in fixed-point.
+    const MIN_TOP_BOOST: u128 = FIXED_POINT_SCALING_FACTOR / 2;
+
+    const TOP_FIRST_BOOSTED_BLOCK: u64 = 250;
+
+    /// The formula of the top block boost the boost steps are derived from.
+    fn calculate_top_block_max_boost_from_formula(block_index: u64) -> u64 {
+        // the boost grows from block to block, the blocks before the first boosted block have the boost reduced instead
+        let pow = if block_index >= TOP_FIRST_BOOSTED_BLOCK {
+            calculate_reduction(
+                &TOP_BOOST_REDUCTION_POWERS,
+                block_index - TOP_FIRST_BOOSTED_BLOCK,
+            )
+            .unwrap()
+        } else {
+            let inverse_pow = calculate_reduction(
+                &TOP_BOOST_REDUCTION_POWERS,
+                TOP_FIRST_BOOSTED_BLOCK - block_index,
+            )
+            .unwrap();
+            fixed_point_div_round(FIXED_POINT_SCALING_FACTOR, inverse_pow).unwrap()
+        };
+        let base_boost = fixed_point_mul(MIN_TOP_BOOST, pow).unwrap();
+
+        // rounded to units below 100, down to tens below 1000 and down to hundreds above
+        let rounded_boost = if base_boost < 100 * FIXED_POINT_SCALING_FACTOR {
+            (base_boost + FIXED_POINT_SCALING_FACTOR / 2) / FIXED_POINT_SCALING_FACTOR
+        } else if base_boost < 1_000 * FIXED_POINT_SCALING_FACTOR {
+            base_boost / (10 * FIXED_POINT_SCALING_FACTOR) * 10
+        } else {
+            base_boost / (100 * FIXED_POINT_SCALING_FACTOR) * 100
+        };
+
+        rounded_boost as u64
+    }
+
+    /// The formula of the bottom block boost the boost steps are derived from.
+    fn calculate_bottom_block_max_boost_from_formula(block_index: u64) -> u64 {
+        let reduction = calculate_reduction(
+            &BOTTOM_BOOST_REDUCTION_POWERS,
+            MAX_BLOCK_INDEX - block_index,
+        )
+        .unwrap();
+
+        ((MAX_BOTTOM_BOOST * reduction + FIXED_POINT_SCALING_FACTOR / 2)
+            / FIXED_POINT_SCALING_FACTOR) as u64
+    }
+
+    /// The published vectors cover blocks beyond `MAX_BLOCK_INDEX`, the math is not defined for them.
+    fn assert_out_of_range(block_index: u64) {
+        assert_eq!(
+            calculate_dust_per_bp(block_index),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+        assert_eq!(
+            calculate_top_bp_with_boost(block_index),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+        assert_eq!(
+            calculate_bottom_bp_with_boost(block_index, 0),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+    }
+
+    fn check_top_block_report(path: &str) -> Result<(), Box<dyn standardError>> {
+        let file = File::open(path)?;
+        let mut rdr = csv::Reader::from_reader(file);
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
+            let dust_without_boost_expected = record.get(1).unwrap().parse::<u64>().unwrap();
+            let dust_with_boost_expected = record.get(2).unwrap().parse::<u64>().unwrap();
+            let bp_with_boost_expected = record.get(3).unwrap().parse::<u64>().unwrap();
+            let dust_per_bp_expected = record.get(4).unwrap().parse::<f64>().unwrap();
+            let until_block_index = record.get(5).unwrap().parse::<u64>().unwrap();
+
+            let indexes = vec![block_index, until_block_index];
+
+            for index in indexes {
+                if valid_block_index(index).is_err() {
+                    assert_out_of_range(index);
+                    continue;
+                }
+
+                let sallar_per_bp = calculate_dust_per_bp(index).unwrap();
+                let top_block_bp_with_boost = calculate_top_bp_with_boost(index).unwrap();
+
+                let (_, top_block_dust_without_boost) =
+                    calculate_user_reward_top_block(1, 0, top_block_bp_with_boost, sallar_per_bp)
+                        .unwrap();
+                let (_, top_block_dust_with_boost) =
+                    calculate_user_reward_top_block(0, 1, top_block_bp_with_boost, sallar_per_bp)
+                        .unwrap();
+
+                let dust_per_bp = calculate_dust_per_bp(index).unwrap();
+
+                assert_eq!(
+                    bp_with_boost_expected.to_string(),
+                    top_block_bp_with_boost.to_string()
+                );
+                assert_eq!(
+                    dust_with_boost_expected.to_string(),
+                    top_block_dust_with_boost.to_string()
+                );
+                assert_eq!(
+                    dust_without_boost_expected.to_string(),
+                    top_block_dust_without_boost.to_string()
+                );
+                assert_eq!(
+                    dust_per_bp_expected.to_string(),
+                    dust_per_bp.to_f64().to_string()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn published_top_block_report_test() -> Result<(), Box<dyn standardError>> {
+        check_top_block_report("./top_block_reports/published/dustAndBlockPartReportTop.csv")
+    }
+
+    fn check_bottom_block_report(path: &str) -> Result<(), Box<dyn standardError>> {
+        let file = File::open(path)?;
+        let mut rdr = csv::Reader::from_reader(file);
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
+            if valid_block_index(block_index).is_err() {
+                assert_out_of_range(block_index);
+                continue;
+            }
+            let balance = record.get(1).unwrap().parse::<u64>().unwrap();
+
+            let sallar_without_boost_expected = record.get(2).unwrap().parse::<u64>().unwrap();
+            let bp_without_boost_expected = record.get(3).unwrap().parse::<u64>().unwrap();
+            let sallar_with_boost_expected = record.get(4).unwrap().parse::<u64>().unwrap();
+            let bp_with_boost_expected = record.get(5).unwrap().parse::<u64>().unwrap();
+            let sallar_per_bp_expected = record.get(6).unwrap().parse::<f64>().unwrap();
+
+            let bottom_block_bp_with_boost =
+                calculate_bottom_bp_with_boost(block_index, balance).unwrap();
+            let bottom_block_bp_without_boost = calculate_bottom_bp_without_boost(balance).unwrap();
+            let sallar_per_bp = calculate_dust_per_bp(block_index).unwrap();
+
+            let (_, bottom_block_staking_dust_without_boost) = calculate_user_reward_bottom_block(
+                1,
+                0,
+                bottom_block_bp_without_boost,
+                bottom_block_bp_with_boost,
+                sallar_per_bp,
+                balance,
+                DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+            )
+            .unwrap();
+            let (_, bottom_block_staking_dust_with_boost) = calculate_user_reward_bottom_block(
+                0,
+                1,
+                bottom_block_bp_without_boost,
+                bottom_block_bp_with_boost,
+                sallar_per_bp,
+                balance,
+                DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+            )
+            .unwrap();
+
+            let bp_without_boost = calculate_bottom_bp_without_boost(balance).unwrap();
+
+            assert_eq!(
+                bp_without_boost_expected.to_string(),
+                bp_without_boost.to_string()
+            );
+            assert_eq!(
+                bp_with_boost_expected.to_string(),
+                bottom_block_bp_with_boost.to_string()
+            );
+
+            assert_eq!(
+                sallar_per_bp_expected.to_string(),
+                sallar_per_bp.to_f64().to_string()
+            );
+
+            assert_eq!(
+                sallar_without_boost_expected.to_string(),
+                bottom_block_staking_dust_without_boost.to_string(),
+                "block_index: {}",
+                block_index
+            );
+            assert_eq!(
+                sallar_with_boost_expected.to_string(),
+                bottom_block_staking_dust_with_boost.to_string(),
+                "block_index: {}",
+                block_index
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn published_bottom_block_report_test() -> Result<(), Box<dyn standardError>> {
+        check_bottom_block_report(
+            "./bottom_block_reports/published/dustAndBlockPartReportBottom.csv",
+        )
+    }
+
+    #[test]
+    fn calculate_user_reward_breakdown_test() {
+        let dust_per_bp = calculate_dust_per_bp(1).unwrap();
+        let top_bp_with_boost = calculate_top_bp_with_boost(1).unwrap();
+
+        let breakdown =
+            calculate_user_reward_breakdown_top_block(2, 3, top_bp_with_boost, dust_per_bp)
+                .unwrap();
+        assert_eq!(
+            breakdown,
+            RewardBreakdown {
+                bp_no_boost: 2_000,
+                bp_boost: 3 * top_bp_with_boost,
+                amount_no_boost: 200_000_000_000,
+                amount_boost: 3 * top_bp_with_boost * 100_000_000,
+            }
+        );
+        assert_eq!(
+            calculate_user_reward_top_block(2, 3, top_bp_with_boost, dust_per_bp).unwrap(),
+            (breakdown.bp().unwrap(), breakdown.amount().unwrap())
+        );
+
+        let breakdown =
+            calculate_user_reward_breakdown_bottom_block(1, 1, 10, 30, dust_per_bp, 5, 5).unwrap();
+        assert_eq!(
+            breakdown,
+            RewardBreakdown {
+                bp_no_boost: 10,
+                bp_boost: 30,
+                amount_no_boost: 1_000_000_000,
+                amount_boost: 3_000_000_000,
+            }
+        );
+        assert_eq!(
+            calculate_user_reward_breakdown_bottom_block(1, 1, 10, 30, dust_per_bp, 4, 5).unwrap(),
+            RewardBreakdown::default()
+        );
+    }
+
+    #[test]
+    fn allocate_block_reward_test() {
+        // 7 BP of 2_000_000_000_000 dust, 1 BP is worth 285_714_285_714 and 2/7 dust
+        let dust_per_bp = DustPerBp { block_bp: 7 };
+
+        assert_eq!(
+            allocate_block_reward(1, dust_per_bp, 0).unwrap(),
+            (285_714_285_714, 2)
+        );
+        assert_eq!(
+            allocate_block_reward(2, dust_per_bp, 2).unwrap(),
+            (571_428_571_428, 6)
+        );
+        assert_eq!(
+            allocate_block_reward(4, dust_per_bp, 6).unwrap(),
+            (1_142_857_142_858, 0)
+        );
+        assert_eq!(
+            285_714_285_714 + 571_428_571_428 + 1_142_857_142_858,
+            DUSTS_PER_BLOCK
+        );
+
+        let dust_per_bp = calculate_dust_per_bp(MAX_BLOCK_INDEX).unwrap();
+        let mut rounding_remainder = 0;
+        let mut allocated_amount = 0;
+        let mut allocated_bp = 0;
+        while allocated_bp < dust_per_bp.block_bp() {
+            let bp = (allocated_bp % 97 + 1).min(dust_per_bp.block_bp() - allocated_bp);
+            let (reward, remainder) =
+                allocate_block_reward(bp, dust_per_bp, rounding_remainder).unwrap();
+            assert!(remainder < dust_per_bp.block_bp());
+            rounding_remainder = remainder;
+            allocated_amount += reward;
+            allocated_bp += bp;
+        }
+        assert_eq!(allocated_amount, DUSTS_PER_BLOCK);
+        assert_eq!(rounding_remainder, 0);
+    }
+
+    #[test]
+    fn calculate_user_rest_reward_test() {
+        assert_eq!(calculate_user_rest_reward(5, 1).unwrap(), (5, 500_000_000));
+        assert_eq!(
+            calculate_user_rest_reward(20_000, 1).unwrap(),
+            (20_000, DUSTS_PER_BLOCK)
+        );
+        assert_eq!(
+            calculate_user_rest_reward(30_000, 1).unwrap(),
+            (20_000, DUSTS_PER_BLOCK)
+        );
+    }
+
+    #[test]
+    fn estimate_staking_apr_test() {
+        assert_eq!(estimate_staking_apr(1_000_000, 1_000_000, 1), 0.001);
+        assert_eq!(estimate_staking_apr(1_000_000, 1_000_000, 2), 0.001999);
+        assert_eq!(estimate_staking_apr(1_000_000, 500_000, 2), 0.003998);
+        assert_eq!(estimate_staking_apr(999, 1_000_000, 438), 0.0);
+        assert_eq!(estimate_staking_apr(1_000_000, 0, 438), 0.0);
+    }
+
+    fn check_top_block_transfers(path: &str) -> Result<(), Box<dyn standardError>> {
+        let file = File::open(path)?;
+        let mut rdr = csv::Reader::from_reader(file);
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
+            if valid_block_index(block_index).is_err() {
+                assert_out_of_range(block_index);
+                continue;
+            }
+            let user_request_without_boost = record.get(1).unwrap().parse::<u64>().unwrap();
+            let user_request_with_boost = record.get(2).unwrap().parse::<u64>().unwrap();
+
+            let reward_dust_expected = record.get(3).unwrap().parse::<u64>().unwrap();
+
+            let top_block_bp_with_boost = calculate_top_bp_with_boost(block_index).unwrap();
+            let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+            let (_, reward_dust) = calculate_user_reward_top_block(
+                user_request_without_boost as u8,
+                user_request_with_boost as u8,
+                top_block_bp_with_boost,
+                dust_per_bp,
+            )
+            .unwrap();
+
+            assert_eq!(reward_dust_expected.to_string(), reward_dust.to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn published_user_reward_top_block_test() -> Result<(), Box<dyn standardError>> {
+        check_top_block_transfers("./top_block_reports/published/topBlockTransferTestData.csv")
+    }
+
+    fn check_bottom_block_transfers(path: &str) -> Result<(), Box<dyn standardError>> {
+        let file = File::open(path)?;
+        let mut rdr = csv::Reader::from_reader(file);
+
+        for result in rdr.records() {
+            let record = result?;
+
+            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
+            if valid_block_index(block_index).is_err() {
+                assert_out_of_range(block_index);
+                continue;
+            }
+            let user_request_without_boost = record.get(1).unwrap().parse::<u64>().unwrap();
+            let user_request_with_boost = record.get(2).unwrap().parse::<u64>().unwrap();
+
+            let user_wallet_balance = record.get(3).unwrap().parse::<u64>().unwrap();
+            let reward_dust_expected = record.get(4).unwrap().parse::<u64>().unwrap();
+            let bp_expected = record.get(5).unwrap().parse::<u64>().unwrap();
+
+            let bottom_block_bp_without_boost =
+                calculate_bottom_bp_without_boost(user_wallet_balance).unwrap();
+            let bottom_block_bp_with_boost =
+                calculate_bottom_bp_with_boost(block_index, user_wallet_balance).unwrap();
+            let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+
+            let (_, reward_dust) = calculate_user_reward_bottom_block(
+                user_request_without_boost as u8,
+                user_request_with_boost as u8,
+                bottom_block_bp_without_boost,
+                bottom_block_bp_with_boost,
+                dust_per_bp,
+                user_wallet_balance,
+                DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+            )
+            .unwrap();
+            let bp = bottom_block_bp_without_boost * user_request_without_boost
+                + bottom_block_bp_with_boost * user_request_with_boost;
+
+            assert_eq!(bp_expected.to_string(), bp.to_string());
+            assert_eq!(reward_dust_expected.to_string(), reward_dust.to_string());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn published_user_reward_bottom_block_test() -> Result<(), Box<dyn standardError>> {
+        check_bottom_block_transfers(
+            "./bottom_block_reports/published/bottomBlockTransferTestData.csv",
+        )
+    }
+
+    #[test]
+    fn test_convert_scaled_reward_parts_to_f64() {
+        assert_eq!(
+            convert_scaled_reward_parts_to_f64(REWARD_PARTS_SCALING_FACTOR),
+            1.0
+        );
+        assert_eq!(convert_scaled_reward_parts_to_f64(0), 0.0);
+        assert_eq!(convert_scaled_reward_parts_to_f64(250_000_000), 0.25);
+        assert_eq!(convert_scaled_reward_parts_to_f64(100_000_000), 0.1);
+    }
+
+    #[test]
+    fn test_calculate_final_staking_reward() {
+        assert_eq!(
+            calculate_final_staking_reward(REWARD_PARTS_SCALING_FACTOR, 1_000_000).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            calculate_final_staking_reward(100_000_000, 1_000_000).unwrap(),
+            100_000
+        );
+        assert_eq!(calculate_final_staking_reward(1, 999_999_999).unwrap(), 0);
+        assert_eq!(
+            calculate_final_staking_reward(REWARD_PARTS_SCALING_FACTOR, u64::MAX).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            calculate_final_staking_reward(u64::MAX, u64::MAX),
+            Err(MathError::U64ConversionError)
+        );
+    }
+
+    #[test]
+    fn test_calculate_snapshot_reward_part() {
+        assert_eq!(
+            calculate_snapshot_reward_part(1_000, 1_000).unwrap(),
+            REWARD_PARTS_SCALING_FACTOR
+        );
+        assert_eq!(
+            calculate_snapshot_reward_part(250, 1_000).unwrap(),
+            250_000_000
+        );
+        assert_eq!(calculate_snapshot_reward_part(1, 3).unwrap(), 333_333_333);
+        assert_eq!(calculate_snapshot_reward_part(1, 2_000_000_000).unwrap(), 0);
+        assert_eq!(
+            calculate_snapshot_reward_part(u64::MAX, u64::MAX).unwrap(),
+            REWARD_PARTS_SCALING_FACTOR
+        );
+        assert_eq!(
+            calculate_snapshot_reward_part(1_001, 1_000),
+            Err(MathError::InvalidRegisteredBalance)
+        );
+        assert_eq!(
+            calculate_snapshot_reward_part(0, 0),
+            Err(MathError::InvalidRegisteredBalance)
+        );
+    }
+
+    #[test]
+    fn test_calculate_max_reward_part() {
+        assert_eq!(
+            calculate_max_reward_part(250, 1_000, 0).unwrap(),
+            250_000_000
+        );
+        assert_eq!(
+            calculate_max_reward_part(250, 1_000, 1_000).unwrap(),
+            275_000_000
+        );
+        assert_eq!(calculate_max_reward_part(1, 3, 0).unwrap(), 333_333_333);
+        assert_eq!(calculate_max_reward_part(0, 1_000, 90_000).unwrap(), 0);
+        assert_eq!(
+            calculate_max_reward_part(u64::MAX, 1, 90_000).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            calculate_max_reward_part(1_000, 0, 1_000),
+            Err(MathError::ZeroRegisteredBalance)
+        );
+    }
+
+    #[test]
+    fn test_calculate_weighted_balance() {
+        assert_eq!(calculate_weighted_balance(1_000, 10_000).unwrap(), 1_000);
+        assert_eq!(calculate_weighted_balance(1_000, 15_000).unwrap(), 1_500);
+        assert_eq!(calculate_weighted_balance(3, 15_000).unwrap(), 4);
+        assert_eq!(
+            calculate_weighted_balance(u64::MAX, 10_000).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            calculate_weighted_balance(u64::MAX, 20_000),
+            Err(MathError::U64ConversionError)
+        );
+    }
+
+    #[test]
+    fn test_calculate_reward_fee() {
+        assert_eq!(calculate_reward_fee(1_000_000, 0).unwrap(), 0);
+        assert_eq!(calculate_reward_fee(1_000_000, 500).unwrap(), 50_000);
+        assert_eq!(calculate_reward_fee(199, 50).unwrap(), 0);
+        assert_eq!(calculate_reward_fee(u64::MAX, 500).unwrap(), u64::MAX / 20);
+    }
+
+    #[test]
+    fn test_calculate_blocks_minted_amount() {
+        assert_eq!(calculate_top_blocks_minted_amount(1, 1), DUSTS_PER_BLOCK);
+        assert_eq!(
+            calculate_top_blocks_minted_amount(1, 250),
+            250 * DUSTS_PER_BLOCK
+        );
+        assert_eq!(
+            calculate_bottom_blocks_minted_amount(MAX_BLOCK_INDEX, MAX_BLOCK_INDEX),
+            DUSTS_PER_BLOCK
+        );
+        assert_eq!(
+            calculate_bottom_blocks_minted_amount(MAX_BLOCK_INDEX, 1),
+            MAX_BLOCK_INDEX * DUSTS_PER_BLOCK
+        );
+        // a shorter schedule starts with both blocks funded
+        assert_eq!(
+            calculate_top_blocks_minted_amount(400_000, 400_010),
+            11 * DUSTS_PER_BLOCK
+        );
+        assert_eq!(
+            calculate_bottom_blocks_minted_amount(400_100, 400_100),
+            DUSTS_PER_BLOCK
+        );
+    }
+
+    #[test]
+    fn test_valid_block_index() {
+        assert!(valid_block_index(1).is_ok());
+        assert!(valid_block_index(MAX_BLOCK_INDEX).is_ok());
+        for block_index in [0, MAX_BLOCK_INDEX + 1, u64::MAX] {
+            assert_eq!(
+                valid_block_index(block_index),
+                Err(MathError::BlockIndexOutOfBounds)
+            );
+            assert_eq!(
+                calculate_bottom_block_max_boost(block_index),
+                Err(MathError::BlockIndexOutOfBounds)
+            );
+        }
+        assert_eq!(
+            calculate_dust_per_bp(0),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_calculate_bottom_bp_with_given_boost() {
+        for block_index in [1, 1_000, 250_000, MAX_BLOCK_INDEX] {
+            let boost = calculate_bottom_block_max_boost(block_index).unwrap();
+
+            for user_wallet_balance in
+                [0, 99_999_999, 100_000_000, 123_456_789_000, u32::MAX as u64]
+            {
+                assert_eq!(
+                    calculate_bottom_bp_with_given_boost(user_wallet_balance, boost).unwrap(),
+                    calculate_bottom_bp_with_boost(block_index, user_wallet_balance).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_calculate_bottom_bp_for_max_balance() {
+        let max_boost = BOTTOM_BLOCK_BOOST_STEPS
+            .iter()
+            .map(|(_, boost)| *boost)
+            .max()
+            .unwrap();
+        let max_staking_sallar = (u64::MAX / 100_000_000) as u128;
+
+        assert_eq!(
+            calculate_bottom_bp_without_boost(u64::MAX),
+            Ok(max_staking_sallar as u64)
+        );
+        assert_eq!(
+            calculate_bottom_bp_with_given_boost(u64::MAX, max_boost),
+            Ok((max_staking_sallar * (max_boost as u128 + 1)) as u64)
+        );
+        assert_eq!(
+            calculate_bottom_bp_with_given_boost(u64::MAX, u64::MAX - 1),
+            Err(MathError::U64ConversionError)
+        );
+        assert_eq!(
+            calculate_bottom_bp_with_given_boost(u64::MAX, u64::MAX),
+            Err(MathError::ArithmeticOverflow)
+        );
+        // the boost does not overflow for a balance below 1 Sallar
+        assert_eq!(
+            calculate_bottom_bp_with_given_boost(99_999_999, u64::MAX - 1),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_calculate_user_reward_for_max_requests() {
+        let dust_per_bp = DustPerBp::from_block_bp(u64::MAX).unwrap();
+        let max_parts = u64::MAX / u8::MAX as u64;
+
+        let breakdown =
+            calculate_user_reward(u8::MAX, u8::MAX, max_parts, max_parts, dust_per_bp).unwrap();
+        assert_eq!(breakdown.bp_no_boost, max_parts * u8::MAX as u64);
+        assert_eq!(breakdown.bp_boost, max_parts * u8::MAX as u64);
+        assert_eq!(
+            breakdown.amount_boost,
+            calculate_single_reward(max_parts, dust_per_bp).unwrap() * u8::MAX as u64
+        );
+        assert_eq!(breakdown.bp(), Err(MathError::ArithmeticOverflow));
+
+        assert_eq!(
+            calculate_user_reward(u8::MAX, 0, max_parts + 1, 0, dust_per_bp),
+            Err(MathError::U64ConversionError)
+        );
+        assert_eq!(
+            calculate_user_reward(0, u8::MAX, 0, max_parts + 1, dust_per_bp),
+            Err(MathError::U64ConversionError)
+        );
+        assert_eq!(
+            calculate_top_bp(u64::MAX / TOKEN_AMOUNT_SCALING_FACTOR),
+            Err(MathError::U64ConversionError)
+        );
+    }
+
+    #[test]
+    fn test_reduction_tables() {
+        for powers in [
+            REDUCTION_INVERSE_POWERS,
+            BOTTOM_BOOST_REDUCTION_POWERS,
+            TOP_BOOST_REDUCTION_POWERS,
+        ] {
+            assert_eq!(
+                calculate_reduction(&powers, 0).unwrap(),
+                FIXED_POINT_SCALING_FACTOR
+            );
+            assert_eq!(calculate_reduction(&powers, 1).unwrap(), powers[0]);
+            for k in 1..REDUCTION_TABLE_SIZE {
+                // every entry is the square of the previous one, up to the rounding of the previous entry
+                let squared = fixed_point_mul(powers[k - 1], powers[k - 1]).unwrap();
+                assert!(squared.abs_diff(powers[k]) <= 1 << k, "entry: {}", k);
+            }
+            assert_eq!(
+                calculate_reduction(&powers, 1 << REDUCTION_TABLE_SIZE),
+                Err(MathError::BlockIndexOutOfBounds)
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_bp_checkpoints() {
+        for (checkpoint_index, checkpoint) in REDUCTION_INVERSE_CHECKPOINTS.iter().enumerate() {
+            let reduction = calculate_reduction(
+                &REDUCTION_INVERSE_POWERS,
+                checkpoint_index as u64 * MAX_BP_CHECKPOINT_INTERVAL,
+            )
+            .unwrap();
+            assert!(
+                checkpoint.abs_diff(reduction) <= REDUCTION_TABLE_SIZE as u128,
+                "checkpoint: {}",
+                checkpoint_index
+            );
+        }
+        assert!(
+            REDUCTION_INVERSE_CHECKPOINTS.len() as u64 * MAX_BP_CHECKPOINT_INTERVAL
+                >= MAX_BLOCK_INDEX
+        );
+    }
+
+    #[test]
+    fn test_block_constant_tables_match_formulas() {
+        for block_index in 1..=MAX_BLOCK_INDEX {
+            let reduction =
+                calculate_reduction(&REDUCTION_INVERSE_POWERS, block_index - 1).unwrap();
+            assert_eq!(
+                calculate_max_bp(block_index).unwrap() as u128,
+                fixed_point_div_round(FIRST_BP as u128, reduction).unwrap(),
+                "block_index: {}",
+                block_index
+            );
+            assert_eq!(
+                calculate_top_block_max_boost(block_index).unwrap(),
+                calculate_top_block_max_boost_from_formula(block_index),
+                "block_index: {}",
+                block_index
+            );
+            assert_eq!(
+                calculate_bottom_block_max_boost(block_index).unwrap(),
+                calculate_bottom_block_max_boost_from_formula(block_index),
+                "block_index: {}",
+                block_index
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_max_bp_and_boosts() {
+        assert_eq!(calculate_max_bp(1).unwrap(), FIRST_BP);
+        assert_eq!(calculate_max_bp(2).unwrap(), 20_000);
+        assert_eq!(calculate_max_bp(MAX_BLOCK_INDEX).unwrap(), 290_698);
+        assert_eq!(calculate_top_block_max_boost(1).unwrap(), 0);
+        assert_eq!(calculate_top_block_max_boost(250).unwrap(), 1);
+        assert_eq!(calculate_top_block_max_boost(MAX_BLOCK_INDEX).unwrap(), 4);
+        assert_eq!(calculate_bottom_block_max_boost(1).unwrap(), 23);
+        assert_eq!(
+            calculate_bottom_block_max_boost(MAX_BLOCK_INDEX).unwrap(),
+            60
+        );
+        assert_eq!(
+            calculate_max_bp(MAX_BLOCK_INDEX + 1),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+        assert_eq!(
+            calculate_top_bp_with_boost(0),
+            Err(MathError::BlockIndexOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_calculate_single_reward() {
+        let dust_per_bp = DustPerBp { block_bp: 3 };
+        assert_eq!(
+            calculate_single_reward(1, dust_per_bp).unwrap(),
+            666_666_666_667
+        );
+        assert_eq!(
+            calculate_single_reward(3, dust_per_bp).unwrap(),
+            DUSTS_PER_BLOCK
+        );
+        assert_eq!(calculate_single_reward(0, dust_per_bp).unwrap(), 0);
+        // exactly a half dust is rounded up
+        let dust_per_bp = DustPerBp {
+            block_bp: 2 * DUSTS_PER_BLOCK,
+        };
+        assert_eq!(calculate_single_reward(1, dust_per_bp).unwrap(), 1);
+        assert_eq!(
+            calculate_single_reward(u64::MAX, DustPerBp { block_bp: 1 }),
+            Err(MathError::U64ConversionError)
+        );
+        assert_eq!(calculate_dust_per_bp(1).unwrap().to_f64(), 100_000_000.0);
+    }
+
+    #[test]
+    fn test_calculate_user_reward_top_block_f64_rounding() {
+        // the exact reward of a boosted request is just below a half dust, its f64 product is rounded up to the half
+        let dust_per_bp = calculate_dust_per_bp(44_488).unwrap();
+        let top_bp_with_boost = calculate_top_bp_with_boost(44_488).unwrap();
+        assert_eq!(top_bp_with_boost, 2_000);
+        assert_eq!(
+            calculate_user_reward_top_block(1, 2, top_bp_with_boost, dust_per_bp).unwrap(),
+            (5_000, 388_093_297_630)
+        );
+    }
+}
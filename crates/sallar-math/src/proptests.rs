@@ -0,0 +1,203 @@
+//! Property-based tests of the token math, enabled by the `proptest` feature (`cargo test -p sallar-math --features proptest`).
+//!
+//! Besides the invariants of the math, the results are compared with the f64 formulas the precomputed tables were derived from,
+//! so the tables and the fixed-point arithmetic cannot silently diverge from them.
+
+use std::vec::Vec;
+
+use proptest::prelude::*;
+
+use super::*;
+
+/// The reduction of BP from block to block, as used by the f64 formula of the maximal BP.
+const REDUCTION_INVERSE: f64 = 0.99999430521433;
+
+/// By default the program rewards bottom block solutions of users with the balance of at least 20_000 Sallar.
+const DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 = 2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
+
+/// The highest number of requests with or without boost of a generated user.
+const MAX_GENERATED_REQUESTS: u8 = 10;
+
+/// The f64 formula of the maximal BP of the block.
+fn calculate_max_bp_from_formula(block_index: u64) -> u64 {
+    (FIRST_BP as f64 / REDUCTION_INVERSE.powf((block_index - 1) as f64)).round() as u64
+}
+
+/// The f64 formula of the reward for the given number of BP.
+fn calculate_single_reward_from_formula(bp: u64, block_bp: u64) -> u64 {
+    (bp as f64 * (DUSTS_PER_BLOCK as f64 / block_bp as f64)).round() as u64
+}
+
+/// Settles the rewards of a block in order, as the block solution process does: a user receives the calculated reward
+/// as long as the user's BP are lower than the remaining BP of the block, the user exhausting the block receives the remaining balance.
+///
+/// ### Returns
+/// The total amount paid from the block and the flag whether the block was exhausted.
+fn settle_block_rewards(block_bp: u64, rewards: &[(u64, u64)]) -> (u64, bool) {
+    let mut remaining_bp = block_bp;
+    let mut remaining_balance = DUSTS_PER_BLOCK;
+    for &(bp, amount) in rewards {
+        if bp < remaining_bp {
+            assert!(amount <= remaining_balance);
+            remaining_bp -= bp;
+            remaining_balance -= amount;
+        } else {
+            return (DUSTS_PER_BLOCK, true);
+        }
+    }
+
+    (DUSTS_PER_BLOCK - remaining_balance, false)
+}
+
+fn block_index() -> impl Strategy<Value = u64> {
+    1..=MAX_BLOCK_INDEX
+}
+
+fn requests() -> impl Strategy<Value = (u8, u8)> {
+    (0..=MAX_GENERATED_REQUESTS, 0..=MAX_GENERATED_REQUESTS)
+}
+
+fn wallet_balance() -> impl Strategy<Value = u64> {
+    0..=1_000_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR
+}
+
+proptest! {
+    #[test]
+    fn max_bp_is_monotonic(block_index in 1..MAX_BLOCK_INDEX) {
+        let max_bp = calculate_max_bp(block_index).unwrap();
+
+        prop_assert!(max_bp <= calculate_max_bp(block_index + 1).unwrap());
+        prop_assert_eq!(calculate_dust_per_bp(block_index).unwrap().block_bp(), max_bp);
+    }
+
+    #[test]
+    fn max_bp_matches_formula(block_index in block_index()) {
+        prop_assert_eq!(
+            calculate_max_bp(block_index).unwrap(),
+            calculate_max_bp_from_formula(block_index)
+        );
+    }
+
+    #[test]
+    fn single_reward_matches_formula(block_index in block_index(), bp_part in 0.0..=1.0f64) {
+        let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+        let bp = (dust_per_bp.block_bp() as f64 * bp_part) as u64;
+        let reward = calculate_single_reward(bp, dust_per_bp).unwrap();
+
+        prop_assert_eq!(
+            reward,
+            calculate_single_reward_from_formula(bp, dust_per_bp.block_bp())
+        );
+        prop_assert!(reward <= calculate_single_reward(bp + 1, dust_per_bp).unwrap());
+        prop_assert!(reward <= DUSTS_PER_BLOCK);
+        prop_assert_eq!(reward > 0, bp > 0);
+        prop_assert_eq!(
+            calculate_single_reward(dust_per_bp.block_bp(), dust_per_bp).unwrap(),
+            DUSTS_PER_BLOCK
+        );
+    }
+
+    #[test]
+    fn user_rewards_are_positive(
+        block_index in block_index(),
+        (request_without_boost, request_with_boost) in requests(),
+        wallet_balance in wallet_balance(),
+    ) {
+        let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+        let has_requests = request_without_boost > 0 || request_with_boost > 0;
+
+        let top_breakdown = calculate_user_reward_breakdown_top_block(
+            request_without_boost,
+            request_with_boost,
+            calculate_top_bp_with_boost(block_index).unwrap(),
+            dust_per_bp,
+        )
+        .unwrap();
+        prop_assert_eq!(top_breakdown.bp().unwrap() > 0, has_requests);
+        prop_assert_eq!(top_breakdown.amount().unwrap() > 0, has_requests);
+
+        let bottom_breakdown = calculate_user_reward_breakdown_bottom_block(
+            request_without_boost,
+            request_with_boost,
+            calculate_bottom_bp_without_boost(wallet_balance).unwrap(),
+            calculate_bottom_bp_with_boost(block_index, wallet_balance).unwrap(),
+            dust_per_bp,
+            wallet_balance,
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+        )
+        .unwrap();
+        prop_assert_eq!(
+            bottom_breakdown.amount().unwrap() > 0,
+            bottom_breakdown.bp().unwrap() > 0
+        );
+    }
+
+    #[test]
+    fn block_payout_does_not_exceed_block_balance(
+        block_index in block_index(),
+        users in proptest::collection::vec(requests(), 1..64),
+    ) {
+        let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+        let top_bp_with_boost = calculate_top_bp_with_boost(block_index).unwrap();
+        let requests_count = users
+            .iter()
+            .map(|&(request_without_boost, request_with_boost)| {
+                u64::from(request_without_boost) + u64::from(request_with_boost)
+            })
+            .sum::<u64>();
+        let rewards = users
+            .into_iter()
+            .map(|(request_without_boost, request_with_boost)| {
+                calculate_user_reward_top_block(
+                    request_without_boost,
+                    request_with_boost,
+                    top_bp_with_boost,
+                    dust_per_bp,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<(u64, u64)>>();
+
+        let (paid_amount, exhausted) = settle_block_rewards(dust_per_bp.block_bp(), &rewards);
+
+        prop_assert!(paid_amount <= DUSTS_PER_BLOCK);
+        if !exhausted {
+            // the reward of every request is rounded separately, by at most half a dust
+            let paid_bp = rewards.iter().map(|&(bp, _)| bp).sum::<u64>();
+            prop_assert!(
+                paid_amount.abs_diff(calculate_single_reward(paid_bp, dust_per_bp).unwrap())
+                    <= requests_count
+            );
+        }
+    }
+
+    #[test]
+    fn block_reward_allocation_is_exact(
+        block_index in block_index(),
+        bps in proptest::collection::vec(1..100_000u64, 1..64),
+    ) {
+        let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
+        let mut remaining_bp = dust_per_bp.block_bp();
+        let mut remaining_balance = DUSTS_PER_BLOCK;
+        let mut rounding_remainder = 0;
+        for bp in bps.into_iter().chain(core::iter::once(u64::MAX)) {
+            let bp = bp.min(remaining_bp);
+            let (reward, remainder) =
+                allocate_block_reward(bp, dust_per_bp, rounding_remainder).unwrap();
+            prop_assert!(reward <= remaining_balance);
+            prop_assert!(remainder < dust_per_bp.block_bp());
+            remaining_bp -= bp;
+            remaining_balance -= reward;
+            rounding_remainder = remainder;
+            // the balance is always exactly worth the remaining BP and the carried remainder
+            prop_assert_eq!(
+                remaining_balance as u128 * dust_per_bp.block_bp() as u128,
+                remaining_bp as u128 * DUSTS_PER_BLOCK as u128 + rounding_remainder as u128
+            );
+        }
+
+        prop_assert_eq!(remaining_bp, 0);
+        prop_assert_eq!(remaining_balance, 0);
+        prop_assert_eq!(rounding_remainder, 0);
+    }
+}
@@ -0,0 +1,594 @@
+//! Precomputed tables of the token math, so the per-block constants are looked up instead of being calculated from the exponential formulas.
+//!
+//! The reduction factors and the checkpoints were computed with 80 significant digits and rounded to the nearest integer scaled by `FIXED_POINT_SCALING_FACTOR`.
+//! The boost steps were derived from the fixed-point formulas for every block from 1 to `MAX_BLOCK_INDEX`,
+//! the token math tests recalculate the formulas for the whole range of blocks and compare them with the tables.
+
+/// The number of entries of the reduction tables, the tables cover reductions by up to 2^19 - 1 blocks (more than `MAX_BLOCK_INDEX`).
+pub(crate) const REDUCTION_TABLE_SIZE: usize = 19;
+
+/// BP reduction factor 0.99999430521433 raised to the powers of two (entry `k` is the factor raised to `2^k`), in fixed-point.
+pub(crate) const REDUCTION_INVERSE_POWERS: [u128; REDUCTION_TABLE_SIZE] = [
+    999_994_305_214_330_000,
+    999_988_610_461_090_584,
+    999_977_221_051_902_764,
+    999_954_442_622_686_005,
+    999_908_887_320_846_637,
+    999_817_782_943_213_577,
+    999_635_599_089_482_939,
+    999_271_330_966_989_463,
+    998_543_192_892_538_595,
+    997_088_508_072_025_540,
+    994_185_492_929_297_740,
+    988_404_794_351_070_726,
+    976_944_037_496_182_414,
+    954_419_652_399_342_270,
+    910_916_872_886_081_325,
+    829_769_549_308_557_242,
+    688_517_504_959_726_208,
+    474_056_354_635_966_604,
+    224_729_427_370_741_335,
+];
+
+/// Bottom block boost reduction factor 0.999997999992 raised to the powers of two (entry `k` is the factor raised to `2^k`), in fixed-point.
+/// The boosts are looked up in the boost steps, the table is used only to verify the steps.
+#[cfg(test)]
+pub(crate) const BOTTOM_BOOST_REDUCTION_POWERS: [u128; REDUCTION_TABLE_SIZE] = [
+    999_997_999_992_000_000,
+    999_995_999_988_000_032,
+    999_991_999_992_000_160,
+    999_984_000_048_000_448,
+    999_968_000_351_999_360,
+    999_936_001_727_976_192,
+    999_872_007_551_731_206,
+    999_744_031_485_529_226,
+    999_488_128_490_938_853,
+    998_976_518_994_319_495,
+    997_954_085_502_007_979,
+    995_912_356_770_149_052,
+    991_841_422_367_472_649,
+    983_749_407_123_931_274,
+    967_762_896_016_686_284,
+    936_565_022_906_603_549,
+    877_154_042_132_046_831,
+    769_399_213_628_588_587,
+    591_975_149_932_290_498,
+];
+
+/// Top block boost growth factor 1.000004498927 raised to the powers of two (entry `k` is the factor raised to `2^k`), in fixed-point.
+/// The boosts are looked up in the boost steps, the table is used only to verify the steps.
+#[cfg(test)]
+pub(crate) const TOP_BOOST_REDUCTION_POWERS: [u128; REDUCTION_TABLE_SIZE] = [
+    1_000_004_498_927_000_000,
+    1_000_008_997_874_240_344,
+    1_000_017_995_829_442_429,
+    1_000_035_991_982_734_736,
+    1_000_071_985_260_892_292,
+    1_000_143_975_703_662_371,
+    1_000_287_972_136_327_986,
+    1_000_576_027_200_607_274,
+    1_001_152_386_208_550_387,
+    1_002_306_100_411_074_431,
+    1_004_617_518_921_254_819,
+    1_009_256_359_323_497_784,
+    1_018_598_398_834_921_273,
+    1_037_542_698_109_065_347,
+    1_076_494_850_399_439_113,
+    1_158_841_162_936_510_796,
+    1_342_912_840_916_044_763,
+    1_803_414_898_297_202_149,
+    3_252_305_295_400_307_970,
+];
+
+/// The number of blocks between the checkpoints of the BP reduction.
+pub(crate) const MAX_BP_CHECKPOINT_INTERVAL: u64 = 1_024;
+
+/// BP reduction factor 0.99999430521433 raised to the multiples of `MAX_BP_CHECKPOINT_INTERVAL` (entry `i` is the factor raised to `i * MAX_BP_CHECKPOINT_INTERVAL`), in fixed-point.
+/// The reduction between a checkpoint and a block needs only the first 10 entries of `REDUCTION_INVERSE_POWERS`.
+pub(crate) const REDUCTION_INVERSE_CHECKPOINTS: [u128; 459] = [
+    1_000_000_000_000_000_000,
+    994_185_492_929_297_740,
+    988_404_794_351_070_726,
+    982_657_707_685_600_412,
+    976_944_037_496_182_414,
+    971_263_589_482_480_447,
+    965_616_170_473_918_907,
+    960_001_588_423_113_867,
+    954_419_652_399_342_270,
+    948_870_172_582_049_101,
+    943_352_960_254_392_302,
+    937_867_827_796_825_230,
+    932_414_588_680_716_420,
+    926_993_057_462_006_455,
+    921_603_049_774_901_712,
+    916_244_382_325_604_779,
+    910_916_872_886_081_325,
+    905_620_340_287_863_213,
+    900_354_604_415_887_645,
+    895_119_486_202_372_130,
+    889_914_807_620_725_063,
+    884_740_391_679_491_716,
+    879_596_062_416_335_424,
+    874_481_644_892_053_775,
+    869_396_965_184_629_585,
+    864_341_850_383_316_470,
+    859_316_128_582_758_801,
+    854_319_628_877_145_857,
+    849_352_181_354_399_962,
+    844_413_617_090_398_415,
+    839_503_768_143_229_022,
+    834_622_467_547_479_026,
+    829_769_549_308_557_242,
+    824_944_848_397_049_208,
+    820_148_200_743_105_161,
+    815_379_443_230_860_640,
+    810_638_413_692_889_528,
+    805_924_950_904_689_358,
+    801_238_894_579_198_670,
+    796_580_085_361_346_257,
+    791_948_364_822_632_099,
+    787_343_575_455_739_811,
+    782_765_560_669_180_414,
+    778_214_164_781_967_245,
+    773_689_233_018_321_843,
+    769_190_611_502_410_602,
+    764_718_147_253_112_040,
+    760_271_688_178_814_488,
+    755_851_083_072_244_027,
+    751_456_181_605_322_503,
+    747_086_834_324_055_433,
+    742_742_892_643_449_645,
+    738_424_208_842_460_457,
+    734_130_636_058_968_248,
+    729_862_028_284_784_230,
+    725_618_240_360_685_259,
+    721_399_127_971_477_522,
+    717_204_547_641_088_922,
+    713_034_356_727_689_994,
+    708_888_413_418_843_203,
+    704_766_576_726_680_432,
+    700_668_706_483_108_522,
+    696_594_663_335_042_681,
+    692_544_308_739_667_615,
+    688_517_504_959_726_208,
+    684_514_115_058_835_602,
+    680_534_002_896_830_501,
+    676_577_033_125_133_568,
+    672_643_071_182_152_722,
+    668_731_983_288_705_211,
+    664_843_636_443_468_289,
+    660_977_898_418_456_340,
+    657_134_637_754_524_305,
+    653_313_723_756_897_255,
+    649_515_026_490_725_953,
+    645_738_416_776_668_261,
+    641_983_766_186_496_240,
+    638_250_947_038_728_791,
+    634_539_832_394_289_689,
+    630_850_296_052_190_864,
+    627_182_212_545_240_786,
+    623_535_457_135_777_796,
+    619_909_905_811_428_250,
+    616_305_435_280_889_328,
+    612_721_922_969_736_363,
+    609_159_247_016_254_545,
+    605_617_286_267_294_868,
+    602_095_920_274_154_168,
+    598_595_029_288_479_114,
+    595_114_494_258_194_026,
+    591_654_196_823_452_357,
+    588_214_019_312_611_726,
+    584_793_844_738_232_350,
+    581_393_556_793_098_738,
+    578_013_039_846_264_529,
+    574_652_178_939_120_317,
+    571_310_859_781_484_341,
+    567_988_968_747_715_913,
+    564_686_392_872_851_434,
+    561_403_019_848_762_885,
+    558_138_738_020_338_652,
+    554_893_436_381_686_556,
+    551_667_004_572_358_965,
+    548_459_332_873_599_847,
+    545_270_312_204_613_657,
+    542_099_834_118_855_902,
+    538_947_790_800_345_292,
+    535_814_075_059_997_321,
+    532_698_580_331_979_175,
+    529_601_200_670_085_826,
+    526_521_830_744_137_206,
+    523_460_365_836_396_321,
+    520_416_701_838_008_203,
+    517_390_735_245_459_554,
+    514_382_363_157_058_989,
+    511_391_483_269_437_731,
+    508_417_993_874_070_669,
+    505_461_793_853_817_627,
+    502_522_782_679_484_756,
+    499_600_860_406_405_917,
+    496_695_927_671_043_937,
+    493_807_885_687_611_633,
+    490_936_636_244_712_482,
+    488_082_081_702_000_818,
+    485_244_124_986_861_456,
+    482_422_669_591_108_618,
+    479_617_619_567_704_057,
+    476_828_879_527_494_255,
+    474_056_354_635_966_604,
+    471_299_950_610_024_438,
+    468_559_573_714_780_824,
+    465_835_130_760_370_994,
+    463_126_529_098_783_305,
+    460_433_676_620_708_634,
+    457_756_481_752_408_085,
+    455_094_853_452_598_918,
+    452_448_701_209_358_573,
+    449_817_935_037_046_703,
+    447_202_465_473_245_105,
+    444_602_203_575_715_438,
+    442_017_060_919_374_635,
+    439_446_949_593_287_900,
+    436_891_782_197_679_188,
+    434_351_471_840_959_070,
+    431_825_932_136_769_880,
+    429_315_077_201_048_037,
+    426_818_821_649_103_457,
+    424_337_080_592_715_938,
+    421_869_769_637_248_436,
+    419_416_804_878_777_121,
+    416_978_102_901_238_121,
+    414_553_580_773_590_858,
+    412_143_156_046_997_873,
+    409_746_746_752_021_059,
+    407_364_271_395_834_184,
+    404_995_648_959_451_632,
+    402_640_798_894_973_250,
+    400_299_641_122_845_221,
+    397_972_096_029_136_860,
+    395_658_084_462_833_245,
+    393_357_527_733_143_589,
+    391_070_347_606_825_265,
+    388_796_466_305_523_389,
+    386_535_806_503_125_870,
+    384_288_291_323_133_844,
+    382_053_844_336_047_392,
+    379_832_389_556_766_464,
+    377_623_851_442_006_910,
+    375_428_154_887_731_541,
+    373_245_225_226_596_123,
+    371_074_988_225_410_222,
+    368_917_370_082_612_816,
+    366_772_297_425_762_582,
+    364_639_697_309_042_773,
+    362_519_497_210_780_612,
+    360_411_625_030_981_099,
+    358_316_009_088_875_168,
+    356_232_578_120_482_088,
+    354_161_261_276_186_050,
+    352_101_988_118_326_835,
+    350_054_688_618_804_501,
+    348_019_293_156_697_984,
+    345_995_732_515_897_561,
+    343_983_937_882_751_067,
+    341_983_840_843_723_803,
+    339_995_373_383_072_055,
+    338_018_467_880_530_128,
+    336_053_057_109_010_840,
+    334_099_074_232_319_386,
+    332_156_452_802_880_486,
+    330_225_126_759_478_756,
+    328_305_030_425_012_216,
+    326_396_098_504_258_862,
+    324_498_266_081_656_218,
+    322_611_468_619_093_804,
+    320_735_641_953_718_443,
+    318_870_722_295_752_318,
+    317_016_646_226_323_730,
+    315_173_350_695_310_453,
+    313_340_773_019_195_648,
+    311_518_850_878_936_223,
+    309_707_522_317_843_605,
+    307_906_725_739_476_825,
+    306_116_399_905_547_856,
+    304_336_483_933_839_127,
+    302_566_917_296_133_155,
+    300_807_639_816_154_202,
+    299_058_591_667_521_915,
+    297_319_713_371_716_849,
+    295_590_945_796_057_832,
+    293_872_230_151_691_085,
+    292_163_507_991_591_035,
+    290_464_721_208_572_753,
+    288_775_812_033_315_946,
+    287_096_723_032_400_443,
+    285_427_397_106_353_103,
+    283_767_777_487_706_071,
+    282_117_807_739_066_338,
+    280_477_431_751_196_516,
+    278_846_593_741_106_773,
+    277_225_238_250_157_867,
+    275_613_310_142_175_206,
+    274_010_754_601_573_873,
+    272_417_517_131_494_560,
+    270_833_543_551_950_331,
+    269_258_779_997_984_167,
+    267_693_172_917_837_224,
+    266_136_669_071_127_736,
+    264_589_215_527_040_517,
+    263_050_759_662_526_975,
+    261_521_249_160_515_611,
+    260_000_632_008_132_906,
+    258_488_856_494_934_560,
+    256_985_871_211_147_022,
+    255_491_625_045_919_227,
+    254_006_067_185_584_519,
+    252_529_147_111_932_664,
+    251_060_814_600_491_921,
+    249_601_019_718_821_091,
+    248_149_712_824_811_512,
+    246_706_844_564_998_910,
+    245_272_365_872_885_080,
+    243_846_227_967_269_318,
+    242_428_382_350_589_555,
+    241_018_780_807_273_141,
+    239_617_375_402_097_213,
+    238_224_118_478_558_601,
+    236_838_962_657_253_209,
+    235_461_860_834_264_822,
+    234_092_766_179_563_278,
+    232_731_632_135_411_956,
+    231_378_412_414_784_526,
+    230_033_060_999_790_898,
+    228_695_532_140_112_329,
+    227_365_780_351_445_630,
+    226_043_760_413_956_412,
+    224_729_427_370_741_335,
+    223_422_736_526_299_290,
+    222_123_643_445_011_474,
+    220_832_103_949_630_307,
+    219_548_074_119_777_125,
+    218_271_510_290_448_617,
+    217_002_369_050_531_943,
+    215_740_607_241_328_483,
+    214_486_181_955_086_180,
+    213_239_050_533_540_400,
+    211_999_170_566_463_292,
+    210_766_499_890_221_577,
+    209_540_996_586_342_717,
+    208_322_618_980_089_429,
+    207_111_325_639_042_486,
+    205_907_075_371_691_755,
+    204_709_827_226_035_430,
+    203_519_540_488_187_409,
+    202_336_174_680_992_768,
+    201_159_689_562_651_288,
+    199_990_045_125_348_981,
+    198_827_201_593_897_575,
+    197_671_119_424_381_914,
+    196_521_759_302_815_214,
+    195_379_082_143_802_147,
+    194_243_049_089_209_692,
+    193_113_621_506_845_716,
+    191_990_760_989_145_242,
+    190_874_429_351_864_349,
+    189_764_588_632_781_675,
+    188_661_201_090_407_460,
+    187_564_229_202_700_105,
+    186_473_635_665_790_186,
+    185_389_383_392_711_892,
+    184_311_435_512_141_836,
+    183_239_755_367_145_204,
+    182_174_306_513_929_185,
+    181_115_052_720_603_663,
+    180_061_957_965_949_101,
+    179_014_986_438_191_597,
+    177_974_102_533_785_062,
+    176_939_270_856_200_480,
+    175_910_456_214_722_200,
+    174_887_623_623_251_237,
+    173_870_738_299_115_527,
+    172_859_765_661_887_098,
+    171_854_671_332_206_120,
+    170_855_421_130_611_794,
+    169_861_981_076_380_039,
+    168_874_317_386_367_934,
+    167_892_396_473_864_880,
+    166_916_184_947_450_446,
+    165_945_649_609_858_849,
+    164_980_757_456_850_045,
+    164_021_475_676_087_376,
+    163_067_771_646_021_747,
+    162_119_612_934_782_292,
+    161_176_967_299_073_487,
+    160_239_802_683_078_677,
+    159_308_087_217_369_981,
+    158_381_789_217_824_531,
+    157_460_877_184_547_015,
+    156_545_319_800_798_486,
+    155_635_085_931_931_397,
+    154_730_144_624_330_828,
+    153_830_465_104_361_873,
+    152_936_016_777_323_144,
+    152_046_769_226_406_359,
+    151_162_692_211_661_984,
+    150_283_755_668_970_886,
+    149_409_929_709_021_964,
+    148_541_184_616_295_728,
+    147_677_490_848_053_787,
+    146_818_819_033_334_210,
+    145_965_139_971_952_732,
+    145_116_424_633_509_768,
+    144_272_644_156_403_194,
+    143_433_769_846_846_876,
+    142_599_773_177_894_904,
+    141_770_625_788_471_496,
+    140_946_299_482_406_544,
+    140_126_766_227_476_773,
+    139_311_998_154_452_467,
+    138_501_967_556_149_743,
+    137_696_646_886_488_335,
+    136_896_008_759_554_856,
+    136_100_025_948_671_506,
+    135_308_671_385_470_194,
+    134_521_918_158_972_049,
+    133_739_739_514_672_276,
+    132_962_108_853_630_335,
+    132_188_999_731_565_418,
+    131_420_385_857_957_172,
+    130_656_241_095_151_660,
+    129_896_539_457_472_522,
+    129_141_255_110_337_293,
+    128_390_362_369_378_872,
+    127_643_835_699_572_093,
+    126_901_649_714_365_374,
+    126_163_779_174_817_415,
+    125_430_198_988_738_920,
+    124_700_884_209_839_306,
+    123_975_810_036_878_372,
+    123_254_951_812_822_902,
+    122_538_285_024_008_177,
+    121_825_785_299_304_353,
+    121_117_428_409_287_692,
+    120_413_190_265_416_614,
+    119_713_046_919_212_533,
+    119_016_974_561_445_460,
+    118_324_949_521_324_344,
+    117_636_948_265_692_116,
+    116_952_947_398_225_413,
+    116_272_923_658_638_962,
+    115_596_853_921_894_582,
+    114_924_715_197_414_789,
+    114_256_484_628_300_977,
+    113_592_139_490_556_137,
+    112_931_657_192_312_101,
+    112_275_015_273_061_279,
+    111_622_191_402_892_860,
+    110_973_163_381_733_458,
+    110_327_909_138_592_172,
+    109_686_406_730_810_031,
+    109_048_634_343_313_812,
+    108_414_570_287_874_189,
+    107_784_193_002_368_197,
+    107_157_481_050_045_990,
+    106_534_413_118_801_854,
+    105_914_968_020_449_465,
+    105_299_124_690_001_358,
+    104_686_862_184_952_586,
+    104_078_159_684_568_546,
+    103_472_996_489_176_944,
+    102_871_352_019_463_874,
+    102_273_205_815_774_000,
+    101_678_537_537_414_795,
+    101_087_326_961_964_831,
+    100_499_553_984_586_095,
+    99_915_198_617_340_296,
+    99_334_240_988_509_150,
+    98_756_661_341_918_622,
+    98_182_440_036_267_087,
+    97_611_557_544_457_412,
+    97_043_994_452_932_903,
+    96_479_731_461_017_134,
+    95_918_749_380_257_595,
+    95_361_029_133_773_169,
+    94_806_551_755_605_400,
+    94_255_298_390_073_533,
+    93_707_250_291_133_299,
+    93_162_388_821_739_438,
+    92_620_695_453_211_921,
+    92_082_151_764_605_860,
+    91_546_739_442_085_080,
+    91_014_440_278_299_339,
+    90_485_236_171_765_159,
+    89_959_109_126_250_267,
+    89_436_041_250_161_608,
+    88_916_014_755_936_924,
+    88_399_011_959_439_863,
+    87_885_015_279_358_606,
+    87_374_007_236_607_999,
+    86_865_970_453_735_152,
+    86_360_887_654_328_495,
+    85_858_741_662_430_278,
+    85_359_515_401_952_479,
+    84_863_191_896_096_108,
+    84_369_754_266_773_894,
+    83_879_185_734_036_325,
+    83_391_469_615_501_023,
+    82_906_589_325_785_439,
+    82_424_528_375_942_851,
+    81_945_270_372_901_632,
+    81_468_799_018_907_787,
+    80_995_098_110_970_727,
+    80_524_151_540_312_264,
+    80_055_943_291_818_818,
+    79_590_457_443_496_799,
+    79_127_678_165_931_159,
+    78_667_589_721_747_100,
+    78_210_176_465_074_897,
+    77_755_422_841_017_847,
+    77_303_313_385_123_305,
+    76_853_832_722_856_793,
+    76_406_965_569_079_173,
+    75_962_696_727_526_858,
+    75_521_011_090_295_042,
+    75_081_893_637_323_937,
+    74_645_329_435_888_002,
+    74_211_303_640_088_132,
+    73_779_801_490_346_807,
+    73_350_808_312_906_176,
+    72_924_309_519_329_057,
+    72_500_290_606_002_838,
+    72_078_737_153_646_266,
+    71_659_634_826_819_100,
+    71_242_969_373_434_619,
+    70_828_726_624_274_959,
+    70_416_892_492_509_274,
+    70_007_452_973_214_698,
+    69_600_394_142_900_086,
+    69_195_702_159_032_529,
+];
+
+/// The boost of top block solutions as the steps of (the first block index of the step, boost), ordered by the block index.
+pub(crate) const TOP_BLOCK_BOOST_STEPS: [(u64, u64); 5] =
+    [(1, 0), (250, 1), (244_445, 2), (357_989, 3), (432_779, 4)];
+
+/// The boost of bottom block solutions as the steps of (the first block index of the step, boost), ordered by the block index.
+pub(crate) const BOTTOM_BLOCK_BOOST_STEPS: [(u64, u64); 38] = [
+    (1, 23),
+    (1_331, 24),
+    (22_167, 25),
+    (42_170, 26),
+    (61_403, 27),
+    (79_923, 28),
+    (97_782, 29),
+    (115_025, 30),
+    (131_693, 31),
+    (147_824, 32),
+    (163_450, 33),
+    (178_602, 34),
+    (193_309, 35),
+    (207_596, 36),
+    (221_486, 37),
+    (235_000, 38),
+    (248_158, 39),
+    (260_980, 40),
+    (273_480, 41),
+    (285_676, 42),
+    (297_581, 43),
+    (309_209, 44),
+    (320_574, 45),
+    (331_685, 46),
+    (342_555, 47),
+    (353_194, 48),
+    (363_611, 49),
+    (373_815, 50),
+    (383_815, 51),
+    (393_620, 52),
+    (403_235, 53),
+    (412_669, 54),
+    (421_929, 55),
+    (431_020, 56),
+    (439_949, 57),
+    (448_721, 58),
+    (457_342, 59),
+    (465_816, 60),
+];
@@ -0,0 +1,328 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use sallar::{cpi::accounts::InitializeUserClaimAccountContext, program::Sallar as SallarProgram};
+
+const VAULT_SEED: &str = "vault";
+const VAULT_TOKEN_ACCOUNT_SEED: &str = "vault_token_account";
+const DISCRIMINATOR_LENGTH: usize = 8;
+
+declare_id!("8bPjtcVcP7UDFyedaUom6L7nrGEUiEMFSpx7tm7tELCN");
+
+/// Example program built on top of Sallar CPI.
+/// It locks Sallar tokens in a vault until the unlock timestamp and forwards rewards received by the vault to the vault's owner.
+#[program]
+pub mod sallar_vault {
+    use super::*;
+
+    /// Creates the vault of the signer and the vault token account.
+    /// It also creates the Sallar claim account of the vault token account using CPI, so rewards below Sallar's minimum payout amount are accumulated for the vault.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize vault context where all required accounts are provided,
+    /// * `unlock_timestamp` - the timestamp after which locked tokens can be withdrawn.
+    pub fn initialize_vault(
+        ctx: Context<InitializeVaultContext>,
+        unlock_timestamp: i64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.owner = ctx.accounts.owner.key();
+        vault.bump = ctx.bumps.vault;
+        vault.token_account = ctx.accounts.vault_token_account.key();
+        vault.token_account_bump = ctx.bumps.vault_token_account;
+        vault.locked_amount = 0;
+        vault.unlock_timestamp = unlock_timestamp;
+
+        let cpi_accounts = InitializeUserClaimAccountContext {
+            blocks_state_account: ctx.accounts.sallar_blocks_state_account.to_account_info(),
+            config_account: ctx.accounts.sallar_config_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            user_token_account: ctx.accounts.vault_token_account.to_account_info(),
+            user_claim_account: ctx.accounts.sallar_user_claim_account.to_account_info(),
+            signer: ctx.accounts.owner.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.sallar_program.to_account_info(), cpi_accounts);
+
+        sallar::cpi::initialize_user_claim_account(cpi_ctx)
+    }
+
+    /// Locks tokens of the owner in the vault.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the deposit context where all required accounts are provided,
+    /// * `amount` - the amount of tokens to lock.
+    pub fn deposit(ctx: Context<DepositContext>, amount: u64) -> Result<()> {
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.owner_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vault_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.locked_amount = vault
+            .locked_amount
+            .checked_add(amount)
+            .ok_or(VaultError::ArithmeticOverflow)?;
+
+        Ok(())
+    }
+
+    /// Forwards tokens received by the vault on top of the locked amount (i.e. Sallar rewards) to the owner's token account.
+    /// It can be invoked by anyone, as tokens can only be forwarded to the owner.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the forward rewards context where all required accounts are provided.
+    ///
+    /// ### Returns
+    /// The amount of forwarded tokens
+    pub fn forward_rewards(ctx: Context<ForwardRewardsContext>) -> Result<u64> {
+        let vault = &ctx.accounts.vault;
+        let rewards = ctx
+            .accounts
+            .vault_token_account
+            .amount
+            .saturating_sub(vault.locked_amount);
+        require!(rewards > 0, VaultError::NoRewardsToForward);
+
+        transfer_from_vault(
+            vault,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.owner_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            rewards,
+        )?;
+
+        Ok(rewards)
+    }
+
+    /// Withdraws all locked tokens to the owner's token account once the unlock timestamp has passed.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the withdraw context where all required accounts are provided.
+    pub fn withdraw(ctx: Context<WithdrawContext>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+        require!(
+            Clock::get()?.unix_timestamp >= vault.unlock_timestamp,
+            VaultError::VaultLocked
+        );
+
+        transfer_from_vault(
+            vault,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.owner_token_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            vault.locked_amount,
+        )?;
+
+        ctx.accounts.vault.locked_amount = 0;
+
+        Ok(())
+    }
+}
+
+/// Transfers tokens from the vault token account, signed by the vault.
+///
+/// ### Arguments
+///
+/// * `vault` - the vault which is the authority of the vault token account,
+/// * `from` - the vault token account,
+/// * `mint` - the mint of the vault token account,
+/// * `to` - the recipient's token account,
+/// * `token_program` - the Solana token program account,
+/// * `amount` - the amount of tokens to transfer.
+fn transfer_from_vault<'info>(
+    vault: &Account<'info, Vault>,
+    from: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[VAULT_SEED.as_bytes(), vault.owner.as_ref(), &[vault.bump]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from,
+        mint: mint.to_account_info(),
+        to,
+        authority: vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program, cpi_accounts, signer_seeds);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Struct defining the vault of a single owner.
+/// Consists of the following attributes:
+/// * `owner` - the owner of the vault, the only account allowed to deposit and to receive tokens,
+/// * `bump` - the bump of the vault account,
+/// * `token_account` - the vault token account holding locked tokens and received rewards,
+/// * `token_account_bump` - the bump of the vault token account,
+/// * `locked_amount` - the amount of tokens deposited by the owner,
+/// * `unlock_timestamp` - the timestamp after which locked tokens can be withdrawn.
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub token_account: Pubkey,
+    pub token_account_bump: u8,
+    pub locked_amount: u64,
+    pub unlock_timestamp: i64,
+}
+
+/// Context for the initialize_vault instruction.
+///
+/// Attributes:
+/// - `vault` - the vault account to create,
+/// - `vault_token_account` - the vault token account to create,
+/// - `mint` - the Sallar mint account,
+/// - `sallar_blocks_state_account` - the Sallar blocks state account, checked by Sallar,
+/// - `sallar_config_account` - the Sallar config account, checked by Sallar,
+/// - `sallar_user_claim_account` - the Sallar claim account of the vault token account, created by Sallar,
+/// - `sallar_program` - the Sallar program account,
+/// - `owner` - the signer of the transaction which becomes the vault's owner and pays for created accounts,
+/// - `token_program` - the Solana token program account,
+/// - `system_program` - the Solana system program account,
+/// - `rent` - the rent sysvar.
+#[derive(Accounts)]
+pub struct InitializeVaultContext<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = DISCRIMINATOR_LENGTH + Vault::INIT_SPACE,
+        seeds = [VAULT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = owner,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+        seeds = [VAULT_TOKEN_ACCOUNT_SEED.as_bytes(), vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The Sallar blocks state account. It is considered safe because it is checked by Sallar.
+    pub sallar_blocks_state_account: AccountInfo<'info>,
+    /// CHECK: The Sallar config account. It is considered safe because it is checked by Sallar.
+    pub sallar_config_account: AccountInfo<'info>,
+    /// CHECK: The Sallar claim account. It is considered safe because it is created and checked by Sallar.
+    #[account(mut)]
+    pub sallar_user_claim_account: AccountInfo<'info>,
+    pub sallar_program: Program<'info, SallarProgram>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Context for the deposit instruction.
+///
+/// Attributes:
+/// - `vault` - the vault of the owner,
+/// - `vault_token_account` - the vault token account,
+/// - `mint` - the mint of the vault token account,
+/// - `owner_token_account` - the owner's token account to take tokens from,
+/// - `owner` - the signer of the transaction which must be the vault's owner,
+/// - `token_program` - the Solana token program account.
+#[derive(Accounts)]
+pub struct DepositContext<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED.as_bytes(), owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, address = vault.token_account)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = vault_token_account.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the forward_rewards instruction.
+///
+/// Attributes:
+/// - `vault` - the vault to forward rewards from,
+/// - `vault_token_account` - the vault token account,
+/// - `mint` - the mint of the vault token account,
+/// - `owner_token_account` - the token account receiving rewards, it must belong to the vault's owner,
+/// - `token_program` - the Solana token program account.
+#[derive(Accounts)]
+pub struct ForwardRewardsContext<'info> {
+    #[account(
+        seeds = [VAULT_SEED.as_bytes(), vault.owner.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, address = vault.token_account)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = vault_token_account.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == vault.owner @ VaultError::InvalidOwnerTokenAccount,
+        constraint = owner_token_account.mint == vault_token_account.mint @ VaultError::InvalidOwnerTokenAccount,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the withdraw instruction.
+///
+/// Attributes:
+/// - `vault` - the vault of the owner,
+/// - `vault_token_account` - the vault token account,
+/// - `mint` - the mint of the vault token account,
+/// - `owner_token_account` - the owner's token account receiving locked tokens,
+/// - `owner` - the signer of the transaction which must be the vault's owner,
+/// - `token_program` - the Solana token program account.
+#[derive(Accounts)]
+pub struct WithdrawContext<'info> {
+    #[account(
+        mut,
+        seeds = [VAULT_SEED.as_bytes(), owner.key().as_ref()],
+        bump = vault.bump,
+        has_one = owner,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut, address = vault.token_account)]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = vault_token_account.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut)]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub owner: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// The enum defining all errors used by the vault.
+#[error_code]
+pub enum VaultError {
+    #[msg("Vault is still locked")]
+    VaultLocked = 0,
+    #[msg("No rewards to forward")]
+    NoRewardsToForward = 1,
+    #[msg("Token account does not belong to the vault's owner")]
+    InvalidOwnerTokenAccount = 2,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow = 3,
+}
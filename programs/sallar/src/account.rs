@@ -6,8 +6,7 @@ use anchor_lang::{
 /// Struct defining the current blocks state in the program.
 /// Consists of the following attributes:
 /// * `authority` - the authority that initialized the contract, an owner of the contract,
-/// * `block_state_nonce` - the nonce of the block state account,
-/// * `mint_nonce` - the nonce of the mint account,
+/// * `pending_authority` - the authority proposed by the current owner, it becomes the owner once it accepts the ownership,
 ///
 /// * `initial_token_distribution_already_performed` - true if initial_token_distribution function was already invoked and completed successfully, false otherwise,
 /// * `blocks_collided` - true if blocks cannot be switched to the next ones, i.e. the current top block number is less than the current bottom block number by 1,
@@ -16,30 +15,275 @@ use anchor_lang::{
 /// * `top_block_available_bp` - the number of left bp for the current top block number (when bp is decreased to 0, then the current block is solved),
 /// * `top_block_solution_timestamp` - the timestamp of recently solved top block,
 /// * `top_block_balance` - amount of tokens left on the current top block to be distributed as the part of the block solution process,
-/// * `top_block_distribution_address` - the address of the top block distribution account,
-/// * `top_block_distribution_nonce` - the nonce of the top block distribution account,
 /// * `top_block_last_account_address` - address of the last account that participated in top block solving,
-/// * `top_block_last_account_rest_bp` - the number of BP that the last account - that participated in top block solving - did not receive due to too low amount of remaining BP on the block,
+/// * `top_block_last_account_rest_bp` - deprecated, replaced by `top_block_rest_carryovers` and kept populated for one release only - the total number of BP that the accounts participating in top block solving did not receive due to too low amount of remaining BP on the block,
 ///
 /// * `bottom_block_number` - current bottom block number,
 /// * `bottom_block_available_bp` - the number of left bp for the current bottom block number (when bp is decreased to 0, then the current block is solved),
 /// * `bottom_block_solution_timestamp` - the timestamp of recently solved bottom block,
 /// * `bottom_block_balance` - amount of tokens left on the current bottom block to be distributed as the part of the block solution process,
-/// * `bottom_block_distribution_address` - the address of the bottom block distribution account,
-/// * `bottom_block_distribution_nonce` - the nonce of the bottom block distribution account,
 /// * `bottom_block_last_account_address` - address of the last account that participated in bottom block solving,
-/// * `bottom_block_last_account_rest_bp` - the number of BP that the last account - that participated in bottom block solving - did not receive due to too low amount of remaining BP on the block,
+/// * `bottom_block_last_account_rest_bp` - deprecated, replaced by `bottom_block_rest_carryovers` and kept populated for one release only - the total number of BP that the accounts participating in bottom block solving did not receive due to too low amount of remaining BP on the block,
 ///
-/// * `final_staking_account_nonce` - the nonce of the final staking account,
 /// * `final_staking_pool_in_round` - prize pool (amount of tokens) to be distributed in the current final staking round,
 /// * `final_staking_last_staking_timestamp` - the timestamp of the recently completed final staking round,
-/// * `final_staking_left_reward_parts_in_round` - the number of left reward parts for the current final staking round (the number starts at 1.0 and is decreased by reward parts of the input accounts participating in the final staking process) - final staking round is completed when this number is decreased to 0,
+/// * `final_staking_left_reward_parts_in_round` - deprecated, replaced by `final_staking_left_reward_parts_in_round_scaled` and kept populated for one release only - the number of left reward parts for the current final staking round (the number starts at 1.0 and is decreased by reward parts of the input accounts participating in the final staking process) - final staking round is completed when this number is decreased to 0,
 /// * `final_staking_left_balance_in_round` - left amount of tokens to be distributed in the current final staking round,
 ///
-/// * `final_mining_account_nonce` - the nonce of the final mining account.
+/// * `min_payout_amount` - the minimum amount of tokens transferred to the user in a single transfer, smaller rewards are accumulated in the user's claim account (0 disables the threshold),
+/// * `top_block_pending_payouts` - the total amount of top block rewards accumulated in users' claim accounts and not transferred yet,
+/// * `bottom_block_pending_payouts` - the total amount of bottom block rewards accumulated in users' claim accounts and not transferred yet,
+///
+/// * `treasury_address` - the token account receiving surplus tokens found on distribution accounts beyond the tracked block accounting,
+///
+/// * `paused` - true if all token-moving instructions are paused by the contract's owner, false otherwise,
+/// * `admin_controls_renounced` - true if the contract's owner permanently gave up parameter setters, pause powers and signer set changes, false otherwise,
+///
+/// * `timelock_delay` - the delay (in seconds) between queuing an authority or parameter change and its execution (0 disables the timelock and the changes are applied directly),
+///
+/// * `block_solution_interval_seconds` - the minimum time (in seconds) between solutions of consecutive top blocks (and consecutive bottom blocks),
+///
+/// * `final_staking_interval_seconds` - the minimum time (in seconds) between consecutive final staking rounds,
+/// * `final_staking_pool_division_factor` - the final staking account balance is divided by this factor to get the prize pool of a final staking round,
+/// * `final_staking_left_reward_parts_in_round_scaled` - the number of left reward parts for the current final staking round as an integer (1.0 reward part is 1_000_000_000 scaled reward parts),
+///   the number starts at 1_000_000_000 and is decreased by reward parts of the input accounts participating in the final staking process, the last account exhausting it receives the rest of the round's balance,
+///
+/// * `reward_fee_bps` - the fee (in basis points) taken from each top and bottom block reward and transferred to the treasury (0 disables the fee),
+///
+/// * `sla_threshold_seconds` - the maximum accepted delay (in seconds) between the moment a block solution or a final staking round becomes eligible and its actual completion, `SlaBreached` event is emitted when it is exceeded (0 disables the event),
+/// * `top_block_max_solution_delay` - the longest observed delay (in seconds) of a top block solution beyond the block solution interval,
+/// * `bottom_block_max_solution_delay` - the longest observed delay (in seconds) of a bottom block solution beyond the block solution interval,
+/// * `final_staking_max_round_delay` - the longest observed delay (in seconds) of a final staking round beyond the final staking interval,
+///
+/// * `guardian` - the independent security monitor allowed to halt block solving, it cannot move funds nor resume block solving (None if not set),
+/// * `top_block_halted` - true if top block solving is halted by the guardian until the contract's owner resumes it, false otherwise,
+/// * `bottom_block_halted` - true if bottom block solving is halted by the guardian until the contract's owner resumes it, false otherwise,
+///
+/// * `final_mining_interval_seconds` - the minimum time (in seconds) between consecutive final mining calls,
+/// * `final_mining_max_payout_per_call` - the maximum amount of tokens distributed by a single final mining call,
+/// * `final_mining_last_timestamp` - the timestamp of the recently completed final mining call,
+///
+/// * `final_staking_min_pool_in_round` - the minimum prize pool of a regular final staking round, once the pool would be smaller the terminal transition is performed (0 disables the transition),
+/// * `final_staking_terminal_mode` - the way the remaining final staking account balance is handled by the terminal transition,
+/// * `final_staking_final_round` - true if the current final staking round distributes the whole remaining final staking account balance, false otherwise,
+/// * `final_staking_finalized` - true if the terminal transition is completed and final staking cannot be invoked anymore, false otherwise,
+///
+/// * `final_staking_round_counter` - the number of started final staking rounds, i.e. the index of the current (or recently completed) round starting at 1 (0 if no round has started yet),
+///
+/// * `global_stats_initialized` - true if the global statistics account is created, it must be provided to all distribution instructions then, false otherwise,
+///
+/// * `block_history_enabled` - true if every block solution batch must record its solvers in the block history account of the solved block, false otherwise,
+///
+/// * `solve_batch_sequence` - the sequence number of the recently processed block solution batch (solve_top_block and solve_bottom_block share the sequence), 0 if no batch has been processed yet,
+/// * `top_block_solve_batch_cursor` - the progress of the chunked top block solution batch in progress (None if no chunked batch is in progress),
+///
+/// * `top_block_unclaimed_root_rewards` - the total amount of top block rewards reserved for Merkle proof claims (of blocks solved in the claim mode) and not claimed yet,
+///
+/// * `solve_coordinator` - the off-chain coordinator key whose ed25519 signature authorizes block solution batches submitted by any relayer (None if not set),
+///
+/// * `bottom_block_min_required_stake` - the minimum balance (locked stake) of a user required to receive rewards in bottom block solutions,
+///
+/// * `top_block_max_user_requests` - the maximum number of requests (with and without boost) a single user can be rewarded for in a single top block (0 disables the cap),
+/// * `bottom_block_max_user_requests` - the maximum number of requests (with and without boost) a single user can be rewarded for in a single bottom block (0 disables the cap),
+///
+/// * `top_block_rest_carryovers` - the BP the accounts participating in top block solving did not receive due to too low amount of remaining BP on the block, settled in order at the start of the next top block (up to 8),
+/// * `bottom_block_rest_carryovers` - the BP the accounts participating in bottom block solving did not receive due to too low amount of remaining BP on the block, settled in order at the start of the next bottom block (up to 8),
+///
+/// * `block_participation_commitment_enabled` - true if every block solution batch must reveal the user info entries committed with the commit_block_participation instruction in an earlier slot, false otherwise,
+/// * `top_block_participation_commitment` - the commitment of the next top block solution batch (None if no commitment is pending),
+/// * `bottom_block_participation_commitment` - the commitment of the next bottom block solution batch (None if no commitment is pending),
+///
+/// * `boost_credits_required` - true if requests with boost in block solution must be covered by the boost credits of the user (see buy_boost_credits), false otherwise.
+///
+/// * `final_staking_unclaimed_root_rewards` - the total amount of final staking rewards reserved for Merkle proof claims (of rounds opened in the claim mode) and not claimed yet,
+/// * `final_staking_round_start_timestamp` - the timestamp at which the current (or recently completed) final staking round was started,
+///
+/// * `final_staking_snapshots_required` - true if the reward parts of the users in final staking are calculated from the balance snapshots registered for the round (see register_for_final_staking), false if they are given by the caller,
+/// * `final_staking_registered_balance_next_round` - the total weighted balance of the snapshots registered for the next final staking round,
+/// * `final_staking_registered_balance_in_round` - the total weighted balance of the snapshots registered for the current (or recently completed) final staking round,
+///
+/// * `final_staking_carried_remainder` - the balance left in the recently completed final staking round due to rounding down of the rewards, added to the prize pool of the next round.
+///
+/// * `final_staking_reward_part_tolerance_bps` - the tolerance (in basis points) by which the reward part of a user given to final_staking may exceed the user's share of the registered balance of the round (None disables the verification).
+///
+/// * `final_mining_tiers_initialized` - true if the final mining tiers account is created, it must be provided to final_mining then, false otherwise (the default tiers apply).
+///
+/// * `final_mining_budget_per_window` - the maximum amount of tokens distributed by final mining within a single budget window (None disables the budget),
+/// * `final_mining_budget_window_seconds` - the length (in seconds) of the final mining budget window,
+/// * `final_mining_budget_window_start` - the timestamp of the beginning of the current final mining budget window,
+/// * `final_mining_budget_window_distributed` - the amount of tokens distributed by final mining within the current budget window.
+///
+/// * `final_mining_checkpoint_required` - true if the final mining balances of the requests given to final_mining must match a recent final mining balance checkpoint, false otherwise,
+/// * `final_mining_checkpoint_balance` - the final mining account's balance recorded by the recent checkpoint,
+/// * `final_mining_checkpoint_slot` - the slot of the recent final mining balance checkpoint (0 if there is no checkpoint).
+///
+/// * `final_mining_claim_period_seconds` - the length (in seconds) of the period in which a user can receive at most one final mining payout (0 disables the limit).
+///
+/// * `final_mining_epoch_counter` - the index of the current final mining epoch, i.e. the epoch whose participants can claim final mining rewards (0 if no epoch is opened yet),
+/// * `final_mining_epoch_timestamp` - the timestamp at which the current final mining epoch was opened.
+///
+/// * `top_block_rounding_remainder` - the fraction of a dust (in units of `1 / block BP` dust) not paid by the recent rewards of the current top block due to rounding down, carried to the following reward of the block,
+/// * `bottom_block_rounding_remainder` - the fraction of a dust (in units of `1 / block BP` dust) not paid by the recent rewards of the current bottom block due to rounding down, carried to the following reward of the block.
+///
+/// * `top_block_max_bp` - the max BP of the current top block (the denominator of its dust per BP), cached when the top block is switched (0 if not cached yet),
+/// * `top_block_bp_with_boost` - the BP of a single request with boost of the current top block, cached when the top block is switched,
+/// * `bottom_block_max_bp` - the max BP of the current bottom block (the denominator of its dust per BP), cached when the bottom block is switched (0 if not cached yet),
+/// * `bottom_block_boost` - the boost of the requests with boost of the current bottom block, cached when the bottom block is switched.
+///
+/// * `version` - the version of the layout of the blocks state, upgraded by the migrate_state instruction (0 for the states of the deployed layout, see `BlocksStateV0`),
+///   all instructions except migrate_state reject the state of a version other than the current one,
+/// * `recent_solvers_initialized` - true if the recent solvers account is created, so it must be provided to block solution instructions, false otherwise,
+/// * `state_mirror_initialized` - true if the state mirror account is created, so it must be provided to the instructions changing the mirrored fields, false otherwise,
+/// * `first_top_block_number` - the top block number the contract was initialized with (1 on mainnet, larger on deployments with shorter schedules),
+/// * `first_bottom_block_number` - the bottom block number the contract was initialized with (470 000 on mainnet, smaller on deployments with shorter schedules),
+/// * `initial_token_distribution_amount` - the amount of tokens minted to the organization account by the initial token distribution (2 600 000 000 tokens on mainnet),
+/// * `denylist_initialized` - true if the denylist account is created, so it must be provided to the instructions distributing tokens, false otherwise,
+/// * `_reserved` - the space reserved for the fields of the future versions of the layout, zero-filled, so the fields can be added without reallocating the account.
 #[account]
 #[derive(InitSpace)]
 pub struct BlocksState {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+
+    pub initial_token_distribution_already_performed: bool,
+    pub blocks_collided: bool,
+
+    pub top_block_number: u64,
+    pub top_block_available_bp: u64,
+    pub top_block_solution_timestamp: i64,
+    pub top_block_balance: u64,
+    pub top_block_last_account_address: Option<Pubkey>,
+    pub top_block_last_account_rest_bp: u64,
+
+    pub bottom_block_number: u64,
+    pub bottom_block_available_bp: u64,
+    pub bottom_block_solution_timestamp: i64,
+    pub bottom_block_balance: u64,
+    pub bottom_block_last_account_address: Option<Pubkey>,
+    pub bottom_block_last_account_rest_bp: u64,
+
+    pub final_staking_pool_in_round: u64,
+    pub final_staking_last_staking_timestamp: i64,
+    pub final_staking_left_reward_parts_in_round: f64,
+    pub final_staking_left_balance_in_round: u64,
+
+    pub min_payout_amount: u64,
+    pub top_block_pending_payouts: u64,
+    pub bottom_block_pending_payouts: u64,
+
+    pub treasury_address: Option<Pubkey>,
+
+    pub paused: bool,
+    pub admin_controls_renounced: bool,
+
+    pub timelock_delay: i64,
+
+    pub block_solution_interval_seconds: i64,
+
+    pub final_staking_interval_seconds: i64,
+    pub final_staking_pool_division_factor: u64,
+    pub final_staking_left_reward_parts_in_round_scaled: u64,
+
+    pub reward_fee_bps: u16,
+
+    pub sla_threshold_seconds: i64,
+    pub top_block_max_solution_delay: i64,
+    pub bottom_block_max_solution_delay: i64,
+    pub final_staking_max_round_delay: i64,
+
+    pub guardian: Option<Pubkey>,
+    pub top_block_halted: bool,
+    pub bottom_block_halted: bool,
+
+    pub final_mining_interval_seconds: i64,
+    pub final_mining_max_payout_per_call: u64,
+    pub final_mining_last_timestamp: i64,
+
+    pub final_staking_min_pool_in_round: u64,
+    pub final_staking_terminal_mode: FinalStakingTerminalMode,
+    pub final_staking_final_round: bool,
+    pub final_staking_finalized: bool,
+
+    pub final_staking_round_counter: u64,
+
+    pub global_stats_initialized: bool,
+
+    pub block_history_enabled: bool,
+
+    pub solve_batch_sequence: u64,
+    pub top_block_solve_batch_cursor: Option<SolveBatchCursor>,
+
+    pub top_block_unclaimed_root_rewards: u64,
+
+    pub solve_coordinator: Option<Pubkey>,
+
+    pub bottom_block_min_required_stake: u64,
+
+    pub top_block_max_user_requests: u16,
+    pub bottom_block_max_user_requests: u16,
+
+    #[max_len(8)]
+    pub top_block_rest_carryovers: Vec<RestCarryover>,
+    #[max_len(8)]
+    pub bottom_block_rest_carryovers: Vec<RestCarryover>,
+
+    pub block_participation_commitment_enabled: bool,
+    pub top_block_participation_commitment: Option<ParticipationCommitment>,
+    pub bottom_block_participation_commitment: Option<ParticipationCommitment>,
+
+    pub boost_credits_required: bool,
+
+    pub final_staking_unclaimed_root_rewards: u64,
+    pub final_staking_round_start_timestamp: i64,
+
+    pub final_staking_snapshots_required: bool,
+    pub final_staking_registered_balance_next_round: u64,
+    pub final_staking_registered_balance_in_round: u64,
+
+    pub final_staking_carried_remainder: u64,
+
+    pub final_staking_reward_part_tolerance_bps: Option<u32>,
+
+    pub final_mining_tiers_initialized: bool,
+
+    pub final_mining_budget_per_window: Option<u64>,
+    pub final_mining_budget_window_seconds: i64,
+    pub final_mining_budget_window_start: i64,
+    pub final_mining_budget_window_distributed: u64,
+
+    pub final_mining_checkpoint_required: bool,
+    pub final_mining_checkpoint_balance: u64,
+    pub final_mining_checkpoint_slot: u64,
+
+    pub final_mining_claim_period_seconds: i64,
+
+    pub final_mining_epoch_counter: u64,
+    pub final_mining_epoch_timestamp: i64,
+
+    pub top_block_rounding_remainder: u64,
+    pub bottom_block_rounding_remainder: u64,
+
+    pub top_block_max_bp: u64,
+    pub top_block_bp_with_boost: u64,
+    pub bottom_block_max_bp: u64,
+    pub bottom_block_boost: u64,
+
+    pub version: u8,
+    pub recent_solvers_initialized: bool,
+    pub first_top_block_number: u64,
+    pub first_bottom_block_number: u64,
+    pub initial_token_distribution_amount: u64,
+    pub state_mirror_initialized: bool,
+    pub denylist_initialized: bool,
+    pub _reserved: [u8; 101],
+}
+
+/// Struct defining the blocks state of the layout the contract was deployed with (version 0 of the layout), frozen so the deployed states can be migrated.
+/// The fields of the later layouts were inserted both in the middle (e.g. `pending_authority`) and at the end of the blocks state,
+/// so a state of this layout cannot be deserialized as the current `BlocksState`, it is mapped to it field by field by migrate_state.
+/// The attributes have the same meaning as the attributes of `BlocksState` of the same names. The state is stored with the discriminator of `BlocksState`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, InitSpace)]
+pub struct BlocksStateV0 {
     pub authority: Pubkey,
     pub block_state_nonce: u8,
     pub mint_nonce: u8,
@@ -73,3 +317,975 @@ pub struct BlocksState {
 
     pub final_mining_account_nonce: u8,
 }
+
+/// Struct defining the blocks state of versions 1 to 3 of the layout, frozen so the states of these versions can be migrated.
+/// Version 4 dropped the nonces and the addresses duplicated in `Config` from the middle of the layout, so a state of these versions
+/// cannot be deserialized as the current `BlocksState`, it is mapped to it field by field by migrate_state.
+/// The attributes have the same meaning as the attributes of `BlocksState` of the same names. The state is stored with the discriminator of `BlocksState`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug, InitSpace)]
+pub struct BlocksStateV3 {
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub block_state_nonce: u8,
+    pub mint_nonce: u8,
+
+    pub initial_token_distribution_already_performed: bool,
+    pub blocks_collided: bool,
+
+    pub top_block_number: u64,
+    pub top_block_available_bp: u64,
+    pub top_block_solution_timestamp: i64,
+    pub top_block_balance: u64,
+    pub top_block_distribution_address: Pubkey,
+    pub top_block_distribution_nonce: u8,
+    pub top_block_last_account_address: Option<Pubkey>,
+    pub top_block_last_account_rest_bp: u64,
+
+    pub bottom_block_number: u64,
+    pub bottom_block_available_bp: u64,
+    pub bottom_block_solution_timestamp: i64,
+    pub bottom_block_balance: u64,
+    pub bottom_block_distribution_address: Pubkey,
+    pub bottom_block_distribution_nonce: u8,
+    pub bottom_block_last_account_address: Option<Pubkey>,
+    pub bottom_block_last_account_rest_bp: u64,
+
+    pub final_staking_account_nonce: u8,
+    pub final_staking_pool_in_round: u64,
+    pub final_staking_last_staking_timestamp: i64,
+    pub final_staking_left_reward_parts_in_round: f64,
+    pub final_staking_left_balance_in_round: u64,
+
+    pub final_mining_account_nonce: u8,
+
+    pub min_payout_amount: u64,
+    pub top_block_pending_payouts: u64,
+    pub bottom_block_pending_payouts: u64,
+
+    pub treasury_address: Option<Pubkey>,
+
+    pub paused: bool,
+    pub admin_controls_renounced: bool,
+
+    pub timelock_delay: i64,
+
+    pub block_solution_interval_seconds: i64,
+
+    pub final_staking_interval_seconds: i64,
+    pub final_staking_pool_division_factor: u64,
+    pub final_staking_left_reward_parts_in_round_scaled: u64,
+
+    pub reward_fee_bps: u16,
+
+    pub sla_threshold_seconds: i64,
+    pub top_block_max_solution_delay: i64,
+    pub bottom_block_max_solution_delay: i64,
+    pub final_staking_max_round_delay: i64,
+
+    pub guardian: Option<Pubkey>,
+    pub top_block_halted: bool,
+    pub bottom_block_halted: bool,
+
+    pub final_mining_interval_seconds: i64,
+    pub final_mining_max_payout_per_call: u64,
+    pub final_mining_last_timestamp: i64,
+
+    pub final_staking_min_pool_in_round: u64,
+    pub final_staking_terminal_mode: FinalStakingTerminalMode,
+    pub final_staking_final_round: bool,
+    pub final_staking_finalized: bool,
+
+    pub final_staking_round_counter: u64,
+
+    pub global_stats_initialized: bool,
+
+    pub block_history_enabled: bool,
+
+    pub solve_batch_sequence: u64,
+    pub top_block_solve_batch_cursor: Option<SolveBatchCursor>,
+
+    pub top_block_unclaimed_root_rewards: u64,
+
+    pub solve_coordinator: Option<Pubkey>,
+
+    pub bottom_block_min_required_stake: u64,
+
+    pub top_block_max_user_requests: u16,
+    pub bottom_block_max_user_requests: u16,
+
+    #[max_len(8)]
+    pub top_block_rest_carryovers: Vec<RestCarryover>,
+    #[max_len(8)]
+    pub bottom_block_rest_carryovers: Vec<RestCarryover>,
+
+    pub block_participation_commitment_enabled: bool,
+    pub top_block_participation_commitment: Option<ParticipationCommitment>,
+    pub bottom_block_participation_commitment: Option<ParticipationCommitment>,
+
+    pub boost_credits_required: bool,
+
+    pub final_staking_unclaimed_root_rewards: u64,
+    pub final_staking_round_start_timestamp: i64,
+
+    pub final_staking_snapshots_required: bool,
+    pub final_staking_registered_balance_next_round: u64,
+    pub final_staking_registered_balance_in_round: u64,
+
+    pub final_staking_carried_remainder: u64,
+
+    pub final_staking_reward_part_tolerance_bps: Option<u32>,
+
+    pub final_mining_tiers_initialized: bool,
+
+    pub final_mining_budget_per_window: Option<u64>,
+    pub final_mining_budget_window_seconds: i64,
+    pub final_mining_budget_window_start: i64,
+    pub final_mining_budget_window_distributed: u64,
+
+    pub final_mining_checkpoint_required: bool,
+    pub final_mining_checkpoint_balance: u64,
+    pub final_mining_checkpoint_slot: u64,
+
+    pub final_mining_claim_period_seconds: i64,
+
+    pub final_mining_epoch_counter: u64,
+    pub final_mining_epoch_timestamp: i64,
+
+    pub top_block_rounding_remainder: u64,
+    pub bottom_block_rounding_remainder: u64,
+
+    pub top_block_max_bp: u64,
+    pub top_block_bp_with_boost: u64,
+    pub bottom_block_max_bp: u64,
+    pub bottom_block_boost: u64,
+
+    pub version: u8,
+    pub recent_solvers_initialized: bool,
+    pub first_top_block_number: u64,
+    pub first_bottom_block_number: u64,
+    pub initial_token_distribution_amount: u64,
+    pub state_mirror_initialized: bool,
+    pub _reserved: [u8; 102],
+}
+
+/// Struct defining the commitment of a block solution batch posted before the batch is submitted.
+/// Consists of the following attributes:
+/// * `hash` - the Keccak-256 hash of the program id, the block kind, the block number and the serialized user info entries of the batch,
+/// * `slot` - the slot the commitment was posted in, the batch can be revealed only in a later slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ParticipationCommitment {
+    pub hash: [u8; 32],
+    pub slot: u64,
+}
+
+/// Struct defining the rest of a user who did not receive all BP of the request due to too low amount of remaining BP on the block.
+/// Consists of the following attributes:
+/// * `user` - the token account of the user,
+/// * `rest_bp` - the number of BP the user is paid at the start of the next block.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct RestCarryover {
+    pub user: Pubkey,
+    pub rest_bp: u64,
+}
+
+/// Struct defining the progress of a block solution batch submitted in chunks (multiple transactions).
+/// Consists of the following attributes:
+/// * `batch_id` - the identifier of the batch chosen by the backend,
+/// * `next_user_index` - the index (within the whole batch) of the first user info entry expected in the next chunk,
+/// * `total_users` - the number of user info entries of the whole batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct SolveBatchCursor {
+    pub batch_id: u64,
+    pub next_user_index: u32,
+    pub total_users: u32,
+}
+
+/// Struct defining the claim account of a single user (a single token account receiving rewards).
+/// It keeps rewards below the minimum payout threshold until the accumulated amount crosses the threshold,
+/// and the rewards held by the escrow distribution backend until the user withdraws them (see withdraw_pending_rewards).
+/// Consists of the following attributes:
+/// * `user` - the token account the claim account belongs to,
+/// * `bump` - the bump of the claim account,
+/// * `pending_top_block_amount` - the amount of top block rewards accumulated for the user and not transferred yet,
+/// * `pending_bottom_block_amount` - the amount of bottom block rewards accumulated for the user and not transferred yet.
+#[account]
+#[derive(InitSpace)]
+pub struct UserClaimAccount {
+    pub user: Pubkey,
+    pub bump: u8,
+    pub pending_top_block_amount: u64,
+    pub pending_bottom_block_amount: u64,
+}
+
+/// Struct defining the stake locked by a single user (a single token account receiving rewards) to back its bottom block boost.
+/// The locked tokens are held in the user's stake vault until the owner of the token account unlocks them.
+/// Consists of the following attributes:
+/// * `user` - the token account the stake belongs to, the locked tokens are returned to it when unlocked,
+/// * `bump` - the bump of the stake lock account,
+/// * `locked_amount` - the amount of tokens currently locked in the stake vault, it is the user's balance in bottom block solution (see `locked_at_bottom_block`),
+/// * `auto_restake` - true if final staking rewards of the user are deposited to the stake vault (and locked) instead of the user's token account,
+/// * `vault_bump` - the bump of the stake vault of the user, so the address of the stake vault is verified without deriving it again,
+/// * `locked_at_bottom_block` - the number of the bottom block which was current when the user locked tokens most recently,
+///   the locked amount is the user's balance in bottom block solution only since the next bottom block,
+/// * `registered_round_index` - the index of the final staking round the locked amount is registered for most recently (0 if never registered),
+///   the tokens cannot be unlocked until the round is completed.
+#[account]
+#[derive(InitSpace)]
+pub struct StakeLock {
+    pub user: Pubkey,
+    pub bump: u8,
+    pub locked_amount: u64,
+    pub auto_restake: bool,
+    pub vault_bump: u8,
+    pub locked_at_bottom_block: u64,
+    pub registered_round_index: u64,
+}
+
+/// Struct defining the boost credits of a single user (a single token account receiving rewards) purchased with the buy_boost_credits instruction.
+/// While boost credits are required, every request with boost of the user in block solution consumes a single credit.
+/// Consists of the following attributes:
+/// * `user` - the token account the boost credits belong to,
+/// * `bump` - the bump of the boost credits account,
+/// * `credits` - the number of boost credits the user has not consumed yet.
+#[account]
+#[derive(InitSpace)]
+pub struct BoostCredits {
+    pub user: Pubkey,
+    pub bump: u8,
+    pub credits: u64,
+}
+
+/// Struct defining the balance snapshot of a single user (a single token account receiving rewards) registered for a final staking round with the register_for_final_staking instruction.
+/// While final staking snapshots are required, the part of the round's prize pool of the user is calculated from the weighted balance of the snapshot.
+/// Consists of the following attributes:
+/// * `user` - the token account the snapshot belongs to,
+/// * `bump` - the bump of the final staking registration account,
+/// * `round_index` - the index of the final staking round the snapshot is registered for,
+/// * `balance` - the amount of tokens locked in the user's stake vault at the moment of the registration,
+/// * `rewarded` - true if the user has been rewarded in the round, false otherwise,
+/// * `registered_since` - the timestamp since which the balance of the registered snapshots has not decreased, the balance age of the user is counted from it,
+/// * `weighted_balance` - the balance weighted by the multiplier of the user's final staking tier at the moment of the registration.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingRegistration {
+    pub user: Pubkey,
+    pub bump: u8,
+    pub round_index: u64,
+    pub balance: u64,
+    pub rewarded: bool,
+    pub registered_since: i64,
+    pub weighted_balance: u64,
+}
+
+/// Struct defining a single final staking tier.
+/// Consists of the following attributes:
+/// * `min_balance_age_seconds` - the minimum balance age of the users in the tier,
+/// * `multiplier_bps` - the multiplier of the balance of the users in the tier in basis points (10_000 is 1x).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct FinalStakingTier {
+    pub min_balance_age_seconds: i64,
+    pub multiplier_bps: u32,
+}
+
+/// Struct defining the final staking tiers rewarding long-term holders, applied to the balance snapshots registered for final staking.
+/// The users whose balance age is below the lowest tier have the multiplier of 1x.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final staking tiers account,
+/// * `tiers` - the tiers ordered by the minimum balance age (up to 8).
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingTiers {
+    pub bump: u8,
+    #[max_len(8)]
+    pub tiers: Vec<FinalStakingTier>,
+}
+
+/// Struct defining a single final mining tier.
+/// Consists of the following attributes:
+/// * `min_final_mining_balance` - the minimum final mining account's balance the tier applies to,
+/// * `reward` - the reward of a single final mining request in the tier.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct FinalMiningTier {
+    pub min_final_mining_balance: u64,
+    pub reward: u64,
+}
+
+/// Struct defining the final mining tiers, i.e. the reward of a single final mining request depending on the final mining account's balance.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final mining tiers account,
+/// * `tiers` - the tiers ordered by the minimum final mining balance (up to 8), the first one starting at zero balance.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalMiningTiers {
+    pub bump: u8,
+    #[max_len(8)]
+    pub tiers: Vec<FinalMiningTier>,
+}
+
+/// Struct defining the lifetime reward statistics of a single wallet, updated by all distribution instructions.
+/// Rewards are attributed to the owner of the token account receiving them, so all token accounts of the wallet are counted together.
+/// Consists of the following attributes:
+/// * `wallet` - the wallet the statistics belong to,
+/// * `bump` - the bump of the user reward stats account,
+/// * `top_block_reward_amount` - the amount of top block rewards received by the wallet (excluding reward fees, including rewards accumulated in claim accounts),
+/// * `bottom_block_reward_amount` - the amount of bottom block rewards received by the wallet (excluding reward fees, including rewards accumulated in claim accounts),
+/// * `final_mining_reward_amount` - the amount of final mining rewards received by the wallet,
+/// * `final_staking_reward_amount` - the amount of final staking rewards received by the wallet.
+///
+/// Only rewards distributed while the account exists and is provided to the distribution instruction are counted.
+#[account]
+#[derive(InitSpace)]
+pub struct UserRewardStats {
+    pub wallet: Pubkey,
+    pub bump: u8,
+    pub top_block_reward_amount: u64,
+    pub bottom_block_reward_amount: u64,
+    pub final_mining_reward_amount: u64,
+    pub final_staking_reward_amount: u64,
+}
+
+/// Struct defining the configuration of the contract, i.e. the data written by the initialize instruction.
+/// Only the distribution backends can be changed afterwards (see set_distribution_backend), the addresses and nonces are never changed.
+/// It is separated from the blocks state, so the accounts of the contract are verified without reading the frequently written state.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the config account,
+/// * `block_state_address` - the address of the block state account,
+/// * `block_state_nonce` - the nonce of the block state account,
+/// * `mint_address` - the address of the mint account,
+/// * `mint_nonce` - the nonce of the mint account,
+/// * `top_block_distribution_address` - the address of the top block distribution account,
+/// * `top_block_distribution_nonce` - the nonce of the top block distribution account,
+/// * `bottom_block_distribution_address` - the address of the bottom block distribution account,
+/// * `bottom_block_distribution_nonce` - the nonce of the bottom block distribution account,
+/// * `final_staking_account_address` - the address of the final staking account,
+/// * `final_staking_account_nonce` - the nonce of the final staking account,
+/// * `final_mining_account_address` - the address of the final mining account,
+/// * `final_mining_account_nonce` - the nonce of the final mining account,
+/// * `top_block_distribution_backend` - the distribution backend of top block rewards,
+/// * `bottom_block_distribution_backend` - the distribution backend of bottom block rewards,
+/// * `final_mining_distribution_backend` - the distribution backend of final mining rewards,
+/// * `final_staking_distribution_backend` - the distribution backend of final staking rewards.
+///
+/// The addresses of all accounts the program signs for are stored, so tokens are never paid to them (or to token accounts
+/// they are the authority of) without deriving the addresses again.
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub bump: u8,
+    pub block_state_address: Pubkey,
+    pub block_state_nonce: u8,
+    pub mint_address: Pubkey,
+    pub mint_nonce: u8,
+    pub top_block_distribution_address: Pubkey,
+    pub top_block_distribution_nonce: u8,
+    pub bottom_block_distribution_address: Pubkey,
+    pub bottom_block_distribution_nonce: u8,
+    pub final_staking_account_address: Pubkey,
+    pub final_staking_account_nonce: u8,
+    pub final_mining_account_address: Pubkey,
+    pub final_mining_account_nonce: u8,
+    pub top_block_distribution_backend: DistributionBackend,
+    pub bottom_block_distribution_backend: DistributionBackend,
+    pub final_mining_distribution_backend: DistributionBackend,
+    pub final_staking_distribution_backend: DistributionBackend,
+}
+
+/// Struct defining the global statistics of the token distribution, updated by all distribution instructions.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the global statistics account,
+/// * `total_minted_amount` - the amount of tokens minted by the contract so far (block distributions and the initial token distribution),
+/// * `top_block_distributed_amount` - the amount of tokens released from top blocks as rewards (including reward fees and rewards accumulated in users' claim accounts),
+/// * `bottom_block_distributed_amount` - the amount of tokens released from bottom blocks as rewards (including reward fees and rewards accumulated in users' claim accounts),
+/// * `final_mining_distributed_amount` - the amount of tokens distributed by final mining,
+/// * `final_staking_distributed_amount` - the amount of tokens distributed by final staking,
+/// * `top_blocks_solved` - the number of solved top blocks,
+/// * `bottom_blocks_solved` - the number of solved bottom blocks,
+/// * `processed_batches` - the number of processed distribution batches, i.e. solve_top_block, solve_bottom_block, final_mining and final_staking calls.
+///
+/// Amounts and numbers of solved blocks are seeded from the blocks state when the account is created,
+/// final mining and final staking amounts and the number of batches are counted from the account creation.
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalStats {
+    pub bump: u8,
+    pub total_minted_amount: u64,
+    pub top_block_distributed_amount: u64,
+    pub bottom_block_distributed_amount: u64,
+    pub final_mining_distributed_amount: u64,
+    pub final_staking_distributed_amount: u64,
+    pub top_blocks_solved: u64,
+    pub bottom_blocks_solved: u64,
+    pub processed_batches: u64,
+}
+
+/// Struct defining the compact mirror of the blocks state, i.e. the current blocks and the final staking round only,
+/// so light clients (e.g. mobile wallets) fetch and decode a single small account instead of the full state.
+/// It is updated at the end of every instruction changing the mirrored fields.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the state mirror account,
+/// * `top_block_number` - current top block number,
+/// * `top_block_available_bp` - the number of left bp for the current top block,
+/// * `top_block_balance` - amount of tokens left on the current top block,
+/// * `bottom_block_number` - current bottom block number,
+/// * `bottom_block_available_bp` - the number of left bp for the current bottom block,
+/// * `bottom_block_balance` - amount of tokens left on the current bottom block,
+/// * `blocks_collided` - true if blocks cannot be switched to the next ones, false otherwise,
+/// * `final_staking_round_counter` - the number of the current (or the recently completed) final staking round,
+/// * `final_staking_pool_in_round` - prize pool to be distributed in the current final staking round,
+/// * `final_staking_left_balance_in_round` - left amount of tokens to be distributed in the current final staking round,
+/// * `final_staking_last_staking_timestamp` - the timestamp of the recently completed final staking round,
+/// * `final_staking_finalized` - true if final staking is completed, false otherwise.
+#[account]
+#[derive(InitSpace)]
+pub struct StateMirror {
+    pub bump: u8,
+    pub top_block_number: u64,
+    pub top_block_available_bp: u64,
+    pub top_block_balance: u64,
+    pub bottom_block_number: u64,
+    pub bottom_block_available_bp: u64,
+    pub bottom_block_balance: u64,
+    pub blocks_collided: bool,
+    pub final_staking_round_counter: u64,
+    pub final_staking_pool_in_round: u64,
+    pub final_staking_left_balance_in_round: u64,
+    pub final_staking_last_staking_timestamp: i64,
+    pub final_staking_finalized: bool,
+}
+
+/// Struct defining the recent solvers of top and bottom blocks, i.e. the ring buffers of the last token accounts rewarded by block solutions,
+/// so explorers can show recent miners without an indexer.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the recent solvers account,
+/// * `top_block_solvers` - the last token accounts rewarded by top block solutions, once the buffer is full the oldest one is overwritten,
+/// * `top_block_next_index` - the index of `top_block_solvers` the next rewarded token account is written to, i.e. the index of the oldest one once the buffer is full,
+/// * `bottom_block_solvers` - the last token accounts rewarded by bottom block solutions, once the buffer is full the oldest one is overwritten,
+/// * `bottom_block_next_index` - the index of `bottom_block_solvers` the next rewarded token account is written to, i.e. the index of the oldest one once the buffer is full.
+#[account]
+#[derive(InitSpace)]
+pub struct RecentSolvers {
+    pub bump: u8,
+    #[max_len(32)]
+    pub top_block_solvers: Vec<Pubkey>,
+    pub top_block_next_index: u8,
+    #[max_len(32)]
+    pub bottom_block_solvers: Vec<Pubkey>,
+    pub bottom_block_next_index: u8,
+}
+
+/// Struct defining the history of a single block, i.e. the solvers of the block and the amounts distributed to them.
+/// The account is created by the first block solution batch of the block and extended by the following ones.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the block history account,
+/// * `block_kind` - the kind of the block (top or bottom),
+/// * `block_number` - the number of the block,
+/// * `solvers` - the solvers of the block in the order of processing.
+#[account]
+#[derive(InitSpace)]
+pub struct BlockHistory {
+    pub bump: u8,
+    pub block_kind: BlockKind,
+    pub block_number: u64,
+    #[max_len(0)]
+    pub solvers: Vec<BlockSolver>,
+}
+
+/// Struct defining the snapshot of a single final staking round, so partially completed rounds can be told apart from new ones off-chain.
+/// The account is created by the first final_staking call it is provided to in the round and updated by the following ones.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final staking round account,
+/// * `round_index` - the index of the round (rounds are numbered from 1 by the round counter stored in the state),
+/// * `pool_in_round` - the prize pool (amount of tokens) distributed in the round,
+/// * `final_round` - true if the round distributes the whole remaining final staking account balance, false otherwise,
+/// * `start_timestamp` - the timestamp at which the round was started (0 if the account was not provided to the call starting the round),
+/// * `end_timestamp` - the timestamp at which the round was completed (0 while the round is in progress),
+/// * `participants_processed` - the number of user info entries rewarded in the round by the calls the account was provided to,
+/// * `distributed_amount` - the amount of tokens distributed in the round by the calls the account was provided to.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingRound {
+    pub bump: u8,
+    pub round_index: u64,
+    pub pool_in_round: u64,
+    pub final_round: bool,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub participants_processed: u64,
+    pub distributed_amount: u64,
+}
+
+/// Struct defining the users paid by final mining in a single claim period, used to prevent the same user from receiving more than one final mining payout in the period.
+/// The account is created by the first final_mining call of the period and extended by the following ones.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final mining claim period participants account,
+/// * `period_index` - the index of the claim period,
+/// * `users` - the token accounts paid in the claim period.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalMiningClaimPeriodParticipants {
+    pub bump: u8,
+    pub period_index: u64,
+    #[max_len(0)]
+    pub users: Vec<Pubkey>,
+}
+
+/// Struct defining a single final mining epoch, in which every participant can claim one final mining reward with the claim_final_mining instruction.
+/// An epoch is opened either by the coordinator publishing the participation Merkle root of the epoch,
+/// or by anyone without the participation root once the coordinator has been inactive for the coordinator timeout.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final mining epoch account,
+/// * `epoch_index` - the index of the final mining epoch,
+/// * `merkle_root` - the Merkle root of the token accounts participating in the epoch (all zeros if the epoch is open),
+/// * `open` - true if the epoch is opened without the participation root, so every token account of the mint can claim the reward, false otherwise,
+/// * `timestamp` - the timestamp at which the epoch was opened,
+/// * `claimed_amount` - the amount of tokens claimed in the epoch so far.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalMiningEpoch {
+    pub bump: u8,
+    pub epoch_index: u64,
+    pub merkle_root: [u8; 32],
+    pub open: bool,
+    pub timestamp: i64,
+    pub claimed_amount: u64,
+}
+
+/// Struct defining the receipt of a claimed final mining reward, its existence prevents the reward from being claimed twice in the epoch.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final mining claim account,
+/// * `epoch_index` - the index of the final mining epoch the reward comes from,
+/// * `user` - the token account which received the reward,
+/// * `amount` - the claimed amount.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalMiningClaim {
+    pub bump: u8,
+    pub epoch_index: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Struct defining the users rewarded in a single final staking round, used to prevent the same user from being rewarded twice in the round across final_staking calls.
+/// The account is created by the first final_staking call of the round and extended by the following ones.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final staking round participants account,
+/// * `round_index` - the index of the round,
+/// * `users` - the token accounts rewarded in the round.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingRoundParticipants {
+    pub bump: u8,
+    pub round_index: u64,
+    #[max_len(0)]
+    pub users: Vec<Pubkey>,
+}
+
+/// Struct defining a single solver recorded in the block history.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `amount` - the amount of tokens released from the block as the reward (including the reward fee and the reward accumulated in the user's claim account).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct BlockSolver {
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Struct defining the numbers of requests the users have been rewarded for in a single block, used to enforce the per-user requests cap of the block.
+/// The account is created by the first block solution batch of the block and extended by the following ones.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the block user requests account,
+/// * `block_kind` - the kind of the block (top or bottom),
+/// * `block_number` - the number of the block,
+/// * `users` - the users rewarded in the block together with their numbers of requests.
+#[account]
+#[derive(InitSpace)]
+pub struct BlockUserRequests {
+    pub bump: u8,
+    pub block_kind: BlockKind,
+    pub block_number: u64,
+    #[max_len(0)]
+    pub users: Vec<UserRequests>,
+}
+
+/// Struct defining the number of requests a single user has been rewarded for in a block.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the rewards,
+/// * `requests` - the total number of requests (with and without boost) of the user in the block.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub struct UserRequests {
+    pub user: Pubkey,
+    pub requests: u16,
+}
+
+/// Struct defining the Merkle root of the rewards of a single top block solved in the claim mode.
+/// The rewards are not transferred by the block solution, the users claim them from the top block distribution account with Merkle proofs.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the block rewards root account,
+/// * `block_number` - the number of the top block,
+/// * `merkle_root` - the Merkle root of the (user, amount) pairs of the block,
+/// * `total_amount` - the amount of tokens reserved for the claims, i.e. the balance of the block at the moment it was solved,
+/// * `claimed_amount` - the amount of tokens claimed so far.
+#[account]
+#[derive(InitSpace)]
+pub struct BlockRewardsRoot {
+    pub bump: u8,
+    pub block_number: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+}
+
+/// Struct defining the receipt of a claimed top block reward, its existence prevents the reward from being claimed twice.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the block reward claim account,
+/// * `block_number` - the number of the top block the reward comes from,
+/// * `user` - the token account which received the reward,
+/// * `amount` - the claimed amount (including the reward fee).
+#[account]
+#[derive(InitSpace)]
+pub struct BlockRewardClaim {
+    pub bump: u8,
+    pub block_number: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Struct defining the Merkle root of the rewards of a single final staking round opened in the claim mode.
+/// The rewards are not transferred when the round is opened, the users claim them from the final staking account with Merkle proofs.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final staking rewards root account,
+/// * `round_index` - the index of the final staking round,
+/// * `merkle_root` - the Merkle root of the (user, amount) pairs of the round,
+/// * `total_amount` - the amount of tokens reserved for the claims, i.e. the prize pool of the round,
+/// * `claimed_amount` - the amount of tokens claimed so far.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingRewardsRoot {
+    pub bump: u8,
+    pub round_index: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+}
+
+/// Struct defining the receipt of a claimed final staking reward, its existence prevents the reward from being claimed twice.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the final staking reward claim account,
+/// * `round_index` - the index of the final staking round the reward comes from,
+/// * `user` - the token account which received the reward,
+/// * `amount` - the claimed amount.
+#[account]
+#[derive(InitSpace)]
+pub struct FinalStakingRewardClaim {
+    pub bump: u8,
+    pub round_index: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Struct defining the result of a block solution batch, returned by the solve_top_block and solve_bottom_block instructions.
+/// Consists of the following attributes:
+/// * `block_number` - the number of the current block (of the solved kind) after processing the batch,
+/// * `solve_batch_sequence` - the sequence number assigned to the batch,
+/// * `skipped_user_entries` - the bitmask of the user info entries skipped in the non-strict mode (the i-th entry is skipped if the bit `i % 8` of the byte `i / 8` is set), empty if no entry is skipped.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SolveBlockResult {
+    pub block_number: u64,
+    pub solve_batch_sequence: u64,
+    pub skipped_user_entries: Vec<u8>,
+}
+
+/// Struct defining the block metrics derived from the blocks state, returned by the get_block_state_summary instruction.
+/// Consists of the following attributes:
+/// * `top_block_number` - current top block number,
+/// * `top_block_dust_per_bp` - the amount of dust per BP of the current top block,
+/// * `top_bp_with_boost` - the boosted BP of a single top block solution (of the current top block),
+///
+/// * `bottom_block_number` - current bottom block number,
+/// * `bottom_block_dust_per_bp` - the amount of dust per BP of the current bottom block,
+/// * `bottom_block_max_boost` - the boost applied to bottom block solutions (of the current bottom block),
+///
+/// * `remaining_blocks_before_collision` - the number of blocks which can still be switched to before the blocks collide,
+/// * `estimated_remaining_supply` - the amount of tokens still to be distributed through blocks, i.e. balances of the current blocks and the blocks not minted yet.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct BlockStateSummary {
+    pub top_block_number: u64,
+    pub top_block_dust_per_bp: f64,
+    pub top_bp_with_boost: u64,
+
+    pub bottom_block_number: u64,
+    pub bottom_block_dust_per_bp: f64,
+    pub bottom_block_max_boost: u64,
+
+    pub remaining_blocks_before_collision: u64,
+    pub estimated_remaining_supply: u64,
+}
+
+/// Struct defining the accounting invariants of a single block, part of the invariants report returned by the verify_invariants instruction.
+/// Consists of the following attributes:
+/// * `block_number` - the number of the block,
+/// * `block_balance` - the balance of the block tracked by the blocks state,
+/// * `pending_payouts` - the total amount of the block rewards accumulated in users' claim accounts and not transferred yet,
+/// * `distribution_balance` - the balance of the block's distribution account,
+/// * `available_bp` - the available BP of the block,
+/// * `available_bp_amount` - the amount of tokens the available BP are worth at the block's dust per BP,
+///
+/// * `distribution_balance_consistent` - true if the distribution account's balance equals the block balance and the pending payouts,
+/// * `available_bp_consistent` - true if the block balance differs from the amount the available BP are worth by at most the amount a single BP is worth,
+///   i.e. by no more than the rounding of the rewards paid from the block.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct BlockInvariants {
+    pub block_number: u64,
+    pub block_balance: u64,
+    pub pending_payouts: u64,
+    pub distribution_balance: u64,
+    pub available_bp: u64,
+    pub available_bp_amount: u64,
+
+    pub distribution_balance_consistent: bool,
+    pub available_bp_consistent: bool,
+}
+
+/// Struct defining the report of the core accounting invariants, returned by the verify_invariants instruction.
+/// Consists of the following attributes:
+/// * `top_block` - the invariants of the current top block,
+/// * `bottom_block` - the invariants of the current bottom block,
+///
+/// * `expected_minted_amount` - the amount of tokens minted for the blocks processed so far and by the initial token distribution,
+/// * `total_supply` - the total supply of the token (lower than the minted amount by the burned tokens),
+/// * `global_stats_minted_amount` - the minted amount tracked by the global statistics, if the global statistics account is created,
+/// * `minted_supply_consistent` - true if the total supply does not exceed the expected minted amount
+///   and the global statistics (if created) track exactly the expected minted amount,
+///
+/// * `consistent` - true if all the invariants hold.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct InvariantsReport {
+    pub top_block: BlockInvariants,
+    pub bottom_block: BlockInvariants,
+
+    pub expected_minted_amount: u64,
+    pub total_supply: u64,
+    pub global_stats_minted_amount: Option<u64>,
+    pub minted_supply_consistent: bool,
+
+    pub consistent: bool,
+}
+
+/// Struct defining the projected final mining emission, returned by the get_final_mining_emission_projection instruction.
+/// Consists of the following attributes:
+/// * `window_seconds` - the length (in seconds) of a single projected window, i.e. the final mining budget window,
+/// * `windows` - the projected windows in order.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub struct FinalMiningEmissionProjection {
+    pub window_seconds: i64,
+    pub windows: Vec<ProjectedFinalMiningWindow>,
+}
+
+/// Struct defining the projected final mining emission of a single window.
+/// Consists of the following attributes:
+/// * `starting_balance` - the final mining account's balance at the beginning of the window,
+/// * `reward_per_request` - the reward of a single final mining request at the beginning of the window,
+/// * `emission` - the amount of tokens distributed by final mining within the window.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProjectedFinalMiningWindow {
+    pub starting_balance: u64,
+    pub reward_per_request: u64,
+    pub emission: u64,
+}
+
+/// Struct defining the simulated result of a block solution batch, returned by the simulate_solve_top_block and simulate_solve_bottom_block instructions.
+/// Consists of the following attributes:
+/// * `block_number` - the number of the simulated block,
+/// * `users` - the simulated rewards of the users in the order of settlement (the rests of the unpaid users of the previous block first),
+/// * `remaining_available_bp` - the available BP of the block after the batch,
+/// * `remaining_balance` - the balance of the block after the batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct BlockSolutionSimulation {
+    pub block_number: u64,
+    pub users: Vec<SimulatedUserReward>,
+    pub remaining_available_bp: u64,
+    pub remaining_balance: u64,
+}
+
+/// Struct defining the simulated reward of a single user of a block solution batch.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `bp` - the number of BPs the user would receive from the block,
+/// * `amount` - the amount of tokens which would be released from the block as the reward (including the reward fee and the reward accumulated in the user's claim account),
+/// * `rest_bp` - the number of BPs the user would not receive due to too low amount of remaining BP on the block.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SimulatedUserReward {
+    pub user: Pubkey,
+    pub bp: u64,
+    pub amount: u64,
+    pub rest_bp: u64,
+}
+
+/// Struct defining the set of signers allowed to approve privileged instructions instead of the contract's owner.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the signer set account,
+/// * `threshold` - the number of registered signers required to approve the instruction (N of M),
+/// * `signers` - the registered signers (up to 10).
+#[account]
+#[derive(InitSpace)]
+pub struct SignerSet {
+    pub bump: u8,
+    pub threshold: u8,
+    #[max_len(10)]
+    pub signers: Vec<Pubkey>,
+}
+
+/// Struct defining the token accounts which must not receive rewards.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the denylist account,
+/// * `token_accounts` - the denylisted token accounts (up to 50).
+#[account]
+#[derive(InitSpace)]
+pub struct Denylist {
+    pub bump: u8,
+    #[max_len(50)]
+    pub token_accounts: Vec<Pubkey>,
+}
+
+/// Enum defining an authority or parameter change which can be queued in the timelock.
+/// Each variant corresponds to the instruction applying the same change directly while the timelock is disabled.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, InitSpace)]
+pub enum ConfigChange {
+    ChangeAuthority {
+        new_authority: Pubkey,
+    },
+    ProposeAuthority {
+        new_authority: Pubkey,
+    },
+    UpdateSignerSet {
+        #[max_len(10)]
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    SetMinPayoutAmount {
+        min_payout_amount: u64,
+    },
+    SetTreasury {
+        treasury_address: Pubkey,
+    },
+    SetTimelockDelay {
+        timelock_delay: i64,
+    },
+    SetBlockSolutionInterval {
+        block_solution_interval_seconds: i64,
+    },
+    UpdateFinalStakingConfig {
+        final_staking_interval_seconds: i64,
+        final_staking_pool_division_factor: u64,
+    },
+    SetRewardFee {
+        reward_fee_bps: u16,
+    },
+    SetGuardian {
+        guardian: Option<Pubkey>,
+    },
+    UpdateFinalMiningConfig {
+        final_mining_interval_seconds: i64,
+        final_mining_max_payout_per_call: u64,
+    },
+    SetFinalStakingTerminalConfig {
+        final_staking_min_pool_in_round: u64,
+        final_staking_terminal_mode: FinalStakingTerminalMode,
+    },
+    SetBlockHistoryEnabled {
+        block_history_enabled: bool,
+    },
+    SetSolveCoordinator {
+        solve_coordinator: Option<Pubkey>,
+    },
+    SetBottomBlockMinRequiredStake {
+        bottom_block_min_required_stake: u64,
+    },
+    SetMaxUserRequestsPerBlock {
+        top_block_max_user_requests: u16,
+        bottom_block_max_user_requests: u16,
+    },
+    SetBlockParticipationCommitmentEnabled {
+        block_participation_commitment_enabled: bool,
+    },
+    SetBoostCreditsRequired {
+        boost_credits_required: bool,
+    },
+    SetFinalStakingSnapshotsRequired {
+        final_staking_snapshots_required: bool,
+    },
+    SetFinalStakingRewardPartTolerance {
+        final_staking_reward_part_tolerance_bps: Option<u32>,
+    },
+    SetFinalMiningTiers {
+        #[max_len(8)]
+        tiers: Vec<FinalMiningTier>,
+    },
+    SetFinalMiningBudget {
+        final_mining_budget_per_window: Option<u64>,
+        final_mining_budget_window_seconds: i64,
+    },
+    SetFinalMiningCheckpointRequired {
+        final_mining_checkpoint_required: bool,
+    },
+    SetFinalMiningClaimPeriod {
+        final_mining_claim_period_seconds: i64,
+    },
+    SetDistributionBackend {
+        subsystem: Subsystem,
+        distribution_backend: DistributionBackend,
+    },
+}
+
+/// Enum defining the kinds of blocks, i.e. top blocks and bottom blocks.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum BlockKind {
+    Top,
+    Bottom,
+}
+
+/// Enum defining the subsystems distributing tokens to users.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum Subsystem {
+    TopBlock,
+    BottomBlock,
+    FinalMining,
+    FinalStaking,
+}
+
+/// Enum defining the ways tokens are moved from the distribution account of a subsystem to users:
+/// * `DirectTransfer` - the reward is transferred to the user's token account right away,
+/// * `ClaimCredit` - the reward is credited to the user's claim account (if provided) while it is below the minimum payout amount,
+///   and the accumulated rewards are transferred once they reach it,
+/// * `Escrow` - the whole reward is held in the user's claim account (it must be provided) until the user withdraws it with withdraw_pending_rewards.
+///
+/// Claim accounts track the rewards of top and bottom blocks only, so final mining and final staking rewards are always transferred directly.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum DistributionBackend {
+    DirectTransfer,
+    ClaimCredit,
+    Escrow,
+}
+
+/// Enum defining the ways the remaining final staking account balance is handled once it can no longer fund a meaningful round:
+/// * `DistributeRemaining` - the whole remaining balance is distributed pro-rata in one final round,
+/// * `SweepToTreasury` - the whole remaining balance is transferred to the treasury.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum FinalStakingTerminalMode {
+    DistributeRemaining,
+    SweepToTreasury,
+}
+
+/// Struct defining the change queued in the timelock.
+/// Only a single change can be pending at a time.
+/// Consists of the following attributes:
+/// * `bump` - the bump of the pending change account,
+/// * `change` - the queued change,
+/// * `execute_after` - the timestamp after which the change can be executed.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingChange {
+    pub bump: u8,
+    pub change: ConfigChange,
+    pub execute_after: i64,
+}
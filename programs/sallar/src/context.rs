@@ -1,23 +1,49 @@
 use anchor_lang::{
     error,
     prelude::{
-        account, borsh, require_keys_neq, Account, AccountInfo, Accounts, AnchorDeserialize, Key,
-        Program, Pubkey, Rent, Signer, SolanaSysvar, System, ToAccountInfo,
+        borsh, require_keys_neq, Account, AccountInfo, AccountMeta, Accounts, AnchorDeserialize,
+        Interface, InterfaceAccount, Key, Program, Pubkey, Rent, Signer, SolanaSysvar, System,
+        ToAccountInfo,
     },
-    solana_program::system_program,
+    solana_program::{system_program, sysvar},
     Id, Space,
 };
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
 use mpl_token_metadata;
 
 use crate::{
-    account::BlocksState, BLOCKS_STATE_SEED, DISTRIBUTION_BOTTOM_BLOCK_SEED,
-    DISTRIBUTION_TOP_BLOCK_SEED, FINAL_MINING_ACCOUNT_SEED, FINAL_STAKING_ACCOUNT_SEED, MINT_SEED,
+    account::{
+        BlockHistory, BlockKind, BlockRewardClaim, BlockRewardsRoot, BlockUserRequests,
+        BlocksState, BoostCredits, Config, Denylist, FinalMiningClaim,
+        FinalMiningClaimPeriodParticipants, FinalMiningEpoch, FinalMiningTiers,
+        FinalStakingRegistration, FinalStakingRewardClaim, FinalStakingRewardsRoot,
+        FinalStakingRound, FinalStakingRoundParticipants, FinalStakingTiers, GlobalStats,
+        PendingChange, RecentSolvers, SignerSet, StakeLock, StateMirror, UserClaimAccount,
+        UserRewardStats,
+    },
+    error::SallarError,
+    utils::{
+        final_mining_claim_period_index, final_staking_round_index, program_owned_token_account,
+        BLOCKS_STATE_VERSION,
+    },
+    BLOCKS_STATE_SEED, BLOCK_HISTORY_SEED, BLOCK_REWARDS_ROOT_SEED, BLOCK_REWARD_CLAIM_SEED,
+    BLOCK_USER_REQUESTS_SEED, BOOST_CREDITS_SEED, CONFIG_SEED, DENYLIST_SEED,
+    DISTRIBUTION_BOTTOM_BLOCK_SEED, DISTRIBUTION_TOP_BLOCK_SEED, FINAL_MINING_ACCOUNT_SEED,
+    FINAL_MINING_CLAIM_PERIOD_PARTICIPANTS_SEED, FINAL_MINING_CLAIM_SEED, FINAL_MINING_EPOCH_SEED,
+    FINAL_MINING_TIERS_SEED, FINAL_STAKING_ACCOUNT_SEED, FINAL_STAKING_REGISTRATION_SEED,
+    FINAL_STAKING_REWARDS_ROOT_SEED, FINAL_STAKING_REWARD_CLAIM_SEED,
+    FINAL_STAKING_ROUND_PARTICIPANTS_SEED, FINAL_STAKING_ROUND_SEED, FINAL_STAKING_TIERS_SEED,
+    GLOBAL_STATS_SEED, MINT_SEED, PENDING_CHANGE_SEED, RECENT_SOLVERS_SEED, SIGNER_SET_SEED,
+    STAKE_LOCK_SEED, STAKE_VAULT_SEED, STATE_MIRROR_SEED, USER_CLAIM_ACCOUNT_SEED,
+    USER_REWARD_STATS_SEED,
 };
 
 /// The discriminator is defined by the first 8 bytes of the SHA256 hash of the account's Rust identifier.
 /// It includes the name of struct type and lets Anchor know what type of account it should deserialize the data as.
-const DISCRIMINATOR_LENGTH: usize = 8;
+pub(crate) const DISCRIMINATOR_LENGTH: usize = 8;
 
 /// Context for the initialize instruction.
 ///
@@ -26,6 +52,7 @@ const DISCRIMINATOR_LENGTH: usize = 8;
 /// The contract state is initialized with the following accounts:
 ///
 /// - `blocks_state_account` - the account that contains the contract state,
+/// - `config_account` - the account that contains the nonces and addresses of the contract's accounts, its addresses and nonces are never changed after the initialization,
 /// - `mint` - the mint account,
 /// - `distribution_top_block_account` - the top block distribution account,
 /// - `distribution_bottom_block_account` - the bottom block distribution account,
@@ -41,6 +68,8 @@ const DISCRIMINATOR_LENGTH: usize = 8;
 pub struct InitializeContext<'info> {
     #[account(init, payer = signer, space = DISCRIMINATOR_LENGTH + BlocksState::INIT_SPACE, seeds = [BLOCKS_STATE_SEED.as_bytes()], bump)]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(init, payer = signer, space = DISCRIMINATOR_LENGTH + Config::INIT_SPACE, seeds = [CONFIG_SEED.as_bytes()], bump)]
+    pub config_account: Box<Account<'info, Config>>,
 
     /// Decimals are set to 8 because it is the highest possible precision,
     /// considering the desired total supply of Sallar which is 54_600_000_000.
@@ -56,59 +85,64 @@ pub struct InitializeContext<'info> {
         seeds = [MINT_SEED.as_bytes()],
         bump,
         mint::decimals = 8,
-        mint::authority = mint
+        mint::authority = mint,
+        mint::token_program = token_program,
     )]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init,
         payer = signer,
         token::mint = mint,
         token::authority = distribution_top_block_account,
+        token::token_program = token_program,
         seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
         bump,
     )]
-    pub distribution_top_block_account: Box<Account<'info, TokenAccount>>,
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = signer,
         token::mint = mint,
         token::authority = distribution_bottom_block_account,
+        token::token_program = token_program,
         seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
         bump,
     )]
-    pub distribution_bottom_block_account: Box<Account<'info, TokenAccount>>,
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = signer,
         token::mint = mint,
         token::authority = final_staking_account,
+        token::token_program = token_program,
         seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
         bump,
     )]
-    pub final_staking_account: Box<Account<'info, TokenAccount>>,
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         init,
         payer = signer,
         token::mint = mint,
         token::authority = final_mining_account,
+        token::token_program = token_program,
         seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
         bump,
     )]
-    pub final_mining_account: Box<Account<'info, TokenAccount>>,
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    /// CHECK: The metadata program account. It is considered safe because it is checked by the inner instruction, ensuring it is the correct account.
-    #[account(mut, address = Pubkey::find_program_address(&[b"metadata", &mpl_token_metadata::id().to_bytes(), &mint.key().to_bytes()], &mpl_token_metadata::id()).0)]
+    /// CHECK: The metadata account. It is considered safe because the inner instruction of the Metaplex token metadata program verifies that it is derived from the mint.
+    #[account(mut)]
     pub metadata_pda: AccountInfo<'info>,
 
     /// CHECK: The metadata program account. It is considered safe because it is checked by the inner instruction, ensuring it is the correct account.
-    #[account(address = mpl_token_metadata::id())]
+    #[account(address = mpl_token_metadata::ID)]
     pub metadata_program: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     #[account(mut)]
     pub signer: Signer<'info>,
     #[account(address = system_program::ID)]
@@ -121,29 +155,43 @@ pub struct InitializeContext<'info> {
 ///
 /// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
 /// - `mint` - the mint account,
 /// - `organization_account` - the account that receives the tokens minted by initial_token_distribution function,
 /// - `token_program` - the Solana token program account,
-/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner.
+/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner,
+/// - `global_stats_account` - optional global statistics account, required once it is created.
 #[derive(Accounts)]
 pub struct InitialTokenDistributionContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
     #[account(
         mut,
         seeds = [MINT_SEED.as_bytes()],
-        bump = blocks_state_account.mint_nonce,
+        bump = config_account.mint_nonce,
     )]
-    pub mint: Box<Account<'info, Mint>>,
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(mut)]
-    pub organization_account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    #[account(mut, constraint = &signer.key() == &blocks_state_account.authority)]
+    pub organization_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut, constraint = signer.key() == blocks_state_account.authority)]
     pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
 }
 
 /// Context for the solve_top_block instruction.
@@ -152,34 +200,291 @@ pub struct InitialTokenDistributionContext<'info> {
 ///
 /// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
 /// - `distribution_top_block_account` - the top block distribution account,
 /// - `mint` - the mint account,
 /// - `token_program` - the Solana token program account,
-/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner.
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when any recipient is denylisted,
+/// - `treasury_token_account` - optional treasury token account receiving surplus tokens of the top block distribution account once the block is solved and the reward fee, it must match the registered treasury (required if the reward fee is set),
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `block_history_account` - optional history account of the current top block, created by the first batch of the block and required while block history is enabled,
+/// - `block_user_requests_account` - optional account of the numbers of requests of the users rewarded in the current top block, created by the first batch of the block and required while the per-user requests cap of top blocks is set,
+/// - `system_program` - optional Solana system program account, required together with the block history account or the block user requests account (and to create recipient associated token accounts),
+/// - `associated_token_program` - optional associated token program account, required only to create recipient associated token accounts,
+/// - `instructions_sysvar_account` - optional instructions sysvar account, required only if the batch is approved by the solve coordinator instead of the owner or the signer quorum,
+/// - `recent_solvers_account` - optional recent solvers account, required once it is created,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
 #[derive(Accounts)]
 #[instruction(bump: u8)]
 pub struct SolveTopBlockContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        constraint = blocks_state_account.treasury_address == Some(treasury_token_account.key()) @ SallarError::InvalidTreasuryAccount,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockHistory::INIT_SPACE,
+        seeds = [BLOCK_HISTORY_SEED.as_bytes(), &[BlockKind::Top as u8], &blocks_state_account.top_block_number.to_le_bytes()],
+        bump,
+    )]
+    pub block_history_account: Option<Box<Account<'info, BlockHistory>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockUserRequests::INIT_SPACE,
+        seeds = [BLOCK_USER_REQUESTS_SEED.as_bytes(), &[BlockKind::Top as u8], &blocks_state_account.top_block_number.to_le_bytes()],
+        bump,
+    )]
+    pub block_user_requests_account: Option<Box<Account<'info, BlockUserRequests>>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    /// CHECK: The instructions sysvar account. It is considered safe because its address is checked.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar_account: Option<AccountInfo<'info>>,
+    #[account(
+        mut,
+        seeds = [RECENT_SOLVERS_SEED.as_bytes()],
+        bump = recent_solvers_account.bump,
+    )]
+    pub recent_solvers_account: Option<Box<Account<'info, RecentSolvers>>>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
+}
+
+/// Context for the commit_block_participation instruction.
+///
+/// This context is used to post the commitment of the next top or bottom block solution batch.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction, either the solve coordinator, the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner.
+#[derive(Accounts)]
+pub struct CommitBlockParticipationContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+}
+
+/// Context for the solve_top_block_with_rewards_root instruction.
+///
+/// This context is used to solve top blocks in the claim mode, i.e. to record the Merkle root of the rewards of current top block instead of distributing them.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used), it pays for the block rewards root account creation,
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `block_rewards_root_account` - the block rewards root account of the current top block to create,
+/// - `system_program` - the Solana system program account,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
+#[derive(Accounts)]
+pub struct SolveTopBlockWithRewardsRootContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
     #[account(
         mut,
         seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
-        bump = blocks_state_account.top_block_distribution_nonce
+        bump = config_account.top_block_distribution_nonce
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockRewardsRoot::INIT_SPACE,
+        seeds = [BLOCK_REWARDS_ROOT_SEED.as_bytes(), &blocks_state_account.top_block_number.to_le_bytes()],
+        bump,
+    )]
+    pub block_rewards_root_account: Box<Account<'info, BlockRewardsRoot>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
+}
+
+/// Context for the claim_block_reward instruction.
+///
+/// This context is used to claim the reward of a top block solved in the claim mode from top block distribution account.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `block_rewards_root_account` - the block rewards root account of the top block the reward comes from,
+/// - `block_reward_claim_account` - the receipt of the claimed reward to create, it prevents the reward from being claimed twice,
+/// - `user_token_account` - the token account receiving the reward, it must be a token account of the Sallar mint,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which pays for the block reward claim account creation (anyone can claim the reward on behalf of the user),
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when the user is denylisted,
+/// - `treasury_token_account` - optional treasury token account receiving the reward fee, it must match the registered treasury (required if the reward fee is set),
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+#[instruction(block_number: u64)]
+pub struct ClaimBlockRewardContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [BLOCK_REWARDS_ROOT_SEED.as_bytes(), &block_number.to_le_bytes()],
+        bump = block_rewards_root_account.bump,
+    )]
+    pub block_rewards_root_account: Box<Account<'info, BlockRewardsRoot>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockRewardClaim::INIT_SPACE,
+        seeds = [BLOCK_REWARD_CLAIM_SEED.as_bytes(), &block_number.to_le_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub block_reward_claim_account: Box<Account<'info, BlockRewardClaim>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+        constraint = !program_owned_token_account(&config_account, &user_token_account.key(), &user_token_account.owner) @ SallarError::RecipientIsProgramTokenAccount,
     )]
-    pub distribution_top_block_account: Box<Account<'info, TokenAccount>>,
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
         seeds = [MINT_SEED.as_bytes()],
-        bump = blocks_state_account.mint_nonce,
+        bump = config_account.mint_nonce,
     )]
-    pub mint: Box<Account<'info, Mint>>,
-    pub token_program: Program<'info, Token>,
-    #[account(mut, constraint = &signer.key() == &blocks_state_account.authority)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub signer: Signer<'info>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        constraint = blocks_state_account.treasury_address == Some(treasury_token_account.key()) @ SallarError::InvalidTreasuryAccount,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
 }
 
 /// Context for the solve_bottom_block instruction.
@@ -188,34 +493,106 @@ pub struct SolveTopBlockContext<'info> {
 ///
 /// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
 /// - `distribution_bottom_block_account` - the bottom block distribution account,
 /// - `mint` - the mint account,
 /// - `token_program` - the Solana token program account,
-/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner.
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when any recipient is denylisted,
+/// - `treasury_token_account` - optional treasury token account receiving surplus tokens of the bottom block distribution account once the block is solved and the reward fee, it must match the registered treasury (required if the reward fee is set),
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `block_history_account` - optional history account of the current bottom block, created by the first batch of the block and required while block history is enabled,
+/// - `block_user_requests_account` - optional account of the numbers of requests of the users rewarded in the current bottom block, created by the first batch of the block and required while the per-user requests cap of bottom blocks is set,
+/// - `system_program` - optional Solana system program account, required together with the block history account or the block user requests account (and to create recipient associated token accounts),
+/// - `associated_token_program` - optional associated token program account, required only to create recipient associated token accounts,
+/// - `instructions_sysvar_account` - optional instructions sysvar account, required only if the batch is approved by the solve coordinator instead of the owner or the signer quorum,
+/// - `recent_solvers_account` - optional recent solvers account, required once it is created,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
 #[derive(Accounts)]
 #[instruction(bump: u8)]
 pub struct SolveBottomBlockContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
     #[account(
         mut,
         seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
-        bump = blocks_state_account.bottom_block_distribution_nonce,
+        bump = config_account.bottom_block_distribution_nonce,
     )]
-    pub distribution_bottom_block_account: Box<Account<'info, TokenAccount>>,
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(
         mut,
         seeds = [MINT_SEED.as_bytes()],
-        bump = blocks_state_account.mint_nonce,
+        bump = config_account.mint_nonce,
     )]
-    pub mint: Box<Account<'info, Mint>>,
-    pub token_program: Program<'info, Token>,
-    #[account(mut, constraint = &signer.key() == &blocks_state_account.authority)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        constraint = blocks_state_account.treasury_address == Some(treasury_token_account.key()) @ SallarError::InvalidTreasuryAccount,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockHistory::INIT_SPACE,
+        seeds = [BLOCK_HISTORY_SEED.as_bytes(), &[BlockKind::Bottom as u8], &blocks_state_account.bottom_block_number.to_le_bytes()],
+        bump,
+    )]
+    pub block_history_account: Option<Box<Account<'info, BlockHistory>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BlockUserRequests::INIT_SPACE,
+        seeds = [BLOCK_USER_REQUESTS_SEED.as_bytes(), &[BlockKind::Bottom as u8], &blocks_state_account.bottom_block_number.to_le_bytes()],
+        bump,
+    )]
+    pub block_user_requests_account: Option<Box<Account<'info, BlockUserRequests>>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    /// CHECK: The instructions sysvar account. It is considered safe because its address is checked.
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions_sysvar_account: Option<AccountInfo<'info>>,
+    #[account(
+        mut,
+        seeds = [RECENT_SOLVERS_SEED.as_bytes()],
+        bump = recent_solvers_account.bump,
+    )]
+    pub recent_solvers_account: Option<Box<Account<'info, RecentSolvers>>>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
 }
 
 /// Context for the final_staking instruction.
@@ -224,82 +601,2744 @@ pub struct SolveBottomBlockContext<'info> {
 ///
 /// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
 /// - `final_staking_account` - the final staking account,
+/// - `mint` - the mint account,
 /// - `token_program` - the Solana token program account,
-/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner.
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when any recipient is denylisted,
+/// - `treasury_token_account` - optional treasury token account receiving the remaining final staking account balance once the pool can no longer fund a meaningful round, it must match the registered treasury (required if the terminal mode is set to sweep to the treasury),
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `final_staking_round_account` - optional snapshot account of the round the call distributes in (the current round or the next one if the current round is completed), created by the first call it is provided to,
+/// - `final_staking_round_participants_account` - optional account of the users rewarded in the round the call distributes in, created by the first call of the round and required while final staking snapshots are not required,
+/// - `system_program` - optional Solana system program account, required to create recipient associated token accounts, the final staking round account and the final staking round participants account,
+/// - `associated_token_program` - optional associated token program account, required only to create recipient associated token accounts,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
 #[derive(Accounts)]
 #[instruction(bump: u8)]
 pub struct FinalStakingContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
     #[account(
         mut,
         seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
-        bump = blocks_state_account.final_staking_account_nonce,
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
     )]
-    pub final_staking_account: Box<Account<'info, TokenAccount>>,
-    pub token_program: Program<'info, Token>,
-    #[account(mut, constraint = &signer.key() == &blocks_state_account.authority)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
     pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        constraint = blocks_state_account.treasury_address == Some(treasury_token_account.key()) @ SallarError::InvalidTreasuryAccount,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingRound::INIT_SPACE,
+        seeds = [FINAL_STAKING_ROUND_SEED.as_bytes(), &final_staking_round_index(&blocks_state_account).to_le_bytes()],
+        bump,
+    )]
+    pub final_staking_round_account: Option<Box<Account<'info, FinalStakingRound>>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingRoundParticipants::INIT_SPACE,
+        seeds = [FINAL_STAKING_ROUND_PARTICIPANTS_SEED.as_bytes(), &final_staking_round_index(&blocks_state_account).to_le_bytes()],
+        bump,
+    )]
+    pub final_staking_round_participants_account:
+        Option<Box<Account<'info, FinalStakingRoundParticipants>>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
 }
 
-/// Context for the final_mining instruction.
+/// Context for the close_final_staking_round instruction.
 ///
-/// This context is used to execute final mining process and distribute tokens to accounts participating in the process.
+/// This context is used to force-close the current final staking round.
 ///
 /// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
-/// - `final_mining_account` - the final mining account,
-/// - `token_program` - the Solana token program account,
-/// - `signer` - the signer of the transaction which executes initialize instruction, the signer becomes contract's owner.
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `final_staking_round_account` - optional snapshot account of the current final staking round, marked as completed if provided,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
 #[derive(Accounts)]
-pub struct FinalMiningContext<'info> {
+pub struct CloseFinalStakingRoundContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
     #[account(
-        mut,
-        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
-        bump = blocks_state_account.final_mining_account_nonce,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
     )]
-    pub final_mining_account: Box<Account<'info, TokenAccount>>,
-    pub token_program: Program<'info, Token>,
-    #[account(mut, constraint = &signer.key() == &blocks_state_account.authority)]
+    pub config_account: Box<Account<'info, Config>>,
     pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_ROUND_SEED.as_bytes(), &blocks_state_account.final_staking_round_counter.to_le_bytes()],
+        bump = final_staking_round_account.bump,
+    )]
+    pub final_staking_round_account: Option<Box<Account<'info, FinalStakingRound>>>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
 }
 
-/// Context for the change_authority instruction.
+/// Context for the open_final_staking_claim_round instruction.
 ///
-/// This context is used to set new authority on contract state.
+/// This context is used to open final staking rounds in the claim mode, i.e. to record the Merkle root of the rewards of a new round instead of distributing them.
 ///
-/// The context includes:
+/// Attributes:
 /// - `blocks_state_account` - the blocks state account defining current contract's state,
-/// - `signer` - the signer of the transaction which must be the contract's owner.
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_staking_account` - the final staking account,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used), it pays for the final staking rewards root account creation,
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `final_staking_rewards_root_account` - the final staking rewards root account of the opened round to create,
+/// - `system_program` - the Solana system program account,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
 #[derive(Accounts)]
-pub struct ChangeAuthorityContext<'info> {
+pub struct OpenFinalStakingClaimRoundContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
     pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingRewardsRoot::INIT_SPACE,
+        seeds = [FINAL_STAKING_REWARDS_ROOT_SEED.as_bytes(), &final_staking_round_index(&blocks_state_account).to_le_bytes()],
+        bump,
+    )]
+    pub final_staking_rewards_root_account: Box<Account<'info, FinalStakingRewardsRoot>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
 }
 
+/// Context for the claim_final_staking_reward instruction.
+///
+/// This context is used to claim the reward of a final staking round opened in the claim mode from final staking account.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_staking_rewards_root_account` - the final staking rewards root account of the round the reward comes from,
+/// - `final_staking_reward_claim_account` - the receipt of the claimed reward to create, it prevents the reward from being claimed twice,
+/// - `user_token_account` - the token account receiving the reward, it must be a token account of the Sallar mint,
+/// - `final_staking_account` - the final staking account,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which pays for the final staking reward claim account creation (anyone can claim the reward on behalf of the user),
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when the user is denylisted,
+/// - `system_program` - the Solana system program account.
 #[derive(Accounts)]
-pub struct SetBlocksCollidedContext<'info> {
+#[instruction(round_index: u64)]
+pub struct ClaimFinalStakingRewardContext<'info> {
     #[account(
         mut,
         seeds = [BLOCKS_STATE_SEED.as_bytes()],
-        bump = blocks_state_account.block_state_nonce,
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
     )]
     pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_REWARDS_ROOT_SEED.as_bytes(), &round_index.to_le_bytes()],
+        bump = final_staking_rewards_root_account.bump,
+    )]
+    pub final_staking_rewards_root_account: Box<Account<'info, FinalStakingRewardsRoot>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingRewardClaim::INIT_SPACE,
+        seeds = [FINAL_STAKING_REWARD_CLAIM_SEED.as_bytes(), &round_index.to_le_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub final_staking_reward_claim_account: Box<Account<'info, FinalStakingRewardClaim>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+        constraint = !program_owned_token_account(&config_account, &user_token_account.key(), &user_token_account.owner) @ SallarError::RecipientIsProgramTokenAccount,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the final_mining instruction.
+///
+/// This context is used to execute final mining process and distribute tokens to accounts participating in the process.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_account` - the final mining account,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when any recipient is denylisted,
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `final_mining_tiers_account` - optional final mining tiers account, required once it is created (the default tiers apply otherwise),
+/// - `final_mining_claim_period_participants_account` - optional account of the users paid in the current claim period, created by the first call of the period and required while the final mining claim period is set,
+/// - `system_program` - optional Solana system program account, required to create recipient associated token accounts and the final mining claim period participants account,
+/// - `associated_token_program` - optional associated token program account, required only to create recipient associated token accounts.
+#[derive(Accounts)]
+pub struct FinalMiningContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump = final_mining_tiers_account.bump,
+    )]
+    pub final_mining_tiers_account: Option<Account<'info, FinalMiningTiers>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalMiningClaimPeriodParticipants::INIT_SPACE,
+        seeds = [FINAL_MINING_CLAIM_PERIOD_PARTICIPANTS_SEED.as_bytes(), &final_mining_claim_period_index(&blocks_state_account).to_le_bytes()],
+        bump,
+    )]
+    pub final_mining_claim_period_participants_account:
+        Option<Box<Account<'info, FinalMiningClaimPeriodParticipants>>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+}
+
+/// Context for the checkpoint_final_mining_balance instruction.
+///
+/// This context is used to record the final mining account's balance at the current slot.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_account` - the final mining account,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used),
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner.
+#[derive(Accounts)]
+pub struct CheckpointFinalMiningBalanceContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+}
+
+/// Context for the publish_final_mining_participation_root instruction.
+///
+/// This context is used by the coordinator to open a new final mining epoch with the participation Merkle root of the epoch.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction, either the contract's owner or one of the registered signers (if signer set is used), it pays for the final mining epoch account creation,
+/// - `signer_set_account` - optional signer set account, required only if the transaction is approved by the quorum of registered signers instead of the owner,
+/// - `final_mining_epoch_account` - the final mining epoch account of the opened epoch to create,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct PublishFinalMiningParticipationRootContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalMiningEpoch::INIT_SPACE,
+        seeds = [FINAL_MINING_EPOCH_SEED.as_bytes(), &(blocks_state_account.final_mining_epoch_counter + 1).to_le_bytes()],
+        bump,
+    )]
+    pub final_mining_epoch_account: Box<Account<'info, FinalMiningEpoch>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the open_final_mining_epoch instruction.
+///
+/// This context is used to open a new final mining epoch without the participation root once the coordinator is inactive.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which pays for the final mining epoch account creation (anyone can open the epoch),
+/// - `final_mining_epoch_account` - the final mining epoch account of the opened epoch to create,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct OpenFinalMiningEpochContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalMiningEpoch::INIT_SPACE,
+        seeds = [FINAL_MINING_EPOCH_SEED.as_bytes(), &(blocks_state_account.final_mining_epoch_counter + 1).to_le_bytes()],
+        bump,
+    )]
+    pub final_mining_epoch_account: Box<Account<'info, FinalMiningEpoch>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the claim_final_mining instruction.
+///
+/// This context is used to claim the final mining reward of a participant of the current final mining epoch from final mining account.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_epoch_account` - the final mining epoch account of the epoch the reward comes from,
+/// - `final_mining_claim_account` - the receipt of the claimed reward to create, it prevents the reward from being claimed twice in the epoch,
+/// - `user_token_account` - the token account receiving the reward, it must be a token account of the Sallar mint,
+/// - `final_mining_account` - the final mining account,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which pays for the final mining claim account creation (anyone can claim the reward on behalf of the user),
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when the user is denylisted,
+/// - `global_stats_account` - optional global statistics account, required once it is created,
+/// - `final_mining_tiers_account` - optional final mining tiers account, required once it is created (the default tiers apply otherwise),
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+#[instruction(epoch_index: u64)]
+pub struct ClaimFinalMiningContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_EPOCH_SEED.as_bytes(), &epoch_index.to_le_bytes()],
+        bump = final_mining_epoch_account.bump,
+    )]
+    pub final_mining_epoch_account: Box<Account<'info, FinalMiningEpoch>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalMiningClaim::INIT_SPACE,
+        seeds = [FINAL_MINING_CLAIM_SEED.as_bytes(), &epoch_index.to_le_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub final_mining_claim_account: Box<Account<'info, FinalMiningClaim>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+        constraint = !program_owned_token_account(&config_account, &user_token_account.key(), &user_token_account.owner) @ SallarError::RecipientIsProgramTokenAccount,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+    #[account(
+        mut,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+    #[account(
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump = final_mining_tiers_account.bump,
+    )]
+    pub final_mining_tiers_account: Option<Account<'info, FinalMiningTiers>>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the settle_collision_remainder instruction.
+///
+/// This context is used to move the remaining balances of the current blocks to the final pools once blocks have collided.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `final_mining_account` - the final mining account receiving the remaining top block balance,
+/// - `final_staking_account` - the final staking account receiving the remaining bottom block balance,
+/// - `mint` - the mint account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `state_mirror_account` - optional state mirror account, required once it is created.
+#[derive(Accounts)]
+pub struct SettleCollisionRemainderContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump = state_mirror_account.bump,
+    )]
+    pub state_mirror_account: Option<Box<Account<'info, StateMirror>>>,
+}
+
+/// Context for the finalize_mint instruction.
+///
+/// This context is used to remove the mint authority once all blocks are solved, so the token supply is fixed forever.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct FinalizeMintContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the close_program_accounts instruction.
+///
+/// This context is used to close the token accounts, the blocks state account and the config account once all tokens are distributed, so the rent is reclaimed.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state, it is closed as well,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts, it is closed as well,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `final_staking_account` - the final staking account,
+/// - `final_mining_account` - the final mining account,
+/// - `recipient` - the account receiving lamports of the closed accounts,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct CloseProgramAccountsContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        close = recipient,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+        close = recipient,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: The recipient of reclaimed lamports. It is considered safe because it only receives lamports and it is chosen by the contract's owner.
+    #[account(mut)]
+    pub recipient: AccountInfo<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the recover_foreign_tokens instruction.
+///
+/// This context is used to transfer out tokens accidentally sent to the blocks state account.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state, the authority of the source token account,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `source_token_account` - the token account held by the blocks state account, it cannot be any of the program's token accounts,
+/// - `mint` - the mint of the recovered tokens,
+/// - `destination_token_account` - the token account receiving recovered tokens,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct RecoverForeignTokensContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        constraint = source_token_account.owner == blocks_state_account.key() @ SallarError::InvalidForeignTokenAccount,
+    )]
+    pub source_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = source_token_account.mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub destination_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the export_regulatory_report instruction.
+///
+/// This context is used to read the supply and the balances reported in the regulatory report event.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `final_staking_account` - the final staking account,
+/// - `final_mining_account` - the final mining account,
+/// - `treasury_token_account` - optional treasury token account, it must match the registered treasury (required if the treasury is registered),
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct ExportRegulatoryReportContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_staking_account_nonce,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        constraint = blocks_state_account.treasury_address == Some(treasury_token_account.key()) @ SallarError::InvalidTreasuryAccount,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the get_block_state_summary instruction.
+///
+/// This context is used to read the blocks state the block metrics are derived from.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts.
+#[derive(Accounts)]
+pub struct GetBlockStateSummaryContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+}
+
+/// Context for the verify_invariants instruction.
+///
+/// This context is used to read the blocks state, the mint and the distribution accounts the invariants are verified against.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `global_stats_account` - optional global statistics account, required once it is created.
+#[derive(Accounts)]
+pub struct VerifyInvariantsContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump = global_stats_account.bump,
+    )]
+    pub global_stats_account: Option<Box<Account<'info, GlobalStats>>>,
+}
+
+/// Context for the get_final_mining_emission_projection instruction.
+///
+/// This context is used to read the final mining account's balance and the final mining configuration the emission is projected from.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_account` - the final mining account,
+/// - `final_mining_tiers_account` - optional final mining tiers account, required once it is created (the default tiers apply otherwise).
+#[derive(Accounts)]
+pub struct GetFinalMiningEmissionProjectionContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump = config_account.final_mining_account_nonce,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump = final_mining_tiers_account.bump,
+    )]
+    pub final_mining_tiers_account: Option<Account<'info, FinalMiningTiers>>,
+}
+
+/// Context for the simulate_solve_top_block and simulate_solve_bottom_block instructions.
+///
+/// This context is used to read the blocks state the block solution batch is simulated against.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts.
+#[derive(Accounts)]
+pub struct SimulateSolveBlockContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+}
+
+/// Context for the update_token_metadata instruction.
+///
+/// This context is used to update the token metadata (name, symbol and uri) set during initialization.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account, which is the metadata update authority,
+/// - `metadata_pda` - the token metadata account,
+/// - `metadata_program` - the Metaplex token metadata program account,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct UpdateTokenMetadataContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: The metadata account. It is considered safe because the inner instruction of the Metaplex token metadata program verifies that it is derived from the mint.
+    #[account(mut)]
+    pub metadata_pda: AccountInfo<'info>,
+    /// CHECK: The metadata program account. It is considered safe because its address is checked against the Metaplex token metadata program id.
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: AccountInfo<'info>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the change_authority instruction.
+///
+/// This context is used to set new authority on contract state.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct ChangeAuthorityContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the initialize_signer_set instruction.
+///
+/// This context is used to create the signer set account defining the registered signers and the quorum required to approve privileged instructions.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer_set_account` - the signer set account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeSignerSetContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + SignerSet::INIT_SPACE,
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump,
+    )]
+    pub signer_set_account: Account<'info, SignerSet>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the update_signer_set instruction.
+///
+/// This context is used to replace the registered signers and the quorum required to approve privileged instructions.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer_set_account` - the signer set account to update,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct UpdateSignerSetContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Account<'info, SignerSet>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the initialize_denylist instruction.
+///
+/// This context is used to create the denylist account defining the token accounts which must not receive rewards.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `denylist_account` - the denylist account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeDenylistContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + Denylist::INIT_SPACE,
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump,
+    )]
+    pub denylist_account: Account<'info, Denylist>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the initialize_final_staking_tiers instruction.
+///
+/// This context is used to create the final staking tiers account defining the multipliers of the balance snapshots registered for final staking.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_staking_tiers_account` - the final staking tiers account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeFinalStakingTiersContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingTiers::INIT_SPACE,
+        seeds = [FINAL_STAKING_TIERS_SEED.as_bytes()],
+        bump,
+    )]
+    pub final_staking_tiers_account: Account<'info, FinalStakingTiers>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the set_final_staking_tiers instruction.
+///
+/// This context is used to replace the final staking tiers.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_staking_tiers_account` - the final staking tiers account to update,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalStakingTiersContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [FINAL_STAKING_TIERS_SEED.as_bytes()],
+        bump = final_staking_tiers_account.bump,
+    )]
+    pub final_staking_tiers_account: Account<'info, FinalStakingTiers>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the initialize_final_mining_tiers instruction.
+///
+/// This context is used to create the final mining tiers account defining the reward of a single final mining request.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_tiers_account` - the final mining tiers account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeFinalMiningTiersContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalMiningTiers::INIT_SPACE,
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump,
+    )]
+    pub final_mining_tiers_account: Account<'info, FinalMiningTiers>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the set_final_mining_tiers instruction.
+///
+/// This context is used to replace the final mining tiers.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `final_mining_tiers_account` - the final mining tiers account to update,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalMiningTiersContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump = final_mining_tiers_account.bump,
+    )]
+    pub final_mining_tiers_account: Account<'info, FinalMiningTiers>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the initialize_global_stats instruction.
+///
+/// This context is used to create the global statistics account tracking totals of the token distribution.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `global_stats_account` - the global statistics account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeGlobalStatsContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + GlobalStats::INIT_SPACE,
+        seeds = [GLOBAL_STATS_SEED.as_bytes()],
+        bump,
+    )]
+    pub global_stats_account: Box<Account<'info, GlobalStats>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the initialize_recent_solvers instruction.
+///
+/// This context is used to create the recent solvers account keeping the last token accounts rewarded by block solutions.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `recent_solvers_account` - the recent solvers account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeRecentSolversContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + RecentSolvers::INIT_SPACE,
+        seeds = [RECENT_SOLVERS_SEED.as_bytes()],
+        bump,
+    )]
+    pub recent_solvers_account: Box<Account<'info, RecentSolvers>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the initialize_state_mirror instruction.
+///
+/// This context is used to create the state mirror account keeping the compact copy of the current blocks and the final staking round.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `state_mirror_account` - the state mirror account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeStateMirrorContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + StateMirror::INIT_SPACE,
+        seeds = [STATE_MIRROR_SEED.as_bytes()],
+        bump,
+    )]
+    pub state_mirror_account: Box<Account<'info, StateMirror>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the migrate_state instruction.
+///
+/// This context is used to grow the blocks state account to the current layout and to migrate its fields.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account to migrate, it is deserialized by the instruction after it is grown,
+///   as the account of a previous layout may be too small to be deserialized to the current one,
+/// - `signer` - the signer of the transaction which must be the contract's owner, it pays the rent of the additional space,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct MigrateStateContext<'info> {
+    /// CHECK: The blocks state account of a previous layout. It is considered safe because its address and owner are checked and it is deserialized as the blocks state by the instruction.
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub blocks_state_account: AccountInfo<'info>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the initialize_config instruction.
+///
+/// This context is used to create the config account of a deployment initialized before the config account was introduced.
+/// The canonical bumps of the contract's accounts are found by Anchor, the same as in the initialize instruction.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state, migrated to the current version of its layout,
+/// - `config_account` - the config account to create,
+/// - `mint` - the mint account,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `final_staking_account` - the final staking account,
+/// - `final_mining_account` - the final mining account,
+/// - `signer` - the signer of the transaction which must be the contract's owner,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeConfigContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump,
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump,
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [FINAL_STAKING_ACCOUNT_SEED.as_bytes()],
+        bump,
+    )]
+    pub final_staking_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [FINAL_MINING_ACCOUNT_SEED.as_bytes()],
+        bump,
+    )]
+    pub final_mining_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the add_to_denylist and remove_from_denylist instructions.
+///
+/// This context is used to add or remove a token account from the denylist.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `denylist_account` - the denylist account to update,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct UpdateDenylistContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Account<'info, Denylist>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the propose_authority instruction.
+///
+/// This context is used to propose new authority which has to accept the ownership to become the contract's owner.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct ProposeAuthorityContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the accept_authority instruction.
+///
+/// This context is used to accept the ownership by the pending authority.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the pending authority.
+#[derive(Accounts)]
+pub struct AcceptAuthorityContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_min_payout_amount instruction.
+///
+/// This context is used to set the minimum payout amount on contract state.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetMinPayoutAmountContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the initialize_user_claim_account instruction.
+///
+/// This context is used to create the claim account for a single token account receiving rewards.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `user_token_account` - the token account the claim account is created for, it must be a token account of the Sallar mint,
+/// - `user_claim_account` - the claim account to create,
+/// - `signer` - the signer of the transaction which pays for the claim account creation,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeUserClaimAccountContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(constraint = user_token_account.mint == mint.key())]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + UserClaimAccount::INIT_SPACE,
+        seeds = [USER_CLAIM_ACCOUNT_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub user_claim_account: Box<Account<'info, UserClaimAccount>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the withdraw_pending_rewards instruction.
+///
+/// This context is used to transfer the rewards pending in the claim account of a user from the distribution accounts to the user's token account.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `user_token_account` - the token account receiving the rewards,
+/// - `user_claim_account` - the claim account of the user holding the pending rewards,
+/// - `distribution_top_block_account` - the top block distribution account,
+/// - `distribution_bottom_block_account` - the bottom block distribution account,
+/// - `token_program` - the Solana token program account,
+/// - `signer` - the signer of the transaction (anyone can withdraw the rewards on behalf of the user),
+/// - `denylist_account` - optional denylist account, required once it is created, the transaction fails when the user is denylisted.
+#[derive(Accounts)]
+pub struct WithdrawPendingRewardsContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [USER_CLAIM_ACCOUNT_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump = user_claim_account.bump,
+    )]
+    pub user_claim_account: Box<Account<'info, UserClaimAccount>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()],
+        bump = config_account.top_block_distribution_nonce
+    )]
+    pub distribution_top_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()],
+        bump = config_account.bottom_block_distribution_nonce
+    )]
+    pub distribution_bottom_block_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [DENYLIST_SEED.as_bytes()],
+        bump = denylist_account.bump,
+    )]
+    pub denylist_account: Option<Account<'info, Denylist>>,
+}
+
+/// Context for the initialize_user_reward_stats instruction.
+///
+/// This context is used to create the lifetime reward statistics account of a single wallet.
+///
+/// Attributes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `wallet` - the wallet the statistics account is created for,
+/// - `user_reward_stats_account` - the user reward stats account to create,
+/// - `signer` - the signer of the transaction which pays for the account creation,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct InitializeUserRewardStatsContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    /// CHECK: The wallet the statistics are collected for. It is considered safe because only its address is used as a seed of the created account.
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + UserRewardStats::INIT_SPACE,
+        seeds = [USER_REWARD_STATS_SEED.as_bytes(), wallet.key().as_ref()],
+        bump,
+    )]
+    pub user_reward_stats_account: Box<Account<'info, UserRewardStats>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the lock_stake instruction.
+///
+/// This context is used to lock tokens of a user in its stake vault to back the user's bottom block boost.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `user_token_account` - the token account the stake belongs to and the tokens are locked from, it must be a token account of the Sallar mint owned by the signer,
+/// - `stake_lock_account` - the stake lock account of the user, created by the first lock,
+/// - `stake_vault_account` - the token account holding the locked tokens of the user, created by the first lock, it is its own authority (as the program's token accounts),
+/// - `signer` - the signer of the transaction which must be the owner of the user's token account, it pays for the accounts creation,
+/// - `token_program` - the Solana token program account,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct LockStakeContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+        constraint = user_token_account.owner == signer.key() @ SallarError::SignerIsNotStakeOwner,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + StakeLock::INIT_SPACE,
+        seeds = [STAKE_LOCK_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub stake_lock_account: Box<Account<'info, StakeLock>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [STAKE_VAULT_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = stake_vault_account,
+        token::token_program = token_program,
+    )]
+    pub stake_vault_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the buy_boost_credits instruction.
+///
+/// This context is used to burn tokens of a user in exchange for boost credits consumed by the user's requests with boost.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account, the burned tokens are removed from its supply,
+/// - `user_token_account` - the token account the boost credits belong to and the tokens are burned from, it must be a token account of the Sallar mint owned by the signer,
+/// - `boost_credits_account` - the boost credits account of the user, created by the first purchase,
+/// - `signer` - the signer of the transaction which must be the owner of the user's token account, it pays for the account creation,
+/// - `token_program` - the Solana token program account,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct BuyBoostCreditsContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+        constraint = user_token_account.owner == signer.key() @ SallarError::SignerIsNotStakeOwner,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + BoostCredits::INIT_SPACE,
+        seeds = [BOOST_CREDITS_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub boost_credits_account: Box<Account<'info, BoostCredits>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the register_for_final_staking instruction.
+///
+/// This context is used to register the balance snapshot of a user for the next final staking round.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `user_token_account` - the token account the snapshot belongs to, it must be a token account of the Sallar mint,
+/// - `stake_lock_account` - the stake lock account of the user, the amount locked in it is the registered balance,
+/// - `final_staking_registration_account` - the final staking registration account of the user, created by the first registration,
+/// - `final_staking_tiers_account` - optional final staking tiers account, if not provided the multiplier of 1x is applied,
+/// - `signer` - the signer of the transaction which must be either the owner of the user's token account or the solve coordinator, it pays for the account creation,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct RegisterForFinalStakingContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        constraint = user_token_account.mint == mint.key() @ SallarError::RecipientAccountMintMismatch,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [STAKE_LOCK_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump = stake_lock_account.bump,
+    )]
+    pub stake_lock_account: Box<Account<'info, StakeLock>>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + FinalStakingRegistration::INIT_SPACE,
+        seeds = [FINAL_STAKING_REGISTRATION_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump,
+    )]
+    pub final_staking_registration_account: Box<Account<'info, FinalStakingRegistration>>,
+    #[account(
+        seeds = [FINAL_STAKING_TIERS_SEED.as_bytes()],
+        bump = final_staking_tiers_account.bump,
+    )]
+    pub final_staking_tiers_account: Option<Box<Account<'info, FinalStakingTiers>>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the unlock_stake instruction.
+///
+/// This context is used to unlock tokens of a user from its stake vault back to the user's token account.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `user_token_account` - the token account the stake belongs to and the tokens are returned to, it must be owned by the signer,
+/// - `stake_lock_account` - the stake lock account of the user,
+/// - `stake_vault_account` - the token account holding the locked tokens of the user,
+/// - `signer` - the signer of the transaction which must be the owner of the user's token account,
+/// - `token_program` - the Solana token program account.
+#[derive(Accounts)]
+pub struct UnlockStakeContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        constraint = user_token_account.owner == signer.key() @ SallarError::SignerIsNotStakeOwner,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [STAKE_LOCK_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump = stake_lock_account.bump,
+    )]
+    pub stake_lock_account: Box<Account<'info, StakeLock>>,
+    #[account(
+        mut,
+        seeds = [STAKE_VAULT_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump = stake_lock_account.vault_bump,
+    )]
+    pub stake_vault_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub signer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Context for the set_final_staking_auto_restake instruction.
+///
+/// This context is used to enable or disable restaking of the final staking rewards of a user in its stake vault.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `user_token_account` - the token account the stake belongs to, it must be owned by the signer,
+/// - `stake_lock_account` - the stake lock account of the user, created by the first lock,
+/// - `signer` - the signer of the transaction which must be the owner of the user's token account.
+#[derive(Accounts)]
+pub struct SetFinalStakingAutoRestakeContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        constraint = user_token_account.owner == signer.key() @ SallarError::SignerIsNotStakeOwner,
+    )]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        seeds = [STAKE_LOCK_SEED.as_bytes(), user_token_account.key().as_ref()],
+        bump = stake_lock_account.bump,
+    )]
+    pub stake_lock_account: Box<Account<'info, StakeLock>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_treasury instruction.
+///
+/// This context is used to register the treasury token account receiving surplus tokens of the distribution accounts.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `mint` - the mint account,
+/// - `treasury_token_account` - the treasury token account, it must be a token account of the Sallar mint,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetTreasuryContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(constraint = treasury_token_account.mint == mint.key())]
+    pub treasury_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the pause and unpause instructions.
+///
+/// This context is used to pause and unpause all token-moving instructions.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetPausedContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_guardian instruction.
+///
+/// This context is used to set or remove the guardian allowed to halt block solving.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetGuardianContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_block_history_enabled instruction.
+///
+/// This context is used to enable or disable recording the solvers of every block in the block history accounts.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetBlockHistoryEnabledContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_solve_coordinator instruction.
+///
+/// This context is used to set or remove the solve coordinator approving block solution batches.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetSolveCoordinatorContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_bottom_block_min_required_stake instruction.
+///
+/// This context is used to set the minimum balance required to participate in bottom block solutions.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetBottomBlockMinRequiredStakeContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_max_user_requests_per_block instruction.
+///
+/// This context is used to set the per-user requests caps of top and bottom blocks.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetMaxUserRequestsPerBlockContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_block_participation_commitment_enabled instruction.
+///
+/// This context is used to enable or disable the commit-reveal scheme of block solution batches.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetBlockParticipationCommitmentEnabledContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_boost_credits_required instruction.
+///
+/// This context is used to require or stop requiring boost credits for requests with boost in block solution.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetBoostCreditsRequiredContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_staking_snapshots_required instruction.
+///
+/// This context is used to require or stop requiring the balance snapshots of the users in final staking.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalStakingSnapshotsRequiredContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_mining_checkpoint_required instruction.
+///
+/// This context is used to require or stop requiring the final mining balances of the requests to match a recent final mining balance checkpoint.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalMiningCheckpointRequiredContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_mining_claim_period instruction.
+///
+/// This context is used to set the period in which a user can receive at most one final mining payout.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalMiningClaimPeriodContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_staking_reward_part_tolerance instruction.
+///
+/// This context is used to set the tolerance of the verification of the reward parts given to final staking against the balances of the users.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalStakingRewardPartToleranceContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the halt_block and resume_block instructions.
+///
+/// This context is used to halt and resume solving of top or bottom blocks.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the guardian (halt_block) or the contract's owner (resume_block).
+#[derive(Accounts)]
+pub struct SetBlockHaltedContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the renounce_admin_controls instruction.
+///
+/// This context is used to permanently disable admin controls of the contract's owner.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct RenounceAdminControlsContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_timelock_delay instruction.
+///
+/// This context is used to set the delay between queuing an authority or parameter change and its execution.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetTimelockDelayContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_block_solution_interval instruction.
+///
+/// This context is used to set the minimum time between solutions of consecutive blocks.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetBlockSolutionIntervalContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_reward_fee instruction.
+///
+/// This context is used to set the fee taken from top and bottom block rewards.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetRewardFeeContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_distribution_backend instruction.
+///
+/// This context is used to set the distribution backend of a subsystem.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the distribution backends of the subsystems,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetDistributionBackendContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_sla_threshold instruction.
+///
+/// This context is used to set the SLA threshold of block solutions and final staking rounds.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetSlaThresholdContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the update_final_staking_config instruction.
+///
+/// This context is used to update the final staking interval and the final staking pool division factor.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct UpdateFinalStakingConfigContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the update_final_mining_config instruction.
+///
+/// This context is used to update the final mining interval and the maximum payout per final mining call.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct UpdateFinalMiningConfigContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_mining_budget instruction.
+///
+/// This context is used to set the maximum amount of tokens distributed by final mining within a single budget window.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalMiningBudgetContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the set_final_staking_terminal_config instruction.
+///
+/// This context is used to update the minimum prize pool of a regular final staking round and the way the remaining final staking account balance is handled once the pool falls below it.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct SetFinalStakingTerminalConfigContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    pub signer: Signer<'info>,
+}
+
+/// Context for the queue_pending_change instruction.
+///
+/// This context is used to queue an authority or parameter change in the timelock.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `pending_change_account` - the pending change account to create,
+/// - `signer` - the signer of the transaction which must be the contract's owner, it pays for the pending change account creation,
+/// - `system_program` - the Solana system program account.
+#[derive(Accounts)]
+pub struct QueuePendingChangeContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        init,
+        payer = signer,
+        space = DISCRIMINATOR_LENGTH + PendingChange::INIT_SPACE,
+        seeds = [PENDING_CHANGE_SEED.as_bytes()],
+        bump,
+    )]
+    pub pending_change_account: Box<Account<'info, PendingChange>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the execute_pending_change instruction.
+///
+/// This context is used to apply the change queued in the timelock once its delay has elapsed.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts, it is updated by the distribution backend change,
+/// - `pending_change_account` - the pending change account, it is closed once the change is applied,
+/// - `mint` - the mint account,
+/// - `treasury_token_account` - optional treasury token account, required only to apply the treasury change, it must be a token account of the Sallar mint,
+/// - `signer_set_account` - optional signer set account, required only to apply the signer set change,
+/// - `final_mining_tiers_account` - optional final mining tiers account, required only to apply the final mining tiers change,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct ExecutePendingChangeContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [PENDING_CHANGE_SEED.as_bytes()],
+        bump = pending_change_account.bump,
+    )]
+    pub pending_change_account: Box<Account<'info, PendingChange>>,
+    #[account(
+        seeds = [MINT_SEED.as_bytes()],
+        bump = config_account.mint_nonce,
+    )]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(constraint = treasury_token_account.mint == mint.key())]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        mut,
+        seeds = [SIGNER_SET_SEED.as_bytes()],
+        bump = signer_set_account.bump,
+    )]
+    pub signer_set_account: Option<Account<'info, SignerSet>>,
+    #[account(
+        mut,
+        seeds = [FINAL_MINING_TIERS_SEED.as_bytes()],
+        bump = final_mining_tiers_account.bump,
+    )]
+    pub final_mining_tiers_account: Option<Account<'info, FinalMiningTiers>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+/// Context for the cancel_pending_change instruction.
+///
+/// This context is used to cancel the change queued in the timelock.
+///
+/// The context includes:
+/// - `blocks_state_account` - the blocks state account defining current contract's state,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `config_account` - the config account holding the nonces and addresses of the contract's accounts,
+/// - `pending_change_account` - the pending change account to close,
+/// - `signer` - the signer of the transaction which must be the contract's owner.
+#[derive(Accounts)]
+pub struct CancelPendingChangeContext<'info> {
+    #[account(
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [PENDING_CHANGE_SEED.as_bytes()],
+        bump = pending_change_account.bump,
+    )]
+    pub pending_change_account: Box<Account<'info, PendingChange>>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetBlocksCollidedContext<'info> {
+    #[account(
+        mut,
+        seeds = [BLOCKS_STATE_SEED.as_bytes()],
+        bump = config_account.block_state_nonce,
+        constraint = blocks_state_account.version == BLOCKS_STATE_VERSION @ SallarError::BlocksStateVersionMismatch,
+    )]
+    pub blocks_state_account: Box<Account<'info, BlocksState>>,
+    #[account(
+        seeds = [CONFIG_SEED.as_bytes()],
+        bump = config_account.bump,
+    )]
+    pub config_account: Box<Account<'info, Config>>,
     pub signer: Signer<'info>,
 }
@@ -0,0 +1,552 @@
+use anchor_lang::prelude::{
+    err, require, AccountInfo, AccountsExit, InterfaceAccount, Pubkey, Result,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+use crate::{
+    account::{
+        BlockKind, BlocksState, Config, Denylist, DistributionBackend, GlobalStats, Subsystem,
+        UserRewardStats,
+    },
+    error::SallarError,
+    token_math::{calculate_reward_fee, DUSTS_PER_BLOCK},
+    utils::{
+        find_user_claim_account, find_user_reward_stats_account, recipient_not_denylisted,
+        settle_bottom_block_payout, settle_top_block_payout, token_account_owner, transfer_tokens,
+    },
+};
+
+/// Source of the distributed tokens, i.e. the distribution account of a subsystem.
+/// Consists of the following attributes:
+/// * `distribution_account` - the distribution account the tokens are transferred from,
+/// * `seed` - the seed of the distribution account,
+/// * `nonce` - the nonce of the distribution account,
+/// * `mint` - the mint account of the distributed tokens,
+/// * `token_program` - the Solana token program account,
+/// * `backend` - the distribution backend of the subsystem (see distribution_backend),
+/// * `fee_destination` - the token account receiving the reward fee (the treasury), if provided,
+/// * `fee_amount` - the reward fee accumulated by distribute_reward and not transferred to the fee destination yet (see transfer_accumulated_fee).
+pub struct DistributionSource<'a, 'info> {
+    pub distribution_account: &'a InterfaceAccount<'info, TokenAccount>,
+    pub seed: &'static str,
+    pub nonce: u8,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: AccountInfo<'info>,
+    pub backend: DistributionBackend,
+    pub fee_destination: Option<AccountInfo<'info>>,
+    pub fee_amount: u64,
+}
+
+/// Returns the distribution backend the subsystem starts with once the contract is initialized.
+///
+/// ### Arguments
+///
+/// * `subsystem` - the subsystem distributing tokens.
+///
+/// ### Returns
+/// The default distribution backend of the subsystem.
+pub fn default_distribution_backend(subsystem: Subsystem) -> DistributionBackend {
+    match subsystem {
+        Subsystem::TopBlock | Subsystem::BottomBlock => DistributionBackend::ClaimCredit,
+        Subsystem::FinalMining | Subsystem::FinalStaking => DistributionBackend::DirectTransfer,
+    }
+}
+
+/// Sets the default distribution backends of all subsystems in the config (see default_distribution_backend).
+///
+/// ### Arguments
+///
+/// * `config` - the config account being initialized.
+pub fn initialize_distribution_backends(config: &mut Config) {
+    for subsystem in [
+        Subsystem::TopBlock,
+        Subsystem::BottomBlock,
+        Subsystem::FinalMining,
+        Subsystem::FinalStaking,
+    ] {
+        set_distribution_backend(config, subsystem, default_distribution_backend(subsystem));
+    }
+}
+
+/// Returns the distribution backend configured for the given subsystem.
+///
+/// ### Arguments
+///
+/// * `config` - the config account,
+/// * `subsystem` - the subsystem distributing tokens.
+///
+/// ### Returns
+/// The distribution backend used by the subsystem.
+pub fn distribution_backend(config: &Config, subsystem: Subsystem) -> DistributionBackend {
+    match subsystem {
+        Subsystem::TopBlock => config.top_block_distribution_backend,
+        Subsystem::BottomBlock => config.bottom_block_distribution_backend,
+        Subsystem::FinalMining => config.final_mining_distribution_backend,
+        Subsystem::FinalStaking => config.final_staking_distribution_backend,
+    }
+}
+
+/// Configures the distribution backend of the given subsystem.
+///
+/// ### Arguments
+///
+/// * `config` - the config account,
+/// * `subsystem` - the subsystem distributing tokens,
+/// * `backend` - the new distribution backend of the subsystem.
+pub fn set_distribution_backend(
+    config: &mut Config,
+    subsystem: Subsystem,
+    backend: DistributionBackend,
+) {
+    match subsystem {
+        Subsystem::TopBlock => config.top_block_distribution_backend = backend,
+        Subsystem::BottomBlock => config.bottom_block_distribution_backend = backend,
+        Subsystem::FinalMining => config.final_mining_distribution_backend = backend,
+        Subsystem::FinalStaking => config.final_staking_distribution_backend = backend,
+    }
+}
+
+/// Distributes the reward of a single user using the distribution backend of the source.
+/// The reward fee (if set) is accumulated in the source and the rest of the reward is distributed to the user.
+/// The fee is transferred to the fee destination by transfer_accumulated_fee, so a batch pays the fee with a single transfer instead of one per user.
+/// The distribution fails if the recipient is denylisted.
+/// The user's part of the reward is recorded in the reward stats account of the wallet owning the recipient (if provided).
+///
+/// ### Arguments
+///
+/// * `subsystem` - the subsystem distributing the reward,
+/// * `state` - contract's state (blocks state),
+/// * `source` - the distribution account the reward is transferred from,
+/// * `recipient` - the token account receiving the reward,
+/// * `denylist` - the denylist the recipient is checked against (if provided),
+/// * `accounts` - the accounts where the user's claim account (used by the claim credit and escrow backends) and reward stats account are searched,
+/// * `reward_amount` - the reward calculated for the user.
+///
+/// ### Returns
+/// The amount transferred to the user.
+pub fn distribute_reward<'info>(
+    subsystem: Subsystem,
+    state: &mut BlocksState,
+    source: &mut DistributionSource<'_, 'info>,
+    recipient: AccountInfo<'info>,
+    denylist: Option<&Denylist>,
+    accounts: &'info [AccountInfo<'info>],
+    reward_amount: u64,
+) -> Result<u64> {
+    distribute_reward_to(
+        subsystem,
+        state,
+        source,
+        recipient.clone(),
+        recipient,
+        denylist,
+        accounts,
+        reward_amount,
+    )
+}
+
+/// Distributes the reward of a single user the same way as distribute_reward, but transfers the tokens to the given destination instead of the recipient,
+/// e.g. to the stake vault of a user restaking its final staking rewards.
+/// The denylist and the reward stats are still checked and recorded for the recipient.
+///
+/// ### Arguments
+///
+/// * `subsystem` - the subsystem distributing the reward,
+/// * `state` - contract's state (blocks state),
+/// * `source` - the distribution account the reward is transferred from,
+/// * `recipient` - the token account the reward belongs to,
+/// * `destination` - the token account the tokens are transferred to,
+/// * `denylist` - the denylist the recipient is checked against (if provided),
+/// * `accounts` - the accounts where the user's claim account (used by the claim credit and escrow backends) and reward stats account are searched,
+/// * `reward_amount` - the reward calculated for the user.
+///
+/// ### Returns
+/// The amount transferred to the destination.
+#[allow(clippy::too_many_arguments)]
+pub fn distribute_reward_to<'info>(
+    subsystem: Subsystem,
+    state: &mut BlocksState,
+    source: &mut DistributionSource<'_, 'info>,
+    recipient: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    denylist: Option<&Denylist>,
+    accounts: &'info [AccountInfo<'info>],
+    reward_amount: u64,
+) -> Result<u64> {
+    recipient_not_denylisted(denylist, recipient.key)?;
+
+    let fee_amount = match subsystem {
+        Subsystem::TopBlock | Subsystem::BottomBlock => {
+            calculate_reward_fee(reward_amount, state.reward_fee_bps)?
+        }
+        Subsystem::FinalMining | Subsystem::FinalStaking => 0,
+    };
+    if fee_amount > 0 {
+        require!(
+            source.fee_destination.is_some(),
+            SallarError::MissingFeeDestinationAccount
+        );
+        source.fee_amount = source
+            .fee_amount
+            .checked_add(fee_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+    }
+    let user_amount = reward_amount
+        .checked_sub(fee_amount)
+        .ok_or(SallarError::ArithmeticUnderflow)?;
+    record_user_reward(subsystem, accounts, &recipient, user_amount)?;
+
+    let payout_amount = match source.backend {
+        DistributionBackend::DirectTransfer => user_amount,
+        DistributionBackend::ClaimCredit => {
+            credit_reward(subsystem, state, accounts, recipient.key, user_amount)?
+        }
+        DistributionBackend::Escrow => {
+            escrow_reward(subsystem, state, accounts, recipient.key, user_amount)?;
+            0
+        }
+    };
+
+    if payout_amount > 0 {
+        transfer_tokens(
+            source.distribution_account,
+            destination,
+            source.mint,
+            source.seed,
+            source.token_program.clone(),
+            source.nonce,
+            payout_amount,
+        )?;
+    }
+
+    Ok(payout_amount)
+}
+
+/// Transfers the reward fee accumulated by distribute_reward to the fee destination with a single transfer.
+/// It must be called once the rewards of a batch are distributed, before the balance of the distribution account is inspected.
+///
+/// ### Arguments
+///
+/// * `source` - the distribution account the rewards were distributed from.
+///
+/// ### Returns
+/// A successful result if the fee is transferred or no fee is accumulated.
+pub fn transfer_accumulated_fee(source: &mut DistributionSource<'_, '_>) -> Result<()> {
+    if source.fee_amount == 0 {
+        return Ok(());
+    }
+    let fee_destination = source
+        .fee_destination
+        .clone()
+        .ok_or(SallarError::MissingFeeDestinationAccount)?;
+    transfer_tokens(
+        source.distribution_account,
+        fee_destination,
+        source.mint,
+        source.seed,
+        source.token_program.clone(),
+        source.nonce,
+        source.fee_amount,
+    )?;
+    source.fee_amount = 0;
+
+    Ok(())
+}
+
+/// Credits the reward to the user's claim account considering the minimum payout threshold.
+/// Pending rewards are tracked only for top and bottom blocks, so the whole reward is paid out for the other subsystems.
+///
+/// ### Returns
+/// The amount to transfer to the user.
+fn credit_reward<'info>(
+    subsystem: Subsystem,
+    state: &mut BlocksState,
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    reward_amount: u64,
+) -> Result<u64> {
+    match subsystem {
+        Subsystem::TopBlock => settle_top_block_payout(state, accounts, user, reward_amount),
+        Subsystem::BottomBlock => settle_bottom_block_payout(state, accounts, user, reward_amount),
+        Subsystem::FinalMining | Subsystem::FinalStaking => Ok(reward_amount),
+    }
+}
+
+/// Holds the whole reward in the user's claim account until the user withdraws it with withdraw_pending_rewards.
+/// The tokens stay in the distribution account and they are counted as pending payouts of the subsystem.
+/// Only top and bottom block rewards can be escrowed, as claim accounts do not track the rewards of the other subsystems.
+fn escrow_reward<'info>(
+    subsystem: Subsystem,
+    state: &mut BlocksState,
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    reward_amount: u64,
+) -> Result<()> {
+    let mut claim_account =
+        find_user_claim_account(accounts, user).ok_or(SallarError::MissingUserClaimAccount)?;
+    let (user_pending_amount, total_pending_amount) = match subsystem {
+        Subsystem::TopBlock => (
+            &mut claim_account.pending_top_block_amount,
+            &mut state.top_block_pending_payouts,
+        ),
+        Subsystem::BottomBlock => (
+            &mut claim_account.pending_bottom_block_amount,
+            &mut state.bottom_block_pending_payouts,
+        ),
+        Subsystem::FinalMining | Subsystem::FinalStaking => {
+            return err!(SallarError::InvalidDistributionBackend)
+        }
+    };
+    *user_pending_amount = user_pending_amount
+        .checked_add(reward_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    *total_pending_amount = total_pending_amount
+        .checked_add(reward_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    claim_account.exit(&crate::ID)?;
+
+    Ok(())
+}
+
+/// Records the reward received by a user in the reward stats account of the wallet owning the recipient token account.
+/// Nothing is recorded if the reward stats account of the wallet is not provided.
+fn record_user_reward<'info>(
+    subsystem: Subsystem,
+    accounts: &'info [AccountInfo<'info>],
+    recipient: &AccountInfo<'info>,
+    reward_amount: u64,
+) -> Result<()> {
+    if reward_amount == 0 {
+        return Ok(());
+    }
+    let Some(wallet) = token_account_owner(recipient) else {
+        return Ok(());
+    };
+
+    if let Some(mut stats_account) = find_user_reward_stats_account(accounts, &wallet) {
+        add_user_reward(&mut stats_account, subsystem, reward_amount)?;
+        stats_account.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
+
+/// Adds the reward received by a user to the lifetime reward statistics of the user's wallet.
+///
+/// ### Arguments
+///
+/// * `stats` - the reward statistics of the wallet,
+/// * `subsystem` - the subsystem which distributed the reward,
+/// * `reward_amount` - the reward received by the user.
+pub fn add_user_reward(
+    stats: &mut UserRewardStats,
+    subsystem: Subsystem,
+    reward_amount: u64,
+) -> Result<()> {
+    let reward_total = match subsystem {
+        Subsystem::TopBlock => &mut stats.top_block_reward_amount,
+        Subsystem::BottomBlock => &mut stats.bottom_block_reward_amount,
+        Subsystem::FinalMining => &mut stats.final_mining_reward_amount,
+        Subsystem::FinalStaking => &mut stats.final_staking_reward_amount,
+    };
+    *reward_total = reward_total
+        .checked_add(reward_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+/// Records a processed distribution batch in the global statistics.
+///
+/// ### Arguments
+///
+/// * `stats` - the global statistics,
+/// * `subsystem` - the subsystem which processed the batch,
+/// * `distributed_amount` - the amount of tokens distributed by the batch.
+pub fn record_distribution_batch(
+    stats: &mut GlobalStats,
+    subsystem: Subsystem,
+    distributed_amount: u64,
+) -> Result<()> {
+    let distributed_total = match subsystem {
+        Subsystem::TopBlock => &mut stats.top_block_distributed_amount,
+        Subsystem::BottomBlock => &mut stats.bottom_block_distributed_amount,
+        Subsystem::FinalMining => &mut stats.final_mining_distributed_amount,
+        Subsystem::FinalStaking => &mut stats.final_staking_distributed_amount,
+    };
+    *distributed_total = distributed_total
+        .checked_add(distributed_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    stats.processed_batches = stats
+        .processed_batches
+        .checked_add(1)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+
+    Ok(())
+}
+
+/// Records a solved block in the global statistics.
+///
+/// ### Arguments
+///
+/// * `stats` - the global statistics,
+/// * `block_kind` - the kind of the solved block,
+/// * `next_block_minted` - true if the block was switched to the next one and tokens were minted for it, false otherwise (the blocks collided).
+pub fn record_block_solution(
+    stats: &mut GlobalStats,
+    block_kind: BlockKind,
+    next_block_minted: bool,
+) -> Result<()> {
+    let blocks_solved = match block_kind {
+        BlockKind::Top => &mut stats.top_blocks_solved,
+        BlockKind::Bottom => &mut stats.bottom_blocks_solved,
+    };
+    *blocks_solved = blocks_solved
+        .checked_add(1)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    if next_block_minted {
+        stats.total_minted_amount = stats
+            .total_minted_amount
+            .checked_add(DUSTS_PER_BLOCK)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distribution_backend() {
+        let mut config = Config::default();
+        initialize_distribution_backends(&mut config);
+
+        assert_eq!(
+            distribution_backend(&config, Subsystem::TopBlock),
+            DistributionBackend::ClaimCredit
+        );
+        assert_eq!(
+            distribution_backend(&config, Subsystem::BottomBlock),
+            DistributionBackend::ClaimCredit
+        );
+        assert_eq!(
+            distribution_backend(&config, Subsystem::FinalMining),
+            DistributionBackend::DirectTransfer
+        );
+        assert_eq!(
+            distribution_backend(&config, Subsystem::FinalStaking),
+            DistributionBackend::DirectTransfer
+        );
+
+        set_distribution_backend(
+            &mut config,
+            Subsystem::BottomBlock,
+            DistributionBackend::Escrow,
+        );
+        assert_eq!(
+            distribution_backend(&config, Subsystem::BottomBlock),
+            DistributionBackend::Escrow
+        );
+        assert_eq!(
+            distribution_backend(&config, Subsystem::TopBlock),
+            DistributionBackend::ClaimCredit
+        );
+    }
+
+    fn empty_global_stats() -> GlobalStats {
+        GlobalStats {
+            bump: 0,
+            total_minted_amount: 0,
+            top_block_distributed_amount: 0,
+            bottom_block_distributed_amount: 0,
+            final_mining_distributed_amount: 0,
+            final_staking_distributed_amount: 0,
+            top_blocks_solved: 0,
+            bottom_blocks_solved: 0,
+            processed_batches: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_distribution_batch() {
+        let mut stats = empty_global_stats();
+
+        record_distribution_batch(&mut stats, Subsystem::TopBlock, 1).unwrap();
+        record_distribution_batch(&mut stats, Subsystem::BottomBlock, 2).unwrap();
+        record_distribution_batch(&mut stats, Subsystem::FinalMining, 3).unwrap();
+        record_distribution_batch(&mut stats, Subsystem::FinalStaking, 4).unwrap();
+        record_distribution_batch(&mut stats, Subsystem::TopBlock, 0).unwrap();
+
+        assert_eq!(stats.top_block_distributed_amount, 1);
+        assert_eq!(stats.bottom_block_distributed_amount, 2);
+        assert_eq!(stats.final_mining_distributed_amount, 3);
+        assert_eq!(stats.final_staking_distributed_amount, 4);
+        assert_eq!(stats.processed_batches, 5);
+    }
+
+    #[test]
+    fn test_record_block_solution() {
+        let mut stats = empty_global_stats();
+
+        record_block_solution(&mut stats, BlockKind::Top, true).unwrap();
+        record_block_solution(&mut stats, BlockKind::Bottom, true).unwrap();
+        record_block_solution(&mut stats, BlockKind::Bottom, false).unwrap();
+
+        assert_eq!(stats.top_blocks_solved, 1);
+        assert_eq!(stats.bottom_blocks_solved, 2);
+        assert_eq!(stats.total_minted_amount, 2 * DUSTS_PER_BLOCK);
+    }
+
+    #[test]
+    fn test_add_user_reward() {
+        let mut stats = UserRewardStats {
+            wallet: Pubkey::default(),
+            bump: 0,
+            top_block_reward_amount: 0,
+            bottom_block_reward_amount: 0,
+            final_mining_reward_amount: 0,
+            final_staking_reward_amount: 0,
+        };
+
+        add_user_reward(&mut stats, Subsystem::TopBlock, 1).unwrap();
+        add_user_reward(&mut stats, Subsystem::BottomBlock, 2).unwrap();
+        add_user_reward(&mut stats, Subsystem::FinalMining, 3).unwrap();
+        add_user_reward(&mut stats, Subsystem::FinalStaking, 4).unwrap();
+        add_user_reward(&mut stats, Subsystem::TopBlock, 5).unwrap();
+
+        assert_eq!(stats.top_block_reward_amount, 6);
+        assert_eq!(stats.bottom_block_reward_amount, 2);
+        assert_eq!(stats.final_mining_reward_amount, 3);
+        assert_eq!(stats.final_staking_reward_amount, 4);
+    }
+
+    #[test]
+    fn test_statistics_overflow() {
+        let mut global_stats = empty_global_stats();
+        global_stats.final_mining_distributed_amount = u64::MAX;
+        global_stats.total_minted_amount = u64::MAX;
+        let mut user_stats = UserRewardStats {
+            wallet: Pubkey::default(),
+            bump: 0,
+            top_block_reward_amount: 0,
+            bottom_block_reward_amount: u64::MAX,
+            final_mining_reward_amount: 0,
+            final_staking_reward_amount: 0,
+        };
+
+        assert_eq!(
+            record_distribution_batch(&mut global_stats, Subsystem::FinalMining, 1).unwrap_err(),
+            SallarError::ArithmeticOverflow.into()
+        );
+        assert_eq!(
+            record_block_solution(&mut global_stats, BlockKind::Top, true).unwrap_err(),
+            SallarError::ArithmeticOverflow.into()
+        );
+        assert_eq!(
+            add_user_reward(&mut user_stats, Subsystem::BottomBlock, 1).unwrap_err(),
+            SallarError::ArithmeticOverflow.into()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::error_code;
+use sallar_math::MathError;
 
 /// The enum defining all errors used by the contract.
 #[error_code]
@@ -47,4 +48,271 @@ pub enum SallarError {
     U64ConversionError = 20,
     #[msg("Illegal execution of set_blocks_collided function outside tests")]
     ExecutionOfSetBlocksCollidedFunctionOutsideTests = 21,
+    #[msg("Recipient account is not an initialized token account")]
+    RecipientAccountNotInitialized = 22,
+    #[msg("Minimum payout amount exceeds the allowed maximum")]
+    MinPayoutAmountTooHigh = 23,
+    #[msg("There is no pending authority to accept")]
+    MissingPendingAuthority = 24,
+    #[msg("Signer is not the pending authority")]
+    SignerIsNotPendingAuthority = 25,
+    #[msg("Signer set exceeds the maximum number of signers")]
+    SignerSetTooLarge = 26,
+    #[msg("Signer set threshold must be between 1 and the number of signers")]
+    InvalidSignerSetThreshold = 27,
+    #[msg("Signer set contains duplicated signer")]
+    SignerDuplicatedInSignerSet = 28,
+    #[msg("Quorum of registered signers not reached")]
+    SignerQuorumNotReached = 29,
+    #[msg("Treasury account does not match the registered treasury")]
+    InvalidTreasuryAccount = 30,
+    #[msg("Program is paused")]
+    ProgramPaused = 31,
+    #[msg("Admin controls have been renounced")]
+    AdminControlsRenounced = 32,
+    #[msg("Timelock is enabled, the change must be queued")]
+    TimelockEnabled = 33,
+    #[msg("Timelock is disabled, the change can be applied directly")]
+    TimelockDisabled = 34,
+    #[msg("Timelock delay is negative or exceeds the allowed maximum")]
+    InvalidTimelockDelay = 35,
+    #[msg("Timelock delay of the pending change has not elapsed yet")]
+    TimelockNotElapsed = 36,
+    #[msg("Account required to execute the pending change is missing")]
+    MissingAccountForPendingChange = 37,
+    #[msg("Block solution interval is out of the allowed range")]
+    InvalidBlockSolutionInterval = 38,
+    #[msg("Final staking interval is out of the allowed range")]
+    InvalidFinalStakingInterval = 39,
+    #[msg("Final staking pool division factor is out of the allowed range")]
+    InvalidFinalStakingPoolDivisionFactor = 40,
+    #[msg("Cluster time is out of the plausible range")]
+    InvalidClusterTime = 41,
+    #[msg("Distribution accounts are not empty")]
+    DistributionAccountsNotEmpty = 42,
+    #[msg("Denylist exceeds the maximum number of token accounts")]
+    DenylistFull = 43,
+    #[msg("Token account is already denylisted")]
+    AccountAlreadyDenylisted = 44,
+    #[msg("Token account is not denylisted")]
+    AccountNotDenylisted = 45,
+    #[msg("Recipient token account is denylisted")]
+    RecipientDenylisted = 46,
+    #[msg("Batch submitted after the maximum valid slot")]
+    BatchExpired = 47,
+    #[msg("Final staking or final mining account is not empty")]
+    FinalPoolsNotEmpty = 48,
+    #[msg("Reward fee exceeds the allowed maximum (500 basis points)")]
+    RewardFeeTooHigh = 49,
+    #[msg("Treasury token account is required to collect the reward fee")]
+    MissingFeeDestinationAccount = 50,
+    #[msg("Token account is not a foreign token account held by the blocks state account")]
+    InvalidForeignTokenAccount = 51,
+    #[msg("SLA threshold must be between 0 and 30 days")]
+    InvalidSlaThreshold = 52,
+    #[msg("Signer is not the guardian")]
+    SignerIsNotGuardian = 53,
+    #[msg("Block solving is halted")]
+    BlockHalted = 54,
+    #[msg("Required time interval between final mining calls not passed")]
+    FinalMiningAheadOfTime = 55,
+    #[msg("Final mining payout exceeds the maximum payout per call")]
+    FinalMiningPayoutTooHigh = 56,
+    #[msg("Final mining config is out of the allowed range")]
+    InvalidFinalMiningConfig = 57,
+    #[msg("Final staking is finalized")]
+    FinalStakingFinalized = 58,
+    #[msg("Final staking terminal config is out of the allowed range or the treasury is not set")]
+    InvalidFinalStakingTerminalConfig = 59,
+    #[msg("Treasury token account is required to sweep the final staking account")]
+    MissingTreasuryAccount = 60,
+    #[msg("Global statistics account is required once it is created")]
+    MissingGlobalStatsAccount = 61,
+    #[msg("Block history account and system program are required while block history is enabled")]
+    MissingBlockHistoryAccount = 62,
+    #[msg("Recipient account of the user info not found in remaining accounts")]
+    MissingRecipientAccount = 63,
+    #[msg("Recipient account is not a token account of the Sallar mint")]
+    RecipientAccountMintMismatch = 64,
+    #[msg("Chunk does not continue the solve batch in progress")]
+    InvalidSolveBatchChunk = 65,
+    #[msg("Chunk contains too many user info entries")]
+    SolveBatchChunkTooLarge = 66,
+    #[msg("Recipient account of the user info not found at the position of the user info in remaining accounts")]
+    RecipientAccountPositionMismatch = 67,
+    #[msg("User is duplicated in user info for top block")]
+    UserDuplicatedInUserInfoForTopBlock = 68,
+    #[msg("User is duplicated in user info for bottom block")]
+    UserDuplicatedInUserInfoForBottomBlock = 69,
+    #[msg("User rest exists, it must be distributed before the block is solved with rewards root")]
+    UserRestExistsButBlockIsSolvedWithRewardsRoot = 70,
+    #[msg("Merkle proof of the block reward is invalid")]
+    InvalidMerkleProof = 71,
+    #[msg("Claimed amount exceeds the unclaimed rewards of the block")]
+    BlockRewardsRootExceeded = 72,
+    #[msg("Batch approval of the solve coordinator not found in the preceding instruction")]
+    MissingCoordinatorApproval = 73,
+    #[msg("Batch approval of the solve coordinator is invalid")]
+    InvalidCoordinatorApproval = 74,
+    #[msg("Stake amount must be greater than 0")]
+    InvalidStakeAmount = 75,
+    #[msg("Unlocked amount exceeds the locked stake")]
+    InsufficientLockedStake = 76,
+    #[msg("Signer is not the owner of the staking token account")]
+    SignerIsNotStakeOwner = 77,
+    #[msg("Minimum required stake for bottom block is out of the allowed range")]
+    InvalidBottomBlockMinRequiredStake = 78,
+    #[msg("Block user requests account is required while the per-user requests cap of the block is set")]
+    MissingBlockUserRequestsAccount = 79,
+    #[msg("User exceeded the maximum number of requests in the block")]
+    UserRequestsPerBlockExceeded = 80,
+    #[msg("Block user requests account does not belong to the block the user is rewarded in")]
+    BlockUserRequestsAccountMismatch = 81,
+    #[msg("Recipient account is not the associated token account of the wallet")]
+    InvalidAssociatedTokenAccount = 82,
+    #[msg(
+        "Wallet account of the recipient associated token account not found in remaining accounts"
+    )]
+    MissingWalletAccount = 83,
+    #[msg("Associated token program and system program are required to create the recipient associated token account")]
+    MissingAssociatedTokenProgram = 84,
+    #[msg("Too many users did not receive all BPs of the block")]
+    RestCarryoversLimitExceeded = 85,
+    #[msg("Block participation commitment scheme is disabled")]
+    BlockParticipationCommitmentDisabled = 86,
+    #[msg("Block participation commitment not found for the block solution batch")]
+    MissingBlockParticipationCommitment = 87,
+    #[msg(
+        "Block participation commitment must be posted before the slot of the block solution batch"
+    )]
+    BlockParticipationCommitmentTooRecent = 88,
+    #[msg(
+        "User info of the block solution batch does not match the block participation commitment"
+    )]
+    BlockParticipationCommitmentMismatch = 89,
+    #[msg("Current blocks have no remaining balance to settle")]
+    NoCollisionRemainder = 90,
+    #[msg("Number of boost credits must be greater than 0")]
+    InvalidBoostCreditsAmount = 91,
+    #[msg("Boost credits account of the user with requests with boost is not provided")]
+    MissingBoostCreditsAccount = 92,
+    #[msg("User does not have enough boost credits for its requests with boost")]
+    InsufficientBoostCredits = 93,
+    #[msg("Block number is out of the range of blocks")]
+    BlockIndexOutOfBounds = 94,
+    #[msg("Final staking round is in progress")]
+    FinalStakingRoundInProgress = 95,
+    #[msg(
+        "Final staking terminal transition is required, the final_staking instruction must be used"
+    )]
+    FinalStakingTerminalTransitionRequired = 96,
+    #[msg("Claimed amount exceeds the unclaimed rewards of the final staking round")]
+    FinalStakingRewardsRootExceeded = 97,
+    #[msg("No final staking round is in progress")]
+    FinalStakingRoundNotInProgress = 98,
+    #[msg("Signer is neither the owner of the token account nor the solve coordinator")]
+    InvalidFinalStakingRegistrant = 99,
+    #[msg("Token account registered for final staking has no balance")]
+    EmptyFinalStakingRegistration = 100,
+    #[msg("Final staking registration of the user for the current round is not provided or already rewarded")]
+    MissingFinalStakingRegistration = 101,
+    #[msg("Final staking tiers must have increasing balance ages and multipliers between 1x and 10x (up to 8 tiers)")]
+    InvalidFinalStakingTiers = 102,
+    #[msg("Stake vault of the user restaking final staking rewards is not provided")]
+    MissingStakeVaultAccount = 103,
+    #[msg("Final staking round participants account is not provided while final staking snapshots are not required")]
+    MissingFinalStakingRoundParticipantsAccount = 104,
+    #[msg("Final staking round participants account does not belong to the current round")]
+    FinalStakingRoundParticipantsAccountMismatch = 105,
+    #[msg("User has already been rewarded in the current final staking round")]
+    FinalStakingParticipantAlreadyRewarded = 106,
+    #[msg("Too many users in a single final staking call")]
+    FinalStakingCallTooLarge = 107,
+    #[msg("Final staking reward part tolerance must not exceed 90000 basis points")]
+    InvalidFinalStakingRewardPartTolerance = 108,
+    #[msg("User reward part exceeds the user's share of the registered balance by more than the tolerance")]
+    RewardPartExceedsBalanceShare = 109,
+    #[msg("Final mining tiers must start at zero balance with increasing balances and rewards up to the maximum final mining reward (up to 8 tiers)")]
+    InvalidFinalMiningTiers = 110,
+    #[msg("Final mining tiers account is required once it is created")]
+    MissingFinalMiningTiersAccount = 111,
+    #[msg("Final mining budget must cover the maximum final mining reward and its window must be between 1 second and 30 days")]
+    InvalidFinalMiningBudget = 112,
+    #[msg("Final mining budget of the current window is exhausted")]
+    FinalMiningBudgetExhausted = 113,
+    #[msg("Final mining balance checkpoint is missing or too old")]
+    FinalMiningCheckpointStale = 114,
+    #[msg(
+        "Final mining balance of the request does not match the final mining balance checkpoint"
+    )]
+    FinalMiningBalanceNotCheckpointed = 115,
+    #[msg("User is duplicated in the user info for final mining")]
+    UserDuplicatedInUserInfoForFinalMining = 116,
+    #[msg("Final mining claim period participants account and system program are required while the final mining claim period is set")]
+    MissingFinalMiningClaimPeriodParticipantsAccount = 117,
+    #[msg("Final mining claim period participants account does not belong to the current claim period")]
+    FinalMiningClaimPeriodParticipantsAccountMismatch = 118,
+    #[msg("User has already received a final mining payout in the current claim period")]
+    FinalMiningAlreadyClaimedInPeriod = 119,
+    #[msg("Final mining claim period must be between 0 and 30 days")]
+    InvalidFinalMiningClaimPeriod = 120,
+    #[msg("Final mining coordinator is active, final mining epochs can be opened without the participation root only after the coordinator timeout")]
+    FinalMiningCoordinatorActive = 121,
+    #[msg("Final mining epoch is not the current one")]
+    FinalMiningEpochNotCurrent = 122,
+    #[msg("Final mining account's balance is below the lowest final mining tier")]
+    FinalMiningBalanceBelowTiers = 123,
+    #[msg("Arithmetic overflow in the token amount accounting")]
+    ArithmeticOverflow = 124,
+    #[msg("Arithmetic underflow in the token amount accounting")]
+    ArithmeticUnderflow = 125,
+    #[msg("Final mining emission projection must cover between 1 and 32 windows")]
+    InvalidFinalMiningEmissionProjection = 126,
+    #[msg("F64 value to be converted is NaN")]
+    F64IsNaN = 127,
+    #[msg("F64 value to be converted is infinite")]
+    F64IsInfinite = 128,
+    #[msg("F64 value to be converted is not integral")]
+    F64NotIntegral = 129,
+    #[msg("Blocks state is already migrated to the current version of its layout")]
+    BlocksStateAlreadyMigrated = 130,
+    #[msg("Blocks state is not migrated to the current version of its layout")]
+    BlocksStateVersionMismatch = 131,
+    #[msg("Recent solvers account is required once it is created")]
+    MissingRecentSolversAccount = 132,
+    #[msg("First block numbers must be valid block indexes and the first top block must be lower than the first bottom block by more than 1")]
+    InvalidFirstBlockNumbers = 133,
+    #[msg("Initial token distribution amount together with the tokens of all blocks exceeds the maximum supply")]
+    InvalidInitialTokenDistributionAmount = 134,
+    #[msg("State mirror account is required once it is created")]
+    MissingStateMirrorAccount = 135,
+    #[msg("Recipient account is not owned by the token program of the Sallar mint")]
+    RecipientAccountTokenProgramMismatch = 136,
+    #[msg("Recipient account is one of the program's token accounts")]
+    RecipientIsProgramTokenAccount = 137,
+    #[msg("Denylist account is required once it is created")]
+    MissingDenylistAccount = 138,
+    #[msg("Stake is registered for a final staking round which is not completed yet")]
+    StakeRegisteredForFinalStaking = 139,
+    #[msg("Final mining and final staking rewards can only be transferred directly")]
+    InvalidDistributionBackend = 140,
+    #[msg("Claim account of the user is required by the escrow distribution backend")]
+    MissingUserClaimAccount = 141,
+    #[msg("User has no pending rewards to withdraw")]
+    NoPendingRewards = 142,
+}
+
+impl From<MathError> for SallarError {
+    fn from(error: MathError) -> Self {
+        match error {
+            MathError::BlockIndexOutOfBounds => SallarError::BlockIndexOutOfBounds,
+            MathError::U64ConversionError => SallarError::U64ConversionError,
+            MathError::F64IsNaN => SallarError::F64IsNaN,
+            MathError::F64IsInfinite => SallarError::F64IsInfinite,
+            MathError::F64NotIntegral => SallarError::F64NotIntegral,
+            MathError::ArithmeticOverflow => SallarError::ArithmeticOverflow,
+            MathError::InvalidRegisteredBalance => SallarError::MissingFinalStakingRegistration,
+            MathError::ZeroRegisteredBalance => SallarError::RewardPartExceedsBalanceShare,
+        }
+    }
 }
@@ -0,0 +1,548 @@
+use anchor_lang::{
+    prelude::{borsh, event, AnchorDeserialize, AnchorSerialize, Result},
+    solana_program::pubkey::Pubkey,
+};
+
+use crate::{
+    account::{BlockKind, ConfigChange, FinalStakingTerminalMode},
+    error::SallarError,
+};
+
+/// Event emitted by the export_regulatory_report instruction, a snapshot of token supply and distribution figures attested by the program.
+/// Consists of the following attributes:
+/// * `authority` - the contract's owner who requested the report,
+/// * `slot` - the slot in which the report was produced,
+/// * `timestamp` - the cluster timestamp at which the report was produced,
+///
+/// * `total_supply` - the current supply of the mint,
+/// * `total_minted_amount` - the amount of tokens minted by the contract so far (block distributions and the initial token distribution),
+/// * `burned_amount` - the amount of tokens minted by the contract that are no longer part of the supply, i.e. burned by the holders,
+///
+/// * `top_block_distributed_amount` - the amount of tokens released from top blocks so far (rewards, reward fees and surpluses),
+/// * `bottom_block_distributed_amount` - the amount of tokens released from bottom blocks so far (rewards, reward fees and surpluses),
+/// * `top_block_pending_payouts` - the total amount of top block rewards accumulated in users' claim accounts and not transferred yet,
+/// * `bottom_block_pending_payouts` - the total amount of bottom block rewards accumulated in users' claim accounts and not transferred yet,
+///
+/// * `distribution_top_block_balance` - the balance of the top block distribution account,
+/// * `distribution_bottom_block_balance` - the balance of the bottom block distribution account,
+/// * `final_staking_balance` - the balance of the final staking account,
+/// * `final_mining_balance` - the balance of the final mining account,
+/// * `treasury_balance` - the balance of the registered treasury token account, None if no treasury is registered.
+#[event]
+pub struct RegulatoryReportEvent {
+    pub authority: Pubkey,
+    pub slot: u64,
+    pub timestamp: i64,
+
+    pub total_supply: u64,
+    pub total_minted_amount: u64,
+    pub burned_amount: u64,
+
+    pub top_block_distributed_amount: u64,
+    pub bottom_block_distributed_amount: u64,
+    pub top_block_pending_payouts: u64,
+    pub bottom_block_pending_payouts: u64,
+
+    pub distribution_top_block_balance: u64,
+    pub distribution_bottom_block_balance: u64,
+    pub final_staking_balance: u64,
+    pub final_mining_balance: u64,
+    pub treasury_balance: Option<u64>,
+}
+
+/// Struct defining the amounts settled for a single user by a block solution batch.
+/// Consists of the following attributes:
+/// * `user` - the token account the amounts were settled for,
+/// * `block_number` - the number of the block the amounts were released from,
+/// * `settled_amount` - the amount of tokens released from the block for the user (including the reward fee and the reward accumulated in the user's claim account),
+/// * `paid_amount` - the amount of tokens transferred to the user.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct UserPaidAmount {
+    pub user: Pubkey,
+    pub block_number: u64,
+    pub settled_amount: u64,
+    pub paid_amount: u64,
+}
+
+/// Struct defining the reward of a single user split into the part for the requests without boost and the part for the requests with boost,
+/// as calculated from the user's requests (before the reward is limited to the available BP of the block).
+/// The amount transferred to the user is allocated from the BPs following the rounding policy of the blocks (see allocate_reward_from_block),
+/// so it may differ from the sum of the parts by the rounding of the parts.
+/// Consists of the following attributes:
+/// * `bp_no_boost` - the number of BPs for the requests without boost,
+/// * `bp_boost` - the number of BPs for the requests with boost,
+/// * `amount_no_boost` - the amount of tokens for the requests without boost,
+/// * `amount_boost` - the amount of tokens for the requests with boost.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RewardBreakdown {
+    pub bp_no_boost: u64,
+    pub bp_boost: u64,
+    pub amount_no_boost: u64,
+    pub amount_boost: u64,
+}
+
+impl RewardBreakdown {
+    /// The total number of BPs of the reward.
+    pub fn bp(&self) -> Result<u64> {
+        Ok(self
+            .bp_no_boost
+            .checked_add(self.bp_boost)
+            .ok_or(SallarError::ArithmeticOverflow)?)
+    }
+
+    /// The total amount of tokens of the reward.
+    pub fn amount(&self) -> Result<u64> {
+        Ok(self
+            .amount_no_boost
+            .checked_add(self.amount_boost)
+            .ok_or(SallarError::ArithmeticOverflow)?)
+    }
+}
+
+impl From<sallar_math::RewardBreakdown> for RewardBreakdown {
+    fn from(breakdown: sallar_math::RewardBreakdown) -> Self {
+        Self {
+            bp_no_boost: breakdown.bp_no_boost,
+            bp_boost: breakdown.bp_boost,
+            amount_no_boost: breakdown.amount_no_boost,
+            amount_boost: breakdown.amount_boost,
+        }
+    }
+}
+
+/// Enum defining the subsystems monitored by the SLA.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlaSubsystem {
+    TopBlock,
+    BottomBlock,
+    FinalStaking,
+}
+
+/// Event emitted when a block solution or a final staking round is completed later than the SLA threshold allows.
+/// Consists of the following attributes:
+/// * `subsystem` - the subsystem which breached the SLA,
+/// * `delay_seconds` - the delay (in seconds) between the moment the block solution or the final staking round became eligible and its completion,
+/// * `threshold_seconds` - the SLA threshold (in seconds) in force,
+/// * `timestamp` - the timestamp of the completion.
+#[event]
+pub struct SlaBreached {
+    pub subsystem: SlaSubsystem,
+    pub delay_seconds: i64,
+    pub threshold_seconds: i64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a block is solved and the current block of its kind is switched to the next one.
+/// Consists of the following attributes:
+/// * `block_kind` - the kind of the solved block (top or bottom),
+/// * `block_number` - the number of the solved block,
+/// * `solution_timestamp` - the timestamp at which the block was solved,
+/// * `distributed_amount` - the total amount of dust released from the solved block,
+/// * `next_block_number` - the number of the new current block,
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which solved the block.
+#[event]
+pub struct BlockSolved {
+    pub block_kind: BlockKind,
+    pub block_number: u64,
+    pub solution_timestamp: i64,
+    pub distributed_amount: u64,
+    pub next_block_number: u64,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted once per block solution batch of solve_top_block and solve_bottom_block with the exact amounts settled for every user of the batch.
+/// Consists of the following attributes:
+/// * `block_kind` - the kind of the solved blocks (top or bottom),
+/// * `solve_batch_sequence` - the sequence number of the block solution batch,
+/// * `paid_amounts` - the amounts settled for the users in the order of processing (a user settling a rest carryover and receiving a reward for its own entry is listed twice).
+#[event]
+pub struct SolveBatchSettled {
+    pub block_kind: BlockKind,
+    pub solve_batch_sequence: u64,
+    pub paid_amounts: Vec<UserPaidAmount>,
+}
+
+/// Event emitted when the remaining balances of the current blocks are moved to the final pools after blocks have collided.
+/// Consists of the following attributes:
+/// * `top_block_number` - the number of the current top block,
+/// * `top_block_amount` - the amount of tokens moved from top block distribution account to the final mining account,
+/// * `bottom_block_number` - the number of the current bottom block,
+/// * `bottom_block_amount` - the amount of tokens moved from bottom block distribution account to the final staking account.
+#[event]
+pub struct CollisionRemainderSettled {
+    pub top_block_number: u64,
+    pub top_block_amount: u64,
+    pub bottom_block_number: u64,
+    pub bottom_block_amount: u64,
+}
+
+/// Event emitted when top block reward is transferred to a user.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `block_number` - the number of the top block the reward comes from,
+/// * `bp` - the number of BPs of the block assigned to the user,
+/// * `amount` - the amount of tokens transferred to the user (including rewards previously accumulated in the user's claim account),
+/// * `reward_breakdown` - the split of the user's reward into the parts without and with boost (None for the rest BPs carried over from the previous block),
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which paid the reward.
+#[event]
+pub struct TopBlockRewardPaid {
+    pub user: Pubkey,
+    pub block_number: u64,
+    pub bp: u64,
+    pub amount: u64,
+    pub reward_breakdown: Option<RewardBreakdown>,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted when a top block is solved in the claim mode, i.e. the Merkle root of its rewards is recorded instead of transferring them.
+/// Consists of the following attributes:
+/// * `block_number` - the number of the solved top block,
+/// * `merkle_root` - the Merkle root of the (user, amount) pairs of the block,
+/// * `total_amount` - the amount of tokens reserved for the claims,
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which recorded the root.
+#[event]
+pub struct BlockRewardsRootRecorded {
+    pub block_number: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted when top block reward is claimed with a Merkle proof.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `block_number` - the number of the top block the reward comes from,
+/// * `amount` - the claimed amount (including the reward fee),
+/// * `payout_amount` - the amount of tokens transferred to the user (including rewards previously accumulated in the user's claim account).
+#[event]
+pub struct BlockRewardClaimed {
+    pub user: Pubkey,
+    pub block_number: u64,
+    pub amount: u64,
+    pub payout_amount: u64,
+}
+
+/// Event emitted when the rewards pending in a user's claim account are withdrawn.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the rewards,
+/// * `top_block_amount` - the amount of top block rewards transferred to the user,
+/// * `bottom_block_amount` - the amount of bottom block rewards transferred to the user.
+#[event]
+pub struct PendingRewardsWithdrawn {
+    pub user: Pubkey,
+    pub top_block_amount: u64,
+    pub bottom_block_amount: u64,
+}
+
+/// Event emitted when bottom block reward is transferred to a user.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `block_number` - the number of the bottom block the reward comes from,
+/// * `bp` - the number of BPs of the block assigned to the user,
+/// * `amount` - the amount of tokens transferred to the user (including rewards previously accumulated in the user's claim account),
+/// * `reward_breakdown` - the split of the user's reward into the parts without and with boost (None for the rest BPs carried over from the previous block),
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which paid the reward.
+#[event]
+pub struct BottomBlockRewardPaid {
+    pub user: Pubkey,
+    pub block_number: u64,
+    pub bp: u64,
+    pub amount: u64,
+    pub reward_breakdown: Option<RewardBreakdown>,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted when an invalid user info entry is skipped by a block solution batch processed in the non-strict mode.
+/// Consists of the following attributes:
+/// * `user` - the token account of the skipped entry,
+/// * `block_kind` - the kind of the solved block,
+/// * `user_entry_index` - the index of the skipped entry in the user info of the batch,
+/// * `error_code` - the code of the error the entry was skipped for,
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which skipped the entry.
+#[event]
+pub struct UserEntrySkipped {
+    pub user: Pubkey,
+    pub block_kind: BlockKind,
+    pub user_entry_index: u32,
+    pub error_code: u32,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted when the rest BPs of a user are carried over to the next block, because the block is solved before the user receives all BPs.
+/// The rest BPs are paid at the start of the next block of the same kind.
+/// Consists of the following attributes:
+/// * `user` - the token account of the user,
+/// * `block_kind` - the kind of the solved block,
+/// * `block_number` - the number of the solved block,
+/// * `rest_bp` - the number of BPs carried over to the next block,
+/// * `solve_batch_sequence` - the sequence number of the block solution batch which carried over the rest BPs.
+#[event]
+pub struct RestCarriedOver {
+    pub user: Pubkey,
+    pub block_kind: BlockKind,
+    pub block_number: u64,
+    pub rest_bp: u64,
+    pub solve_batch_sequence: u64,
+}
+
+/// Event emitted when the commitment of the next block solution batch is posted.
+/// Consists of the following attributes:
+/// * `block_kind` - the kind of the block the batch solves,
+/// * `block_number` - the number of the current block of the kind,
+/// * `commitment` - the hash of the batch to reveal,
+/// * `slot` - the slot the commitment was posted in.
+#[event]
+pub struct BlockParticipationCommitted {
+    pub block_kind: BlockKind,
+    pub block_number: u64,
+    pub commitment: [u8; 32],
+    pub slot: u64,
+}
+
+/// Event emitted when a user locks tokens in its stake vault.
+/// Consists of the following attributes:
+/// * `user` - the token account the stake belongs to,
+/// * `amount` - the amount of tokens locked,
+/// * `locked_amount` - the total amount of tokens locked by the user after the change.
+#[event]
+pub struct StakeLocked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub locked_amount: u64,
+}
+
+/// Event emitted when a user unlocks tokens from its stake vault.
+/// Consists of the following attributes:
+/// * `user` - the token account the stake belongs to,
+/// * `amount` - the amount of tokens unlocked,
+/// * `locked_amount` - the total amount of tokens locked by the user after the change.
+#[event]
+pub struct StakeUnlocked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub locked_amount: u64,
+}
+
+/// Event emitted when a user enables or disables restaking of its final staking rewards.
+/// Consists of the following attributes:
+/// * `user` - the token account the stake belongs to,
+/// * `auto_restake` - true if final staking rewards of the user are deposited to its stake vault, false if they are transferred to the token account.
+#[event]
+pub struct FinalStakingAutoRestakeSet {
+    pub user: Pubkey,
+    pub auto_restake: bool,
+}
+
+/// Event emitted when a user buys boost credits.
+/// Consists of the following attributes:
+/// * `user` - the token account the boost credits belong to,
+/// * `credits` - the number of boost credits bought,
+/// * `burned_amount` - the amount of tokens burned for the boost credits,
+/// * `credits_balance` - the number of boost credits of the user after the purchase.
+#[event]
+pub struct BoostCreditsPurchased {
+    pub user: Pubkey,
+    pub credits: u64,
+    pub burned_amount: u64,
+    pub credits_balance: u64,
+}
+
+/// Event emitted when final mining reward is transferred to a user.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `amount` - the amount of tokens transferred to the user.
+#[event]
+pub struct FinalMiningRewardPaid {
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+/// Event emitted when the final mining account's balance is checkpointed.
+/// Consists of the following attributes:
+/// * `balance` - the final mining account's balance,
+/// * `slot` - the slot the balance is recorded at.
+#[event]
+pub struct FinalMiningBalanceCheckpointed {
+    pub balance: u64,
+    pub slot: u64,
+}
+
+/// Event emitted when a final mining epoch is opened, either by the coordinator publishing its participation root or by anyone once the coordinator is inactive.
+/// Consists of the following attributes:
+/// * `epoch_index` - the index of the opened epoch,
+/// * `merkle_root` - the Merkle root of the token accounts participating in the epoch (all zeros if the epoch is open),
+/// * `open` - true if the epoch is opened without the participation root, false otherwise,
+/// * `timestamp` - the timestamp at which the epoch was opened.
+#[event]
+pub struct FinalMiningEpochOpened {
+    pub epoch_index: u64,
+    pub merkle_root: [u8; 32],
+    pub open: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when final mining reward is claimed by a participant of a final mining epoch.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `epoch_index` - the index of the final mining epoch the reward comes from,
+/// * `amount` - the claimed amount,
+/// * `payout_amount` - the amount of tokens transferred to the user.
+#[event]
+pub struct FinalMiningRewardClaimed {
+    pub user: Pubkey,
+    pub epoch_index: u64,
+    pub amount: u64,
+    pub payout_amount: u64,
+}
+
+/// Event emitted when final staking reward is transferred to a user.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `round_index` - the index of the round the reward is paid in,
+/// * `reward_part` - the part of the round's prize pool requested by the user in scaled reward parts (1_000_000_000 is the whole prize pool),
+/// * `amount` - the amount of tokens transferred to the user.
+#[event]
+pub struct FinalStakingRewardPaid {
+    pub user: Pubkey,
+    pub round_index: u64,
+    pub reward_part: u64,
+    pub amount: u64,
+}
+
+/// Event emitted when final staking reward is deposited to the stake vault of a user restaking its rewards instead of the user's token account.
+/// Consists of the following attributes:
+/// * `user` - the token account the stake belongs to,
+/// * `round_index` - the index of the round the reward is paid in,
+/// * `amount` - the amount of tokens deposited to the stake vault,
+/// * `locked_amount` - the total amount of tokens locked by the user after the deposit.
+#[event]
+pub struct FinalStakingRewardRestaked {
+    pub user: Pubkey,
+    pub round_index: u64,
+    pub amount: u64,
+    pub locked_amount: u64,
+}
+
+/// Event emitted when the final staking pool can no longer fund a meaningful round and the terminal transition is performed.
+/// Consists of the following attributes:
+/// * `terminal_mode` - the way the remaining final staking account balance was handled,
+/// * `amount` - the remaining final staking account balance distributed in the final round or swept to the treasury,
+/// * `timestamp` - the timestamp at which final staking was finalized.
+#[event]
+pub struct FinalStakingFinalized {
+    pub terminal_mode: FinalStakingTerminalMode,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a final staking round is started, i.e. its prize pool is reserved.
+/// Consists of the following attributes:
+/// * `round_index` - the index of the started round (rounds are numbered from 1),
+/// * `pool_in_round` - the prize pool (amount of tokens) to be distributed in the round,
+/// * `final_round` - true if the round distributes the whole remaining final staking account balance, false otherwise,
+/// * `timestamp` - the timestamp at which the round was started.
+#[event]
+pub struct FinalStakingRoundStarted {
+    pub round_index: u64,
+    pub pool_in_round: u64,
+    pub final_round: bool,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a final staking round is completed, i.e. its whole prize pool is distributed.
+/// Consists of the following attributes:
+/// * `round_index` - the index of the completed round,
+/// * `pool_in_round` - the prize pool (amount of tokens) of the round,
+/// * `distributed_amount` - the total amount of tokens distributed in the round,
+/// * `timestamp` - the timestamp at which the round was completed.
+#[event]
+pub struct FinalStakingRoundCompleted {
+    pub round_index: u64,
+    pub pool_in_round: u64,
+    pub distributed_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a final staking round is opened in the claim mode, i.e. the Merkle root of its rewards is recorded instead of transferring them.
+/// Consists of the following attributes:
+/// * `round_index` - the index of the opened round,
+/// * `merkle_root` - the Merkle root of the (user, amount) pairs of the round,
+/// * `total_amount` - the amount of tokens reserved for the claims.
+#[event]
+pub struct FinalStakingRewardsRootRecorded {
+    pub round_index: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+}
+
+/// Event emitted when final staking reward is claimed with a Merkle proof.
+/// Consists of the following attributes:
+/// * `user` - the token account receiving the reward,
+/// * `round_index` - the index of the final staking round the reward comes from,
+/// * `amount` - the claimed amount,
+/// * `payout_amount` - the amount of tokens transferred to the user (including rewards previously accumulated in the user's claim account).
+#[event]
+pub struct FinalStakingRewardClaimed {
+    pub user: Pubkey,
+    pub round_index: u64,
+    pub amount: u64,
+    pub payout_amount: u64,
+}
+
+/// Event emitted when a user registers the balance snapshot for a final staking round.
+/// Consists of the following attributes:
+/// * `user` - the token account the snapshot belongs to,
+/// * `round_index` - the index of the final staking round the snapshot is registered for,
+/// * `balance` - the registered balance of the token account,
+/// * `weighted_balance` - the registered balance weighted by the multiplier of the user's final staking tier,
+/// * `registered_balance` - the total weighted balance of the snapshots registered for the round after the registration.
+#[event]
+pub struct FinalStakingRegistered {
+    pub user: Pubkey,
+    pub round_index: u64,
+    pub balance: u64,
+    pub weighted_balance: u64,
+    pub registered_balance: u64,
+}
+
+/// Event emitted when the contract's owner is changed, either directly, by accepting the proposed ownership or by executing a queued change.
+/// Consists of the following attributes:
+/// * `signer` - the signer of the transaction transferring the ownership,
+/// * `previous_authority` - the contract's owner before the change,
+/// * `new_authority` - the new contract's owner,
+/// * `timestamp` - the timestamp of the change.
+#[event]
+pub struct AuthorityChanged {
+    pub signer: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Event emitted when a contract's parameter (or the proposed authority) is changed, either directly or by executing a queued change.
+/// Consists of the following attributes:
+/// * `signer` - the signer of the transaction applying the change,
+/// * `previous_values` - the values of the changed parameters before the change, unset optional values are represented by the default public key,
+/// * `new_values` - the values of the changed parameters after the change,
+/// * `timestamp` - the timestamp of the change.
+#[event]
+pub struct ConfigChanged {
+    pub signer: Pubkey,
+    pub previous_values: ConfigChange,
+    pub new_values: ConfigChange,
+    pub timestamp: i64,
+}
+
+/// Event emitted when the blocks state is migrated to the current version of its layout.
+/// Consists of the following attributes:
+/// * `signer` - the signer of the transaction migrating the state,
+/// * `previous_version` - the version of the layout before the migration,
+/// * `new_version` - the version of the layout after the migration,
+/// * `space` - the space of the blocks state account after the migration.
+#[event]
+pub struct BlocksStateMigrated {
+    pub signer: Pubkey,
+    pub previous_version: u8,
+    pub new_version: u8,
+    pub space: u64,
+}
@@ -1,23 +1,23 @@
 //! Sallar program
 
-use anchor_lang::{
-    err,
-    prelude::*,
-    program,
-    solana_program::{pubkey::Pubkey, sysvar::Sysvar},
-};
-use anchor_spl::token;
+use anchor_lang::{err, prelude::*, program, solana_program::pubkey::Pubkey};
 
+use account::{
+    BlockKind, BlockSolutionSimulation, BlockSolver, BlockStateSummary, BlocksState, ConfigChange,
+    DistributionBackend, FinalMiningEmissionProjection, FinalMiningTier, FinalStakingTerminalMode,
+    FinalStakingTier, InvariantsReport, ParticipationCommitment, RestCarryover, SolveBlockResult,
+    Subsystem,
+};
 use context::*;
 
 pub mod account;
 pub mod context;
+pub mod distribution;
 pub mod error;
+pub mod event;
 pub mod token_math;
 pub mod utils;
 
-const FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR: u64 = 1000;
-
 /// set seeds for pda accounts
 const MINT_SEED: &str = "sallar";
 const BLOCKS_STATE_SEED: &str = "blocks_state";
@@ -25,26 +25,119 @@ const DISTRIBUTION_TOP_BLOCK_SEED: &str = "distribution_top_block";
 const DISTRIBUTION_BOTTOM_BLOCK_SEED: &str = "distribution_bottom_block";
 const FINAL_STAKING_ACCOUNT_SEED: &str = "final_staking";
 const FINAL_MINING_ACCOUNT_SEED: &str = "final_mining";
+const USER_CLAIM_ACCOUNT_SEED: &str = "user_claim";
+const SIGNER_SET_SEED: &str = "signer_set";
+const PENDING_CHANGE_SEED: &str = "pending_change";
+const DENYLIST_SEED: &str = "denylist";
+const GLOBAL_STATS_SEED: &str = "global_stats";
+const BLOCK_HISTORY_SEED: &str = "block_history";
+const BLOCK_USER_REQUESTS_SEED: &str = "block_user_requests";
+const USER_REWARD_STATS_SEED: &str = "user_reward_stats";
+const BLOCK_REWARDS_ROOT_SEED: &str = "block_rewards_root";
+const BLOCK_REWARD_CLAIM_SEED: &str = "block_reward_claim";
+const STAKE_LOCK_SEED: &str = "stake_lock";
+const STAKE_VAULT_SEED: &str = "stake_vault";
+const BOOST_CREDITS_SEED: &str = "boost_credits";
+const FINAL_STAKING_ROUND_SEED: &str = "final_staking_round";
+const FINAL_STAKING_REWARDS_ROOT_SEED: &str = "final_staking_rewards_root";
+const FINAL_STAKING_REWARD_CLAIM_SEED: &str = "final_staking_reward_claim";
+const FINAL_STAKING_REGISTRATION_SEED: &str = "final_staking_registration";
+const FINAL_STAKING_TIERS_SEED: &str = "final_staking_tiers";
+const FINAL_STAKING_ROUND_PARTICIPANTS_SEED: &str = "final_staking_round_participants";
+const FINAL_MINING_TIERS_SEED: &str = "final_mining_tiers";
+const FINAL_MINING_CLAIM_PERIOD_PARTICIPANTS_SEED: &str = "final_mining_claim_period_participants";
+const FINAL_MINING_EPOCH_SEED: &str = "final_mining_epoch";
+const FINAL_MINING_CLAIM_SEED: &str = "final_mining_claim";
+const CONFIG_SEED: &str = "config";
+const RECENT_SOLVERS_SEED: &str = "recent_solvers";
+const STATE_MIRROR_SEED: &str = "state_mirror";
 
 declare_id!("ALLdaozmHS1MTT2dMtVUW6LUbDeJGNAMAxU8q9wN6Nny");
 
 /// This program is used to mint and distribute Sallar tokens.
 #[program]
 pub mod sallar {
+    use distribution::{
+        distribute_reward, distribute_reward_to, distribution_backend,
+        initialize_distribution_backends, record_block_solution, record_distribution_batch,
+        transfer_accumulated_fee, DistributionSource,
+    };
     use error::SallarError;
+    use event::{
+        BlockParticipationCommitted, BlockRewardClaimed, BlockRewardsRootRecorded,
+        BlocksStateMigrated, BoostCreditsPurchased, BottomBlockRewardPaid,
+        CollisionRemainderSettled, FinalMiningBalanceCheckpointed, FinalMiningRewardClaimed,
+        FinalMiningRewardPaid, FinalStakingAutoRestakeSet, FinalStakingRegistered,
+        FinalStakingRewardClaimed, FinalStakingRewardPaid, FinalStakingRewardRestaked,
+        FinalStakingRewardsRootRecorded, FinalStakingRoundCompleted, FinalStakingRoundStarted,
+        PendingRewardsWithdrawn, RegulatoryReportEvent, SlaSubsystem, SolveBatchSettled,
+        StakeLocked, StakeUnlocked, TopBlockRewardPaid, UserPaidAmount,
+    };
     use token_math::{
-        calculate_bottom_bp_with_boost, calculate_bottom_bp_without_boost, calculate_dust_per_bp,
-        calculate_max_bp, calculate_single_reward, calculate_top_bp_with_boost,
-        calculate_user_reward_bottom_block, calculate_user_reward_top_block, DUSTS_PER_BLOCK,
-        TOKEN_AMOUNT_SCALING_FACTOR,
+        calculate_bottom_blocks_minted_amount, calculate_bottom_bp_with_given_boost,
+        calculate_bottom_bp_without_boost, calculate_final_mining_reward,
+        calculate_top_blocks_minted_amount, calculate_user_reward_breakdown_bottom_block,
+        calculate_user_reward_breakdown_top_block, calculate_weighted_balance,
+        convert_scaled_reward_parts_to_f64, project_final_mining_emission, DUSTS_PER_BLOCK,
+        FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR,
+        INITIAL_TOKEN_DISTRIBUTION_AMOUNT, REWARD_PARTS_SCALING_FACTOR,
     };
+    #[cfg(feature = "shadow-reads")]
+    use utils::assert_reward_parts_shadow_consistent;
     use utils::{
-        blocks_collided, blocks_solution_required_interval_elapsed, blocks_solved,
-        bottom_block_not_solved, convert_f64_to_u64, convert_u64_to_f64,
-        final_staking_required_interval_elapsed, initial_token_distribution_not_performed_yet,
-        mint_tokens, set_token_metadata, switch_bottom_block_to_next_one_if_applicable,
-        switch_top_block_to_next_one_if_applicable, top_block_not_solved, transfer_tokens,
-        update_blocks_collided, valid_owner, valid_signer,
+        admin_controls_not_renounced, advance_solve_batch_cursor, allocate_reward_from_block,
+        append_block_history, batch_not_stale, block_history_provided, block_not_halted,
+        block_participation_revealed, block_user_requests_provided, blocks_collided,
+        blocks_solution_required_interval_elapsed, blocks_solved, bottom_block_not_solved,
+        bottom_block_params, bottom_block_within_bounds, burn_tokens_from_user,
+        cache_bottom_block_params, cache_top_block_params, calculate_block_reward_leaf,
+        calculate_block_state_summary, calculate_distribution_surplus,
+        calculate_final_mining_participation_leaf, calculate_final_staking_transfer,
+        calculate_invariants_report, close_token_account, collision_remainder_exists,
+        consume_boost_credits, consume_final_staking_registration, current_cluster_timestamp,
+        current_config_values, denylist_provided, deserialize_blocks_state_for_migration,
+        distribution_accounts_empty, emit_authority_changed, emit_config_changed,
+        final_mining_balances_checkpointed, final_mining_claim_period_index,
+        final_mining_claim_period_participants_provided, final_mining_coordinator_inactive,
+        final_mining_payout_within_limit, final_mining_required_interval_elapsed,
+        final_mining_tiers, final_mining_tiers_provided, final_pools_empty,
+        final_staking_not_finalized, final_staking_required_interval_elapsed,
+        final_staking_round_in_progress, final_staking_round_participants_provided,
+        final_staking_terminal_transition_required, final_staking_tier_multiplier_bps,
+        finalize_final_staking, find_recipient_account, find_rest_carryover_entries,
+        find_user_account, find_user_restake_accounts, find_user_stake_lock_account,
+        fit_block_user_requests, fit_final_mining_claim_period_participants,
+        fit_final_staking_round_participants, foreign_token_account, global_stats_provided,
+        grow_account, has_duplicated_users, initial_token_distribution_not_performed_yet,
+        migrate_blocks_state, mint_tokens, pending_change_executable,
+        prepare_associated_token_account, program_not_paused, push_rest_carryover,
+        recent_solvers_provided, recipient_not_denylisted, record_final_mining_budget,
+        record_final_mining_claimant, record_final_staking_participant, record_final_staking_round,
+        record_recent_solvers, record_sla_delay, record_user_requests, remove_mint_authority,
+        reward_part_within_balance_share, set_block_halted, set_token_metadata,
+        simulate_block_solution, skip_user_entry, stake_lock_balance,
+        stake_not_registered_for_final_staking, start_final_mining_epoch, state_mirror_provided,
+        switch_bottom_block_to_next_one_if_applicable, switch_top_block_to_next_one_if_applicable,
+        sync_state_mirror, timelock_disabled, timelock_enabled, top_block_not_solved,
+        top_block_params, transfer_tokens, transfer_tokens_from_blocks_state,
+        transfer_tokens_from_stake_vault, transfer_tokens_from_user, update_blocks_collided,
+        user_request_for_unsolved_block, valid_block_solution_interval,
+        valid_bottom_block_min_required_stake, valid_config_change, valid_distribution_backend,
+        valid_final_mining_budget, valid_final_mining_claim_period, valid_final_mining_config,
+        valid_final_mining_tiers, valid_final_staking_config, valid_final_staking_registrant,
+        valid_final_staking_reward_part_tolerance, valid_final_staking_terminal_config,
+        valid_final_staking_tiers, valid_guardian, valid_initial_schedule, valid_min_payout_amount,
+        valid_owner, valid_owner_or_signer_quorum, valid_participation_committer,
+        valid_pending_authority, valid_recipient_account, valid_recipient_accounts,
+        valid_reward_fee, valid_signer, valid_signer_set, valid_sla_threshold, valid_solver,
+        valid_timelock_delay, verify_merkle_proof, with_user_entry_context,
+        AssociatedTokenAccountCreation, BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS,
+        BLOCKS_STATE_VERSION, BOOST_CREDIT_PRICE, DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS,
+        DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE, DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS,
+        DEFAULT_FINAL_MINING_INTERVAL_SECONDS, DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL,
+        DEFAULT_FINAL_STAKING_INTERVAL_SECONDS, DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND,
+        DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER, DEFAULT_FIRST_TOP_BLOCK_NUMBER,
+        MAX_FINAL_MINING_PROJECTION_WINDOWS, MAX_USERS_PER_FINAL_STAKING_CALL,
     };
 
     use super::*;
@@ -58,42 +151,76 @@ pub mod sallar {
     /// * `token_metadata_name` - token's name to set in metadata,
     /// * `token_metadata_symbol` - token's symbol to set in metadata,
     /// * `token_metadata_uri` - token's uri to set in metadata,
+    /// * `first_top_block_number` - the top block number to start with, 1 (mainnet) if not provided,
+    /// * `first_bottom_block_number` - the bottom block number to start with, 470 000 (mainnet) if not provided,
+    /// * `initial_token_distribution_amount` - the amount of tokens minted by the initial token distribution, 2 600 000 000 tokens (mainnet) if not provided.
+    ///
+    /// Deployments with shorter schedules (e.g. devnet or staging) start with closer block numbers, so the blocks collide sooner.
     #[access_control(valid_signer(&ctx.accounts.signer))]
     pub fn initialize(
         ctx: Context<InitializeContext>,
         token_metadata_name: String,
         token_metadata_symbol: String,
         token_metadata_uri: String,
+        first_top_block_number: Option<u64>,
+        first_bottom_block_number: Option<u64>,
+        initial_token_distribution_amount: Option<u64>,
     ) -> Result<()> {
-        let program_id = id();
-        let (_, mint_nonce) = Pubkey::find_program_address(&[MINT_SEED.as_bytes()], &program_id);
-        let (_, blocks_state_nonce) =
-            Pubkey::find_program_address(&[BLOCKS_STATE_SEED.as_bytes()], &program_id);
-        let (_, top_block_nonce) =
-            Pubkey::find_program_address(&[DISTRIBUTION_TOP_BLOCK_SEED.as_bytes()], &program_id);
-        let (_, bottom_block_nonce) =
-            Pubkey::find_program_address(&[DISTRIBUTION_BOTTOM_BLOCK_SEED.as_bytes()], &program_id);
-        let (_, final_staking_account_nonce) =
-            Pubkey::find_program_address(&[FINAL_STAKING_ACCOUNT_SEED.as_bytes()], &program_id);
-        let (_, final_mining_account_nonce) =
-            Pubkey::find_program_address(&[FINAL_MINING_ACCOUNT_SEED.as_bytes()], &program_id);
+        let first_top_block_number =
+            first_top_block_number.unwrap_or(DEFAULT_FIRST_TOP_BLOCK_NUMBER);
+        let first_bottom_block_number =
+            first_bottom_block_number.unwrap_or(DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER);
+        let initial_token_distribution_amount =
+            initial_token_distribution_amount.unwrap_or(INITIAL_TOKEN_DISTRIBUTION_AMOUNT);
+        valid_initial_schedule(
+            first_top_block_number,
+            first_bottom_block_number,
+            initial_token_distribution_amount,
+        )?;
+
+        // the canonical bumps are found by Anchor while the accounts are created, they are stored so the PDAs are never derived again
+        let mint_nonce = ctx.bumps.mint;
+        let blocks_state_nonce = ctx.bumps.blocks_state_account;
+        let top_block_nonce = ctx.bumps.distribution_top_block_account;
+        let bottom_block_nonce = ctx.bumps.distribution_bottom_block_account;
+        let final_staking_account_nonce = ctx.bumps.final_staking_account;
+        let final_mining_account_nonce = ctx.bumps.final_mining_account;
+
+        let config = &mut ctx.accounts.config_account;
+        config.bump = ctx.bumps.config_account;
+        config.block_state_address = ctx.accounts.blocks_state_account.key();
+        config.block_state_nonce = blocks_state_nonce;
+        config.mint_address = ctx.accounts.mint.key();
+        config.mint_nonce = mint_nonce;
+        config.top_block_distribution_address = ctx.accounts.distribution_top_block_account.key();
+        config.top_block_distribution_nonce = top_block_nonce;
+        config.bottom_block_distribution_address =
+            ctx.accounts.distribution_bottom_block_account.key();
+        config.bottom_block_distribution_nonce = bottom_block_nonce;
+        config.final_staking_account_address = ctx.accounts.final_staking_account.key();
+        config.final_staking_account_nonce = final_staking_account_nonce;
+        config.final_mining_account_address = ctx.accounts.final_mining_account.key();
+        config.final_mining_account_nonce = final_mining_account_nonce;
+        initialize_distribution_backends(config);
 
         let blocks_state = &mut ctx.accounts.blocks_state_account;
         blocks_state.authority = ctx.accounts.signer.key();
-        blocks_state.mint_nonce = mint_nonce;
-        blocks_state.block_state_nonce = blocks_state_nonce;
+        blocks_state.pending_authority = None;
+        blocks_state.version = BLOCKS_STATE_VERSION;
+        blocks_state.first_top_block_number = first_top_block_number;
+        blocks_state.first_bottom_block_number = first_bottom_block_number;
+        blocks_state.initial_token_distribution_amount = initial_token_distribution_amount;
 
-        blocks_state.top_block_distribution_address =
-            ctx.accounts.distribution_top_block_account.key();
-        blocks_state.top_block_distribution_nonce = top_block_nonce;
         blocks_state.top_block_solution_timestamp = 0;
-        blocks_state.top_block_number = 1_u64;
+        blocks_state.top_block_number = first_top_block_number;
         blocks_state.top_block_last_account_address = None;
         blocks_state.top_block_last_account_rest_bp = 0;
+        blocks_state.top_block_rest_carryovers = Vec::new();
 
-        blocks_state.top_block_available_bp =
-            convert_f64_to_u64(calculate_max_bp(blocks_state.top_block_number)?)?;
+        cache_top_block_params(blocks_state)?;
+        blocks_state.top_block_available_bp = blocks_state.top_block_max_bp;
         blocks_state.top_block_balance = DUSTS_PER_BLOCK;
+        blocks_state.top_block_rounding_remainder = 0;
 
         mint_tokens(
             ctx.accounts.mint.to_account_info(),
@@ -106,17 +233,16 @@ pub mod sallar {
             DUSTS_PER_BLOCK,
         )?;
 
-        blocks_state.bottom_block_distribution_address =
-            ctx.accounts.distribution_bottom_block_account.key();
-        blocks_state.bottom_block_distribution_nonce = bottom_block_nonce;
         blocks_state.bottom_block_solution_timestamp = 0;
-        blocks_state.bottom_block_number = 470_000_u64;
+        blocks_state.bottom_block_number = first_bottom_block_number;
         blocks_state.bottom_block_last_account_address = None;
         blocks_state.bottom_block_last_account_rest_bp = 0;
+        blocks_state.bottom_block_rest_carryovers = Vec::new();
 
-        blocks_state.bottom_block_available_bp =
-            convert_f64_to_u64(calculate_max_bp(blocks_state.bottom_block_number)?)?;
+        cache_bottom_block_params(blocks_state)?;
+        blocks_state.bottom_block_available_bp = blocks_state.bottom_block_max_bp;
         blocks_state.bottom_block_balance = DUSTS_PER_BLOCK;
+        blocks_state.bottom_block_rounding_remainder = 0;
 
         mint_tokens(
             ctx.accounts.mint.to_account_info(),
@@ -132,13 +258,103 @@ pub mod sallar {
         blocks_state.initial_token_distribution_already_performed = false;
         blocks_state.blocks_collided = false;
 
-        blocks_state.final_staking_account_nonce = final_staking_account_nonce;
         blocks_state.final_staking_pool_in_round = 0;
         blocks_state.final_staking_last_staking_timestamp = 0;
         blocks_state.final_staking_left_reward_parts_in_round = 1.0;
+        blocks_state.final_staking_left_reward_parts_in_round_scaled = REWARD_PARTS_SCALING_FACTOR;
         blocks_state.final_staking_left_balance_in_round = 0;
 
-        blocks_state.final_mining_account_nonce = final_mining_account_nonce;
+        blocks_state.min_payout_amount = 0;
+        blocks_state.top_block_pending_payouts = 0;
+        blocks_state.bottom_block_pending_payouts = 0;
+
+        blocks_state.treasury_address = None;
+
+        blocks_state.paused = false;
+        blocks_state.admin_controls_renounced = false;
+
+        blocks_state.timelock_delay = 0;
+
+        blocks_state.block_solution_interval_seconds = DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS;
+
+        blocks_state.final_staking_interval_seconds = DEFAULT_FINAL_STAKING_INTERVAL_SECONDS;
+        blocks_state.final_staking_pool_division_factor =
+            FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR;
+
+        blocks_state.reward_fee_bps = 0;
+
+        blocks_state.sla_threshold_seconds = 0;
+        blocks_state.top_block_max_solution_delay = 0;
+        blocks_state.bottom_block_max_solution_delay = 0;
+        blocks_state.final_staking_max_round_delay = 0;
+
+        blocks_state.guardian = None;
+        blocks_state.top_block_halted = false;
+        blocks_state.bottom_block_halted = false;
+
+        blocks_state.final_mining_interval_seconds = DEFAULT_FINAL_MINING_INTERVAL_SECONDS;
+        blocks_state.final_mining_max_payout_per_call = DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL;
+        blocks_state.final_mining_last_timestamp = 0;
+
+        blocks_state.final_staking_min_pool_in_round = DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND;
+        blocks_state.final_staking_terminal_mode = FinalStakingTerminalMode::DistributeRemaining;
+        blocks_state.final_staking_final_round = false;
+        blocks_state.final_staking_finalized = false;
+
+        blocks_state.final_staking_round_counter = 0;
+
+        blocks_state.global_stats_initialized = false;
+        blocks_state.recent_solvers_initialized = false;
+        blocks_state.state_mirror_initialized = false;
+        blocks_state.denylist_initialized = false;
+
+        blocks_state.block_history_enabled = false;
+
+        blocks_state.solve_batch_sequence = 0;
+        blocks_state.top_block_solve_batch_cursor = None;
+
+        blocks_state.top_block_unclaimed_root_rewards = 0;
+
+        blocks_state.solve_coordinator = None;
+
+        blocks_state.bottom_block_min_required_stake = DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE;
+
+        blocks_state.top_block_max_user_requests = 0;
+        blocks_state.bottom_block_max_user_requests = 0;
+
+        blocks_state.block_participation_commitment_enabled = false;
+        blocks_state.top_block_participation_commitment = None;
+        blocks_state.bottom_block_participation_commitment = None;
+
+        blocks_state.boost_credits_required = false;
+
+        blocks_state.final_staking_unclaimed_root_rewards = 0;
+        blocks_state.final_staking_round_start_timestamp = 0;
+
+        blocks_state.final_staking_snapshots_required = false;
+        blocks_state.final_staking_registered_balance_next_round = 0;
+        blocks_state.final_staking_registered_balance_in_round = 0;
+
+        blocks_state.final_staking_carried_remainder = 0;
+
+        blocks_state.final_staking_reward_part_tolerance_bps = None;
+
+        blocks_state.final_mining_tiers_initialized = false;
+
+        blocks_state.final_mining_budget_per_window = None;
+        blocks_state.final_mining_budget_window_seconds =
+            DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS;
+        blocks_state.final_mining_budget_window_start = 0;
+        blocks_state.final_mining_budget_window_distributed = 0;
+
+        blocks_state.final_mining_checkpoint_required = false;
+        blocks_state.final_mining_checkpoint_balance = 0;
+        blocks_state.final_mining_checkpoint_slot = 0;
+
+        blocks_state.final_mining_claim_period_seconds = 0;
+
+        blocks_state.final_mining_epoch_counter = 0;
+        blocks_state.final_mining_epoch_timestamp = 0;
 
         set_token_metadata(
             ctx,
@@ -148,16 +364,17 @@ pub mod sallar {
         )
     }
 
-    /// Distributes 2 600 000 000 tokens to the organization account provided in the context by minting tokens to the account.
+    /// Distributes the initial token distribution amount (2 600 000 000 tokens on mainnet) to the organization account provided in the context by minting tokens to the account.
     /// This function can be called only once and it can be called at any time after the initialization.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - the initial token distribution context where the organization account is provided.
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) initial_token_distribution_not_performed_yet(&ctx.accounts.blocks_state_account))]
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) initial_token_distribution_not_performed_yet(&ctx.accounts.blocks_state_account) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account))]
     pub fn initial_token_distribution(ctx: Context<InitialTokenDistributionContext>) -> Result<()> {
         let blocks_state = &mut ctx.accounts.blocks_state_account;
-        let mint_nonce = blocks_state.mint_nonce;
+        let mint_nonce = ctx.accounts.config_account.mint_nonce;
+        let initial_token_distribution_amount = blocks_state.initial_token_distribution_amount;
 
         mint_tokens(
             ctx.accounts.mint.to_account_info(),
@@ -165,10 +382,62 @@ pub mod sallar {
             ctx.accounts.mint.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
             mint_nonce,
-            260_000_000_000_000_u64 * TOKEN_AMOUNT_SCALING_FACTOR,
+            initial_token_distribution_amount,
         )?;
 
         blocks_state.initial_token_distribution_already_performed = true;
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            global_stats_account.total_minted_amount = global_stats_account
+                .total_minted_amount
+                .checked_add(initial_token_distribution_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// Posts the commitment of the next top or bottom block solution batch, so the batch cannot be reordered or censored once the requests are known.
+    /// While the commit-reveal scheme is enabled, solve_top_block and solve_bottom_block accept only the user info entries matching the commitment of the block kind posted in an earlier slot.
+    /// Posting a new commitment replaces the pending one of the block kind.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the commit block participation context where the solve coordinator, the contract's owner or one of the registered signers is the signer,
+    /// * `block_kind` - the kind of the block the batch solves,
+    /// * `commitment` - the Keccak-256 hash of the program id, the block kind, the current block number and the serialized user info entries of the batch.
+    #[access_control(valid_participation_committer(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) block_not_halted(&ctx.accounts.blocks_state_account, block_kind))]
+    pub fn commit_block_participation(
+        ctx: Context<CommitBlockParticipationContext>,
+        block_kind: BlockKind,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        require!(
+            blocks_state.block_participation_commitment_enabled,
+            SallarError::BlockParticipationCommitmentDisabled
+        );
+
+        let slot = Clock::get()?.slot;
+        let participation_commitment = Some(ParticipationCommitment {
+            hash: commitment,
+            slot,
+        });
+        let block_number = match block_kind {
+            BlockKind::Top => {
+                blocks_state.top_block_participation_commitment = participation_commitment;
+                blocks_state.top_block_number
+            }
+            BlockKind::Bottom => {
+                blocks_state.bottom_block_participation_commitment = participation_commitment;
+                blocks_state.bottom_block_number
+            }
+        };
+        emit!(BlockParticipationCommitted {
+            block_kind,
+            block_number,
+            commitment,
+            slot,
+        });
 
         Ok(())
     }
@@ -178,134 +447,623 @@ pub mod sallar {
     /// Once the block is solved and all tokens from top block distribution account are distributed, the block is switched to the next one and the distribution account is refilled.
     /// This function can be called multiple times, until all blocks are solved or the blocks would collide after the switch to the next block (i.e. the next block number is already used as the current bottom block number).
     /// The function cannot be invoked for 3 minutes after the block has been solved.
+    /// The batch can be submitted by any relayer if the solve coordinator approved it with the ed25519 signature verified by the preceding instruction of the transaction.
+    /// A large batch can be split into chunks of up to 25 user info entries submitted in consecutive transactions,
+    /// the progress of the batch is tracked in the blocks state, so a duplicated or dropped chunk is rejected and the backend can safely resume the batch.
+    /// The batch is complete once all its entries are processed or the block is solved.
+    /// The rewards are allocated from the BPs the users receive following the rounding policy of the blocks (see allocate_reward_from_block),
+    /// so the rewards for all BPs of the block sum up exactly to the block's balance.
+    /// The users who do not receive all BPs because the block is solved (up to 8 per block) are paid their rests at the start of the next block,
+    /// the first call for the next block must begin with the entries of these users in the same order.
+    /// The entries following the user who solves the block are not rejected with `UserRequestForSolvedBlock` anymore, their whole rewards are carried over instead.
+    /// Every carried over rest is reported with the `RestCarriedOver` event and a batch carrying over more than 8 rests is rejected with `RestCarryoversLimitExceeded`.
+    /// If roll over is requested, the solved block is switched to the next one within the same call (if the blocks would not collide)
+    /// and the distribution continues in the new block with the rests of the unpaid users and the remaining entries, so the batch is not held back by the 3 minutes interval.
+    /// The exact amounts settled for every user of the batch are reported with a single `SolveBatchSettled` event.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - the solve top block context where all required accounts are provided,
-    /// * `users_info` - a vector of accounts solving the current top block, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts.
+    /// * `users_info` - a vector of accounts solving the current top block, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts,
+    /// * `max_valid_slot` - the last slot the batch can be processed in, the batch is rejected if it is processed later (no bound if not provided),
+    /// * `chunk` - the position of `users_info` within the chunked batch (the call is not a part of a chunked batch if not provided),
+    /// * `positional_accounts` - true if the account of the i-th entry of `users_info` is the i-th remaining account, so remaining accounts are not searched, false otherwise,
+    /// * `roll_over` - true if the distribution continues in the next block(s) once the current block is solved, false otherwise
+    ///   (while the per-user requests cap of top blocks is set, the users cannot be rewarded beyond the block of the provided block user requests account),
+    /// * `strict` - true if the whole batch fails when any user info entry is invalid, false if invalid entries (e.g. with a missing, uninitialized or frozen recipient account) are skipped and reported with `UserEntrySkipped` events and in the returned bitmask.
     ///
     /// ### Returns
-    /// Number of current top block after processing all input accounts
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) top_block_not_solved(&ctx.accounts.blocks_state_account) blocks_solution_required_interval_elapsed(&ctx.accounts.blocks_state_account.top_block_solution_timestamp))]
+    /// Number of current top block after processing all input accounts, the sequence number assigned to the batch and the bitmask of skipped user info entries
+    #[access_control(valid_solver(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts, &ctx.accounts.instructions_sysvar_account, BlockKind::Top, &users_info) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) block_not_halted(&ctx.accounts.blocks_state_account, BlockKind::Top) top_block_not_solved(&ctx.accounts.blocks_state_account) blocks_solution_required_interval_elapsed(&ctx.accounts.blocks_state_account.top_block_solution_timestamp, &ctx.accounts.blocks_state_account.block_solution_interval_seconds) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) block_history_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.block_history_account, &ctx.accounts.system_program) block_user_requests_provided(&ctx.accounts.blocks_state_account, BlockKind::Top, &ctx.accounts.block_user_requests_account, &ctx.accounts.system_program) block_participation_revealed(&ctx.accounts.blocks_state_account, BlockKind::Top, &users_info) recent_solvers_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.recent_solvers_account) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
     pub fn solve_top_block<'info>(
-        ctx: Context<'_, '_, '_, 'info, SolveTopBlockContext<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SolveTopBlockContext<'info>>,
         users_info: Vec<UserInfoTopBlock>,
-    ) -> Result<u64> {
+        max_valid_slot: Option<u64>,
+        chunk: Option<SolveBatchChunk>,
+        positional_accounts: bool,
+        roll_over: bool,
+        strict: bool,
+    ) -> Result<SolveBlockResult> {
         require!(!&users_info.is_empty(), SallarError::MissingUserInfo);
-        let first_user_info_key = users_info.first().unwrap().user_public_key;
+        require!(
+            !has_duplicated_users(
+                users_info
+                    .iter()
+                    .map(|user_info| &user_info.user_public_key)
+            ),
+            SallarError::UserDuplicatedInUserInfoForTopBlock
+        );
+        let mint_key = ctx.accounts.mint.key();
+        let token_program_key = ctx.accounts.token_program.key();
+        if strict {
+            valid_recipient_accounts(
+                &ctx.accounts.config_account,
+                ctx.remaining_accounts,
+                users_info
+                    .iter()
+                    .map(|user_info| &user_info.user_public_key),
+                &mint_key,
+                &token_program_key,
+            )?;
+        }
+        let associated_token_account_creation = AssociatedTokenAccountCreation {
+            payer: ctx.accounts.signer.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx
+                .accounts
+                .associated_token_program
+                .as_ref()
+                .map(|associated_token_program| associated_token_program.to_account_info()),
+            system_program: ctx
+                .accounts
+                .system_program
+                .as_ref()
+                .map(|system_program| system_program.to_account_info()),
+        };
         let blocks_state = &mut ctx.accounts.blocks_state_account;
-        let block_number = blocks_state.top_block_number;
-        let mint_nonce = blocks_state.mint_nonce;
-
-        let top_bp_with_boost = calculate_top_bp_with_boost(block_number)?;
-        let dust_per_bp = calculate_dust_per_bp(block_number)?;
-
-        let has_unprocessed_rest_from_last_block = blocks_state.top_block_last_account_rest_bp > 0;
-        if has_unprocessed_rest_from_last_block {
-            require!(
-                blocks_state.top_block_balance == DUSTS_PER_BLOCK,
-                SallarError::UserRestExistsButBlockIsNotNew
-            );
-            require!(
-                first_user_info_key == blocks_state.top_block_last_account_address.unwrap(),
-                SallarError::UserRestExistsButFirstRequestForNewBlockIsNotForThisAccount
-            );
+        if let Some(chunk) = &chunk {
+            blocks_state.top_block_solve_batch_cursor = advance_solve_batch_cursor(
+                blocks_state.top_block_solve_batch_cursor,
+                chunk,
+                users_info.len(),
+            )?;
+        }
+        blocks_state.solve_batch_sequence += 1;
+        let solve_batch_sequence = blocks_state.solve_batch_sequence;
+        blocks_state.top_block_participation_commitment = None;
+        let mint_nonce = ctx.accounts.config_account.mint_nonce;
+        let mut next_user_entry_index = 0;
+        let mut skipped_user_entries = Vec::new();
+        let mut paid_amounts = Vec::new();
+        let max_user_requests = blocks_state.top_block_max_user_requests;
+        let boost_credits_required = blocks_state.boost_credits_required;
+        if let Some(block_user_requests_account) = ctx.accounts.block_user_requests_account.as_mut()
+        {
+            block_user_requests_account.bump = ctx.bumps.block_user_requests_account.unwrap();
+            block_user_requests_account.block_kind = BlockKind::Top;
+            block_user_requests_account.block_number = blocks_state.top_block_number;
+        }
 
-            let account = ctx.remaining_accounts.iter().find(|account| {
-                account.key() == blocks_state.top_block_last_account_address.unwrap()
-            });
-            let account_info = match account {
-                Some(acc) => acc.to_account_info(),
-                None => {
-                    return err!(
-                        SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount
-                    )
-                }
+        loop {
+            let block_number = blocks_state.top_block_number;
+            let previous_solution_timestamp = blocks_state.top_block_solution_timestamp;
+
+            let (top_bp_with_boost, dust_per_bp) = top_block_params(blocks_state)?;
+
+            let mut distributed_amount: u64 = 0;
+            let mut solvers = Vec::new();
+            let mut distribution_source = DistributionSource {
+                distribution_account: &ctx.accounts.distribution_top_block_account,
+                seed: DISTRIBUTION_TOP_BLOCK_SEED,
+                nonce: ctx.accounts.config_account.top_block_distribution_nonce,
+                mint: &ctx.accounts.mint,
+                token_program: ctx.accounts.token_program.to_account_info(),
+                backend: distribution_backend(&ctx.accounts.config_account, Subsystem::TopBlock),
+                fee_destination: ctx
+                    .accounts
+                    .treasury_token_account
+                    .as_ref()
+                    .map(|treasury_token_account| treasury_token_account.to_account_info()),
+                fee_amount: 0,
             };
 
-            let user_rest_bp = blocks_state
-                .top_block_last_account_rest_bp
-                .min(blocks_state.top_block_available_bp);
-            let user_rest_transfer_amount: u64;
-            if user_rest_bp < blocks_state.top_block_available_bp {
-                user_rest_transfer_amount = calculate_single_reward(user_rest_bp, dust_per_bp)?;
-            } else {
-                user_rest_transfer_amount = blocks_state.top_block_balance;
-            }
+            if !blocks_state.top_block_rest_carryovers.is_empty() {
+                if next_user_entry_index == 0 {
+                    require!(
+                        blocks_state.top_block_balance == DUSTS_PER_BLOCK,
+                        SallarError::UserRestExistsButBlockIsNotNew
+                    );
+                }
+                let rest_carryovers = blocks_state.top_block_rest_carryovers.clone();
+                let rest_entry_indexes = find_rest_carryover_entries(
+                    &rest_carryovers,
+                    &users_info
+                        .iter()
+                        .map(|user_info| user_info.user_public_key)
+                        .collect::<Vec<Pubkey>>(),
+                    next_user_entry_index,
+                )?;
+                if next_user_entry_index == 0 {
+                    next_user_entry_index = rest_carryovers.len();
+                }
 
-            transfer_tokens(
-                &ctx.accounts.distribution_top_block_account,
-                account_info,
-                DISTRIBUTION_TOP_BLOCK_SEED,
-                ctx.accounts.token_program.to_account_info(),
-                blocks_state.top_block_distribution_nonce,
-                user_rest_transfer_amount,
-            )?;
+                let mut remaining_rest_carryovers = Vec::new();
+                for (rest_carryover, user_rest_entry_index) in
+                    rest_carryovers.into_iter().zip(rest_entry_indexes)
+                {
+                    if blocks_state.top_block_available_bp == 0 {
+                        remaining_rest_carryovers.push(rest_carryover);
+                        continue;
+                    }
+
+                    let account = find_user_account(
+                        ctx.remaining_accounts,
+                        &rest_carryover.user,
+                        positional_accounts.then_some(user_rest_entry_index),
+                    );
+                    let account_info = match account {
+                        Some(acc) => acc.to_account_info(),
+                        None => {
+                            return err!(
+                            SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount
+                        )
+                        }
+                    };
+                    let user_key = account_info.key();
+                    with_user_entry_context(
+                        valid_recipient_account(
+                            &ctx.accounts.config_account,
+                            &account_info,
+                            &mint_key,
+                            &token_program_key,
+                        ),
+                        user_rest_entry_index,
+                        &user_key,
+                    )?;
 
-            blocks_state.top_block_available_bp =
-                blocks_state.top_block_available_bp - user_rest_bp;
-            blocks_state.top_block_last_account_rest_bp =
-                blocks_state.top_block_last_account_rest_bp - user_rest_bp;
-            blocks_state.top_block_balance =
-                blocks_state.top_block_balance - user_rest_transfer_amount;
-        }
-        let users_info_without_info_for_user_rest = match has_unprocessed_rest_from_last_block {
-            true => users_info
-                .into_iter()
-                .skip(1)
-                .collect::<Vec<UserInfoTopBlock>>(),
-            false => users_info,
-        };
+                    let user_rest_bp = rest_carryover
+                        .rest_bp
+                        .min(blocks_state.top_block_available_bp);
+                    let (user_rest_transfer_amount, rounding_remainder) =
+                        allocate_reward_from_block(
+                            blocks_state.top_block_balance,
+                            blocks_state.top_block_available_bp,
+                            blocks_state.top_block_rounding_remainder,
+                            user_rest_bp,
+                            dust_per_bp,
+                        )?;
+                    blocks_state.top_block_rounding_remainder = rounding_remainder;
+
+                    let payout_amount = with_user_entry_context(
+                        distribute_reward(
+                            Subsystem::TopBlock,
+                            blocks_state,
+                            &mut distribution_source,
+                            account_info,
+                            ctx.accounts.denylist_account.as_deref(),
+                            ctx.remaining_accounts,
+                            user_rest_transfer_amount,
+                        ),
+                        user_rest_entry_index,
+                        &user_key,
+                    )?;
+                    if payout_amount > 0 {
+                        emit!(TopBlockRewardPaid {
+                            user: user_key,
+                            block_number,
+                            bp: user_rest_bp,
+                            amount: payout_amount,
+                            reward_breakdown: None,
+                            solve_batch_sequence,
+                        });
+                    }
+
+                    blocks_state.top_block_available_bp = blocks_state
+                        .top_block_available_bp
+                        .checked_sub(user_rest_bp)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                    blocks_state.top_block_last_account_rest_bp = blocks_state
+                        .top_block_last_account_rest_bp
+                        .checked_sub(user_rest_bp)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                    blocks_state.top_block_balance = blocks_state
+                        .top_block_balance
+                        .checked_sub(user_rest_transfer_amount)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                    distributed_amount = distributed_amount
+                        .checked_add(user_rest_transfer_amount)
+                        .ok_or(SallarError::ArithmeticOverflow)?;
+                    solvers.push(BlockSolver {
+                        user: user_key,
+                        amount: user_rest_transfer_amount,
+                    });
+                    paid_amounts.push(UserPaidAmount {
+                        user: user_key,
+                        block_number,
+                        settled_amount: user_rest_transfer_amount,
+                        paid_amount: payout_amount,
+                    });
+                    if user_rest_bp < rest_carryover.rest_bp {
+                        remaining_rest_carryovers.push(RestCarryover {
+                            user: rest_carryover.user,
+                            rest_bp: rest_carryover.rest_bp - user_rest_bp,
+                        });
+                    }
+                }
+                blocks_state.top_block_rest_carryovers = remaining_rest_carryovers;
+            }
 
-        for user_info in &users_info_without_info_for_user_rest {
-            require!(
-                blocks_state.top_block_available_bp > 0,
-                SallarError::UserRequestForSolvedBlock
-            );
+            for (user_entry_index, user_info) in
+                users_info.iter().enumerate().skip(next_user_entry_index)
+            {
+                if roll_over && blocks_state.top_block_available_bp == 0 {
+                    break;
+                }
 
-            let account = ctx
-                .remaining_accounts
-                .iter()
-                .find(|account| account.key() == user_info.user_public_key);
-            let account_info = match account {
-                Some(acc) => acc.to_account_info(),
-                None => return err!(SallarError::MismatchBetweenRemainingAccountsAndUserInfo),
-            };
+                let recipient_account = with_user_entry_context(
+                    match &user_info.wallet {
+                        Some(wallet) => prepare_associated_token_account(
+                            ctx.remaining_accounts,
+                            &user_info.user_public_key,
+                            wallet,
+                            positional_accounts.then_some(user_entry_index),
+                            &associated_token_account_creation,
+                        ),
+                        None => Ok(()),
+                    }
+                    .and_then(|_| {
+                        find_recipient_account(
+                            &ctx.accounts.config_account,
+                            ctx.remaining_accounts,
+                            &user_info.user_public_key,
+                            &mint_key,
+                            &token_program_key,
+                            positional_accounts.then_some(user_entry_index),
+                        )
+                    })
+                    .and_then(|account_info| {
+                        if let (Some(block_user_requests_account), Some(_)) = (
+                            ctx.accounts.block_user_requests_account.as_mut(),
+                            ctx.accounts.system_program.as_ref(),
+                        ) {
+                            record_user_requests(
+                                block_user_requests_account,
+                                block_number,
+                                &user_info.user_public_key,
+                                u16::from(user_info.user_request_without_boost)
+                                    + u16::from(user_info.user_request_with_boost),
+                                max_user_requests,
+                            )?;
+                        }
+                        if boost_credits_required && user_info.user_request_with_boost > 0 {
+                            consume_boost_credits(
+                                ctx.remaining_accounts,
+                                &user_info.user_public_key,
+                                user_info.user_request_with_boost,
+                            )?;
+                        }
+                        Ok(account_info)
+                    }),
+                    user_entry_index,
+                    &user_info.user_public_key,
+                );
+                let account_info = match recipient_account {
+                    Ok(account_info) => account_info,
+                    Err(error) if !strict => {
+                        skip_user_entry(
+                            &mut skipped_user_entries,
+                            BlockKind::Top,
+                            user_entry_index,
+                            &user_info.user_public_key,
+                            &error,
+                            solve_batch_sequence,
+                        );
+                        next_user_entry_index = user_entry_index + 1;
+                        continue;
+                    }
+                    Err(error) => return Err(error),
+                };
 
-            let (current_user_reward_bp, mut current_user_transfer_amount) =
-                calculate_user_reward_top_block(
+                let reward_breakdown = calculate_user_reward_breakdown_top_block(
                     user_info.user_request_without_boost,
                     user_info.user_request_with_boost,
                     top_bp_with_boost,
                     dust_per_bp,
                 )?;
+                let current_user_reward_bp = reward_breakdown.bp()?;
+
+                if blocks_state.top_block_available_bp == 0 {
+                    if current_user_reward_bp > 0 {
+                        with_user_entry_context(
+                            push_rest_carryover(
+                                &mut blocks_state.top_block_rest_carryovers,
+                                BlockKind::Top,
+                                block_number,
+                                user_info.user_public_key,
+                                current_user_reward_bp,
+                                solve_batch_sequence,
+                            ),
+                            user_entry_index,
+                            &user_info.user_public_key,
+                        )?;
+                        blocks_state.top_block_last_account_rest_bp = blocks_state
+                            .top_block_last_account_rest_bp
+                            .checked_add(current_user_reward_bp)
+                            .ok_or(SallarError::ArithmeticOverflow)?;
+                    }
+                    blocks_state.top_block_last_account_address = Some(user_info.user_public_key);
+                    next_user_entry_index = user_entry_index + 1;
+                    continue;
+                }
 
-            if current_user_reward_bp <= blocks_state.top_block_available_bp {
-                blocks_state.top_block_last_account_rest_bp = 0;
-                blocks_state.top_block_available_bp -= current_user_reward_bp;
-            } else {
-                blocks_state.top_block_last_account_rest_bp =
-                    current_user_reward_bp - blocks_state.top_block_available_bp;
-                blocks_state.top_block_available_bp = 0;
+                let user_bp = current_user_reward_bp.min(blocks_state.top_block_available_bp);
+                let (current_user_transfer_amount, rounding_remainder) =
+                    allocate_reward_from_block(
+                        blocks_state.top_block_balance,
+                        blocks_state.top_block_available_bp,
+                        blocks_state.top_block_rounding_remainder,
+                        user_bp,
+                        dust_per_bp,
+                    )?;
+                blocks_state.top_block_rounding_remainder = rounding_remainder;
+                if current_user_reward_bp <= blocks_state.top_block_available_bp {
+                    blocks_state.top_block_available_bp = blocks_state
+                        .top_block_available_bp
+                        .checked_sub(current_user_reward_bp)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                } else {
+                    let user_rest_bp = current_user_reward_bp
+                        .checked_sub(blocks_state.top_block_available_bp)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                    with_user_entry_context(
+                        push_rest_carryover(
+                            &mut blocks_state.top_block_rest_carryovers,
+                            BlockKind::Top,
+                            block_number,
+                            user_info.user_public_key,
+                            user_rest_bp,
+                            solve_batch_sequence,
+                        ),
+                        user_entry_index,
+                        &user_info.user_public_key,
+                    )?;
+                    blocks_state.top_block_last_account_rest_bp = blocks_state
+                        .top_block_last_account_rest_bp
+                        .checked_add(user_rest_bp)
+                        .ok_or(SallarError::ArithmeticOverflow)?;
+                    blocks_state.top_block_available_bp = 0;
+                }
+
+                let payout_amount = with_user_entry_context(
+                    distribute_reward(
+                        Subsystem::TopBlock,
+                        blocks_state,
+                        &mut distribution_source,
+                        account_info,
+                        ctx.accounts.denylist_account.as_deref(),
+                        ctx.remaining_accounts,
+                        current_user_transfer_amount,
+                    ),
+                    user_entry_index,
+                    &user_info.user_public_key,
+                )?;
+                if payout_amount > 0 {
+                    emit!(TopBlockRewardPaid {
+                        user: user_info.user_public_key,
+                        block_number,
+                        bp: user_bp,
+                        amount: payout_amount,
+                        reward_breakdown: Some(reward_breakdown),
+                        solve_batch_sequence,
+                    });
+                }
+
+                blocks_state.top_block_balance = blocks_state
+                    .top_block_balance
+                    .checked_sub(current_user_transfer_amount)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                distributed_amount = distributed_amount
+                    .checked_add(current_user_transfer_amount)
+                    .ok_or(SallarError::ArithmeticOverflow)?;
+                solvers.push(BlockSolver {
+                    user: user_info.user_public_key,
+                    amount: current_user_transfer_amount,
+                });
+                paid_amounts.push(UserPaidAmount {
+                    user: user_info.user_public_key,
+                    block_number,
+                    settled_amount: current_user_transfer_amount,
+                    paid_amount: payout_amount,
+                });
+                blocks_state.top_block_last_account_address = Some(user_info.user_public_key);
+                next_user_entry_index = user_entry_index + 1;
             }
 
+            transfer_accumulated_fee(&mut distribution_source)?;
+
             if blocks_state.top_block_available_bp == 0 {
-                current_user_transfer_amount = blocks_state.top_block_balance;
+                if let Some(treasury_token_account) = &ctx.accounts.treasury_token_account {
+                    ctx.accounts.distribution_top_block_account.reload()?;
+                    let surplus_amount = calculate_distribution_surplus(
+                        ctx.accounts.distribution_top_block_account.amount,
+                        blocks_state.top_block_balance,
+                        blocks_state
+                            .top_block_pending_payouts
+                            .saturating_add(blocks_state.top_block_unclaimed_root_rewards),
+                    );
+                    if surplus_amount > 0 {
+                        msg!(
+                            "Transferring surplus of top block distribution account to treasury: {}",
+                            surplus_amount
+                        );
+                        transfer_tokens(
+                            &ctx.accounts.distribution_top_block_account,
+                            treasury_token_account.to_account_info(),
+                            &ctx.accounts.mint,
+                            DISTRIBUTION_TOP_BLOCK_SEED,
+                            ctx.accounts.token_program.to_account_info(),
+                            ctx.accounts.config_account.top_block_distribution_nonce,
+                            surplus_amount,
+                        )?;
+                    }
+                }
             }
 
-            transfer_tokens(
-                &ctx.accounts.distribution_top_block_account,
-                account_info,
-                DISTRIBUTION_TOP_BLOCK_SEED,
+            if let Some(recent_solvers_account) = ctx.accounts.recent_solvers_account.as_mut() {
+                record_recent_solvers(recent_solvers_account, BlockKind::Top, &solvers);
+            }
+            if let (Some(block_history_account), Some(system_program)) = (
+                ctx.accounts.block_history_account.as_mut(),
+                ctx.accounts.system_program.as_ref(),
+            ) {
+                append_block_history(
+                    block_history_account,
+                    BlockKind::Top,
+                    block_number,
+                    ctx.bumps.block_history_account.unwrap(),
+                    solvers,
+                    ctx.accounts.signer.to_account_info(),
+                    system_program.to_account_info(),
+                )?;
+            }
+
+            let block_solved = blocks_state.top_block_available_bp == 0;
+            switch_top_block_to_next_one_if_applicable(
+                blocks_state,
+                mint_nonce,
+                &ctx.accounts.mint,
+                ctx.accounts
+                    .distribution_top_block_account
+                    .to_account_info(),
                 ctx.accounts.token_program.to_account_info(),
-                blocks_state.top_block_distribution_nonce,
-                current_user_transfer_amount,
             )?;
+            let block_switched = blocks_state.top_block_number != block_number;
+            if blocks_state.top_block_solution_timestamp != previous_solution_timestamp {
+                let solution_timestamp = blocks_state.top_block_solution_timestamp;
+                record_sla_delay(
+                    blocks_state,
+                    SlaSubsystem::TopBlock,
+                    previous_solution_timestamp,
+                    solution_timestamp,
+                );
+            }
+            update_blocks_collided(blocks_state)?;
+
+            if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+                record_distribution_batch(
+                    global_stats_account,
+                    Subsystem::TopBlock,
+                    distributed_amount,
+                )?;
+                if block_solved {
+                    record_block_solution(global_stats_account, BlockKind::Top, block_switched)?;
+                }
+            }
+
+            let rolled_over = roll_over
+                && block_switched
+                && (next_user_entry_index < users_info.len()
+                    || !blocks_state.top_block_rest_carryovers.is_empty());
+            if block_solved && !rolled_over {
+                blocks_state.top_block_solve_batch_cursor = None;
+            }
+            if !rolled_over {
+                break;
+            }
+        }
+
+        if let (Some(block_user_requests_account), Some(system_program)) = (
+            ctx.accounts.block_user_requests_account.as_ref(),
+            ctx.accounts.system_program.as_ref(),
+        ) {
+            fit_block_user_requests(
+                block_user_requests_account,
+                ctx.accounts.signer.to_account_info(),
+                system_program.to_account_info(),
+            )?;
+        }
+
+        if next_user_entry_index < users_info.len() {
+            with_user_entry_context(
+                user_request_for_unsolved_block(blocks_state.top_block_available_bp),
+                next_user_entry_index,
+                &users_info[next_user_entry_index].user_public_key,
+            )?;
+        }
+
+        emit!(SolveBatchSettled {
+            block_kind: BlockKind::Top,
+            solve_batch_sequence,
+            paid_amounts,
+        });
 
-            blocks_state.top_block_balance -= current_user_transfer_amount;
-            blocks_state.top_block_last_account_address = Some(user_info.user_public_key);
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
         }
 
+        Ok(SolveBlockResult {
+            block_number: blocks_state.top_block_number,
+            solve_batch_sequence,
+            skipped_user_entries,
+        })
+    }
+
+    /// Solves current top block in the claim mode.
+    /// Instead of distributing tokens to the accounts solving the block, the Merkle root of the (user, amount) pairs of the block is recorded
+    /// and the remaining balance of the block is reserved on top block distribution account for the users, who claim their rewards with the claim_block_reward instruction.
+    /// The block is switched to the next one and the distribution account is refilled as if the block was solved by the solve_top_block instruction.
+    /// The rests of the accounts solving the previous block (if any) must be distributed with the solve_top_block instruction first.
+    /// The function cannot be invoked for 3 minutes after the block has been solved.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the solve top block with rewards root context where all required accounts are provided,
+    /// * `merkle_root` - the Merkle root of the (user, amount) pairs of the block, the amounts must sum up to the remaining balance of the block,
+    /// * `max_valid_slot` - the last slot the root can be recorded in, the solution is rejected if it is processed later (no bound if not provided).
+    ///
+    /// ### Returns
+    /// Number of current top block after recording the root and the sequence number assigned to the solution
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) block_not_halted(&ctx.accounts.blocks_state_account, BlockKind::Top) top_block_not_solved(&ctx.accounts.blocks_state_account) blocks_solution_required_interval_elapsed(&ctx.accounts.blocks_state_account.top_block_solution_timestamp, &ctx.accounts.blocks_state_account.block_solution_interval_seconds) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account))]
+    pub fn solve_top_block_with_rewards_root(
+        ctx: Context<SolveTopBlockWithRewardsRootContext>,
+        merkle_root: [u8; 32],
+        max_valid_slot: Option<u64>,
+    ) -> Result<SolveBlockResult> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        require!(
+            blocks_state.top_block_rest_carryovers.is_empty(),
+            SallarError::UserRestExistsButBlockIsSolvedWithRewardsRoot
+        );
+        blocks_state.solve_batch_sequence += 1;
+        let solve_batch_sequence = blocks_state.solve_batch_sequence;
+        let block_number = blocks_state.top_block_number;
+        let mint_nonce = ctx.accounts.config_account.mint_nonce;
+        let previous_solution_timestamp = blocks_state.top_block_solution_timestamp;
+        let total_amount = blocks_state.top_block_balance;
+
+        let block_rewards_root_account = &mut ctx.accounts.block_rewards_root_account;
+        block_rewards_root_account.bump = ctx.bumps.block_rewards_root_account;
+        block_rewards_root_account.block_number = block_number;
+        block_rewards_root_account.merkle_root = merkle_root;
+        block_rewards_root_account.total_amount = total_amount;
+        block_rewards_root_account.claimed_amount = 0;
+
+        blocks_state.top_block_unclaimed_root_rewards = blocks_state
+            .top_block_unclaimed_root_rewards
+            .checked_add(total_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        blocks_state.top_block_available_bp = 0;
+        blocks_state.top_block_balance = 0;
+        blocks_state.top_block_rounding_remainder = 0;
+        blocks_state.top_block_solve_batch_cursor = None;
+        emit!(BlockRewardsRootRecorded {
+            block_number,
+            merkle_root,
+            total_amount,
+            solve_batch_sequence,
+        });
+
         switch_top_block_to_next_one_if_applicable(
             blocks_state,
             mint_nonce,
@@ -315,9 +1073,124 @@ pub mod sallar {
                 .to_account_info(),
             ctx.accounts.token_program.to_account_info(),
         )?;
+        if blocks_state.top_block_solution_timestamp != previous_solution_timestamp {
+            let solution_timestamp = blocks_state.top_block_solution_timestamp;
+            record_sla_delay(
+                blocks_state,
+                SlaSubsystem::TopBlock,
+                previous_solution_timestamp,
+                solution_timestamp,
+            );
+        }
         update_blocks_collided(blocks_state)?;
 
-        Ok(blocks_state.top_block_number)
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(global_stats_account, Subsystem::TopBlock, total_amount)?;
+            record_block_solution(
+                global_stats_account,
+                BlockKind::Top,
+                blocks_state.top_block_number != block_number,
+            )?;
+        }
+
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
+        }
+
+        Ok(SolveBlockResult {
+            block_number: blocks_state.top_block_number,
+            solve_batch_sequence,
+            skipped_user_entries: Vec::new(),
+        })
+    }
+
+    /// Claims the reward of a top block solved in the claim mode.
+    /// The reward is verified against the Merkle root recorded for the block and distributed from top block distribution account
+    /// the same way as the rewards distributed by the solve_top_block instruction (the reward fee and the minimum payout threshold apply).
+    /// Anyone can claim the reward on behalf of the user, the tokens are always transferred to the user's token account.
+    /// Each reward can be claimed only once.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the claim block reward context where all required accounts are provided,
+    /// * `block_number` - the number of the top block the reward comes from,
+    /// * `amount` - the reward of the user (including the reward fee),
+    /// * `proof` - the Merkle proof of the (user, amount) pair, i.e. the sibling nodes from the leaf up to the root.
+    ///
+    /// ### Returns
+    /// A successful result or an error if the proof is invalid.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
+    pub fn claim_block_reward<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimBlockRewardContext<'info>>,
+        block_number: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let user_key = ctx.accounts.user_token_account.key();
+        let block_rewards_root_account = &mut ctx.accounts.block_rewards_root_account;
+        require!(
+            verify_merkle_proof(
+                &proof,
+                &block_rewards_root_account.merkle_root,
+                calculate_block_reward_leaf(&user_key, amount),
+            ),
+            SallarError::InvalidMerkleProof
+        );
+        require!(
+            amount
+                <= block_rewards_root_account.total_amount
+                    - block_rewards_root_account.claimed_amount,
+            SallarError::BlockRewardsRootExceeded
+        );
+
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.distribution_top_block_account,
+            seed: DISTRIBUTION_TOP_BLOCK_SEED,
+            nonce: ctx.accounts.config_account.top_block_distribution_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::TopBlock),
+            fee_destination: ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|treasury_token_account| treasury_token_account.to_account_info()),
+            fee_amount: 0,
+        };
+        blocks_state.top_block_unclaimed_root_rewards = blocks_state
+            .top_block_unclaimed_root_rewards
+            .checked_sub(amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+        let payout_amount = distribute_reward(
+            Subsystem::TopBlock,
+            blocks_state,
+            &mut distribution_source,
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.denylist_account.as_deref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        transfer_accumulated_fee(&mut distribution_source)?;
+        block_rewards_root_account.claimed_amount = block_rewards_root_account
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        let block_reward_claim_account = &mut ctx.accounts.block_reward_claim_account;
+        block_reward_claim_account.bump = ctx.bumps.block_reward_claim_account;
+        block_reward_claim_account.block_number = block_number;
+        block_reward_claim_account.user = user_key;
+        block_reward_claim_account.amount = amount;
+
+        emit!(BlockRewardClaimed {
+            user: user_key,
+            block_number,
+            amount,
+            payout_amount,
+        });
+
+        Ok(())
     }
 
     /// Solves current bottom block.
@@ -325,143 +1198,479 @@ pub mod sallar {
     /// Once the block is solved and all tokens are from bottom block distribution account are distributed, the block is switched to the next one and the distribution account is refilled.
     /// This function can be called multiple times, until all blocks are solved or the blocks would collide after the switch to the next block (i.e. the next block number is already used as the current top block number).
     /// The function cannot be invoked for 3 minutes after the block has been solved.
+    /// The batch can be submitted by any relayer if the solve coordinator approved it with the ed25519 signature verified by the preceding instruction of the transaction.
+    /// The rewards are allocated from the BPs the users receive following the rounding policy of the blocks (see allocate_reward_from_block),
+    /// so the rewards for all BPs of the block sum up exactly to the block's balance.
+    /// The users who do not receive all BPs because the block is solved (up to 8 per block) are paid their rests at the start of the next block,
+    /// the first call for the next block must begin with the entries of these users in the same order.
+    /// The entries following the user who solves the block are not rejected with `UserRequestForSolvedBlock` anymore, their whole rewards are carried over instead.
+    /// Every carried over rest is reported with the `RestCarriedOver` event and a batch carrying over more than 8 rests is rejected with `RestCarryoversLimitExceeded`.
+    /// The exact amounts settled for every user of the batch are reported with a single `SolveBatchSettled` event.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - the solve bottom block context where all required accounts are provided,
-    /// * `users_info` - a vector of accounts solving the current bottom block, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts.
+    /// * `users_info` - a vector of accounts solving the current bottom block, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts
+    ///   (the stake lock accounts of the users must be provided among remaining accounts, the balance of a user without it is 0),
+    /// * `max_valid_slot` - the last slot the batch can be processed in, the batch is rejected if it is processed later (no bound if not provided),
+    /// * `positional_accounts` - true if the account of the i-th entry of `users_info` is the i-th remaining account, so remaining accounts are not searched, false otherwise,
+    /// * `strict` - true if the whole batch fails when any user info entry is invalid, false if invalid entries (e.g. with a missing, uninitialized or frozen recipient account) are skipped and reported with `UserEntrySkipped` events and in the returned bitmask.
     ///
     /// ### Returns
-    /// Number of current bottom block after processing all input accounts
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) bottom_block_not_solved(&ctx.accounts.blocks_state_account) blocks_solution_required_interval_elapsed(&ctx.accounts.blocks_state_account.bottom_block_solution_timestamp))]
+    /// Number of current bottom block after processing all input accounts, the sequence number assigned to the batch and the bitmask of skipped user info entries
+    #[access_control(valid_solver(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts, &ctx.accounts.instructions_sysvar_account, BlockKind::Bottom, &users_info) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) block_not_halted(&ctx.accounts.blocks_state_account, BlockKind::Bottom) bottom_block_not_solved(&ctx.accounts.blocks_state_account) bottom_block_within_bounds(&ctx.accounts.blocks_state_account) blocks_solution_required_interval_elapsed(&ctx.accounts.blocks_state_account.bottom_block_solution_timestamp, &ctx.accounts.blocks_state_account.block_solution_interval_seconds) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) block_history_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.block_history_account, &ctx.accounts.system_program) block_user_requests_provided(&ctx.accounts.blocks_state_account, BlockKind::Bottom, &ctx.accounts.block_user_requests_account, &ctx.accounts.system_program) block_participation_revealed(&ctx.accounts.blocks_state_account, BlockKind::Bottom, &users_info) recent_solvers_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.recent_solvers_account) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
     pub fn solve_bottom_block<'info>(
-        ctx: Context<'_, '_, '_, 'info, SolveBottomBlockContext<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SolveBottomBlockContext<'info>>,
         users_info: Vec<UserInfoBottomBlock>,
-    ) -> Result<u64> {
+        max_valid_slot: Option<u64>,
+        positional_accounts: bool,
+        strict: bool,
+    ) -> Result<SolveBlockResult> {
         require!(!&users_info.is_empty(), SallarError::MissingUserInfo);
-        let first_user_info_key = users_info.first().unwrap().user_public_key;
+        require!(
+            !has_duplicated_users(
+                users_info
+                    .iter()
+                    .map(|user_info| &user_info.user_public_key)
+            ),
+            SallarError::UserDuplicatedInUserInfoForBottomBlock
+        );
+        let mint_key = ctx.accounts.mint.key();
+        let token_program_key = ctx.accounts.token_program.key();
+        if strict {
+            valid_recipient_accounts(
+                &ctx.accounts.config_account,
+                ctx.remaining_accounts,
+                users_info
+                    .iter()
+                    .map(|user_info| &user_info.user_public_key),
+                &mint_key,
+                &token_program_key,
+            )?;
+        }
+        let associated_token_account_creation = AssociatedTokenAccountCreation {
+            payer: ctx.accounts.signer.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx
+                .accounts
+                .associated_token_program
+                .as_ref()
+                .map(|associated_token_program| associated_token_program.to_account_info()),
+            system_program: ctx
+                .accounts
+                .system_program
+                .as_ref()
+                .map(|system_program| system_program.to_account_info()),
+        };
         let blocks_state = &mut ctx.accounts.blocks_state_account;
+        blocks_state.solve_batch_sequence += 1;
+        let solve_batch_sequence = blocks_state.solve_batch_sequence;
+        blocks_state.bottom_block_participation_commitment = None;
         let block_number = blocks_state.bottom_block_number;
-        let mint_nonce = blocks_state.mint_nonce;
+        let mint_nonce = ctx.accounts.config_account.mint_nonce;
+        let previous_solution_timestamp = blocks_state.bottom_block_solution_timestamp;
+        let max_user_requests = blocks_state.bottom_block_max_user_requests;
+        let boost_credits_required = blocks_state.boost_credits_required;
+        if let Some(block_user_requests_account) = ctx.accounts.block_user_requests_account.as_mut()
+        {
+            block_user_requests_account.bump = ctx.bumps.block_user_requests_account.unwrap();
+            block_user_requests_account.block_kind = BlockKind::Bottom;
+            block_user_requests_account.block_number = blocks_state.bottom_block_number;
+        }
 
         let mut current_user_reward_bp;
-        let mut current_user_transfer_amount;
 
-        let dust_per_bp = calculate_dust_per_bp(block_number)?;
+        let (dust_per_bp, bottom_block_boost) = bottom_block_params(blocks_state)?;
+
+        let mut distributed_amount: u64 = 0;
+        let mut solvers = Vec::new();
+        let mut skipped_user_entries = Vec::new();
+        let mut paid_amounts = Vec::new();
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.distribution_bottom_block_account,
+            seed: DISTRIBUTION_BOTTOM_BLOCK_SEED,
+            nonce: ctx.accounts.config_account.bottom_block_distribution_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::BottomBlock),
+            fee_destination: ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|treasury_token_account| treasury_token_account.to_account_info()),
+            fee_amount: 0,
+        };
 
-        let has_unprocessed_rest_from_last_block =
-            blocks_state.bottom_block_last_account_rest_bp > 0;
-        if has_unprocessed_rest_from_last_block {
+        let mut next_user_entry_index = 0;
+        if !blocks_state.bottom_block_rest_carryovers.is_empty() {
             require!(
                 blocks_state.bottom_block_balance == DUSTS_PER_BLOCK,
                 SallarError::UserRestExistsButBlockIsNotNew
             );
-            require!(
-                first_user_info_key == blocks_state.bottom_block_last_account_address.unwrap(),
-                SallarError::UserRestExistsButFirstRequestForNewBlockIsNotForThisAccount
-            );
-
-            let account = ctx.remaining_accounts.iter().find(|account| {
-                account.key() == blocks_state.bottom_block_last_account_address.unwrap()
-            });
-            let account_info = match account {
-                Some(acc) => acc.to_account_info(),
-                None => {
-                    return err!(
-                        SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount
-                    )
+            let rest_carryovers = blocks_state.bottom_block_rest_carryovers.clone();
+            let rest_entry_indexes = find_rest_carryover_entries(
+                &rest_carryovers,
+                &users_info
+                    .iter()
+                    .map(|user_info| user_info.user_public_key)
+                    .collect::<Vec<Pubkey>>(),
+                0,
+            )?;
+            next_user_entry_index = rest_carryovers.len();
+
+            let mut remaining_rest_carryovers = Vec::new();
+            for (rest_carryover, user_rest_entry_index) in
+                rest_carryovers.into_iter().zip(rest_entry_indexes)
+            {
+                if blocks_state.bottom_block_available_bp == 0 {
+                    remaining_rest_carryovers.push(rest_carryover);
+                    continue;
                 }
-            };
 
-            let user_rest_bp = blocks_state
-                .bottom_block_last_account_rest_bp
-                .min(blocks_state.bottom_block_available_bp);
-            let user_rest_transfer_amount: u64;
-            if user_rest_bp < blocks_state.bottom_block_available_bp {
-                user_rest_transfer_amount = calculate_single_reward(user_rest_bp, dust_per_bp)?;
-            } else {
-                user_rest_transfer_amount = blocks_state.bottom_block_balance;
-            }
+                let account = find_user_account(
+                    ctx.remaining_accounts,
+                    &rest_carryover.user,
+                    positional_accounts.then_some(user_rest_entry_index),
+                );
+                let account_info = match account {
+                    Some(acc) => acc.to_account_info(),
+                    None => {
+                        return err!(
+                            SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount
+                        )
+                    }
+                };
+                let user_key = account_info.key();
+                with_user_entry_context(
+                    valid_recipient_account(
+                        &ctx.accounts.config_account,
+                        &account_info,
+                        &mint_key,
+                        &token_program_key,
+                    ),
+                    user_rest_entry_index,
+                    &user_key,
+                )?;
 
-            transfer_tokens(
-                &ctx.accounts.distribution_bottom_block_account,
-                account_info,
-                DISTRIBUTION_BOTTOM_BLOCK_SEED,
-                ctx.accounts.token_program.to_account_info(),
-                blocks_state.bottom_block_distribution_nonce,
-                user_rest_transfer_amount,
-            )?;
+                let user_rest_bp = rest_carryover
+                    .rest_bp
+                    .min(blocks_state.bottom_block_available_bp);
+                let (user_rest_transfer_amount, rounding_remainder) = allocate_reward_from_block(
+                    blocks_state.bottom_block_balance,
+                    blocks_state.bottom_block_available_bp,
+                    blocks_state.bottom_block_rounding_remainder,
+                    user_rest_bp,
+                    dust_per_bp,
+                )?;
+                blocks_state.bottom_block_rounding_remainder = rounding_remainder;
+
+                let payout_amount = with_user_entry_context(
+                    distribute_reward(
+                        Subsystem::BottomBlock,
+                        blocks_state,
+                        &mut distribution_source,
+                        account_info,
+                        ctx.accounts.denylist_account.as_deref(),
+                        ctx.remaining_accounts,
+                        user_rest_transfer_amount,
+                    ),
+                    user_rest_entry_index,
+                    &user_key,
+                )?;
+                if payout_amount > 0 {
+                    emit!(BottomBlockRewardPaid {
+                        user: user_key,
+                        block_number,
+                        bp: user_rest_bp,
+                        amount: payout_amount,
+                        reward_breakdown: None,
+                        solve_batch_sequence,
+                    });
+                }
 
-            blocks_state.bottom_block_available_bp =
-                blocks_state.bottom_block_available_bp - user_rest_bp;
-            blocks_state.bottom_block_last_account_rest_bp =
-                blocks_state.bottom_block_last_account_rest_bp - user_rest_bp;
-            blocks_state.bottom_block_balance =
-                blocks_state.bottom_block_balance - user_rest_transfer_amount;
+                blocks_state.bottom_block_available_bp = blocks_state
+                    .bottom_block_available_bp
+                    .checked_sub(user_rest_bp)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                blocks_state.bottom_block_last_account_rest_bp = blocks_state
+                    .bottom_block_last_account_rest_bp
+                    .checked_sub(user_rest_bp)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                blocks_state.bottom_block_balance = blocks_state
+                    .bottom_block_balance
+                    .checked_sub(user_rest_transfer_amount)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                distributed_amount = distributed_amount
+                    .checked_add(user_rest_transfer_amount)
+                    .ok_or(SallarError::ArithmeticOverflow)?;
+                solvers.push(BlockSolver {
+                    user: user_key,
+                    amount: user_rest_transfer_amount,
+                });
+                paid_amounts.push(UserPaidAmount {
+                    user: user_key,
+                    block_number,
+                    settled_amount: user_rest_transfer_amount,
+                    paid_amount: payout_amount,
+                });
+                if user_rest_bp < rest_carryover.rest_bp {
+                    remaining_rest_carryovers.push(RestCarryover {
+                        user: rest_carryover.user,
+                        rest_bp: rest_carryover.rest_bp - user_rest_bp,
+                    });
+                }
+            }
+            blocks_state.bottom_block_rest_carryovers = remaining_rest_carryovers;
         }
-        let users_info_without_info_for_user_rest = match has_unprocessed_rest_from_last_block {
-            true => users_info
-                .into_iter()
-                .skip(1)
-                .collect::<Vec<UserInfoBottomBlock>>(),
-            false => users_info,
-        };
 
-        for user_info in &users_info_without_info_for_user_rest {
-            require!(
-                blocks_state.bottom_block_available_bp > 0,
-                SallarError::UserRequestForSolvedBlock
+        for (user_entry_index, user_info) in
+            users_info.iter().enumerate().skip(next_user_entry_index)
+        {
+            let recipient_account = with_user_entry_context(
+                match &user_info.wallet {
+                    Some(wallet) => prepare_associated_token_account(
+                        ctx.remaining_accounts,
+                        &user_info.user_public_key,
+                        wallet,
+                        positional_accounts.then_some(user_entry_index),
+                        &associated_token_account_creation,
+                    ),
+                    None => Ok(()),
+                }
+                .and_then(|_| {
+                    find_recipient_account(
+                        &ctx.accounts.config_account,
+                        ctx.remaining_accounts,
+                        &user_info.user_public_key,
+                        &mint_key,
+                        &token_program_key,
+                        positional_accounts.then_some(user_entry_index),
+                    )
+                })
+                .and_then(|account_info| {
+                    if let (Some(block_user_requests_account), Some(_)) = (
+                        ctx.accounts.block_user_requests_account.as_mut(),
+                        ctx.accounts.system_program.as_ref(),
+                    ) {
+                        record_user_requests(
+                            block_user_requests_account,
+                            block_number,
+                            &user_info.user_public_key,
+                            u16::from(user_info.user_request_without_boost)
+                                + u16::from(user_info.user_request_with_boost),
+                            max_user_requests,
+                        )?;
+                    }
+                    if boost_credits_required && user_info.user_request_with_boost > 0 {
+                        consume_boost_credits(
+                            ctx.remaining_accounts,
+                            &user_info.user_public_key,
+                            user_info.user_request_with_boost,
+                        )?;
+                    }
+                    Ok(account_info)
+                }),
+                user_entry_index,
+                &user_info.user_public_key,
             );
-
-            let account = ctx
-                .remaining_accounts
-                .iter()
-                .find(|account| account.key() == user_info.user_public_key);
-            let account_info = match account {
-                Some(acc) => acc.to_account_info(),
-                None => return err!(SallarError::MismatchBetweenRemainingAccountsAndUserInfo),
+            let account_info = match recipient_account {
+                Ok(account_info) => account_info,
+                Err(error) if !strict => {
+                    skip_user_entry(
+                        &mut skipped_user_entries,
+                        BlockKind::Bottom,
+                        user_entry_index,
+                        &user_info.user_public_key,
+                        &error,
+                        solve_batch_sequence,
+                    );
+                    continue;
+                }
+                Err(error) => return Err(error),
             };
 
+            let user_balance =
+                find_user_stake_lock_account(ctx.remaining_accounts, &user_info.user_public_key)
+                    .map_or(0, |stake_lock_account| {
+                        stake_lock_balance(&stake_lock_account, blocks_state.bottom_block_number)
+                    });
             let bottom_bp_with_boost =
-                calculate_bottom_bp_with_boost(block_number, user_info.user_balance)?;
-            let bottom_bp_without_boost = calculate_bottom_bp_without_boost(user_info.user_balance);
+                calculate_bottom_bp_with_given_boost(user_balance, bottom_block_boost)?;
+            let bottom_bp_without_boost = calculate_bottom_bp_without_boost(user_balance)?;
+
+            let reward_breakdown = calculate_user_reward_breakdown_bottom_block(
+                user_info.user_request_without_boost,
+                user_info.user_request_with_boost,
+                bottom_bp_without_boost,
+                bottom_bp_with_boost,
+                dust_per_bp,
+                user_balance,
+                blocks_state.bottom_block_min_required_stake,
+            )?;
+            current_user_reward_bp = reward_breakdown.bp()?;
 
-            (current_user_reward_bp, current_user_transfer_amount) =
-                calculate_user_reward_bottom_block(
-                    user_info.user_request_without_boost,
-                    user_info.user_request_with_boost,
-                    bottom_bp_without_boost,
-                    bottom_bp_with_boost,
-                    dust_per_bp,
-                    user_info.user_balance,
-                )?;
+            if blocks_state.bottom_block_available_bp == 0 {
+                if current_user_reward_bp > 0 {
+                    with_user_entry_context(
+                        push_rest_carryover(
+                            &mut blocks_state.bottom_block_rest_carryovers,
+                            BlockKind::Bottom,
+                            block_number,
+                            user_info.user_public_key,
+                            current_user_reward_bp,
+                            solve_batch_sequence,
+                        ),
+                        user_entry_index,
+                        &user_info.user_public_key,
+                    )?;
+                    blocks_state.bottom_block_last_account_rest_bp = blocks_state
+                        .bottom_block_last_account_rest_bp
+                        .checked_add(current_user_reward_bp)
+                        .ok_or(SallarError::ArithmeticOverflow)?;
+                }
+                blocks_state.bottom_block_last_account_address = Some(user_info.user_public_key);
+                continue;
+            }
 
+            let user_bp = current_user_reward_bp.min(blocks_state.bottom_block_available_bp);
+            let (current_user_transfer_amount, rounding_remainder) = allocate_reward_from_block(
+                blocks_state.bottom_block_balance,
+                blocks_state.bottom_block_available_bp,
+                blocks_state.bottom_block_rounding_remainder,
+                user_bp,
+                dust_per_bp,
+            )?;
+            blocks_state.bottom_block_rounding_remainder = rounding_remainder;
             if current_user_reward_bp <= blocks_state.bottom_block_available_bp {
-                blocks_state.bottom_block_last_account_rest_bp = 0;
-                blocks_state.bottom_block_available_bp -= current_user_reward_bp;
+                blocks_state.bottom_block_available_bp = blocks_state
+                    .bottom_block_available_bp
+                    .checked_sub(current_user_reward_bp)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
             } else {
-                blocks_state.bottom_block_last_account_rest_bp =
-                    current_user_reward_bp - blocks_state.bottom_block_available_bp;
+                let user_rest_bp = current_user_reward_bp
+                    .checked_sub(blocks_state.bottom_block_available_bp)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                with_user_entry_context(
+                    push_rest_carryover(
+                        &mut blocks_state.bottom_block_rest_carryovers,
+                        BlockKind::Bottom,
+                        block_number,
+                        user_info.user_public_key,
+                        user_rest_bp,
+                        solve_batch_sequence,
+                    ),
+                    user_entry_index,
+                    &user_info.user_public_key,
+                )?;
+                blocks_state.bottom_block_last_account_rest_bp = blocks_state
+                    .bottom_block_last_account_rest_bp
+                    .checked_add(user_rest_bp)
+                    .ok_or(SallarError::ArithmeticOverflow)?;
                 blocks_state.bottom_block_available_bp = 0;
             }
 
-            if blocks_state.bottom_block_available_bp == 0 {
-                current_user_transfer_amount = blocks_state.bottom_block_balance;
-            }
-
-            transfer_tokens(
-                &ctx.accounts.distribution_bottom_block_account,
-                account_info,
-                DISTRIBUTION_BOTTOM_BLOCK_SEED,
-                ctx.accounts.token_program.to_account_info(),
-                blocks_state.bottom_block_distribution_nonce,
-                current_user_transfer_amount,
+            let payout_amount = with_user_entry_context(
+                distribute_reward(
+                    Subsystem::BottomBlock,
+                    blocks_state,
+                    &mut distribution_source,
+                    account_info,
+                    ctx.accounts.denylist_account.as_deref(),
+                    ctx.remaining_accounts,
+                    current_user_transfer_amount,
+                ),
+                user_entry_index,
+                &user_info.user_public_key,
             )?;
+            if payout_amount > 0 {
+                emit!(BottomBlockRewardPaid {
+                    user: user_info.user_public_key,
+                    block_number,
+                    bp: user_bp,
+                    amount: payout_amount,
+                    reward_breakdown: Some(reward_breakdown),
+                    solve_batch_sequence,
+                });
+            }
 
-            blocks_state.bottom_block_balance -= current_user_transfer_amount;
+            blocks_state.bottom_block_balance = blocks_state
+                .bottom_block_balance
+                .checked_sub(current_user_transfer_amount)
+                .ok_or(SallarError::ArithmeticUnderflow)?;
+            distributed_amount = distributed_amount
+                .checked_add(current_user_transfer_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+            solvers.push(BlockSolver {
+                user: user_info.user_public_key,
+                amount: current_user_transfer_amount,
+            });
+            paid_amounts.push(UserPaidAmount {
+                user: user_info.user_public_key,
+                block_number,
+                settled_amount: current_user_transfer_amount,
+                paid_amount: payout_amount,
+            });
             blocks_state.bottom_block_last_account_address = Some(user_info.user_public_key);
         }
 
+        transfer_accumulated_fee(&mut distribution_source)?;
+
+        if blocks_state.bottom_block_available_bp == 0 {
+            if let Some(treasury_token_account) = &ctx.accounts.treasury_token_account {
+                ctx.accounts.distribution_bottom_block_account.reload()?;
+                let surplus_amount = calculate_distribution_surplus(
+                    ctx.accounts.distribution_bottom_block_account.amount,
+                    blocks_state.bottom_block_balance,
+                    blocks_state.bottom_block_pending_payouts,
+                );
+                if surplus_amount > 0 {
+                    msg!(
+                        "Transferring surplus of bottom block distribution account to treasury: {}",
+                        surplus_amount
+                    );
+                    transfer_tokens(
+                        &ctx.accounts.distribution_bottom_block_account,
+                        treasury_token_account.to_account_info(),
+                        &ctx.accounts.mint,
+                        DISTRIBUTION_BOTTOM_BLOCK_SEED,
+                        ctx.accounts.token_program.to_account_info(),
+                        ctx.accounts.config_account.bottom_block_distribution_nonce,
+                        surplus_amount,
+                    )?;
+                }
+            }
+        }
+
+        if let Some(recent_solvers_account) = ctx.accounts.recent_solvers_account.as_mut() {
+            record_recent_solvers(recent_solvers_account, BlockKind::Bottom, &solvers);
+        }
+        if let (Some(block_history_account), Some(system_program)) = (
+            ctx.accounts.block_history_account.as_mut(),
+            ctx.accounts.system_program.as_ref(),
+        ) {
+            append_block_history(
+                block_history_account,
+                BlockKind::Bottom,
+                block_number,
+                ctx.bumps.block_history_account.unwrap(),
+                solvers,
+                ctx.accounts.signer.to_account_info(),
+                system_program.to_account_info(),
+            )?;
+        }
+        if let (Some(block_user_requests_account), Some(system_program)) = (
+            ctx.accounts.block_user_requests_account.as_ref(),
+            ctx.accounts.system_program.as_ref(),
+        ) {
+            fit_block_user_requests(
+                block_user_requests_account,
+                ctx.accounts.signer.to_account_info(),
+                system_program.to_account_info(),
+            )?;
+        }
+
+        let block_solved = blocks_state.bottom_block_available_bp == 0;
         switch_bottom_block_to_next_one_if_applicable(
             blocks_state,
             mint_nonce,
@@ -471,85 +1680,587 @@ pub mod sallar {
                 .to_account_info(),
             ctx.accounts.token_program.to_account_info(),
         )?;
+        if blocks_state.bottom_block_solution_timestamp != previous_solution_timestamp {
+            let solution_timestamp = blocks_state.bottom_block_solution_timestamp;
+            record_sla_delay(
+                blocks_state,
+                SlaSubsystem::BottomBlock,
+                previous_solution_timestamp,
+                solution_timestamp,
+            );
+        }
         update_blocks_collided(blocks_state)?;
 
-        Ok(blocks_state.bottom_block_number)
-    }
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(
+                global_stats_account,
+                Subsystem::BottomBlock,
+                distributed_amount,
+            )?;
+            if block_solved {
+                record_block_solution(
+                    global_stats_account,
+                    BlockKind::Bottom,
+                    blocks_state.bottom_block_number != block_number,
+                )?;
+            }
+        }
+
+        emit!(SolveBatchSettled {
+            block_kind: BlockKind::Bottom,
+            solve_batch_sequence,
+            paid_amounts,
+        });
+
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
+        }
+
+        Ok(SolveBlockResult {
+            block_number: blocks_state.bottom_block_number,
+            solve_batch_sequence,
+            skipped_user_entries,
+        })
+    }
 
     /// Distributes tokens from final mining account to accounts passed in the input to this function.
     /// The amount of tokens transferred to particular account depends on the final mining account's balance in the moment when user requested participation in final mining on the client side so the balance is passed in the input.
+    /// The reward of the balance is given by the final mining tiers (see set_final_mining_tiers), the default tiers apply until the final mining tiers account is created.
     /// This function can be called unlimited number of times but only after all top and bottom blocks are solved.
+    /// Consecutive calls must be separated by the final mining interval (3 minutes by default) and a single call cannot distribute more than the maximum payout per call.
+    /// While the final mining budget is set (see set_final_mining_budget), the calls within a single budget window cannot distribute more than the budget in total.
+    /// While the final mining checkpoint is required (see set_final_mining_checkpoint_required), the final mining balances given in `users_info`
+    /// must match the recent final mining balance checkpoint (see checkpoint_final_mining_balance) instead of being arbitrary balances.
+    /// While the final mining claim period is set (see set_final_mining_claim_period), every user can be given in `users_info` only once
+    /// and can receive at most one final mining payout per claim period, the final mining claim period participants account tracks the users paid in the period across calls.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - the final mining context where all required accounts are provided,
-    /// * `users_info` - a vector of accounts participating in the final mining process, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts.
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account))]
+    /// * `users_info` - a vector of accounts participating in the final mining process, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts,
+    /// * `max_valid_slot` - the last slot the batch can be processed in, the batch is rejected if it is processed later (no bound if not provided).
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_mining_required_interval_elapsed(&ctx.accounts.blocks_state_account.final_mining_last_timestamp, &ctx.accounts.blocks_state_account.final_mining_interval_seconds) final_mining_tiers_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.final_mining_tiers_account) final_mining_balances_checkpointed(&ctx.accounts.blocks_state_account, &users_info) final_mining_payout_within_limit(&ctx.accounts.blocks_state_account, &ctx.accounts.final_mining_tiers_account, &users_info) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) final_mining_claim_period_participants_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.final_mining_claim_period_participants_account, &ctx.accounts.system_program) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
     pub fn final_mining<'info>(
-        ctx: Context<'_, '_, '_, 'info, FinalMiningContext<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, FinalMiningContext<'info>>,
         users_info: Vec<UserInfoFinalMining>,
+        max_valid_slot: Option<u64>,
     ) -> Result<()> {
         require!(!users_info.is_empty(), SallarError::MissingUserInfo);
+        require!(
+            ctx.accounts
+                .blocks_state_account
+                .final_mining_claim_period_seconds
+                == 0
+                || !has_duplicated_users(
+                    users_info
+                        .iter()
+                        .map(|user_info| &user_info.user_public_key)
+                ),
+            SallarError::UserDuplicatedInUserInfoForFinalMining
+        );
+        let mint_key = ctx.accounts.mint.key();
+        let token_program_key = ctx.accounts.token_program.key();
+        valid_recipient_accounts(
+            &ctx.accounts.config_account,
+            ctx.remaining_accounts,
+            users_info
+                .iter()
+                .map(|user_info| &user_info.user_public_key),
+            &mint_key,
+            &token_program_key,
+        )?;
+        let associated_token_account_creation = AssociatedTokenAccountCreation {
+            payer: ctx.accounts.signer.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx
+                .accounts
+                .associated_token_program
+                .as_ref()
+                .map(|associated_token_program| associated_token_program.to_account_info()),
+            system_program: ctx
+                .accounts
+                .system_program
+                .as_ref()
+                .map(|system_program| system_program.to_account_info()),
+        };
+        let claim_period_index =
+            final_mining_claim_period_index(&ctx.accounts.blocks_state_account);
+        if let Some(final_mining_claim_period_participants_account) = ctx
+            .accounts
+            .final_mining_claim_period_participants_account
+            .as_mut()
+        {
+            if final_mining_claim_period_participants_account.period_index == 0 {
+                final_mining_claim_period_participants_account.bump = ctx
+                    .bumps
+                    .final_mining_claim_period_participants_account
+                    .unwrap();
+                final_mining_claim_period_participants_account.period_index = claim_period_index;
+            }
+        }
         let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.final_mining_account,
+            seed: FINAL_MINING_ACCOUNT_SEED,
+            nonce: ctx.accounts.config_account.final_mining_account_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::FinalMining),
+            fee_destination: None,
+            fee_amount: 0,
+        };
+        let tiers = final_mining_tiers(ctx.accounts.final_mining_tiers_account.as_deref());
+        let mut distributed_amount: u64 = 0;
 
-        for account in ctx.remaining_accounts.iter() {
+        for (index, account) in ctx
+            .remaining_accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, account)| {
+                !account.is_signer
+                    && !users_info
+                        .iter()
+                        .any(|user_info| user_info.wallet == Some(account.key()))
+            })
+        {
             let user_find_result = users_info
                 .iter()
                 .filter(|user_info| user_info.user_public_key == account.key())
                 .collect::<Vec<&UserInfoFinalMining>>();
 
-            require!(
-                user_find_result.len() > 0,
-                SallarError::MismatchBetweenRemainingAccountsAndUserInfo
-            );
+            with_user_entry_context(
+                if user_find_result.is_empty() {
+                    err!(SallarError::MismatchBetweenRemainingAccountsAndUserInfo)
+                } else {
+                    match user_find_result
+                        .iter()
+                        .find_map(|user_info| user_info.wallet)
+                    {
+                        Some(wallet) => prepare_associated_token_account(
+                            ctx.remaining_accounts,
+                            account.key,
+                            &wallet,
+                            None,
+                            &associated_token_account_creation,
+                        ),
+                        None => Ok(()),
+                    }
+                    .and_then(|_| {
+                        valid_recipient_account(
+                            &ctx.accounts.config_account,
+                            account,
+                            &mint_key,
+                            &token_program_key,
+                        )
+                    })
+                    .and_then(|_| {
+                        match ctx
+                            .accounts
+                            .final_mining_claim_period_participants_account
+                            .as_mut()
+                        {
+                            Some(final_mining_claim_period_participants_account) => {
+                                record_final_mining_claimant(
+                                    final_mining_claim_period_participants_account,
+                                    claim_period_index,
+                                    account.key,
+                                )
+                            }
+                            None => Ok(()),
+                        }
+                    })
+                },
+                index,
+                account.key,
+            )?;
 
-            let mut total_amount = 0;
+            let mut total_amount: u64 = 0;
             for user_sub_info in &user_find_result {
-                let transfer_amount = match user_sub_info.final_mining_balance {
-                    0...12_499_999_999_999_999 => 2_500_000_000,
-                    12_500_000_000_000_000...24_999_999_999_999_999 => 5_000_000_000,
-                    25_000_000_000_000_000...49_999_999_999_999_999 => 10_000_000_000,
-                    50_000_000_000_000_000...99_999_999_999_999_999 => 25_000_000_000,
-                    _ => 50_000_000_000,
-                };
-                total_amount += transfer_amount;
+                total_amount = total_amount
+                    .checked_add(calculate_final_mining_reward(
+                        tiers,
+                        user_sub_info.final_mining_balance,
+                    ))
+                    .ok_or(SallarError::ArithmeticOverflow)?;
             }
-            transfer_tokens(
-                &ctx.accounts.final_mining_account,
-                account.to_account_info(),
-                FINAL_MINING_ACCOUNT_SEED,
-                ctx.accounts.token_program.to_account_info(),
-                blocks_state.final_mining_account_nonce,
-                total_amount,
+            let payout_amount = with_user_entry_context(
+                distribute_reward(
+                    Subsystem::FinalMining,
+                    blocks_state,
+                    &mut distribution_source,
+                    account.to_account_info(),
+                    ctx.accounts.denylist_account.as_deref(),
+                    ctx.remaining_accounts,
+                    total_amount,
+                ),
+                index,
+                account.key,
+            )?;
+            if payout_amount > 0 {
+                emit!(FinalMiningRewardPaid {
+                    user: account.key(),
+                    amount: payout_amount,
+                });
+            }
+            distributed_amount = distributed_amount
+                .checked_add(total_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+        }
+        if let (Some(final_mining_claim_period_participants_account), Some(system_program)) = (
+            ctx.accounts
+                .final_mining_claim_period_participants_account
+                .as_ref(),
+            ctx.accounts.system_program.as_ref(),
+        ) {
+            fit_final_mining_claim_period_participants(
+                final_mining_claim_period_participants_account,
+                ctx.accounts.signer.to_account_info(),
+                system_program.to_account_info(),
+            )?;
+        }
+
+        let timestamp = current_cluster_timestamp()?;
+        record_final_mining_budget(blocks_state, distributed_amount, timestamp)?;
+        blocks_state.final_mining_last_timestamp = timestamp;
+
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(
+                global_stats_account,
+                Subsystem::FinalMining,
+                distributed_amount,
             )?;
         }
 
         Ok(())
     }
 
+    /// Records the final mining account's balance at the current slot.
+    /// While the final mining checkpoint is required, final_mining accepts only the final mining balance of the recent checkpoint,
+    /// so the reward tiers cannot be picked from arbitrary balances. The checkpoint is recent for about 3 minutes.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the checkpoint final mining balance context where all required accounts are provided.
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account))]
+    pub fn checkpoint_final_mining_balance<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckpointFinalMiningBalanceContext<'info>>,
+    ) -> Result<()> {
+        let balance = ctx.accounts.final_mining_account.amount;
+        let slot = Clock::get()?.slot;
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        blocks_state.final_mining_checkpoint_balance = balance;
+        blocks_state.final_mining_checkpoint_slot = slot;
+
+        emit!(FinalMiningBalanceCheckpointed { balance, slot });
+
+        Ok(())
+    }
+
+    /// Opens the next final mining epoch with the participation Merkle root of the epoch published by the coordinator.
+    /// The participants of the epoch claim their final mining rewards themselves with the claim_final_mining instruction,
+    /// once the epoch is opened the rewards of the previous epoch cannot be claimed anymore.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the publish final mining participation root context where all required accounts are provided,
+    /// * `merkle_root` - the Merkle root of the token accounts participating in the epoch (see calculate_final_mining_participation_leaf),
+    /// * `max_valid_slot` - the last slot the root can be published in, the root is rejected if it is processed later (no bound if not provided).
+    ///
+    /// ### Returns
+    /// The index of the opened epoch.
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account))]
+    pub fn publish_final_mining_participation_root(
+        ctx: Context<PublishFinalMiningParticipationRootContext>,
+        merkle_root: [u8; 32],
+        max_valid_slot: Option<u64>,
+    ) -> Result<u64> {
+        start_final_mining_epoch(
+            &mut ctx.accounts.blocks_state_account,
+            &mut ctx.accounts.final_mining_epoch_account,
+            ctx.bumps.final_mining_epoch_account,
+            merkle_root,
+            false,
+        )
+    }
+
+    /// Opens the next final mining epoch without the participation root, so every token account of the mint can claim one final mining reward in the epoch.
+    /// Anyone can open the epoch, but only once the coordinator has neither published a participation root nor called final_mining
+    /// for the coordinator timeout (FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS), so final mining keeps functioning if the coordinator goes offline.
+    /// The rewards claimed in open epochs are still bounded by the final mining tiers and the final mining budget.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the open final mining epoch context where all required accounts are provided.
+    ///
+    /// ### Returns
+    /// The index of the opened epoch.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_mining_coordinator_inactive(&ctx.accounts.blocks_state_account))]
+    pub fn open_final_mining_epoch(ctx: Context<OpenFinalMiningEpochContext>) -> Result<u64> {
+        start_final_mining_epoch(
+            &mut ctx.accounts.blocks_state_account,
+            &mut ctx.accounts.final_mining_epoch_account,
+            ctx.bumps.final_mining_epoch_account,
+            [0; 32],
+            true,
+        )
+    }
+
+    /// Claims the final mining reward of a participant of the current final mining epoch.
+    /// The participation is verified against the Merkle root published for the epoch, unless the epoch is opened without the participation root.
+    /// The reward is calculated on-chain from the final mining account's balance and the final mining tiers (see set_final_mining_tiers)
+    /// and distributed from final mining account the same way as the rewards distributed by the final_mining instruction.
+    /// Anyone can claim the reward on behalf of the user, the tokens are always transferred to the user's token account.
+    /// Each user can claim the reward only once per epoch and the claims count towards the final mining budget (see set_final_mining_budget).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the claim final mining context where all required accounts are provided,
+    /// * `epoch_index` - the index of the current final mining epoch,
+    /// * `proof` - the Merkle proof of the participation of the user, i.e. the sibling nodes from the leaf up to the root (ignored in open epochs).
+    ///
+    /// ### Returns
+    /// A successful result or an error if the proof is invalid.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_mining_tiers_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.final_mining_tiers_account) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
+    pub fn claim_final_mining<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimFinalMiningContext<'info>>,
+        epoch_index: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let user_key = ctx.accounts.user_token_account.key();
+        let final_mining_epoch_account = &mut ctx.accounts.final_mining_epoch_account;
+        require!(
+            epoch_index == ctx.accounts.blocks_state_account.final_mining_epoch_counter,
+            SallarError::FinalMiningEpochNotCurrent
+        );
+        require!(
+            final_mining_epoch_account.open
+                || verify_merkle_proof(
+                    &proof,
+                    &final_mining_epoch_account.merkle_root,
+                    calculate_final_mining_participation_leaf(&user_key, epoch_index),
+                ),
+            SallarError::InvalidMerkleProof
+        );
+        let tiers = final_mining_tiers(ctx.accounts.final_mining_tiers_account.as_deref());
+        let amount = calculate_final_mining_reward(tiers, ctx.accounts.final_mining_account.amount);
+        require!(amount > 0, SallarError::FinalMiningBalanceBelowTiers);
+
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        record_final_mining_budget(blocks_state, amount, current_cluster_timestamp()?)?;
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.final_mining_account,
+            seed: FINAL_MINING_ACCOUNT_SEED,
+            nonce: ctx.accounts.config_account.final_mining_account_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::FinalMining),
+            fee_destination: None,
+            fee_amount: 0,
+        };
+        let payout_amount = distribute_reward(
+            Subsystem::FinalMining,
+            blocks_state,
+            &mut distribution_source,
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.denylist_account.as_deref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        final_mining_epoch_account.claimed_amount = final_mining_epoch_account
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        let final_mining_claim_account = &mut ctx.accounts.final_mining_claim_account;
+        final_mining_claim_account.bump = ctx.bumps.final_mining_claim_account;
+        final_mining_claim_account.epoch_index = epoch_index;
+        final_mining_claim_account.user = user_key;
+        final_mining_claim_account.amount = amount;
+
+        emit!(FinalMiningRewardClaimed {
+            user: user_key,
+            epoch_index,
+            amount,
+            payout_amount,
+        });
+
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(global_stats_account, Subsystem::FinalMining, amount)?;
+        }
+
+        Ok(())
+    }
+
     /// Distributes tokens from final staking account to accounts passed in the input to this function.
     /// Final staking processed is organized as rounds. At the beginning of each round 0.1% of the current final staking account balance is reserved as the prize pool for the round.
+    /// Rewards are rounded down and the balance left in a round once its reward parts are used up is added to the prize pool of the next round (except for the final round, where the last user receives it).
     /// The amount of tokens transferred to particular account depends on the account's balance and the prize pool of the current round.
+    /// Rounds are numbered by the round counter stored in the state, `FinalStakingRoundStarted` and `FinalStakingRoundCompleted` events are emitted when a round starts and completes.
+    /// If the final staking round account is provided, the pool, the start and end timestamps, the processed participants and the distributed amount of the round are recorded in it.
     /// This function can be called unlimited number of times but only after all top and bottom blocks are solved.
     /// The function cannot be invoked for 20 hours after the final staking round has been completed.
+    /// The final staking account balance reserved for the claims of the rounds opened in the claim mode is not taken into account.
+    /// While final staking snapshots are required, the reward part of each user is calculated from the balance snapshot registered for the round (see register_for_final_staking)
+    /// instead of the reward part given in `users_info`, the final staking registration accounts of the users must be provided among remaining accounts then.
+    /// Otherwise the final staking round participants account must be provided, it tracks the users rewarded in the round, so a user cannot be rewarded twice in a round across calls.
+    /// While the final staking reward part tolerance is set (see set_final_staking_reward_part_tolerance), the reward part given in `users_info` for each user
+    /// must not exceed the user's share of the registered balance of the round by more than the tolerance.
+    /// A single call can reward up to MAX_USERS_PER_FINAL_STAKING_CALL user info entries, larger rounds are distributed by multiple calls.
+    /// Rewards of the users restaking their final staking rewards (see set_final_staking_auto_restake) are deposited to their stake vaults and locked
+    /// if their stake lock accounts and stake vaults are provided among remaining accounts, `FinalStakingRewardRestaked` events are emitted for them instead of `FinalStakingRewardPaid`.
+    /// Once the prize pool of a new round would be below the minimum pool in round, the terminal transition is performed instead:
+    /// depending on the terminal mode the whole remaining balance is either distributed in one final round or swept to the treasury (the requests of the call are not processed then),
+    /// and final staking is finalized, so it cannot be invoked anymore.
     ///
     /// ### Arguments
     ///
     /// * `ctx` - the final staking context where all required accounts are provided,
-    /// * `users_info` - a vector of accounts participating in the final staking process, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts.
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_staking_required_interval_elapsed(&ctx.accounts.blocks_state_account.final_staking_last_staking_timestamp))]
+    /// * `users_info` - a vector of accounts participating in the final staking process, containing the information for each of the accounts needed to calculate the number of tokens to distribute to the accounts,
+    /// * `max_valid_slot` - the last slot the batch can be processed in, the batch is rejected if it is processed later (no bound if not provided).
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_staking_not_finalized(&ctx.accounts.blocks_state_account) final_staking_required_interval_elapsed(&ctx.accounts.blocks_state_account.final_staking_last_staking_timestamp, &ctx.accounts.blocks_state_account.final_staking_interval_seconds) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) final_staking_round_participants_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.final_staking_round_participants_account, &ctx.accounts.system_program) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
     pub fn final_staking<'info>(
-        ctx: Context<'_, '_, '_, 'info, FinalStakingContext<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, FinalStakingContext<'info>>,
         users_info: Vec<UserInfoFinalStaking>,
+        max_valid_slot: Option<u64>,
     ) -> Result<()> {
+        require!(
+            users_info.len() <= MAX_USERS_PER_FINAL_STAKING_CALL,
+            SallarError::FinalStakingCallTooLarge
+        );
+
+        let mint_key = ctx.accounts.mint.key();
+        let token_program_key = ctx.accounts.token_program.key();
+        valid_recipient_accounts(
+            &ctx.accounts.config_account,
+            ctx.remaining_accounts,
+            users_info
+                .iter()
+                .map(|user_info| &user_info.user_public_key),
+            &mint_key,
+            &token_program_key,
+        )?;
+        let associated_token_account_creation = AssociatedTokenAccountCreation {
+            payer: ctx.accounts.signer.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx
+                .accounts
+                .associated_token_program
+                .as_ref()
+                .map(|associated_token_program| associated_token_program.to_account_info()),
+            system_program: ctx
+                .accounts
+                .system_program
+                .as_ref()
+                .map(|system_program| system_program.to_account_info()),
+        };
         let blocks_state = &mut ctx.accounts.blocks_state_account;
-        let mut total_users_reward_part = 0.0;
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.final_staking_account,
+            seed: FINAL_STAKING_ACCOUNT_SEED,
+            nonce: ctx.accounts.config_account.final_staking_account_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::FinalStaking),
+            fee_destination: None,
+            fee_amount: 0,
+        };
+        let mut total_users_reward_part: u64 = 0;
+        let mut distributed_amount: u64 = 0;
+        let mut participants_processed = 0;
+        let final_staking_snapshots_required = blocks_state.final_staking_snapshots_required;
+        let reward_part_tolerance_bps = blocks_state.final_staking_reward_part_tolerance_bps;
 
         if blocks_state.final_staking_left_balance_in_round == 0 {
-            let final_staking_account_balance =
-                token::accessor::amount(&ctx.accounts.final_staking_account.to_account_info())?;
+            let final_staking_account_balance = ctx
+                .accounts
+                .final_staking_account
+                .amount
+                .saturating_sub(blocks_state.final_staking_unclaimed_root_rewards);
             blocks_state.final_staking_pool_in_round = final_staking_account_balance
-                / FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR;
+                / blocks_state.final_staking_pool_division_factor
+                + blocks_state.final_staking_carried_remainder;
+            blocks_state.final_staking_carried_remainder = 0;
+
+            if final_staking_terminal_transition_required(
+                blocks_state,
+                blocks_state.final_staking_pool_in_round,
+            ) {
+                match blocks_state.final_staking_terminal_mode {
+                    FinalStakingTerminalMode::DistributeRemaining
+                        if final_staking_account_balance == 0 =>
+                    {
+                        finalize_final_staking(blocks_state, 0)?;
+                        if let Some(global_stats_account) =
+                            ctx.accounts.global_stats_account.as_mut()
+                        {
+                            record_distribution_batch(
+                                global_stats_account,
+                                Subsystem::FinalStaking,
+                                0,
+                            )?;
+                        }
+
+                        if let Some(state_mirror_account) =
+                            ctx.accounts.state_mirror_account.as_mut()
+                        {
+                            sync_state_mirror(state_mirror_account, blocks_state);
+                        }
+
+                        return Ok(());
+                    }
+                    FinalStakingTerminalMode::DistributeRemaining => {
+                        msg!(
+                            "Distributing remaining final staking account balance in the final round: {}",
+                            final_staking_account_balance
+                        );
+                        blocks_state.final_staking_pool_in_round = final_staking_account_balance;
+                        blocks_state.final_staking_final_round = true;
+                    }
+                    FinalStakingTerminalMode::SweepToTreasury => {
+                        let treasury_token_account = ctx
+                            .accounts
+                            .treasury_token_account
+                            .as_ref()
+                            .ok_or(SallarError::MissingTreasuryAccount)?;
+                        msg!(
+                            "Transferring remaining final staking account balance to treasury: {}",
+                            final_staking_account_balance
+                        );
+                        if final_staking_account_balance > 0 {
+                            transfer_tokens(
+                                &ctx.accounts.final_staking_account,
+                                treasury_token_account.to_account_info(),
+                                &ctx.accounts.mint,
+                                FINAL_STAKING_ACCOUNT_SEED,
+                                ctx.accounts.token_program.to_account_info(),
+                                ctx.accounts.config_account.final_staking_account_nonce,
+                                final_staking_account_balance,
+                            )?;
+                        }
+                        blocks_state.final_staking_pool_in_round = 0;
+                        finalize_final_staking(blocks_state, final_staking_account_balance)?;
+                        if let Some(global_stats_account) =
+                            ctx.accounts.global_stats_account.as_mut()
+                        {
+                            record_distribution_batch(
+                                global_stats_account,
+                                Subsystem::FinalStaking,
+                                0,
+                            )?;
+                        }
+
+                        if let Some(state_mirror_account) =
+                            ctx.accounts.state_mirror_account.as_mut()
+                        {
+                            sync_state_mirror(state_mirror_account, blocks_state);
+                        }
+
+                        return Ok(());
+                    }
+                }
+            }
 
             require!(
                 blocks_state.final_staking_pool_in_round > 0,
@@ -559,259 +2270,4547 @@ pub mod sallar {
             blocks_state.final_staking_left_balance_in_round =
                 blocks_state.final_staking_pool_in_round;
             blocks_state.final_staking_left_reward_parts_in_round = 1.0;
+            blocks_state.final_staking_left_reward_parts_in_round_scaled =
+                REWARD_PARTS_SCALING_FACTOR;
+            blocks_state.final_staking_round_counter += 1;
+            blocks_state.final_staking_registered_balance_in_round =
+                blocks_state.final_staking_registered_balance_next_round;
+            blocks_state.final_staking_registered_balance_next_round = 0;
+            let round_start_timestamp = current_cluster_timestamp()?;
+            blocks_state.final_staking_round_start_timestamp = round_start_timestamp;
+            if let Some(final_staking_round_account) =
+                ctx.accounts.final_staking_round_account.as_mut()
+            {
+                final_staking_round_account.start_timestamp = round_start_timestamp;
+                final_staking_round_account.final_round = blocks_state.final_staking_final_round;
+            }
+
+            emit!(FinalStakingRoundStarted {
+                round_index: blocks_state.final_staking_round_counter,
+                pool_in_round: blocks_state.final_staking_pool_in_round,
+                final_round: blocks_state.final_staking_final_round,
+                timestamp: round_start_timestamp,
+            });
         }
 
-        users_info
-            .iter()
-            .for_each(|user_info| total_users_reward_part += user_info.reward_part);
+        if let Some(final_staking_round_participants_account) = ctx
+            .accounts
+            .final_staking_round_participants_account
+            .as_mut()
+        {
+            if final_staking_round_participants_account.round_index == 0 {
+                final_staking_round_participants_account.bump =
+                    ctx.bumps.final_staking_round_participants_account.unwrap();
+                final_staking_round_participants_account.round_index =
+                    blocks_state.final_staking_round_counter;
+            }
+        }
+
+        for user_info in &users_info {
+            total_users_reward_part = total_users_reward_part
+                .checked_add(user_info.reward_part)
+                .ok_or(SallarError::UserRewardPartsSumTooHigh)?;
+        }
 
         require!(
-            total_users_reward_part <= 1.0,
+            total_users_reward_part <= REWARD_PARTS_SCALING_FACTOR,
             SallarError::UserRewardPartsSumTooHigh
         );
 
-        let mut current_user_transfer_amount;
+        // the stake vaults of the users restaking their rewards are provided among remaining accounts as well, they are not recipients
+        let mut restake_vault_keys = Vec::new();
+        for user_info in &users_info {
+            if let Some((_, stake_vault_account)) =
+                find_user_restake_accounts(ctx.remaining_accounts, &user_info.user_public_key)?
+            {
+                restake_vault_keys.push(stake_vault_account.key());
+            }
+        }
 
-        for account in ctx.remaining_accounts.iter() {
+        for (index, account) in ctx
+            .remaining_accounts
+            .iter()
+            .enumerate()
+            .filter(|(_, account)| {
+                !account.is_signer
+                    && account.owner != &crate::ID
+                    && !restake_vault_keys.contains(account.key)
+                    && !users_info
+                        .iter()
+                        .any(|user_info| user_info.wallet == Some(account.key()))
+            })
+        {
             let user_find_result = users_info
                 .iter()
                 .filter(|user_info| user_info.user_public_key == account.key())
                 .collect::<Vec<&UserInfoFinalStaking>>();
 
-            require!(
-                user_find_result.len() > 0,
-                SallarError::MismatchBetweenRemainingAccountsAndUserInfo
-            );
+            with_user_entry_context(
+                if user_find_result.is_empty() {
+                    err!(SallarError::MismatchBetweenRemainingAccountsAndUserInfo)
+                } else {
+                    match user_find_result
+                        .iter()
+                        .find_map(|user_info| user_info.wallet)
+                    {
+                        Some(wallet) => prepare_associated_token_account(
+                            ctx.remaining_accounts,
+                            account.key,
+                            &wallet,
+                            None,
+                            &associated_token_account_creation,
+                        ),
+                        None => Ok(()),
+                    }
+                    .and_then(|_| {
+                        valid_recipient_account(
+                            &ctx.accounts.config_account,
+                            account,
+                            &mint_key,
+                            &token_program_key,
+                        )
+                    })
+                    .and_then(|_| {
+                        match ctx
+                            .accounts
+                            .final_staking_round_participants_account
+                            .as_mut()
+                        {
+                            Some(final_staking_round_participants_account) => {
+                                record_final_staking_participant(
+                                    final_staking_round_participants_account,
+                                    blocks_state.final_staking_round_counter,
+                                    account.key,
+                                )
+                            }
+                            None => Ok(()),
+                        }
+                    })
+                },
+                index,
+                account.key,
+            )?;
 
             for user_sub_info in &user_find_result {
+                let reward_part = if final_staking_snapshots_required {
+                    let snapshot_reward_part = with_user_entry_context(
+                        consume_final_staking_registration(
+                            ctx.remaining_accounts,
+                            account.key,
+                            blocks_state,
+                        ),
+                        index,
+                        account.key,
+                    )?;
+                    if snapshot_reward_part == 0 {
+                        continue;
+                    }
+                    snapshot_reward_part
+                } else {
+                    if let Some(tolerance_bps) = reward_part_tolerance_bps {
+                        with_user_entry_context(
+                            reward_part_within_balance_share(
+                                blocks_state,
+                                account,
+                                user_sub_info.reward_part,
+                                tolerance_bps,
+                            ),
+                            index,
+                            account.key,
+                        )?;
+                    }
+                    user_sub_info.reward_part
+                };
                 require!(
-                    user_sub_info.reward_part <= 1.0 && user_sub_info.reward_part > 0.0,
+                    reward_part <= REWARD_PARTS_SCALING_FACTOR && reward_part > 0,
                     SallarError::UserRequestExceedsAvailableRewardParts
                 );
 
                 let reward_parts_pool_after_user = blocks_state
-                    .final_staking_left_reward_parts_in_round
-                    - user_sub_info.reward_part;
-                require!(
-                    reward_parts_pool_after_user >= 0.0,
-                    SallarError::UserRequestExceedsAvailableRewardParts
-                );
-
-                if reward_parts_pool_after_user == 0.0 {
-                    current_user_transfer_amount = blocks_state.final_staking_left_balance_in_round;
-                } else {
-                    current_user_transfer_amount = convert_f64_to_u64(
-                        user_sub_info.reward_part
-                            * convert_u64_to_f64(blocks_state.final_staking_pool_in_round)?,
+                    .final_staking_left_reward_parts_in_round_scaled
+                    .checked_sub(reward_part)
+                    .ok_or(SallarError::UserRequestExceedsAvailableRewardParts)?;
+
+                let (current_user_transfer_amount, carried_remainder) =
+                    calculate_final_staking_transfer(
+                        blocks_state,
+                        reward_part,
+                        reward_parts_pool_after_user,
                     )?;
-                }
-
-                require!(
-                    current_user_transfer_amount
-                        <= blocks_state.final_staking_left_balance_in_round,
-                    SallarError::LackOfFundsToPayTheReward
-                );
 
-                transfer_tokens(
-                    &ctx.accounts.final_staking_account,
-                    account.to_account_info(),
-                    FINAL_STAKING_ACCOUNT_SEED,
-                    ctx.accounts.token_program.to_account_info(),
-                    blocks_state.final_staking_account_nonce,
-                    current_user_transfer_amount,
+                let restake_accounts = with_user_entry_context(
+                    find_user_restake_accounts(ctx.remaining_accounts, account.key),
+                    index,
+                    account.key,
+                )?;
+                let payout_amount = with_user_entry_context(
+                    distribute_reward_to(
+                        Subsystem::FinalStaking,
+                        blocks_state,
+                        &mut distribution_source,
+                        account.to_account_info(),
+                        restake_accounts.as_ref().map_or_else(
+                            || account.to_account_info(),
+                            |(_, stake_vault_account)| stake_vault_account.clone(),
+                        ),
+                        ctx.accounts.denylist_account.as_deref(),
+                        ctx.remaining_accounts,
+                        current_user_transfer_amount,
+                    ),
+                    index,
+                    account.key,
                 )?;
+                if payout_amount > 0 {
+                    match restake_accounts {
+                        Some((mut stake_lock_account, _)) => {
+                            stake_lock_account.locked_amount = stake_lock_account
+                                .locked_amount
+                                .checked_add(payout_amount)
+                                .ok_or(SallarError::ArithmeticOverflow)?;
+                            stake_lock_account.exit(&crate::ID)?;
+                            emit!(FinalStakingRewardRestaked {
+                                user: account.key(),
+                                round_index: blocks_state.final_staking_round_counter,
+                                amount: payout_amount,
+                                locked_amount: stake_lock_account.locked_amount,
+                            });
+                        }
+                        None => emit!(FinalStakingRewardPaid {
+                            user: account.key(),
+                            round_index: blocks_state.final_staking_round_counter,
+                            reward_part,
+                            amount: payout_amount,
+                        }),
+                    }
+                }
 
-                blocks_state.final_staking_left_reward_parts_in_round =
+                blocks_state.final_staking_left_reward_parts_in_round_scaled =
                     reward_parts_pool_after_user;
-                blocks_state.final_staking_left_balance_in_round -= current_user_transfer_amount;
+                blocks_state.final_staking_left_reward_parts_in_round =
+                    convert_scaled_reward_parts_to_f64(reward_parts_pool_after_user)?;
+                blocks_state.final_staking_left_balance_in_round = blocks_state
+                    .final_staking_left_balance_in_round
+                    .checked_sub(current_user_transfer_amount)
+                    .ok_or(SallarError::ArithmeticUnderflow)?;
+                distributed_amount = distributed_amount
+                    .checked_add(current_user_transfer_amount)
+                    .ok_or(SallarError::ArithmeticOverflow)?;
+                participants_processed += 1;
+                if carried_remainder > 0 {
+                    msg!(
+                        "Carrying remainder of final staking round to the next round: {}",
+                        carried_remainder
+                    );
+                    blocks_state.final_staking_left_balance_in_round = blocks_state
+                        .final_staking_left_balance_in_round
+                        .checked_sub(carried_remainder)
+                        .ok_or(SallarError::ArithmeticUnderflow)?;
+                    blocks_state.final_staking_carried_remainder = carried_remainder;
+                }
+
+                #[cfg(feature = "shadow-reads")]
+                assert_reward_parts_shadow_consistent(
+                    blocks_state.final_staking_left_reward_parts_in_round,
+                    blocks_state.final_staking_left_reward_parts_in_round_scaled,
+                );
             }
         }
 
+        if let (Some(final_staking_round_participants_account), Some(system_program)) = (
+            ctx.accounts
+                .final_staking_round_participants_account
+                .as_ref(),
+            ctx.accounts.system_program.as_ref(),
+        ) {
+            fit_final_staking_round_participants(
+                final_staking_round_participants_account,
+                ctx.accounts.signer.to_account_info(),
+                system_program.to_account_info(),
+            )?;
+        }
+
         if blocks_state.final_staking_left_balance_in_round == 0 {
-            blocks_state.final_staking_last_staking_timestamp = Clock::get()?.unix_timestamp;
+            let previous_staking_timestamp = blocks_state.final_staking_last_staking_timestamp;
+            blocks_state.final_staking_last_staking_timestamp = current_cluster_timestamp()?;
+            let staking_timestamp = blocks_state.final_staking_last_staking_timestamp;
+            record_sla_delay(
+                blocks_state,
+                SlaSubsystem::FinalStaking,
+                previous_staking_timestamp,
+                staking_timestamp,
+            );
+
+            emit!(FinalStakingRoundCompleted {
+                round_index: blocks_state.final_staking_round_counter,
+                pool_in_round: blocks_state.final_staking_pool_in_round,
+                distributed_amount: blocks_state.final_staking_pool_in_round
+                    - blocks_state.final_staking_carried_remainder,
+                timestamp: staking_timestamp,
+            });
+
+            if blocks_state.final_staking_final_round {
+                let final_round_amount = blocks_state.final_staking_pool_in_round;
+                finalize_final_staking(blocks_state, final_round_amount)?;
+            }
+        }
+
+        if let Some(final_staking_round_account) = ctx.accounts.final_staking_round_account.as_mut()
+        {
+            record_final_staking_round(
+                final_staking_round_account,
+                ctx.bumps.final_staking_round_account.unwrap(),
+                blocks_state,
+                participants_processed,
+                distributed_amount,
+            )?;
+        }
+
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(
+                global_stats_account,
+                Subsystem::FinalStaking,
+                distributed_amount,
+            )?;
+        }
+
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
         }
 
         Ok(())
     }
 
-    /// Sets new authority
+    /// Force-closes the current final staking round, e.g. if rounding leftovers prevent its prize pool from being distributed completely.
+    /// The balance left in the round is carried to the prize pool of the next round,
+    /// the round is completed and the final staking interval starts, as if the whole prize pool was distributed.
+    /// If the closed round is the final one, final staking is finalized.
+    /// The function cannot be invoked until the final staking interval passes since the round was started.
     ///
     /// ### Arguments
     ///
-    /// * `new_authority` - new authority
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
-    pub fn change_authority<'info>(
-        ctx: Context<'_, '_, '_, 'info, ChangeAuthorityContext<'info>>,
-        new_authority: Pubkey,
-    ) -> Result<()> {
-        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
-        blocks_state_account.authority = new_authority;
+    /// * `ctx` - the close final staking round context where all required accounts are provided.
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) blocks_collided(&ctx.accounts.blocks_state_account) final_staking_not_finalized(&ctx.accounts.blocks_state_account) final_staking_round_in_progress(&ctx.accounts.blocks_state_account) final_staking_required_interval_elapsed(&ctx.accounts.blocks_state_account.final_staking_round_start_timestamp, &ctx.accounts.blocks_state_account.final_staking_interval_seconds) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account))]
+    pub fn close_final_staking_round(ctx: Context<CloseFinalStakingRoundContext>) -> Result<()> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let residual_amount = blocks_state.final_staking_left_balance_in_round;
+        msg!(
+            "Returning residual balance of final staking round to the pool: {}",
+            residual_amount
+        );
+        blocks_state.final_staking_left_balance_in_round = 0;
+        blocks_state.final_staking_carried_remainder = blocks_state
+            .final_staking_carried_remainder
+            .checked_add(residual_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        blocks_state.final_staking_left_reward_parts_in_round = 0.0;
+        blocks_state.final_staking_left_reward_parts_in_round_scaled = 0;
+
+        let previous_staking_timestamp = blocks_state.final_staking_last_staking_timestamp;
+        blocks_state.final_staking_last_staking_timestamp = current_cluster_timestamp()?;
+        let staking_timestamp = blocks_state.final_staking_last_staking_timestamp;
+        record_sla_delay(
+            blocks_state,
+            SlaSubsystem::FinalStaking,
+            previous_staking_timestamp,
+            staking_timestamp,
+        );
+
+        emit!(FinalStakingRoundCompleted {
+            round_index: blocks_state.final_staking_round_counter,
+            pool_in_round: blocks_state.final_staking_pool_in_round,
+            distributed_amount: blocks_state.final_staking_pool_in_round - residual_amount,
+            timestamp: staking_timestamp,
+        });
+
+        if blocks_state.final_staking_final_round {
+            let final_round_amount = blocks_state
+                .final_staking_pool_in_round
+                .checked_sub(residual_amount)
+                .ok_or(SallarError::ArithmeticUnderflow)?;
+            finalize_final_staking(blocks_state, final_round_amount)?;
+        }
+
+        if let Some(final_staking_round_account) = ctx.accounts.final_staking_round_account.as_mut()
+        {
+            let bump = final_staking_round_account.bump;
+            record_final_staking_round(final_staking_round_account, bump, blocks_state, 0, 0)?;
+        }
+
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
+        }
 
         Ok(())
     }
 
-    /// Set blocks collided flag
-    /// This function is only available in tests
+    /// Opens a final staking round in the claim mode.
+    /// Instead of distributing tokens to the accounts participating in final staking, the Merkle root of the (user, amount) pairs of the round is recorded
+    /// and the prize pool of the round is reserved on final staking account for the users, who claim their rewards with the claim_final_staking_reward instruction.
+    /// The prize pool is calculated the same way as by the final_staking instruction (including the remainder carried from the previous round) and the round is completed once it is opened,
+    /// so the next round (of either mode) can be started after the final staking interval.
+    /// The function cannot be invoked while a round started by the final_staking instruction is in progress
+    /// nor once the terminal transition of final staking is required (it is performed by the final_staking instruction).
     ///
     /// ### Arguments
     ///
-    /// * `collided` - new value of blocks collided flag
-    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
-    pub fn set_blocks_collided<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetBlocksCollidedContext<'info>>,
-        collided: bool,
-    ) -> Result<()> {
+    /// * `ctx` - the open final staking claim round context where all required accounts are provided,
+    /// * `merkle_root` - the Merkle root of the (user, amount) pairs of the round, the amounts must sum up to the prize pool of the round,
+    /// * `max_valid_slot` - the last slot the root can be recorded in, the root is rejected if it is processed later (no bound if not provided).
+    ///
+    /// ### Returns
+    /// The index of the opened round.
+    #[access_control(valid_owner_or_signer_quorum(&ctx.accounts.blocks_state_account, &ctx.accounts.signer, &ctx.accounts.signer_set_account, ctx.remaining_accounts) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) batch_not_stale(max_valid_slot) blocks_collided(&ctx.accounts.blocks_state_account) blocks_solved(&ctx.accounts.blocks_state_account) final_staking_not_finalized(&ctx.accounts.blocks_state_account) final_staking_required_interval_elapsed(&ctx.accounts.blocks_state_account.final_staking_last_staking_timestamp, &ctx.accounts.blocks_state_account.final_staking_interval_seconds) global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account))]
+    pub fn open_final_staking_claim_round(
+        ctx: Context<OpenFinalStakingClaimRoundContext>,
+        merkle_root: [u8; 32],
+        max_valid_slot: Option<u64>,
+    ) -> Result<u64> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
         require!(
-            cfg!(feature = "bpf-tests"),
-            SallarError::ExecutionOfSetBlocksCollidedFunctionOutsideTests
+            blocks_state.final_staking_left_balance_in_round == 0,
+            SallarError::FinalStakingRoundInProgress
+        );
+        let pool_in_round = ctx
+            .accounts
+            .final_staking_account
+            .amount
+            .saturating_sub(blocks_state.final_staking_unclaimed_root_rewards)
+            / blocks_state.final_staking_pool_division_factor
+            + blocks_state.final_staking_carried_remainder;
+        require!(
+            !final_staking_terminal_transition_required(blocks_state, pool_in_round),
+            SallarError::FinalStakingTerminalTransitionRequired
+        );
+        require!(
+            pool_in_round > 0,
+            SallarError::FinalStakingPoolInRoundIsEmpty
         );
 
-        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
-        blocks_state_account.blocks_collided = collided;
-        blocks_state_account.top_block_available_bp = 0;
-        blocks_state_account.bottom_block_available_bp = 0;
+        blocks_state.final_staking_pool_in_round = pool_in_round;
+        blocks_state.final_staking_carried_remainder = 0;
+        blocks_state.final_staking_round_counter += 1;
+        blocks_state.final_staking_registered_balance_in_round =
+            blocks_state.final_staking_registered_balance_next_round;
+        blocks_state.final_staking_registered_balance_next_round = 0;
+        blocks_state.final_staking_unclaimed_root_rewards = blocks_state
+            .final_staking_unclaimed_root_rewards
+            .checked_add(pool_in_round)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        let round_index = blocks_state.final_staking_round_counter;
+        let previous_staking_timestamp = blocks_state.final_staking_last_staking_timestamp;
+        let staking_timestamp = current_cluster_timestamp()?;
+        blocks_state.final_staking_last_staking_timestamp = staking_timestamp;
+        blocks_state.final_staking_round_start_timestamp = staking_timestamp;
+        record_sla_delay(
+            blocks_state,
+            SlaSubsystem::FinalStaking,
+            previous_staking_timestamp,
+            staking_timestamp,
+        );
 
-        Ok(())
-    }
-}
+        let final_staking_rewards_root_account =
+            &mut ctx.accounts.final_staking_rewards_root_account;
+        final_staking_rewards_root_account.bump = ctx.bumps.final_staking_rewards_root_account;
+        final_staking_rewards_root_account.round_index = round_index;
+        final_staking_rewards_root_account.merkle_root = merkle_root;
+        final_staking_rewards_root_account.total_amount = pool_in_round;
+        final_staking_rewards_root_account.claimed_amount = 0;
+
+        emit!(FinalStakingRoundStarted {
+            round_index,
+            pool_in_round,
+            final_round: false,
+            timestamp: staking_timestamp,
+        });
+        emit!(FinalStakingRewardsRootRecorded {
+            round_index,
+            merkle_root,
+            total_amount: pool_in_round,
+        });
+
+        if let Some(global_stats_account) = ctx.accounts.global_stats_account.as_mut() {
+            record_distribution_batch(
+                global_stats_account,
+                Subsystem::FinalStaking,
+                pool_in_round,
+            )?;
+        }
 
-/// Struct defining single account participating in the top block solution process.
-/// Consists of the account address and data required to calculate the number of tokens to transfer to the account (number of requests to participate in the current top block solution on the client side).
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UserInfoTopBlock {
-    pub user_public_key: Pubkey,
-    pub user_request_without_boost: u8,
-    pub user_request_with_boost: u8,
-}
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
+        }
 
-/// Struct defining single account participating in the bottom block solution process.
-/// Consists of the account address and data required to calculate the number of tokens to transfer to the account (account's balance and number of requests to participate in the current bottom block solution on the client side).
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UserInfoBottomBlock {
-    pub user_public_key: Pubkey,
-    pub user_balance: u64,
-    pub user_request_without_boost: u8,
-    pub user_request_with_boost: u8,
-}
+        Ok(round_index)
+    }
 
-/// Struct defining single account participating in the final mining process.
-/// Consists of the account address and data required to calculate the number of tokens to be transferred to the account (final mining account balance at the time the account requested participation in the final mining process on the client side).
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UserInfoFinalMining {
-    pub user_public_key: Pubkey,
-    pub final_mining_balance: u64,
-}
+    /// Claims the reward of a final staking round opened in the claim mode.
+    /// The reward is verified against the Merkle root recorded for the round (the leaves are calculated the same way as the leaves of block rewards trees)
+    /// and distributed from final staking account the same way as the rewards distributed by the final_staking instruction.
+    /// Anyone can claim the reward on behalf of the user, the tokens are always transferred to the user's token account,
+    /// unless the user restakes its final staking rewards (see set_final_staking_auto_restake) and its stake lock account and stake vault are provided among remaining accounts.
+    /// Each reward can be claimed only once, also after final staking is finalized.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the claim final staking reward context where all required accounts are provided,
+    /// * `round_index` - the index of the final staking round the reward comes from,
+    /// * `amount` - the reward of the user,
+    /// * `proof` - the Merkle proof of the (user, amount) pair, i.e. the sibling nodes from the leaf up to the root.
+    ///
+    /// ### Returns
+    /// A successful result or an error if the proof is invalid.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
+    pub fn claim_final_staking_reward<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimFinalStakingRewardContext<'info>>,
+        round_index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let user_key = ctx.accounts.user_token_account.key();
+        let final_staking_rewards_root_account =
+            &mut ctx.accounts.final_staking_rewards_root_account;
+        require!(
+            verify_merkle_proof(
+                &proof,
+                &final_staking_rewards_root_account.merkle_root,
+                calculate_block_reward_leaf(&user_key, amount),
+            ),
+            SallarError::InvalidMerkleProof
+        );
+        require!(
+            amount
+                <= final_staking_rewards_root_account.total_amount
+                    - final_staking_rewards_root_account.claimed_amount,
+            SallarError::FinalStakingRewardsRootExceeded
+        );
 
-/// Struct defining single account participating in the final staking process.
-/// Consists of the account address and data required to calculate the number of tokens to be transferred to the account (part of the total prize pool declared for the current final staking round).
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct UserInfoFinalStaking {
-    pub user_public_key: Pubkey,
-    pub reward_part: f64,
-}
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let mut distribution_source = DistributionSource {
+            distribution_account: &ctx.accounts.final_staking_account,
+            seed: FINAL_STAKING_ACCOUNT_SEED,
+            nonce: ctx.accounts.config_account.final_staking_account_nonce,
+            mint: &ctx.accounts.mint,
+            token_program: ctx.accounts.token_program.to_account_info(),
+            backend: distribution_backend(&ctx.accounts.config_account, Subsystem::FinalStaking),
+            fee_destination: None,
+            fee_amount: 0,
+        };
+        blocks_state.final_staking_unclaimed_root_rewards = blocks_state
+            .final_staking_unclaimed_root_rewards
+            .checked_sub(amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+        let restake_accounts = find_user_restake_accounts(ctx.remaining_accounts, &user_key)?;
+        let payout_amount = distribute_reward_to(
+            Subsystem::FinalStaking,
+            blocks_state,
+            &mut distribution_source,
+            ctx.accounts.user_token_account.to_account_info(),
+            restake_accounts.as_ref().map_or_else(
+                || ctx.accounts.user_token_account.to_account_info(),
+                |(_, stake_vault_account)| stake_vault_account.clone(),
+            ),
+            ctx.accounts.denylist_account.as_deref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        final_staking_rewards_root_account.claimed_amount = final_staking_rewards_root_account
+            .claimed_amount
+            .checked_add(amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        if let Some((mut stake_lock_account, _)) = restake_accounts.filter(|_| payout_amount > 0) {
+            stake_lock_account.locked_amount = stake_lock_account
+                .locked_amount
+                .checked_add(payout_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+            stake_lock_account.exit(&crate::ID)?;
+            emit!(FinalStakingRewardRestaked {
+                user: user_key,
+                round_index,
+                amount: payout_amount,
+                locked_amount: stake_lock_account.locked_amount,
+            });
+        }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+        let final_staking_reward_claim_account =
+            &mut ctx.accounts.final_staking_reward_claim_account;
+        final_staking_reward_claim_account.bump = ctx.bumps.final_staking_reward_claim_account;
+        final_staking_reward_claim_account.round_index = round_index;
+        final_staking_reward_claim_account.user = user_key;
+        final_staking_reward_claim_account.amount = amount;
 
-    use anchor_lang::{prelude::AccountMeta, system_program, InstructionData, ToAccountMetas};
-    use anchor_spl::token::spl_token;
-    use solana_program_test::*;
-    use spl_token::state::Account;
+        emit!(FinalStakingRewardClaimed {
+            user: user_key,
+            round_index,
+            amount,
+            payout_amount,
+        });
 
-    use solana_sdk::{
-        commitment_config::CommitmentLevel, signature::Keypair, signer::Signer,
-        transaction::Transaction,
-    };
+        Ok(())
+    }
 
-    use solana_program::{
+    /// Moves the remaining balances of the current top and bottom blocks to the final pools once blocks have collided.
+    /// The current blocks cannot be switched to the next ones anymore, so the tokens not distributed by their solutions would be orphaned on the distribution accounts.
+    /// The remaining top block balance is moved to the final mining account and the remaining bottom block balance to the final staking account,
+    /// both blocks are marked as solved and the rests of the users not paid yet are dropped.
+    /// Tokens reserved on the distribution accounts for claim accounts and Merkle proof claims are not moved.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the settle collision remainder context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) blocks_collided(&ctx.accounts.blocks_state_account) collision_remainder_exists(&ctx.accounts.blocks_state_account) state_mirror_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.state_mirror_account))]
+    pub fn settle_collision_remainder<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleCollisionRemainderContext<'info>>,
+    ) -> Result<()> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let top_block_amount = blocks_state.top_block_balance;
+        let bottom_block_amount = blocks_state.bottom_block_balance;
+
+        if top_block_amount > 0 {
+            transfer_tokens(
+                &ctx.accounts.distribution_top_block_account,
+                ctx.accounts.final_mining_account.to_account_info(),
+                &ctx.accounts.mint,
+                DISTRIBUTION_TOP_BLOCK_SEED,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.config_account.top_block_distribution_nonce,
+                top_block_amount,
+            )?;
+        }
+        if bottom_block_amount > 0 {
+            transfer_tokens(
+                &ctx.accounts.distribution_bottom_block_account,
+                ctx.accounts.final_staking_account.to_account_info(),
+                &ctx.accounts.mint,
+                DISTRIBUTION_BOTTOM_BLOCK_SEED,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.config_account.bottom_block_distribution_nonce,
+                bottom_block_amount,
+            )?;
+        }
+
+        blocks_state.top_block_balance = 0;
+        blocks_state.top_block_available_bp = 0;
+        blocks_state.top_block_rounding_remainder = 0;
+        blocks_state.top_block_last_account_rest_bp = 0;
+        blocks_state.top_block_rest_carryovers = Vec::new();
+        blocks_state.top_block_solve_batch_cursor = None;
+        blocks_state.bottom_block_balance = 0;
+        blocks_state.bottom_block_available_bp = 0;
+        blocks_state.bottom_block_rounding_remainder = 0;
+        blocks_state.bottom_block_last_account_rest_bp = 0;
+        blocks_state.bottom_block_rest_carryovers = Vec::new();
+
+        emit!(CollisionRemainderSettled {
+            top_block_number: blocks_state.top_block_number,
+            top_block_amount,
+            bottom_block_number: blocks_state.bottom_block_number,
+            bottom_block_amount,
+        });
+
+        if let Some(state_mirror_account) = ctx.accounts.state_mirror_account.as_mut() {
+            sync_state_mirror(state_mirror_account, blocks_state);
+        }
+
+        Ok(())
+    }
+
+    /// Removes the mint authority, so the token supply is fixed forever and holders can verify it.
+    /// It can be invoked only when blocks have collided and all tokens minted for blocks have been distributed,
+    /// i.e. both distribution accounts are empty.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the finalize mint context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) blocks_collided(&ctx.accounts.blocks_state_account) distribution_accounts_empty(&ctx.accounts.distribution_top_block_account, &ctx.accounts.distribution_bottom_block_account))]
+    pub fn finalize_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinalizeMintContext<'info>>,
+    ) -> Result<()> {
+        remove_mint_authority(
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.config_account.mint_nonce,
+        )
+    }
+
+    /// Closes the token accounts (both distribution accounts, final staking and final mining accounts) the blocks state account and the config account,
+    /// transferring their lamports to the recipient. It can be called once all blocks are solved and all the accounts are empty.
+    /// The contract cannot be used anymore after this function is called.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the close program accounts context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) blocks_collided(&ctx.accounts.blocks_state_account) distribution_accounts_empty(&ctx.accounts.distribution_top_block_account, &ctx.accounts.distribution_bottom_block_account) final_pools_empty(&ctx.accounts.final_staking_account, &ctx.accounts.final_mining_account))]
+    pub fn close_program_accounts<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseProgramAccountsContext<'info>>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config_account;
+        let recipient = ctx.accounts.recipient.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+
+        close_token_account(
+            &ctx.accounts.distribution_top_block_account,
+            recipient.clone(),
+            DISTRIBUTION_TOP_BLOCK_SEED,
+            token_program.clone(),
+            config.top_block_distribution_nonce,
+        )?;
+        close_token_account(
+            &ctx.accounts.distribution_bottom_block_account,
+            recipient.clone(),
+            DISTRIBUTION_BOTTOM_BLOCK_SEED,
+            token_program.clone(),
+            config.bottom_block_distribution_nonce,
+        )?;
+        close_token_account(
+            &ctx.accounts.final_staking_account,
+            recipient.clone(),
+            FINAL_STAKING_ACCOUNT_SEED,
+            token_program.clone(),
+            config.final_staking_account_nonce,
+        )?;
+        close_token_account(
+            &ctx.accounts.final_mining_account,
+            recipient,
+            FINAL_MINING_ACCOUNT_SEED,
+            token_program,
+            config.final_mining_account_nonce,
+        )
+    }
+
+    /// Transfers tokens accidentally sent to the blocks state account (i.e. held by token accounts whose authority is the blocks state account) to the destination token account.
+    /// Tokens of the program's token accounts (distribution, final staking and final mining accounts) cannot be recovered.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the recover foreign tokens context where all required accounts are provided,
+    /// * `amount` - the amount of tokens to recover.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn recover_foreign_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, RecoverForeignTokensContext<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        foreign_token_account(
+            &ctx.accounts.config_account,
+            &ctx.accounts.source_token_account.key(),
+        )?;
+        transfer_tokens_from_blocks_state(
+            ctx.accounts.source_token_account.to_account_info(),
+            ctx.accounts.destination_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.blocks_state_account.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.config_account.block_state_nonce,
+            amount,
+        )
+    }
+
+    /// Emits a summary of token supply, distributed amounts and balances of the program accounts and the treasury as `RegulatoryReportEvent`.
+    /// The figures are read on-chain by the program, so periodic regulatory reports can cite program-attested numbers.
+    /// The function does not modify any state.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the export regulatory report context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn export_regulatory_report(ctx: Context<ExportRegulatoryReportContext>) -> Result<()> {
+        let blocks_state = &ctx.accounts.blocks_state_account;
+        require!(
+            blocks_state.treasury_address.is_some()
+                == ctx.accounts.treasury_token_account.is_some(),
+            SallarError::InvalidTreasuryAccount
+        );
+
+        let top_blocks_minted_amount = calculate_top_blocks_minted_amount(
+            blocks_state.first_top_block_number,
+            blocks_state.top_block_number,
+        );
+        let bottom_blocks_minted_amount = calculate_bottom_blocks_minted_amount(
+            blocks_state.first_bottom_block_number,
+            blocks_state.bottom_block_number,
+        );
+        let mut total_minted_amount = top_blocks_minted_amount
+            .checked_add(bottom_blocks_minted_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        if blocks_state.initial_token_distribution_already_performed {
+            total_minted_amount = total_minted_amount
+                .checked_add(blocks_state.initial_token_distribution_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+        }
+        let total_supply = ctx.accounts.mint.supply;
+
+        emit!(RegulatoryReportEvent {
+            authority: ctx.accounts.signer.key(),
+            slot: Clock::get()?.slot,
+            timestamp: current_cluster_timestamp()?,
+            total_supply,
+            total_minted_amount,
+            burned_amount: total_minted_amount.saturating_sub(total_supply),
+            top_block_distributed_amount: top_blocks_minted_amount
+                .saturating_sub(blocks_state.top_block_balance),
+            bottom_block_distributed_amount: bottom_blocks_minted_amount
+                .saturating_sub(blocks_state.bottom_block_balance),
+            top_block_pending_payouts: blocks_state.top_block_pending_payouts,
+            bottom_block_pending_payouts: blocks_state.bottom_block_pending_payouts,
+            distribution_top_block_balance: ctx.accounts.distribution_top_block_account.amount,
+            distribution_bottom_block_balance: ctx
+                .accounts
+                .distribution_bottom_block_account
+                .amount,
+            final_staking_balance: ctx.accounts.final_staking_account.amount,
+            final_mining_balance: ctx.accounts.final_mining_account.amount,
+            treasury_balance: ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .map(|treasury_token_account| treasury_token_account.amount),
+        });
+
+        Ok(())
+    }
+
+    /// Returns the block metrics derived from the blocks state (dust per BP, boosts, blocks remaining before the collision and the remaining supply),
+    /// so clients can display them without re-implementing the token math.
+    /// The summary is set as the return data of the transaction, it can be read by simulating the instruction.
+    /// The function does not modify any state.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the get block state summary context where the blocks state account is provided.
+    pub fn get_block_state_summary(
+        ctx: Context<GetBlockStateSummaryContext>,
+    ) -> Result<BlockStateSummary> {
+        calculate_block_state_summary(&ctx.accounts.blocks_state_account)
+    }
+
+    /// Returns the projected final mining emission of the following final mining budget windows (each projected in full),
+    /// so wallets can display the expected final mining rewards consistently with the on-chain logic.
+    /// The emission is projected from the current final mining account's balance, the final mining tiers and the final mining budget (see project_final_mining_emission).
+    /// The projection is set as the return data of the transaction, it can be read by simulating the instruction.
+    /// The function does not modify any state.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the get final mining emission projection context where all required accounts are provided,
+    /// * `requests_per_window` - the number of final mining requests expected within a single window,
+    /// * `windows` - the number of windows to project, between 1 and MAX_FINAL_MINING_PROJECTION_WINDOWS.
+    #[access_control(final_mining_tiers_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.final_mining_tiers_account))]
+    pub fn get_final_mining_emission_projection(
+        ctx: Context<GetFinalMiningEmissionProjectionContext>,
+        requests_per_window: u64,
+        windows: u16,
+    ) -> Result<FinalMiningEmissionProjection> {
+        require!(
+            (1..=MAX_FINAL_MINING_PROJECTION_WINDOWS).contains(&windows),
+            SallarError::InvalidFinalMiningEmissionProjection
+        );
+        let blocks_state = &ctx.accounts.blocks_state_account;
+
+        Ok(FinalMiningEmissionProjection {
+            window_seconds: blocks_state.final_mining_budget_window_seconds,
+            windows: project_final_mining_emission(
+                final_mining_tiers(ctx.accounts.final_mining_tiers_account.as_deref()),
+                ctx.accounts.final_mining_account.amount,
+                requests_per_window,
+                blocks_state.final_mining_budget_per_window,
+                usize::from(windows),
+            ),
+        })
+    }
+
+    /// Verifies the core accounting invariants of the contract and returns the report of them, so drift can be detected early:
+    /// * the distribution accounts hold the balances and the pending payouts of the current blocks,
+    /// * the available BP of the current blocks are worth their remaining balances at the current dust per BP,
+    /// * the total supply does not exceed the amount minted for the blocks processed so far and the global statistics track the minted amount exactly.
+    ///
+    /// The report is set as the return data of the transaction, it can be read by simulating the instruction.
+    /// The function does not modify any state.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the verify invariants context where all required accounts are provided.
+    #[access_control(global_stats_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.global_stats_account))]
+    pub fn verify_invariants(ctx: Context<VerifyInvariantsContext>) -> Result<InvariantsReport> {
+        calculate_invariants_report(
+            &ctx.accounts.blocks_state_account,
+            ctx.accounts.mint.supply,
+            ctx.accounts.distribution_top_block_account.amount,
+            ctx.accounts.distribution_bottom_block_account.amount,
+            ctx.accounts
+                .global_stats_account
+                .as_ref()
+                .map(|global_stats_account| global_stats_account.total_minted_amount),
+        )
+    }
+
+    /// Simulates solve_top_block for the given batch against the current top block without performing any transfers or state changes,
+    /// so the backend can validate the batch sizing against the available BP of the block before submitting it.
+    /// The batch must begin with the entries of the users whose rests are carried over to the current block (see solve_top_block), their rests are settled first.
+    /// The simulation covers the BP and the reward computation only, the recipient accounts, the per-user requests cap and the boost credits are not verified,
+    /// and the distribution does not continue in the next block once the current one is exhausted.
+    /// The simulation is set as the return data of the transaction, it can be read by simulating the instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the simulate solve block context where the blocks state account is provided,
+    /// * `users_info` - the user info entries of the batch, as passed to solve_top_block.
+    pub fn simulate_solve_top_block(
+        ctx: Context<SimulateSolveBlockContext>,
+        users_info: Vec<UserInfoTopBlock>,
+    ) -> Result<BlockSolutionSimulation> {
+        let blocks_state = &ctx.accounts.blocks_state_account;
+        let block_number = blocks_state.top_block_number;
+        let (top_bp_with_boost, dust_per_bp) = top_block_params(blocks_state)?;
+
+        let rest_carryovers = &blocks_state.top_block_rest_carryovers;
+        find_rest_carryover_entries(
+            rest_carryovers,
+            &users_info
+                .iter()
+                .map(|user_info| user_info.user_public_key)
+                .collect::<Vec<Pubkey>>(),
+            0,
+        )?;
+        let mut user_rewards = rest_carryovers
+            .iter()
+            .map(|rest_carryover| (rest_carryover.user, rest_carryover.rest_bp))
+            .collect::<Vec<_>>();
+        for user_info in users_info.iter().skip(rest_carryovers.len()) {
+            let reward_breakdown = calculate_user_reward_breakdown_top_block(
+                user_info.user_request_without_boost,
+                user_info.user_request_with_boost,
+                top_bp_with_boost,
+                dust_per_bp,
+            )?;
+            user_rewards.push((user_info.user_public_key, reward_breakdown.bp()?));
+        }
+
+        simulate_block_solution(
+            block_number,
+            blocks_state.top_block_available_bp,
+            blocks_state.top_block_balance,
+            blocks_state.top_block_rounding_remainder,
+            dust_per_bp,
+            user_rewards,
+        )
+    }
+
+    /// Simulates solve_bottom_block for the given batch against the current bottom block without performing any transfers or state changes,
+    /// so the backend can validate the batch sizing against the available BP of the block before submitting it.
+    /// The batch must begin with the entries of the users whose rests are carried over to the current block (see solve_bottom_block), their rests are settled first.
+    /// The simulation covers the BP and the reward computation only, the recipient accounts, the per-user requests cap and the boost credits are not verified.
+    /// The simulation is set as the return data of the transaction, it can be read by simulating the instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the simulate solve block context where the blocks state account is provided
+    ///   (the stake lock accounts of the users must be provided among remaining accounts, the balance of a user without it is 0),
+    /// * `users_info` - the user info entries of the batch, as passed to solve_bottom_block.
+    pub fn simulate_solve_bottom_block<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SimulateSolveBlockContext<'info>>,
+        users_info: Vec<UserInfoBottomBlock>,
+    ) -> Result<BlockSolutionSimulation> {
+        let blocks_state = &ctx.accounts.blocks_state_account;
+        let block_number = blocks_state.bottom_block_number;
+        let (dust_per_bp, bottom_block_boost) = bottom_block_params(blocks_state)?;
+
+        let rest_carryovers = &blocks_state.bottom_block_rest_carryovers;
+        find_rest_carryover_entries(
+            rest_carryovers,
+            &users_info
+                .iter()
+                .map(|user_info| user_info.user_public_key)
+                .collect::<Vec<Pubkey>>(),
+            0,
+        )?;
+        let mut user_rewards = rest_carryovers
+            .iter()
+            .map(|rest_carryover| (rest_carryover.user, rest_carryover.rest_bp))
+            .collect::<Vec<_>>();
+        for user_info in users_info.iter().skip(rest_carryovers.len()) {
+            let user_balance =
+                find_user_stake_lock_account(ctx.remaining_accounts, &user_info.user_public_key)
+                    .map_or(0, |stake_lock_account| {
+                        stake_lock_balance(&stake_lock_account, block_number)
+                    });
+            let reward_breakdown = calculate_user_reward_breakdown_bottom_block(
+                user_info.user_request_without_boost,
+                user_info.user_request_with_boost,
+                calculate_bottom_bp_without_boost(user_balance)?,
+                calculate_bottom_bp_with_given_boost(user_balance, bottom_block_boost)?,
+                dust_per_bp,
+                user_balance,
+                blocks_state.bottom_block_min_required_stake,
+            )?;
+            user_rewards.push((user_info.user_public_key, reward_breakdown.bp()?));
+        }
+
+        simulate_block_solution(
+            block_number,
+            blocks_state.bottom_block_available_bp,
+            blocks_state.bottom_block_balance,
+            blocks_state.bottom_block_rounding_remainder,
+            dust_per_bp,
+            user_rewards,
+        )
+    }
+
+    /// Creates the global statistics account tracking totals of the token distribution, so dashboards do not have to reconstruct them from the transaction history.
+    /// The minted and block distributed amounts and the numbers of solved blocks are seeded from the blocks state,
+    /// the final mining and final staking amounts and the number of processed batches are counted from the account creation.
+    /// Once the account is created, it must be provided to all distribution instructions.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize global stats context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_global_stats(ctx: Context<InitializeGlobalStatsContext>) -> Result<()> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+
+        let top_blocks_minted_amount = calculate_top_blocks_minted_amount(
+            blocks_state.first_top_block_number,
+            blocks_state.top_block_number,
+        );
+        let bottom_blocks_minted_amount = calculate_bottom_blocks_minted_amount(
+            blocks_state.first_bottom_block_number,
+            blocks_state.bottom_block_number,
+        );
+        let mut total_minted_amount = top_blocks_minted_amount
+            .checked_add(bottom_blocks_minted_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+        if blocks_state.initial_token_distribution_already_performed {
+            total_minted_amount = total_minted_amount
+                .checked_add(blocks_state.initial_token_distribution_amount)
+                .ok_or(SallarError::ArithmeticOverflow)?;
+        }
+
+        let global_stats_account = &mut ctx.accounts.global_stats_account;
+        global_stats_account.bump = ctx.bumps.global_stats_account;
+        global_stats_account.total_minted_amount = total_minted_amount;
+        global_stats_account.top_block_distributed_amount =
+            top_blocks_minted_amount - blocks_state.top_block_balance;
+        global_stats_account.bottom_block_distributed_amount =
+            bottom_blocks_minted_amount - blocks_state.bottom_block_balance;
+        global_stats_account.final_mining_distributed_amount = 0;
+        global_stats_account.final_staking_distributed_amount = 0;
+        global_stats_account.top_blocks_solved = top_blocks_minted_amount / DUSTS_PER_BLOCK - 1
+            + u64::from(blocks_state.top_block_available_bp == 0);
+        global_stats_account.bottom_blocks_solved = bottom_blocks_minted_amount / DUSTS_PER_BLOCK
+            - 1
+            + u64::from(blocks_state.bottom_block_available_bp == 0);
+        global_stats_account.processed_batches = 0;
+
+        blocks_state.global_stats_initialized = true;
+
+        Ok(())
+    }
+
+    /// Creates the recent solvers account keeping the last token accounts rewarded by top and bottom block solutions,
+    /// so explorers can show recent miners without an indexer. The buffers are filled by block solutions from the account creation.
+    /// Once the account is created, it must be provided to all block solution instructions.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize recent solvers context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_recent_solvers(ctx: Context<InitializeRecentSolversContext>) -> Result<()> {
+        let recent_solvers_account = &mut ctx.accounts.recent_solvers_account;
+        recent_solvers_account.bump = ctx.bumps.recent_solvers_account;
+        recent_solvers_account.top_block_solvers = Vec::new();
+        recent_solvers_account.top_block_next_index = 0;
+        recent_solvers_account.bottom_block_solvers = Vec::new();
+        recent_solvers_account.bottom_block_next_index = 0;
+
+        ctx.accounts.blocks_state_account.recent_solvers_initialized = true;
+
+        Ok(())
+    }
+
+    /// Creates the state mirror account keeping the compact copy of the current blocks and the final staking round,
+    /// so light clients fetch a single small account instead of the full blocks state. The mirror is synchronized with the blocks state on creation.
+    /// Once the account is created, it must be provided to all instructions changing the mirrored fields.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize state mirror context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_state_mirror(ctx: Context<InitializeStateMirrorContext>) -> Result<()> {
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+
+        let state_mirror_account = &mut ctx.accounts.state_mirror_account;
+        state_mirror_account.bump = ctx.bumps.state_mirror_account;
+        sync_state_mirror(state_mirror_account, blocks_state);
+
+        blocks_state.state_mirror_initialized = true;
+
+        Ok(())
+    }
+
+    /// Migrates the blocks state to the current version of its layout, so the fields added to the blocks state can be introduced
+    /// to the existing deployments. The state is deserialized in the layout of its version (see deserialize_blocks_state_for_migration),
+    /// the account is resized to the current layout (the signer covers the additional rent) and the fields introduced by the versions
+    /// newer than the one of the state are set (see migrate_blocks_state).
+    /// It can be executed only by the contract's owner, and only once per version of the layout.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the migrate state context where all required accounts are provided.
+    pub fn migrate_state(ctx: Context<MigrateStateContext>) -> Result<()> {
+        let blocks_state_account = &ctx.accounts.blocks_state_account;
+        let mut blocks_state =
+            deserialize_blocks_state_for_migration(&blocks_state_account.try_borrow_data()?)?;
+        valid_owner(&blocks_state, &ctx.accounts.signer)?;
+
+        let required_space = DISCRIMINATOR_LENGTH + BlocksState::INIT_SPACE;
+        grow_account(
+            blocks_state_account.to_account_info(),
+            required_space,
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+        // the layouts of the previous versions may be larger, e.g. with the fields dropped by version 4
+        if blocks_state_account.data_len() > required_space {
+            blocks_state_account.realloc(required_space, false)?;
+        }
+
+        let previous_version = blocks_state.version;
+        migrate_blocks_state(&mut blocks_state)?;
+        blocks_state.try_serialize(&mut &mut blocks_state_account.try_borrow_mut_data()?[..])?;
+
+        emit!(BlocksStateMigrated {
+            signer: ctx.accounts.signer.key(),
+            previous_version,
+            new_version: blocks_state.version,
+            space: blocks_state_account.data_len() as u64,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the config account of a deployment initialized before the config account was introduced.
+    /// The nonces are the canonical bumps of the accounts, found by Anchor as they were found by the initialize instruction,
+    /// the blocks state must be migrated to the current version of its layout first (see migrate_state).
+    /// Once the account is created, it must be provided to all instructions reading the blocks state.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize config context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_config(ctx: Context<InitializeConfigContext>) -> Result<()> {
+        let config = &mut ctx.accounts.config_account;
+        config.bump = ctx.bumps.config_account;
+        config.block_state_address = ctx.accounts.blocks_state_account.key();
+        config.block_state_nonce = ctx.bumps.blocks_state_account;
+        config.mint_address = ctx.accounts.mint.key();
+        config.mint_nonce = ctx.bumps.mint;
+        config.top_block_distribution_address = ctx.accounts.distribution_top_block_account.key();
+        config.top_block_distribution_nonce = ctx.bumps.distribution_top_block_account;
+        config.bottom_block_distribution_address =
+            ctx.accounts.distribution_bottom_block_account.key();
+        config.bottom_block_distribution_nonce = ctx.bumps.distribution_bottom_block_account;
+        config.final_staking_account_address = ctx.accounts.final_staking_account.key();
+        config.final_staking_account_nonce = ctx.bumps.final_staking_account;
+        config.final_mining_account_address = ctx.accounts.final_mining_account.key();
+        config.final_mining_account_nonce = ctx.bumps.final_mining_account;
+        initialize_distribution_backends(config);
+
+        Ok(())
+    }
+
+    /// Updates token metadata set during initialization, e.g. to fix the uri or the symbol without redeploying the mint.
+    /// Metadata does not affect the token distribution, so the update is not subject to the timelock.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the update token metadata context where all required accounts are provided,
+    /// * `token_metadata_name` - token's new name to set in metadata,
+    /// * `token_metadata_symbol` - token's new symbol to set in metadata,
+    /// * `token_metadata_uri` - token's new uri to set in metadata
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn update_token_metadata(
+        ctx: Context<UpdateTokenMetadataContext>,
+        token_metadata_name: String,
+        token_metadata_symbol: String,
+        token_metadata_uri: String,
+    ) -> Result<()> {
+        utils::update_token_metadata(
+            ctx,
+            token_metadata_name,
+            token_metadata_symbol,
+            token_metadata_uri,
+        )
+    }
+
+    /// Sets new authority
+    ///
+    /// ### Arguments
+    ///
+    /// * `new_authority` - new authority
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn change_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, ChangeAuthorityContext<'info>>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_authority = blocks_state_account.authority;
+        blocks_state_account.authority = new_authority;
+        blocks_state_account.pending_authority = None;
+
+        emit_authority_changed(ctx.accounts.signer.key(), previous_authority, new_authority)
+    }
+
+    /// Creates the signer set allowing the quorum of registered signers to approve privileged instructions
+    /// (solve_top_block, solve_bottom_block, final_mining and final_staking) instead of the contract's owner.
+    /// The first registered signer signs the transaction as the instruction's signer and the others are provided as signing remaining accounts.
+    ///
+    /// ### Arguments
+    ///
+    /// * `signers` - the registered signers (up to 10),
+    /// * `threshold` - the number of registered signers required to approve the instruction.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_signer_set(&signers, threshold))]
+    pub fn initialize_signer_set(
+        ctx: Context<InitializeSignerSetContext>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let signer_set_account = &mut ctx.accounts.signer_set_account;
+        signer_set_account.bump = ctx.bumps.signer_set_account;
+        signer_set_account.threshold = threshold;
+        signer_set_account.signers = signers;
+
+        Ok(())
+    }
+
+    /// Replaces the registered signers and the quorum required to approve privileged instructions.
+    ///
+    /// ### Arguments
+    ///
+    /// * `signers` - the registered signers (up to 10),
+    /// * `threshold` - the number of registered signers required to approve the instruction.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_signer_set(&signers, threshold) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn update_signer_set(
+        ctx: Context<UpdateSignerSetContext>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let change = ConfigChange::UpdateSignerSet {
+            signers: signers.clone(),
+            threshold,
+        };
+        let previous_values = current_config_values(
+            &ctx.accounts.blocks_state_account,
+            None,
+            Some(&ctx.accounts.signer_set_account),
+            None,
+            &change,
+        );
+        let signer_set_account = &mut ctx.accounts.signer_set_account;
+        signer_set_account.threshold = threshold;
+        signer_set_account.signers = signers;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Creates the empty denylist defining the token accounts which must not receive rewards.
+    /// Once the denylist is created, it must be provided to all instructions distributing tokens (solve_top_block, solve_bottom_block,
+    /// final_mining, final_staking and the claims), and the instruction fails if any of the recipients is denylisted.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize denylist context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn initialize_denylist(ctx: Context<InitializeDenylistContext>) -> Result<()> {
+        let denylist_account = &mut ctx.accounts.denylist_account;
+        denylist_account.bump = ctx.bumps.denylist_account;
+        denylist_account.token_accounts = Vec::new();
+
+        ctx.accounts.blocks_state_account.denylist_initialized = true;
+
+        Ok(())
+    }
+
+    /// Adds the token account to the denylist (up to 50 token accounts).
+    ///
+    /// ### Arguments
+    ///
+    /// * `token_account` - the token account which must not receive rewards
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn add_to_denylist(
+        ctx: Context<UpdateDenylistContext>,
+        token_account: Pubkey,
+    ) -> Result<()> {
+        utils::add_to_denylist(&mut ctx.accounts.denylist_account, token_account)
+    }
+
+    /// Removes the token account from the denylist.
+    ///
+    /// ### Arguments
+    ///
+    /// * `token_account` - the token account which can receive rewards again
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn remove_from_denylist(
+        ctx: Context<UpdateDenylistContext>,
+        token_account: Pubkey,
+    ) -> Result<()> {
+        utils::remove_from_denylist(&mut ctx.accounts.denylist_account, &token_account)
+    }
+
+    /// Creates the empty final staking tiers account defining the multipliers of the balance snapshots registered for final staking.
+    /// Once the tiers are defined, the snapshots of long-term holders are weighted by the multipliers of their tiers (see register_for_final_staking).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize final staking tiers context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn initialize_final_staking_tiers(
+        ctx: Context<InitializeFinalStakingTiersContext>,
+    ) -> Result<()> {
+        let final_staking_tiers_account = &mut ctx.accounts.final_staking_tiers_account;
+        final_staking_tiers_account.bump = ctx.bumps.final_staking_tiers_account;
+        final_staking_tiers_account.tiers = Vec::new();
+
+        Ok(())
+    }
+
+    /// Replaces the final staking tiers (up to 8 tiers ordered by the minimum balance age with multipliers between 1x and 10x).
+    /// The tiers apply to the snapshots registered afterwards, the snapshots already registered keep their weighted balances.
+    ///
+    /// ### Arguments
+    ///
+    /// * `tiers` - the new final staking tiers
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_final_staking_tiers(&tiers))]
+    pub fn set_final_staking_tiers(
+        ctx: Context<SetFinalStakingTiersContext>,
+        tiers: Vec<FinalStakingTier>,
+    ) -> Result<()> {
+        ctx.accounts.final_staking_tiers_account.tiers = tiers;
+
+        Ok(())
+    }
+
+    /// Creates the final mining tiers account defining the reward of a single final mining request depending on the final mining account's balance.
+    /// The account is created with the default tiers, so final_mining rewards do not change until the tiers are replaced (see set_final_mining_tiers).
+    /// Once the account is created, it must be provided to final_mining.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the initialize final mining tiers context where all required accounts are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn initialize_final_mining_tiers(
+        ctx: Context<InitializeFinalMiningTiersContext>,
+    ) -> Result<()> {
+        let final_mining_tiers_account = &mut ctx.accounts.final_mining_tiers_account;
+        final_mining_tiers_account.bump = ctx.bumps.final_mining_tiers_account;
+        final_mining_tiers_account.tiers = final_mining_tiers(None).to_vec();
+
+        ctx.accounts
+            .blocks_state_account
+            .final_mining_tiers_initialized = true;
+
+        Ok(())
+    }
+
+    /// Replaces the final mining tiers (up to 8 tiers ordered by the minimum final mining balance, the first one starting at zero balance,
+    /// with rewards up to the maximum final mining reward), so the emission curve of final mining can be tuned without a program upgrade.
+    ///
+    /// ### Arguments
+    ///
+    /// * `tiers` - the new final mining tiers
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_mining_tiers(&tiers))]
+    pub fn set_final_mining_tiers(
+        ctx: Context<SetFinalMiningTiersContext>,
+        tiers: Vec<FinalMiningTier>,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalMiningTiers {
+            tiers: tiers.clone(),
+        };
+        let previous_values = current_config_values(
+            &ctx.accounts.blocks_state_account,
+            None,
+            None,
+            Some(&ctx.accounts.final_mining_tiers_account),
+            &change,
+        );
+        ctx.accounts.final_mining_tiers_account.tiers = tiers;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Proposes new authority, the first step of the two-step ownership transfer.
+    /// The ownership is not transferred until the proposed authority accepts it using accept_authority function,
+    /// so the contract cannot be handed over to an unreachable key by mistake.
+    /// Proposing another authority replaces the previous proposal.
+    ///
+    /// ### Arguments
+    ///
+    /// * `new_authority` - proposed authority
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn propose_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, ProposeAuthorityContext<'info>>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let change = ConfigChange::ProposeAuthority { new_authority };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.pending_authority = Some(new_authority);
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Accepts the ownership proposed using propose_authority function, the second step of the two-step ownership transfer.
+    /// It must be signed by the proposed authority which becomes the contract's owner.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the accept authority context where the pending authority is the signer.
+    #[access_control(valid_pending_authority(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn accept_authority<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcceptAuthorityContext<'info>>,
+    ) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_authority = blocks_state_account.authority;
+        blocks_state_account.authority = ctx.accounts.signer.key();
+        blocks_state_account.pending_authority = None;
+
+        emit_authority_changed(
+            ctx.accounts.signer.key(),
+            previous_authority,
+            ctx.accounts.signer.key(),
+        )
+    }
+
+    /// Sets the minimum payout amount.
+    /// Rewards for top and bottom blocks below this amount are accumulated in user's claim account (if provided) instead of being transferred,
+    /// and they are transferred once the accumulated amount reaches the minimum payout amount. Setting it to 0 disables the threshold.
+    ///
+    /// ### Arguments
+    ///
+    /// * `min_payout_amount` - new minimum payout amount (in dusts), it cannot exceed 1 Sallar
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_min_payout_amount(min_payout_amount) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_min_payout_amount<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMinPayoutAmountContext<'info>>,
+        min_payout_amount: u64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetMinPayoutAmount { min_payout_amount };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.min_payout_amount = min_payout_amount;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the treasury token account.
+    /// Once a block is solved, tokens found on its distribution account beyond the tracked block accounting are transferred to the treasury.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set treasury context where the treasury token account is provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_treasury<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetTreasuryContext<'info>>,
+    ) -> Result<()> {
+        let change = ConfigChange::SetTreasury {
+            treasury_address: ctx.accounts.treasury_token_account.key(),
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.treasury_address = Some(ctx.accounts.treasury_token_account.key());
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Pauses all token-moving instructions (initial_token_distribution, solve_top_block, solve_bottom_block, final_mining and final_staking).
+    /// It is intended for incident response, e.g. when the backend signer is compromised.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set paused context where the contract's owner is the signer.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn pause<'info>(ctx: Context<'_, '_, '_, 'info, SetPausedContext<'info>>) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        blocks_state_account.paused = true;
+
+        Ok(())
+    }
+
+    /// Unpauses all token-moving instructions paused using pause function.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set paused context where the contract's owner is the signer.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn unpause<'info>(ctx: Context<'_, '_, '_, 'info, SetPausedContext<'info>>) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        blocks_state_account.paused = false;
+
+        Ok(())
+    }
+
+    /// Sets or removes the guardian, an independent security monitor allowed to halt block solving.
+    /// The guardian cannot move funds nor resume block solving, only the contract's owner can resume it.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set guardian context where the contract's owner is the signer,
+    /// * `guardian` - the new guardian, None removes the guardian
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_guardian<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetGuardianContext<'info>>,
+        guardian: Option<Pubkey>,
+    ) -> Result<()> {
+        let change = ConfigChange::SetGuardian { guardian };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.guardian = guardian;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Enables or disables block history, i.e. recording the solvers of every block and the amounts distributed to them in the block history account of the block.
+    /// Once block history is enabled, the block history account must be provided to solve_top_block and solve_bottom_block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set block history enabled context where the contract's owner is the signer,
+    /// * `block_history_enabled` - true to enable block history, false to disable it
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_block_history_enabled<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlockHistoryEnabledContext<'info>>,
+        block_history_enabled: bool,
+    ) -> Result<()> {
+        let change = ConfigChange::SetBlockHistoryEnabled {
+            block_history_enabled,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.block_history_enabled = block_history_enabled;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets or removes the solve coordinator, the off-chain key approving block solution batches.
+    /// A batch approved by the coordinator with an ed25519 signature can be submitted by any relayer, without the contract's owner or the signer quorum.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set solve coordinator context where the contract's owner is the signer,
+    /// * `solve_coordinator` - the new solve coordinator, None removes the coordinator
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_solve_coordinator<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetSolveCoordinatorContext<'info>>,
+        solve_coordinator: Option<Pubkey>,
+    ) -> Result<()> {
+        let change = ConfigChange::SetSolveCoordinator { solve_coordinator };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.solve_coordinator = solve_coordinator;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the minimum balance (locked stake) a user must have to receive rewards in bottom block solutions.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set bottom block min required stake context where the contract's owner is the signer,
+    /// * `bottom_block_min_required_stake` - new minimum required stake (in dusts), it must be between 1 Sallar and 200_000 Sallar
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_bottom_block_min_required_stake(bottom_block_min_required_stake) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_bottom_block_min_required_stake<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBottomBlockMinRequiredStakeContext<'info>>,
+        bottom_block_min_required_stake: u64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetBottomBlockMinRequiredStake {
+            bottom_block_min_required_stake,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.bottom_block_min_required_stake = bottom_block_min_required_stake;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the maximum numbers of requests (with and without boost) a single user can be rewarded for in a single top block and in a single bottom block.
+    /// While a cap is set, the block user requests account of the solved block must be provided to the solve instruction of the block kind.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set max user requests per block context where the contract's owner is the signer,
+    /// * `top_block_max_user_requests` - new per-user requests cap of top blocks, 0 disables the cap,
+    /// * `bottom_block_max_user_requests` - new per-user requests cap of bottom blocks, 0 disables the cap
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_max_user_requests_per_block<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetMaxUserRequestsPerBlockContext<'info>>,
+        top_block_max_user_requests: u16,
+        bottom_block_max_user_requests: u16,
+    ) -> Result<()> {
+        let change = ConfigChange::SetMaxUserRequestsPerBlock {
+            top_block_max_user_requests,
+            bottom_block_max_user_requests,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.top_block_max_user_requests = top_block_max_user_requests;
+        blocks_state_account.bottom_block_max_user_requests = bottom_block_max_user_requests;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Enables or disables the commit-reveal scheme of block solution batches.
+    /// Once the scheme is enabled, every batch of solve_top_block and solve_bottom_block must be committed with the commit_block_participation instruction in an earlier slot.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set block participation commitment enabled context where the contract's owner is the signer,
+    /// * `block_participation_commitment_enabled` - true to enable the scheme, false to disable it
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_block_participation_commitment_enabled<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlockParticipationCommitmentEnabledContext<'info>>,
+        block_participation_commitment_enabled: bool,
+    ) -> Result<()> {
+        let change = ConfigChange::SetBlockParticipationCommitmentEnabled {
+            block_participation_commitment_enabled,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.block_participation_commitment_enabled =
+            block_participation_commitment_enabled;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Requires or stops requiring boost credits for requests with boost in block solution.
+    /// While boost credits are required, every request with boost consumes a single boost credit of the user (see buy_boost_credits)
+    /// and the boost credits accounts of the users with requests with boost must be provided among remaining accounts of solve_top_block and solve_bottom_block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set boost credits required context where the contract's owner is the signer,
+    /// * `boost_credits_required` - true to require boost credits, false to stop requiring them
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_boost_credits_required<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBoostCreditsRequiredContext<'info>>,
+        boost_credits_required: bool,
+    ) -> Result<()> {
+        let change = ConfigChange::SetBoostCreditsRequired {
+            boost_credits_required,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.boost_credits_required = boost_credits_required;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Requires or stops requiring the balance snapshots of the users in final staking.
+    /// While final staking snapshots are required, the reward part of each user in final_staking is calculated from the balance snapshot registered for the round (see register_for_final_staking).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set final staking snapshots required context where the contract's owner is the signer,
+    /// * `final_staking_snapshots_required` - true to require the snapshots, false to stop requiring them
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_final_staking_snapshots_required<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalStakingSnapshotsRequiredContext<'info>>,
+        final_staking_snapshots_required: bool,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalStakingSnapshotsRequired {
+            final_staking_snapshots_required,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_staking_snapshots_required = final_staking_snapshots_required;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the final mining claim period, i.e. the period in which a user can receive at most one final mining payout.
+    /// Claim periods are counted from the Unix epoch, so changing the claim period starts tracking the users paid from scratch.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set final mining claim period context where the contract's owner is the signer,
+    /// * `final_mining_claim_period_seconds` - new final mining claim period (in seconds), it must be between 0 and 30 days, 0 disables the limit
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_mining_claim_period(final_mining_claim_period_seconds))]
+    pub fn set_final_mining_claim_period<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalMiningClaimPeriodContext<'info>>,
+        final_mining_claim_period_seconds: i64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalMiningClaimPeriod {
+            final_mining_claim_period_seconds,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_mining_claim_period_seconds = final_mining_claim_period_seconds;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Requires or stops requiring the final mining balances given to final_mining to match a recent final mining balance checkpoint (see checkpoint_final_mining_balance).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set final mining checkpoint required context where the contract's owner is the signer,
+    /// * `final_mining_checkpoint_required` - true to require the checkpoint, false to stop requiring it
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account))]
+    pub fn set_final_mining_checkpoint_required<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalMiningCheckpointRequiredContext<'info>>,
+        final_mining_checkpoint_required: bool,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalMiningCheckpointRequired {
+            final_mining_checkpoint_required,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_mining_checkpoint_required = final_mining_checkpoint_required;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Halts solving of top or bottom blocks (solve_top_block or solve_bottom_block) until the contract's owner resumes it.
+    /// It can be invoked only by the guardian and only while admin controls are not renounced, so halted blocks can always be resumed.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set block halted context where the guardian is the signer,
+    /// * `block_kind` - the kind of blocks to halt
+    #[access_control(valid_guardian(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn halt_block<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlockHaltedContext<'info>>,
+        block_kind: BlockKind,
+    ) -> Result<()> {
+        set_block_halted(&mut ctx.accounts.blocks_state_account, block_kind, true);
+
+        Ok(())
+    }
+
+    /// Resumes solving of top or bottom blocks halted by the guardian.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set block halted context where the contract's owner is the signer,
+    /// * `block_kind` - the kind of blocks to resume
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account))]
+    pub fn resume_block<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlockHaltedContext<'info>>,
+        block_kind: BlockKind,
+    ) -> Result<()> {
+        set_block_halted(&mut ctx.accounts.blocks_state_account, block_kind, false);
+
+        Ok(())
+    }
+
+    /// Permanently renounces admin controls of the contract's owner, i.e. parameter setters (minimum payout amount, treasury),
+    /// pause and unpause, and signer set changes. It is a one-way switch, admin controls cannot be restored.
+    /// Block solutions, final mining and final staking keep working as before.
+    /// The program cannot be paused nor block solving halted while renouncing, otherwise it would stay paused (or halted) forever.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the renounce admin controls context where the contract's owner is the signer.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) program_not_paused(&ctx.accounts.blocks_state_account) block_not_halted(&ctx.accounts.blocks_state_account, BlockKind::Top) block_not_halted(&ctx.accounts.blocks_state_account, BlockKind::Bottom))]
+    pub fn renounce_admin_controls<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenounceAdminControlsContext<'info>>,
+    ) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        blocks_state_account.admin_controls_renounced = true;
+
+        Ok(())
+    }
+
+    /// Sets the timelock delay, i.e. the delay between queuing an authority or parameter change and its execution.
+    /// Once the timelock is enabled, authority and parameter changes (including the timelock delay itself) must be queued using queue_pending_change function.
+    ///
+    /// ### Arguments
+    ///
+    /// * `timelock_delay` - new timelock delay (in seconds), it cannot exceed 30 days, 0 disables the timelock
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_timelock_delay(timelock_delay))]
+    pub fn set_timelock_delay<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetTimelockDelayContext<'info>>,
+        timelock_delay: i64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetTimelockDelay { timelock_delay };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.timelock_delay = timelock_delay;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the block solution interval, i.e. the minimum time between solutions of consecutive top blocks (and consecutive bottom blocks).
+    ///
+    /// ### Arguments
+    ///
+    /// * `block_solution_interval_seconds` - new block solution interval (in seconds), it must be between 1 minute and 1 hour
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_block_solution_interval(block_solution_interval_seconds))]
+    pub fn set_block_solution_interval<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlockSolutionIntervalContext<'info>>,
+        block_solution_interval_seconds: i64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetBlockSolutionInterval {
+            block_solution_interval_seconds,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.block_solution_interval_seconds = block_solution_interval_seconds;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Updates the final staking config, i.e. the minimum time between consecutive final staking rounds
+    /// and the factor the final staking account balance is divided by to get the prize pool of a round.
+    /// The new pool division factor is applied starting from the next final staking round.
+    ///
+    /// ### Arguments
+    ///
+    /// * `final_staking_interval_seconds` - new final staking interval (in seconds), it must be between 1 hour and 7 days,
+    /// * `final_staking_pool_division_factor` - new final staking pool division factor, it must be between 100 and 100000
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_staking_config(final_staking_interval_seconds, final_staking_pool_division_factor))]
+    pub fn update_final_staking_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateFinalStakingConfigContext<'info>>,
+        final_staking_interval_seconds: i64,
+        final_staking_pool_division_factor: u64,
+    ) -> Result<()> {
+        let change = ConfigChange::UpdateFinalStakingConfig {
+            final_staking_interval_seconds,
+            final_staking_pool_division_factor,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_staking_interval_seconds = final_staking_interval_seconds;
+        blocks_state_account.final_staking_pool_division_factor =
+            final_staking_pool_division_factor;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Updates the final mining config, i.e. the minimum time between consecutive final mining calls
+    /// and the maximum amount of tokens distributed by a single final mining call.
+    ///
+    /// ### Arguments
+    ///
+    /// * `final_mining_interval_seconds` - new final mining interval (in seconds), it must be between 0 and 1 day,
+    /// * `final_mining_max_payout_per_call` - new maximum payout per final mining call, it must be between the highest single final mining reward and the amount of tokens of 10 blocks
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_mining_config(final_mining_interval_seconds, final_mining_max_payout_per_call))]
+    pub fn update_final_mining_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateFinalMiningConfigContext<'info>>,
+        final_mining_interval_seconds: i64,
+        final_mining_max_payout_per_call: u64,
+    ) -> Result<()> {
+        let change = ConfigChange::UpdateFinalMiningConfig {
+            final_mining_interval_seconds,
+            final_mining_max_payout_per_call,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_mining_interval_seconds = final_mining_interval_seconds;
+        blocks_state_account.final_mining_max_payout_per_call = final_mining_max_payout_per_call;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the final mining budget, i.e. the maximum amount of tokens distributed by final mining within a single budget window,
+    /// so the emission from the final mining account is bounded regardless of the number of calls.
+    /// The amount already distributed within the current window is kept.
+    ///
+    /// ### Arguments
+    ///
+    /// * `final_mining_budget_per_window` - new final mining budget, it must not be lower than the highest single final mining reward, None disables the budget,
+    /// * `final_mining_budget_window_seconds` - new final mining budget window (in seconds), it must be between 1 second and 30 days
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_mining_budget(final_mining_budget_per_window, final_mining_budget_window_seconds))]
+    pub fn set_final_mining_budget<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalMiningBudgetContext<'info>>,
+        final_mining_budget_per_window: Option<u64>,
+        final_mining_budget_window_seconds: i64,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalMiningBudget {
+            final_mining_budget_per_window,
+            final_mining_budget_window_seconds,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_mining_budget_per_window = final_mining_budget_per_window;
+        blocks_state_account.final_mining_budget_window_seconds =
+            final_mining_budget_window_seconds;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the final staking terminal config, i.e. the minimum prize pool of a regular final staking round
+    /// and the way the remaining final staking account balance is handled once a regular round would be smaller.
+    ///
+    /// ### Arguments
+    ///
+    /// * `final_staking_min_pool_in_round` - new minimum pool in round, it must not exceed the amount of tokens of a single block, 0 disables the terminal transition,
+    /// * `final_staking_terminal_mode` - new terminal mode, the treasury must be set to sweep the remaining balance to it
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_staking_terminal_config(&ctx.accounts.blocks_state_account, final_staking_min_pool_in_round, final_staking_terminal_mode))]
+    pub fn set_final_staking_terminal_config<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalStakingTerminalConfigContext<'info>>,
+        final_staking_min_pool_in_round: u64,
+        final_staking_terminal_mode: FinalStakingTerminalMode,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalStakingTerminalConfig {
+            final_staking_min_pool_in_round,
+            final_staking_terminal_mode,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_staking_min_pool_in_round = final_staking_min_pool_in_round;
+        blocks_state_account.final_staking_terminal_mode = final_staking_terminal_mode;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the tolerance of the verification of the reward parts given to final_staking against the balances of the users.
+    /// While the tolerance is set (and final staking snapshots are not required), the reward part of each user must not exceed the user's share of the registered balance of the round
+    /// (the balance of the user's token account divided by the total weighted balance of the snapshots registered for the round) by more than the tolerance.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set final staking reward part tolerance context where the contract's owner is the signer,
+    /// * `final_staking_reward_part_tolerance_bps` - new tolerance (in basis points), it must not exceed 90_000 (reward parts up to 10x the share of the user), None disables the verification
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_final_staking_reward_part_tolerance(final_staking_reward_part_tolerance_bps))]
+    pub fn set_final_staking_reward_part_tolerance<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetFinalStakingRewardPartToleranceContext<'info>>,
+        final_staking_reward_part_tolerance_bps: Option<u32>,
+    ) -> Result<()> {
+        let change = ConfigChange::SetFinalStakingRewardPartTolerance {
+            final_staking_reward_part_tolerance_bps,
+        };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.final_staking_reward_part_tolerance_bps =
+            final_staking_reward_part_tolerance_bps;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the fee taken from each top and bottom block reward and transferred to the treasury.
+    /// Once the fee is set, the treasury token account must be provided to solve_top_block and solve_bottom_block.
+    ///
+    /// ### Arguments
+    ///
+    /// * `reward_fee_bps` - new reward fee (in basis points), it must not exceed 500 (5%), 0 disables the fee
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_reward_fee(reward_fee_bps))]
+    pub fn set_reward_fee<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetRewardFeeContext<'info>>,
+        reward_fee_bps: u16,
+    ) -> Result<()> {
+        let change = ConfigChange::SetRewardFee { reward_fee_bps };
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        let previous_values =
+            current_config_values(blocks_state_account, None, None, None, &change);
+        blocks_state_account.reward_fee_bps = reward_fee_bps;
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the distribution backend of the subsystem, i.e. the way its rewards are moved from the distribution account to users (see DistributionBackend).
+    /// Top and bottom block rewards can be transferred directly, credited to claim accounts below the minimum payout amount or held in claim accounts until users withdraw them,
+    /// final mining and final staking rewards are always transferred directly.
+    ///
+    /// ### Arguments
+    ///
+    /// * `subsystem` - the subsystem to configure,
+    /// * `distribution_backend` - new distribution backend of the subsystem
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) timelock_disabled(&ctx.accounts.blocks_state_account) valid_distribution_backend(subsystem, distribution_backend))]
+    pub fn set_distribution_backend<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetDistributionBackendContext<'info>>,
+        subsystem: Subsystem,
+        distribution_backend: DistributionBackend,
+    ) -> Result<()> {
+        let change = ConfigChange::SetDistributionBackend {
+            subsystem,
+            distribution_backend,
+        };
+        let previous_values = current_config_values(
+            &ctx.accounts.blocks_state_account,
+            Some(&ctx.accounts.config_account),
+            None,
+            None,
+            &change,
+        );
+        distribution::set_distribution_backend(
+            &mut ctx.accounts.config_account,
+            subsystem,
+            distribution_backend,
+        );
+
+        emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+    }
+
+    /// Sets the SLA threshold, i.e. the maximum accepted delay between the moment a block solution or a final staking round becomes eligible and its completion.
+    /// `SlaBreached` event is emitted whenever the threshold is exceeded.
+    /// The threshold is used for monitoring only and it does not affect the token distribution, so the update is not subject to the timelock.
+    ///
+    /// ### Arguments
+    ///
+    /// * `sla_threshold_seconds` - new SLA threshold (in seconds), it must be between 0 and 30 days, 0 disables the event
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) admin_controls_not_renounced(&ctx.accounts.blocks_state_account) valid_sla_threshold(sla_threshold_seconds))]
+    pub fn set_sla_threshold<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetSlaThresholdContext<'info>>,
+        sla_threshold_seconds: i64,
+    ) -> Result<()> {
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        blocks_state_account.sla_threshold_seconds = sla_threshold_seconds;
+
+        Ok(())
+    }
+
+    /// Queues an authority or parameter change in the timelock.
+    /// The change can be executed using execute_pending_change function once the timelock delay elapses.
+    /// Only a single change can be pending at a time.
+    ///
+    /// ### Arguments
+    ///
+    /// * `change` - the change to queue
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) timelock_enabled(&ctx.accounts.blocks_state_account) valid_config_change(&ctx.accounts.blocks_state_account, &change))]
+    pub fn queue_pending_change(
+        ctx: Context<QueuePendingChangeContext>,
+        change: ConfigChange,
+    ) -> Result<()> {
+        let pending_change_account = &mut ctx.accounts.pending_change_account;
+        pending_change_account.bump = ctx.bumps.pending_change_account;
+        pending_change_account.change = change;
+        pending_change_account.execute_after =
+            current_cluster_timestamp()? + ctx.accounts.blocks_state_account.timelock_delay;
+
+        Ok(())
+    }
+
+    /// Executes the change queued in the timelock once its delay has elapsed.
+    /// The change is validated again, so the change cannot be applied if admin controls were renounced after queuing it.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the execute pending change context where the accounts required by the change are provided.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer) pending_change_executable(&ctx.accounts.pending_change_account) valid_config_change(&ctx.accounts.blocks_state_account, &ctx.accounts.pending_change_account.change))]
+    pub fn execute_pending_change<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecutePendingChangeContext<'info>>,
+    ) -> Result<()> {
+        let change = ctx.accounts.pending_change_account.change.clone();
+        let previous_values = current_config_values(
+            &ctx.accounts.blocks_state_account,
+            Some(&ctx.accounts.config_account),
+            ctx.accounts.signer_set_account.as_deref(),
+            ctx.accounts.final_mining_tiers_account.as_deref(),
+            &change,
+        );
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+
+        match change.clone() {
+            ConfigChange::ChangeAuthority { new_authority } => {
+                blocks_state_account.authority = new_authority;
+                blocks_state_account.pending_authority = None;
+            }
+            ConfigChange::ProposeAuthority { new_authority } => {
+                blocks_state_account.pending_authority = Some(new_authority);
+            }
+            ConfigChange::UpdateSignerSet { signers, threshold } => {
+                let signer_set_account = match &mut ctx.accounts.signer_set_account {
+                    Some(signer_set_account) => signer_set_account,
+                    None => return err!(SallarError::MissingAccountForPendingChange),
+                };
+                signer_set_account.threshold = threshold;
+                signer_set_account.signers = signers;
+            }
+            ConfigChange::SetMinPayoutAmount { min_payout_amount } => {
+                blocks_state_account.min_payout_amount = min_payout_amount;
+            }
+            ConfigChange::SetTreasury { treasury_address } => {
+                let treasury_token_account = match &ctx.accounts.treasury_token_account {
+                    Some(treasury_token_account) => treasury_token_account,
+                    None => return err!(SallarError::MissingAccountForPendingChange),
+                };
+                require_keys_eq!(
+                    treasury_token_account.key(),
+                    treasury_address,
+                    SallarError::InvalidTreasuryAccount
+                );
+                blocks_state_account.treasury_address = Some(treasury_address);
+            }
+            ConfigChange::SetTimelockDelay { timelock_delay } => {
+                blocks_state_account.timelock_delay = timelock_delay;
+            }
+            ConfigChange::SetBlockSolutionInterval {
+                block_solution_interval_seconds,
+            } => {
+                blocks_state_account.block_solution_interval_seconds =
+                    block_solution_interval_seconds;
+            }
+            ConfigChange::UpdateFinalStakingConfig {
+                final_staking_interval_seconds,
+                final_staking_pool_division_factor,
+            } => {
+                blocks_state_account.final_staking_interval_seconds =
+                    final_staking_interval_seconds;
+                blocks_state_account.final_staking_pool_division_factor =
+                    final_staking_pool_division_factor;
+            }
+            ConfigChange::SetRewardFee { reward_fee_bps } => {
+                blocks_state_account.reward_fee_bps = reward_fee_bps;
+            }
+            ConfigChange::SetGuardian { guardian } => {
+                blocks_state_account.guardian = guardian;
+            }
+            ConfigChange::UpdateFinalMiningConfig {
+                final_mining_interval_seconds,
+                final_mining_max_payout_per_call,
+            } => {
+                blocks_state_account.final_mining_interval_seconds = final_mining_interval_seconds;
+                blocks_state_account.final_mining_max_payout_per_call =
+                    final_mining_max_payout_per_call;
+            }
+            ConfigChange::SetBlockHistoryEnabled {
+                block_history_enabled,
+            } => {
+                blocks_state_account.block_history_enabled = block_history_enabled;
+            }
+            ConfigChange::SetSolveCoordinator { solve_coordinator } => {
+                blocks_state_account.solve_coordinator = solve_coordinator;
+            }
+            ConfigChange::SetBottomBlockMinRequiredStake {
+                bottom_block_min_required_stake,
+            } => {
+                blocks_state_account.bottom_block_min_required_stake =
+                    bottom_block_min_required_stake;
+            }
+            ConfigChange::SetMaxUserRequestsPerBlock {
+                top_block_max_user_requests,
+                bottom_block_max_user_requests,
+            } => {
+                blocks_state_account.top_block_max_user_requests = top_block_max_user_requests;
+                blocks_state_account.bottom_block_max_user_requests =
+                    bottom_block_max_user_requests;
+            }
+            ConfigChange::SetBlockParticipationCommitmentEnabled {
+                block_participation_commitment_enabled,
+            } => {
+                blocks_state_account.block_participation_commitment_enabled =
+                    block_participation_commitment_enabled;
+            }
+            ConfigChange::SetBoostCreditsRequired {
+                boost_credits_required,
+            } => {
+                blocks_state_account.boost_credits_required = boost_credits_required;
+            }
+            ConfigChange::SetFinalStakingSnapshotsRequired {
+                final_staking_snapshots_required,
+            } => {
+                blocks_state_account.final_staking_snapshots_required =
+                    final_staking_snapshots_required;
+            }
+            ConfigChange::SetFinalMiningClaimPeriod {
+                final_mining_claim_period_seconds,
+            } => {
+                blocks_state_account.final_mining_claim_period_seconds =
+                    final_mining_claim_period_seconds;
+            }
+            ConfigChange::SetFinalMiningCheckpointRequired {
+                final_mining_checkpoint_required,
+            } => {
+                blocks_state_account.final_mining_checkpoint_required =
+                    final_mining_checkpoint_required;
+            }
+            ConfigChange::SetFinalStakingRewardPartTolerance {
+                final_staking_reward_part_tolerance_bps,
+            } => {
+                blocks_state_account.final_staking_reward_part_tolerance_bps =
+                    final_staking_reward_part_tolerance_bps;
+            }
+            ConfigChange::SetFinalStakingTerminalConfig {
+                final_staking_min_pool_in_round,
+                final_staking_terminal_mode,
+            } => {
+                blocks_state_account.final_staking_min_pool_in_round =
+                    final_staking_min_pool_in_round;
+                blocks_state_account.final_staking_terminal_mode = final_staking_terminal_mode;
+            }
+            ConfigChange::SetFinalMiningBudget {
+                final_mining_budget_per_window,
+                final_mining_budget_window_seconds,
+            } => {
+                blocks_state_account.final_mining_budget_per_window =
+                    final_mining_budget_per_window;
+                blocks_state_account.final_mining_budget_window_seconds =
+                    final_mining_budget_window_seconds;
+            }
+            ConfigChange::SetFinalMiningTiers { tiers } => {
+                let final_mining_tiers_account = match &mut ctx.accounts.final_mining_tiers_account
+                {
+                    Some(final_mining_tiers_account) => final_mining_tiers_account,
+                    None => return err!(SallarError::MissingAccountForPendingChange),
+                };
+                final_mining_tiers_account.tiers = tiers;
+            }
+            ConfigChange::SetDistributionBackend {
+                subsystem,
+                distribution_backend,
+            } => {
+                distribution::set_distribution_backend(
+                    &mut ctx.accounts.config_account,
+                    subsystem,
+                    distribution_backend,
+                );
+            }
+        }
+
+        match (previous_values, change) {
+            (
+                ConfigChange::ChangeAuthority {
+                    new_authority: previous_authority,
+                },
+                ConfigChange::ChangeAuthority { new_authority },
+            ) => {
+                emit_authority_changed(ctx.accounts.signer.key(), previous_authority, new_authority)
+            }
+            (previous_values, change) => {
+                emit_config_changed(ctx.accounts.signer.key(), previous_values, change)
+            }
+        }
+    }
+
+    /// Cancels the change queued in the timelock.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the cancel pending change context where the contract's owner is the signer.
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn cancel_pending_change<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelPendingChangeContext<'info>>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Creates the claim account for the given token account.
+    /// The claim account keeps rewards below the minimum payout amount until they are transferred,
+    /// and the rewards held by the escrow distribution backend until they are withdrawn.
+    /// It can be created by anyone as the signer only pays for the account creation.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the context where the token account and its claim account are provided.
+    #[access_control(valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_user_claim_account(
+        ctx: Context<InitializeUserClaimAccountContext>,
+    ) -> Result<()> {
+        let user_claim_account = &mut ctx.accounts.user_claim_account;
+        user_claim_account.user = ctx.accounts.user_token_account.key();
+        user_claim_account.bump = ctx.bumps.user_claim_account;
+        user_claim_account.pending_top_block_amount = 0;
+        user_claim_account.pending_bottom_block_amount = 0;
+
+        Ok(())
+    }
+
+    /// Withdraws the rewards pending in the user's claim account, i.e. the rewards held by the escrow distribution backend
+    /// or accumulated below the minimum payout amount by the claim credit backend, from the top and bottom block distribution accounts.
+    /// Anyone can withdraw the rewards on behalf of the user, the tokens are always transferred to the user's token account.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the withdraw pending rewards context where the user's token account and its claim account are provided.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) denylist_provided(&ctx.accounts.blocks_state_account, &ctx.accounts.denylist_account))]
+    pub fn withdraw_pending_rewards(ctx: Context<WithdrawPendingRewardsContext>) -> Result<()> {
+        let user = ctx.accounts.user_token_account.key();
+        recipient_not_denylisted(ctx.accounts.denylist_account.as_deref(), &user)?;
+
+        let user_claim_account = &mut ctx.accounts.user_claim_account;
+        let top_block_amount = user_claim_account.pending_top_block_amount;
+        let bottom_block_amount = user_claim_account.pending_bottom_block_amount;
+        require!(
+            top_block_amount > 0 || bottom_block_amount > 0,
+            SallarError::NoPendingRewards
+        );
+        user_claim_account.pending_top_block_amount = 0;
+        user_claim_account.pending_bottom_block_amount = 0;
+
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        blocks_state.top_block_pending_payouts = blocks_state
+            .top_block_pending_payouts
+            .checked_sub(top_block_amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+        blocks_state.bottom_block_pending_payouts = blocks_state
+            .bottom_block_pending_payouts
+            .checked_sub(bottom_block_amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+
+        if top_block_amount > 0 {
+            transfer_tokens(
+                &ctx.accounts.distribution_top_block_account,
+                ctx.accounts.user_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                DISTRIBUTION_TOP_BLOCK_SEED,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.config_account.top_block_distribution_nonce,
+                top_block_amount,
+            )?;
+        }
+        if bottom_block_amount > 0 {
+            transfer_tokens(
+                &ctx.accounts.distribution_bottom_block_account,
+                ctx.accounts.user_token_account.to_account_info(),
+                &ctx.accounts.mint,
+                DISTRIBUTION_BOTTOM_BLOCK_SEED,
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.config_account.bottom_block_distribution_nonce,
+                bottom_block_amount,
+            )?;
+        }
+
+        emit!(PendingRewardsWithdrawn {
+            user,
+            top_block_amount,
+            bottom_block_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the lifetime reward statistics account for the given wallet.
+    /// Once created, rewards received by token accounts owned by the wallet are counted by the distribution instructions
+    /// whenever the account is provided among their remaining accounts.
+    /// It can be created by anyone as the signer only pays for the account creation.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the context where the wallet and its reward stats account are provided.
+    #[access_control(valid_signer(&ctx.accounts.signer))]
+    pub fn initialize_user_reward_stats(
+        ctx: Context<InitializeUserRewardStatsContext>,
+    ) -> Result<()> {
+        let user_reward_stats_account = &mut ctx.accounts.user_reward_stats_account;
+        user_reward_stats_account.wallet = ctx.accounts.wallet.key();
+        user_reward_stats_account.bump = ctx.bumps.user_reward_stats_account;
+        user_reward_stats_account.top_block_reward_amount = 0;
+        user_reward_stats_account.bottom_block_reward_amount = 0;
+        user_reward_stats_account.final_mining_reward_amount = 0;
+        user_reward_stats_account.final_staking_reward_amount = 0;
+
+        Ok(())
+    }
+
+    /// Locks tokens of the user in its stake vault.
+    /// The locked amount is the user's balance in bottom block solution (the stake lock account must be provided among remaining accounts of solve_bottom_block),
+    /// so the bottom block boost is backed by tokens which cannot be moved to another account while they are locked.
+    /// The whole locked amount is the user's balance only since the next bottom block (see stake_lock_balance),
+    /// so tokens unlocked by another token account within the current bottom block do not back the boost of both users.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the lock stake context where the owner of the user's token account is the signer,
+    /// * `amount` - the amount of tokens to lock.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account))]
+    pub fn lock_stake(ctx: Context<LockStakeContext>, amount: u64) -> Result<()> {
+        require!(amount > 0, SallarError::InvalidStakeAmount);
+
+        transfer_tokens_from_user(
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.stake_vault_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+
+        let user = ctx.accounts.user_token_account.key();
+        let bottom_block_number = ctx.accounts.blocks_state_account.bottom_block_number;
+        let stake_lock_account = &mut ctx.accounts.stake_lock_account;
+        stake_lock_account.user = user;
+        stake_lock_account.bump = ctx.bumps.stake_lock_account;
+        stake_lock_account.vault_bump = ctx.bumps.stake_vault_account;
+        stake_lock_account.locked_at_bottom_block = bottom_block_number;
+        stake_lock_account.locked_amount = stake_lock_account
+            .locked_amount
+            .checked_add(amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        emit!(StakeLocked {
+            user,
+            amount,
+            locked_amount: stake_lock_account.locked_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Unlocks tokens of the user from its stake vault back to the user's token account.
+    /// The tokens cannot be unlocked while they are registered for a final staking round which is not completed yet (see register_for_final_staking).
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the unlock stake context where the owner of the user's token account is the signer,
+    /// * `amount` - the amount of tokens to unlock.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) stake_not_registered_for_final_staking(&ctx.accounts.stake_lock_account, &ctx.accounts.blocks_state_account))]
+    pub fn unlock_stake(ctx: Context<UnlockStakeContext>, amount: u64) -> Result<()> {
+        require!(amount > 0, SallarError::InvalidStakeAmount);
+        require!(
+            amount <= ctx.accounts.stake_lock_account.locked_amount,
+            SallarError::InsufficientLockedStake
+        );
+
+        let user = ctx.accounts.user_token_account.key();
+        transfer_tokens_from_stake_vault(
+            ctx.accounts.stake_vault_account.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            &user,
+            ctx.accounts.stake_lock_account.vault_bump,
+            ctx.accounts.token_program.to_account_info(),
+            amount,
+        )?;
+
+        let stake_lock_account = &mut ctx.accounts.stake_lock_account;
+        stake_lock_account.locked_amount = stake_lock_account
+            .locked_amount
+            .checked_sub(amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+
+        emit!(StakeUnlocked {
+            user,
+            amount,
+            locked_amount: stake_lock_account.locked_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Enables or disables restaking of the user's final staking rewards.
+    /// While enabled, final staking rewards of the user are deposited to its stake vault and locked (compounding the user's stake) instead of being transferred to the user's token account,
+    /// provided that the stake lock account and the stake vault of the user are passed among remaining accounts of the distributing instruction.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the set final staking auto restake context where the owner of the user's token account is the signer,
+    /// * `auto_restake` - true to deposit final staking rewards to the stake vault, false to transfer them to the user's token account.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account))]
+    pub fn set_final_staking_auto_restake(
+        ctx: Context<SetFinalStakingAutoRestakeContext>,
+        auto_restake: bool,
+    ) -> Result<()> {
+        ctx.accounts.stake_lock_account.auto_restake = auto_restake;
+
+        emit!(FinalStakingAutoRestakeSet {
+            user: ctx.accounts.user_token_account.key(),
+            auto_restake,
+        });
+
+        Ok(())
+    }
+
+    /// Buys boost credits for the user's token account by burning BOOST_CREDIT_PRICE tokens per credit from it.
+    /// While boost credits are required, requests with boost in block solution consume the credits of the user.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the buy boost credits context where the owner of the user's token account is the signer,
+    /// * `credits` - the number of boost credits to buy.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account))]
+    pub fn buy_boost_credits(ctx: Context<BuyBoostCreditsContext>, credits: u64) -> Result<()> {
+        require!(credits > 0, SallarError::InvalidBoostCreditsAmount);
+        let burned_amount = credits
+            .checked_mul(BOOST_CREDIT_PRICE)
+            .ok_or(SallarError::InvalidBoostCreditsAmount)?;
+
+        burn_tokens_from_user(
+            ctx.accounts.user_token_account.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.signer.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            burned_amount,
+        )?;
+
+        let user = ctx.accounts.user_token_account.key();
+        let boost_credits_account = &mut ctx.accounts.boost_credits_account;
+        boost_credits_account.user = user;
+        boost_credits_account.bump = ctx.bumps.boost_credits_account;
+        boost_credits_account.credits = boost_credits_account
+            .credits
+            .checked_add(credits)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        emit!(BoostCreditsPurchased {
+            user,
+            credits,
+            burned_amount,
+            credits_balance: boost_credits_account.credits,
+        });
+
+        Ok(())
+    }
+
+    /// Registers the balance snapshot of the user's token account for the next final staking round.
+    /// The snapshot is the amount of tokens currently locked in the user's stake vault (see lock_stake), registering again for the same round replaces the previous snapshot.
+    /// The registered tokens cannot be unlocked until the round is completed, so the same tokens cannot be registered by another token account for the round.
+    /// The balance is weighted by the multiplier of the user's final staking tier, based on the balance age counted from the first registration
+    /// (the age is reset whenever the registered balance decreases).
+    /// While final staking snapshots are required, the part of the round's prize pool of the user is proportional to the weighted balance of the snapshot.
+    ///
+    /// ### Arguments
+    ///
+    /// * `ctx` - the register for final staking context where the owner of the user's token account or the solve coordinator is the signer.
+    ///
+    /// ### Returns
+    /// The index of the final staking round the snapshot is registered for.
+    #[access_control(valid_signer(&ctx.accounts.signer) program_not_paused(&ctx.accounts.blocks_state_account) final_staking_not_finalized(&ctx.accounts.blocks_state_account) valid_final_staking_registrant(&ctx.accounts.blocks_state_account, &ctx.accounts.user_token_account.owner, &ctx.accounts.signer))]
+    pub fn register_for_final_staking(ctx: Context<RegisterForFinalStakingContext>) -> Result<u64> {
+        let balance = ctx.accounts.stake_lock_account.locked_amount;
+        require!(balance > 0, SallarError::EmptyFinalStakingRegistration);
+
+        let blocks_state = &mut ctx.accounts.blocks_state_account;
+        let round_index = blocks_state.final_staking_round_counter + 1;
+        let user = ctx.accounts.user_token_account.key();
+        let registration_timestamp = current_cluster_timestamp()?;
+        let final_staking_registration_account =
+            &mut ctx.accounts.final_staking_registration_account;
+        if final_staking_registration_account.round_index == round_index {
+            blocks_state.final_staking_registered_balance_next_round = blocks_state
+                .final_staking_registered_balance_next_round
+                .checked_sub(final_staking_registration_account.weighted_balance)
+                .ok_or(SallarError::ArithmeticUnderflow)?;
+        }
+        if final_staking_registration_account.registered_since == 0
+            || balance < final_staking_registration_account.balance
+        {
+            final_staking_registration_account.registered_since = registration_timestamp;
+        }
+        let multiplier_bps = ctx.accounts.final_staking_tiers_account.as_ref().map_or(
+            BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS,
+            |final_staking_tiers_account| {
+                final_staking_tier_multiplier_bps(
+                    &final_staking_tiers_account.tiers,
+                    registration_timestamp - final_staking_registration_account.registered_since,
+                )
+            },
+        );
+        let weighted_balance = calculate_weighted_balance(balance, multiplier_bps)?;
+        final_staking_registration_account.user = user;
+        final_staking_registration_account.bump = ctx.bumps.final_staking_registration_account;
+        final_staking_registration_account.round_index = round_index;
+        final_staking_registration_account.balance = balance;
+        final_staking_registration_account.rewarded = false;
+        final_staking_registration_account.weighted_balance = weighted_balance;
+        ctx.accounts.stake_lock_account.registered_round_index = round_index;
+        blocks_state.final_staking_registered_balance_next_round = blocks_state
+            .final_staking_registered_balance_next_round
+            .checked_add(weighted_balance)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        emit!(FinalStakingRegistered {
+            user,
+            round_index,
+            balance,
+            weighted_balance,
+            registered_balance: blocks_state.final_staking_registered_balance_next_round,
+        });
+
+        Ok(round_index)
+    }
+
+    /// Set blocks collided flag
+    /// This function is only available in tests
+    ///
+    /// ### Arguments
+    ///
+    /// * `collided` - new value of blocks collided flag
+    #[access_control(valid_owner(&ctx.accounts.blocks_state_account, &ctx.accounts.signer) valid_signer(&ctx.accounts.signer))]
+    pub fn set_blocks_collided<'info>(
+        ctx: Context<'_, '_, '_, 'info, SetBlocksCollidedContext<'info>>,
+        collided: bool,
+    ) -> Result<()> {
+        require!(
+            cfg!(feature = "bpf-tests"),
+            SallarError::ExecutionOfSetBlocksCollidedFunctionOutsideTests
+        );
+
+        let blocks_state_account = &mut ctx.accounts.blocks_state_account;
+        blocks_state_account.blocks_collided = collided;
+        blocks_state_account.top_block_available_bp = 0;
+        blocks_state_account.bottom_block_available_bp = 0;
+
+        Ok(())
+    }
+}
+
+/// Struct defining single account participating in the top block solution process.
+/// Consists of the account address and data required to calculate the number of tokens to transfer to the account (number of requests to participate in the current top block solution on the client side).
+/// If the wallet is given, the account must be the associated token account of the wallet, it is created (at the signer's expense) if it does not exist yet.
+/// While boost credits are required, every request with boost consumes a boost credit of the account (its boost credits account must be provided among remaining accounts).
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UserInfoTopBlock {
+    pub user_public_key: Pubkey,
+    pub user_request_without_boost: u8,
+    pub user_request_with_boost: u8,
+    pub wallet: Option<Pubkey>,
+}
+
+/// Struct defining a chunk of a block solution batch split into multiple transactions.
+/// Consists of the identifier of the batch chosen by the backend, the index (within the whole batch) of the first user info entry of the chunk
+/// and the number of user info entries of the whole batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SolveBatchChunk {
+    pub batch_id: u64,
+    pub start_index: u32,
+    pub total_users: u32,
+}
+
+/// Struct defining single account participating in the bottom block solution process.
+/// Consists of the account address and data required to calculate the number of tokens to transfer to the account (number of requests to participate in the current bottom block solution on the client side).
+/// If the wallet is given, the account must be the associated token account of the wallet, it is created (at the signer's expense) if it does not exist yet.
+/// The account's balance is the amount of tokens locked in the account's stake vault (see lock_stake), so it cannot be altered by the client.
+/// While boost credits are required, every request with boost consumes a boost credit of the account (its boost credits account must be provided among remaining accounts).
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UserInfoBottomBlock {
+    pub user_public_key: Pubkey,
+    pub user_request_without_boost: u8,
+    pub user_request_with_boost: u8,
+    pub wallet: Option<Pubkey>,
+}
+
+/// Struct defining single account participating in the final mining process.
+/// Consists of the account address and data required to calculate the number of tokens to be transferred to the account (final mining account balance at the time the account requested participation in the final mining process on the client side).
+/// If the wallet is given, the account must be the associated token account of the wallet, it is created (at the signer's expense) if it does not exist yet.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UserInfoFinalMining {
+    pub user_public_key: Pubkey,
+    pub final_mining_balance: u64,
+    pub wallet: Option<Pubkey>,
+}
+
+/// Struct defining single account participating in the final staking process.
+/// Consists of the account address and data required to calculate the number of tokens to be transferred to the account (part of the total prize pool declared for the current final staking round).
+/// The reward part is given in scaled reward parts, i.e. 1_000_000_000 (`REWARD_PARTS_SCALING_FACTOR`) is the whole prize pool of the round, it is ignored while final staking snapshots are required.
+/// If the wallet is given, the account must be the associated token account of the wallet, it is created (at the signer's expense) if it does not exist yet.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UserInfoFinalStaking {
+    pub user_public_key: Pubkey,
+    pub reward_part: u64,
+    pub wallet: Option<Pubkey>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use anchor_lang::{prelude::AccountMeta, system_program, InstructionData, ToAccountMetas};
+    use anchor_spl::token::spl_token;
+    use solana_program_test::*;
+    use spl_token::state::Account;
+
+    use solana_sdk::{
+        commitment_config::CommitmentLevel, signature::Keypair, signer::Signer,
+        transaction::Transaction,
+    };
+
+    use solana_program::{
         hash::Hash, instruction::Instruction, program_pack::Pack, system_instruction,
     };
     use utils::final_staking_required_interval_elapsed;
 
-    #[cfg(feature = "bpf-tests")]
-    use solana_program::{instruction::InstructionError, sysvar::clock::Clock};
+    #[cfg(feature = "bpf-tests")]
+    use solana_program::{instruction::InstructionError, sysvar::clock::Clock};
+
+    #[cfg(feature = "bpf-tests")]
+    use std::collections::HashMap;
+
+    #[cfg(feature = "bpf-tests")]
+    use solana_sdk::transaction::TransactionError;
+
+    #[cfg(feature = "bpf-tests")]
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    #[cfg(feature = "bpf-tests")]
+    use anchor_lang::Event;
+
+    #[cfg(feature = "bpf-tests")]
+    use crate::{
+        account::{FinalStakingRegistration, StakeLock},
+        event::{FinalStakingRewardRestaked, RestCarriedOver},
+    };
+
+    impl Clone for UserInfoBottomBlock {
+        fn clone(&self) -> Self {
+            Self {
+                user_public_key: self.user_public_key,
+                user_request_without_boost: self.user_request_without_boost,
+                user_request_with_boost: self.user_request_with_boost,
+                wallet: self.wallet,
+            }
+        }
+    }
+
+    impl Clone for UserInfoFinalStaking {
+        fn clone(&self) -> Self {
+            Self {
+                user_public_key: self.user_public_key,
+                reward_part: self.reward_part,
+                wallet: self.wallet,
+            }
+        }
+    }
+
+    impl Clone for UserInfoTopBlock {
+        fn clone(&self) -> Self {
+            Self {
+                user_public_key: self.user_public_key,
+                user_request_without_boost: self.user_request_without_boost,
+                user_request_with_boost: self.user_request_with_boost,
+                wallet: self.wallet,
+            }
+        }
+    }
+
+    async fn initialize_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+    ) -> Result<()> {
+        let program_id = id();
+        let (
+            mint_pda,
+            _,
+            blocks_state_pda,
+            _,
+            distribution_top_block_pda,
+            _,
+            distribution_bottom_block_pda,
+            _,
+            final_staking_account_pda,
+            _,
+            final_mining_account_pda,
+            _,
+        ) = get_pda_accounts();
+        let metadata_seed1 = "metadata".as_bytes();
+        let metadata_seed2 = &mpl_token_metadata::ID.to_bytes();
+        let metadata_seed3 = &mint_pda.to_bytes();
+        let (metadata_pda, _) = Pubkey::find_program_address(
+            &[metadata_seed1, metadata_seed2, metadata_seed3],
+            &mpl_token_metadata::ID,
+        );
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+        let token_metadata_name = "Sallar".to_string();
+        let token_metadata_symbol = "ALL".to_string();
+        let token_metadata_uri = "http://sallar.io".to_string();
+
+        let data = instruction::Initialize {
+            token_metadata_name,
+            token_metadata_symbol,
+            token_metadata_uri,
+            first_top_block_number: None,
+            first_bottom_block_number: None,
+            initial_token_distribution_amount: None,
+        }
+        .data();
+
+        let accs = accounts::InitializeContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            token_program,
+            signer,
+            system_program: system_program::ID,
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            distribution_bottom_block_account: distribution_bottom_block_pda,
+            final_staking_account: final_staking_account_pda,
+            final_mining_account: final_mining_account_pda,
+            metadata_pda,
+            metadata_program: mpl_token_metadata::ID,
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Confirmed)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    async fn initial_token_distribution_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        organization_account: Pubkey,
+    ) -> Result<()> {
+        let program_id = id();
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let data = instruction::InitialTokenDistribution {}.data();
+
+        let accs = accounts::InitialTokenDistributionContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            organization_account,
+            token_program,
+            signer,
+            global_stats_account: None,
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_initialize() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_initial_token_distribution() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _) = Pubkey::find_program_address(&[MINT_SEED.as_bytes()], &program_id);
+        let organization_account =
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap();
+        initial_token_distribution_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            organization_account,
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn solve_top_block_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        key_list: &[Pubkey],
+        users_info: &[UserInfoTopBlock],
+    ) -> Result<()> {
+        let program_id = id();
+
+        let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
+            get_pda_accounts();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let data = instruction::SolveTopBlock {
+            users_info: users_info.to_vec(),
+            max_valid_slot: None,
+            chunk: None,
+            positional_accounts: false,
+            roll_over: false,
+            strict: true,
+        }
+        .data();
+
+        let accs = accounts::SolveTopBlockContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        for key in key_list.iter() {
+            accounts.push(AccountMeta::new(*key, false));
+        }
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    async fn solve_bottom_block_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        key_list: &[Pubkey],
+        users_info: &[UserInfoBottomBlock],
+    ) -> Result<()> {
+        let program_id = id();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, distribution_bottom_block_pda, _, _, _, _, _) =
+            get_pda_accounts();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let data = instruction::SolveBottomBlock {
+            users_info: users_info.to_vec(),
+            max_valid_slot: None,
+            positional_accounts: false,
+            strict: true,
+        }
+        .data();
+
+        let accs = accounts::SolveBottomBlockContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_bottom_block_account: distribution_bottom_block_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        for key in key_list.iter() {
+            accounts.push(AccountMeta::new(*key, false));
+        }
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Finalized)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    async fn set_blocks_collided_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        collided: bool,
+    ) -> Result<()> {
+        let program_id = id();
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let signer = payer.pubkey();
+
+        let data = instruction::SetBlocksCollided { collided }.data();
+
+        let accs = accounts::SetBlocksCollidedContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer,
+        };
+
+        let accounts = accs.to_account_metas(Some(false));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
+            .await
+            .unwrap();
+
+        Ok(())
+    }
+
+    async fn default_top_block_setup(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+    ) -> (Vec<Pubkey>, Vec<UserInfoTopBlock>) {
+        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let mut key_list = vec![];
+
+        for _ in 0..5 {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            key_list.push(
+                create_token_account(banks_client, payer, recent_blockhash, mint_pda)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let mut users_info: Vec<UserInfoTopBlock> = vec![];
+
+        for key in key_list.iter() {
+            let user_info = UserInfoTopBlock {
+                user_public_key: *key,
+                user_request_with_boost: 1,
+                user_request_without_boost: 1,
+                wallet: None,
+            };
+            users_info.push(user_info);
+        }
+
+        (key_list, users_info)
+    }
+
+    async fn default_bottom_block_setup(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+    ) -> (Vec<Pubkey>, Vec<UserInfoBottomBlock>) {
+        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let mut key_list: Vec<Pubkey> = vec![];
+        for _ in 0..1 {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            key_list.push(
+                create_token_account(banks_client, payer, recent_blockhash, mint_pda)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let mut users_info: Vec<UserInfoBottomBlock> = vec![];
+        for key in key_list.iter() {
+            users_info.push(UserInfoBottomBlock {
+                user_public_key: *key,
+                user_request_without_boost: 25,
+                user_request_with_boost: 0,
+                wallet: None,
+            });
+        }
+
+        (key_list, users_info)
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_top_block_full_block() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        solve_top_block_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        solve_top_block_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+
+        for key in key_list.iter() {
+            let account = banks_client.get_account(*key).await.unwrap().unwrap();
+            let account_data = Account::unpack(&account.data).unwrap();
+            assert_eq!(account_data.amount, 400000000000);
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_top_two_blocks_with_user_rest() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+        program_test.set_compute_max_units(5000000);
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let mut program_test_context = program_test.start_with_context().await;
+        let mut banks_client = program_test_context.banks_client.clone();
+        let recent_blockhash = program_test_context.last_blockhash;
+
+        let mut time_in_timestamp = 1677978061;
+        set_time(&mut program_test_context, time_in_timestamp).await;
+
+        initialize_instruction(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+        )
+        .await
+        .unwrap();
+
+        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let key_list = vec![create_token_account(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+            mint_pda,
+        )
+        .await
+        .unwrap()];
+        let users_info: Vec<UserInfoTopBlock> = vec![UserInfoTopBlock {
+            user_public_key: key_list[0],
+            user_request_without_boost: 50,
+            user_request_with_boost: 0,
+            wallet: None,
+        }];
+
+        for _ in 0..2 {
+            let recent_blockhash = program_test_context
+                .banks_client
+                .get_latest_blockhash()
+                .await
+                .unwrap();
+            solve_top_block_instruction(
+                &mut banks_client,
+                &program_test_context.payer,
+                recent_blockhash,
+                &key_list,
+                &users_info,
+            )
+            .await
+            .unwrap();
+
+            // move time forward for 3 minutes to pass the required time between solved blocks
+            time_in_timestamp += 180;
+            set_time(&mut program_test_context, time_in_timestamp).await;
+        }
+
+        let key_list = vec![
+            key_list[0],
+            create_token_account(
+                &mut banks_client,
+                &program_test_context.payer,
+                recent_blockhash,
+                mint_pda,
+            )
+            .await
+            .unwrap(),
+        ];
+        let users_info: Vec<UserInfoTopBlock> = vec![
+            UserInfoTopBlock {
+                user_public_key: key_list[0],
+                user_request_without_boost: 0,
+                user_request_with_boost: 0,
+                wallet: None,
+            },
+            UserInfoTopBlock {
+                user_public_key: key_list[1],
+                user_request_without_boost: 7,
+                user_request_with_boost: 0,
+                wallet: None,
+            },
+        ];
+
+        let recent_blockhash = program_test_context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        solve_top_block_instruction(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+
+        let expected_user_balances: HashMap<Pubkey, u64> =
+            HashMap::from([(key_list[0], 5000000000000), (key_list[1], 700000000000)]);
+        for key in key_list.iter() {
+            let user_account = banks_client.get_account(*key).await.unwrap();
+            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
+            assert_eq!(user_account_data.amount, expected_user_balances[key]);
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_top_block_rest_carryovers_limit() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+        program_test.set_compute_max_units(5000000);
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
+            get_pda_accounts();
+
+        let mut key_list = vec![];
+        for _ in 0..=utils::MAX_REST_CARRYOVERS {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            key_list.push(
+                create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                    .await
+                    .unwrap(),
+            );
+        }
+        // the first user solves the block, the whole rewards of the following users are carried over
+        let users_info: Vec<UserInfoTopBlock> = key_list
+            .iter()
+            .enumerate()
+            .map(|(index, key)| UserInfoTopBlock {
+                user_public_key: *key,
+                user_request_without_boost: if index == 0 { 50 } else { 1 },
+                user_request_with_boost: 0,
+                wallet: None,
+            })
+            .collect();
+
+        let solve_top_block = |users_info: &[UserInfoTopBlock]| {
+            let mut accounts = accounts::SolveTopBlockContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                mint: mint_pda,
+                distribution_top_block_account: distribution_top_block_pda,
+                token_program: spl_token::id(),
+                signer: payer.pubkey(),
+                signer_set_account: None,
+                denylist_account: None,
+                treasury_token_account: None,
+                global_stats_account: None,
+                block_history_account: None,
+                block_user_requests_account: None,
+                system_program: None,
+                associated_token_program: None,
+                instructions_sysvar_account: None,
+                recent_solvers_account: None,
+                state_mirror_account: None,
+            }
+            .to_account_metas(Some(false));
+            accounts.extend(
+                users_info
+                    .iter()
+                    .map(|user_info| AccountMeta::new(user_info.user_public_key, false)),
+            );
+            Instruction::new_with_bytes(
+                program_id,
+                &instruction::SolveTopBlock {
+                    users_info: users_info.to_vec(),
+                    max_valid_slot: None,
+                    chunk: None,
+                    positional_accounts: false,
+                    roll_over: false,
+                    strict: true,
+                }
+                .data(),
+                accounts,
+            )
+        };
+
+        // the holder of the remainder and all the following users exceed the rest carryovers limit
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction =
+            Transaction::new_with_payer(&[solve_top_block(&users_info)], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6085);
+
+        let users_info = &users_info[..utils::MAX_REST_CARRYOVERS];
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction =
+            Transaction::new_with_payer(&[solve_top_block(users_info)], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        let result = banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        assert!(result.result.is_ok());
+
+        let blocks_state = account::BlocksState::try_deserialize(
+            &mut banks_client
+                .get_account(blocks_state_pda)
+                .await
+                .unwrap()
+                .unwrap()
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(
+            blocks_state.top_block_rest_carryovers.len(),
+            utils::MAX_REST_CARRYOVERS
+        );
+        let log_messages = result.metadata.unwrap().log_messages;
+        for (rest_carryover, user_info) in blocks_state
+            .top_block_rest_carryovers
+            .iter()
+            .zip(users_info)
+        {
+            assert_eq!(rest_carryover.user, user_info.user_public_key);
+            let event_data = RestCarriedOver {
+                user: rest_carryover.user,
+                block_kind: BlockKind::Top,
+                block_number: 1,
+                rest_bp: rest_carryover.rest_bp,
+                solve_batch_sequence: blocks_state.solve_batch_sequence,
+            }
+            .data();
+            let event_log = format!("Program data: {}", STANDARD.encode(event_data));
+            assert!(log_messages.contains(&event_log));
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_fail_solve_top_block() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
+
+        for _ in 0..3 {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            solve_top_block_instruction(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &key_list,
+                &users_info,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_bottom_block() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        solve_bottom_block_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+
+        for key in key_list.iter() {
+            let account = banks_client.get_account(*key).await.unwrap().unwrap();
+            let account_data = Account::unpack(&account.data).unwrap();
+            assert_eq!(account_data.amount, 1000000000000);
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_bottom_block_full_block() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
+
+        for _ in 0..2 {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            solve_bottom_block_instruction(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &key_list,
+                &users_info,
+            )
+            .await
+            .unwrap();
+        }
+
+        for key in key_list.iter() {
+            let user_account = banks_client.get_account(*key).await.unwrap();
+            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
+            assert_eq!(user_account_data.amount, 2000000000000);
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_solve_bottom_two_blocks_with_user_rest() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+        program_test.set_compute_max_units(5000000);
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let mut program_test_context = program_test.start_with_context().await;
+        let mut banks_client = program_test_context.banks_client.clone();
+        let recent_blockhash = program_test_context.last_blockhash;
+
+        let time_in_timestamp = 1677978061;
+        set_time(&mut program_test_context, time_in_timestamp).await;
+
+        initialize_instruction(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+        )
+        .await
+        .unwrap();
+
+        let payer = &program_test_context.payer;
+
+        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut key_list = vec![
+            create_token_account(&mut banks_client, payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+        ];
+
+        let mut users_info: Vec<UserInfoBottomBlock> = vec![];
+        for key in key_list.iter() {
+            users_info.push(UserInfoBottomBlock {
+                user_public_key: *key,
+                user_request_without_boost: 255,
+                user_request_with_boost: 255,
+                wallet: None,
+            });
+        }
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        solve_bottom_block_instruction(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+
+        // move time forward for 3 minutes to pass the required time between solved blocks
+        let time_in_timestamp = time_in_timestamp + 180;
+        set_time(&mut program_test_context, time_in_timestamp).await;
+
+        // the user that solved the previous block must be provided as the first one in the request to solve next block
+        // so one of ways to do this is to reuse the users provided in the first request but in the reversed order
+        key_list.reverse();
+        users_info.reverse();
+
+        let recent_blockhash = program_test_context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        solve_bottom_block_instruction(
+            &mut banks_client,
+            &program_test_context.payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
+
+        let expected_user_balances: HashMap<Pubkey, u64> =
+            HashMap::from([(key_list[0], 1173789936729), (key_list[1], 2347582599105)]);
+        for key in key_list.iter() {
+            let user_account = banks_client.get_account(*key).await.unwrap();
+            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
+            assert_eq!(user_account_data.amount, expected_user_balances[key]);
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_fail_solve_bottom_block_block() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
+
+        for _ in 0..3 {
+            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+            solve_bottom_block_instruction(
+                &mut banks_client,
+                &payer,
+                recent_blockhash,
+                &key_list,
+                &users_info,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_final_mining_fail_blocks_not_collided() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, final_mining_account_pda, _) =
+            get_pda_accounts();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let key_list = [
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+        ];
+
+        let users_info: Vec<UserInfoFinalMining> = vec![
+            UserInfoFinalMining {
+                user_public_key: key_list[0],
+                final_mining_balance: 1,
+                wallet: None,
+            },
+            UserInfoFinalMining {
+                user_public_key: key_list[1],
+                final_mining_balance: 1,
+                wallet: None,
+            },
+            UserInfoFinalMining {
+                user_public_key: key_list[2],
+                final_mining_balance: 1,
+                wallet: None,
+            },
+            UserInfoFinalMining {
+                user_public_key: key_list[3],
+                final_mining_balance: 1,
+                wallet: None,
+            },
+        ];
+
+        let data = instruction::FinalMining {
+            users_info,
+            max_valid_slot: None,
+        }
+        .data();
+
+        let accs = accounts::FinalMiningContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            final_mining_account: final_mining_account_pda,
+            mint: mint_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            global_stats_account: None,
+            final_mining_tiers_account: None,
+            final_mining_claim_period_participants_account: None,
+            system_program: None,
+            associated_token_program: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        accounts.push(AccountMeta::new(key_list[0], false));
+        accounts.push(AccountMeta::new(key_list[1], false));
+        accounts.push(AccountMeta::new(key_list[2], false));
+        accounts.push(AccountMeta::new(key_list[3], false));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_final_mining() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, final_mining_account_pda, _) =
+            get_pda_accounts();
+
+        initial_token_distribution_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            final_mining_account_pda,
+        )
+        .await
+        .unwrap();
+
+        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
+            .await
+            .unwrap();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let key_list =
+            [
+                create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                    .await
+                    .unwrap(),
+            ];
+
+        let users_info: Vec<UserInfoFinalMining> = vec![UserInfoFinalMining {
+            user_public_key: key_list[0],
+            final_mining_balance: 1,
+            wallet: None,
+        }];
+
+        let data = instruction::FinalMining {
+            users_info,
+            max_valid_slot: None,
+        }
+        .data();
+
+        let accs = accounts::FinalMiningContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            final_mining_account: final_mining_account_pda,
+            mint: mint_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            global_stats_account: None,
+            final_mining_tiers_account: None,
+            final_mining_claim_period_participants_account: None,
+            system_program: None,
+            associated_token_program: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        accounts.push(AccountMeta::new(key_list[0], false));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_final_staking_fail_blocks_not_collided() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let program_id = id();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+            get_pda_accounts();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let key_list = [
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap(),
+        ];
+
+        let users_info: Vec<UserInfoFinalStaking> = vec![
+            UserInfoFinalStaking {
+                user_public_key: key_list[0],
+                reward_part: 100_000_000,
+                wallet: None,
+            },
+            UserInfoFinalStaking {
+                user_public_key: key_list[1],
+                reward_part: 100_000_000,
+                wallet: None,
+            },
+            UserInfoFinalStaking {
+                user_public_key: key_list[2],
+                reward_part: 100_000_000,
+                wallet: None,
+            },
+            UserInfoFinalStaking {
+                user_public_key: key_list[3],
+                reward_part: 100_000_000,
+                wallet: None,
+            },
+        ];
+
+        let data = instruction::FinalStaking {
+            users_info,
+            max_valid_slot: None,
+        }
+        .data();
+
+        let accs = accounts::FinalStakingContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            final_staking_account: final_staking_account_pda,
+            mint: mint_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            final_staking_round_account: None,
+            final_staking_round_participants_account: None,
+            system_program: None,
+            associated_token_program: None,
+            state_mirror_account: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        accounts.push(AccountMeta::new(key_list[0], false));
+        accounts.push(AccountMeta::new(key_list[1], false));
+        accounts.push(AccountMeta::new(key_list[2], false));
+        accounts.push(AccountMeta::new(key_list[3], false));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_final_staking() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+            get_pda_accounts();
+
+        initial_token_distribution_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            final_staking_account_pda,
+        )
+        .await
+        .unwrap();
+
+        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
+            .await
+            .unwrap();
+
+        let program_id = id();
+
+        let token_program = spl_token::id();
+        let signer = payer.pubkey();
+
+        let key_list =
+            [
+                create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                    .await
+                    .unwrap(),
+            ];
+
+        let users_info: Vec<UserInfoFinalStaking> = vec![UserInfoFinalStaking {
+            user_public_key: key_list[0],
+            reward_part: 100_000_000,
+            wallet: None,
+        }];
+
+        let data = instruction::FinalStaking {
+            users_info,
+            max_valid_slot: None,
+        }
+        .data();
+
+        let accs = accounts::FinalStakingContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            final_staking_account: final_staking_account_pda,
+            mint: mint_pda,
+            token_program,
+            signer,
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            final_staking_round_account: None,
+            final_staking_round_participants_account: None,
+            system_program: None,
+            associated_token_program: None,
+            state_mirror_account: None,
+        };
+
+        let mut accounts = accs.to_account_metas(Some(false));
+        accounts.push(AccountMeta::new(key_list[0], false));
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Confirmed)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    async fn final_staking_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        users_info: Vec<UserInfoFinalStaking>,
+        remaining_accounts: &[AccountMeta],
+    ) -> BanksTransactionResultWithMetadata {
+        let program_id = id();
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+            get_pda_accounts();
+
+        let data = instruction::FinalStaking {
+            users_info,
+            max_valid_slot: None,
+        }
+        .data();
+        let accs = accounts::FinalStakingContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            final_staking_account: final_staking_account_pda,
+            mint: mint_pda,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            final_staking_round_account: None,
+            final_staking_round_participants_account: None,
+            system_program: None,
+            associated_token_program: None,
+            state_mirror_account: None,
+        };
+        let mut accounts = accs.to_account_metas(Some(false));
+        accounts.extend_from_slice(remaining_accounts);
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap()
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_final_staking_restakes_rewards_into_stake_vault() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+            get_pda_accounts();
+
+        initial_token_distribution_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            final_staking_account_pda,
+        )
+        .await
+        .unwrap();
+
+        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
+            .await
+            .unwrap();
+
+        let user = create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+            .await
+            .unwrap();
+        let user_info = UserInfoFinalStaking {
+            user_public_key: user,
+            reward_part: 100_000_000,
+            wallet: None,
+        };
+        let token_balance =
+            |account: solana_sdk::account::Account| Account::unpack(&account.data).unwrap().amount;
+
+        // the first reward is transferred to the user's token account, so the user has tokens to lock
+        let result = final_staking_instruction(
+            &mut banks_client,
+            &payer,
+            vec![user_info.clone()],
+            &[AccountMeta::new(user, false)],
+        )
+        .await;
+        result.result.unwrap();
+        let user_balance = token_balance(banks_client.get_account(user).await.unwrap().unwrap());
+        assert!(user_balance > 0);
+
+        let (stake_lock_pda, _) =
+            Pubkey::find_program_address(&[STAKE_LOCK_SEED.as_bytes(), user.as_ref()], &program_id);
+        let (stake_vault_pda, _) = Pubkey::find_program_address(
+            &[STAKE_VAULT_SEED.as_bytes(), user.as_ref()],
+            &program_id,
+        );
+        let locked_amount = user_balance / 2;
+        let lock_stake = Instruction::new_with_bytes(
+            program_id,
+            &instruction::LockStake {
+                amount: locked_amount,
+            }
+            .data(),
+            accounts::LockStakeContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                mint: mint_pda,
+                user_token_account: user,
+                stake_lock_account: stake_lock_pda,
+                stake_vault_account: stake_vault_pda,
+                signer: payer.pubkey(),
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(Some(false)),
+        );
+        let set_auto_restake = Instruction::new_with_bytes(
+            program_id,
+            &instruction::SetFinalStakingAutoRestake { auto_restake: true }.data(),
+            accounts::SetFinalStakingAutoRestakeContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                user_token_account: user,
+                stake_lock_account: stake_lock_pda,
+                signer: payer.pubkey(),
+            }
+            .to_account_metas(Some(false)),
+        );
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction =
+            Transaction::new_with_payer(&[lock_stake, set_auto_restake], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        // the next reward of the round is deposited to the stake vault and locked
+        let result = final_staking_instruction(
+            &mut banks_client,
+            &payer,
+            vec![user_info],
+            &[
+                AccountMeta::new(user, false),
+                AccountMeta::new(stake_lock_pda, false),
+                AccountMeta::new(stake_vault_pda, false),
+            ],
+        )
+        .await;
+        result.result.unwrap();
+
+        let stake_lock = StakeLock::try_deserialize(
+            &mut banks_client
+                .get_account(stake_lock_pda)
+                .await
+                .unwrap()
+                .unwrap()
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+        let restaked_amount = stake_lock.locked_amount - locked_amount;
+        assert!(restaked_amount > 0);
+        assert_eq!(
+            token_balance(
+                banks_client
+                    .get_account(stake_vault_pda)
+                    .await
+                    .unwrap()
+                    .unwrap()
+            ),
+            stake_lock.locked_amount
+        );
+        assert_eq!(
+            token_balance(banks_client.get_account(user).await.unwrap().unwrap()),
+            user_balance - locked_amount
+        );
+
+        let event_data = FinalStakingRewardRestaked {
+            user,
+            round_index: 1,
+            amount: restaked_amount,
+            locked_amount: stake_lock.locked_amount,
+        }
+        .data();
+        let event_log = format!("Program data: {}", STANDARD.encode(event_data));
+        assert!(result.metadata.unwrap().log_messages.contains(&event_log));
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_registered_stake_cannot_be_registered_again() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+            get_pda_accounts();
+
+        initial_token_distribution_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            final_staking_account_pda,
+        )
+        .await
+        .unwrap();
+
+        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
+            .await
+            .unwrap();
+
+        let user = create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+            .await
+            .unwrap();
+        let user_info = UserInfoFinalStaking {
+            user_public_key: user,
+            reward_part: 100_000_000,
+            wallet: None,
+        };
+
+        // the reward is transferred to the user's token account, so the user has tokens to lock
+        let result = final_staking_instruction(
+            &mut banks_client,
+            &payer,
+            vec![user_info],
+            &[AccountMeta::new(user, false)],
+        )
+        .await;
+        result.result.unwrap();
+        let user_balance =
+            Account::unpack(&banks_client.get_account(user).await.unwrap().unwrap().data)
+                .unwrap()
+                .amount;
+
+        let (stake_lock_pda, _) =
+            Pubkey::find_program_address(&[STAKE_LOCK_SEED.as_bytes(), user.as_ref()], &program_id);
+        let (stake_vault_pda, _) = Pubkey::find_program_address(
+            &[STAKE_VAULT_SEED.as_bytes(), user.as_ref()],
+            &program_id,
+        );
+        let (final_staking_registration_pda, _) = Pubkey::find_program_address(
+            &[FINAL_STAKING_REGISTRATION_SEED.as_bytes(), user.as_ref()],
+            &program_id,
+        );
+        let lock_stake = Instruction::new_with_bytes(
+            program_id,
+            &instruction::LockStake {
+                amount: user_balance,
+            }
+            .data(),
+            accounts::LockStakeContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                mint: mint_pda,
+                user_token_account: user,
+                stake_lock_account: stake_lock_pda,
+                stake_vault_account: stake_vault_pda,
+                signer: payer.pubkey(),
+                token_program: spl_token::id(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(Some(false)),
+        );
+        let register_for_final_staking = Instruction::new_with_bytes(
+            program_id,
+            &instruction::RegisterForFinalStaking {}.data(),
+            accounts::RegisterForFinalStakingContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                mint: mint_pda,
+                user_token_account: user,
+                stake_lock_account: stake_lock_pda,
+                final_staking_registration_account: final_staking_registration_pda,
+                final_staking_tiers_account: None,
+                signer: payer.pubkey(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(Some(false)),
+        );
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[lock_stake, register_for_final_staking],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let registration = FinalStakingRegistration::try_deserialize(
+            &mut banks_client
+                .get_account(final_staking_registration_pda)
+                .await
+                .unwrap()
+                .unwrap()
+                .data
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(registration.round_index, 2);
+        assert_eq!(registration.balance, user_balance);
+
+        // the registered tokens cannot be unlocked and registered by another token account for the same round
+        let unlock_stake = Instruction::new_with_bytes(
+            program_id,
+            &instruction::UnlockStake {
+                amount: user_balance,
+            }
+            .data(),
+            accounts::UnlockStakeContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                mint: mint_pda,
+                user_token_account: user,
+                stake_lock_account: stake_lock_pda,
+                stake_vault_account: stake_vault_pda,
+                signer: payer.pubkey(),
+                token_program: spl_token::id(),
+            }
+            .to_account_metas(Some(false)),
+        );
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(&[unlock_stake], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6139);
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn test_fail_final_staking_required_interval_elapsed_without_context() {
+        final_staking_required_interval_elapsed(&1, &utils::DEFAULT_FINAL_STAKING_INTERVAL_SECONDS)
+            .unwrap();
+    }
 
     #[cfg(feature = "bpf-tests")]
-    use std::collections::HashMap;
+    #[tokio::test]
+    async fn test_new_authority() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+        program_test.set_compute_max_units(500000);
 
-    #[cfg(feature = "bpf-tests")]
-    use solana_sdk::transaction::TransactionError;
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
 
-    impl Clone for UserInfoBottomBlock {
-        fn clone(&self) -> Self {
-            Self {
-                user_public_key: self.user_public_key.clone(),
-                user_balance: self.user_balance.clone(),
-                user_request_without_boost: self.user_request_without_boost.clone(),
-                user_request_with_boost: self.user_request_with_boost.clone(),
-            }
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        let signer = payer.pubkey();
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let data = instruction::ChangeAuthority {
+            new_authority: signer,
         }
+        .data();
+
+        let accs = accounts::ChangeAuthorityContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer,
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
     }
 
-    impl Clone for UserInfoTopBlock {
-        fn clone(&self) -> Self {
-            Self {
-                user_public_key: self.user_public_key.clone(),
-                user_request_without_boost: self.user_request_without_boost.clone(),
-                user_request_with_boost: self.user_request_with_boost.clone(),
-            }
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_new_authority_with_wrong_signer() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+        program_test.set_compute_max_units(500000);
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        let signer = payer.pubkey();
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let data = instruction::ChangeAuthority {
+            new_authority: signer,
         }
+        .data();
+
+        let sub_signer = Keypair::new();
+        let accs = accounts::ChangeAuthorityContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer: sub_signer.pubkey(),
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[&payer, &sub_signer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6000);
     }
 
-    async fn initialize_instruction(
+    #[cfg(feature = "bpf-tests")]
+    async fn set_min_payout_amount_instruction(
         banks_client: &mut BanksClient,
         payer: &Keypair,
         recent_blockhash: Hash,
-    ) -> Result<()> {
+        min_payout_amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
         let program_id = id();
-        let (
-            mint_pda,
-            _,
-            blocks_state_pda,
-            _,
-            distribution_top_block_pda,
-            _,
-            distribution_bottom_block_pda,
-            _,
-            final_staking_account_pda,
-            _,
-            final_mining_account_pda,
-            _,
-        ) = get_pda_accounts();
-        let metadata_seed1 = "metadata".as_bytes();
-        let metadata_seed2 = &mpl_token_metadata::id().to_bytes();
-        let metadata_seed3 = &mint_pda.to_bytes();
-        let (metadata_pda, _) = Pubkey::find_program_address(
-            &[metadata_seed1, metadata_seed2, metadata_seed3],
-            &mpl_token_metadata::id(),
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+
+        let data = instruction::SetMinPayoutAmount { min_payout_amount }.data();
+
+        let accs = accounts::SetMinPayoutAmountContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer: payer.pubkey(),
+        };
+
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
         );
 
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
-        let token_metadata_name = "Sallar".to_string();
-        let token_metadata_symbol = "ALL".to_string();
-        let token_metadata_uri = "http://sallar.io".to_string();
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
+            .await
+    }
 
-        let data = instruction::Initialize {
-            token_metadata_name,
-            token_metadata_symbol,
-            token_metadata_uri,
-        }
-        .data();
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_set_min_payout_amount() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        let accs = accounts::InitializeContext {
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        set_min_payout_amount_instruction(&mut banks_client, &payer, recent_blockhash, 100_000)
+            .await
+            .unwrap();
+
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let blocks_state_account = banks_client
+            .get_account(blocks_state_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let blocks_state =
+            account::BlocksState::try_deserialize(&mut blocks_state_account.data.as_slice())
+                .unwrap();
+        assert_eq!(blocks_state.min_payout_amount, 100_000);
+    }
+
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_set_treasury() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let treasury_token_account =
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap();
+
+        let data = instruction::SetTreasury {}.data();
+
+        let accs = accounts::SetTreasuryContext {
             blocks_state_account: blocks_state_pda,
-            token_program,
-            signer,
-            system_program: system_program::ID,
+            config_account: get_config_pda(),
             mint: mint_pda,
-            distribution_top_block_account: distribution_top_block_pda,
-            distribution_bottom_block_account: distribution_bottom_block_pda,
-            final_staking_account: final_staking_account_pda,
-            final_mining_account: final_mining_account_pda,
-            metadata_pda,
-            metadata_program: mpl_token_metadata::id(),
+            treasury_token_account,
+            signer: payer.pubkey(),
         };
 
         let mut transaction = Transaction::new_with_payer(
@@ -823,36 +6822,42 @@ mod test {
             Some(&payer.pubkey()),
         );
 
-        transaction.sign(&[payer], recent_blockhash);
+        transaction.sign(&[&payer], recent_blockhash);
         banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Confirmed)
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
 
-        Ok(())
+        let blocks_state_account = banks_client
+            .get_account(blocks_state_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let blocks_state =
+            account::BlocksState::try_deserialize(&mut blocks_state_account.data.as_slice())
+                .unwrap();
+        assert_eq!(blocks_state.treasury_address, Some(treasury_token_account));
     }
 
     #[cfg(feature = "bpf-tests")]
-    async fn initial_token_distribution_instruction(
+    async fn set_paused_instruction(
         banks_client: &mut BanksClient,
         payer: &Keypair,
         recent_blockhash: Hash,
-        organization_account: Pubkey,
+        paused: bool,
     ) -> Result<()> {
         let program_id = id();
-        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
-
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
 
-        let data = instruction::InitialTokenDistribution {}.data();
+        let data = match paused {
+            true => instruction::Pause {}.data(),
+            false => instruction::Unpause {}.data(),
+        };
 
-        let accs = accounts::InitialTokenDistributionContext {
+        let accs = accounts::SetPausedContext {
             blocks_state_account: blocks_state_pda,
-            mint: mint_pda,
-            organization_account,
-            token_program,
-            signer,
+            config_account: get_config_pda(),
+            signer: payer.pubkey(),
         };
 
         let mut transaction = Transaction::new_with_payer(
@@ -866,7 +6871,7 @@ mod test {
 
         transaction.sign(&[payer], recent_blockhash);
         banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
 
@@ -875,28 +6880,95 @@ mod test {
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_initialize() {
+    async fn test_pause_and_unpause() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
-
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        set_paused_instruction(&mut banks_client, &payer, recent_blockhash, true)
+            .await
+            .unwrap();
+
+        let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
+            get_pda_accounts();
+        let data = instruction::SolveTopBlock {
+            users_info: users_info.clone(),
+            max_valid_slot: None,
+            chunk: None,
+            positional_accounts: false,
+            roll_over: false,
+            strict: true,
+        }
+        .data();
+        let accs = accounts::SolveTopBlockContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
+        };
+        let mut accounts = accs.to_account_metas(Some(false));
+        for key in key_list.iter() {
+            accounts.push(AccountMeta::new(*key, false));
+        }
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6031);
+
+        set_paused_instruction(&mut banks_client, &payer, recent_blockhash, false)
             .await
             .unwrap();
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        solve_top_block_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_initial_token_distribution() {
+    async fn test_denylisted_recipient_rejected() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -905,373 +6977,365 @@ mod test {
             .await
             .unwrap();
 
-        let (mint_pda, _) = Pubkey::find_program_address(&[MINT_SEED.as_bytes()], &program_id);
-        let organization_account =
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap();
-        initial_token_distribution_instruction(
-            &mut banks_client,
-            &payer,
-            recent_blockhash,
-            organization_account,
-        )
-        .await
-        .unwrap();
-    }
-
-    async fn solve_top_block_instruction(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-        recent_blockhash: Hash,
-        key_list: &Vec<Pubkey>,
-        users_info: &Vec<UserInfoTopBlock>,
-    ) -> Result<()> {
-        let program_id = id();
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
 
         let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
             get_pda_accounts();
+        let (denylist_pda, _) =
+            Pubkey::find_program_address(&[DENYLIST_SEED.as_bytes()], &program_id);
+
+        let initialize_denylist = Instruction::new_with_bytes(
+            program_id,
+            &instruction::InitializeDenylist {}.data(),
+            accounts::InitializeDenylistContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                denylist_account: denylist_pda,
+                signer: payer.pubkey(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(Some(false)),
+        );
+        let add_to_denylist = Instruction::new_with_bytes(
+            program_id,
+            &instruction::AddToDenylist {
+                token_account: key_list[0],
+            }
+            .data(),
+            accounts::UpdateDenylistContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                denylist_account: denylist_pda,
+                signer: payer.pubkey(),
+            }
+            .to_account_metas(Some(false)),
+        );
 
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(
+            &[initialize_denylist, add_to_denylist],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
 
         let data = instruction::SolveTopBlock {
             users_info: users_info.clone(),
+            max_valid_slot: None,
+            chunk: None,
+            positional_accounts: false,
+            roll_over: false,
+            strict: true,
         }
         .data();
-
         let accs = accounts::SolveTopBlockContext {
             blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
             mint: mint_pda,
             distribution_top_block_account: distribution_top_block_pda,
-            token_program,
-            signer,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+            signer_set_account: None,
+            denylist_account: Some(denylist_pda),
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
         };
-
         let mut accounts = accs.to_account_metas(Some(false));
         for key in key_list.iter() {
             accounts.push(AccountMeta::new(*key, false));
         }
-
         let mut transaction = Transaction::new_with_payer(
             &[Instruction::new_with_bytes(program_id, &data, accounts)],
             Some(&payer.pubkey()),
         );
-
-        transaction.sign(&[payer], recent_blockhash);
-        banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
             .await
+            .unwrap_err()
             .unwrap();
-
-        Ok(())
+        assert_eq!(get_custom_error_code(error).unwrap(), 6046);
     }
 
-    async fn solve_bottom_block_instruction(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-        recent_blockhash: Hash,
-        key_list: &Vec<Pubkey>,
-        users_info: &Vec<UserInfoBottomBlock>,
-    ) -> Result<()> {
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_omitted_denylist_rejected() {
         let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        let (mint_pda, _, blocks_state_pda, _, _, _, distribution_bottom_block_pda, _, _, _, _, _) =
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
+
+        let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
             get_pda_accounts();
+        let (denylist_pda, _) =
+            Pubkey::find_program_address(&[DENYLIST_SEED.as_bytes()], &program_id);
+
+        let initialize_denylist = Instruction::new_with_bytes(
+            program_id,
+            &instruction::InitializeDenylist {}.data(),
+            accounts::InitializeDenylistContext {
+                blocks_state_account: blocks_state_pda,
+                config_account: get_config_pda(),
+                denylist_account: denylist_pda,
+                signer: payer.pubkey(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(Some(false)),
+        );
 
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction =
+            Transaction::new_with_payer(&[initialize_denylist], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
 
-        let data = instruction::SolveBottomBlock {
+        // the recipients are not denylisted, but the denylist must be provided once it is created
+        let data = instruction::SolveTopBlock {
             users_info: users_info.clone(),
+            max_valid_slot: None,
+            chunk: None,
+            positional_accounts: false,
+            roll_over: false,
+            strict: true,
         }
         .data();
-
-        let accs = accounts::SolveBottomBlockContext {
+        let accs = accounts::SolveTopBlockContext {
             blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
             mint: mint_pda,
-            distribution_bottom_block_account: distribution_bottom_block_pda,
-            token_program,
-            signer,
+            distribution_top_block_account: distribution_top_block_pda,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+            signer_set_account: None,
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
         };
-
         let mut accounts = accs.to_account_metas(Some(false));
-        for key in key_list.into_iter() {
+        for key in key_list.iter() {
             accounts.push(AccountMeta::new(*key, false));
         }
-
         let mut transaction = Transaction::new_with_payer(
             &[Instruction::new_with_bytes(program_id, &data, accounts)],
             Some(&payer.pubkey()),
         );
-
-        transaction.sign(&[payer], recent_blockhash);
-        banks_client
-            .process_transaction_with_commitment(transaction, CommitmentLevel::Finalized)
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
             .await
+            .unwrap_err()
             .unwrap();
-
-        Ok(())
+        assert_eq!(get_custom_error_code(error).unwrap(), 6138);
     }
 
     #[cfg(feature = "bpf-tests")]
-    async fn set_blocks_collided_instruction(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-        recent_blockhash: Hash,
-        collided: bool,
-    ) -> Result<()> {
+    #[tokio::test]
+    async fn test_renounce_admin_controls() {
         let program_id = id();
-        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        let signer = payer.pubkey();
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
 
-        let data = instruction::SetBlocksCollided { collided }.data();
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-        let accs = accounts::SetBlocksCollidedContext {
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let data = instruction::RenounceAdminControls {}.data();
+        let accs = accounts::RenounceAdminControlsContext {
             blocks_state_account: blocks_state_pda,
-            signer,
+            config_account: get_config_pda(),
+            signer: payer.pubkey(),
         };
-
-        let accounts = accs.to_account_metas(Some(false));
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
             Some(&payer.pubkey()),
         );
-
-        transaction.sign(&[payer], recent_blockhash);
-
+        transaction.sign(&[&payer], recent_blockhash);
         banks_client
             .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
-
-        Ok(())
-    }
-
-    async fn default_top_block_setup(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-    ) -> (Vec<Pubkey>, Vec<UserInfoTopBlock>) {
-        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
-
-        let mut key_list = vec![];
-
-        for _ in 0..5 {
-            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-            key_list.push(
-                create_token_account(banks_client, payer, recent_blockhash, mint_pda)
-                    .await
-                    .unwrap(),
-            );
-        }
-
-        let mut users_info: Vec<UserInfoTopBlock> = vec![];
-
-        for key in key_list.iter() {
-            let user_info = UserInfoTopBlock {
-                user_public_key: *key,
-                user_request_with_boost: 1,
-                user_request_without_boost: 1,
-            };
-            users_info.push(user_info);
-        }
-
-        (key_list, users_info)
-    }
-
-    async fn default_bottom_block_setup(
-        banks_client: &mut BanksClient,
-        payer: &Keypair,
-    ) -> (Vec<Pubkey>, Vec<UserInfoBottomBlock>) {
-        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
-
-        let mut key_list: Vec<Pubkey> = vec![];
-        for _ in 0..1 {
-            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-            key_list.push(
-                create_token_account(banks_client, payer, recent_blockhash, mint_pda)
-                    .await
-                    .unwrap(),
-            );
-        }
-
-        let mut users_info: Vec<UserInfoBottomBlock> = vec![];
-        for key in key_list.iter() {
-            users_info.push(UserInfoBottomBlock {
-                user_public_key: key.clone(),
-                user_balance: 107_753_703_900_000_000,
-                user_request_without_boost: 25,
-                user_request_with_boost: 0,
-            });
-        }
-
-        (key_list, users_info)
-    }
-
-    #[cfg(feature = "bpf-tests")]
-    #[tokio::test]
-    async fn test_solve_top_block_full_block() {
-        let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
-
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
-        program_test.prefer_bpf(true);
-
-        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
-            .await
-            .unwrap();
-
-        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
-
-        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-        solve_top_block_instruction(
-            &mut banks_client,
-            &payer,
-            recent_blockhash,
-            &key_list,
-            &users_info,
-        )
-        .await
-        .unwrap();
-        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-        solve_top_block_instruction(
-            &mut banks_client,
-            &payer,
-            recent_blockhash,
-            &key_list,
-            &users_info,
-        )
-        .await
-        .unwrap();
-
-        for key in key_list.iter() {
-            let account = banks_client.get_account(*key).await.unwrap().unwrap();
-            let account_data = Account::unpack(&account.data).unwrap();
-            assert_eq!(account_data.amount, 400000000000);
-        }
+
+        let error =
+            set_min_payout_amount_instruction(&mut banks_client, &payer, recent_blockhash, 100_000)
+                .await
+                .unwrap_err()
+                .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6032);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_solve_top_two_blocks_with_user_rest() {
+    async fn test_timelocked_min_payout_amount_change() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
-        program_test.set_compute_max_units(5000000);
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let mut program_test_context = program_test.start_with_context().await;
         let mut banks_client = program_test_context.banks_client.clone();
         let recent_blockhash = program_test_context.last_blockhash;
+        let payer = Keypair::from_bytes(&program_test_context.payer.to_bytes()).unwrap();
 
-        let mut time_in_timestamp = 1677978061;
+        let time_in_timestamp = 1677978061;
         set_time(&mut program_test_context, time_in_timestamp).await;
 
-        initialize_instruction(
-            &mut banks_client,
-            &program_test_context.payer,
-            recent_blockhash,
-        )
-        .await
-        .unwrap();
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
 
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let (pending_change_pda, _) =
+            Pubkey::find_program_address(&[PENDING_CHANGE_SEED.as_bytes()], &program_id);
         let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
 
-        let key_list = vec![create_token_account(
-            &mut banks_client,
-            &program_test_context.payer,
-            recent_blockhash,
-            mint_pda,
-        )
-        .await
-        .unwrap()];
-        let users_info: Vec<UserInfoTopBlock> = vec![UserInfoTopBlock {
-            user_public_key: key_list[0].clone(),
-            user_request_without_boost: 50,
-            user_request_with_boost: 0,
-        }];
+        let data = instruction::SetTimelockDelay {
+            timelock_delay: 86_400,
+        }
+        .data();
+        let accs = accounts::SetTimelockDelayContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer: payer.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
+            .await
+            .unwrap();
 
-        for _ in 0..2 {
-            let recent_blockhash = program_test_context
-                .banks_client
-                .get_latest_blockhash()
+        let error =
+            set_min_payout_amount_instruction(&mut banks_client, &payer, recent_blockhash, 100_000)
                 .await
+                .unwrap_err()
                 .unwrap();
-            solve_top_block_instruction(
-                &mut banks_client,
-                &program_test_context.payer,
-                recent_blockhash,
-                &key_list,
-                &users_info,
-            )
+        assert_eq!(get_custom_error_code(error).unwrap(), 6033);
+
+        let data = instruction::QueuePendingChange {
+            change: ConfigChange::SetMinPayoutAmount {
+                min_payout_amount: 100_000,
+            },
+        }
+        .data();
+        let accs = accounts::QueuePendingChangeContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            pending_change_account: pending_change_pda,
+            signer: payer.pubkey(),
+            system_program: system_program::ID,
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
 
-            // move time forward for 3 minutes to pass the required time between solved blocks
-            time_in_timestamp = time_in_timestamp + 180;
-            set_time(&mut program_test_context, time_in_timestamp).await;
-        }
+        let data = instruction::ExecutePendingChange {}.data();
+        let accs = accounts::ExecutePendingChangeContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            pending_change_account: pending_change_pda,
+            mint: mint_pda,
+            treasury_token_account: None,
+            signer_set_account: None,
+            final_mining_tiers_account: None,
+            signer: payer.pubkey(),
+        };
+        let instruction =
+            Instruction::new_with_bytes(program_id, &data, accs.to_account_metas(Some(false)));
 
-        let key_list = vec![
-            key_list[0],
-            create_token_account(
-                &mut banks_client,
-                &program_test_context.payer,
-                recent_blockhash,
-                mint_pda,
-            )
+        let mut transaction =
+            Transaction::new_with_payer(std::slice::from_ref(&instruction), Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
             .await
-            .unwrap(),
-        ];
-        let users_info: Vec<UserInfoTopBlock> = vec![
-            UserInfoTopBlock {
-                user_public_key: key_list[0].clone(),
-                user_request_without_boost: 0,
-                user_request_with_boost: 0,
-            },
-            UserInfoTopBlock {
-                user_public_key: key_list[1].clone(),
-                user_request_without_boost: 7,
-                user_request_with_boost: 0,
-            },
-        ];
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6036);
 
-        let recent_blockhash = program_test_context
-            .banks_client
-            .get_latest_blockhash()
+        // move time forward for 1 day to pass the timelock delay
+        set_time(&mut program_test_context, time_in_timestamp + 86_400).await;
+
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
-        solve_top_block_instruction(
-            &mut banks_client,
-            &program_test_context.payer,
-            recent_blockhash,
-            &key_list,
-            &users_info,
-        )
-        .await
-        .unwrap();
 
-        let expected_user_balances: HashMap<Pubkey, u64> =
-            HashMap::from([(key_list[0], 5000000000000), (key_list[1], 700000000000)]);
-        for key in key_list.iter() {
-            let user_account = (&mut banks_client).get_account(*key).await.unwrap();
-            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
-            assert_eq!(user_account_data.amount, expected_user_balances[key]);
-        }
+        let blocks_state_account = banks_client
+            .get_account(blocks_state_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let blocks_state =
+            account::BlocksState::try_deserialize(&mut blocks_state_account.data.as_slice())
+                .unwrap();
+        assert_eq!(blocks_state.min_payout_amount, 100_000);
+        assert!(banks_client
+            .get_account(pending_change_pda)
+            .await
+            .unwrap()
+            .is_none());
     }
 
+    #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    #[should_panic]
-    async fn test_fail_solve_top_block() {
+    async fn test_finalize_mint_fail_blocks_not_collided() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1280,29 +7344,55 @@ mod test {
             .await
             .unwrap();
 
-        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
+        let (
+            mint_pda,
+            _,
+            blocks_state_pda,
+            _,
+            distribution_top_block_pda,
+            _,
+            distribution_bottom_block_pda,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = get_pda_accounts();
 
-        for _ in 0..3 {
-            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-            solve_top_block_instruction(
-                &mut banks_client,
-                &payer,
-                recent_blockhash,
-                &key_list,
-                &users_info,
-            )
+        let data = instruction::FinalizeMint {}.data();
+        let accs = accounts::FinalizeMintContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            distribution_bottom_block_account: distribution_bottom_block_pda,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
             .await
+            .unwrap_err()
             .unwrap();
-        }
+        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_solve_bottom_block() {
+    async fn test_close_program_accounts_fail_blocks_not_collided() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1311,33 +7401,114 @@ mod test {
             .await
             .unwrap();
 
-        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
+        let (
+            _,
+            _,
+            blocks_state_pda,
+            _,
+            distribution_top_block_pda,
+            _,
+            distribution_bottom_block_pda,
+            _,
+            final_staking_pda,
+            _,
+            final_mining_pda,
+            _,
+        ) = get_pda_accounts();
 
-        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-        solve_bottom_block_instruction(
-            &mut banks_client,
-            &payer,
-            recent_blockhash,
-            &key_list,
-            &users_info,
-        )
-        .await
-        .unwrap();
+        let data = instruction::CloseProgramAccounts {}.data();
+        let accs = accounts::CloseProgramAccountsContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            distribution_top_block_account: distribution_top_block_pda,
+            distribution_bottom_block_account: distribution_bottom_block_pda,
+            final_staking_account: final_staking_pda,
+            final_mining_account: final_mining_pda,
+            recipient: payer.pubkey(),
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
+    }
 
-        for key in key_list.iter() {
-            let account = banks_client.get_account(*key).await.unwrap().unwrap();
-            let account_data = Account::unpack(&account.data).unwrap();
-            assert_eq!(account_data.amount, 1000000000000);
-        }
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_recover_foreign_tokens_fail_program_token_account() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
+
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
+
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
+
+        let (
+            mint_pda,
+            _,
+            blocks_state_pda,
+            _,
+            distribution_top_block_pda,
+            _,
+            distribution_bottom_block_pda,
+            _,
+            _,
+            _,
+            _,
+            _,
+        ) = get_pda_accounts();
+
+        let data = instruction::RecoverForeignTokens { amount: 1 }.data();
+        let accs = accounts::RecoverForeignTokensContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            source_token_account: distribution_top_block_pda,
+            mint: mint_pda,
+            destination_token_account: distribution_bottom_block_pda,
+            token_program: spl_token::id(),
+            signer: payer.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        let error = banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6051);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_solve_bottom_block_full_block() {
+    async fn test_export_regulatory_report() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1346,128 +7517,79 @@ mod test {
             .await
             .unwrap();
 
-        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
-
-        for _ in 0..2 {
-            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-            solve_bottom_block_instruction(
-                &mut banks_client,
-                &payer,
-                recent_blockhash,
-                &key_list,
-                &users_info,
-            )
-            .await
-            .unwrap();
-        }
+        let (
+            mint_pda,
+            _,
+            blocks_state_pda,
+            _,
+            distribution_top_block_pda,
+            _,
+            distribution_bottom_block_pda,
+            _,
+            final_staking_pda,
+            _,
+            final_mining_pda,
+            _,
+        ) = get_pda_accounts();
 
-        for key in key_list.iter() {
-            let user_account = (&mut banks_client).get_account(*key).await.unwrap();
-            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
-            assert_eq!(user_account_data.amount, 2000000000000);
-        }
+        let data = instruction::ExportRegulatoryReport {}.data();
+        let accs = accounts::ExportRegulatoryReportContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            distribution_bottom_block_account: distribution_bottom_block_pda,
+            final_staking_account: final_staking_pda,
+            final_mining_account: final_mining_pda,
+            treasury_token_account: None,
+            signer: payer.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_solve_bottom_two_blocks_with_user_rest() {
+    async fn test_set_min_payout_amount_too_high() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
-        program_test.set_compute_max_units(5000000);
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
-        let mut program_test_context = program_test.start_with_context().await;
-        let mut banks_client = program_test_context.banks_client.clone();
-        let recent_blockhash = program_test_context.last_blockhash;
-
-        let time_in_timestamp = 1677978061;
-        set_time(&mut program_test_context, time_in_timestamp).await;
-
-        initialize_instruction(
-            &mut banks_client,
-            &program_test_context.payer,
-            recent_blockhash,
-        )
-        .await
-        .unwrap();
-
-        let payer = &program_test_context.payer;
-
-        let (mint_pda, _, _, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
-
-        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-        let mut key_list = vec![
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-        ];
-
-        let mut users_info: Vec<UserInfoBottomBlock> = vec![];
-        for key in key_list.iter() {
-            users_info.push(UserInfoBottomBlock {
-                user_public_key: key.clone(),
-                user_balance: 200_000_000_000_000,
-                user_request_without_boost: 255,
-                user_request_with_boost: 255,
-            });
-        }
-
-        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-        solve_bottom_block_instruction(
-            &mut banks_client,
-            &program_test_context.payer,
-            recent_blockhash,
-            &key_list,
-            &users_info,
-        )
-        .await
-        .unwrap();
-
-        // move time forward for 3 minutes to pass the required time between solved blocks
-        let time_in_timestamp = time_in_timestamp + 180;
-        set_time(&mut program_test_context, time_in_timestamp).await;
-
-        // the user that solved the previous block must be provided as the first one in the request to solve next block
-        // so one of ways to do this is to reuse the users provided in the first request but in the reversed order
-        key_list.reverse();
-        users_info.reverse();
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-        let recent_blockhash = program_test_context
-            .banks_client
-            .get_latest_blockhash()
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
             .await
             .unwrap();
-        solve_bottom_block_instruction(
+
+        let error = set_min_payout_amount_instruction(
             &mut banks_client,
-            &program_test_context.payer,
+            &payer,
             recent_blockhash,
-            &key_list,
-            &users_info,
+            100_000_001,
         )
         .await
+        .unwrap_err()
         .unwrap();
-
-        let expected_user_balances: HashMap<Pubkey, u64> =
-            HashMap::from([(key_list[0], 1173789936729), (key_list[1], 2347582599105)]);
-        for key in key_list.iter() {
-            let user_account = (&mut banks_client).get_account(*key).await.unwrap();
-            let user_account_data = Account::unpack(&user_account.unwrap().data).unwrap();
-            assert_eq!(user_account_data.amount, expected_user_balances[key]);
-        }
+        assert_eq!(get_custom_error_code(error).unwrap(), 6023);
     }
 
+    #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    #[should_panic]
-    async fn test_fail_solve_bottom_block_block() {
+    async fn test_set_reward_fee() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1476,29 +7598,55 @@ mod test {
             .await
             .unwrap();
 
-        let (key_list, users_info) = default_bottom_block_setup(&mut banks_client, &payer).await;
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let set_reward_fee = |reward_fee_bps: u16| {
+            let mut transaction = Transaction::new_with_payer(
+                &[Instruction::new_with_bytes(
+                    program_id,
+                    &instruction::SetRewardFee { reward_fee_bps }.data(),
+                    accounts::SetRewardFeeContext {
+                        blocks_state_account: blocks_state_pda,
+                        config_account: get_config_pda(),
+                        signer: payer.pubkey(),
+                    }
+                    .to_account_metas(Some(false)),
+                )],
+                Some(&payer.pubkey()),
+            );
+            transaction.sign(&[&payer], recent_blockhash);
+            transaction
+        };
 
-        for _ in 0..3 {
-            let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
-            solve_bottom_block_instruction(
-                &mut banks_client,
-                &payer,
-                recent_blockhash,
-                &key_list,
-                &users_info,
-            )
+        let error = banks_client
+            .process_transaction(set_reward_fee(501))
             .await
+            .unwrap_err()
             .unwrap();
-        }
+        assert_eq!(get_custom_error_code(error).unwrap(), 6049);
+
+        banks_client
+            .process_transaction(set_reward_fee(100))
+            .await
+            .unwrap();
+
+        let blocks_state_account = banks_client
+            .get_account(blocks_state_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let blocks_state =
+            account::BlocksState::try_deserialize(&mut blocks_state_account.data.as_slice())
+                .unwrap();
+        assert_eq!(blocks_state.reward_fee_bps, 100);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_final_mining_fail_blocks_not_collided() {
+    async fn test_set_distribution_backend() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1507,238 +7655,246 @@ mod test {
             .await
             .unwrap();
 
-        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, final_mining_account_pda, _) =
-            get_pda_accounts();
-
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
-
-        let key_list = vec![
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-        ];
-
-        let users_info: Vec<UserInfoFinalMining> = vec![
-            UserInfoFinalMining {
-                user_public_key: key_list[0],
-                final_mining_balance: 1,
-            },
-            UserInfoFinalMining {
-                user_public_key: key_list[1],
-                final_mining_balance: 1,
-            },
-            UserInfoFinalMining {
-                user_public_key: key_list[2],
-                final_mining_balance: 1,
-            },
-            UserInfoFinalMining {
-                user_public_key: key_list[3],
-                final_mining_balance: 1,
-            },
-        ];
-
-        let data = instruction::FinalMining { users_info }.data();
-
-        let accs = accounts::FinalMiningContext {
-            blocks_state_account: blocks_state_pda,
-            final_mining_account: final_mining_account_pda,
-            token_program,
-            signer,
-        };
-
-        let mut accounts = accs.to_account_metas(Some(false));
-        accounts.push(AccountMeta::new(key_list[0], false));
-        accounts.push(AccountMeta::new(key_list[1], false));
-        accounts.push(AccountMeta::new(key_list[2], false));
-        accounts.push(AccountMeta::new(key_list[3], false));
-
-        let mut transaction = Transaction::new_with_payer(
-            &[Instruction::new_with_bytes(program_id, &data, accounts)],
-            Some(&payer.pubkey()),
-        );
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let set_distribution_backend =
+            |subsystem: Subsystem, distribution_backend: DistributionBackend| {
+                let mut transaction = Transaction::new_with_payer(
+                    &[Instruction::new_with_bytes(
+                        program_id,
+                        &instruction::SetDistributionBackend {
+                            subsystem,
+                            distribution_backend,
+                        }
+                        .data(),
+                        accounts::SetDistributionBackendContext {
+                            blocks_state_account: blocks_state_pda,
+                            config_account: get_config_pda(),
+                            signer: payer.pubkey(),
+                        }
+                        .to_account_metas(Some(false)),
+                    )],
+                    Some(&payer.pubkey()),
+                );
+                transaction.sign(&[&payer], recent_blockhash);
+                transaction
+            };
 
-        transaction.sign(&[&payer], recent_blockhash);
         let error = banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .process_transaction(set_distribution_backend(
+                Subsystem::FinalStaking,
+                DistributionBackend::Escrow,
+            ))
             .await
             .unwrap_err()
             .unwrap();
-        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
-    }
-
-    #[cfg(feature = "bpf-tests")]
-    #[tokio::test]
-    async fn test_final_mining() {
-        let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
-
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
-        program_test.prefer_bpf(true);
-
-        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        assert_eq!(get_custom_error_code(error).unwrap(), 6140);
 
-        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+        banks_client
+            .process_transaction(set_distribution_backend(
+                Subsystem::TopBlock,
+                DistributionBackend::Escrow,
+            ))
             .await
             .unwrap();
 
-        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, final_mining_account_pda, _) =
-            get_pda_accounts();
-
-        initial_token_distribution_instruction(
-            &mut banks_client,
-            &payer,
-            recent_blockhash,
-            final_mining_account_pda,
-        )
-        .await
-        .unwrap();
-
-        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
+        let config_account = banks_client
+            .get_account(get_config_pda())
             .await
+            .unwrap()
             .unwrap();
+        let config = account::Config::try_deserialize(&mut config_account.data.as_slice()).unwrap();
+        assert_eq!(
+            config.top_block_distribution_backend,
+            DistributionBackend::Escrow
+        );
+        assert_eq!(
+            config.bottom_block_distribution_backend,
+            DistributionBackend::ClaimCredit
+        );
+    }
 
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
+    #[cfg(feature = "bpf-tests")]
+    #[tokio::test]
+    async fn test_initialize_user_claim_account() {
+        let program_id = id();
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        let key_list =
-            vec![
-                create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                    .await
-                    .unwrap(),
-            ];
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+        program_test.prefer_bpf(true);
 
-        let users_info: Vec<UserInfoFinalMining> = vec![UserInfoFinalMining {
-            user_public_key: key_list[0],
-            final_mining_balance: 1,
-        }];
+        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-        let data = instruction::FinalMining { users_info }.data();
+        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+            .await
+            .unwrap();
 
-        let accs = accounts::FinalMiningContext {
+        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let user_token_account =
+            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
+                .await
+                .unwrap();
+        let (user_claim_account_pda, _) = Pubkey::find_program_address(
+            &[
+                USER_CLAIM_ACCOUNT_SEED.as_bytes(),
+                user_token_account.as_ref(),
+            ],
+            &program_id,
+        );
+
+        let data = instruction::InitializeUserClaimAccount {}.data();
+
+        let accs = accounts::InitializeUserClaimAccountContext {
             blocks_state_account: blocks_state_pda,
-            final_mining_account: final_mining_account_pda,
-            token_program,
-            signer,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            user_token_account,
+            user_claim_account: user_claim_account_pda,
+            signer: payer.pubkey(),
+            system_program: system_program::ID,
         };
 
-        let mut accounts = accs.to_account_metas(Some(false));
-        accounts.push(AccountMeta::new(key_list[0], false));
-
         let mut transaction = Transaction::new_with_payer(
-            &[Instruction::new_with_bytes(program_id, &data, accounts)],
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
             Some(&payer.pubkey()),
         );
 
         transaction.sign(&[&payer], recent_blockhash);
         banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
+
+        let user_claim_account = banks_client
+            .get_account(user_claim_account_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let user_claim_account =
+            account::UserClaimAccount::try_deserialize(&mut user_claim_account.data.as_slice())
+                .unwrap();
+        assert_eq!(user_claim_account.user, user_token_account);
+        assert_eq!(user_claim_account.pending_top_block_amount, 0);
+        assert_eq!(user_claim_account.pending_bottom_block_amount, 0);
     }
 
     #[cfg(feature = "bpf-tests")]
-    #[tokio::test]
-    async fn test_final_staking_fail_blocks_not_collided() {
+    async fn initialize_signer_set_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<Pubkey> {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let (signer_set_pda, _) =
+            Pubkey::find_program_address(&[SIGNER_SET_SEED.as_bytes()], &program_id);
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
-        program_test.prefer_bpf(true);
+        let data = instruction::InitializeSignerSet { signers, threshold }.data();
 
-        let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+        let accs = accounts::InitializeSignerSetContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer_set_account: signer_set_pda,
+            signer: payer.pubkey(),
+            system_program: system_program::ID,
+        };
 
-        initialize_instruction(&mut banks_client, &payer, recent_blockhash)
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
             .unwrap();
 
-        let program_id = id();
+        Ok(signer_set_pda)
+    }
 
-        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
+    #[cfg(feature = "bpf-tests")]
+    async fn solve_top_block_with_signer_set_instruction(
+        banks_client: &mut BanksClient,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        signers: &[&Keypair],
+        key_list: &[Pubkey],
+        users_info: &[UserInfoTopBlock],
+    ) -> std::result::Result<(), BanksClientError> {
+        let program_id = id();
+        let (mint_pda, _, blocks_state_pda, _, distribution_top_block_pda, _, _, _, _, _, _, _) =
             get_pda_accounts();
+        let (signer_set_pda, _) =
+            Pubkey::find_program_address(&[SIGNER_SET_SEED.as_bytes()], &program_id);
 
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
-
-        let key_list = vec![
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-            create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                .await
-                .unwrap(),
-        ];
-
-        let users_info: Vec<UserInfoFinalStaking> = vec![
-            UserInfoFinalStaking {
-                user_public_key: key_list[0],
-                reward_part: 0.1,
-            },
-            UserInfoFinalStaking {
-                user_public_key: key_list[1],
-                reward_part: 0.1,
-            },
-            UserInfoFinalStaking {
-                user_public_key: key_list[2],
-                reward_part: 0.1,
-            },
-            UserInfoFinalStaking {
-                user_public_key: key_list[3],
-                reward_part: 0.1,
-            },
-        ];
-
-        let data = instruction::FinalStaking { users_info }.data();
+        let data = instruction::SolveTopBlock {
+            users_info: users_info.to_vec(),
+            max_valid_slot: None,
+            chunk: None,
+            positional_accounts: false,
+            roll_over: false,
+            strict: true,
+        }
+        .data();
 
-        let accs = accounts::FinalStakingContext {
+        let accs = accounts::SolveTopBlockContext {
             blocks_state_account: blocks_state_pda,
-            final_staking_account: final_staking_account_pda,
-            token_program,
-            signer,
+            config_account: get_config_pda(),
+            mint: mint_pda,
+            distribution_top_block_account: distribution_top_block_pda,
+            token_program: spl_token::id(),
+            signer: signers[0].pubkey(),
+            signer_set_account: Some(signer_set_pda),
+            denylist_account: None,
+            treasury_token_account: None,
+            global_stats_account: None,
+            block_history_account: None,
+            block_user_requests_account: None,
+            system_program: None,
+            associated_token_program: None,
+            instructions_sysvar_account: None,
+            recent_solvers_account: None,
+            state_mirror_account: None,
         };
 
         let mut accounts = accs.to_account_metas(Some(false));
-        accounts.push(AccountMeta::new(key_list[0], false));
-        accounts.push(AccountMeta::new(key_list[1], false));
-        accounts.push(AccountMeta::new(key_list[2], false));
-        accounts.push(AccountMeta::new(key_list[3], false));
+        for key in key_list.iter() {
+            accounts.push(AccountMeta::new(*key, false));
+        }
+        for signer in signers.iter().skip(1) {
+            accounts.push(AccountMeta::new_readonly(signer.pubkey(), true));
+        }
 
         let mut transaction = Transaction::new_with_payer(
             &[Instruction::new_with_bytes(program_id, &data, accounts)],
             Some(&payer.pubkey()),
         );
 
-        transaction.sign(&[&payer], recent_blockhash);
-        let error = banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Finalized)
+        let mut transaction_signers = vec![payer];
+        transaction_signers.extend_from_slice(signers);
+        transaction.sign(&transaction_signers, recent_blockhash);
+        banks_client
+            .process_transaction_with_commitment(transaction, CommitmentLevel::Confirmed)
             .await
-            .unwrap_err()
-            .unwrap();
-        assert_eq!(get_custom_error_code(error).unwrap(), 6007);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_final_staking() {
+    async fn test_solve_top_block_approved_by_signer_quorum() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1747,77 +7903,60 @@ mod test {
             .await
             .unwrap();
 
-        let (mint_pda, _, blocks_state_pda, _, _, _, _, _, final_staking_account_pda, _, _, _) =
-            get_pda_accounts();
-
-        initial_token_distribution_instruction(
+        let first_signer = Keypair::new();
+        let second_signer = Keypair::new();
+        initialize_signer_set_instruction(
             &mut banks_client,
             &payer,
             recent_blockhash,
-            final_staking_account_pda,
+            vec![first_signer.pubkey(), second_signer.pubkey()],
+            2,
         )
         .await
         .unwrap();
 
-        set_blocks_collided_instruction(&mut banks_client, &payer, recent_blockhash, true)
-            .await
-            .unwrap();
-
-        let program_id = id();
-
-        let token_program = spl_token::id();
-        let signer = payer.pubkey();
-
-        let key_list =
-            vec![
-                create_token_account(&mut banks_client, &payer, recent_blockhash, mint_pda)
-                    .await
-                    .unwrap(),
-            ];
-
-        let users_info: Vec<UserInfoFinalStaking> = vec![UserInfoFinalStaking {
-            user_public_key: key_list[0],
-            reward_part: 0.1,
-        }];
-
-        let data = instruction::FinalStaking { users_info }.data();
-
-        let accs = accounts::FinalStakingContext {
-            blocks_state_account: blocks_state_pda,
-            final_staking_account: final_staking_account_pda,
-            token_program,
-            signer,
-        };
-
-        let mut accounts = accs.to_account_metas(Some(false));
-        accounts.push(AccountMeta::new(key_list[0], false));
+        let (key_list, users_info) = default_top_block_setup(&mut banks_client, &payer).await;
 
-        let mut transaction = Transaction::new_with_payer(
-            &[Instruction::new_with_bytes(program_id, &data, accounts)],
-            Some(&payer.pubkey()),
-        );
+        let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+        let error = solve_top_block_with_signer_set_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[&first_signer],
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap_err()
+        .unwrap();
+        assert_eq!(get_custom_error_code(error).unwrap(), 6029);
 
-        transaction.sign(&[&payer], recent_blockhash);
-        banks_client
-            .process_transaction_with_commitment(transaction.clone(), CommitmentLevel::Confirmed)
-            .await
-            .unwrap();
-    }
+        solve_top_block_with_signer_set_instruction(
+            &mut banks_client,
+            &payer,
+            recent_blockhash,
+            &[&first_signer, &second_signer],
+            &key_list,
+            &users_info,
+        )
+        .await
+        .unwrap();
 
-    #[tokio::test]
-    #[should_panic]
-    async fn test_fail_final_staking_required_interval_elapsed_without_context() {
-        final_staking_required_interval_elapsed(&1).unwrap();
+        for key in key_list.iter() {
+            let account = banks_client.get_account(*key).await.unwrap().unwrap();
+            let account_data = Account::unpack(&account.data).unwrap();
+            assert_eq!(account_data.amount, 200000000000);
+        }
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_new_authority() {
+    async fn test_propose_and_accept_authority() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
         program_test.set_compute_max_units(500000);
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
@@ -1828,17 +7967,17 @@ mod test {
             .unwrap();
 
         let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let new_authority = Keypair::new();
 
-        let data = instruction::ChangeAuthority {
-            new_authority: signer,
+        let data = instruction::ProposeAuthority {
+            new_authority: new_authority.pubkey(),
         }
         .data();
-
-        let accs = accounts::ChangeAuthorityContext {
+        let accs = accounts::ProposeAuthorityContext {
             blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
             signer,
         };
-
         let mut transaction = Transaction::new_with_payer(
             &[Instruction::new_with_bytes(
                 program_id,
@@ -1847,41 +7986,63 @@ mod test {
             )],
             Some(&payer.pubkey()),
         );
-
         transaction.sign(&[&payer], recent_blockhash);
         banks_client.process_transaction(transaction).await.unwrap();
+
+        let data = instruction::AcceptAuthority {}.data();
+        let accs = accounts::AcceptAuthorityContext {
+            blocks_state_account: blocks_state_pda,
+            config_account: get_config_pda(),
+            signer: new_authority.pubkey(),
+        };
+        let mut transaction = Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &data,
+                accs.to_account_metas(Some(false)),
+            )],
+            Some(&payer.pubkey()),
+        );
+        transaction.sign(&[&payer, &new_authority], recent_blockhash);
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let blocks_state_account = banks_client
+            .get_account(blocks_state_pda)
+            .await
+            .unwrap()
+            .unwrap();
+        let blocks_state =
+            account::BlocksState::try_deserialize(&mut blocks_state_account.data.as_slice())
+                .unwrap();
+        assert_eq!(blocks_state.authority, new_authority.pubkey());
+        assert_eq!(blocks_state.pending_authority, None);
     }
 
     #[cfg(feature = "bpf-tests")]
     #[tokio::test]
-    async fn test_new_authority_with_wrong_signer() {
+    async fn test_accept_authority_without_proposal() {
         let program_id = id();
-        let mut program_test = ProgramTest::new("sallar", program_id, processor!(entry));
+        let mut program_test = ProgramTest::new("sallar", program_id, processor!(test_entry));
         program_test.set_compute_max_units(500000);
 
-        program_test.add_program("mpl_token_metadata", mpl_token_metadata::id(), None);
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
         program_test.prefer_bpf(true);
 
         let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-        let signer = payer.pubkey();
 
         initialize_instruction(&mut banks_client, &payer, recent_blockhash)
             .await
             .unwrap();
 
         let (_, _, blocks_state_pda, _, _, _, _, _, _, _, _, _) = get_pda_accounts();
+        let new_authority = Keypair::new();
 
-        let data = instruction::ChangeAuthority {
-            new_authority: signer,
-        }
-        .data();
-
-        let sub_signer = Keypair::new();
-        let accs = accounts::ChangeAuthorityContext {
+        let data = instruction::AcceptAuthority {}.data();
+        let accs = accounts::AcceptAuthorityContext {
             blocks_state_account: blocks_state_pda,
-            signer: sub_signer.pubkey(),
+            config_account: get_config_pda(),
+            signer: new_authority.pubkey(),
         };
-
         let mut transaction = Transaction::new_with_payer(
             &[Instruction::new_with_bytes(
                 program_id,
@@ -1890,14 +8051,13 @@ mod test {
             )],
             Some(&payer.pubkey()),
         );
-
-        transaction.sign(&[&payer, &sub_signer], recent_blockhash);
+        transaction.sign(&[&payer, &new_authority], recent_blockhash);
         let error = banks_client
             .process_transaction(transaction)
             .await
             .unwrap_err()
             .unwrap();
-        assert_eq!(get_custom_error_code(error).unwrap(), 6000);
+        assert_eq!(get_custom_error_code(error).unwrap(), 6024);
     }
 
     async fn create_token_account(
@@ -1926,7 +8086,7 @@ mod test {
                 .unwrap(),
             ],
             Some(&payer.pubkey()),
-            &[&payer, &new_keypair],
+            &[payer, &new_keypair],
             recent_blockhash,
         );
         banks_client.process_transaction(transaction).await.unwrap();
@@ -1934,6 +8094,21 @@ mod test {
         Ok(new_keypair.pubkey())
     }
 
+    /// The program entrypoint requires accounts living as long as the accounts' data,
+    /// so they are leaked to match the processor signature expected by solana-program-test.
+    fn test_entry(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+        let accounts = Box::leak(Box::new(accounts.to_vec()));
+        entry(program_id, accounts, instruction_data)
+    }
+
+    fn get_config_pda() -> Pubkey {
+        Pubkey::find_program_address(&[CONFIG_SEED.as_bytes()], &id()).0
+    }
+
     fn get_pda_accounts() -> (
         Pubkey,
         u8,
@@ -1983,7 +8158,7 @@ mod test {
     async fn set_time(ctx: &mut ProgramTestContext, time: i64) {
         let clock_sysvar: Clock = ctx.banks_client.get_sysvar().await.unwrap();
         let mut new_clock = clock_sysvar.clone();
-        new_clock.epoch = new_clock.epoch + 30;
+        new_clock.epoch += 30;
         new_clock.unix_timestamp = time;
 
         ctx.set_sysvar(&new_clock);
@@ -1,389 +1,416 @@
-use anchor_lang::error::Error;
-
-use crate::utils::{convert_f64_to_u64, convert_u64_to_f64};
-
-/// In this module for numerical calculations, we have carefully considered the appropriate data types to use for different types of calculations.
-/// To ensure accurate and efficient computations, we have employed a strategy that utilizes f64 for non-financial calculations and u64 for financial calculations.
-/// This approach takes into account the performance, precision, compliance, readability, maintainability, portability, and robustness requirements of the calculations.
-///
-/// Using f64 for numerical calculations allows for efficient and fast computations due to its native floating-point implementation in Rust.
-/// f64 can accurately represent approximately 15-17 significant decimal digits, which provides a high level of precision for calculations.
-/// To ensure accurate representation and manipulation of financial amounts with strict adherence to rounding rules and precision requirements,
-/// we utilize u64 as the data type for storing and processing financial results as much as possible and f64 only for the final part
-/// of some of the calculations where it is strictly required. By using f64 for numerical calculations and u64 for most of the financial amounts,
-/// we strike a balance between performance and precision, ensuring efficient computations while maintaining accuracy and compliance in financial calculations.
-/// In the most cases f64 is enough to provide full precision.
-/// In the other rare cases some small lack of precision is introduced but it influences results in a very limited way (the inaccuracy is very low).
-///
-/// The accuracy and compliance of the calculations were thoroughly verified using Python scripts,
-/// which were also used to generate comprehensive test data to ensure the correctness and reliability of the implementation.
-/// This approach combines the performance benefits of f64 with the precise representation of u64,
-/// while also utilizing Python for both verification and test data generation to ensure the accuracy and reliability of the calculations in our Rust module.
-///
-/// Total supply for SPL Token cannot exceed u64 range in Solana.
-/// There are no operations in this contract that would exceed total supply so this is why usage of u64 is safe here.
-
-/// Set of functions and constants defining the most important math functions used by the contract.
-
-pub const TOKEN_AMOUNT_SCALING_FACTOR: u64 = 1_000;
-pub const DUSTS_PER_BLOCK: u64 = 2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
-
-const MAX_BLOCK_INDEX: u64 = 470_000;
-
-const FIRST_BP: f64 = 20.0 * (TOKEN_AMOUNT_SCALING_FACTOR as f64);
-const REDUCTION_INVERSE: f64 = 0.99999430521433;
-
-const MIN_REQUIRED_STAKE_FOR_BOTTOM_BLOCK_DUST: u64 =
-    2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR as u64;
-
-const MAX_BOTTOM_BOOST: f64 = 60.0;
-const BOTTOM_BOOST_REDUCTION: f64 = 0.999997999992;
+//! Set of functions and constants defining the most important math functions used by the contract.
+//!
+//! The math of blocks, rewards and final staking is implemented in the `sallar-math` crate, so it can be used off-chain
+//! without Anchor nor Solana dependencies with exactly the same results. This module re-exports it with the errors mapped to `SallarError`
+//! and adds the final mining math, which depends on the accounts of the contract.
 
-const MIN_TOP_BOOST: f64 = 0.5;
-const TOP_BOOST_REDUCTION: f64 = 1.000004498927;
-
-const TOP_FIRST_BOOSTED_BLOCK: f64 = 250.0;
-const TOP_BP_WITHOUT_BOOST: u64 = 1 * TOKEN_AMOUNT_SCALING_FACTOR;
-
-fn dust_to_staking_sallar(dusts: u64) -> u64 {
-    // 1 dust = 1e-8 sallar, only the whole sallar will be staked
-    // truncation of the decimal part is intentional
-    dusts / (100_000_000)
-}
-
-fn calculate_bp_reduction_factor(block_index: u64) -> Result<f64, Error> {
-    Ok(REDUCTION_INVERSE.powf(convert_u64_to_f64(block_index - 1)?))
-}
-
-pub fn calculate_max_bp(block_index: u64) -> Result<f64, Error> {
-    let bp_reduction_factor = calculate_bp_reduction_factor(block_index)?;
-
-    Ok((FIRST_BP / bp_reduction_factor).round())
+use anchor_lang::error::Error;
+use sallar_math::MathError;
+
+use crate::{
+    account::{FinalMiningTier, ProjectedFinalMiningWindow},
+    error::SallarError,
+    event::RewardBreakdown,
+};
+
+pub use sallar_math::{
+    calculate_bottom_blocks_minted_amount, calculate_top_blocks_minted_amount, DustPerBp,
+    BASIS_POINTS_DIVISOR, DUSTS_PER_BLOCK,
+    FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR, FIXED_POINT_SCALING_FACTOR,
+    INITIAL_TOKEN_DISTRIBUTION_AMOUNT, MAX_BLOCK_INDEX, REWARD_PARTS_SCALING_FACTOR,
+    TOKEN_AMOUNT_SCALING_FACTOR,
+};
+
+/// The highest reward of a single final mining request.
+pub const MAX_FINAL_MINING_REWARD: u64 = 50_000_000_000;
+
+/// The final mining tiers applied until the final mining tiers account is created.
+pub const DEFAULT_FINAL_MINING_TIERS: [FinalMiningTier; 5] = [
+    FinalMiningTier {
+        min_final_mining_balance: 0,
+        reward: 2_500_000_000,
+    },
+    FinalMiningTier {
+        min_final_mining_balance: 12_500_000_000_000_000,
+        reward: 5_000_000_000,
+    },
+    FinalMiningTier {
+        min_final_mining_balance: 25_000_000_000_000_000,
+        reward: 10_000_000_000,
+    },
+    FinalMiningTier {
+        min_final_mining_balance: 50_000_000_000_000_000,
+        reward: 25_000_000_000,
+    },
+    FinalMiningTier {
+        min_final_mining_balance: 100_000_000_000_000_000,
+        reward: MAX_FINAL_MINING_REWARD,
+    },
+];
+
+fn map_math_error<T>(result: Result<T, MathError>) -> Result<T, Error> {
+    result.map_err(|error| SallarError::from(error).into())
 }
 
-pub fn calculate_dust_per_bp(block_index: u64) -> Result<f64, Error> {
-    let max_bp = calculate_max_bp(block_index)?;
-    Ok(convert_u64_to_f64(DUSTS_PER_BLOCK)? / max_bp)
+pub fn valid_block_index(block_index: u64) -> Result<(), Error> {
+    map_math_error(sallar_math::valid_block_index(block_index))
 }
 
-fn calculate_top_block_max_boost(block_index: u64) -> Result<u64, Error> {
-    let exp = convert_u64_to_f64(block_index)? - TOP_FIRST_BOOSTED_BLOCK;
-    let pow = TOP_BOOST_REDUCTION.powf(exp);
-
-    let base_boost = MIN_TOP_BOOST * pow;
-    let rounded_boost;
-
-    if base_boost < 1e+2 {
-        rounded_boost = convert_f64_to_u64(base_boost.round())?;
-    } else if base_boost < 1e+3 {
-        rounded_boost = convert_f64_to_u64(base_boost * 0.1)? * 10;
-    } else {
-        rounded_boost = convert_f64_to_u64(base_boost * 0.01)? * 100;
-    }
-
-    Ok(rounded_boost)
+pub fn calculate_max_bp(block_index: u64) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_max_bp(block_index))
 }
 
-fn calculate_base_bp_for_given_boost(boost: u64) -> u64 {
-    1 + boost
+pub fn calculate_dust_per_bp(block_index: u64) -> Result<DustPerBp, Error> {
+    map_math_error(sallar_math::calculate_dust_per_bp(block_index))
 }
 
-fn calculate_top_bp(boost: u64) -> Result<u64, Error> {
-    Ok(TOKEN_AMOUNT_SCALING_FACTOR * calculate_base_bp_for_given_boost(boost))
+pub fn dust_per_bp_from_block_bp(block_bp: u64) -> Result<DustPerBp, Error> {
+    map_math_error(DustPerBp::from_block_bp(block_bp))
 }
 
 pub fn calculate_top_bp_with_boost(block_index: u64) -> Result<u64, Error> {
-    let boost = calculate_top_block_max_boost(block_index)?;
-
-    Ok(calculate_top_bp(boost)?)
+    map_math_error(sallar_math::calculate_top_bp_with_boost(block_index))
 }
 
-fn calculate_bottom_block_max_boost(block_index: u64) -> Result<u64, Error> {
-    let base_boost = MAX_BOTTOM_BOOST
-        * BOTTOM_BOOST_REDUCTION.powf(convert_u64_to_f64(MAX_BLOCK_INDEX - block_index)?);
-
-    convert_f64_to_u64(base_boost.round())
+pub fn calculate_bottom_block_max_boost(block_index: u64) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_bottom_block_max_boost(block_index))
 }
 
-fn calculate_bottom_bp(user_wallet_balance: u64, boost: u64) -> u64 {
-    calculate_base_bp_for_given_boost(boost) * (dust_to_staking_sallar(user_wallet_balance))
+pub fn calculate_bottom_bp_without_boost(user_wallet_balance: u64) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_bottom_bp_without_boost(
+        user_wallet_balance,
+    ))
 }
 
-pub fn calculate_bottom_bp_without_boost(user_wallet_balance: u64) -> u64 {
-    calculate_bottom_bp(user_wallet_balance, 0)
+pub fn calculate_bottom_bp_with_given_boost(
+    user_wallet_balance: u64,
+    boost: u64,
+) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_bottom_bp_with_given_boost(
+        user_wallet_balance,
+        boost,
+    ))
 }
 
 pub fn calculate_bottom_bp_with_boost(
     block_index: u64,
     user_wallet_balance: u64,
 ) -> Result<u64, Error> {
-    let boost = calculate_bottom_block_max_boost(block_index)?;
-
-    Ok(calculate_bottom_bp(user_wallet_balance, boost))
-}
-
-pub fn calculate_single_reward(bp: u64, dust_per_bp: f64) -> Result<u64, Error> {
-    Ok(convert_f64_to_u64(
-        (convert_u64_to_f64(bp)? * dust_per_bp).round(),
-    )?)
+    map_math_error(sallar_math::calculate_bottom_bp_with_boost(
+        block_index,
+        user_wallet_balance,
+    ))
 }
 
-/// The function calculates parts of the reward separately for requests with boost and without boost.
-/// They are kept separate from each other, and the reason they are summed up in the end
-/// is to consolidate them into a single transfer, instead of two separate transfers for each reward part.
-/// However, the calculation is intentionally done this way, as the parts are semantically separated.
-fn calculate_user_reward(
-    user_request_without_boost: u8,
-    user_request_with_boost: u8,
-    parts_without_boost: u64,
-    parts_with_boost: u64,
-    dust_per_bp: f64,
-) -> Result<(u64, u64), Error> {
-    let amount_without_boost = (user_request_without_boost as u64)
-        * calculate_single_reward(parts_without_boost, dust_per_bp)?;
-    let amount_with_boost =
-        (user_request_with_boost as u64) * calculate_single_reward(parts_with_boost, dust_per_bp)?;
-
-    let total_bp = ((user_request_without_boost as u64) * parts_without_boost)
-        + ((user_request_with_boost as u64) * parts_with_boost);
-    let summary_amount = amount_without_boost + amount_with_boost;
-
-    Ok((total_bp, summary_amount))
+pub fn calculate_single_reward(bp: u64, dust_per_bp: DustPerBp) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_single_reward(bp, dust_per_bp))
 }
 
-pub fn calculate_user_reward_bottom_block(
+pub fn calculate_user_reward_breakdown_bottom_block(
     user_request_without_boost: u8,
     user_request_with_boost: u8,
     parts_without_boost: u64,
     parts_with_boost: u64,
-    dust_per_bp: f64,
+    dust_per_bp: DustPerBp,
     user_wallet_balance: u64,
-) -> Result<(u64, u64), Error> {
-    if user_wallet_balance < MIN_REQUIRED_STAKE_FOR_BOTTOM_BLOCK_DUST {
-        return Ok((0, 0));
-    }
-
-    Ok(calculate_user_reward(
+    min_required_stake: u64,
+) -> Result<RewardBreakdown, Error> {
+    map_math_error(sallar_math::calculate_user_reward_breakdown_bottom_block(
         user_request_without_boost,
         user_request_with_boost,
         parts_without_boost,
         parts_with_boost,
         dust_per_bp,
-    )?)
+        user_wallet_balance,
+        min_required_stake,
+    ))
+    .map(RewardBreakdown::from)
 }
 
-pub fn calculate_user_reward_top_block(
+pub fn calculate_user_reward_breakdown_top_block(
     user_request_without_boost: u8,
     user_request_with_boost: u8,
     parts_with_boost: u64,
-    dust_per_bp: f64,
-) -> Result<(u64, u64), Error> {
-    Ok(calculate_user_reward(
+    dust_per_bp: DustPerBp,
+) -> Result<RewardBreakdown, Error> {
+    map_math_error(sallar_math::calculate_user_reward_breakdown_top_block(
         user_request_without_boost,
         user_request_with_boost,
-        TOP_BP_WITHOUT_BOOST,
         parts_with_boost,
         dust_per_bp,
-    )?)
+    ))
+    .map(RewardBreakdown::from)
 }
 
-#[cfg(test)]
-mod tests {
-    use std::{error::Error as standardError, fs::File};
+pub fn allocate_block_reward(
+    bp: u64,
+    dust_per_bp: DustPerBp,
+    rounding_remainder: u64,
+) -> Result<(u64, u64), Error> {
+    map_math_error(sallar_math::allocate_block_reward(
+        bp,
+        dust_per_bp,
+        rounding_remainder,
+    ))
+}
 
-    use super::*;
+pub fn calculate_user_rest_reward(
+    user_rest_bp: u64,
+    block_index: u64,
+) -> Result<(u64, u64), Error> {
+    map_math_error(sallar_math::calculate_user_rest_reward(
+        user_rest_bp,
+        block_index,
+    ))
+}
 
-    #[test]
-    fn generate_csv_report_top_block() -> Result<(), Box<dyn standardError>> {
-        let file = File::open("./top_block_reports/dustAndBlockPartReportTop.csv")?;
-        let mut rdr = csv::Reader::from_reader(file);
-
-        for result in rdr.records() {
-            let record = result?;
-
-            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
-            let dust_without_boost_expected = record.get(1).unwrap().parse::<u64>().unwrap();
-            let dust_with_boost_expected = record.get(2).unwrap().parse::<u64>().unwrap();
-            let bp_with_boost_expected = record.get(3).unwrap().parse::<u64>().unwrap();
-            let dust_per_bp_expected = record.get(4).unwrap().parse::<f64>().unwrap();
-            let until_block_index = record.get(5).unwrap().parse::<u64>().unwrap();
-
-            let indexes = vec![block_index, until_block_index];
-
-            for index in indexes {
-                let sallar_per_bp = calculate_dust_per_bp(index).unwrap();
-                let top_block_bp_with_boost = calculate_top_bp_with_boost(index).unwrap();
-
-                let (_, top_block_dust_without_boost) =
-                    calculate_user_reward_top_block(1, 0, top_block_bp_with_boost, sallar_per_bp)
-                        .unwrap();
-                let (_, top_block_dust_with_boost) =
-                    calculate_user_reward_top_block(0, 1, top_block_bp_with_boost, sallar_per_bp)
-                        .unwrap();
-
-                let dust_per_bp = calculate_dust_per_bp(index).unwrap();
-
-                assert_eq!(
-                    bp_with_boost_expected.to_string(),
-                    top_block_bp_with_boost.to_string()
-                );
-                assert_eq!(
-                    dust_with_boost_expected.to_string(),
-                    top_block_dust_with_boost.to_string()
-                );
-                assert_eq!(
-                    dust_without_boost_expected.to_string(),
-                    top_block_dust_without_boost.to_string()
-                );
-                assert_eq!(dust_per_bp_expected.to_string(), dust_per_bp.to_string());
-            }
-        }
+pub fn calculate_final_staking_reward(reward_part: u64, pool_in_round: u64) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_final_staking_reward(
+        reward_part,
+        pool_in_round,
+    ))
+}
 
-        Ok(())
-    }
+pub fn calculate_snapshot_reward_part(balance: u64, registered_balance: u64) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_snapshot_reward_part(
+        balance,
+        registered_balance,
+    ))
+}
 
-    #[test]
-    fn generate_csv_report_bottom_block() -> Result<(), Box<dyn standardError>> {
-        let file = File::open("./bottom_block_reports/dustAndBlockPartReportBottom.csv")?;
-        let mut rdr = csv::Reader::from_reader(file);
-
-        for result in rdr.records() {
-            let record = result?;
-
-            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
-            let balance = record.get(1).unwrap().parse::<u64>().unwrap();
-
-            let sallar_without_boost_expected = record.get(2).unwrap().parse::<u64>().unwrap();
-            let bp_without_boost_expected = record.get(3).unwrap().parse::<u64>().unwrap();
-            let sallar_with_boost_expected = record.get(4).unwrap().parse::<u64>().unwrap();
-            let bp_with_boost_expected = record.get(5).unwrap().parse::<u64>().unwrap();
-            let sallar_per_bp_expected = record.get(6).unwrap().parse::<f64>().unwrap();
-
-            let bottom_block_bp_with_boost =
-                calculate_bottom_bp_with_boost(block_index, balance).unwrap();
-            let bottom_block_bp_without_boost = calculate_bottom_bp_without_boost(balance);
-            let sallar_per_bp = calculate_dust_per_bp(block_index).unwrap();
-
-            let (_, bottom_block_staking_dust_without_boost) = calculate_user_reward_bottom_block(
-                1,
-                0,
-                bottom_block_bp_without_boost,
-                bottom_block_bp_with_boost,
-                sallar_per_bp,
-                balance,
-            )
-            .unwrap();
-            let (_, bottom_block_staking_dust_with_boost) = calculate_user_reward_bottom_block(
-                0,
-                1,
-                bottom_block_bp_without_boost,
-                bottom_block_bp_with_boost,
-                sallar_per_bp,
-                balance,
-            )
-            .unwrap();
-
-            let bp_without_boost = calculate_bottom_bp_without_boost(balance);
-
-            assert_eq!(
-                bp_without_boost_expected.to_string(),
-                bp_without_boost.to_string()
-            );
-            assert_eq!(
-                bp_with_boost_expected.to_string(),
-                bottom_block_bp_with_boost.to_string()
-            );
+pub fn calculate_max_reward_part(
+    balance: u64,
+    registered_balance: u64,
+    tolerance_bps: u32,
+) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_max_reward_part(
+        balance,
+        registered_balance,
+        tolerance_bps,
+    ))
+}
 
-            assert_eq!(
-                sallar_per_bp_expected.to_string(),
-                sallar_per_bp.to_string()
-            );
+pub fn calculate_weighted_balance(balance: u64, multiplier_bps: u32) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_weighted_balance(
+        balance,
+        multiplier_bps,
+    ))
+}
 
-            assert_eq!(
-                sallar_without_boost_expected.to_string(),
-                bottom_block_staking_dust_without_boost.to_string(),
-                "block_index: {}",
-                block_index
-            );
-            assert_eq!(
-                sallar_with_boost_expected.to_string(),
-                bottom_block_staking_dust_with_boost.to_string(),
-                "block_index: {}",
-                block_index
+pub fn calculate_reward_fee(reward_amount: u64, reward_fee_bps: u16) -> Result<u64, Error> {
+    map_math_error(sallar_math::calculate_reward_fee(
+        reward_amount,
+        reward_fee_bps,
+    ))
+}
+
+pub fn convert_scaled_reward_parts_to_f64(scaled_reward_parts: u64) -> Result<f64, Error> {
+    Ok(sallar_math::convert_scaled_reward_parts_to_f64(
+        scaled_reward_parts,
+    ))
+}
+
+/// Calculates the reward of a single final mining request.
+/// The reward depends on the final mining account's balance in the moment when the user requested participation in final mining.
+///
+/// ### Arguments
+///
+/// * `tiers` - the final mining tiers ordered by the minimum final mining balance,
+/// * `final_mining_balance` - the final mining account's balance at the time of the request.
+///
+/// ### Returns
+/// The reward of the highest tier the balance belongs to, 0 if it does not belong to any tier.
+pub fn calculate_final_mining_reward(tiers: &[FinalMiningTier], final_mining_balance: u64) -> u64 {
+    tiers
+        .iter()
+        .rev()
+        .find(|tier| tier.min_final_mining_balance <= final_mining_balance)
+        .map_or(0, |tier| tier.reward)
+}
+
+/// Projects the final mining emission of the following windows from the final mining account's balance, the same way final mining distributes the rewards:
+/// every request is rewarded by the tier of the balance at the time of the request, and the requests are not rewarded
+/// once the balance cannot cover the reward or the budget of the window would be exceeded.
+///
+/// ### Arguments
+///
+/// * `tiers` - the final mining tiers ordered by the minimum final mining balance,
+/// * `final_mining_balance` - the final mining account's balance at the beginning of the first window,
+/// * `requests_per_window` - the number of final mining requests expected within a single window,
+/// * `budget_per_window` - the maximum amount of tokens distributed within a single window (no limit if not provided),
+/// * `windows` - the number of windows to project.
+///
+/// ### Returns
+/// The projected windows, starting with the first one.
+pub fn project_final_mining_emission(
+    tiers: &[FinalMiningTier],
+    final_mining_balance: u64,
+    requests_per_window: u64,
+    budget_per_window: Option<u64>,
+    windows: usize,
+) -> Vec<ProjectedFinalMiningWindow> {
+    let mut balance = final_mining_balance;
+
+    (0..windows)
+        .map(|_| {
+            let starting_balance = balance;
+            let emission = project_final_mining_window_emission(
+                tiers,
+                starting_balance,
+                requests_per_window,
+                budget_per_window.unwrap_or(u64::MAX),
             );
+            balance -= emission;
+
+            ProjectedFinalMiningWindow {
+                starting_balance,
+                reward_per_request: calculate_final_mining_reward(tiers, starting_balance),
+                emission,
+            }
+        })
+        .collect()
+}
+
+/// Projects the emission of a single window, the requests rewarded by the same tier are counted at once.
+fn project_final_mining_window_emission(
+    tiers: &[FinalMiningTier],
+    final_mining_balance: u64,
+    requests: u64,
+    budget: u64,
+) -> u64 {
+    let mut balance = final_mining_balance;
+    let mut remaining_requests = requests;
+    let mut emission = 0;
+
+    while remaining_requests > 0 {
+        let Some(tier) = tiers
+            .iter()
+            .rev()
+            .find(|tier| tier.min_final_mining_balance <= balance)
+        else {
+            break;
+        };
+        if tier.reward == 0 {
+            break;
+        }
+        let requests_in_tier = remaining_requests
+            .min((balance - tier.min_final_mining_balance) / tier.reward + 1)
+            .min(balance / tier.reward)
+            .min((budget - emission) / tier.reward);
+        if requests_in_tier == 0 {
+            break;
         }
 
-        Ok(())
+        let tier_emission = requests_in_tier * tier.reward;
+        emission += tier_emission;
+        balance -= tier_emission;
+        remaining_requests -= requests_in_tier;
     }
 
-    #[test]
-    pub fn calculate_user_reward_top_block_test() -> Result<(), Box<dyn standardError>> {
-        let file = File::open("./top_block_reports/topBlockTransferTestData.csv")?;
-        let mut rdr = csv::Reader::from_reader(file);
-
-        for result in rdr.records() {
-            let record = result?;
-
-            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
-            let user_request_without_boost = record.get(1).unwrap().parse::<u64>().unwrap();
-            let user_request_with_boost = record.get(2).unwrap().parse::<u64>().unwrap();
-
-            let reward_dust_expected = record.get(3).unwrap().parse::<u64>().unwrap();
-
-            let top_block_bp_with_boost = calculate_top_bp_with_boost(block_index).unwrap();
-            let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
-            let (_, reward_dust) = calculate_user_reward_top_block(
-                user_request_without_boost as u8,
-                user_request_with_boost as u8,
-                top_block_bp_with_boost,
-                dust_per_bp,
-            )
-            .unwrap();
-
-            assert_eq!(reward_dust_expected.to_string(), reward_dust.to_string());
-        }
+    emission
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(())
+    #[test]
+    fn test_math_errors_mapped_to_sallar_errors() {
+        assert_eq!(
+            valid_block_index(MAX_BLOCK_INDEX + 1),
+            Err(SallarError::BlockIndexOutOfBounds.into())
+        );
+        assert_eq!(
+            calculate_final_staking_reward(u64::MAX, u64::MAX),
+            Err(SallarError::U64ConversionError.into())
+        );
+        assert_eq!(
+            calculate_snapshot_reward_part(1_001, 1_000),
+            Err(SallarError::MissingFinalStakingRegistration.into())
+        );
+        assert_eq!(
+            calculate_max_reward_part(1_000, 0, 1_000),
+            Err(SallarError::RewardPartExceedsBalanceShare.into())
+        );
     }
 
     #[test]
-    pub fn calculate_user_reward_bottom_block_test() -> Result<(), Box<dyn standardError>> {
-        let file = File::open("./bottom_block_reports/bottomBlockTransferTestData.csv")?;
-        let mut rdr = csv::Reader::from_reader(file);
-
-        for result in rdr.records() {
-            let record = result?;
-
-            let block_index = record.get(0).unwrap().parse::<u64>().unwrap();
-            let user_request_without_boost = record.get(1).unwrap().parse::<u64>().unwrap();
-            let user_request_with_boost = record.get(2).unwrap().parse::<u64>().unwrap();
-
-            let user_wallet_balance = record.get(3).unwrap().parse::<u64>().unwrap();
-            let reward_dust_expected = record.get(4).unwrap().parse::<u64>().unwrap();
-            let bp_expected = record.get(5).unwrap().parse::<u64>().unwrap();
-
-            let bottom_block_bp_without_boost =
-                calculate_bottom_bp_without_boost(user_wallet_balance);
-            let bottom_block_bp_with_boost =
-                calculate_bottom_bp_with_boost(block_index, user_wallet_balance).unwrap();
-            let dust_per_bp = calculate_dust_per_bp(block_index).unwrap();
-
-            let (_, reward_dust) = calculate_user_reward_bottom_block(
-                user_request_without_boost as u8,
-                user_request_with_boost as u8,
-                bottom_block_bp_without_boost,
-                bottom_block_bp_with_boost,
-                dust_per_bp,
-                user_wallet_balance,
-            )
-            .unwrap();
-            let bp = bottom_block_bp_without_boost * user_request_without_boost
-                + bottom_block_bp_with_boost * user_request_with_boost;
-
-            assert_eq!(bp_expected.to_string(), bp.to_string());
-            assert_eq!(reward_dust_expected.to_string(), reward_dust.to_string());
-        }
+    fn test_project_final_mining_emission() {
+        let tiers = [
+            FinalMiningTier {
+                min_final_mining_balance: 0,
+                reward: 10,
+            },
+            FinalMiningTier {
+                min_final_mining_balance: 1_000,
+                reward: 100,
+            },
+        ];
+
+        // 3 requests rewarded by the upper tier (1_200, 1_100, 1_000), then 2 by the lower one.
+        let windows = project_final_mining_emission(&tiers, 1_200, 5, None, 2);
+        assert_eq!(
+            windows[0],
+            ProjectedFinalMiningWindow {
+                starting_balance: 1_200,
+                reward_per_request: 100,
+                emission: 320,
+            }
+        );
+        assert_eq!(
+            windows[1],
+            ProjectedFinalMiningWindow {
+                starting_balance: 880,
+                reward_per_request: 10,
+                emission: 50,
+            }
+        );
+
+        let windows = project_final_mining_emission(&tiers, 1_200, 5, Some(250), 1);
+        assert_eq!(windows[0].emission, 200);
+
+        let windows = project_final_mining_emission(&tiers, 25, 5, None, 2);
+        assert_eq!(windows[0].emission, 20);
+        assert_eq!(windows[1].starting_balance, 5);
+        assert_eq!(windows[1].emission, 0);
+
+        assert_eq!(
+            project_final_mining_emission(&tiers[1..], 999, 5, None, 1)[0].emission,
+            0
+        );
+        assert!(project_final_mining_emission(&tiers, 1_200, 5, None, 0).is_empty());
+
+        let windows =
+            project_final_mining_emission(&DEFAULT_FINAL_MINING_TIERS, u64::MAX, u64::MAX, None, 1);
+        assert_eq!(windows[0].reward_per_request, MAX_FINAL_MINING_REWARD);
+        assert!(windows[0].emission > 0);
+    }
 
-        Ok(())
+    #[test]
+    fn test_calculate_final_mining_reward() {
+        let reward = |final_mining_balance| {
+            calculate_final_mining_reward(&DEFAULT_FINAL_MINING_TIERS, final_mining_balance)
+        };
+        assert_eq!(reward(0), 2_500_000_000);
+        assert_eq!(reward(12_499_999_999_999_999), 2_500_000_000);
+        assert_eq!(reward(12_500_000_000_000_000), 5_000_000_000);
+        assert_eq!(reward(25_000_000_000_000_000), 10_000_000_000);
+        assert_eq!(reward(50_000_000_000_000_000), 25_000_000_000);
+        assert_eq!(reward(100_000_000_000_000_000), MAX_FINAL_MINING_REWARD);
+
+        let tiers = [
+            FinalMiningTier {
+                min_final_mining_balance: 1_000,
+                reward: 10,
+            },
+            FinalMiningTier {
+                min_final_mining_balance: 2_000,
+                reward: 20,
+            },
+        ];
+        assert_eq!(calculate_final_mining_reward(&tiers, 999), 0);
+        assert_eq!(calculate_final_mining_reward(&tiers, 1_999), 10);
+        assert_eq!(calculate_final_mining_reward(&tiers, u64::MAX), 20);
+        assert_eq!(calculate_final_mining_reward(&[], u64::MAX), 0);
     }
 }
@@ -1,23 +1,145 @@
 use anchor_lang::{
-    context,
+    context, emit, err, error,
+    error::{Error, ErrorCode},
     prelude::{
-        require, Account, AccountInfo, Clock, CpiContext, Result, SolanaSysvar, ToAccountInfo,
+        msg, require, Account, AccountDeserialize, AccountInfo, AccountsExit, AnchorDeserialize,
+        AnchorSerialize, Clock, CpiContext, InterfaceAccount, Pubkey, Rent, Result, SolanaSysvar,
+        ToAccountInfo,
+    },
+    solana_program::{
+        ed25519_program, keccak,
+        sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    },
+    system_program, Discriminator, Space,
+};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id},
+    token,
+    token_2022::{
+        self,
+        spl_token_2022::{
+            extension::StateWithExtensions,
+            instruction::AuthorityType,
+            state::{Account as SplTokenAccount, AccountState},
+        },
+    },
+    token_interface::{
+        self, Burn, CloseAccount, Mint, MintTo, SetAuthority, TokenAccount, TransferChecked,
     },
-    solana_program::program::invoke_signed,
 };
-use anchor_spl::token::{self, Mint, MintTo, TokenAccount, Transfer};
 
-use mpl_token_metadata::instruction::create_metadata_accounts_v3;
+use mpl_token_metadata::{
+    instructions::{CreateMetadataAccountV3CpiBuilder, UpdateMetadataAccountV2CpiBuilder},
+    types::DataV2,
+};
 
+use crate::token_math::REWARD_PARTS_SCALING_FACTOR;
 use crate::{
-    account::BlocksState, context as SallarContext, error::SallarError,
-    token_math::calculate_max_bp, token_math::DUSTS_PER_BLOCK, MINT_SEED,
+    account::{
+        BlockHistory, BlockInvariants, BlockKind, BlockSolutionSimulation, BlockSolver,
+        BlockStateSummary, BlockUserRequests, BlocksState, BlocksStateV0, BlocksStateV3,
+        BoostCredits, Config, ConfigChange, Denylist, DistributionBackend,
+        FinalMiningClaimPeriodParticipants, FinalMiningEpoch, FinalMiningTier, FinalMiningTiers,
+        FinalStakingRegistration, FinalStakingRound, FinalStakingRoundParticipants,
+        FinalStakingTerminalMode, FinalStakingTier, InvariantsReport, ParticipationCommitment,
+        PendingChange, RecentSolvers, RestCarryover, SignerSet, SimulatedUserReward,
+        SolveBatchCursor, StakeLock, StateMirror, Subsystem, UserClaimAccount, UserRequests,
+        UserRewardStats,
+    },
+    context as SallarContext,
+    distribution::{default_distribution_backend, distribution_backend},
+    error::SallarError,
+    event::{
+        AuthorityChanged, BlockSolved, ConfigChanged, FinalMiningEpochOpened,
+        FinalStakingFinalized, RestCarriedOver, SlaBreached, SlaSubsystem, UserEntrySkipped,
+    },
+    token_math::{
+        allocate_block_reward, dust_per_bp_from_block_bp, valid_block_index, DustPerBp,
+        DUSTS_PER_BLOCK, FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR,
+        TOKEN_AMOUNT_SCALING_FACTOR,
+    },
+    token_math::{
+        calculate_bottom_block_max_boost, calculate_bottom_blocks_minted_amount,
+        calculate_dust_per_bp, calculate_max_bp, calculate_single_reward,
+        calculate_top_blocks_minted_amount, calculate_top_bp_with_boost,
+        INITIAL_TOKEN_DISTRIBUTION_AMOUNT, MAX_BLOCK_INDEX,
+    },
+    token_math::{
+        calculate_final_mining_reward, DEFAULT_FINAL_MINING_TIERS, MAX_FINAL_MINING_REWARD,
+    },
+    token_math::{
+        calculate_final_staking_reward, calculate_max_reward_part, calculate_snapshot_reward_part,
+    },
+    SolveBatchChunk, UserInfoFinalMining, BLOCKS_STATE_SEED, MINT_SEED, STAKE_VAULT_SEED,
 };
 use context::*;
-use SallarContext::InitializeContext;
+use SallarContext::{InitializeContext, UpdateTokenMetadataContext};
 
-const MIN_BLOCKS_SOLUTION_INTERVAL_SECONDS: i64 = 180;
-const MIN_FINAL_STAKING_SOLUTION_INTERVAL_SECONDS: i64 = 72_000;
+pub const DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS: i64 = 180;
+const MIN_BLOCK_SOLUTION_INTERVAL_SECONDS: i64 = 60;
+const MAX_BLOCK_SOLUTION_INTERVAL_SECONDS: i64 = 3_600;
+pub const DEFAULT_FINAL_STAKING_INTERVAL_SECONDS: i64 = 72_000;
+/// By default final mining can be called every 3 minutes, like block solutions.
+pub const DEFAULT_FINAL_MINING_INTERVAL_SECONDS: i64 = 180;
+/// By default a single final mining call can distribute at most the amount of tokens of a single block.
+pub const DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL: u64 = DUSTS_PER_BLOCK;
+/// By default the final mining budget (if set) applies to windows of 1 day.
+pub const DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS: i64 = 86_400;
+/// By default the terminal transition of final staking is performed once a regular round would distribute less than 1 Sallar.
+pub const DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND: u64 = 100_000_000;
+/// By default a user participates in bottom block solutions if the user's balance is at least 20_000 Sallar.
+pub const DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 =
+    2_000_000_000 * TOKEN_AMOUNT_SCALING_FACTOR;
+/// A single boost credit costs 1 Sallar burned from the user's token account.
+pub const BOOST_CREDIT_PRICE: u64 = 100_000_000;
+const MIN_FINAL_STAKING_INTERVAL_SECONDS: i64 = 3_600;
+const MAX_FINAL_STAKING_INTERVAL_SECONDS: i64 = 604_800;
+const MIN_FINAL_STAKING_POOL_DIVISION_FACTOR: u64 = 100;
+const MAX_FINAL_STAKING_POOL_DIVISION_FACTOR: u64 = 100_000;
+const MAX_FINAL_STAKING_MIN_POOL_IN_ROUND: u64 = DUSTS_PER_BLOCK;
+const MAX_MIN_PAYOUT_AMOUNT: u64 = 100_000_000;
+const MIN_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 = 100_000_000;
+const MAX_BOTTOM_BLOCK_MIN_REQUIRED_STAKE: u64 = 10 * DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE;
+const MAX_SIGNER_SET_SIZE: usize = 10;
+const MAX_DENYLIST_SIZE: usize = 50;
+const MAX_FINAL_STAKING_TIERS: usize = 8;
+const MAX_FINAL_MINING_TIERS: usize = 8;
+pub const BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS: u32 = 10_000;
+const MAX_FINAL_STAKING_TIER_MULTIPLIER_BPS: u32 = 100_000;
+/// The tolerance lets the reward parts cover the maximum final staking tier multiplier at most.
+const MAX_FINAL_STAKING_REWARD_PART_TOLERANCE_BPS: u32 =
+    MAX_FINAL_STAKING_TIER_MULTIPLIER_BPS - BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS;
+pub const MAX_REST_CARRYOVERS: usize = 8;
+const MAX_REWARD_FEE_BPS: u16 = 500;
+#[cfg(any(test, feature = "shadow-reads"))]
+const SHADOW_READ_REWARD_PARTS_TOLERANCE: u64 = 1_000;
+const MAX_TIMELOCK_DELAY_SECONDS: i64 = 2_592_000;
+const MAX_SLA_THRESHOLD_SECONDS: i64 = 2_592_000;
+const MAX_FINAL_MINING_INTERVAL_SECONDS: i64 = 86_400;
+const MAX_FINAL_MINING_BUDGET_WINDOW_SECONDS: i64 = 2_592_000;
+/// A final mining balance checkpoint is recent for about 3 minutes (the default final mining interval).
+const MAX_FINAL_MINING_CHECKPOINT_AGE_SLOTS: u64 = 450;
+const MAX_FINAL_MINING_CLAIM_PERIOD_SECONDS: i64 = 2_592_000;
+/// Anyone can open a final mining epoch without the participation root once the coordinator has been inactive for a day.
+pub const FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS: i64 = 86_400;
+/// The projection of 32 windows fits into the return data of the transaction.
+pub const MAX_FINAL_MINING_PROJECTION_WINDOWS: u16 = 32;
+pub const MAX_USERS_PER_SOLVE_BATCH_CHUNK: usize = 25;
+/// The current version of the layout of the blocks state, see migrate_state.
+pub const BLOCKS_STATE_VERSION: u8 = 4;
+/// The number of the last rewarded token accounts kept by the recent solvers account per block kind (the `max_len` of its buffers).
+pub const RECENT_SOLVERS_CAPACITY: usize = 32;
+/// The top block number the contract is initialized with on mainnet.
+pub const DEFAULT_FIRST_TOP_BLOCK_NUMBER: u64 = 1;
+/// The bottom block number the contract is initialized with on mainnet.
+pub const DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER: u64 = MAX_BLOCK_INDEX;
+/// The maximum number of user info entries of a single final_staking call, larger rounds are distributed by multiple calls.
+pub const MAX_USERS_PER_FINAL_STAKING_CALL: usize = 25;
+const MAX_FINAL_MINING_MAX_PAYOUT_PER_CALL: u64 = 10 * DUSTS_PER_BLOCK;
+/// 2023-01-01T00:00:00Z, before the program deployment
+const MIN_PLAUSIBLE_CLUSTER_TIMESTAMP: i64 = 1_672_531_200;
+/// 2050-01-01T00:00:00Z
+const MAX_PLAUSIBLE_CLUSTER_TIMESTAMP: i64 = 2_524_608_000;
 
 /// Transfers tokens between two accounts.
 ///
@@ -25,6 +147,7 @@ const MIN_FINAL_STAKING_SOLUTION_INTERVAL_SECONDS: i64 = 72_000;
 ///
 /// * `authority` - the authority that is going to transfer the tokens, it also the source account,
 /// * `to` - the destination account,
+/// * `mint` - the mint account of the transferred tokens,
 /// * `program_account_seed` - the seed of the program account,
 /// * `program_account` - the program account,
 /// * `program_account_nonce` - the nonce of the program account,
@@ -33,8 +156,9 @@ const MIN_FINAL_STAKING_SOLUTION_INTERVAL_SECONDS: i64 = 72_000;
 /// ### Returns
 /// The result of the transfer
 pub fn transfer_tokens<'a>(
-    authority: &Box<Account<'a, TokenAccount>>,
+    authority: &InterfaceAccount<'a, TokenAccount>,
     to: AccountInfo<'a>,
+    mint: &InterfaceAccount<'a, Mint>,
     program_account_seed: &'a str,
     program_account: AccountInfo<'a>,
     program_account_nonce: u8,
@@ -46,8 +170,9 @@ pub fn transfer_tokens<'a>(
     let from = authority.to_account_info();
     let authority = authority.to_account_info();
 
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from,
+        mint: mint.to_account_info(),
         to,
         authority,
     };
@@ -58,7 +183,183 @@ pub fn transfer_tokens<'a>(
         signer_seeds,
     );
 
-    token::transfer(cpi_ctx, amount)
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Transfers tokens from a token account held by the blocks state account, signed by the blocks state account.
+///
+/// ### Arguments
+///
+/// * `from` - the source token account, its authority is the blocks state account,
+/// * `to` - the destination account,
+/// * `mint` - the mint account of the transferred tokens,
+/// * `blocks_state_account` - the blocks state account,
+/// * `program_account` - the program account,
+/// * `blocks_state_nonce` - the nonce of the blocks state account,
+/// * `amount` - the amount of tokens to transfer.
+///
+/// ### Returns
+/// The result of the transfer
+pub fn transfer_tokens_from_blocks_state<'a>(
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: &InterfaceAccount<'a, Mint>,
+    blocks_state_account: AccountInfo<'a>,
+    program_account: AccountInfo<'a>,
+    blocks_state_nonce: u8,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[BLOCKS_STATE_SEED.as_bytes(), &[blocks_state_nonce]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from,
+        mint: mint.to_account_info(),
+        to,
+        authority: blocks_state_account,
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(program_account, cpi_accounts, signer_seeds);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Transfers tokens from a user's token account, signed by the owner of the token account.
+///
+/// ### Arguments
+///
+/// * `from` - the source token account of the user,
+/// * `to` - the destination account,
+/// * `mint` - the mint account of the transferred tokens,
+/// * `owner` - the owner of the source token account, it must be the signer of the transaction,
+/// * `program_account` - the program account,
+/// * `amount` - the amount of tokens to transfer.
+///
+/// ### Returns
+/// The result of the transfer
+pub fn transfer_tokens_from_user<'a>(
+    from: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: &InterfaceAccount<'a, Mint>,
+    owner: AccountInfo<'a>,
+    program_account: AccountInfo<'a>,
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from,
+        mint: mint.to_account_info(),
+        to,
+        authority: owner,
+    };
+
+    let cpi_ctx = CpiContext::new(program_account, cpi_accounts);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Burns tokens from a user's token account, signed by the owner of the token account.
+///
+/// ### Arguments
+///
+/// * `from` - the token account of the user the tokens are burned from,
+/// * `mint` - the mint account of the burned tokens,
+/// * `owner` - the owner of the token account, it must be the signer of the transaction,
+/// * `program_account` - the program account,
+/// * `amount` - the amount of tokens to burn.
+///
+/// ### Returns
+/// The result of the burn
+pub fn burn_tokens_from_user<'a>(
+    from: AccountInfo<'a>,
+    mint: &InterfaceAccount<'a, Mint>,
+    owner: AccountInfo<'a>,
+    program_account: AccountInfo<'a>,
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Burn {
+        mint: mint.to_account_info(),
+        from,
+        authority: owner,
+    };
+
+    let cpi_ctx = CpiContext::new(program_account, cpi_accounts);
+
+    token_interface::burn(cpi_ctx, amount)
+}
+
+/// Transfers tokens from the stake vault of a user, signed by the stake vault itself.
+///
+/// ### Arguments
+///
+/// * `stake_vault` - the stake vault of the user, it is its own authority,
+/// * `to` - the destination account,
+/// * `mint` - the mint account of the transferred tokens,
+/// * `user` - the token account the stake belongs to,
+/// * `stake_vault_bump` - the bump of the stake vault,
+/// * `program_account` - the program account,
+/// * `amount` - the amount of tokens to transfer.
+///
+/// ### Returns
+/// The result of the transfer
+pub fn transfer_tokens_from_stake_vault<'a>(
+    stake_vault: AccountInfo<'a>,
+    to: AccountInfo<'a>,
+    mint: &InterfaceAccount<'a, Mint>,
+    user: &Pubkey,
+    stake_vault_bump: u8,
+    program_account: AccountInfo<'a>,
+    amount: u64,
+) -> Result<()> {
+    let seeds = &[
+        STAKE_VAULT_SEED.as_bytes(),
+        user.as_ref(),
+        &[stake_vault_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = TransferChecked {
+        from: stake_vault.clone(),
+        mint: mint.to_account_info(),
+        to,
+        authority: stake_vault,
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(program_account, cpi_accounts, signer_seeds);
+
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Closes the token account owned by the program and transfers its lamports to the destination account.
+///
+/// ### Arguments
+///
+/// * `account` - the token account to close, it is also the authority of the account,
+/// * `destination` - the account receiving lamports of the closed account,
+/// * `program_account_seed` - the seed of the token account,
+/// * `program_account` - the program account,
+/// * `program_account_nonce` - the nonce of the token account.
+///
+/// ### Returns
+/// The result of the account closure
+pub fn close_token_account<'a>(
+    account: &InterfaceAccount<'a, TokenAccount>,
+    destination: AccountInfo<'a>,
+    program_account_seed: &'a str,
+    program_account: AccountInfo<'a>,
+    program_account_nonce: u8,
+) -> Result<()> {
+    let seeds = &[program_account_seed.as_bytes(), &[program_account_nonce]];
+    let signer_seeds = &[&seeds[..]];
+
+    let cpi_accounts = CloseAccount {
+        account: account.to_account_info(),
+        destination,
+        authority: account.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(program_account, cpi_accounts, signer_seeds);
+
+    token_interface::close_account(cpi_ctx)
 }
 
 /// Mints tokens to given account.
@@ -93,439 +394,4642 @@ pub fn mint_tokens<'a>(
 
     let cpi_ctx = CpiContext::new_with_signer(program_account, cpi_accounts, signer_seeds);
 
-    token::mint_to(cpi_ctx, amount)
+    token_interface::mint_to(cpi_ctx, amount)
 }
 
-/// Asserts that the signer is authorized to perform the action, i.e. if the signer is contract's owner.
+/// Removes the mint authority, so no more tokens can be minted.
 ///
 /// ### Arguments
 ///
-/// * `state` - the current state of the contract,
-/// * `signer` - the account which is the signer of the current transaction.
+/// * `mint` - the mint account, it is also the mint authority,
+/// * `program_account` - the program account,
+/// * `mint_nonce` - the nonce of the mint account.
 ///
 /// ### Returns
-/// An error if the signer is not an owner of the contract, otherwise a successful result.
-pub fn valid_owner(state: &BlocksState, signer: &AccountInfo) -> Result<()> {
-    require!(signer.key.eq(&state.authority), SallarError::Unauthorized);
+/// The result of the authority removal
+pub fn remove_mint_authority<'a>(
+    mint: AccountInfo<'a>,
+    program_account: AccountInfo<'a>,
+    mint_nonce: u8,
+) -> Result<()> {
+    let seeds = &[MINT_SEED.as_bytes(), &[mint_nonce]];
+    let signer_seeds = &[&seeds[..]];
 
-    Ok(())
+    let cpi_accounts = SetAuthority {
+        current_authority: mint.clone(),
+        account_or_mint: mint,
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(program_account, cpi_accounts, signer_seeds);
+
+    token_interface::set_authority(cpi_ctx, AuthorityType::MintTokens, None)
 }
 
-/// Asserts that the given account is a signer.
+/// Asserts that the given account can receive tokens, i.e. it is owned by the token program (or the Token-2022 program) and it is an initialized token account.
+/// It allows to fail fast with a meaningful error instead of an opaque token program failure in the middle of the batch.
 ///
 /// ### Arguments
 ///
-/// * `signer` - the account which is supposed to be a signer.
+/// * `account` - the account which is supposed to receive tokens.
 ///
 /// ### Returns
-/// An error if the account is not a signer, otherwise a successful result.
-pub fn valid_signer(signer: &AccountInfo) -> Result<()> {
-    require!(signer.is_signer, SallarError::Unauthorized);
+/// An error if the account is not an initialized token account, otherwise a successful result.
+pub fn recipient_account_initialized(account: &AccountInfo) -> Result<()> {
+    let initialized = (account.owner == &token::ID || account.owner == &token_2022::ID)
+        && StateWithExtensions::<SplTokenAccount>::unpack(&account.try_borrow_data()?)
+            .map(|token_account| token_account.base.state == AccountState::Initialized)
+            .unwrap_or(false);
+
+    if !initialized {
+        msg!("Recipient account not initialized: {}", account.key);
+        return err!(SallarError::RecipientAccountNotInitialized);
+    }
 
     Ok(())
 }
 
-/// Asserts that required time (3 minutes) passed since last block solution.
-/// It supports both: top and bottom blocks as both of them have require the same time interval between solved blocks.
+/// Asserts that the given token account holds tokens of the given mint.
+/// The account must be an initialized token account (see `recipient_account_initialized`).
 ///
 /// ### Arguments
 ///
-/// * `last_solved_block_timestamp` - timestamp of the moment when last block was solved (either top or bottom).
+/// * `account` - the token account which is supposed to receive tokens,
+/// * `mint` - the mint of the distributed tokens.
 ///
 /// ### Returns
-/// An error if less than 3 minutes passed since last block solution, otherwise a successful result.
-pub fn blocks_solution_required_interval_elapsed(last_solved_block_timestamp: &i64) -> Result<()> {
-    require!(
-        Clock::get()?.unix_timestamp - last_solved_block_timestamp
-            >= MIN_BLOCKS_SOLUTION_INTERVAL_SECONDS,
-        SallarError::BlockSolutionAheadOfTime
-    );
+/// An error if the account is not a token account of the mint, otherwise a successful result.
+pub fn recipient_account_of_mint(account: &AccountInfo, mint: &Pubkey) -> Result<()> {
+    let of_mint = StateWithExtensions::<SplTokenAccount>::unpack(&account.try_borrow_data()?)
+        .map(|token_account| token_account.base.mint == *mint)
+        .unwrap_or(false);
+
+    if !of_mint {
+        msg!("Recipient account mint mismatch: {}", account.key);
+        return err!(SallarError::RecipientAccountMintMismatch);
+    }
 
     Ok(())
 }
 
-/// Asserts that required time (20 hours) passed since last completed final staking.
+/// Asserts that the given token account is owned by the token program the distributed tokens are transferred with,
+/// so a token account of the other token program (Token or Token-2022) fails fast instead of failing the transfer.
 ///
 /// ### Arguments
 ///
-/// * `last_completed_final_staking_timestamp` - timestamp of the moment when last block final staking was completed.
+/// * `account` - the token account which is supposed to receive tokens,
+/// * `token_program` - the token program of the mint of the distributed tokens.
 ///
 /// ### Returns
-/// An error if less than 20 hours passed since last completed final staking, otherwise a successful result.
-pub fn final_staking_required_interval_elapsed(
-    last_completed_final_staking_timestamp: &i64,
+/// An error if the account is not owned by the token program, otherwise a successful result.
+pub fn recipient_account_of_token_program(
+    account: &AccountInfo,
+    token_program: &Pubkey,
 ) -> Result<()> {
-    require!(
-        Clock::get()?.unix_timestamp - last_completed_final_staking_timestamp
-            >= MIN_FINAL_STAKING_SOLUTION_INTERVAL_SECONDS,
-        SallarError::FinalStakingAheadOfTime
-    );
+    if account.owner != token_program {
+        msg!("Recipient account token program mismatch: {}", account.key);
+        return err!(SallarError::RecipientAccountTokenProgramMismatch);
+    }
 
     Ok(())
 }
 
-/// Asserts that blocks have collided, i.e. that `blocks_collided` attribute of the current `BlocksState` is set to true
+/// Asserts that the given token account is not controlled by the program (see `program_owned_token_account`),
+/// as rewards paid to the program's token accounts or to stake vaults would corrupt the balances tracked by the program.
+/// The account must be an initialized token account (see `recipient_account_initialized`).
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `account` - the token account which is supposed to receive tokens.
 ///
 /// ### Returns
-/// An error if blocks have not collided yet, otherwise a successful result.
-pub fn blocks_collided(state: &BlocksState) -> Result<()> {
-    require!(state.blocks_collided, SallarError::BlocksNotCollidedYet);
+/// An error if the account is controlled by the program, otherwise a successful result.
+pub fn recipient_not_program_token_account(config: &Config, account: &AccountInfo) -> Result<()> {
+    let program_owned = StateWithExtensions::<SplTokenAccount>::unpack(&account.try_borrow_data()?)
+        .map(|token_account| {
+            program_owned_token_account(config, account.key, &token_account.base.owner)
+        })
+        .unwrap_or_else(|_| program_token_account(config, account.key));
+
+    if program_owned {
+        msg!(
+            "Recipient account is a program token account: {}",
+            account.key
+        );
+        return err!(SallarError::RecipientIsProgramTokenAccount);
+    }
 
     Ok(())
 }
 
-/// Asserts that the current top block is not solved yet, i.e. it has some available BPs.
+/// Asserts that the given account can receive the distributed tokens, i.e. it is an initialized token account of the mint
+/// owned by the token program of the mint and it is not controlled by the program.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `account` - the account which is supposed to receive tokens,
+/// * `mint` - the mint of the distributed tokens,
+/// * `token_program` - the token program of the mint of the distributed tokens.
 ///
 /// ### Returns
-/// An error if top block is solved (has no available BPs), otherwise a successful result.
-pub fn top_block_not_solved(state: &BlocksState) -> Result<()> {
-    require!(
-        state.top_block_available_bp > 0,
-        SallarError::BlockAlreadySolved
-    );
+/// An error naming the account if it cannot receive the distributed tokens, otherwise a successful result.
+pub fn valid_recipient_account(
+    config: &Config,
+    account: &AccountInfo,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    recipient_account_initialized(account)?;
+    recipient_account_of_mint(account, mint)?;
+    recipient_account_of_token_program(account, token_program)?;
+    recipient_not_program_token_account(config, account)
+}
 
-    Ok(())
+/// Asserts up front, before any tokens are transferred, that all provided recipient accounts can receive the distributed tokens
+/// (see `valid_recipient_account`). Uninitialized accounts owned by the system program are skipped, as they may be created
+/// as associated token accounts during the batch (and are validated again before the transfer).
+///
+/// ### Arguments
+///
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `accounts` - the provided accounts (usually remaining accounts of the instruction),
+/// * `recipients` - the token accounts specified in the user info entries,
+/// * `mint` - the mint of the distributed tokens,
+/// * `token_program` - the token program of the mint of the distributed tokens.
+///
+/// ### Returns
+/// An error naming the first provided recipient account which cannot receive the distributed tokens, otherwise a successful result.
+pub fn valid_recipient_accounts<'a>(
+    config: &Config,
+    accounts: &[AccountInfo],
+    recipients: impl IntoIterator<Item = &'a Pubkey>,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    let recipients: Vec<&Pubkey> = recipients.into_iter().collect();
+
+    accounts
+        .iter()
+        .filter(|account| recipients.contains(&account.key))
+        .filter(|account| !(account.owner == &system_program::ID && account.data_is_empty()))
+        .try_for_each(|account| valid_recipient_account(config, account, mint, token_program))
 }
 
-/// Asserts that the current bottom block is not solved yet, i.e. it has some available BPs.
+/// Finds the account of the user among the provided accounts.
+/// If the position is given (positional pairing of user info entries and remaining accounts), only the account at this position is considered,
+/// so the accounts do not have to be scanned.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account specified in the user info,
+/// * `position` - the position of the user's account among the accounts (None if the accounts have to be searched).
 ///
 /// ### Returns
-/// An error if bottom block is solved (has no available BPs), otherwise a successful result.
-pub fn bottom_block_not_solved(state: &BlocksState) -> Result<()> {
+/// The account of the user if it is provided (at the given position), `None` otherwise.
+pub fn find_user_account<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    user: &Pubkey,
+    position: Option<usize>,
+) -> Option<&'a AccountInfo<'info>> {
+    match position {
+        Some(index) => accounts.get(index).filter(|account| account.key == user),
+        None => accounts.iter().find(|account| account.key == user),
+    }
+}
+
+/// Finds the recipient token account of the user info among the provided accounts and asserts that it can receive the distributed tokens.
+///
+/// ### Arguments
+///
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account specified in the user info,
+/// * `mint` - the mint of the distributed tokens,
+/// * `token_program` - the token program of the mint of the distributed tokens,
+/// * `position` - the position of the recipient account among the accounts (None if the accounts have to be searched).
+///
+/// ### Returns
+/// The recipient account if it is provided and it is an initialized token account of the mint owned by its token program, otherwise an error.
+pub fn find_recipient_account<'info>(
+    config: &Config,
+    accounts: &[AccountInfo<'info>],
+    user: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    position: Option<usize>,
+) -> Result<AccountInfo<'info>> {
+    let account = match find_user_account(accounts, user, position) {
+        Some(account) => account,
+        None if position.is_some() => return err!(SallarError::RecipientAccountPositionMismatch),
+        None => return err!(SallarError::MissingRecipientAccount),
+    };
+    valid_recipient_account(config, account, mint, token_program)?;
+
+    Ok(account.clone())
+}
+
+/// Accounts required to create the associated token accounts of the recipients.
+/// Consists of the following attributes:
+/// * `payer` - the account paying the rent of the created accounts (the signer of the transaction),
+/// * `mint` - the mint account of the distributed tokens,
+/// * `token_program` - the Solana token program account,
+/// * `associated_token_program` - the associated token program account (if provided),
+/// * `system_program` - the Solana system program account (if provided).
+pub struct AssociatedTokenAccountCreation<'info> {
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: Option<AccountInfo<'info>>,
+    pub system_program: Option<AccountInfo<'info>>,
+}
+
+/// Asserts that the recipient account of the user info is the associated token account of the given wallet and creates it if it does not exist yet.
+/// The wallet account must be provided among the accounts, as well as the associated token program and the system program, only if the account is created.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account specified in the user info,
+/// * `wallet` - the wallet owning the token account,
+/// * `position` - the position of the recipient account among the accounts (None if the accounts have to be searched),
+/// * `creation` - the accounts required to create the associated token account.
+///
+/// ### Returns
+/// A successful result if the recipient account is the associated token account of the wallet and it exists (or it has been created), otherwise an error.
+pub fn prepare_associated_token_account<'info>(
+    accounts: &[AccountInfo<'info>],
+    user: &Pubkey,
+    wallet: &Pubkey,
+    position: Option<usize>,
+    creation: &AssociatedTokenAccountCreation<'info>,
+) -> Result<()> {
     require!(
-        state.bottom_block_available_bp > 0,
-        SallarError::BlockAlreadySolved
+        *user
+            == get_associated_token_address_with_program_id(
+                wallet,
+                creation.mint.key,
+                creation.token_program.key
+            ),
+        SallarError::InvalidAssociatedTokenAccount
     );
+    let account = match find_user_account(accounts, user, position) {
+        Some(account) => account,
+        None if position.is_some() => return err!(SallarError::RecipientAccountPositionMismatch),
+        None => return err!(SallarError::MissingRecipientAccount),
+    };
+    if account.owner != &system_program::ID {
+        return Ok(());
+    }
 
-    Ok(())
+    let wallet_account = match accounts.iter().find(|account| account.key == wallet) {
+        Some(wallet_account) => wallet_account,
+        None => return err!(SallarError::MissingWalletAccount),
+    };
+    let (Some(associated_token_program), Some(system_program)) = (
+        creation.associated_token_program.as_ref(),
+        creation.system_program.as_ref(),
+    ) else {
+        return err!(SallarError::MissingAssociatedTokenProgram);
+    };
+
+    msg!("Creating recipient associated token account: {}", user);
+    associated_token::create_idempotent(CpiContext::new(
+        associated_token_program.clone(),
+        associated_token::Create {
+            payer: creation.payer.clone(),
+            associated_token: account.clone(),
+            authority: wallet_account.clone(),
+            mint: creation.mint.clone(),
+            system_program: system_program.clone(),
+            token_program: creation.token_program.clone(),
+        },
+    ))
 }
 
-/// Asserts that the both top block and bottom block are solved, i.e. they have no available BPs.
+/// Advances the cursor of the chunked block solution batch by the given chunk.
+/// The first chunk of a batch must start at index 0 (it abandons the batch in progress, if any),
+/// and every following chunk must start where the previous one ended, so duplicated or dropped chunks are rejected.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `cursor` - the progress of the batch in progress (None if no batch is in progress),
+/// * `chunk` - the chunk to process,
+/// * `chunk_len` - the number of user info entries of the chunk.
 ///
 /// ### Returns
-/// An error if either top block or bottom is not solved (any of them has available BPs), otherwise a successful result.
-pub fn blocks_solved(state: &BlocksState) -> Result<()> {
+/// The progress of the batch after processing the chunk (None if the batch is complete), or an error if the chunk does not continue the batch.
+pub fn advance_solve_batch_cursor(
+    cursor: Option<SolveBatchCursor>,
+    chunk: &SolveBatchChunk,
+    chunk_len: usize,
+) -> Result<Option<SolveBatchCursor>> {
     require!(
-        state.top_block_available_bp == 0,
-        SallarError::TopBlockNotSolvedYet
+        chunk_len <= MAX_USERS_PER_SOLVE_BATCH_CHUNK,
+        SallarError::SolveBatchChunkTooLarge
+    );
+
+    let expected_start_index = match cursor {
+        Some(cursor) if cursor.batch_id == chunk.batch_id => {
+            require!(
+                cursor.total_users == chunk.total_users,
+                SallarError::InvalidSolveBatchChunk
+            );
+            cursor.next_user_index
+        }
+        _ => 0,
+    };
+    require!(
+        chunk.start_index == expected_start_index,
+        SallarError::InvalidSolveBatchChunk
     );
+
+    let next_user_index = u64::from(chunk.start_index) + chunk_len as u64;
     require!(
-        state.bottom_block_available_bp == 0,
-        SallarError::BottomBlockNotSolvedYet
+        next_user_index <= u64::from(chunk.total_users),
+        SallarError::InvalidSolveBatchChunk
     );
 
-    Ok(())
+    if next_user_index == u64::from(chunk.total_users) {
+        return Ok(None);
+    }
+
+    Ok(Some(SolveBatchCursor {
+        batch_id: chunk.batch_id,
+        next_user_index: next_user_index as u32,
+        total_users: chunk.total_users,
+    }))
 }
 
-/// Sets `blocks_collided` attribute of `BlocksState` to true to mark blocks as collided.
-/// It happens only if blocks really collided, i.e. bottom block's number is great by 1 than top block's number.
+/// Asserts that the block requested by the user info is not solved yet, i.e. it has BPs available.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `available_bp` - the available BPs of the block.
 ///
 /// ### Returns
-/// A successful result.
-pub fn update_blocks_collided(state: &mut BlocksState) -> Result<()> {
-    if !can_block_be_switched(state) {
-        state.blocks_collided = true;
-    }
+/// An error if the block is already solved, otherwise a successful result.
+pub fn user_request_for_unsolved_block(available_bp: u64) -> Result<()> {
+    require!(available_bp > 0, SallarError::UserRequestForSolvedBlock);
 
     Ok(())
 }
 
-/// Asserts that initial_token_distribution function has not yet been successfully executed.
+/// Adds the rest of the user to the rest carryovers of the block, so it is paid at the start of the next block, and emits the `RestCarriedOver` event.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `rest_carryovers` - the rest carryovers of the block,
+/// * `block_kind` - the kind of the solved block,
+/// * `block_number` - the number of the solved block,
+/// * `user` - the token account of the user who did not receive all BPs,
+/// * `rest_bp` - the number of BPs the user did not receive,
+/// * `solve_batch_sequence` - the sequence number of the batch.
 ///
 /// ### Returns
-/// An error if the initial_token_distribution function has been already successfully executed, otherwise a successful result.
-pub fn initial_token_distribution_not_performed_yet(state: &BlocksState) -> Result<()> {
+/// An error if the rest carryovers are full, otherwise a successful result.
+pub fn push_rest_carryover(
+    rest_carryovers: &mut Vec<RestCarryover>,
+    block_kind: BlockKind,
+    block_number: u64,
+    user: Pubkey,
+    rest_bp: u64,
+    solve_batch_sequence: u64,
+) -> Result<()> {
     require!(
-        !state.initial_token_distribution_already_performed,
-        SallarError::InitialTokenDistributionAlreadyPerformed
+        rest_carryovers.len() < MAX_REST_CARRYOVERS,
+        SallarError::RestCarryoversLimitExceeded
     );
 
+    rest_carryovers.push(RestCarryover { user, rest_bp });
+    emit!(RestCarriedOver {
+        user,
+        block_kind,
+        block_number,
+        rest_bp,
+        solve_batch_sequence,
+    });
+
     Ok(())
 }
 
-/// Specifies if any block can be switched to the next one.
+/// Finds the user info entries settling the rest carryovers of the previous block.
+/// In the first call for a new block, the carryovers must be settled by the leading entries of the batch (in the order of the carryovers),
+/// otherwise (the block was switched within the call) the entries are searched among the already processed ones.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state).
+/// * `rest_carryovers` - the rest carryovers of the previous block,
+/// * `users` - the token accounts specified in the user info entries of the batch,
+/// * `processed_entries` - the number of user info entries of the batch processed so far.
 ///
 /// ### Returns
-/// True if current bottom block number is greater than by current top block number by at least 2, false otherwise.
-pub fn can_block_be_switched(state: &BlocksState) -> bool {
-    state.bottom_block_number - 1 > state.top_block_number
+/// The index of the user info entry of each rest carryover or an error if any carryover is not settled by the batch.
+pub fn find_rest_carryover_entries(
+    rest_carryovers: &[RestCarryover],
+    users: &[Pubkey],
+    processed_entries: usize,
+) -> Result<Vec<usize>> {
+    if processed_entries == 0 {
+        require!(
+            rest_carryovers.len() <= users.len()
+                && rest_carryovers
+                    .iter()
+                    .zip(users)
+                    .all(|(rest_carryover, user)| rest_carryover.user == *user),
+            SallarError::UserRestExistsButFirstRequestForNewBlockIsNotForThisAccount
+        );
+
+        return Ok((0..rest_carryovers.len()).collect());
+    }
+
+    rest_carryovers
+        .iter()
+        .map(|rest_carryover| {
+            users
+                .iter()
+                .take(processed_entries)
+                .position(|user| *user == rest_carryover.user)
+                .ok_or_else(|| {
+                    error!(SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount)
+                })
+        })
+        .collect()
 }
 
-/// Switches top block to the next one if the current one is already solved.
-/// It updates top block related attributes of `BlocksState`:
-/// - `top_block_solution_timestamp` to update timestamp of recently solved block to the current one,
-/// - `top_block_number` - sets next block's number (current block's number + 1),
-/// - `top_block_available_bp` - sets current block's BP to the max BP for the new current block (after switching its number),
-/// - `top_block_balance` - sets current block's balance to the max block's balance (an initial one).
-/// It also mints tokens to top block distribution account for the new block.
+/// Checks whether any user is specified more than once in the user info entries of a batch.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state),
-/// * `mint_nonce` - the nonce of mint account,
-/// * `mint` - reference to mint account,
-/// * `distribution_top_block_account` - reference to top block distribution account where new tokens will be minted,
-/// * `token_program` - the program account for the token being used.
+/// * `users` - the token accounts specified in the user info entries.
 ///
-/// ### Errors
-/// This function can return a `MismatchBetweenAvailableBlockBPAndBalance` error if the balance and the available block's BP of the bottom block do not match.
+/// ### Returns
+/// True if any user is duplicated, false otherwise.
+pub fn has_duplicated_users<'a>(users: impl Iterator<Item = &'a Pubkey>) -> bool {
+    let mut users = users.collect::<Vec<&Pubkey>>();
+    users.sort_unstable();
+    users.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+/// Calculates the leaf of the block rewards Merkle tree for the reward of a single user.
+///
+/// ### Arguments
+///
+/// * `user` - the token account receiving the reward,
+/// * `amount` - the reward of the user (including the reward fee).
 ///
 /// ### Returns
-/// A successful result.
-pub fn switch_top_block_to_next_one_if_applicable<'a>(
-    state: &mut BlocksState,
-    mint_nonce: u8,
-    mint: &Box<Account<'a, Mint>>,
-    distribution_top_block_account: AccountInfo<'a>,
-    token_program: AccountInfo<'a>,
-) -> Result<()> {
-    require!(
-        (state.top_block_balance == 0 && state.top_block_available_bp == 0)
-            || (state.top_block_balance > 0 && state.top_block_available_bp > 0),
-        SallarError::MismatchBetweenAvailableBlockBPAndBalance
-    );
+/// The Keccak-256 hash of the user's address followed by the little endian bytes of the amount.
+pub fn calculate_block_reward_leaf(user: &Pubkey, amount: u64) -> [u8; 32] {
+    keccak::hashv(&[user.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
 
-    if state.top_block_available_bp == 0 && can_block_be_switched(state) {
-        state.top_block_solution_timestamp = Clock::get()?.unix_timestamp;
-        state.top_block_number += 1;
+/// Calculates the leaf of the final mining participation Merkle tree for a single participant of the epoch.
+///
+/// ### Arguments
+///
+/// * `user` - the token account participating in the epoch,
+/// * `epoch_index` - the index of the final mining epoch.
+///
+/// ### Returns
+/// The Keccak-256 hash of the user's address followed by the little endian bytes of the epoch index.
+pub fn calculate_final_mining_participation_leaf(user: &Pubkey, epoch_index: u64) -> [u8; 32] {
+    keccak::hashv(&[user.as_ref(), &epoch_index.to_le_bytes()]).to_bytes()
+}
 
-        let authority = mint.to_account_info();
-        let mint_token_account = mint.to_account_info();
+/// Verifies the Merkle proof of a block rewards tree leaf.
+/// The pairs of nodes are hashed in sorted order, so the proof consists only of the sibling nodes from the leaf up to the root.
+///
+/// ### Arguments
+///
+/// * `proof` - the sibling nodes on the path from the leaf to the root,
+/// * `root` - the Merkle root of the block rewards,
+/// * `leaf` - the leaf to verify.
+///
+/// ### Returns
+/// True if the leaf belongs to the tree with the given root, false otherwise.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        }
+    });
 
-        mint_tokens(
-            authority,
-            distribution_top_block_account,
-            mint_token_account,
-            token_program,
-            mint_nonce,
-            DUSTS_PER_BLOCK,
-        )?;
+    computed_root == *root
+}
 
-        state.top_block_available_bp =
-            convert_f64_to_u64(calculate_max_bp(state.top_block_number)?)?;
-        state.top_block_balance = DUSTS_PER_BLOCK;
+/// Logs the entry of a distribution batch (a user info entry or a remaining account) the error occurred for,
+/// so the failing entry of the batch can be identified from the transaction logs.
+///
+/// ### Arguments
+///
+/// * `result` - the result of processing the entry,
+/// * `index` - the index of the entry in the batch,
+/// * `user` - the token account of the entry.
+///
+/// ### Returns
+/// The unchanged result.
+pub fn with_user_entry_context<T>(result: Result<T>, index: usize, user: &Pubkey) -> Result<T> {
+    result.map_err(|error| {
+        msg!("Batch entry {} ({}) failed: {}", index, user, error);
+        error
+    })
+}
+
+/// Marks the user info entry as skipped in the bitmask of skipped entries and emits the `UserEntrySkipped` event,
+/// so the entries skipped by a batch processed in the non-strict mode can be retried by the backend.
+///
+/// ### Arguments
+///
+/// * `skipped_user_entries` - the bitmask of skipped entries (the i-th entry is marked by the bit `i % 8` of the byte `i / 8`),
+/// * `block_kind` - the kind of the solved block,
+/// * `index` - the index of the skipped entry in the batch,
+/// * `user` - the token account of the skipped entry,
+/// * `error` - the error the entry is skipped for,
+/// * `solve_batch_sequence` - the sequence number of the batch.
+pub fn skip_user_entry(
+    skipped_user_entries: &mut Vec<u8>,
+    block_kind: BlockKind,
+    index: usize,
+    user: &Pubkey,
+    error: &Error,
+    solve_batch_sequence: u64,
+) {
+    if skipped_user_entries.len() <= index / 8 {
+        skipped_user_entries.resize(index / 8 + 1, 0);
     }
+    skipped_user_entries[index / 8] |= 1 << (index % 8);
 
-    Ok(())
+    msg!("Batch entry {} ({}) skipped", index, user);
+    emit!(UserEntrySkipped {
+        user: *user,
+        block_kind,
+        user_entry_index: index as u32,
+        error_code: match error {
+            Error::AnchorError(error) => error.error_code_number,
+            Error::ProgramError(_) => 0,
+        },
+        solve_batch_sequence,
+    });
 }
 
-/// Switches bottom block to the next one if the current one is already solved.
-/// It updates bottom block related attributes of `BlocksState`:
-/// - `bottom_block_solution_timestamp` to update timestamp of recently solved block to the current one,
-/// - `bottom_block_number` - sets next block's number (current block's number + 1),
-/// - `bottom_block_available_bp` - sets current block's BP to the max BP for the new current block (after switching its number),
-/// - `bottom_block_balance` - sets current block's balance to the max block's balance (an initial one).
-/// It also mints tokens to bottom block distribution account for the new block.
+/// Asserts that the signer is authorized to perform the action, i.e. if the signer is contract's owner.
 ///
 /// ### Arguments
 ///
-/// * `state` - contract's state (blocks state),
-/// * `mint_nonce` - the nonce of mint account,
-/// * `mint` - reference to mint account,
-/// * `distribution_bottom_block_account` - reference to bottom block distribution account where new tokens will be minted,
-/// * `token_program` - the program account for the token being used.
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction.
 ///
-/// ### Errors
-/// This function can return a `MismatchBetweenAvailableBlockBPAndBalance` error if the balance and the available block's BP of the bottom block do not match.
+/// ### Returns
+/// An error if the signer is not an owner of the contract, otherwise a successful result.
+pub fn valid_owner(state: &BlocksState, signer: &AccountInfo) -> Result<()> {
+    require!(signer.key.eq(&state.authority), SallarError::Unauthorized);
+
+    Ok(())
+}
+
+/// Asserts that the signer is the guardian of the contract.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction.
 ///
 /// ### Returns
-/// A successful result.
-pub fn switch_bottom_block_to_next_one_if_applicable<'a>(
-    state: &mut BlocksState,
-    mint_nonce: u8,
-    mint: &Box<Account<'a, Mint>>,
-    distribution_bottom_block_account: AccountInfo<'a>,
-    token_program: AccountInfo<'a>,
-) -> Result<()> {
+/// An error if no guardian is set or the signer is not the guardian, otherwise a successful result.
+pub fn valid_guardian(state: &BlocksState, signer: &AccountInfo) -> Result<()> {
     require!(
-        (state.bottom_block_balance == 0 && state.bottom_block_available_bp == 0)
-            || (state.bottom_block_balance > 0 && state.bottom_block_available_bp > 0),
-        SallarError::MismatchBetweenAvailableBlockBPAndBalance
+        state.guardian == Some(*signer.key),
+        SallarError::SignerIsNotGuardian
     );
 
-    if state.bottom_block_available_bp == 0 && can_block_be_switched(state) {
-        state.bottom_block_solution_timestamp = Clock::get()?.unix_timestamp;
-        state.bottom_block_number -= 1;
+    Ok(())
+}
 
-        let authority = mint.to_account_info();
-        let mint_token_account = mint.to_account_info();
+/// Asserts that the block solution batch is authorized, i.e. it is approved by the solve coordinator
+/// or the signer is an owner of the contract or the quorum of registered signers approved the transaction.
+/// The coordinator's approval is checked only if the coordinator is set and the instructions sysvar account is provided,
+/// so any relayer can submit the batch approved by the coordinator.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction,
+/// * `signer_set` - the signer set account, if provided,
+/// * `remaining_accounts` - the remaining accounts of the instruction where additional signers are searched,
+/// * `instructions_sysvar` - the instructions sysvar account, if provided,
+/// * `block_kind` - the kind of the solved block,
+/// * `users_info` - the user info entries of the batch.
+///
+/// ### Returns
+/// An error if the batch is neither approved by the coordinator nor by the owner or the signer quorum, otherwise a successful result.
+pub fn valid_solver<T: AnchorSerialize>(
+    state: &BlocksState,
+    signer: &AccountInfo,
+    signer_set: &Option<Account<SignerSet>>,
+    remaining_accounts: &[AccountInfo],
+    instructions_sysvar: &Option<AccountInfo>,
+    block_kind: BlockKind,
+    users_info: &[T],
+) -> Result<()> {
+    match (state.solve_coordinator, instructions_sysvar) {
+        (Some(solve_coordinator), Some(instructions_sysvar)) => coordinator_approval_verified(
+            instructions_sysvar,
+            &solve_coordinator,
+            &calculate_solve_batch_approval_message(state, block_kind, users_info)?,
+        ),
+        _ => valid_owner_or_signer_quorum(state, signer, signer_set, remaining_accounts),
+    }
+}
 
-        mint_tokens(
-            authority,
-            distribution_bottom_block_account,
-            mint_token_account,
-            token_program,
-            mint_nonce,
-            DUSTS_PER_BLOCK,
-        )?;
+/// Calculates the message the solve coordinator signs to approve a block solution batch.
+/// The message is bound to the program, the solved block and the sequence number the batch is assigned, so the approval cannot be replayed.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `block_kind` - the kind of the solved block,
+/// * `users_info` - the user info entries of the batch.
+///
+/// ### Returns
+/// The Keccak-256 hash of the program id, the block kind, the block number, the sequence number of the batch and the serialized user info entries.
+pub fn calculate_solve_batch_approval_message<T: AnchorSerialize>(
+    state: &BlocksState,
+    block_kind: BlockKind,
+    users_info: &[T],
+) -> Result<[u8; 32]> {
+    let block_number = match block_kind {
+        BlockKind::Top => state.top_block_number,
+        BlockKind::Bottom => state.bottom_block_number,
+    };
+    let users_info = users_info.try_to_vec()?;
 
-        state.bottom_block_available_bp =
-            convert_f64_to_u64(calculate_max_bp(state.bottom_block_number)?)?;
-        state.bottom_block_balance = DUSTS_PER_BLOCK;
+    Ok(keccak::hashv(&[
+        crate::ID.as_ref(),
+        &[block_kind as u8],
+        &block_number.to_le_bytes(),
+        &(state.solve_batch_sequence + 1).to_le_bytes(),
+        &users_info,
+    ])
+    .to_bytes())
+}
+
+/// Asserts that the signer can post the commitment of a block solution batch, i.e. it is the solve coordinator,
+/// or the instruction is approved either by the contract's owner or by the quorum of registered signers.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction,
+/// * `signer_set` - the signer set account, if provided,
+/// * `remaining_accounts` - the remaining accounts of the instruction where additional signers are searched.
+///
+/// ### Returns
+/// An error if the signer is not the solve coordinator and the instruction is not approved by the owner or the signer quorum, otherwise a successful result.
+pub fn valid_participation_committer(
+    state: &BlocksState,
+    signer: &AccountInfo,
+    signer_set: &Option<Account<SignerSet>>,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if signer.is_signer && state.solve_coordinator == Some(*signer.key) {
+        return Ok(());
     }
 
-    Ok(())
+    valid_owner_or_signer_quorum(state, signer, signer_set, remaining_accounts)
 }
 
-/// Converts a given `f64` value to an `u64` value and returns it as a result.
-/// Performs various checks to ensure that the conversion can be performed,
-/// i.e. provided `f64` number is in the range of `u64`.
-///
-/// The conversion is not safe in the context of precision
-/// so there is no guarantee that provided `f64` number will be exactly the same number
-/// as in `u64` after the conversion.
+/// Calculates the commitment of a block solution batch posted with the commit_block_participation instruction.
 ///
 /// ### Arguments
 ///
-/// * value - the f64 value to be converted to u64
+/// * `block_kind` - the kind of the solved block,
+/// * `block_number` - the number of the solved block,
+/// * `users_info` - the user info entries of the batch.
 ///
 /// ### Returns
+/// The Keccak-256 hash of the program id, the block kind, the block number and the serialized user info entries.
+pub fn calculate_block_participation_commitment<T: AnchorSerialize>(
+    block_kind: BlockKind,
+    block_number: u64,
+    users_info: &[T],
+) -> Result<[u8; 32]> {
+    let users_info = users_info.try_to_vec()?;
+
+    Ok(keccak::hashv(&[
+        crate::ID.as_ref(),
+        &[block_kind as u8],
+        &block_number.to_le_bytes(),
+        &users_info,
+    ])
+    .to_bytes())
+}
+
+/// Asserts that the user info entries of the block solution batch reveal the commitment posted for the block kind in an earlier slot.
+/// The check is skipped while the commit-reveal scheme is disabled.
 ///
-/// The result of the conversion if the input value is in the scope of `u64`, or an error otherwise.
-pub fn convert_f64_to_u64(value: f64) -> Result<u64> {
-    require!(value <= u64::MAX as f64, SallarError::U64ConversionError);
-    require!(value >= u64::MIN as f64, SallarError::U64ConversionError);
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `block_kind` - the kind of the solved block,
+/// * `users_info` - the user info entries of the batch.
+///
+/// ### Returns
+/// An error if the scheme is enabled and the commitment is missing, posted in the current slot or not matching the entries, otherwise a successful result.
+pub fn block_participation_revealed<T: AnchorSerialize>(
+    state: &BlocksState,
+    block_kind: BlockKind,
+    users_info: &[T],
+) -> Result<()> {
+    if !state.block_participation_commitment_enabled {
+        return Ok(());
+    }
 
-    Ok(value as u64)
+    let (commitment, block_number) = match block_kind {
+        BlockKind::Top => (
+            state.top_block_participation_commitment,
+            state.top_block_number,
+        ),
+        BlockKind::Bottom => (
+            state.bottom_block_participation_commitment,
+            state.bottom_block_number,
+        ),
+    };
+    participation_commitment_revealed(
+        commitment,
+        Clock::get()?.slot,
+        &calculate_block_participation_commitment(block_kind, block_number, users_info)?,
+    )
 }
 
-/// Converts a given `u64` value to an `f64` value and returns it as a result.
+/// Asserts that the commitment exists, was posted before the current slot and matches the revealed hash.
+fn participation_commitment_revealed(
+    commitment: Option<ParticipationCommitment>,
+    current_slot: u64,
+    revealed_hash: &[u8; 32],
+) -> Result<()> {
+    let commitment =
+        commitment.ok_or_else(|| error!(SallarError::MissingBlockParticipationCommitment))?;
+    require!(
+        commitment.slot < current_slot,
+        SallarError::BlockParticipationCommitmentTooRecent
+    );
+    require!(
+        commitment.hash == *revealed_hash,
+        SallarError::BlockParticipationCommitmentMismatch
+    );
+
+    Ok(())
+}
+
+/// Asserts that the instruction preceding the current one verifies the ed25519 signature of the message by the solve coordinator.
 ///
 /// ### Arguments
 ///
-/// * value - the u64 value to be converted to f64
+/// * `instructions_sysvar` - the instructions sysvar account,
+/// * `solve_coordinator` - the solve coordinator key,
+/// * `message` - the message the coordinator must sign.
 ///
 /// ### Returns
+/// An error if the preceding instruction is not an ed25519 program instruction verifying the coordinator's signature of the message, otherwise a successful result.
+pub fn coordinator_approval_verified(
+    instructions_sysvar: &AccountInfo,
+    solve_coordinator: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, SallarError::MissingCoordinatorApproval);
+    let instruction =
+        load_instruction_at_checked(usize::from(current_index - 1), instructions_sysvar)?;
+    require!(
+        instruction.program_id == ed25519_program::ID,
+        SallarError::MissingCoordinatorApproval
+    );
+    require!(
+        ed25519_instruction_verifies(&instruction.data, solve_coordinator, message),
+        SallarError::InvalidCoordinatorApproval
+    );
+
+    Ok(())
+}
+
+/// Checks whether the data of an ed25519 program instruction verifies exactly one signature of the message by the given key.
+/// The signature, the key and the message must be contained in the ed25519 program instruction itself.
 ///
-/// The result of the conversion.
+/// ### Arguments
 ///
-pub fn convert_u64_to_f64(value: u64) -> Result<f64> {
-    Ok(value as f64)
+/// * `data` - the data of the ed25519 program instruction,
+/// * `public_key` - the expected signing key,
+/// * `message` - the expected signed message.
+///
+/// ### Returns
+/// True if the instruction verifies the signature of the message by the key, false otherwise.
+pub fn ed25519_instruction_verifies(data: &[u8], public_key: &Pubkey, message: &[u8]) -> bool {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_END: usize = OFFSETS_START + 14;
+    const CURRENT_INSTRUCTION_INDEX: usize = u16::MAX as usize;
+
+    if data.len() < OFFSETS_END || data[0] != 1 {
+        return false;
+    }
+    let read_u16 =
+        |offset: usize| usize::from(u16::from_le_bytes([data[offset], data[offset + 1]]));
+    let signature_instruction_index = read_u16(OFFSETS_START + 2);
+    let public_key_offset = read_u16(OFFSETS_START + 4);
+    let public_key_instruction_index = read_u16(OFFSETS_START + 6);
+    let message_offset = read_u16(OFFSETS_START + 8);
+    let message_size = read_u16(OFFSETS_START + 10);
+    let message_instruction_index = read_u16(OFFSETS_START + 12);
+
+    signature_instruction_index == CURRENT_INSTRUCTION_INDEX
+        && public_key_instruction_index == CURRENT_INSTRUCTION_INDEX
+        && message_instruction_index == CURRENT_INSTRUCTION_INDEX
+        && data.get(public_key_offset..public_key_offset + 32) == Some(public_key.as_ref())
+        && data.get(message_offset..message_offset + message_size) == Some(message)
 }
 
-/// Sets token metadata
+/// Asserts that the privileged instruction is approved either by the contract's owner or by the quorum of registered signers.
+/// The quorum is reached if at least `threshold` of the signers registered in the signer set signed the transaction,
+/// i.e. they are either the signer of the instruction or they are provided as signing accounts in the remaining accounts.
 ///
 /// ### Arguments
 ///
-/// * `name` - token name
-/// * `symbol` - token symbol
-/// * `uri` - token uri
-pub fn set_token_metadata(
-    ctx: Context<InitializeContext>,
-    name: String,
-    symbol: String,
-    uri: String,
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction,
+/// * `signer_set` - the signer set account, if provided,
+/// * `remaining_accounts` - the remaining accounts of the instruction where additional signers are searched.
+///
+/// ### Returns
+/// An error if the signer is not an owner of the contract and the quorum of registered signers is not reached, otherwise a successful result.
+pub fn valid_owner_or_signer_quorum(
+    state: &BlocksState,
+    signer: &AccountInfo,
+    signer_set: &Option<Account<SignerSet>>,
+    remaining_accounts: &[AccountInfo],
 ) -> Result<()> {
-    let program_id = ctx.accounts.metadata_program.to_account_info();
-    let metadata_pda = ctx.accounts.metadata_pda.to_account_info();
-    let mint = ctx.accounts.mint.to_account_info();
-    let mint_authority = ctx.accounts.mint.to_account_info();
-    let payer = ctx.accounts.signer.to_account_info();
-    let update_authority = ctx.accounts.mint.to_account_info();
-    let system_program = ctx.accounts.system_program.to_account_info();
-
-    let seeds = &[
-        MINT_SEED.as_bytes(),
-        &[ctx.accounts.blocks_state_account.mint_nonce],
-    ];
+    if signer.key.eq(&state.authority) {
+        return Ok(());
+    }
 
-    let account_infos = &[
-        program_id.clone(),
-        metadata_pda.clone(),
-        mint.clone(),
-        mint_authority.clone(),
-        payer.clone(),
-        update_authority.clone(),
-        system_program.clone(),
-    ];
+    let signer_set = match signer_set {
+        Some(signer_set) => signer_set,
+        None => return err!(SallarError::Unauthorized),
+    };
 
-    let create_metadata_accounts_instruction = create_metadata_accounts_v3(
-        *program_id.key,
-        *metadata_pda.key,
-        *mint.key,
-        *mint_authority.key,
-        *payer.key,
-        *update_authority.key,
-        name.clone(),
-        symbol.clone(),
-        uri.clone(),
-        None,
-        0u16,
-        false,
-        true,
-        None,
-        None,
-        None,
-    );
-
-    invoke_signed(
-        &create_metadata_accounts_instruction,
-        account_infos,
-        &[seeds],
-    )?;
+    let approvals = signer_set
+        .signers
+        .iter()
+        .filter(|registered_signer| {
+            (signer.is_signer && signer.key == *registered_signer)
+                || remaining_accounts
+                    .iter()
+                    .any(|account| account.is_signer && account.key == *registered_signer)
+        })
+        .count();
+    require!(
+        approvals >= signer_set.threshold as usize,
+        SallarError::SignerQuorumNotReached
+    );
 
     Ok(())
 }
 
-#[cfg(test)]
-mod test {
-    use anchor_lang::err;
-    use anchor_lang::prelude::AccountInfo;
-    use anchor_lang::prelude::Pubkey;
+/// Asserts that the signer set is valid, i.e. it does not exceed the maximum number of signers (10),
+/// it does not contain duplicated signers and the threshold is between 1 and the number of signers.
+///
+/// ### Arguments
+///
+/// * `signers` - the signers to register,
+/// * `threshold` - the number of signers required to approve the instruction.
+///
+/// ### Returns
+/// An error if the signer set is not valid, otherwise a successful result.
+pub fn valid_signer_set(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        signers.len() <= MAX_SIGNER_SET_SIZE,
+        SallarError::SignerSetTooLarge
+    );
+    require!(
+        threshold > 0 && threshold as usize <= signers.len(),
+        SallarError::InvalidSignerSetThreshold
+    );
 
-    use super::*;
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    for (index, signer) in signers.iter().enumerate() {
+        require!(
+            !signers[index + 1..].contains(signer),
+            SallarError::SignerDuplicatedInSignerSet
+        );
+    }
 
-    impl PartialEq for BlocksState {
-        fn eq(&self, other: &Self) -> bool {
-            self.top_block_number == other.top_block_number
-                && self.block_state_nonce == other.block_state_nonce
-                && self.top_block_balance == other.top_block_balance
-                && self.top_block_available_bp == other.top_block_available_bp
-                && self.top_block_solution_timestamp == other.top_block_solution_timestamp
-                && self.bottom_block_number == other.bottom_block_number
-                && self.bottom_block_balance == other.bottom_block_balance
-                && self.bottom_block_available_bp == other.bottom_block_available_bp
-                && self.bottom_block_solution_timestamp == other.bottom_block_solution_timestamp
-                && self.blocks_collided == other.blocks_collided
-                && self.initial_token_distribution_already_performed
-                    == other.initial_token_distribution_already_performed
-        }
+    Ok(())
+}
+
+/// Asserts that the recipient is not denylisted.
+///
+/// ### Arguments
+///
+/// * `denylist` - the denylist to check (if provided),
+/// * `recipient` - the token account receiving tokens.
+///
+/// ### Returns
+/// An error if the recipient is denylisted, otherwise a successful result.
+pub fn recipient_not_denylisted(denylist: Option<&Denylist>, recipient: &Pubkey) -> Result<()> {
+    if let Some(denylist) = denylist {
+        require!(
+            !denylist.token_accounts.contains(recipient),
+            SallarError::RecipientDenylisted
+        );
     }
 
-    impl std::fmt::Debug for BlocksState {
+    Ok(())
+}
+
+/// Adds the token account to the denylist.
+///
+/// ### Arguments
+///
+/// * `denylist` - the denylist to update,
+/// * `token_account` - the token account to add.
+///
+/// ### Returns
+/// An error if the token account is already denylisted or the denylist is full, otherwise a successful result.
+pub fn add_to_denylist(denylist: &mut Denylist, token_account: Pubkey) -> Result<()> {
+    require!(
+        !denylist.token_accounts.contains(&token_account),
+        SallarError::AccountAlreadyDenylisted
+    );
+    require!(
+        denylist.token_accounts.len() < MAX_DENYLIST_SIZE,
+        SallarError::DenylistFull
+    );
+
+    denylist.token_accounts.push(token_account);
+
+    Ok(())
+}
+
+/// Removes the token account from the denylist.
+///
+/// ### Arguments
+///
+/// * `denylist` - the denylist to update,
+/// * `token_account` - the token account to remove.
+///
+/// ### Returns
+/// An error if the token account is not denylisted, otherwise a successful result.
+pub fn remove_from_denylist(denylist: &mut Denylist, token_account: &Pubkey) -> Result<()> {
+    let index = denylist
+        .token_accounts
+        .iter()
+        .position(|denylisted| denylisted == token_account)
+        .ok_or(SallarError::AccountNotDenylisted)?;
+
+    denylist.token_accounts.remove(index);
+
+    Ok(())
+}
+
+/// Asserts that the final staking tiers are valid, i.e. there are at most 8 tiers ordered by strictly increasing non-negative minimum balance ages
+/// and their multipliers are between 1x and 10x.
+///
+/// ### Arguments
+///
+/// * `tiers` - the final staking tiers.
+///
+/// ### Returns
+/// An error if the tiers are invalid, otherwise a successful result.
+pub fn valid_final_staking_tiers(tiers: &[FinalStakingTier]) -> Result<()> {
+    require!(
+        tiers.len() <= MAX_FINAL_STAKING_TIERS
+            && tiers.iter().all(|tier| {
+                tier.min_balance_age_seconds >= 0
+                    && (BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS
+                        ..=MAX_FINAL_STAKING_TIER_MULTIPLIER_BPS)
+                        .contains(&tier.multiplier_bps)
+            })
+            && tiers
+                .windows(2)
+                .all(|pair| pair[0].min_balance_age_seconds < pair[1].min_balance_age_seconds),
+        SallarError::InvalidFinalStakingTiers
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final mining tiers are valid, i.e. there are between 1 and 8 tiers, the first one starting at zero balance,
+/// ordered by strictly increasing minimum final mining balances and their rewards are positive and do not exceed the maximum final mining reward.
+///
+/// ### Arguments
+///
+/// * `tiers` - the final mining tiers.
+///
+/// ### Returns
+/// An error if the tiers are invalid, otherwise a successful result.
+pub fn valid_final_mining_tiers(tiers: &[FinalMiningTier]) -> Result<()> {
+    require!(
+        (1..=MAX_FINAL_MINING_TIERS).contains(&tiers.len())
+            && tiers[0].min_final_mining_balance == 0
+            && tiers
+                .iter()
+                .all(|tier| (1..=MAX_FINAL_MINING_REWARD).contains(&tier.reward))
+            && tiers
+                .windows(2)
+                .all(|pair| pair[0].min_final_mining_balance < pair[1].min_final_mining_balance),
+        SallarError::InvalidFinalMiningTiers
+    );
+
+    Ok(())
+}
+
+/// Returns the final mining tiers in force, i.e. the tiers of the final mining tiers account if it is provided or the default tiers otherwise.
+///
+/// ### Arguments
+///
+/// * `final_mining_tiers_account` - the final mining tiers account provided to the instruction (if any).
+///
+/// ### Returns
+/// The final mining tiers ordered by the minimum final mining balance.
+pub fn final_mining_tiers(
+    final_mining_tiers_account: Option<&FinalMiningTiers>,
+) -> &[FinalMiningTier] {
+    final_mining_tiers_account.map_or(&DEFAULT_FINAL_MINING_TIERS, |final_mining_tiers_account| {
+        &final_mining_tiers_account.tiers
+    })
+}
+
+/// Returns the multiplier of the highest final staking tier the user with the given balance age belongs to.
+///
+/// ### Arguments
+///
+/// * `tiers` - the final staking tiers ordered by the minimum balance age,
+/// * `balance_age_seconds` - the balance age of the user.
+///
+/// ### Returns
+/// The multiplier in basis points, 10_000 (1x) if the user does not belong to any tier.
+pub fn final_staking_tier_multiplier_bps(
+    tiers: &[FinalStakingTier],
+    balance_age_seconds: i64,
+) -> u32 {
+    tiers
+        .iter()
+        .rev()
+        .find(|tier| tier.min_balance_age_seconds <= balance_age_seconds)
+        .map_or(BASE_FINAL_STAKING_TIER_MULTIPLIER_BPS, |tier| {
+            tier.multiplier_bps
+        })
+}
+
+/// Asserts that the signer is the pending authority proposed by the contract's owner.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `signer` - the account which is the signer of the current transaction.
+///
+/// ### Returns
+/// An error if there is no pending authority or the signer is not the pending authority, otherwise a successful result.
+pub fn valid_pending_authority(state: &BlocksState, signer: &AccountInfo) -> Result<()> {
+    let pending_authority = match state.pending_authority {
+        Some(pending_authority) => pending_authority,
+        None => return err!(SallarError::MissingPendingAuthority),
+    };
+    require!(
+        signer.key.eq(&pending_authority),
+        SallarError::SignerIsNotPendingAuthority
+    );
+
+    Ok(())
+}
+
+/// Asserts that the given account is a signer.
+///
+/// ### Arguments
+///
+/// * `signer` - the account which is supposed to be a signer.
+///
+/// ### Returns
+/// An error if the account is not a signer, otherwise a successful result.
+pub fn valid_signer(signer: &AccountInfo) -> Result<()> {
+    require!(signer.is_signer, SallarError::Unauthorized);
+
+    Ok(())
+}
+
+/// Asserts that the cluster timestamp is in the plausible range, i.e. it is not earlier than the program deployment
+/// and it is not decades in the future, so a corrupted clock sysvar (e.g. on a misconfigured test validator) is not used to gate intervals.
+///
+/// ### Arguments
+///
+/// * `timestamp` - the cluster timestamp to validate.
+///
+/// ### Returns
+/// An error if the cluster timestamp is out of the plausible range, otherwise a successful result.
+pub fn valid_cluster_timestamp(timestamp: i64) -> Result<()> {
+    require!(
+        (MIN_PLAUSIBLE_CLUSTER_TIMESTAMP..=MAX_PLAUSIBLE_CLUSTER_TIMESTAMP).contains(&timestamp),
+        SallarError::InvalidClusterTime
+    );
+
+    Ok(())
+}
+
+/// Returns the current cluster timestamp read from the clock sysvar once it is validated to be in the plausible range.
+///
+/// ### Returns
+/// The current cluster timestamp or an error if it is out of the plausible range.
+pub fn current_cluster_timestamp() -> Result<i64> {
+    let timestamp = Clock::get()?.unix_timestamp;
+    valid_cluster_timestamp(timestamp)?;
+
+    Ok(timestamp)
+}
+
+/// Asserts that the batch is not stale, i.e. it is processed not later than the maximum valid slot specified by the submitter.
+/// It prevents long-delayed retries from being applied against a materially changed block or round state.
+///
+/// ### Arguments
+///
+/// * `max_valid_slot` - the last slot the batch can be processed in, no bound if not provided.
+///
+/// ### Returns
+/// An error if the current slot is greater than the maximum valid slot, otherwise a successful result.
+pub fn batch_not_stale(max_valid_slot: Option<u64>) -> Result<()> {
+    match max_valid_slot {
+        Some(max_valid_slot) => slot_not_after(Clock::get()?.slot, max_valid_slot),
+        None => Ok(()),
+    }
+}
+
+/// Asserts that the current slot does not exceed the maximum valid slot.
+fn slot_not_after(current_slot: u64, max_valid_slot: u64) -> Result<()> {
+    require!(current_slot <= max_valid_slot, SallarError::BatchExpired);
+
+    Ok(())
+}
+
+/// Asserts that required time (block solution interval stored in the state, 3 minutes by default) passed since last block solution.
+/// It supports both: top and bottom blocks as both of them have require the same time interval between solved blocks.
+///
+/// ### Arguments
+///
+/// * `last_solved_block_timestamp` - timestamp of the moment when last block was solved (either top or bottom),
+/// * `block_solution_interval_seconds` - the required time between solutions of consecutive blocks.
+///
+/// ### Returns
+/// An error if less than the block solution interval passed since last block solution, otherwise a successful result.
+pub fn blocks_solution_required_interval_elapsed(
+    last_solved_block_timestamp: &i64,
+    block_solution_interval_seconds: &i64,
+) -> Result<()> {
+    require!(
+        current_cluster_timestamp()? - last_solved_block_timestamp
+            >= *block_solution_interval_seconds,
+        SallarError::BlockSolutionAheadOfTime
+    );
+
+    Ok(())
+}
+
+/// Asserts that required time (final staking interval stored in the state, 20 hours by default) passed since last completed final staking.
+///
+/// ### Arguments
+///
+/// * `last_completed_final_staking_timestamp` - timestamp of the moment when last block final staking was completed,
+/// * `final_staking_interval_seconds` - the required time between consecutive final staking rounds.
+///
+/// ### Returns
+/// An error if less than the final staking interval passed since last completed final staking, otherwise a successful result.
+pub fn final_staking_required_interval_elapsed(
+    last_completed_final_staking_timestamp: &i64,
+    final_staking_interval_seconds: &i64,
+) -> Result<()> {
+    require!(
+        current_cluster_timestamp()? - last_completed_final_staking_timestamp
+            >= *final_staking_interval_seconds,
+        SallarError::FinalStakingAheadOfTime
+    );
+
+    Ok(())
+}
+
+/// Asserts that the global statistics account is provided if it is created, so the statistics do not miss any distribution.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `global_stats_account` - the global statistics account provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the global statistics account is created but not provided, otherwise a successful result.
+pub fn global_stats_provided<T>(
+    state: &BlocksState,
+    global_stats_account: &Option<T>,
+) -> Result<()> {
+    require!(
+        !state.global_stats_initialized || global_stats_account.is_some(),
+        SallarError::MissingGlobalStatsAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that the recent solvers account is provided if it is created, so the recent solvers do not miss any block solution.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `recent_solvers_account` - the recent solvers account provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the recent solvers account is created but not provided, otherwise a successful result.
+pub fn recent_solvers_provided<T>(
+    state: &BlocksState,
+    recent_solvers_account: &Option<T>,
+) -> Result<()> {
+    require!(
+        !state.recent_solvers_initialized || recent_solvers_account.is_some(),
+        SallarError::MissingRecentSolversAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that the denylist account is provided if it is created, so denylisted token accounts cannot receive tokens
+/// by leaving the denylist out of the instruction.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `denylist_account` - the denylist account provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the denylist account is created but not provided, otherwise a successful result.
+pub fn denylist_provided<T>(state: &BlocksState, denylist_account: &Option<T>) -> Result<()> {
+    require!(
+        !state.denylist_initialized || denylist_account.is_some(),
+        SallarError::MissingDenylistAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that the state mirror account is provided if it is created, so the mirror does not fall behind the blocks state.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `state_mirror_account` - the state mirror account provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the state mirror account is created but not provided, otherwise a successful result.
+pub fn state_mirror_provided<T>(
+    state: &BlocksState,
+    state_mirror_account: &Option<T>,
+) -> Result<()> {
+    require!(
+        !state.state_mirror_initialized || state_mirror_account.is_some(),
+        SallarError::MissingStateMirrorAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final mining tiers account is provided if it is created, so the rewards are not calculated from the default tiers.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_mining_tiers_account` - the final mining tiers account provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the final mining tiers account is created but not provided, otherwise a successful result.
+pub fn final_mining_tiers_provided<T>(
+    state: &BlocksState,
+    final_mining_tiers_account: &Option<T>,
+) -> Result<()> {
+    require!(
+        !state.final_mining_tiers_initialized || final_mining_tiers_account.is_some(),
+        SallarError::MissingFinalMiningTiersAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that the block history account and the system program are provided while block history is enabled,
+/// so every batch of the solved block is recorded.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `block_history_account` - the block history account provided to the instruction (if any),
+/// * `system_program` - the system program provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if block history is enabled but the block history account or the system program is not provided, otherwise a successful result.
+pub fn block_history_provided<T, U>(
+    state: &BlocksState,
+    block_history_account: &Option<T>,
+    system_program: &Option<U>,
+) -> Result<()> {
+    require!(
+        !state.block_history_enabled
+            || (block_history_account.is_some() && system_program.is_some()),
+        SallarError::MissingBlockHistoryAccount
+    );
+
+    Ok(())
+}
+
+/// Appends the solvers of a block solution batch to the block history account.
+/// The account is reallocated to fit the new solvers and the payer covers the additional rent.
+///
+/// ### Arguments
+///
+/// * `block_history_account` - the history account of the solved block,
+/// * `block_kind` - the kind of the solved block,
+/// * `block_number` - the number of the solved block,
+/// * `bump` - the bump of the block history account,
+/// * `solvers` - the solvers processed by the batch,
+/// * `payer` - the account paying the rent of the reallocated block history account,
+/// * `system_program` - the Solana system program account.
+///
+/// ### Returns
+/// A successful result, or an error if the rent cannot be paid or the account cannot be reallocated.
+pub fn append_block_history<'info>(
+    block_history_account: &mut Account<'info, BlockHistory>,
+    block_kind: BlockKind,
+    block_number: u64,
+    bump: u8,
+    solvers: Vec<BlockSolver>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let required_space = SallarContext::DISCRIMINATOR_LENGTH
+        + BlockHistory::INIT_SPACE
+        + (block_history_account.solvers.len() + solvers.len()) * BlockSolver::INIT_SPACE;
+    grow_account(
+        block_history_account.to_account_info(),
+        required_space,
+        payer,
+        system_program,
+    )?;
+
+    block_history_account.bump = bump;
+    block_history_account.block_kind = block_kind;
+    block_history_account.block_number = block_number;
+    block_history_account.solvers.extend(solvers);
+
+    Ok(())
+}
+
+/// Copies the mirrored fields of the blocks state (the current blocks and the final staking round) to the state mirror account.
+///
+/// ### Arguments
+///
+/// * `state_mirror` - the state mirror account,
+/// * `state` - contract's state (blocks state).
+pub fn sync_state_mirror(state_mirror: &mut StateMirror, state: &BlocksState) {
+    state_mirror.top_block_number = state.top_block_number;
+    state_mirror.top_block_available_bp = state.top_block_available_bp;
+    state_mirror.top_block_balance = state.top_block_balance;
+    state_mirror.bottom_block_number = state.bottom_block_number;
+    state_mirror.bottom_block_available_bp = state.bottom_block_available_bp;
+    state_mirror.bottom_block_balance = state.bottom_block_balance;
+    state_mirror.blocks_collided = state.blocks_collided;
+    state_mirror.final_staking_round_counter = state.final_staking_round_counter;
+    state_mirror.final_staking_pool_in_round = state.final_staking_pool_in_round;
+    state_mirror.final_staking_left_balance_in_round = state.final_staking_left_balance_in_round;
+    state_mirror.final_staking_last_staking_timestamp = state.final_staking_last_staking_timestamp;
+    state_mirror.final_staking_finalized = state.final_staking_finalized;
+}
+
+/// Records the token accounts rewarded by a block solution batch in the ring buffer of the recent solvers of the block kind.
+/// Once the buffer is full, the oldest token account is overwritten. A token account rewarded again right after itself
+/// (e.g. the account receiving the rest of its BP in the next block) is recorded once.
+///
+/// ### Arguments
+///
+/// * `recent_solvers` - the recent solvers account,
+/// * `block_kind` - the kind of the solved block,
+/// * `solvers` - the solvers processed by the batch in the order of processing.
+pub fn record_recent_solvers(
+    recent_solvers: &mut RecentSolvers,
+    block_kind: BlockKind,
+    solvers: &[BlockSolver],
+) {
+    let (buffer, next_index) = match block_kind {
+        BlockKind::Top => (
+            &mut recent_solvers.top_block_solvers,
+            &mut recent_solvers.top_block_next_index,
+        ),
+        BlockKind::Bottom => (
+            &mut recent_solvers.bottom_block_solvers,
+            &mut recent_solvers.bottom_block_next_index,
+        ),
+    };
+
+    for solver in solvers {
+        let last_index =
+            (usize::from(*next_index) + RECENT_SOLVERS_CAPACITY - 1) % RECENT_SOLVERS_CAPACITY;
+        if buffer.get(last_index) == Some(&solver.user) {
+            continue;
+        }
+
+        if buffer.len() < RECENT_SOLVERS_CAPACITY {
+            buffer.push(solver.user);
+        } else {
+            buffer[usize::from(*next_index)] = solver.user;
+        }
+        *next_index = ((usize::from(*next_index) + 1) % RECENT_SOLVERS_CAPACITY) as u8;
+    }
+}
+
+/// Deserializes the blocks state account being migrated by migrate_state, before it is resized to the current layout.
+/// The layout of the state is recognized by the size of the account: a state of the deployed layout (version 0) is deserialized
+/// as the frozen `BlocksStateV0`, a state of versions 1 to 3 as the frozen `BlocksStateV3` (the accounts of version 1 lack 64 bytes
+/// of the reserved space, they are read as zeros) and a state of the current layout as `BlocksState`.
+/// The states of the frozen layouts are mapped to the current layout field by field (see blocks_state_from_v0 and blocks_state_from_v3).
+///
+/// ### Arguments
+///
+/// * `data` - the data of the blocks state account.
+///
+/// ### Returns
+/// The blocks state of the current layout with the version of the deserialized state, or an error if the data is not a blocks state.
+pub fn deserialize_blocks_state_for_migration(data: &[u8]) -> Result<BlocksState> {
+    let space = data
+        .len()
+        .saturating_sub(SallarContext::DISCRIMINATOR_LENGTH);
+    if space == BlocksState::INIT_SPACE {
+        return BlocksState::try_deserialize(&mut &data[..]);
+    }
+
+    require!(
+        data.len() >= SallarContext::DISCRIMINATOR_LENGTH
+            && data[..SallarContext::DISCRIMINATOR_LENGTH] == BlocksState::DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let mut state_data = data[SallarContext::DISCRIMINATOR_LENGTH..].to_vec();
+    if space == BlocksStateV0::INIT_SPACE {
+        let state = BlocksStateV0::deserialize(&mut &state_data[..])
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        return blocks_state_from_v0(state);
+    }
+
+    state_data.resize(space.max(BlocksStateV3::INIT_SPACE), 0);
+    let state = BlocksStateV3::deserialize(&mut &state_data[..])
+        .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+
+    Ok(blocks_state_from_v3(state))
+}
+
+/// Maps the blocks state of versions 1 to 3 of the layout to the current layout field by field,
+/// the nonces and the addresses duplicated in `Config` are dropped. The state is left at its version,
+/// so the remaining steps of migrate_blocks_state are applied to it.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state) of versions 1 to 3 of the layout.
+///
+/// ### Returns
+/// The blocks state of the current layout.
+pub fn blocks_state_from_v3(state: BlocksStateV3) -> BlocksState {
+    BlocksState {
+        authority: state.authority,
+        pending_authority: state.pending_authority,
+        initial_token_distribution_already_performed: state
+            .initial_token_distribution_already_performed,
+        blocks_collided: state.blocks_collided,
+        top_block_number: state.top_block_number,
+        top_block_available_bp: state.top_block_available_bp,
+        top_block_solution_timestamp: state.top_block_solution_timestamp,
+        top_block_balance: state.top_block_balance,
+        top_block_last_account_address: state.top_block_last_account_address,
+        top_block_last_account_rest_bp: state.top_block_last_account_rest_bp,
+        bottom_block_number: state.bottom_block_number,
+        bottom_block_available_bp: state.bottom_block_available_bp,
+        bottom_block_solution_timestamp: state.bottom_block_solution_timestamp,
+        bottom_block_balance: state.bottom_block_balance,
+        bottom_block_last_account_address: state.bottom_block_last_account_address,
+        bottom_block_last_account_rest_bp: state.bottom_block_last_account_rest_bp,
+        final_staking_pool_in_round: state.final_staking_pool_in_round,
+        final_staking_last_staking_timestamp: state.final_staking_last_staking_timestamp,
+        final_staking_left_reward_parts_in_round: state.final_staking_left_reward_parts_in_round,
+        final_staking_left_balance_in_round: state.final_staking_left_balance_in_round,
+        min_payout_amount: state.min_payout_amount,
+        top_block_pending_payouts: state.top_block_pending_payouts,
+        bottom_block_pending_payouts: state.bottom_block_pending_payouts,
+        treasury_address: state.treasury_address,
+        paused: state.paused,
+        admin_controls_renounced: state.admin_controls_renounced,
+        timelock_delay: state.timelock_delay,
+        block_solution_interval_seconds: state.block_solution_interval_seconds,
+        final_staking_interval_seconds: state.final_staking_interval_seconds,
+        final_staking_pool_division_factor: state.final_staking_pool_division_factor,
+        final_staking_left_reward_parts_in_round_scaled: state
+            .final_staking_left_reward_parts_in_round_scaled,
+        reward_fee_bps: state.reward_fee_bps,
+        sla_threshold_seconds: state.sla_threshold_seconds,
+        top_block_max_solution_delay: state.top_block_max_solution_delay,
+        bottom_block_max_solution_delay: state.bottom_block_max_solution_delay,
+        final_staking_max_round_delay: state.final_staking_max_round_delay,
+        guardian: state.guardian,
+        top_block_halted: state.top_block_halted,
+        bottom_block_halted: state.bottom_block_halted,
+        final_mining_interval_seconds: state.final_mining_interval_seconds,
+        final_mining_max_payout_per_call: state.final_mining_max_payout_per_call,
+        final_mining_last_timestamp: state.final_mining_last_timestamp,
+        final_staking_min_pool_in_round: state.final_staking_min_pool_in_round,
+        final_staking_terminal_mode: state.final_staking_terminal_mode,
+        final_staking_final_round: state.final_staking_final_round,
+        final_staking_finalized: state.final_staking_finalized,
+        final_staking_round_counter: state.final_staking_round_counter,
+        global_stats_initialized: state.global_stats_initialized,
+        block_history_enabled: state.block_history_enabled,
+        solve_batch_sequence: state.solve_batch_sequence,
+        top_block_solve_batch_cursor: state.top_block_solve_batch_cursor,
+        top_block_unclaimed_root_rewards: state.top_block_unclaimed_root_rewards,
+        solve_coordinator: state.solve_coordinator,
+        bottom_block_min_required_stake: state.bottom_block_min_required_stake,
+        top_block_max_user_requests: state.top_block_max_user_requests,
+        bottom_block_max_user_requests: state.bottom_block_max_user_requests,
+        top_block_rest_carryovers: state.top_block_rest_carryovers,
+        bottom_block_rest_carryovers: state.bottom_block_rest_carryovers,
+        block_participation_commitment_enabled: state.block_participation_commitment_enabled,
+        top_block_participation_commitment: state.top_block_participation_commitment,
+        bottom_block_participation_commitment: state.bottom_block_participation_commitment,
+        boost_credits_required: state.boost_credits_required,
+        final_staking_unclaimed_root_rewards: state.final_staking_unclaimed_root_rewards,
+        final_staking_round_start_timestamp: state.final_staking_round_start_timestamp,
+        final_staking_snapshots_required: state.final_staking_snapshots_required,
+        final_staking_registered_balance_next_round: state
+            .final_staking_registered_balance_next_round,
+        final_staking_registered_balance_in_round: state.final_staking_registered_balance_in_round,
+        final_staking_carried_remainder: state.final_staking_carried_remainder,
+        final_staking_reward_part_tolerance_bps: state.final_staking_reward_part_tolerance_bps,
+        final_mining_tiers_initialized: state.final_mining_tiers_initialized,
+        final_mining_budget_per_window: state.final_mining_budget_per_window,
+        final_mining_budget_window_seconds: state.final_mining_budget_window_seconds,
+        final_mining_budget_window_start: state.final_mining_budget_window_start,
+        final_mining_budget_window_distributed: state.final_mining_budget_window_distributed,
+        final_mining_checkpoint_required: state.final_mining_checkpoint_required,
+        final_mining_checkpoint_balance: state.final_mining_checkpoint_balance,
+        final_mining_checkpoint_slot: state.final_mining_checkpoint_slot,
+        final_mining_claim_period_seconds: state.final_mining_claim_period_seconds,
+        final_mining_epoch_counter: state.final_mining_epoch_counter,
+        final_mining_epoch_timestamp: state.final_mining_epoch_timestamp,
+        top_block_rounding_remainder: state.top_block_rounding_remainder,
+        bottom_block_rounding_remainder: state.bottom_block_rounding_remainder,
+        top_block_max_bp: state.top_block_max_bp,
+        top_block_bp_with_boost: state.top_block_bp_with_boost,
+        bottom_block_max_bp: state.bottom_block_max_bp,
+        bottom_block_boost: state.bottom_block_boost,
+        version: state.version,
+        recent_solvers_initialized: state.recent_solvers_initialized,
+        first_top_block_number: state.first_top_block_number,
+        first_bottom_block_number: state.first_bottom_block_number,
+        initial_token_distribution_amount: state.initial_token_distribution_amount,
+        state_mirror_initialized: state.state_mirror_initialized,
+        denylist_initialized: false,
+        _reserved: [0; 101],
+    }
+}
+
+/// Maps the blocks state of the deployed layout (version 0) to the current layout field by field.
+/// The fields introduced by the later layouts are set as by the initialize instruction, the deprecated rest BP of the last accounts
+/// are carried over as the rest carryovers and the nonces and the addresses duplicated in `Config` are dropped.
+/// The state is left at version 0, so all the steps of migrate_blocks_state are applied to it.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state) of the deployed layout.
+///
+/// ### Returns
+/// The blocks state of the current layout, or an error if the final staking reward parts cannot be scaled.
+pub fn blocks_state_from_v0(state: BlocksStateV0) -> Result<BlocksState> {
+    let rest_carryovers = |address: Option<Pubkey>, rest_bp: u64| match address {
+        Some(user) if rest_bp > 0 => vec![RestCarryover { user, rest_bp }],
+        _ => Vec::new(),
+    };
+    let final_staking_left_reward_parts_in_round_scaled = convert_f64_to_u64(
+        (state.final_staking_left_reward_parts_in_round * REWARD_PARTS_SCALING_FACTOR as f64)
+            .round(),
+    )?;
+
+    Ok(BlocksState {
+        authority: state.authority,
+        pending_authority: None,
+        initial_token_distribution_already_performed: state
+            .initial_token_distribution_already_performed,
+        blocks_collided: state.blocks_collided,
+        top_block_number: state.top_block_number,
+        top_block_available_bp: state.top_block_available_bp,
+        top_block_solution_timestamp: state.top_block_solution_timestamp,
+        top_block_balance: state.top_block_balance,
+        top_block_last_account_address: state.top_block_last_account_address,
+        top_block_last_account_rest_bp: state.top_block_last_account_rest_bp,
+        bottom_block_number: state.bottom_block_number,
+        bottom_block_available_bp: state.bottom_block_available_bp,
+        bottom_block_solution_timestamp: state.bottom_block_solution_timestamp,
+        bottom_block_balance: state.bottom_block_balance,
+        bottom_block_last_account_address: state.bottom_block_last_account_address,
+        bottom_block_last_account_rest_bp: state.bottom_block_last_account_rest_bp,
+        final_staking_pool_in_round: state.final_staking_pool_in_round,
+        final_staking_last_staking_timestamp: state.final_staking_last_staking_timestamp,
+        final_staking_left_reward_parts_in_round: state.final_staking_left_reward_parts_in_round,
+        final_staking_left_balance_in_round: state.final_staking_left_balance_in_round,
+        min_payout_amount: 0,
+        top_block_pending_payouts: 0,
+        bottom_block_pending_payouts: 0,
+        treasury_address: None,
+        paused: false,
+        admin_controls_renounced: false,
+        timelock_delay: 0,
+        block_solution_interval_seconds: DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS,
+        final_staking_interval_seconds: DEFAULT_FINAL_STAKING_INTERVAL_SECONDS,
+        final_staking_pool_division_factor:
+            FINAL_STAKING_ACCOUNT_BALANCE_PART_FOR_STAKING_DIVISION_FACTOR,
+        final_staking_left_reward_parts_in_round_scaled,
+        reward_fee_bps: 0,
+        sla_threshold_seconds: 0,
+        top_block_max_solution_delay: 0,
+        bottom_block_max_solution_delay: 0,
+        final_staking_max_round_delay: 0,
+        guardian: None,
+        top_block_halted: false,
+        bottom_block_halted: false,
+        final_mining_interval_seconds: DEFAULT_FINAL_MINING_INTERVAL_SECONDS,
+        final_mining_max_payout_per_call: DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL,
+        final_mining_last_timestamp: 0,
+        final_staking_min_pool_in_round: DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND,
+        final_staking_terminal_mode: FinalStakingTerminalMode::DistributeRemaining,
+        final_staking_final_round: false,
+        final_staking_finalized: false,
+        final_staking_round_counter: 0,
+        global_stats_initialized: false,
+        block_history_enabled: false,
+        solve_batch_sequence: 0,
+        top_block_solve_batch_cursor: None,
+        top_block_unclaimed_root_rewards: 0,
+        solve_coordinator: None,
+        bottom_block_min_required_stake: DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+        top_block_max_user_requests: 0,
+        bottom_block_max_user_requests: 0,
+        top_block_rest_carryovers: rest_carryovers(
+            state.top_block_last_account_address,
+            state.top_block_last_account_rest_bp,
+        ),
+        bottom_block_rest_carryovers: rest_carryovers(
+            state.bottom_block_last_account_address,
+            state.bottom_block_last_account_rest_bp,
+        ),
+        block_participation_commitment_enabled: false,
+        top_block_participation_commitment: None,
+        bottom_block_participation_commitment: None,
+        boost_credits_required: false,
+        final_staking_unclaimed_root_rewards: 0,
+        final_staking_round_start_timestamp: 0,
+        final_staking_snapshots_required: false,
+        final_staking_registered_balance_next_round: 0,
+        final_staking_registered_balance_in_round: 0,
+        final_staking_carried_remainder: 0,
+        final_staking_reward_part_tolerance_bps: None,
+        final_mining_tiers_initialized: false,
+        final_mining_budget_per_window: None,
+        final_mining_budget_window_seconds: DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS,
+        final_mining_budget_window_start: 0,
+        final_mining_budget_window_distributed: 0,
+        final_mining_checkpoint_required: false,
+        final_mining_checkpoint_balance: 0,
+        final_mining_checkpoint_slot: 0,
+        final_mining_claim_period_seconds: 0,
+        final_mining_epoch_counter: 0,
+        final_mining_epoch_timestamp: 0,
+        top_block_rounding_remainder: 0,
+        bottom_block_rounding_remainder: 0,
+        top_block_max_bp: 0,
+        top_block_bp_with_boost: 0,
+        bottom_block_max_bp: 0,
+        bottom_block_boost: 0,
+        version: 0,
+        recent_solvers_initialized: false,
+        first_top_block_number: DEFAULT_FIRST_TOP_BLOCK_NUMBER,
+        first_bottom_block_number: DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER,
+        initial_token_distribution_amount: INITIAL_TOKEN_DISTRIBUTION_AMOUNT,
+        state_mirror_initialized: false,
+        denylist_initialized: false,
+        _reserved: [0; 101],
+    })
+}
+
+/// Upgrades the blocks state to the current version of its layout, one version at a time.
+/// The fields appended by a version are read either from the zero-filled space the account was grown by
+/// or from the bytes left beyond the serialized state of the previous layout, so every step sets them explicitly.
+///
+/// Version 0 is the layout the contract was deployed with. The fields of the later layouts were inserted in the middle of it as well,
+/// so a state of version 0 is not deserialized as `BlocksState` but mapped to it from `BlocksStateV0` (see blocks_state_from_v0).
+/// Version 1 introduces the version and the reserved space. The rounding remainders and the cached parameters of the current blocks
+/// are reset and the parameters are cached again.
+/// Version 2 extends the reserved space to 128 bytes, the whole space is zero-filled. The fields taken from the reserved space since then
+/// (`recent_solvers_initialized`, `state_mirror_initialized`, `denylist_initialized`) are zero-filled as well, so they do not require a new version.
+/// Version 3 takes the first block numbers and the initial token distribution amount from the reserved space, the deployed states were initialized with the mainnet ones.
+/// Version 4 drops the nonces and the addresses duplicated in `Config` (the block state, mint, distribution, final staking and final mining ones),
+/// the states of the previous versions are mapped from their frozen layouts without them, so the instructions read them only from `Config`.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state) deserialized from the migrated account (see deserialize_blocks_state_for_migration).
+///
+/// ### Returns
+/// A successful result, or an error if the state is already at the current version.
+pub fn migrate_blocks_state(state: &mut BlocksState) -> Result<()> {
+    require!(
+        state.version < BLOCKS_STATE_VERSION,
+        SallarError::BlocksStateAlreadyMigrated
+    );
+
+    if state.version < 1 {
+        state.top_block_rounding_remainder = 0;
+        state.bottom_block_rounding_remainder = 0;
+        cache_top_block_params(state)?;
+        cache_bottom_block_params(state)?;
+    }
+    if state.version < 2 {
+        state.recent_solvers_initialized = false;
+        state._reserved = [0; 101];
+    }
+    if state.version < 3 {
+        state.first_top_block_number = DEFAULT_FIRST_TOP_BLOCK_NUMBER;
+        state.first_bottom_block_number = DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER;
+        state.initial_token_distribution_amount = INITIAL_TOKEN_DISTRIBUTION_AMOUNT;
+    }
+    if state.version < 4 {
+        // the duplicated fields are dropped by the mapping from the frozen layout, the account shrinks by their size
+        state._reserved = [0; 101];
+    }
+    state.version = BLOCKS_STATE_VERSION;
+
+    Ok(())
+}
+
+/// Reallocates the account to the required space (if it is smaller) and the payer covers the additional rent.
+///
+/// ### Arguments
+///
+/// * `account_info` - the account to reallocate,
+/// * `required_space` - the space the account must fit in,
+/// * `payer` - the account paying the rent of the reallocated account,
+/// * `system_program` - the Solana system program account.
+///
+/// ### Returns
+/// A successful result, or an error if the rent cannot be paid or the account cannot be reallocated.
+pub fn grow_account<'info>(
+    account_info: AccountInfo<'info>,
+    required_space: usize,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    if account_info.data_len() < required_space {
+        let required_lamports = Rent::get()?
+            .minimum_balance(required_space)
+            .saturating_sub(account_info.lamports());
+        if required_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program,
+                    system_program::Transfer {
+                        from: payer,
+                        to: account_info.clone(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+        account_info.realloc(required_space, false)?;
+    }
+
+    Ok(())
+}
+
+/// Asserts that the block user requests account and the system program are provided while the per-user requests cap of the block kind is set,
+/// so the requests of every batch of the solved block are counted.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `block_kind` - the kind of the solved block,
+/// * `block_user_requests_account` - the block user requests account provided to the instruction (if any),
+/// * `system_program` - the system program provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the cap is set but the block user requests account or the system program is not provided, otherwise a successful result.
+pub fn block_user_requests_provided<T, U>(
+    state: &BlocksState,
+    block_kind: BlockKind,
+    block_user_requests_account: &Option<T>,
+    system_program: &Option<U>,
+) -> Result<()> {
+    let max_user_requests = match block_kind {
+        BlockKind::Top => state.top_block_max_user_requests,
+        BlockKind::Bottom => state.bottom_block_max_user_requests,
+    };
+    require!(
+        max_user_requests == 0
+            || (block_user_requests_account.is_some() && system_program.is_some()),
+        SallarError::MissingBlockUserRequestsAccount
+    );
+
+    Ok(())
+}
+
+/// Adds the requests of the user rewarded in the block to the block user requests and asserts that the user does not exceed the per-user requests cap of the block.
+///
+/// ### Arguments
+///
+/// * `block_user_requests` - the requests of the users rewarded in the block so far,
+/// * `block_number` - the number of the block the user is rewarded in,
+/// * `user` - the rewarded user,
+/// * `requests` - the number of requests (with and without boost) the user is rewarded for,
+/// * `max_user_requests` - the per-user requests cap of the block (0 disables the cap).
+///
+/// ### Returns
+/// An error if the block user requests belong to another block or the user exceeds the cap, otherwise a successful result.
+pub fn record_user_requests(
+    block_user_requests: &mut BlockUserRequests,
+    block_number: u64,
+    user: &Pubkey,
+    requests: u16,
+    max_user_requests: u16,
+) -> Result<()> {
+    require!(
+        block_user_requests.block_number == block_number,
+        SallarError::BlockUserRequestsAccountMismatch
+    );
+
+    let user_requests = block_user_requests
+        .users
+        .iter_mut()
+        .find(|user_requests| user_requests.user == *user);
+    let total_requests = user_requests
+        .as_ref()
+        .map_or(0, |user_requests| user_requests.requests)
+        .saturating_add(requests);
+    require!(
+        max_user_requests == 0 || total_requests <= max_user_requests,
+        SallarError::UserRequestsPerBlockExceeded
+    );
+
+    match user_requests {
+        Some(user_requests) => user_requests.requests = total_requests,
+        None => block_user_requests.users.push(UserRequests {
+            user: *user,
+            requests: total_requests,
+        }),
+    }
+
+    Ok(())
+}
+
+/// Reallocates the block user requests account to fit all of its users, the payer covers the additional rent.
+///
+/// ### Arguments
+///
+/// * `block_user_requests_account` - the block user requests account of the solved block,
+/// * `payer` - the account paying the rent of the reallocated account,
+/// * `system_program` - the Solana system program account.
+///
+/// ### Returns
+/// A successful result, or an error if the rent cannot be paid or the account cannot be reallocated.
+pub fn fit_block_user_requests<'info>(
+    block_user_requests_account: &Account<'info, BlockUserRequests>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let required_space = SallarContext::DISCRIMINATOR_LENGTH
+        + BlockUserRequests::INIT_SPACE
+        + block_user_requests_account.users.len() * UserRequests::INIT_SPACE;
+
+    grow_account(
+        block_user_requests_account.to_account_info(),
+        required_space,
+        payer,
+        system_program,
+    )
+}
+
+/// Asserts that final staking is not finalized, i.e. the terminal transition of the final staking pool has not been completed yet.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if final staking is finalized, otherwise a successful result.
+pub fn final_staking_not_finalized(state: &BlocksState) -> Result<()> {
+    require!(
+        !state.final_staking_finalized,
+        SallarError::FinalStakingFinalized
+    );
+
+    Ok(())
+}
+
+/// Calculates the amount of tokens transferred to a user in the current final staking round and the remainder of the round carried to the next round.
+/// Rewards are rounded down, so once the reward parts of the round are used up, the balance left in the round is carried to the prize pool of the next round
+/// instead of being paid to the last user, except for the final round which distributes the whole remaining balance.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `reward_part` - the part of the round's prize pool of the user in scaled reward parts,
+/// * `reward_parts_after_user` - the reward parts left in the round after the user.
+///
+/// ### Returns
+/// The amount transferred to the user and the remainder carried to the next round, or an error if the reward exceeds the balance left in the round.
+pub fn calculate_final_staking_transfer(
+    state: &BlocksState,
+    reward_part: u64,
+    reward_parts_after_user: u64,
+) -> Result<(u64, u64)> {
+    if reward_parts_after_user == 0 && state.final_staking_final_round {
+        return Ok((state.final_staking_left_balance_in_round, 0));
+    }
+
+    let transfer_amount =
+        calculate_final_staking_reward(reward_part, state.final_staking_pool_in_round)?;
+    let left_balance_after_user = state
+        .final_staking_left_balance_in_round
+        .checked_sub(transfer_amount)
+        .ok_or_else(|| error!(SallarError::LackOfFundsToPayTheReward))?;
+
+    if reward_parts_after_user == 0 {
+        Ok((transfer_amount, left_balance_after_user))
+    } else {
+        Ok((transfer_amount, 0))
+    }
+}
+
+/// Asserts that a final staking round started by the final_staking instruction is in progress, i.e. its prize pool is not distributed yet.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if no final staking round is in progress, otherwise a successful result.
+pub fn final_staking_round_in_progress(state: &BlocksState) -> Result<()> {
+    require!(
+        state.final_staking_left_balance_in_round > 0,
+        SallarError::FinalStakingRoundNotInProgress
+    );
+
+    Ok(())
+}
+
+/// Checks whether the final staking pool can no longer fund a meaningful round, i.e. the prize pool of a regular round would be below the minimum pool in round.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `pool_in_round` - the prize pool of the regular final staking round.
+///
+/// ### Returns
+/// True if the terminal transition should be performed instead of the regular round, false otherwise.
+pub fn final_staking_terminal_transition_required(state: &BlocksState, pool_in_round: u64) -> bool {
+    pool_in_round < state.final_staking_min_pool_in_round
+}
+
+/// Completes the terminal transition of final staking, so final staking cannot be invoked anymore, and emits the `FinalStakingFinalized` event.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `amount` - the remaining final staking account balance distributed in the final round or swept to the treasury.
+///
+/// ### Returns
+/// A successful result, or an error if the cluster timestamp cannot be read.
+pub fn finalize_final_staking(state: &mut BlocksState, amount: u64) -> Result<()> {
+    state.final_staking_final_round = false;
+    state.final_staking_finalized = true;
+
+    emit!(FinalStakingFinalized {
+        terminal_mode: state.final_staking_terminal_mode,
+        amount,
+        timestamp: current_cluster_timestamp()?,
+    });
+
+    Ok(())
+}
+
+/// Returns the index of the final staking round the next final_staking call distributes in,
+/// i.e. the current round if its prize pool is not distributed yet, otherwise the next one.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The index of the final staking round.
+pub fn final_staking_round_index(state: &BlocksState) -> u64 {
+    if state.final_staking_left_balance_in_round == 0 {
+        state.final_staking_round_counter + 1
+    } else {
+        state.final_staking_round_counter
+    }
+}
+
+/// Asserts that the final staking round participants account and the system program are provided while final staking snapshots are not required,
+/// so the users rewarded by every final_staking call of the round are tracked (snapshot registrations track the rewarded users otherwise).
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_staking_round_participants_account` - the final staking round participants account provided to the instruction (if any),
+/// * `system_program` - the system program provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if final staking snapshots are not required but the final staking round participants account or the system program is not provided, otherwise a successful result.
+pub fn final_staking_round_participants_provided<T, U>(
+    state: &BlocksState,
+    final_staking_round_participants_account: &Option<T>,
+    system_program: &Option<U>,
+) -> Result<()> {
+    require!(
+        state.final_staking_snapshots_required
+            || (final_staking_round_participants_account.is_some() && system_program.is_some()),
+        SallarError::MissingFinalStakingRoundParticipantsAccount
+    );
+
+    Ok(())
+}
+
+/// Adds the user rewarded in the final staking round to the round participants and asserts that the user has not been rewarded in the round yet.
+///
+/// ### Arguments
+///
+/// * `round_participants` - the users rewarded in the round so far,
+/// * `round_index` - the index of the round the user is rewarded in,
+/// * `user` - the rewarded user.
+///
+/// ### Returns
+/// An error if the round participants belong to another round or the user has already been rewarded in the round, otherwise a successful result.
+pub fn record_final_staking_participant(
+    round_participants: &mut FinalStakingRoundParticipants,
+    round_index: u64,
+    user: &Pubkey,
+) -> Result<()> {
+    require!(
+        round_participants.round_index == round_index,
+        SallarError::FinalStakingRoundParticipantsAccountMismatch
+    );
+    require!(
+        !round_participants.users.contains(user),
+        SallarError::FinalStakingParticipantAlreadyRewarded
+    );
+    round_participants.users.push(*user);
+
+    Ok(())
+}
+
+/// Reallocates the final staking round participants account to fit all of its users, the payer covers the additional rent.
+///
+/// ### Arguments
+///
+/// * `final_staking_round_participants_account` - the final staking round participants account of the current round,
+/// * `payer` - the account paying the rent of the reallocated account,
+/// * `system_program` - the Solana system program account.
+///
+/// ### Returns
+/// A successful result, or an error if the rent cannot be paid or the account cannot be reallocated.
+pub fn fit_final_staking_round_participants<'info>(
+    final_staking_round_participants_account: &Account<'info, FinalStakingRoundParticipants>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let required_space = SallarContext::DISCRIMINATOR_LENGTH
+        + FinalStakingRoundParticipants::INIT_SPACE
+        + final_staking_round_participants_account.users.len() * std::mem::size_of::<Pubkey>();
+
+    grow_account(
+        final_staking_round_participants_account.to_account_info(),
+        required_space,
+        payer,
+        system_program,
+    )
+}
+
+/// Records the progress of the current final staking round made by a final_staking call in the round's snapshot account.
+/// The round is marked as completed once its whole prize pool is distributed.
+///
+/// ### Arguments
+///
+/// * `final_staking_round` - the snapshot account of the current round,
+/// * `bump` - the bump of the final staking round account,
+/// * `state` - contract's state (blocks state) after the call,
+/// * `participants_processed` - the number of user info entries rewarded by the call,
+/// * `distributed_amount` - the amount of tokens distributed by the call.
+pub fn record_final_staking_round(
+    final_staking_round: &mut FinalStakingRound,
+    bump: u8,
+    state: &BlocksState,
+    participants_processed: u64,
+    distributed_amount: u64,
+) -> Result<()> {
+    final_staking_round.bump = bump;
+    final_staking_round.round_index = state.final_staking_round_counter;
+    final_staking_round.pool_in_round = state.final_staking_pool_in_round;
+    final_staking_round.participants_processed = final_staking_round
+        .participants_processed
+        .checked_add(participants_processed)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    final_staking_round.distributed_amount = final_staking_round
+        .distributed_amount
+        .checked_add(distributed_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+    if state.final_staking_left_balance_in_round == 0 {
+        final_staking_round.end_timestamp = state.final_staking_last_staking_timestamp;
+    }
+
+    Ok(())
+}
+
+/// Asserts that required time (final mining interval stored in the state, 3 minutes by default) passed since last final mining call.
+///
+/// ### Arguments
+///
+/// * `last_final_mining_timestamp` - timestamp of the moment when last final mining call was completed,
+/// * `final_mining_interval_seconds` - the required time between consecutive final mining calls.
+///
+/// ### Returns
+/// An error if less than the final mining interval passed since last final mining call, otherwise a successful result.
+pub fn final_mining_required_interval_elapsed(
+    last_final_mining_timestamp: &i64,
+    final_mining_interval_seconds: &i64,
+) -> Result<()> {
+    require!(
+        current_cluster_timestamp()? - last_final_mining_timestamp
+            >= *final_mining_interval_seconds,
+        SallarError::FinalMiningAheadOfTime
+    );
+
+    Ok(())
+}
+
+/// Records the amount distributed by a final mining call in the final mining budget of the current window.
+/// A new window starts with the first call once the previous window has elapsed.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `distributed_amount` - the amount distributed by the final mining call,
+/// * `timestamp` - the timestamp of the final mining call.
+///
+/// ### Returns
+/// An error if the amount distributed within the current window would exceed the final mining budget, otherwise a successful result.
+pub fn record_final_mining_budget(
+    state: &mut BlocksState,
+    distributed_amount: u64,
+    timestamp: i64,
+) -> Result<()> {
+    let Some(final_mining_budget_per_window) = state.final_mining_budget_per_window else {
+        return Ok(());
+    };
+    if timestamp - state.final_mining_budget_window_start
+        >= state.final_mining_budget_window_seconds
+    {
+        state.final_mining_budget_window_start = timestamp;
+        state.final_mining_budget_window_distributed = 0;
+    }
+    let window_distributed = state
+        .final_mining_budget_window_distributed
+        .saturating_add(distributed_amount);
+    require!(
+        window_distributed <= final_mining_budget_per_window,
+        SallarError::FinalMiningBudgetExhausted
+    );
+    state.final_mining_budget_window_distributed = window_distributed;
+
+    Ok(())
+}
+
+/// Asserts that the final mining balances of the requests match a recent final mining balance checkpoint (see checkpoint_final_mining_balance)
+/// while the checkpoint is required, so the reward tiers are not picked from arbitrary balances.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `users_info` - the requests of the final mining call.
+///
+/// ### Returns
+/// An error if the checkpoint is required and it is missing, too old or does not match the final mining balance of any request, otherwise a successful result.
+pub fn final_mining_balances_checkpointed(
+    state: &BlocksState,
+    users_info: &[UserInfoFinalMining],
+) -> Result<()> {
+    if !state.final_mining_checkpoint_required {
+        return Ok(());
+    }
+
+    final_mining_balances_match_checkpoint(state, users_info, Clock::get()?.slot)
+}
+
+/// Asserts that the final mining checkpoint is recent at the current slot and the final mining balances of the requests match it.
+fn final_mining_balances_match_checkpoint(
+    state: &BlocksState,
+    users_info: &[UserInfoFinalMining],
+    current_slot: u64,
+) -> Result<()> {
+    require!(
+        state.final_mining_checkpoint_slot > 0
+            && current_slot.saturating_sub(state.final_mining_checkpoint_slot)
+                <= MAX_FINAL_MINING_CHECKPOINT_AGE_SLOTS,
+        SallarError::FinalMiningCheckpointStale
+    );
+    require!(
+        users_info.iter().all(
+            |user_info| user_info.final_mining_balance == state.final_mining_checkpoint_balance
+        ),
+        SallarError::FinalMiningBalanceNotCheckpointed
+    );
+
+    Ok(())
+}
+
+/// Returns the index of the final mining claim period of the current timestamp, claim periods are counted from the Unix epoch.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The index of the claim period, 0 if the final mining claim period is not set.
+pub fn final_mining_claim_period_index(state: &BlocksState) -> u64 {
+    Clock::get().map_or(0, |clock| {
+        claim_period_index_at(
+            state.final_mining_claim_period_seconds,
+            clock.unix_timestamp,
+        )
+    })
+}
+
+/// Returns the index of the claim period the timestamp belongs to, 0 if the claim period is not set.
+fn claim_period_index_at(claim_period_seconds: i64, timestamp: i64) -> u64 {
+    if claim_period_seconds > 0 {
+        (timestamp / claim_period_seconds).unsigned_abs()
+    } else {
+        0
+    }
+}
+
+/// Asserts that the final mining claim period participants account and the system program are provided while the final mining claim period is set,
+/// so the users paid by every final_mining call of the period are tracked.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_mining_claim_period_participants_account` - the final mining claim period participants account provided to the instruction (if any),
+/// * `system_program` - the system program provided to the instruction (if any).
+///
+/// ### Returns
+/// An error if the final mining claim period is set but the final mining claim period participants account or the system program is not provided, otherwise a successful result.
+pub fn final_mining_claim_period_participants_provided<T, U>(
+    state: &BlocksState,
+    final_mining_claim_period_participants_account: &Option<T>,
+    system_program: &Option<U>,
+) -> Result<()> {
+    require!(
+        state.final_mining_claim_period_seconds == 0
+            || (final_mining_claim_period_participants_account.is_some()
+                && system_program.is_some()),
+        SallarError::MissingFinalMiningClaimPeriodParticipantsAccount
+    );
+
+    Ok(())
+}
+
+/// Adds the user paid by final mining to the claim period participants and asserts that the user has not been paid in the claim period yet.
+///
+/// ### Arguments
+///
+/// * `period_participants` - the users paid in the claim period so far,
+/// * `period_index` - the index of the claim period the user is paid in,
+/// * `user` - the paid user.
+///
+/// ### Returns
+/// An error if the claim period participants belong to another claim period or the user has already been paid in the claim period, otherwise a successful result.
+pub fn record_final_mining_claimant(
+    period_participants: &mut FinalMiningClaimPeriodParticipants,
+    period_index: u64,
+    user: &Pubkey,
+) -> Result<()> {
+    require!(
+        period_participants.period_index == period_index,
+        SallarError::FinalMiningClaimPeriodParticipantsAccountMismatch
+    );
+    require!(
+        !period_participants.users.contains(user),
+        SallarError::FinalMiningAlreadyClaimedInPeriod
+    );
+    period_participants.users.push(*user);
+
+    Ok(())
+}
+
+/// Reallocates the final mining claim period participants account to fit all of its users, the payer covers the additional rent.
+///
+/// ### Arguments
+///
+/// * `final_mining_claim_period_participants_account` - the final mining claim period participants account of the current claim period,
+/// * `payer` - the account paying the rent of the reallocated account,
+/// * `system_program` - the Solana system program account.
+///
+/// ### Returns
+/// A successful result, or an error if the rent cannot be paid or the account cannot be reallocated.
+pub fn fit_final_mining_claim_period_participants<'info>(
+    final_mining_claim_period_participants_account: &Account<
+        'info,
+        FinalMiningClaimPeriodParticipants,
+    >,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let required_space = SallarContext::DISCRIMINATOR_LENGTH
+        + FinalMiningClaimPeriodParticipants::INIT_SPACE
+        + final_mining_claim_period_participants_account.users.len()
+            * std::mem::size_of::<Pubkey>();
+
+    grow_account(
+        final_mining_claim_period_participants_account.to_account_info(),
+        required_space,
+        payer,
+        system_program,
+    )
+}
+
+/// Opens the next final mining epoch, so the rewards of the previous epoch cannot be claimed anymore.
+/// The `FinalMiningEpochOpened` event is emitted.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_mining_epoch` - the final mining epoch account of the opened epoch,
+/// * `bump` - the bump of the final mining epoch account,
+/// * `merkle_root` - the Merkle root of the token accounts participating in the epoch (all zeros if the epoch is open),
+/// * `open` - true if the epoch is opened without the participation root, false otherwise.
+///
+/// ### Returns
+/// The index of the opened epoch.
+pub fn start_final_mining_epoch(
+    state: &mut BlocksState,
+    final_mining_epoch: &mut FinalMiningEpoch,
+    bump: u8,
+    merkle_root: [u8; 32],
+    open: bool,
+) -> Result<u64> {
+    let timestamp = current_cluster_timestamp()?;
+    state.final_mining_epoch_counter += 1;
+    state.final_mining_epoch_timestamp = timestamp;
+    let epoch_index = state.final_mining_epoch_counter;
+
+    final_mining_epoch.bump = bump;
+    final_mining_epoch.epoch_index = epoch_index;
+    final_mining_epoch.merkle_root = merkle_root;
+    final_mining_epoch.open = open;
+    final_mining_epoch.timestamp = timestamp;
+    final_mining_epoch.claimed_amount = 0;
+
+    emit!(FinalMiningEpochOpened {
+        epoch_index,
+        merkle_root,
+        open,
+        timestamp,
+    });
+
+    Ok(epoch_index)
+}
+
+/// Asserts that the final mining coordinator is inactive, i.e. it has neither opened a final mining epoch nor called final_mining
+/// for the coordinator timeout, so anyone can open a final mining epoch without the participation root.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if the coordinator has been active within the coordinator timeout, otherwise a successful result.
+pub fn final_mining_coordinator_inactive(state: &BlocksState) -> Result<()> {
+    require!(
+        final_mining_coordinator_inactive_at(state, current_cluster_timestamp()?),
+        SallarError::FinalMiningCoordinatorActive
+    );
+
+    Ok(())
+}
+
+/// Returns true if the final mining coordinator has been inactive for the coordinator timeout at the timestamp, false otherwise.
+fn final_mining_coordinator_inactive_at(state: &BlocksState, timestamp: i64) -> bool {
+    let last_activity_timestamp = state
+        .final_mining_epoch_timestamp
+        .max(state.final_mining_last_timestamp);
+
+    timestamp - last_activity_timestamp >= FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS
+}
+
+/// Asserts that the final mining call does not distribute more than the maximum payout per call.
+/// All requests of the batch are counted, so the sum is an upper bound of the amount actually distributed.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_mining_tiers_account` - the final mining tiers account provided to the instruction (if any), the default tiers apply otherwise,
+/// * `users_info` - the requests of the final mining call.
+///
+/// ### Returns
+/// An error if the total reward of the requests exceeds the maximum payout per call, otherwise a successful result.
+pub fn final_mining_payout_within_limit(
+    state: &BlocksState,
+    final_mining_tiers_account: &Option<Account<FinalMiningTiers>>,
+    users_info: &[UserInfoFinalMining],
+) -> Result<()> {
+    let tiers = final_mining_tiers(final_mining_tiers_account.as_deref());
+    let total_payout = users_info.iter().fold(0_u64, |total_payout, user_info| {
+        total_payout.saturating_add(calculate_final_mining_reward(
+            tiers,
+            user_info.final_mining_balance,
+        ))
+    });
+    require!(
+        total_payout <= state.final_mining_max_payout_per_call,
+        SallarError::FinalMiningPayoutTooHigh
+    );
+
+    Ok(())
+}
+
+/// Asserts that both distribution accounts are empty, i.e. all tokens minted for blocks have been distributed.
+///
+/// ### Arguments
+///
+/// * `distribution_top_block_account` - the top block distribution account,
+/// * `distribution_bottom_block_account` - the bottom block distribution account.
+///
+/// ### Returns
+/// An error if any of the distribution accounts holds tokens, otherwise a successful result.
+pub fn distribution_accounts_empty(
+    distribution_top_block_account: &TokenAccount,
+    distribution_bottom_block_account: &TokenAccount,
+) -> Result<()> {
+    require!(
+        distribution_top_block_account.amount == 0 && distribution_bottom_block_account.amount == 0,
+        SallarError::DistributionAccountsNotEmpty
+    );
+
+    Ok(())
+}
+
+/// Checks if the token account is one of the program's token accounts (distribution, final staking and final mining accounts),
+/// whose balances are tracked by the blocks state.
+///
+/// ### Arguments
+///
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `token_account` - the address of the token account.
+///
+/// ### Returns
+/// True if the token account is one of the program's token accounts, false otherwise.
+pub fn program_token_account(config: &Config, token_account: &Pubkey) -> bool {
+    [
+        config.top_block_distribution_address,
+        config.bottom_block_distribution_address,
+        config.final_staking_account_address,
+        config.final_mining_account_address,
+    ]
+    .contains(token_account)
+}
+
+/// Checks if the token account is controlled by the program, i.e. it is one of the program's token accounts (see `program_token_account`),
+/// its authority is one of the accounts the program signs for (blocks state, mint and the program's token accounts),
+/// or it is its own authority. The program's token accounts and the stake vaults of users are their own authorities,
+/// so a stake vault is recognized without deriving its address from the token account it belongs to.
+///
+/// ### Arguments
+///
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `token_account` - the address of the token account,
+/// * `authority` - the authority (owner) of the token account.
+///
+/// ### Returns
+/// True if the token account is controlled by the program, false otherwise.
+pub fn program_owned_token_account(
+    config: &Config,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+) -> bool {
+    program_token_account(config, token_account)
+        || program_token_account(config, authority)
+        || [
+            config.block_state_address,
+            config.mint_address,
+            *token_account,
+        ]
+        .contains(authority)
+}
+
+/// Asserts that the token account is not one of the program's token accounts (see `program_token_account`),
+/// so only tokens accidentally sent to the blocks state account can be recovered.
+///
+/// ### Arguments
+///
+/// * `config` - contract's config holding the addresses and nonces of the program's accounts,
+/// * `token_account` - the address of the token account to recover tokens from.
+///
+/// ### Returns
+/// An error if the token account is one of the program's token accounts, otherwise a successful result.
+pub fn foreign_token_account(config: &Config, token_account: &Pubkey) -> Result<()> {
+    require!(
+        !program_token_account(config, token_account),
+        SallarError::InvalidForeignTokenAccount
+    );
+
+    Ok(())
+}
+
+/// Asserts that both final staking and final mining accounts are empty, i.e. all tokens of the final pools have been distributed.
+///
+/// ### Arguments
+///
+/// * `final_staking_account` - the final staking account,
+/// * `final_mining_account` - the final mining account.
+///
+/// ### Returns
+/// An error if any of the final pool accounts holds tokens, otherwise a successful result.
+pub fn final_pools_empty(
+    final_staking_account: &TokenAccount,
+    final_mining_account: &TokenAccount,
+) -> Result<()> {
+    require!(
+        final_staking_account.amount == 0 && final_mining_account.amount == 0,
+        SallarError::FinalPoolsNotEmpty
+    );
+
+    Ok(())
+}
+
+/// Asserts that the program is not paused, i.e. that `paused` attribute of the current `BlocksState` is set to false
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if the program is paused, otherwise a successful result.
+pub fn program_not_paused(state: &BlocksState) -> Result<()> {
+    require!(!state.paused, SallarError::ProgramPaused);
+
+    Ok(())
+}
+
+/// Asserts that solving of the given kind of blocks is not halted by the guardian.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `block_kind` - the kind of blocks to check.
+///
+/// ### Returns
+/// An error if solving of the given kind of blocks is halted, otherwise a successful result.
+pub fn block_not_halted(state: &BlocksState, block_kind: BlockKind) -> Result<()> {
+    let halted = match block_kind {
+        BlockKind::Top => state.top_block_halted,
+        BlockKind::Bottom => state.bottom_block_halted,
+    };
+    require!(!halted, SallarError::BlockHalted);
+
+    Ok(())
+}
+
+/// Halts or resumes solving of the given kind of blocks.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `block_kind` - the kind of blocks to halt or resume,
+/// * `halted` - true to halt, false to resume.
+pub fn set_block_halted(state: &mut BlocksState, block_kind: BlockKind, halted: bool) {
+    match block_kind {
+        BlockKind::Top => state.top_block_halted = halted,
+        BlockKind::Bottom => state.bottom_block_halted = halted,
+    }
+}
+
+/// Asserts that admin controls are not renounced, i.e. that `admin_controls_renounced` attribute of the current `BlocksState` is set to false
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if admin controls have been renounced, otherwise a successful result.
+pub fn admin_controls_not_renounced(state: &BlocksState) -> Result<()> {
+    require!(
+        !state.admin_controls_renounced,
+        SallarError::AdminControlsRenounced
+    );
+
+    Ok(())
+}
+
+/// Asserts that the timelock is disabled, i.e. that `timelock_delay` attribute of the current `BlocksState` is set to 0,
+/// so authority and parameter changes can be applied directly.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if the timelock is enabled, otherwise a successful result.
+pub fn timelock_disabled(state: &BlocksState) -> Result<()> {
+    require!(state.timelock_delay == 0, SallarError::TimelockEnabled);
+
+    Ok(())
+}
+
+/// Asserts that the timelock is enabled, i.e. that `timelock_delay` attribute of the current `BlocksState` is greater than 0,
+/// so authority and parameter changes must be queued.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if the timelock is disabled, otherwise a successful result.
+pub fn timelock_enabled(state: &BlocksState) -> Result<()> {
+    require!(state.timelock_delay > 0, SallarError::TimelockDisabled);
+
+    Ok(())
+}
+
+/// Asserts that the timelock delay is not negative and does not exceed the allowed maximum (30 days).
+///
+/// ### Arguments
+///
+/// * `timelock_delay` - the timelock delay to validate (in seconds).
+///
+/// ### Returns
+/// An error if the timelock delay is not valid, otherwise a successful result.
+pub fn valid_timelock_delay(timelock_delay: i64) -> Result<()> {
+    require!(
+        (0..=MAX_TIMELOCK_DELAY_SECONDS).contains(&timelock_delay),
+        SallarError::InvalidTimelockDelay
+    );
+
+    Ok(())
+}
+
+/// Asserts that the schedule the contract is initialized with is valid: both first block numbers are valid block indexes
+/// with at least one block between them (so the blocks are not collided from the start), and the initial token distribution amount
+/// together with the tokens of all blocks between the first block numbers does not exceed the maximum supply (`u64::MAX` dusts).
+///
+/// ### Arguments
+///
+/// * `first_top_block_number` - the top block number the contract is initialized with,
+/// * `first_bottom_block_number` - the bottom block number the contract is initialized with,
+/// * `initial_token_distribution_amount` - the amount of tokens minted by the initial token distribution.
+///
+/// ### Returns
+/// An error if the schedule is not valid, otherwise a successful result.
+pub fn valid_initial_schedule(
+    first_top_block_number: u64,
+    first_bottom_block_number: u64,
+    initial_token_distribution_amount: u64,
+) -> Result<()> {
+    require!(
+        valid_block_index(first_top_block_number).is_ok()
+            && valid_block_index(first_bottom_block_number).is_ok()
+            && first_top_block_number + 1 < first_bottom_block_number,
+        SallarError::InvalidFirstBlockNumbers
+    );
+    (first_bottom_block_number - first_top_block_number + 1)
+        .checked_mul(DUSTS_PER_BLOCK)
+        .and_then(|blocks_amount| blocks_amount.checked_add(initial_token_distribution_amount))
+        .ok_or(SallarError::InvalidInitialTokenDistributionAmount)?;
+
+    Ok(())
+}
+
+/// Asserts that the block solution interval is in the allowed range (between 1 minute and 1 hour).
+///
+/// ### Arguments
+///
+/// * `block_solution_interval_seconds` - the block solution interval to validate (in seconds).
+///
+/// ### Returns
+/// An error if the block solution interval is out of the allowed range, otherwise a successful result.
+pub fn valid_block_solution_interval(block_solution_interval_seconds: i64) -> Result<()> {
+    require!(
+        (MIN_BLOCK_SOLUTION_INTERVAL_SECONDS..=MAX_BLOCK_SOLUTION_INTERVAL_SECONDS)
+            .contains(&block_solution_interval_seconds),
+        SallarError::InvalidBlockSolutionInterval
+    );
+
+    Ok(())
+}
+
+/// Asserts that the distribution backend can be used by the subsystem,
+/// i.e. final mining and final staking rewards are transferred directly, as claim accounts track only top and bottom block rewards.
+///
+/// ### Arguments
+///
+/// * `subsystem` - the subsystem the backend is configured for,
+/// * `distribution_backend` - the distribution backend to validate.
+///
+/// ### Returns
+/// An error if the subsystem cannot use the backend, otherwise a successful result.
+pub fn valid_distribution_backend(
+    subsystem: Subsystem,
+    distribution_backend: DistributionBackend,
+) -> Result<()> {
+    require!(
+        matches!(subsystem, Subsystem::TopBlock | Subsystem::BottomBlock)
+            || distribution_backend == DistributionBackend::DirectTransfer,
+        SallarError::InvalidDistributionBackend
+    );
+
+    Ok(())
+}
+
+/// Asserts that the reward fee does not exceed the allowed maximum (500 basis points, i.e. 5%).
+///
+/// ### Arguments
+///
+/// * `reward_fee_bps` - the reward fee to validate (in basis points).
+///
+/// ### Returns
+/// An error if the reward fee is too high, otherwise a successful result.
+pub fn valid_reward_fee(reward_fee_bps: u16) -> Result<()> {
+    require!(
+        reward_fee_bps <= MAX_REWARD_FEE_BPS,
+        SallarError::RewardFeeTooHigh
+    );
+
+    Ok(())
+}
+
+/// Asserts that the SLA threshold is in the allowed range, i.e. between 0 (disabled) and 30 days.
+///
+/// ### Arguments
+///
+/// * `sla_threshold_seconds` - the SLA threshold to validate (in seconds).
+///
+/// ### Returns
+/// An error if the SLA threshold is out of the allowed range, otherwise a successful result.
+pub fn valid_sla_threshold(sla_threshold_seconds: i64) -> Result<()> {
+    require!(
+        (0..=MAX_SLA_THRESHOLD_SECONDS).contains(&sla_threshold_seconds),
+        SallarError::InvalidSlaThreshold
+    );
+
+    Ok(())
+}
+
+/// Records the delay of a block solution or a final staking round beyond the moment it became eligible,
+/// i.e. the time between the previous completion increased by the required interval and the current completion.
+/// It updates the longest observed delay of the subsystem and emits `SlaBreached` event if the delay exceeds the SLA threshold.
+/// Nothing is recorded for the first completion, as there is no previous completion to measure the delay from.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `subsystem` - the subsystem which completed the block solution or the final staking round,
+/// * `previous_timestamp` - the timestamp of the previous completion,
+/// * `current_timestamp` - the timestamp of the current completion.
+///
+/// ### Returns
+/// The recorded delay, None if nothing is recorded.
+pub fn record_sla_delay(
+    state: &mut BlocksState,
+    subsystem: SlaSubsystem,
+    previous_timestamp: i64,
+    current_timestamp: i64,
+) -> Option<i64> {
+    if previous_timestamp == 0 {
+        return None;
+    }
+
+    let (interval_seconds, max_delay) = match subsystem {
+        SlaSubsystem::TopBlock => (
+            state.block_solution_interval_seconds,
+            &mut state.top_block_max_solution_delay,
+        ),
+        SlaSubsystem::BottomBlock => (
+            state.block_solution_interval_seconds,
+            &mut state.bottom_block_max_solution_delay,
+        ),
+        SlaSubsystem::FinalStaking => (
+            state.final_staking_interval_seconds,
+            &mut state.final_staking_max_round_delay,
+        ),
+    };
+    let delay_seconds = (current_timestamp - previous_timestamp - interval_seconds).max(0);
+    *max_delay = (*max_delay).max(delay_seconds);
+
+    if state.sla_threshold_seconds > 0 && delay_seconds > state.sla_threshold_seconds {
+        emit!(SlaBreached {
+            subsystem,
+            delay_seconds,
+            threshold_seconds: state.sla_threshold_seconds,
+            timestamp: current_timestamp,
+        });
+    }
+
+    Some(delay_seconds)
+}
+
+/// Asserts that the final staking config is in the allowed range,
+/// i.e. the interval is between 1 hour and 7 days and the pool division factor is between 100 (1% of the balance) and 100000 (0.001% of the balance).
+///
+/// ### Arguments
+///
+/// * `final_staking_interval_seconds` - the final staking interval to validate (in seconds),
+/// * `final_staking_pool_division_factor` - the final staking pool division factor to validate.
+///
+/// ### Returns
+/// An error if the final staking config is out of the allowed range, otherwise a successful result.
+pub fn valid_final_staking_config(
+    final_staking_interval_seconds: i64,
+    final_staking_pool_division_factor: u64,
+) -> Result<()> {
+    require!(
+        (MIN_FINAL_STAKING_INTERVAL_SECONDS..=MAX_FINAL_STAKING_INTERVAL_SECONDS)
+            .contains(&final_staking_interval_seconds),
+        SallarError::InvalidFinalStakingInterval
+    );
+    require!(
+        (MIN_FINAL_STAKING_POOL_DIVISION_FACTOR..=MAX_FINAL_STAKING_POOL_DIVISION_FACTOR)
+            .contains(&final_staking_pool_division_factor),
+        SallarError::InvalidFinalStakingPoolDivisionFactor
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final mining config is in the allowed range,
+/// i.e. the interval is between 0 and 1 day and the maximum payout per call is between the highest single final mining reward and the amount of tokens of 10 blocks.
+///
+/// ### Arguments
+///
+/// * `final_mining_interval_seconds` - the final mining interval to validate (in seconds),
+/// * `final_mining_max_payout_per_call` - the maximum payout per final mining call to validate.
+///
+/// ### Returns
+/// An error if the final mining config is out of the allowed range, otherwise a successful result.
+pub fn valid_final_mining_config(
+    final_mining_interval_seconds: i64,
+    final_mining_max_payout_per_call: u64,
+) -> Result<()> {
+    require!(
+        (0..=MAX_FINAL_MINING_INTERVAL_SECONDS).contains(&final_mining_interval_seconds),
+        SallarError::InvalidFinalMiningConfig
+    );
+    require!(
+        (MAX_FINAL_MINING_REWARD..=MAX_FINAL_MINING_MAX_PAYOUT_PER_CALL)
+            .contains(&final_mining_max_payout_per_call),
+        SallarError::InvalidFinalMiningConfig
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final mining budget is in the allowed range, i.e. the budget (if set) covers at least the highest single final mining reward
+/// and the budget window is between 1 second and 30 days.
+///
+/// ### Arguments
+///
+/// * `final_mining_budget_per_window` - the final mining budget to validate, None disables the budget,
+/// * `final_mining_budget_window_seconds` - the final mining budget window to validate (in seconds).
+///
+/// ### Returns
+/// An error if the final mining budget is out of the allowed range, otherwise a successful result.
+pub fn valid_final_mining_budget(
+    final_mining_budget_per_window: Option<u64>,
+    final_mining_budget_window_seconds: i64,
+) -> Result<()> {
+    require!(
+        final_mining_budget_per_window.is_none_or(|final_mining_budget_per_window| {
+            final_mining_budget_per_window >= MAX_FINAL_MINING_REWARD
+        }) && (1..=MAX_FINAL_MINING_BUDGET_WINDOW_SECONDS)
+            .contains(&final_mining_budget_window_seconds),
+        SallarError::InvalidFinalMiningBudget
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final mining claim period is in the allowed range, i.e. it is between 0 (no limit) and 30 days.
+///
+/// ### Arguments
+///
+/// * `final_mining_claim_period_seconds` - the final mining claim period to validate (in seconds).
+///
+/// ### Returns
+/// An error if the final mining claim period is out of the allowed range, otherwise a successful result.
+pub fn valid_final_mining_claim_period(final_mining_claim_period_seconds: i64) -> Result<()> {
+    require!(
+        (0..=MAX_FINAL_MINING_CLAIM_PERIOD_SECONDS).contains(&final_mining_claim_period_seconds),
+        SallarError::InvalidFinalMiningClaimPeriod
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final staking reward part tolerance is in the allowed range, i.e. it does not exceed 90_000 basis points (reward parts up to 10x the share of the user).
+///
+/// ### Arguments
+///
+/// * `final_staking_reward_part_tolerance_bps` - the tolerance to validate (in basis points), None disables the verification.
+///
+/// ### Returns
+/// An error if the tolerance is out of the allowed range, otherwise a successful result.
+pub fn valid_final_staking_reward_part_tolerance(
+    final_staking_reward_part_tolerance_bps: Option<u32>,
+) -> Result<()> {
+    require!(
+        final_staking_reward_part_tolerance_bps.is_none_or(
+            |tolerance_bps| tolerance_bps <= MAX_FINAL_STAKING_REWARD_PART_TOLERANCE_BPS
+        ),
+        SallarError::InvalidFinalStakingRewardPartTolerance
+    );
+
+    Ok(())
+}
+
+/// Asserts that the reward part given to final staking for a user does not exceed the user's share of the registered balance of the round by more than the tolerance.
+/// The share is calculated from the current balance of the user's token account and the total weighted balance of the snapshots registered for the round.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `account` - the token account of the user,
+/// * `reward_part` - the reward part of the user in scaled reward parts,
+/// * `tolerance_bps` - the tolerance (in basis points) by which the reward part may exceed the user's share.
+///
+/// ### Returns
+/// An error if the reward part exceeds the user's share by more than the tolerance, otherwise a successful result.
+pub fn reward_part_within_balance_share<'info>(
+    state: &BlocksState,
+    account: &'info AccountInfo<'info>,
+    reward_part: u64,
+    tolerance_bps: u32,
+) -> Result<()> {
+    let balance = InterfaceAccount::<TokenAccount>::try_from(account)?.amount;
+    require!(
+        reward_part
+            <= calculate_max_reward_part(
+                balance,
+                state.final_staking_registered_balance_in_round,
+                tolerance_bps
+            )?,
+        SallarError::RewardPartExceedsBalanceShare
+    );
+
+    Ok(())
+}
+
+/// Asserts that the final staking terminal config is in the allowed range,
+/// i.e. the minimum pool in round does not exceed the amount of tokens of a single block and the treasury is set if the remaining balance is swept to it.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `final_staking_min_pool_in_round` - the minimum pool in round to validate,
+/// * `final_staking_terminal_mode` - the terminal mode to validate.
+///
+/// ### Returns
+/// An error if the final staking terminal config is invalid, otherwise a successful result.
+pub fn valid_final_staking_terminal_config(
+    state: &BlocksState,
+    final_staking_min_pool_in_round: u64,
+    final_staking_terminal_mode: FinalStakingTerminalMode,
+) -> Result<()> {
+    require!(
+        final_staking_min_pool_in_round <= MAX_FINAL_STAKING_MIN_POOL_IN_ROUND,
+        SallarError::InvalidFinalStakingTerminalConfig
+    );
+    require!(
+        final_staking_terminal_mode != FinalStakingTerminalMode::SweepToTreasury
+            || state.treasury_address.is_some(),
+        SallarError::InvalidFinalStakingTerminalConfig
+    );
+
+    Ok(())
+}
+
+/// Asserts that the change can be applied, i.e. its parameters are valid
+/// and admin controls are not renounced if the change is one of them (all changes except authority changes).
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `change` - the change to validate.
+///
+/// ### Returns
+/// An error if the change cannot be applied, otherwise a successful result.
+pub fn valid_config_change(state: &BlocksState, change: &ConfigChange) -> Result<()> {
+    match change {
+        ConfigChange::ChangeAuthority { .. } | ConfigChange::ProposeAuthority { .. } => Ok(()),
+        ConfigChange::UpdateSignerSet { signers, threshold } => {
+            admin_controls_not_renounced(state)?;
+            valid_signer_set(signers, *threshold)
+        }
+        ConfigChange::SetMinPayoutAmount { min_payout_amount } => {
+            admin_controls_not_renounced(state)?;
+            valid_min_payout_amount(*min_payout_amount)
+        }
+        ConfigChange::SetTreasury { .. } => admin_controls_not_renounced(state),
+        ConfigChange::SetTimelockDelay { timelock_delay } => {
+            admin_controls_not_renounced(state)?;
+            valid_timelock_delay(*timelock_delay)
+        }
+        ConfigChange::SetBlockSolutionInterval {
+            block_solution_interval_seconds,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_block_solution_interval(*block_solution_interval_seconds)
+        }
+        ConfigChange::UpdateFinalStakingConfig {
+            final_staking_interval_seconds,
+            final_staking_pool_division_factor,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_staking_config(
+                *final_staking_interval_seconds,
+                *final_staking_pool_division_factor,
+            )
+        }
+        ConfigChange::SetRewardFee { reward_fee_bps } => {
+            admin_controls_not_renounced(state)?;
+            valid_reward_fee(*reward_fee_bps)
+        }
+        ConfigChange::SetGuardian { .. } => admin_controls_not_renounced(state),
+        ConfigChange::UpdateFinalMiningConfig {
+            final_mining_interval_seconds,
+            final_mining_max_payout_per_call,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_mining_config(
+                *final_mining_interval_seconds,
+                *final_mining_max_payout_per_call,
+            )
+        }
+        ConfigChange::SetFinalMiningBudget {
+            final_mining_budget_per_window,
+            final_mining_budget_window_seconds,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_mining_budget(
+                *final_mining_budget_per_window,
+                *final_mining_budget_window_seconds,
+            )
+        }
+        ConfigChange::SetBlockHistoryEnabled { .. } => admin_controls_not_renounced(state),
+        ConfigChange::SetSolveCoordinator { .. } => admin_controls_not_renounced(state),
+        ConfigChange::SetBottomBlockMinRequiredStake {
+            bottom_block_min_required_stake,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_bottom_block_min_required_stake(*bottom_block_min_required_stake)
+        }
+        ConfigChange::SetMaxUserRequestsPerBlock { .. } => admin_controls_not_renounced(state),
+        ConfigChange::SetBlockParticipationCommitmentEnabled { .. } => {
+            admin_controls_not_renounced(state)
+        }
+        ConfigChange::SetBoostCreditsRequired { .. } => admin_controls_not_renounced(state),
+        ConfigChange::SetFinalStakingSnapshotsRequired { .. } => {
+            admin_controls_not_renounced(state)
+        }
+        ConfigChange::SetFinalMiningCheckpointRequired { .. } => {
+            admin_controls_not_renounced(state)
+        }
+        ConfigChange::SetFinalMiningClaimPeriod {
+            final_mining_claim_period_seconds,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_mining_claim_period(*final_mining_claim_period_seconds)
+        }
+        ConfigChange::SetFinalStakingRewardPartTolerance {
+            final_staking_reward_part_tolerance_bps,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_staking_reward_part_tolerance(*final_staking_reward_part_tolerance_bps)
+        }
+        ConfigChange::SetFinalMiningTiers { tiers } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_mining_tiers(tiers)
+        }
+        ConfigChange::SetFinalStakingTerminalConfig {
+            final_staking_min_pool_in_round,
+            final_staking_terminal_mode,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_final_staking_terminal_config(
+                state,
+                *final_staking_min_pool_in_round,
+                *final_staking_terminal_mode,
+            )
+        }
+        ConfigChange::SetDistributionBackend {
+            subsystem,
+            distribution_backend,
+        } => {
+            admin_controls_not_renounced(state)?;
+            valid_distribution_backend(*subsystem, *distribution_backend)
+        }
+    }
+}
+
+/// Returns the current values of the parameters modified by the given change, as a change of the same kind.
+/// It is used to report the previous values in the `ConfigChanged` event.
+/// Unset optional values (the pending authority and the treasury) are represented by the default public key,
+/// and a missing signer set by an empty list of signers and zero threshold.
+/// A missing final mining tiers account is represented by the default final mining tiers,
+/// and a missing config account by the default distribution backends.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `config` - the config account (if provided),
+/// * `signer_set` - the current signer set (if provided),
+/// * `final_mining_tiers_account` - the current final mining tiers account (if provided),
+/// * `change` - the change to be applied.
+///
+/// ### Returns
+/// The change which would restore the current values.
+pub fn current_config_values(
+    state: &BlocksState,
+    config: Option<&Config>,
+    signer_set: Option<&SignerSet>,
+    final_mining_tiers_account: Option<&FinalMiningTiers>,
+    change: &ConfigChange,
+) -> ConfigChange {
+    match change {
+        ConfigChange::ChangeAuthority { .. } => ConfigChange::ChangeAuthority {
+            new_authority: state.authority,
+        },
+        ConfigChange::ProposeAuthority { .. } => ConfigChange::ProposeAuthority {
+            new_authority: state.pending_authority.unwrap_or_default(),
+        },
+        ConfigChange::UpdateSignerSet { .. } => ConfigChange::UpdateSignerSet {
+            signers: signer_set
+                .map(|signer_set| signer_set.signers.clone())
+                .unwrap_or_default(),
+            threshold: signer_set.map_or(0, |signer_set| signer_set.threshold),
+        },
+        ConfigChange::SetMinPayoutAmount { .. } => ConfigChange::SetMinPayoutAmount {
+            min_payout_amount: state.min_payout_amount,
+        },
+        ConfigChange::SetTreasury { .. } => ConfigChange::SetTreasury {
+            treasury_address: state.treasury_address.unwrap_or_default(),
+        },
+        ConfigChange::SetTimelockDelay { .. } => ConfigChange::SetTimelockDelay {
+            timelock_delay: state.timelock_delay,
+        },
+        ConfigChange::SetBlockSolutionInterval { .. } => ConfigChange::SetBlockSolutionInterval {
+            block_solution_interval_seconds: state.block_solution_interval_seconds,
+        },
+        ConfigChange::UpdateFinalStakingConfig { .. } => ConfigChange::UpdateFinalStakingConfig {
+            final_staking_interval_seconds: state.final_staking_interval_seconds,
+            final_staking_pool_division_factor: state.final_staking_pool_division_factor,
+        },
+        ConfigChange::SetRewardFee { .. } => ConfigChange::SetRewardFee {
+            reward_fee_bps: state.reward_fee_bps,
+        },
+        ConfigChange::SetGuardian { .. } => ConfigChange::SetGuardian {
+            guardian: state.guardian,
+        },
+        ConfigChange::UpdateFinalMiningConfig { .. } => ConfigChange::UpdateFinalMiningConfig {
+            final_mining_interval_seconds: state.final_mining_interval_seconds,
+            final_mining_max_payout_per_call: state.final_mining_max_payout_per_call,
+        },
+        ConfigChange::SetFinalMiningBudget { .. } => ConfigChange::SetFinalMiningBudget {
+            final_mining_budget_per_window: state.final_mining_budget_per_window,
+            final_mining_budget_window_seconds: state.final_mining_budget_window_seconds,
+        },
+        ConfigChange::SetBlockHistoryEnabled { .. } => ConfigChange::SetBlockHistoryEnabled {
+            block_history_enabled: state.block_history_enabled,
+        },
+        ConfigChange::SetSolveCoordinator { .. } => ConfigChange::SetSolveCoordinator {
+            solve_coordinator: state.solve_coordinator,
+        },
+        ConfigChange::SetBottomBlockMinRequiredStake { .. } => {
+            ConfigChange::SetBottomBlockMinRequiredStake {
+                bottom_block_min_required_stake: state.bottom_block_min_required_stake,
+            }
+        }
+        ConfigChange::SetMaxUserRequestsPerBlock { .. } => {
+            ConfigChange::SetMaxUserRequestsPerBlock {
+                top_block_max_user_requests: state.top_block_max_user_requests,
+                bottom_block_max_user_requests: state.bottom_block_max_user_requests,
+            }
+        }
+        ConfigChange::SetBlockParticipationCommitmentEnabled { .. } => {
+            ConfigChange::SetBlockParticipationCommitmentEnabled {
+                block_participation_commitment_enabled: state
+                    .block_participation_commitment_enabled,
+            }
+        }
+        ConfigChange::SetBoostCreditsRequired { .. } => ConfigChange::SetBoostCreditsRequired {
+            boost_credits_required: state.boost_credits_required,
+        },
+        ConfigChange::SetFinalStakingSnapshotsRequired { .. } => {
+            ConfigChange::SetFinalStakingSnapshotsRequired {
+                final_staking_snapshots_required: state.final_staking_snapshots_required,
+            }
+        }
+        ConfigChange::SetFinalMiningCheckpointRequired { .. } => {
+            ConfigChange::SetFinalMiningCheckpointRequired {
+                final_mining_checkpoint_required: state.final_mining_checkpoint_required,
+            }
+        }
+        ConfigChange::SetFinalMiningClaimPeriod { .. } => ConfigChange::SetFinalMiningClaimPeriod {
+            final_mining_claim_period_seconds: state.final_mining_claim_period_seconds,
+        },
+        ConfigChange::SetFinalStakingRewardPartTolerance { .. } => {
+            ConfigChange::SetFinalStakingRewardPartTolerance {
+                final_staking_reward_part_tolerance_bps: state
+                    .final_staking_reward_part_tolerance_bps,
+            }
+        }
+        ConfigChange::SetFinalMiningTiers { .. } => ConfigChange::SetFinalMiningTiers {
+            tiers: final_mining_tiers(final_mining_tiers_account).to_vec(),
+        },
+        ConfigChange::SetFinalStakingTerminalConfig { .. } => {
+            ConfigChange::SetFinalStakingTerminalConfig {
+                final_staking_min_pool_in_round: state.final_staking_min_pool_in_round,
+                final_staking_terminal_mode: state.final_staking_terminal_mode,
+            }
+        }
+        ConfigChange::SetDistributionBackend { subsystem, .. } => {
+            ConfigChange::SetDistributionBackend {
+                subsystem: *subsystem,
+                distribution_backend: config
+                    .map_or(default_distribution_backend(*subsystem), |config| {
+                        distribution_backend(config, *subsystem)
+                    }),
+            }
+        }
+    }
+}
+
+/// Emits the `ConfigChanged` event.
+///
+/// ### Arguments
+///
+/// * `signer` - the signer of the transaction applying the change,
+/// * `previous_values` - the values before the change (see `current_config_values`),
+/// * `new_values` - the applied change.
+///
+/// ### Returns
+/// A successful result.
+pub fn emit_config_changed(
+    signer: Pubkey,
+    previous_values: ConfigChange,
+    new_values: ConfigChange,
+) -> Result<()> {
+    emit!(ConfigChanged {
+        signer,
+        previous_values,
+        new_values,
+        timestamp: current_cluster_timestamp()?,
+    });
+
+    Ok(())
+}
+
+/// Emits the `AuthorityChanged` event.
+///
+/// ### Arguments
+///
+/// * `signer` - the signer of the transaction transferring the ownership,
+/// * `previous_authority` - the contract's owner before the change,
+/// * `new_authority` - the new contract's owner.
+///
+/// ### Returns
+/// A successful result.
+pub fn emit_authority_changed(
+    signer: Pubkey,
+    previous_authority: Pubkey,
+    new_authority: Pubkey,
+) -> Result<()> {
+    emit!(AuthorityChanged {
+        signer,
+        previous_authority,
+        new_authority,
+        timestamp: current_cluster_timestamp()?,
+    });
+
+    Ok(())
+}
+
+/// Asserts that the timelock delay of the pending change has elapsed.
+///
+/// ### Arguments
+///
+/// * `pending_change` - the change queued in the timelock.
+///
+/// ### Returns
+/// An error if the change cannot be executed yet, otherwise a successful result.
+pub fn pending_change_executable(pending_change: &PendingChange) -> Result<()> {
+    require!(
+        current_cluster_timestamp()? >= pending_change.execute_after,
+        SallarError::TimelockNotElapsed
+    );
+
+    Ok(())
+}
+
+/// Asserts that blocks have collided, i.e. that `blocks_collided` attribute of the current `BlocksState` is set to true
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if blocks have not collided yet, otherwise a successful result.
+pub fn blocks_collided(state: &BlocksState) -> Result<()> {
+    require!(state.blocks_collided, SallarError::BlocksNotCollidedYet);
+
+    Ok(())
+}
+
+/// Asserts that the current top block is not solved yet, i.e. it has some available BPs.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if top block is solved (has no available BPs), otherwise a successful result.
+pub fn top_block_not_solved(state: &BlocksState) -> Result<()> {
+    require!(
+        state.top_block_available_bp > 0,
+        SallarError::BlockAlreadySolved
+    );
+
+    Ok(())
+}
+
+/// Asserts that the current bottom block number is within the range of blocks, so corrupted state fails loudly instead of producing wrong rewards.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if bottom block number is out of the range of blocks, otherwise a successful result.
+pub fn bottom_block_within_bounds(state: &BlocksState) -> Result<()> {
+    valid_block_index(state.bottom_block_number)
+}
+
+/// Asserts that the current bottom block is not solved yet, i.e. it has some available BPs.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if bottom block is solved (has no available BPs), otherwise a successful result.
+pub fn bottom_block_not_solved(state: &BlocksState) -> Result<()> {
+    require!(
+        state.bottom_block_available_bp > 0,
+        SallarError::BlockAlreadySolved
+    );
+
+    Ok(())
+}
+
+/// Asserts that the both top block and bottom block are solved, i.e. they have no available BPs.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if either top block or bottom is not solved (any of them has available BPs), otherwise a successful result.
+pub fn blocks_solved(state: &BlocksState) -> Result<()> {
+    require!(
+        state.top_block_available_bp == 0,
+        SallarError::TopBlockNotSolvedYet
+    );
+    require!(
+        state.bottom_block_available_bp == 0,
+        SallarError::BottomBlockNotSolvedYet
+    );
+
+    Ok(())
+}
+
+/// Asserts that any of the current blocks has a remaining balance or available BPs, i.e. the collision remainder is not settled yet.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if both current blocks are solved and have no remaining balance, otherwise a successful result.
+pub fn collision_remainder_exists(state: &BlocksState) -> Result<()> {
+    require!(
+        state.top_block_balance > 0
+            || state.top_block_available_bp > 0
+            || state.bottom_block_balance > 0
+            || state.bottom_block_available_bp > 0,
+        SallarError::NoCollisionRemainder
+    );
+
+    Ok(())
+}
+
+/// Sets `blocks_collided` attribute of `BlocksState` to true to mark blocks as collided.
+/// It happens only if blocks really collided, i.e. bottom block's number is great by 1 than top block's number.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// A successful result.
+pub fn update_blocks_collided(state: &mut BlocksState) -> Result<()> {
+    if !can_block_be_switched(state) {
+        state.blocks_collided = true;
+    }
+
+    Ok(())
+}
+
+/// Asserts that initial_token_distribution function has not yet been successfully executed.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// An error if the initial_token_distribution function has been already successfully executed, otherwise a successful result.
+pub fn initial_token_distribution_not_performed_yet(state: &BlocksState) -> Result<()> {
+    require!(
+        !state.initial_token_distribution_already_performed,
+        SallarError::InitialTokenDistributionAlreadyPerformed
+    );
+
+    Ok(())
+}
+
+/// Specifies if any block can be switched to the next one.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// True if current bottom block number is greater than by current top block number by at least 2, false otherwise.
+pub fn can_block_be_switched(state: &BlocksState) -> bool {
+    state.bottom_block_number - 1 > state.top_block_number
+}
+
+/// Calculates the block metrics derived from the blocks state with the same token math the contract uses to distribute tokens.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The summary of the current top and bottom blocks and the blocks remaining before the collision.
+pub fn calculate_block_state_summary(state: &BlocksState) -> Result<BlockStateSummary> {
+    let (top_bp_with_boost, top_block_dust_per_bp) = top_block_params(state)?;
+    let (bottom_block_dust_per_bp, bottom_block_max_boost) = bottom_block_params(state)?;
+    let remaining_blocks_before_collision = state
+        .bottom_block_number
+        .saturating_sub(state.top_block_number)
+        .saturating_sub(1);
+
+    Ok(BlockStateSummary {
+        top_block_number: state.top_block_number,
+        top_block_dust_per_bp: top_block_dust_per_bp.to_f64(),
+        top_bp_with_boost,
+        bottom_block_number: state.bottom_block_number,
+        bottom_block_dust_per_bp: bottom_block_dust_per_bp.to_f64(),
+        bottom_block_max_boost,
+        remaining_blocks_before_collision,
+        estimated_remaining_supply: state.top_block_balance
+            + state.bottom_block_balance
+            + remaining_blocks_before_collision * DUSTS_PER_BLOCK,
+    })
+}
+
+/// Calculates the total amount of tokens minted so far: the amounts minted for the top and bottom blocks reached
+/// and the initial token distribution amount, if the initial token distribution has been performed.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The total amount of tokens minted so far.
+pub fn calculate_total_minted_amount(state: &BlocksState) -> Result<u64> {
+    let mut total_minted_amount =
+        calculate_top_blocks_minted_amount(state.first_top_block_number, state.top_block_number)
+            .checked_add(calculate_bottom_blocks_minted_amount(
+                state.first_bottom_block_number,
+                state.bottom_block_number,
+            ))
+            .ok_or(SallarError::ArithmeticOverflow)?;
+    if state.initial_token_distribution_already_performed {
+        total_minted_amount = total_minted_amount
+            .checked_add(state.initial_token_distribution_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+    }
+
+    Ok(total_minted_amount)
+}
+
+/// Verifies the accounting invariants of a single block.
+/// The distribution account must hold the block balance and the pending payouts of the block.
+/// The available BP must be worth the block balance at the block's dust per BP,
+/// up to the amount a single BP is worth, which covers the rounding of the rewards paid from the block.
+///
+/// ### Arguments
+///
+/// * `block_number` - the number of the block,
+/// * `block_balance` - the balance of the block tracked by the blocks state,
+/// * `pending_payouts` - the pending payouts of the block,
+/// * `available_bp` - the available BP of the block,
+/// * `distribution_balance` - the balance of the block's distribution account.
+///
+/// ### Returns
+/// The invariants of the block.
+pub fn verify_block_invariants(
+    block_number: u64,
+    block_balance: u64,
+    pending_payouts: u64,
+    available_bp: u64,
+    distribution_balance: u64,
+) -> Result<BlockInvariants> {
+    let dust_per_bp = calculate_dust_per_bp(block_number)?;
+    let available_bp_amount = calculate_single_reward(available_bp, dust_per_bp)?;
+    let single_bp_amount = calculate_single_reward(1, dust_per_bp)?;
+
+    Ok(BlockInvariants {
+        block_number,
+        block_balance,
+        pending_payouts,
+        distribution_balance,
+        available_bp,
+        available_bp_amount,
+        distribution_balance_consistent: block_balance.checked_add(pending_payouts)
+            == Some(distribution_balance),
+        available_bp_consistent: block_balance.abs_diff(available_bp_amount) <= single_bp_amount,
+    })
+}
+
+/// Verifies the core accounting invariants of the contract without modifying any state:
+/// the distribution balances of the current blocks, the available BP of the current blocks and the minted supply.
+/// The total supply may be lower than the minted amount, since the tokens spent on boost credits are burned.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `total_supply` - the total supply of the token,
+/// * `distribution_top_block_balance` - the balance of the top block distribution account,
+/// * `distribution_bottom_block_balance` - the balance of the bottom block distribution account,
+/// * `global_stats_minted_amount` - the minted amount tracked by the global statistics, if the global statistics account is created.
+///
+/// ### Returns
+/// The report of the invariants.
+pub fn calculate_invariants_report(
+    state: &BlocksState,
+    total_supply: u64,
+    distribution_top_block_balance: u64,
+    distribution_bottom_block_balance: u64,
+    global_stats_minted_amount: Option<u64>,
+) -> Result<InvariantsReport> {
+    let top_block = verify_block_invariants(
+        state.top_block_number,
+        state.top_block_balance,
+        state.top_block_pending_payouts,
+        state.top_block_available_bp,
+        distribution_top_block_balance,
+    )?;
+    let bottom_block = verify_block_invariants(
+        state.bottom_block_number,
+        state.bottom_block_balance,
+        state.bottom_block_pending_payouts,
+        state.bottom_block_available_bp,
+        distribution_bottom_block_balance,
+    )?;
+    let expected_minted_amount = calculate_total_minted_amount(state)?;
+    let minted_supply_consistent = total_supply <= expected_minted_amount
+        && global_stats_minted_amount
+            .is_none_or(|minted_amount| minted_amount == expected_minted_amount);
+    let consistent = top_block.distribution_balance_consistent
+        && top_block.available_bp_consistent
+        && bottom_block.distribution_balance_consistent
+        && bottom_block.available_bp_consistent
+        && minted_supply_consistent;
+
+    Ok(InvariantsReport {
+        top_block,
+        bottom_block,
+        expected_minted_amount,
+        total_supply,
+        global_stats_minted_amount,
+        minted_supply_consistent,
+        consistent,
+    })
+}
+
+/// Allocates the reward for the BP paid from the current block following the rounding policy of the blocks (see `allocate_block_reward`):
+/// the reward is rounded down and the fraction of a dust not paid is carried in the block's rounding remainder to the following reward,
+/// so the rewards for all BPs of the block sum up exactly to the block's balance.
+/// The reward exhausting the block is the remaining balance of the block, which equals the allocated amount for the blocks allocated by the policy
+/// (the blocks in progress when the policy was introduced settle the drift of the previous per-user rounding with it).
+///
+/// ### Arguments
+///
+/// * `balance` - the balance of the block,
+/// * `available_bp` - the available BP of the block,
+/// * `rounding_remainder` - the rounding remainder of the block,
+/// * `bp` - the number of BP paid, at most the available BP of the block,
+/// * `dust_per_bp` - the dust per BP of the block.
+///
+/// ### Errors
+/// This function can return a `MismatchBetweenAvailableBlockBPAndBalance` error if the reward for the BP not exhausting the block is not lower than the block's balance.
+///
+/// ### Returns
+/// The reward and the rounding remainder of the block after the reward.
+pub fn allocate_reward_from_block(
+    balance: u64,
+    available_bp: u64,
+    rounding_remainder: u64,
+    bp: u64,
+    dust_per_bp: DustPerBp,
+) -> Result<(u64, u64)> {
+    if bp >= available_bp {
+        return Ok((balance, 0));
+    }
+
+    let (amount, rounding_remainder) = allocate_block_reward(bp, dust_per_bp, rounding_remainder)?;
+    require!(
+        amount < balance,
+        SallarError::MismatchBetweenAvailableBlockBPAndBalance
+    );
+
+    Ok((amount, rounding_remainder))
+}
+
+/// Simulates the distribution of a block solution batch without performing any transfers or state changes.
+/// The rewards are settled in order exactly as by solve_top_block and solve_bottom_block: a user receives at most the remaining BP of the block,
+/// the rewards are allocated following the rounding policy of the blocks (see `allocate_reward_from_block`) and the BPs the users do not receive are reported as their rests.
+///
+/// ### Arguments
+///
+/// * `block_number` - the number of the simulated block,
+/// * `available_bp` - the available BP of the block,
+/// * `balance` - the balance of the block,
+/// * `rounding_remainder` - the rounding remainder of the block,
+/// * `dust_per_bp` - the dust per BP of the block,
+/// * `user_rewards` - the token account and the BP calculated for each user in the order of settlement.
+///
+/// ### Returns
+/// The simulated rewards of the users and the available BP and the balance of the block after the batch.
+pub fn simulate_block_solution(
+    block_number: u64,
+    available_bp: u64,
+    balance: u64,
+    rounding_remainder: u64,
+    dust_per_bp: DustPerBp,
+    user_rewards: Vec<(Pubkey, u64)>,
+) -> Result<BlockSolutionSimulation> {
+    let mut remaining_available_bp = available_bp;
+    let mut remaining_balance = balance;
+    let mut remaining_rounding_remainder = rounding_remainder;
+    let users = user_rewards
+        .into_iter()
+        .map(|(user, reward_bp)| {
+            let bp = reward_bp.min(remaining_available_bp);
+            let amount = if remaining_available_bp == 0 {
+                0
+            } else {
+                let (amount, rounding_remainder) = allocate_reward_from_block(
+                    remaining_balance,
+                    remaining_available_bp,
+                    remaining_rounding_remainder,
+                    bp,
+                    dust_per_bp,
+                )?;
+                remaining_rounding_remainder = rounding_remainder;
+                amount
+            };
+            remaining_available_bp -= bp;
+            remaining_balance -= amount;
+
+            Ok(SimulatedUserReward {
+                user,
+                bp,
+                amount,
+                rest_bp: reward_bp - bp,
+            })
+        })
+        .collect::<Result<Vec<SimulatedUserReward>>>()?;
+
+    Ok(BlockSolutionSimulation {
+        block_number,
+        users,
+        remaining_available_bp,
+        remaining_balance,
+    })
+}
+
+/// Caches the parameters of the current top block in `BlocksState`: its max BP (which determines its dust per BP) and the BP of a request with boost.
+/// They change only when the top block is switched, so the block solutions read them from the state instead of recalculating them in every call.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// A successful result, or an error if the top block number is out of the range of blocks.
+pub fn cache_top_block_params(state: &mut BlocksState) -> Result<()> {
+    state.top_block_max_bp = calculate_max_bp(state.top_block_number)?;
+    state.top_block_bp_with_boost = calculate_top_bp_with_boost(state.top_block_number)?;
+
+    Ok(())
+}
+
+/// Caches the parameters of the current bottom block in `BlocksState`: its max BP (which determines its dust per BP) and the boost of requests with boost.
+/// They change only when the bottom block is switched, so the block solutions read them from the state instead of recalculating them in every call.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// A successful result, or an error if the bottom block number is out of the range of blocks.
+pub fn cache_bottom_block_params(state: &mut BlocksState) -> Result<()> {
+    state.bottom_block_max_bp = calculate_max_bp(state.bottom_block_number)?;
+    state.bottom_block_boost = calculate_bottom_block_max_boost(state.bottom_block_number)?;
+
+    Ok(())
+}
+
+/// Returns the cached parameters of the current top block.
+/// The parameters are calculated from the block number if they are not cached yet (the state was created before the parameters were cached).
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The BP of a request with boost and the dust per BP of the current top block.
+pub fn top_block_params(state: &BlocksState) -> Result<(u64, DustPerBp)> {
+    if state.top_block_max_bp == 0 {
+        return Ok((
+            calculate_top_bp_with_boost(state.top_block_number)?,
+            calculate_dust_per_bp(state.top_block_number)?,
+        ));
+    }
+
+    Ok((
+        state.top_block_bp_with_boost,
+        dust_per_bp_from_block_bp(state.top_block_max_bp)?,
+    ))
+}
+
+/// Returns the cached parameters of the current bottom block.
+/// The parameters are calculated from the block number if they are not cached yet (the state was created before the parameters were cached).
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The dust per BP and the boost of requests with boost of the current bottom block.
+pub fn bottom_block_params(state: &BlocksState) -> Result<(DustPerBp, u64)> {
+    if state.bottom_block_max_bp == 0 {
+        return Ok((
+            calculate_dust_per_bp(state.bottom_block_number)?,
+            calculate_bottom_block_max_boost(state.bottom_block_number)?,
+        ));
+    }
+
+    Ok((
+        dust_per_bp_from_block_bp(state.bottom_block_max_bp)?,
+        state.bottom_block_boost,
+    ))
+}
+
+/// Switches top block to the next one if the current one is already solved.
+/// It updates top block related attributes of `BlocksState`:
+/// - `top_block_solution_timestamp` to update timestamp of recently solved block to the current one,
+/// - `top_block_number` - sets next block's number (current block's number + 1),
+/// - `top_block_available_bp` - sets current block's BP to the max BP for the new current block (after switching its number),
+/// - `top_block_balance` - sets current block's balance to the max block's balance (an initial one),
+/// - `top_block_rounding_remainder` - resets the rounding remainder of the block,
+/// - `top_block_max_bp` and `top_block_bp_with_boost` - caches the parameters of the new current block (see cache_top_block_params).
+///
+/// It also mints tokens to top block distribution account for the new block and emits the `BlockSolved` event.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `mint_nonce` - the nonce of mint account,
+/// * `mint` - reference to mint account,
+/// * `distribution_top_block_account` - reference to top block distribution account where new tokens will be minted,
+/// * `token_program` - the program account for the token being used.
+///
+/// ### Errors
+/// This function can return a `MismatchBetweenAvailableBlockBPAndBalance` error if the balance and the available block's BP of the bottom block do not match.
+///
+/// ### Returns
+/// A successful result.
+pub fn switch_top_block_to_next_one_if_applicable<'a>(
+    state: &mut BlocksState,
+    mint_nonce: u8,
+    mint: &InterfaceAccount<'a, Mint>,
+    distribution_top_block_account: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+) -> Result<()> {
+    require!(
+        (state.top_block_balance == 0 && state.top_block_available_bp == 0)
+            || (state.top_block_balance > 0 && state.top_block_available_bp > 0),
+        SallarError::MismatchBetweenAvailableBlockBPAndBalance
+    );
+
+    if state.top_block_available_bp == 0 && can_block_be_switched(state) {
+        let solved_block_number = state.top_block_number;
+        let distributed_amount = DUSTS_PER_BLOCK - state.top_block_balance;
+        state.top_block_solution_timestamp = current_cluster_timestamp()?;
+        state.top_block_number += 1;
+
+        let authority = mint.to_account_info();
+        let mint_token_account = mint.to_account_info();
+
+        mint_tokens(
+            authority,
+            distribution_top_block_account,
+            mint_token_account,
+            token_program,
+            mint_nonce,
+            DUSTS_PER_BLOCK,
+        )?;
+
+        cache_top_block_params(state)?;
+        state.top_block_available_bp = state.top_block_max_bp;
+        state.top_block_balance = DUSTS_PER_BLOCK;
+        state.top_block_rounding_remainder = 0;
+
+        emit!(BlockSolved {
+            block_kind: BlockKind::Top,
+            block_number: solved_block_number,
+            solution_timestamp: state.top_block_solution_timestamp,
+            distributed_amount,
+            next_block_number: state.top_block_number,
+            solve_batch_sequence: state.solve_batch_sequence,
+        });
+    }
+
+    Ok(())
+}
+
+/// Switches bottom block to the next one if the current one is already solved.
+/// It updates bottom block related attributes of `BlocksState`:
+/// - `bottom_block_solution_timestamp` to update timestamp of recently solved block to the current one,
+/// - `bottom_block_number` - sets next block's number (current block's number + 1),
+/// - `bottom_block_available_bp` - sets current block's BP to the max BP for the new current block (after switching its number),
+/// - `bottom_block_balance` - sets current block's balance to the max block's balance (an initial one),
+/// - `bottom_block_rounding_remainder` - resets the rounding remainder of the block,
+/// - `bottom_block_max_bp` and `bottom_block_boost` - caches the parameters of the new current block (see cache_bottom_block_params).
+///
+/// It also mints tokens to bottom block distribution account for the new block and emits the `BlockSolved` event.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `mint_nonce` - the nonce of mint account,
+/// * `mint` - reference to mint account,
+/// * `distribution_bottom_block_account` - reference to bottom block distribution account where new tokens will be minted,
+/// * `token_program` - the program account for the token being used.
+///
+/// ### Errors
+/// This function can return a `MismatchBetweenAvailableBlockBPAndBalance` error if the balance and the available block's BP of the bottom block do not match.
+///
+/// ### Returns
+/// A successful result.
+pub fn switch_bottom_block_to_next_one_if_applicable<'a>(
+    state: &mut BlocksState,
+    mint_nonce: u8,
+    mint: &InterfaceAccount<'a, Mint>,
+    distribution_bottom_block_account: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+) -> Result<()> {
+    require!(
+        (state.bottom_block_balance == 0 && state.bottom_block_available_bp == 0)
+            || (state.bottom_block_balance > 0 && state.bottom_block_available_bp > 0),
+        SallarError::MismatchBetweenAvailableBlockBPAndBalance
+    );
+
+    if state.bottom_block_available_bp == 0 && can_block_be_switched(state) {
+        let solved_block_number = state.bottom_block_number;
+        let distributed_amount = DUSTS_PER_BLOCK - state.bottom_block_balance;
+        state.bottom_block_solution_timestamp = current_cluster_timestamp()?;
+        state.bottom_block_number -= 1;
+
+        let authority = mint.to_account_info();
+        let mint_token_account = mint.to_account_info();
+
+        mint_tokens(
+            authority,
+            distribution_bottom_block_account,
+            mint_token_account,
+            token_program,
+            mint_nonce,
+            DUSTS_PER_BLOCK,
+        )?;
+
+        cache_bottom_block_params(state)?;
+        state.bottom_block_available_bp = state.bottom_block_max_bp;
+        state.bottom_block_balance = DUSTS_PER_BLOCK;
+        state.bottom_block_rounding_remainder = 0;
+
+        emit!(BlockSolved {
+            block_kind: BlockKind::Bottom,
+            block_number: solved_block_number,
+            solution_timestamp: state.bottom_block_solution_timestamp,
+            distributed_amount,
+            next_block_number: state.bottom_block_number,
+            solve_batch_sequence: state.solve_batch_sequence,
+        });
+    }
+
+    Ok(())
+}
+
+/// Asserts that the minimum payout amount does not exceed the allowed maximum (1 Sallar),
+/// so the threshold cannot be used to withhold rewards from users.
+///
+/// ### Arguments
+///
+/// * `min_payout_amount` - the minimum payout amount to validate.
+///
+/// ### Returns
+/// An error if the minimum payout amount is too high, otherwise a successful result.
+pub fn valid_min_payout_amount(min_payout_amount: u64) -> Result<()> {
+    require!(
+        min_payout_amount <= MAX_MIN_PAYOUT_AMOUNT,
+        SallarError::MinPayoutAmountTooHigh
+    );
+
+    Ok(())
+}
+
+/// Asserts that the minimum balance required to participate in bottom block solutions is between 1 Sallar and 200_000 Sallar,
+/// so the threshold can neither let dust accounts into bottom block solutions nor exclude nearly all of the users.
+///
+/// ### Arguments
+///
+/// * `bottom_block_min_required_stake` - the minimum required stake to validate.
+///
+/// ### Returns
+/// An error if the minimum required stake is out of the allowed range, otherwise a successful result.
+pub fn valid_bottom_block_min_required_stake(bottom_block_min_required_stake: u64) -> Result<()> {
+    require!(
+        (MIN_BOTTOM_BLOCK_MIN_REQUIRED_STAKE..=MAX_BOTTOM_BLOCK_MIN_REQUIRED_STAKE)
+            .contains(&bottom_block_min_required_stake),
+        SallarError::InvalidBottomBlockMinRequiredStake
+    );
+
+    Ok(())
+}
+
+/// Finds the stake lock account of the given user among the provided accounts.
+/// Only accounts owned by the program and deserializable as `StakeLock` are considered.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the stake belongs to.
+///
+/// ### Returns
+/// The stake lock account of the user if it is provided, `None` otherwise.
+pub fn find_user_stake_lock_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+) -> Option<Account<'info, StakeLock>> {
+    accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .filter_map(|account| Account::<StakeLock>::try_from(account).ok())
+        .find(|stake_lock_account| stake_lock_account.user == *user)
+}
+
+/// Calculates the user's balance in bottom block solution backed by the stake lock account of the user.
+/// Only the stake locked before the current bottom block started counts, so tokens unlocked by one token account
+/// and locked again by another one within the block do not back the boost of both users in the block.
+///
+/// ### Arguments
+///
+/// * `stake_lock` - the stake lock account of the user,
+/// * `bottom_block_number` - the number of the current bottom block.
+///
+/// ### Returns
+/// The locked amount if the user has not locked tokens within the current bottom block, 0 otherwise.
+pub fn stake_lock_balance(stake_lock: &StakeLock, bottom_block_number: u64) -> u64 {
+    if stake_lock.locked_at_bottom_block == bottom_block_number {
+        0
+    } else {
+        stake_lock.locked_amount
+    }
+}
+
+/// Finds the accounts of the given user restaking its final staking rewards among the provided accounts.
+/// The user restakes the rewards if its stake lock account is provided and auto restake is enabled in it,
+/// the stake vault of the user must be provided among the accounts then.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the stake belongs to.
+///
+/// ### Returns
+/// The stake lock account and the stake vault of the user if the user restakes its rewards, `None` otherwise,
+/// or an error if the stake vault of the user restaking its rewards is not provided.
+pub fn find_user_restake_accounts<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+) -> Result<Option<(Account<'info, StakeLock>, AccountInfo<'info>)>> {
+    let Some(stake_lock_account) = find_user_stake_lock_account(accounts, user)
+        .filter(|stake_lock_account| stake_lock_account.auto_restake)
+    else {
+        return Ok(None);
+    };
+    let stake_vault_key = Pubkey::create_program_address(
+        &[
+            STAKE_VAULT_SEED.as_bytes(),
+            user.as_ref(),
+            &[stake_lock_account.vault_bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| SallarError::MissingStakeVaultAccount)?;
+    let stake_vault_account = accounts
+        .iter()
+        .find(|account| *account.key == stake_vault_key)
+        .ok_or(SallarError::MissingStakeVaultAccount)?;
+
+    Ok(Some((stake_lock_account, stake_vault_account.clone())))
+}
+
+/// Finds the boost credits account of the given user among the provided accounts.
+/// Only accounts owned by the program and deserializable as `BoostCredits` are considered.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the boost credits belong to.
+///
+/// ### Returns
+/// The boost credits account of the user if it is provided, `None` otherwise.
+pub fn find_user_boost_credits_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+) -> Option<Account<'info, BoostCredits>> {
+    accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .filter_map(|account| Account::<BoostCredits>::try_from(account).ok())
+        .find(|boost_credits_account| boost_credits_account.user == *user)
+}
+
+/// Consumes a single boost credit of the user for every request with boost of the user's entry in block solution.
+/// The boost credits account of the user must be provided among the given accounts and it must be writable.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the boost credits belong to,
+/// * `requests_with_boost` - the number of requests with boost of the user.
+///
+/// ### Returns
+/// An error if the boost credits account is not provided or the user does not have enough boost credits, otherwise a successful result.
+pub fn consume_boost_credits<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    requests_with_boost: u8,
+) -> Result<()> {
+    let mut boost_credits_account = find_user_boost_credits_account(accounts, user)
+        .ok_or_else(|| error!(SallarError::MissingBoostCreditsAccount))?;
+    boost_credits_account.credits =
+        remaining_boost_credits(boost_credits_account.credits, requests_with_boost)?;
+
+    boost_credits_account.exit(&crate::ID)
+}
+
+fn remaining_boost_credits(credits: u64, requests_with_boost: u8) -> Result<u64> {
+    credits
+        .checked_sub(u64::from(requests_with_boost))
+        .ok_or_else(|| error!(SallarError::InsufficientBoostCredits))
+}
+
+/// Asserts that the stake of the user is not registered for a final staking round which is not completed yet,
+/// so the tokens of a registered snapshot cannot be moved and registered again by another token account for the same round.
+/// The round is completed once its whole prize pool is distributed (or its claim round is opened), stake of a round which never starts is released when final staking is finalized.
+///
+/// ### Arguments
+///
+/// * `stake_lock` - the stake lock account of the user,
+/// * `state` - the current state of the contract.
+///
+/// ### Returns
+/// An error if the stake is registered for a final staking round which is not completed yet, otherwise a successful result.
+pub fn stake_not_registered_for_final_staking(
+    stake_lock: &StakeLock,
+    state: &BlocksState,
+) -> Result<()> {
+    require!(
+        state.final_staking_finalized
+            || stake_lock.registered_round_index < final_staking_round_index(state),
+        SallarError::StakeRegisteredForFinalStaking
+    );
+
+    Ok(())
+}
+
+/// Asserts that the signer can register the balance snapshot of the token account for final staking,
+/// i.e. it is the owner of the token account or the solve coordinator.
+///
+/// ### Arguments
+///
+/// * `state` - the current state of the contract,
+/// * `token_account_owner` - the owner of the registered token account,
+/// * `signer` - the account which is the signer of the current transaction.
+///
+/// ### Returns
+/// An error if the signer is neither the owner of the token account nor the solve coordinator, otherwise a successful result.
+pub fn valid_final_staking_registrant(
+    state: &BlocksState,
+    token_account_owner: &Pubkey,
+    signer: &AccountInfo,
+) -> Result<()> {
+    require!(
+        signer.is_signer
+            && (signer.key == token_account_owner || state.solve_coordinator == Some(*signer.key)),
+        SallarError::InvalidFinalStakingRegistrant
+    );
+
+    Ok(())
+}
+
+/// Finds the final staking registration account of the given user among the provided accounts.
+/// Only accounts owned by the program and deserializable as `FinalStakingRegistration` are considered.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the registration belongs to.
+///
+/// ### Returns
+/// The final staking registration account of the user if it is provided, `None` otherwise.
+pub fn find_user_final_staking_registration_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+) -> Option<Account<'info, FinalStakingRegistration>> {
+    accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .filter_map(|account| Account::<FinalStakingRegistration>::try_from(account).ok())
+        .find(|registration_account| registration_account.user == *user)
+}
+
+/// Marks the user as rewarded in the current final staking round and calculates the user's part of the round's prize pool from the balance snapshot registered for the round.
+/// The final staking registration account of the user must be provided among the given accounts and it must be writable.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the registration belongs to,
+/// * `state` - contract's state (blocks state).
+///
+/// ### Returns
+/// The part of the prize pool of the user in scaled reward parts, or an error if the registration for the current round is not provided or the user has already been rewarded.
+pub fn consume_final_staking_registration<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    state: &BlocksState,
+) -> Result<u64> {
+    let mut registration_account = find_user_final_staking_registration_account(accounts, user)
+        .ok_or_else(|| error!(SallarError::MissingFinalStakingRegistration))?;
+    require!(
+        registration_account.round_index == state.final_staking_round_counter
+            && !registration_account.rewarded,
+        SallarError::MissingFinalStakingRegistration
+    );
+    let reward_part = calculate_snapshot_reward_part(
+        registration_account.weighted_balance,
+        state.final_staking_registered_balance_in_round,
+    )?;
+    registration_account.rewarded = true;
+
+    registration_account.exit(&crate::ID)?;
+    Ok(reward_part)
+}
+
+/// Finds the claim account of the given user among the provided accounts.
+/// Only accounts owned by the program and deserializable as `UserClaimAccount` are considered.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `user` - the token account the claim account belongs to.
+///
+/// ### Returns
+/// The claim account of the user if it is provided, `None` otherwise.
+pub fn find_user_claim_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+) -> Option<Account<'info, UserClaimAccount>> {
+    accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .filter_map(|account| Account::<UserClaimAccount>::try_from(account).ok())
+        .find(|claim_account| claim_account.user == *user)
+}
+
+/// Finds the reward stats account of the given wallet among the provided accounts.
+/// Only accounts owned by the program and deserializable as `UserRewardStats` are considered.
+///
+/// ### Arguments
+///
+/// * `accounts` - the accounts to search (usually remaining accounts of the instruction),
+/// * `wallet` - the wallet the reward stats account belongs to.
+///
+/// ### Returns
+/// The reward stats account of the wallet if it is provided, `None` otherwise.
+pub fn find_user_reward_stats_account<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    wallet: &Pubkey,
+) -> Option<Account<'info, UserRewardStats>> {
+    accounts
+        .iter()
+        .filter(|account| account.owner == &crate::ID)
+        .filter_map(|account| Account::<UserRewardStats>::try_from(account).ok())
+        .find(|stats_account| stats_account.wallet == *wallet)
+}
+
+/// Returns the owner (wallet) of the given token account.
+///
+/// ### Arguments
+///
+/// * `account` - the token account.
+///
+/// ### Returns
+/// The owner of the token account, `None` if the account is not a token account.
+pub fn token_account_owner(account: &AccountInfo) -> Option<Pubkey> {
+    if account.owner != &token::ID && account.owner != &token_2022::ID {
+        return None;
+    }
+
+    let data = account.try_borrow_data().ok()?;
+    StateWithExtensions::<SplTokenAccount>::unpack(&data)
+        .map(|token_account| token_account.base.owner)
+        .ok()
+}
+
+/// Applies the minimum payout threshold to a single reward.
+/// If the reward together with the amount already accumulated for the user reaches the threshold, the whole accumulated amount is released,
+/// otherwise the reward is accumulated and nothing should be transferred.
+///
+/// ### Arguments
+///
+/// * `min_payout_amount` - the minimum payout amount,
+/// * `reward_amount` - the reward calculated for the user,
+/// * `user_pending_amount` - the amount accumulated for the user so far, it is updated by the function,
+/// * `total_pending_amount` - the amount accumulated for all users so far, it is updated by the function.
+///
+/// ### Returns
+/// The amount to transfer to the user (0 if the reward has been accumulated).
+pub fn apply_min_payout_threshold(
+    min_payout_amount: u64,
+    reward_amount: u64,
+    user_pending_amount: &mut u64,
+    total_pending_amount: &mut u64,
+) -> Result<u64> {
+    let user_total_amount = user_pending_amount
+        .checked_add(reward_amount)
+        .ok_or(SallarError::ArithmeticOverflow)?;
+
+    if user_total_amount >= min_payout_amount {
+        *total_pending_amount = total_pending_amount
+            .checked_sub(*user_pending_amount)
+            .ok_or(SallarError::ArithmeticUnderflow)?;
+        *user_pending_amount = 0;
+
+        Ok(user_total_amount)
+    } else {
+        *user_pending_amount = user_total_amount;
+        *total_pending_amount = total_pending_amount
+            .checked_add(reward_amount)
+            .ok_or(SallarError::ArithmeticOverflow)?;
+
+        Ok(0)
+    }
+}
+
+/// Settles top block reward of a single user considering the minimum payout threshold.
+/// The threshold is applied only if the user's claim account is provided, otherwise the whole reward is transferred.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `accounts` - the accounts where the user's claim account is searched,
+/// * `user` - the token account receiving the reward,
+/// * `reward_amount` - the reward calculated for the user.
+///
+/// ### Returns
+/// The amount to transfer to the user.
+pub fn settle_top_block_payout<'info>(
+    state: &mut BlocksState,
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    reward_amount: u64,
+) -> Result<u64> {
+    if state.min_payout_amount == 0 && state.top_block_pending_payouts == 0 {
+        return Ok(reward_amount);
+    }
+
+    match find_user_claim_account(accounts, user) {
+        Some(mut claim_account) => {
+            let payout_amount = apply_min_payout_threshold(
+                state.min_payout_amount,
+                reward_amount,
+                &mut claim_account.pending_top_block_amount,
+                &mut state.top_block_pending_payouts,
+            )?;
+            claim_account.exit(&crate::ID)?;
+
+            Ok(payout_amount)
+        }
+        None => Ok(reward_amount),
+    }
+}
+
+/// Settles bottom block reward of a single user considering the minimum payout threshold.
+/// The threshold is applied only if the user's claim account is provided, otherwise the whole reward is transferred.
+///
+/// ### Arguments
+///
+/// * `state` - contract's state (blocks state),
+/// * `accounts` - the accounts where the user's claim account is searched,
+/// * `user` - the token account receiving the reward,
+/// * `reward_amount` - the reward calculated for the user.
+///
+/// ### Returns
+/// The amount to transfer to the user.
+pub fn settle_bottom_block_payout<'info>(
+    state: &mut BlocksState,
+    accounts: &'info [AccountInfo<'info>],
+    user: &Pubkey,
+    reward_amount: u64,
+) -> Result<u64> {
+    if state.min_payout_amount == 0 && state.bottom_block_pending_payouts == 0 {
+        return Ok(reward_amount);
+    }
+
+    match find_user_claim_account(accounts, user) {
+        Some(mut claim_account) => {
+            let payout_amount = apply_min_payout_threshold(
+                state.min_payout_amount,
+                reward_amount,
+                &mut claim_account.pending_bottom_block_amount,
+                &mut state.bottom_block_pending_payouts,
+            )?;
+            claim_account.exit(&crate::ID)?;
+
+            Ok(payout_amount)
+        }
+        None => Ok(reward_amount),
+    }
+}
+
+/// Calculates the surplus of a distribution account, i.e. the amount of tokens held by the account beyond the tracked block accounting.
+/// The tracked block accounting consists of the balance left on the current block and the rewards accumulated in users' claim accounts.
+/// Tokens can appear on the distribution account beyond it only if they are minted or transferred there outside of the block solution process.
+///
+/// ### Arguments
+///
+/// * `distribution_account_balance` - the actual balance of the distribution account,
+/// * `block_balance` - the tracked balance left on the current block,
+/// * `pending_payouts` - the tracked amount of rewards accumulated in users' claim accounts.
+///
+/// ### Returns
+/// The surplus amount, 0 if the distribution account holds no more than the tracked block accounting.
+pub fn calculate_distribution_surplus(
+    distribution_account_balance: u64,
+    block_balance: u64,
+    pending_payouts: u64,
+) -> u64 {
+    distribution_account_balance.saturating_sub(block_balance.saturating_add(pending_payouts))
+}
+
+/// Converts a given `f64` value to an `u64` value and returns it as a result.
+/// Performs various checks to ensure that the conversion can be performed,
+/// i.e. provided `f64` number is a finite number in the range of `u64`.
+///
+/// The fractional part of the value is truncated,
+/// use `convert_integral_f64_to_u64` if the value must not lose its fractional part.
+///
+/// ### Arguments
+///
+/// * value - the f64 value to be converted to u64
+///
+/// ### Returns
+///
+/// The result of the conversion if the input value is a finite number in the scope of `u64`,
+/// or `F64IsNaN`, `F64IsInfinite` or `U64ConversionError` error otherwise.
+pub fn convert_f64_to_u64(value: f64) -> Result<u64> {
+    Ok(sallar_math::convert_f64_to_u64(value).map_err(SallarError::from)?)
+}
+
+/// Converts a given integral `f64` value to an `u64` value and returns it as a result.
+/// Performs the same checks as `convert_f64_to_u64` and rejects the values with a fractional part.
+///
+/// ### Arguments
+///
+/// * value - the integral f64 value to be converted to u64
+///
+/// ### Returns
+///
+/// The result of the conversion, or an error of `convert_f64_to_u64` or `F64NotIntegral` error if the value has a fractional part.
+pub fn convert_integral_f64_to_u64(value: f64) -> Result<u64> {
+    Ok(sallar_math::convert_integral_f64_to_u64(value).map_err(SallarError::from)?)
+}
+
+/// Asserts that the deprecated f64 reward parts and the scaled integer reward parts describe the same final staking accounting (shadow read).
+/// It is compiled only into test builds and builds with `shadow-reads` feature, so the migration can be validated before the f64 field is removed.
+///
+/// ### Arguments
+///
+/// * `legacy_reward_parts` - left reward parts tracked by the deprecated f64 field,
+/// * `scaled_reward_parts` - left reward parts tracked by the scaled integer field.
+#[cfg(any(test, feature = "shadow-reads"))]
+pub fn assert_reward_parts_shadow_consistent(legacy_reward_parts: f64, scaled_reward_parts: u64) {
+    let legacy_scaled_reward_parts = legacy_reward_parts * REWARD_PARTS_SCALING_FACTOR as f64;
+    assert!(
+        (legacy_scaled_reward_parts - scaled_reward_parts as f64).abs()
+            <= SHADOW_READ_REWARD_PARTS_TOLERANCE as f64,
+        "Shadow read mismatch: legacy reward parts {} vs scaled reward parts {}",
+        legacy_reward_parts,
+        scaled_reward_parts
+    );
+}
+
+/// Converts a given `u64` value to an `f64` value and returns it as a result.
+///
+/// ### Arguments
+///
+/// * value - the u64 value to be converted to f64
+///
+/// ### Returns
+///
+/// The result of the conversion.
+///
+pub fn convert_u64_to_f64(value: u64) -> Result<f64> {
+    Ok(sallar_math::convert_u64_to_f64(value))
+}
+
+/// Sets token metadata
+///
+/// ### Arguments
+///
+/// * `name` - token name
+/// * `symbol` - token symbol
+/// * `uri` - token uri
+pub fn set_token_metadata(
+    ctx: Context<InitializeContext>,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    let program_id = ctx.accounts.metadata_program.to_account_info();
+    let metadata_pda = ctx.accounts.metadata_pda.to_account_info();
+    let mint = ctx.accounts.mint.to_account_info();
+    let mint_authority = ctx.accounts.mint.to_account_info();
+    let payer = ctx.accounts.signer.to_account_info();
+    let update_authority = ctx.accounts.mint.to_account_info();
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let seeds = &[
+        MINT_SEED.as_bytes(),
+        &[ctx.accounts.config_account.mint_nonce],
+    ];
+
+    let data = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0u16,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+
+    CreateMetadataAccountV3CpiBuilder::new(&program_id)
+        .metadata(&metadata_pda)
+        .mint(&mint)
+        .mint_authority(&mint_authority)
+        .payer(&payer)
+        .update_authority(&update_authority, false)
+        .system_program(&system_program)
+        .data(data)
+        .is_mutable(true)
+        .invoke_signed(&[seeds])?;
+
+    Ok(())
+}
+
+/// Updates token metadata
+///
+/// ### Arguments
+///
+/// * `ctx` - the update token metadata context where all required accounts are provided,
+/// * `name` - new token name
+/// * `symbol` - new token symbol
+/// * `uri` - new token uri
+pub fn update_token_metadata(
+    ctx: Context<UpdateTokenMetadataContext>,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    let program_id = ctx.accounts.metadata_program.to_account_info();
+    let metadata_pda = ctx.accounts.metadata_pda.to_account_info();
+    let update_authority = ctx.accounts.mint.to_account_info();
+
+    let seeds = &[
+        MINT_SEED.as_bytes(),
+        &[ctx.accounts.config_account.mint_nonce],
+    ];
+
+    let data = DataV2 {
+        name,
+        symbol,
+        uri,
+        seller_fee_basis_points: 0u16,
+        creators: None,
+        collection: None,
+        uses: None,
+    };
+
+    UpdateMetadataAccountV2CpiBuilder::new(&program_id)
+        .metadata(&metadata_pda)
+        .update_authority(&update_authority)
+        .data(data)
+        .invoke_signed(&[seeds])?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use anchor_lang::err;
+    use anchor_lang::prelude::AccountInfo;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_lang::{AccountDeserialize, AccountSerialize};
+
+    use super::*;
+    use crate::token_math::MAX_BLOCK_INDEX;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    impl PartialEq for BlocksState {
+        fn eq(&self, other: &Self) -> bool {
+            self.top_block_number == other.top_block_number
+                && self.top_block_balance == other.top_block_balance
+                && self.top_block_available_bp == other.top_block_available_bp
+                && self.top_block_solution_timestamp == other.top_block_solution_timestamp
+                && self.bottom_block_number == other.bottom_block_number
+                && self.bottom_block_balance == other.bottom_block_balance
+                && self.bottom_block_available_bp == other.bottom_block_available_bp
+                && self.bottom_block_solution_timestamp == other.bottom_block_solution_timestamp
+                && self.blocks_collided == other.blocks_collided
+                && self.initial_token_distribution_already_performed
+                    == other.initial_token_distribution_already_performed
+        }
+    }
+
+    impl std::fmt::Debug for BlocksState {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             f.debug_struct("BlocksState")
-                .field("block_state_nonce", &self.block_state_nonce)
                 .field("top_block_number", &self.top_block_number)
                 .field("top_block_balance", &self.top_block_balance)
                 .field("top_block_available_bp", &self.top_block_available_bp)
@@ -566,65 +5070,2471 @@ mod test {
         }
     }
 
-    impl BlocksState {
-        pub fn default() -> Self {
-            BlocksState {
-                block_state_nonce: 0,
-                top_block_number: 0,
-                top_block_balance: 0,
-                top_block_available_bp: 0,
-                top_block_solution_timestamp: 0,
-                top_block_last_account_address: Some(Pubkey::new_unique()),
-                top_block_last_account_rest_bp: 0,
-                bottom_block_number: 0,
-                bottom_block_balance: 0,
-                bottom_block_available_bp: 0,
-                bottom_block_solution_timestamp: 0,
-                bottom_block_last_account_address: Some(Pubkey::new_unique()),
-                bottom_block_last_account_rest_bp: 0,
-                blocks_collided: false,
-                initial_token_distribution_already_performed: false,
-                authority: Pubkey::new_unique(),
-                mint_nonce: 0,
-                top_block_distribution_address: Pubkey::new_unique(),
-                top_block_distribution_nonce: 0,
-                bottom_block_distribution_address: Pubkey::new_unique(),
-                bottom_block_distribution_nonce: 0,
-                final_staking_account_nonce: 0,
-                final_staking_pool_in_round: 0,
-                final_staking_last_staking_timestamp: 0,
-                final_staking_left_reward_parts_in_round: 0.0,
-                final_staking_left_balance_in_round: 0,
-                final_mining_account_nonce: 0,
+    impl BlocksState {
+        #[allow(clippy::should_implement_trait)]
+        pub fn default() -> Self {
+            BlocksState {
+                pending_authority: None,
+                top_block_number: 0,
+                top_block_balance: 0,
+                top_block_available_bp: 0,
+                top_block_solution_timestamp: 0,
+                top_block_last_account_address: Some(Pubkey::new_unique()),
+                top_block_last_account_rest_bp: 0,
+                bottom_block_number: 0,
+                bottom_block_balance: 0,
+                bottom_block_available_bp: 0,
+                bottom_block_solution_timestamp: 0,
+                bottom_block_last_account_address: Some(Pubkey::new_unique()),
+                bottom_block_last_account_rest_bp: 0,
+                blocks_collided: false,
+                initial_token_distribution_already_performed: false,
+                authority: Pubkey::new_unique(),
+                final_staking_pool_in_round: 0,
+                final_staking_last_staking_timestamp: 0,
+                final_staking_left_reward_parts_in_round: 0.0,
+                final_staking_left_balance_in_round: 0,
+                min_payout_amount: 0,
+                top_block_pending_payouts: 0,
+                bottom_block_pending_payouts: 0,
+                treasury_address: None,
+                paused: false,
+                admin_controls_renounced: false,
+                timelock_delay: 0,
+                block_solution_interval_seconds: DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS,
+                final_staking_interval_seconds: DEFAULT_FINAL_STAKING_INTERVAL_SECONDS,
+                final_staking_pool_division_factor: 1000,
+                final_staking_left_reward_parts_in_round_scaled: 0,
+                reward_fee_bps: 0,
+                sla_threshold_seconds: 0,
+                top_block_max_solution_delay: 0,
+                bottom_block_max_solution_delay: 0,
+                final_staking_max_round_delay: 0,
+                guardian: None,
+                top_block_halted: false,
+                bottom_block_halted: false,
+                final_mining_interval_seconds: DEFAULT_FINAL_MINING_INTERVAL_SECONDS,
+                final_mining_max_payout_per_call: DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL,
+                final_mining_last_timestamp: 0,
+                final_staking_min_pool_in_round: DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND,
+                final_staking_terminal_mode: FinalStakingTerminalMode::DistributeRemaining,
+                final_staking_final_round: false,
+                final_staking_finalized: false,
+                final_staking_round_counter: 0,
+                global_stats_initialized: false,
+                block_history_enabled: false,
+                solve_batch_sequence: 0,
+                top_block_solve_batch_cursor: None,
+                top_block_unclaimed_root_rewards: 0,
+                solve_coordinator: None,
+                bottom_block_min_required_stake: DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE,
+                top_block_max_user_requests: 0,
+                bottom_block_max_user_requests: 0,
+                top_block_rest_carryovers: Vec::new(),
+                bottom_block_rest_carryovers: Vec::new(),
+                block_participation_commitment_enabled: false,
+                top_block_participation_commitment: None,
+                bottom_block_participation_commitment: None,
+                boost_credits_required: false,
+                final_staking_unclaimed_root_rewards: 0,
+                final_staking_round_start_timestamp: 0,
+                final_staking_snapshots_required: false,
+                final_staking_registered_balance_next_round: 0,
+                final_staking_registered_balance_in_round: 0,
+                final_staking_carried_remainder: 0,
+                final_staking_reward_part_tolerance_bps: None,
+                final_mining_tiers_initialized: false,
+                final_mining_budget_per_window: None,
+                final_mining_budget_window_seconds: DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS,
+                final_mining_budget_window_start: 0,
+                final_mining_budget_window_distributed: 0,
+                final_mining_checkpoint_required: false,
+                final_mining_checkpoint_balance: 0,
+                final_mining_checkpoint_slot: 0,
+                final_mining_claim_period_seconds: 0,
+                final_mining_epoch_counter: 0,
+                final_mining_epoch_timestamp: 0,
+                top_block_rounding_remainder: 0,
+                bottom_block_rounding_remainder: 0,
+                top_block_max_bp: 0,
+                top_block_bp_with_boost: 0,
+                bottom_block_max_bp: 0,
+                bottom_block_boost: 0,
+                version: BLOCKS_STATE_VERSION,
+                recent_solvers_initialized: false,
+                first_top_block_number: DEFAULT_FIRST_TOP_BLOCK_NUMBER,
+                first_bottom_block_number: DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER,
+                initial_token_distribution_amount: INITIAL_TOKEN_DISTRIBUTION_AMOUNT,
+                state_mirror_initialized: false,
+                denylist_initialized: false,
+                _reserved: [0; 101],
+            }
+        }
+    }
+
+    impl Config {
+        #[allow(clippy::should_implement_trait)]
+        pub fn default() -> Self {
+            Config {
+                bump: 0,
+                block_state_address: Pubkey::new_unique(),
+                block_state_nonce: 0,
+                mint_address: Pubkey::new_unique(),
+                mint_nonce: 0,
+                top_block_distribution_address: Pubkey::new_unique(),
+                top_block_distribution_nonce: 0,
+                bottom_block_distribution_address: Pubkey::new_unique(),
+                bottom_block_distribution_nonce: 0,
+                final_staking_account_address: Pubkey::new_unique(),
+                final_staking_account_nonce: 0,
+                final_mining_account_address: Pubkey::new_unique(),
+                final_mining_account_nonce: 0,
+                top_block_distribution_backend: DistributionBackend::ClaimCredit,
+                bottom_block_distribution_backend: DistributionBackend::ClaimCredit,
+                final_mining_distribution_backend: DistributionBackend::DirectTransfer,
+                final_staking_distribution_backend: DistributionBackend::DirectTransfer,
+            }
+        }
+    }
+
+    #[test]
+    fn test_valid_signer() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let mut binding = 0u64;
+        let deps = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        valid_signer(&deps).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_valid_signer() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let mut binding = 0u64;
+        let deps = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        valid_signer(&deps).unwrap();
+    }
+
+    #[test]
+    fn test_valid_owner() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let authority = Pubkey::new_unique();
+        let mut binding = 0u64;
+
+        let signer = AccountInfo {
+            key: &authority,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let state = BlocksState {
+            authority,
+            ..BlocksState::default()
+        };
+
+        valid_owner(&state, &signer).unwrap()
+    }
+
+    #[test]
+    fn test_recipient_account_initialized() {
+        let mut data = [0u8; SplTokenAccount::LEN];
+        SplTokenAccount::pack(
+            SplTokenAccount {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                state: AccountState::Initialized,
+                ..SplTokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let mut binding = 0u64;
+        let account = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data: Rc::new(RefCell::new(&mut data[..])),
+            owner: &token::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        recipient_account_initialized(&account).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_recipient_account_initialized_uninitialized_data() {
+        let mut data = [0u8; SplTokenAccount::LEN];
+        let mut binding = 0u64;
+        let account = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data: Rc::new(RefCell::new(&mut data[..])),
+            owner: &token::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        recipient_account_initialized(&account).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_recipient_account_initialized_wrong_owner() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let mut binding = 0u64;
+        let account = AccountInfo {
+            key: &Pubkey::new_unique(),
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        recipient_account_initialized(&account).unwrap();
+    }
+
+    #[test]
+    fn test_find_recipient_account() {
+        let mint = Pubkey::new_unique();
+        let mut data = [0u8; SplTokenAccount::LEN];
+        SplTokenAccount::pack(
+            SplTokenAccount {
+                mint,
+                owner: Pubkey::new_unique(),
+                state: AccountState::Initialized,
+                ..SplTokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let user = Pubkey::new_unique();
+        let mut binding = 0u64;
+        let accounts = [AccountInfo {
+            key: &user,
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data: Rc::new(RefCell::new(&mut data[..])),
+            owner: &token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }];
+
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &user,
+                &mint,
+                &token::ID,
+                None
+            )
+            .unwrap()
+            .key,
+            &user
+        );
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &Pubkey::new_unique(),
+                &mint,
+                &token::ID,
+                None
+            )
+            .map(|_| ()),
+            err!(SallarError::MissingRecipientAccount)
+        );
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &user,
+                &Pubkey::new_unique(),
+                &token::ID,
+                None
+            )
+            .map(|_| ()),
+            err!(SallarError::RecipientAccountMintMismatch)
+        );
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &user,
+                &mint,
+                &token::ID,
+                Some(0)
+            )
+            .unwrap()
+            .key,
+            &user
+        );
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &user,
+                &mint,
+                &token::ID,
+                Some(1)
+            )
+            .map(|_| ()),
+            err!(SallarError::RecipientAccountPositionMismatch)
+        );
+        assert_eq!(
+            find_recipient_account(
+                &Config::default(),
+                &accounts,
+                &user,
+                &mint,
+                &token_2022::ID,
+                None
+            )
+            .map(|_| ()),
+            err!(SallarError::RecipientAccountTokenProgramMismatch)
+        );
+    }
+
+    #[test]
+    fn test_valid_recipient_accounts() {
+        let config = Config::default();
+        let mint = Pubkey::new_unique();
+        let mut data = [0u8; SplTokenAccount::LEN];
+        SplTokenAccount::pack(
+            SplTokenAccount {
+                mint,
+                owner: Pubkey::new_unique(),
+                state: AccountState::Initialized,
+                ..SplTokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        let (user, new_user, other) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (mut user_lamports, mut new_user_lamports, mut other_lamports) = (0u64, 0u64, 0u64);
+        let (mut new_user_data, mut other_data) = ([0u8; 0], [0u8; 0]);
+        let account_info = |key, lamports, data, owner| AccountInfo {
+            key,
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(lamports)),
+            data: Rc::new(RefCell::new(data)),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let accounts = [
+            account_info(&user, &mut user_lamports, &mut data[..], &token::ID),
+            // not created yet, validated again after the associated token account is created
+            account_info(
+                &new_user,
+                &mut new_user_lamports,
+                &mut new_user_data[..],
+                &system_program::ID,
+            ),
+            // not a token account
+            account_info(&other, &mut other_lamports, &mut other_data[..], &crate::ID),
+        ];
+
+        valid_recipient_accounts(&config, &accounts, [&user, &new_user], &mint, &token::ID)
+            .unwrap();
+        assert_eq!(
+            valid_recipient_accounts(
+                &config,
+                &accounts,
+                [&user],
+                &Pubkey::new_unique(),
+                &token::ID
+            ),
+            err!(SallarError::RecipientAccountMintMismatch)
+        );
+        assert_eq!(
+            valid_recipient_accounts(&config, &accounts, [&user], &mint, &token_2022::ID),
+            err!(SallarError::RecipientAccountTokenProgramMismatch)
+        );
+        assert_eq!(
+            valid_recipient_accounts(&config, &accounts, [&other], &mint, &token::ID),
+            err!(SallarError::RecipientAccountNotInitialized)
+        );
+    }
+
+    #[test]
+    fn test_prepare_associated_token_account() {
+        let mint = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let user = get_associated_token_address_with_program_id(&wallet, &mint, &token::ID);
+        let payer = Pubkey::new_unique();
+        let token_program = token::ID;
+        let (mut user_lamports, mut wallet_lamports, mut payer_lamports) = (0u64, 0u64, 0u64);
+        let (mut mint_lamports, mut token_program_lamports) = (0u64, 0u64);
+        let (mut user_data, mut wallet_data, mut payer_data) = ([0u8; 0], [0u8; 0], [0u8; 0]);
+        let (mut mint_data, mut token_program_data) = ([0u8; 0], [0u8; 0]);
+        let account_info = |key, lamports, data, owner| AccountInfo {
+            key,
+            is_signer: false,
+            is_writable: true,
+            lamports: Rc::new(RefCell::new(lamports)),
+            data: Rc::new(RefCell::new(data)),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut accounts = vec![account_info(
+            &user,
+            &mut user_lamports,
+            &mut user_data[..],
+            &system_program::ID,
+        )];
+        let creation = AssociatedTokenAccountCreation {
+            payer: account_info(
+                &payer,
+                &mut payer_lamports,
+                &mut payer_data[..],
+                &system_program::ID,
+            ),
+            mint: account_info(&mint, &mut mint_lamports, &mut mint_data[..], &token::ID),
+            token_program: account_info(
+                &token_program,
+                &mut token_program_lamports,
+                &mut token_program_data[..],
+                &system_program::ID,
+            ),
+            associated_token_program: None,
+            system_program: None,
+        };
+
+        assert_eq!(
+            prepare_associated_token_account(
+                &accounts,
+                &Pubkey::new_unique(),
+                &wallet,
+                None,
+                &creation
+            ),
+            err!(SallarError::InvalidAssociatedTokenAccount)
+        );
+        assert_eq!(
+            prepare_associated_token_account(&accounts, &user, &wallet, Some(1), &creation),
+            err!(SallarError::RecipientAccountPositionMismatch)
+        );
+        assert_eq!(
+            prepare_associated_token_account(&accounts, &user, &wallet, None, &creation),
+            err!(SallarError::MissingWalletAccount)
+        );
+
+        accounts.push(account_info(
+            &wallet,
+            &mut wallet_lamports,
+            &mut wallet_data[..],
+            &system_program::ID,
+        ));
+        assert_eq!(
+            prepare_associated_token_account(&accounts, &user, &wallet, Some(0), &creation),
+            err!(SallarError::MissingAssociatedTokenProgram)
+        );
+
+        accounts[0].owner = &token::ID;
+        prepare_associated_token_account(&accounts, &user, &wallet, Some(0), &creation).unwrap();
+    }
+
+    #[test]
+    fn test_advance_solve_batch_cursor() {
+        let chunk = |batch_id, start_index| SolveBatchChunk {
+            batch_id,
+            start_index,
+            total_users: 30,
+        };
+
+        let cursor = advance_solve_batch_cursor(None, &chunk(1, 0), 20).unwrap();
+        assert_eq!(
+            cursor,
+            Some(SolveBatchCursor {
+                batch_id: 1,
+                next_user_index: 20,
+                total_users: 30,
+            })
+        );
+
+        assert_eq!(
+            advance_solve_batch_cursor(cursor, &chunk(1, 0), 20),
+            err!(SallarError::InvalidSolveBatchChunk)
+        );
+        assert_eq!(
+            advance_solve_batch_cursor(cursor, &chunk(1, 25), 5),
+            err!(SallarError::InvalidSolveBatchChunk)
+        );
+        assert_eq!(
+            advance_solve_batch_cursor(cursor, &chunk(1, 20), 11),
+            err!(SallarError::InvalidSolveBatchChunk)
+        );
+        assert_eq!(
+            advance_solve_batch_cursor(cursor, &chunk(2, 20), 10),
+            err!(SallarError::InvalidSolveBatchChunk)
+        );
+        assert_eq!(
+            advance_solve_batch_cursor(cursor, &chunk(1, 20), 10),
+            Ok(None)
+        );
+        assert!(advance_solve_batch_cursor(cursor, &chunk(2, 0), 10)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn test_fail_advance_solve_batch_cursor_chunk_too_large() {
+        let chunk = SolveBatchChunk {
+            batch_id: 1,
+            start_index: 0,
+            total_users: 100,
+        };
+
+        assert_eq!(
+            advance_solve_batch_cursor(None, &chunk, MAX_USERS_PER_SOLVE_BATCH_CHUNK + 1),
+            err!(SallarError::SolveBatchChunkTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_user_request_for_unsolved_block() {
+        user_request_for_unsolved_block(1).unwrap();
+        assert_eq!(
+            user_request_for_unsolved_block(0),
+            err!(SallarError::UserRequestForSolvedBlock)
+        );
+    }
+
+    #[test]
+    fn test_push_rest_carryover() {
+        let mut rest_carryovers = Vec::new();
+        let user = Pubkey::new_unique();
+
+        push_rest_carryover(&mut rest_carryovers, BlockKind::Top, 1, user, 100, 1).unwrap();
+        assert_eq!(rest_carryovers, vec![RestCarryover { user, rest_bp: 100 }]);
+
+        for _ in 1..MAX_REST_CARRYOVERS {
+            push_rest_carryover(
+                &mut rest_carryovers,
+                BlockKind::Top,
+                1,
+                Pubkey::new_unique(),
+                1,
+                1,
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            push_rest_carryover(
+                &mut rest_carryovers,
+                BlockKind::Top,
+                1,
+                Pubkey::new_unique(),
+                1,
+                1
+            ),
+            err!(SallarError::RestCarryoversLimitExceeded)
+        );
+        assert_eq!(rest_carryovers.len(), MAX_REST_CARRYOVERS);
+    }
+
+    #[test]
+    fn test_remaining_boost_credits() {
+        assert_eq!(remaining_boost_credits(5, 0).unwrap(), 5);
+        assert_eq!(remaining_boost_credits(5, 3).unwrap(), 2);
+        assert_eq!(remaining_boost_credits(5, 5).unwrap(), 0);
+        assert_eq!(
+            remaining_boost_credits(2, 3),
+            err!(SallarError::InsufficientBoostCredits)
+        );
+    }
+
+    #[test]
+    fn test_find_rest_carryover_entries() {
+        let users = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        let rest_carryovers = [
+            RestCarryover {
+                user: users[0],
+                rest_bp: 10,
+            },
+            RestCarryover {
+                user: users[1],
+                rest_bp: 20,
+            },
+        ];
+
+        assert_eq!(
+            find_rest_carryover_entries(&rest_carryovers, &users, 0).unwrap(),
+            vec![0, 1]
+        );
+        assert_eq!(
+            find_rest_carryover_entries(&rest_carryovers, &[users[1], users[0]], 0),
+            err!(SallarError::UserRestExistsButFirstRequestForNewBlockIsNotForThisAccount)
+        );
+        assert_eq!(
+            find_rest_carryover_entries(&rest_carryovers, &users[..1], 0),
+            err!(SallarError::UserRestExistsButFirstRequestForNewBlockIsNotForThisAccount)
+        );
+
+        assert_eq!(
+            find_rest_carryover_entries(&rest_carryovers[1..], &users, 3).unwrap(),
+            vec![1]
+        );
+        assert_eq!(
+            find_rest_carryover_entries(&rest_carryovers, &users, 1),
+            err!(SallarError::UserRestExistsButFirstRequestForNewBlockMissedTheAccount)
+        );
+    }
+
+    #[test]
+    fn test_has_duplicated_users() {
+        let first_user = Pubkey::new_unique();
+        let second_user = Pubkey::new_unique();
+
+        assert!(!has_duplicated_users([].iter()));
+        assert!(!has_duplicated_users([first_user, second_user].iter()));
+        assert!(has_duplicated_users(
+            [first_user, second_user, first_user].iter()
+        ));
+    }
+
+    #[test]
+    fn test_verify_merkle_proof() {
+        let hash_pair = |first: [u8; 32], second: [u8; 32]| {
+            if first <= second {
+                keccak::hashv(&[&first, &second]).to_bytes()
+            } else {
+                keccak::hashv(&[&second, &first]).to_bytes()
+            }
+        };
+        let leaves = [
+            calculate_block_reward_leaf(&Pubkey::new_unique(), 100),
+            calculate_block_reward_leaf(&Pubkey::new_unique(), 200),
+            calculate_block_reward_leaf(&Pubkey::new_unique(), 300),
+        ];
+        let left_node = hash_pair(leaves[0], leaves[1]);
+        let root = hash_pair(left_node, leaves[2]);
+
+        assert!(verify_merkle_proof(
+            &[leaves[1], leaves[2]],
+            &root,
+            leaves[0]
+        ));
+        assert!(verify_merkle_proof(
+            &[leaves[0], leaves[2]],
+            &root,
+            leaves[1]
+        ));
+        assert!(verify_merkle_proof(&[left_node], &root, leaves[2]));
+        assert!(verify_merkle_proof(&[], &leaves[0], leaves[0]));
+
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, leaves[0]));
+        assert!(!verify_merkle_proof(&[left_node], &root, leaves[1]));
+        assert!(!verify_merkle_proof(
+            &[leaves[1], leaves[2]],
+            &root,
+            calculate_block_reward_leaf(&Pubkey::new_unique(), 100)
+        ));
+    }
+
+    #[test]
+    fn test_ed25519_instruction_verifies() {
+        let ed25519_instruction_data =
+            |public_key: &Pubkey, message: &[u8], instruction_index: u16| {
+                let mut data = vec![1u8, 0];
+                for value in [
+                    16u16,
+                    instruction_index,
+                    80,
+                    instruction_index,
+                    112,
+                    message.len() as u16,
+                    instruction_index,
+                ] {
+                    data.extend_from_slice(&value.to_le_bytes());
+                }
+                data.extend_from_slice(&[0u8; 64]);
+                data.extend_from_slice(public_key.as_ref());
+                data.extend_from_slice(message);
+                data
+            };
+        let solve_coordinator = Pubkey::new_unique();
+        let message = [7u8; 32];
+
+        let data = ed25519_instruction_data(&solve_coordinator, &message, u16::MAX);
+        assert!(ed25519_instruction_verifies(
+            &data,
+            &solve_coordinator,
+            &message
+        ));
+        assert!(!ed25519_instruction_verifies(
+            &data,
+            &Pubkey::new_unique(),
+            &message
+        ));
+        assert!(!ed25519_instruction_verifies(
+            &data,
+            &solve_coordinator,
+            &[8u8; 32]
+        ));
+        assert!(!ed25519_instruction_verifies(
+            &data[..100],
+            &solve_coordinator,
+            &message
+        ));
+
+        let mut data_with_two_signatures = data.clone();
+        data_with_two_signatures[0] = 2;
+        assert!(!ed25519_instruction_verifies(
+            &data_with_two_signatures,
+            &solve_coordinator,
+            &message
+        ));
+
+        let data_from_other_instruction = ed25519_instruction_data(&solve_coordinator, &message, 0);
+        assert!(!ed25519_instruction_verifies(
+            &data_from_other_instruction,
+            &solve_coordinator,
+            &message
+        ));
+    }
+
+    #[test]
+    fn test_calculate_solve_batch_approval_message() {
+        let mut state = BlocksState::default();
+        let users_info = [crate::UserInfoTopBlock {
+            user_public_key: Pubkey::new_unique(),
+            user_request_without_boost: 1,
+            user_request_with_boost: 0,
+            wallet: None,
+        }];
+        let message =
+            calculate_solve_batch_approval_message(&state, BlockKind::Top, &users_info).unwrap();
+
+        assert_ne!(
+            calculate_solve_batch_approval_message(&state, BlockKind::Bottom, &users_info).unwrap(),
+            message
+        );
+        assert_ne!(
+            calculate_solve_batch_approval_message(&state, BlockKind::Top, &users_info[..0])
+                .unwrap(),
+            message
+        );
+
+        state.solve_batch_sequence += 1;
+        assert_ne!(
+            calculate_solve_batch_approval_message(&state, BlockKind::Top, &users_info).unwrap(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_calculate_block_participation_commitment() {
+        let users_info = [crate::UserInfoBottomBlock {
+            user_public_key: Pubkey::new_unique(),
+            user_request_without_boost: 1,
+            user_request_with_boost: 0,
+            wallet: None,
+        }];
+        let commitment =
+            calculate_block_participation_commitment(BlockKind::Bottom, 470_000, &users_info)
+                .unwrap();
+
+        assert_eq!(
+            calculate_block_participation_commitment(BlockKind::Bottom, 470_000, &users_info)
+                .unwrap(),
+            commitment
+        );
+        assert_ne!(
+            calculate_block_participation_commitment(BlockKind::Top, 470_000, &users_info).unwrap(),
+            commitment
+        );
+        assert_ne!(
+            calculate_block_participation_commitment(BlockKind::Bottom, 469_999, &users_info)
+                .unwrap(),
+            commitment
+        );
+        assert_ne!(
+            calculate_block_participation_commitment(BlockKind::Bottom, 470_000, &users_info[..0])
+                .unwrap(),
+            commitment
+        );
+    }
+
+    #[test]
+    fn test_participation_commitment_revealed() {
+        let hash = [7u8; 32];
+        let commitment = Some(ParticipationCommitment { hash, slot: 100 });
+
+        participation_commitment_revealed(commitment, 101, &hash).unwrap();
+        assert_eq!(
+            participation_commitment_revealed(None, 101, &hash),
+            err!(SallarError::MissingBlockParticipationCommitment)
+        );
+        assert_eq!(
+            participation_commitment_revealed(commitment, 100, &hash),
+            err!(SallarError::BlockParticipationCommitmentTooRecent)
+        );
+        assert_eq!(
+            participation_commitment_revealed(commitment, 101, &[8u8; 32]),
+            err!(SallarError::BlockParticipationCommitmentMismatch)
+        );
+    }
+
+    #[test]
+    fn test_valid_pending_authority() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let pending_authority = Pubkey::new_unique();
+        let mut binding = 0u64;
+
+        let signer = AccountInfo {
+            key: &pending_authority,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let state = BlocksState {
+            pending_authority: Some(pending_authority),
+            ..BlocksState::default()
+        };
+
+        valid_pending_authority(&state, &signer).unwrap()
+    }
+
+    #[test]
+    fn test_fail_valid_pending_authority() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let signer_key = Pubkey::new_unique();
+        let mut binding = 0u64;
+
+        let signer = AccountInfo {
+            key: &signer_key,
+            is_signer: true,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let state = BlocksState {
+            pending_authority: None,
+            ..BlocksState::default()
+        };
+        assert_eq!(
+            valid_pending_authority(&state, &signer),
+            err!(SallarError::MissingPendingAuthority)
+        );
+
+        let state = BlocksState {
+            pending_authority: Some(Pubkey::new_unique()),
+            ..BlocksState::default()
+        };
+        assert_eq!(
+            valid_pending_authority(&state, &signer),
+            err!(SallarError::SignerIsNotPendingAuthority)
+        );
+    }
+
+    #[test]
+    fn test_valid_signer_set() {
+        let signers = vec![
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+
+        valid_signer_set(&signers, 1).unwrap();
+        valid_signer_set(&signers, 3).unwrap();
+    }
+
+    #[test]
+    fn test_fail_valid_signer_set() {
+        let signer = Pubkey::new_unique();
+        let signers = vec![signer, Pubkey::new_unique()];
+
+        assert_eq!(
+            valid_signer_set(&signers, 0),
+            err!(SallarError::InvalidSignerSetThreshold)
+        );
+        assert_eq!(
+            valid_signer_set(&signers, 3),
+            err!(SallarError::InvalidSignerSetThreshold)
+        );
+        assert_eq!(
+            valid_signer_set(&[signer, signer], 1),
+            err!(SallarError::SignerDuplicatedInSignerSet)
+        );
+        assert_eq!(
+            valid_signer_set(&vec![Pubkey::new_unique(); MAX_SIGNER_SET_SIZE + 1], 1),
+            err!(SallarError::SignerSetTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_assert_reward_parts_shadow_consistent() {
+        assert_reward_parts_shadow_consistent(1.0, REWARD_PARTS_SCALING_FACTOR);
+        assert_reward_parts_shadow_consistent(0.0, 0);
+        assert_reward_parts_shadow_consistent(1.0 - 0.1 - 0.2, 700_000_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_assert_reward_parts_shadow_consistent() {
+        assert_reward_parts_shadow_consistent(0.5, 400_000_000);
+    }
+
+    #[test]
+    fn test_final_staking_round_index() {
+        let mut state = BlocksState {
+            final_staking_round_counter: 3,
+            final_staking_left_balance_in_round: 10,
+            ..BlocksState::default()
+        };
+        assert_eq!(final_staking_round_index(&state), 3);
+
+        state.final_staking_left_balance_in_round = 0;
+        assert_eq!(final_staking_round_index(&state), 4);
+    }
+
+    #[test]
+    fn test_stake_not_registered_for_final_staking() {
+        let mut state = BlocksState {
+            final_staking_round_counter: 3,
+            final_staking_left_balance_in_round: 10,
+            ..BlocksState::default()
+        };
+        let mut stake_lock = StakeLock {
+            user: Pubkey::new_unique(),
+            bump: 0,
+            locked_amount: 1_000,
+            auto_restake: false,
+            vault_bump: 0,
+            locked_at_bottom_block: 0,
+            registered_round_index: 0,
+        };
+        stake_not_registered_for_final_staking(&stake_lock, &state).unwrap();
+
+        // the stake registered for the next round cannot be unlocked and registered again by another token account
+        stake_lock.registered_round_index = 4;
+        assert_eq!(
+            stake_not_registered_for_final_staking(&stake_lock, &state),
+            err!(SallarError::StakeRegisteredForFinalStaking)
+        );
+
+        // the round is completed once its whole prize pool is distributed
+        state.final_staking_round_counter = 4;
+        assert_eq!(
+            stake_not_registered_for_final_staking(&stake_lock, &state),
+            err!(SallarError::StakeRegisteredForFinalStaking)
+        );
+        state.final_staking_left_balance_in_round = 0;
+        stake_not_registered_for_final_staking(&stake_lock, &state).unwrap();
+
+        // the stake registered for a round which never starts is released by finalization
+        stake_lock.registered_round_index = 5;
+        assert_eq!(
+            stake_not_registered_for_final_staking(&stake_lock, &state),
+            err!(SallarError::StakeRegisteredForFinalStaking)
+        );
+        state.final_staking_finalized = true;
+        stake_not_registered_for_final_staking(&stake_lock, &state).unwrap();
+    }
+
+    #[test]
+    fn test_record_final_staking_round() {
+        let mut state = BlocksState {
+            final_staking_round_counter: 2,
+            final_staking_pool_in_round: 100,
+            final_staking_left_balance_in_round: 60,
+            final_staking_last_staking_timestamp: 1_000,
+            ..BlocksState::default()
+        };
+        let mut final_staking_round = FinalStakingRound {
+            bump: 0,
+            round_index: 0,
+            pool_in_round: 0,
+            final_round: false,
+            start_timestamp: 500,
+            end_timestamp: 0,
+            participants_processed: 0,
+            distributed_amount: 0,
+        };
+
+        record_final_staking_round(&mut final_staking_round, 254, &state, 4, 40).unwrap();
+        assert_eq!(final_staking_round.bump, 254);
+        assert_eq!(final_staking_round.round_index, 2);
+        assert_eq!(final_staking_round.pool_in_round, 100);
+        assert_eq!(final_staking_round.participants_processed, 4);
+        assert_eq!(final_staking_round.distributed_amount, 40);
+        assert_eq!(final_staking_round.end_timestamp, 0);
+
+        state.final_staking_left_balance_in_round = 0;
+        state.final_staking_last_staking_timestamp = 2_000;
+        record_final_staking_round(&mut final_staking_round, 254, &state, 2, 60).unwrap();
+        assert_eq!(final_staking_round.participants_processed, 6);
+        assert_eq!(final_staking_round.distributed_amount, 100);
+        assert_eq!(final_staking_round.start_timestamp, 500);
+        assert_eq!(final_staking_round.end_timestamp, 2_000);
+    }
+
+    #[test]
+    fn test_collision_remainder_exists() {
+        let mut state = BlocksState {
+            top_block_balance: 1,
+            top_block_available_bp: 1,
+            ..BlocksState::default()
+        };
+        collision_remainder_exists(&state).unwrap();
+
+        state.top_block_balance = 0;
+        state.top_block_available_bp = 0;
+        assert_eq!(
+            collision_remainder_exists(&state),
+            err!(SallarError::NoCollisionRemainder)
+        );
+
+        state.bottom_block_balance = 1;
+        state.bottom_block_available_bp = 1;
+        collision_remainder_exists(&state).unwrap();
+    }
+
+    #[test]
+    fn test_slot_not_after() {
+        slot_not_after(99, 100).unwrap();
+        slot_not_after(100, 100).unwrap();
+        assert_eq!(slot_not_after(101, 100), err!(SallarError::BatchExpired));
+    }
+
+    #[test]
+    fn test_recipient_not_denylisted() {
+        let denylisted = Pubkey::new_unique();
+        let denylist = Denylist {
+            bump: 0,
+            token_accounts: vec![denylisted],
+        };
+
+        recipient_not_denylisted(None, &denylisted).unwrap();
+        recipient_not_denylisted(Some(&denylist), &Pubkey::new_unique()).unwrap();
+        assert_eq!(
+            recipient_not_denylisted(Some(&denylist), &denylisted),
+            err!(SallarError::RecipientDenylisted)
+        );
+    }
+
+    #[test]
+    fn test_add_to_and_remove_from_denylist() {
+        let token_account = Pubkey::new_unique();
+        let mut denylist = Denylist {
+            bump: 0,
+            token_accounts: vec![],
+        };
+
+        add_to_denylist(&mut denylist, token_account).unwrap();
+        assert_eq!(denylist.token_accounts, vec![token_account]);
+        assert_eq!(
+            add_to_denylist(&mut denylist, token_account),
+            err!(SallarError::AccountAlreadyDenylisted)
+        );
+
+        remove_from_denylist(&mut denylist, &token_account).unwrap();
+        assert!(denylist.token_accounts.is_empty());
+        assert_eq!(
+            remove_from_denylist(&mut denylist, &token_account),
+            err!(SallarError::AccountNotDenylisted)
+        );
+    }
+
+    #[test]
+    fn test_foreign_token_account() {
+        let config = Config::default();
+
+        foreign_token_account(&config, &Pubkey::new_unique()).unwrap();
+        for program_token_account in [
+            config.top_block_distribution_address,
+            config.bottom_block_distribution_address,
+            config.final_staking_account_address,
+            config.final_mining_account_address,
+        ] {
+            assert_eq!(
+                foreign_token_account(&config, &program_token_account),
+                err!(SallarError::InvalidForeignTokenAccount)
+            );
+        }
+    }
+
+    #[test]
+    fn test_program_owned_token_account() {
+        let config = Config::default();
+        let (user, token_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(!program_owned_token_account(&config, &token_account, &user));
+        for program_token_account in [
+            config.top_block_distribution_address,
+            config.bottom_block_distribution_address,
+            config.final_staking_account_address,
+            config.final_mining_account_address,
+        ] {
+            assert!(program_owned_token_account(
+                &config,
+                &program_token_account,
+                &program_token_account
+            ));
+            // a token account of the user cannot be passed off as the program's token account
+            assert!(program_owned_token_account(
+                &config,
+                &program_token_account,
+                &user
+            ));
+            // the program signs for the authority, so tokens paid to the account would be locked
+            assert!(program_owned_token_account(
+                &config,
+                &token_account,
+                &program_token_account
+            ));
+        }
+        for program_account in [config.block_state_address, config.mint_address] {
+            assert!(program_owned_token_account(
+                &config,
+                &token_account,
+                &program_account
+            ));
+        }
+        // a stake vault is its own authority
+        let stake_vault =
+            Pubkey::find_program_address(&[STAKE_VAULT_SEED.as_bytes(), user.as_ref()], &crate::ID)
+                .0;
+        assert!(program_owned_token_account(
+            &config,
+            &stake_vault,
+            &stake_vault
+        ));
+    }
+
+    #[test]
+    fn test_recipient_not_program_token_account() {
+        let config = Config::default();
+        let user = Pubkey::new_unique();
+        let stake_vault =
+            Pubkey::find_program_address(&[STAKE_VAULT_SEED.as_bytes(), user.as_ref()], &crate::ID)
+                .0;
+        let token_account_data = |owner| {
+            let mut data = [0u8; SplTokenAccount::LEN];
+            SplTokenAccount::pack(
+                SplTokenAccount {
+                    mint: config.mint_address,
+                    owner,
+                    state: AccountState::Initialized,
+                    ..SplTokenAccount::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            data
+        };
+        let recipient = |key: &Pubkey, owner: Pubkey| {
+            let mut lamports = 0u64;
+            let mut data = token_account_data(owner);
+            let account = AccountInfo {
+                key,
+                is_signer: false,
+                is_writable: true,
+                lamports: Rc::new(RefCell::new(&mut lamports)),
+                data: Rc::new(RefCell::new(&mut data[..])),
+                owner: &token::ID,
+                executable: false,
+                rent_epoch: 0,
+            };
+            recipient_not_program_token_account(&config, &account)
+        };
+
+        recipient(&Pubkey::new_unique(), Pubkey::new_unique()).unwrap();
+        for program_token_account in &[
+            config.top_block_distribution_address,
+            config.bottom_block_distribution_address,
+            config.final_staking_account_address,
+            config.final_mining_account_address,
+        ] {
+            assert_eq!(
+                recipient(program_token_account, *program_token_account),
+                err!(SallarError::RecipientIsProgramTokenAccount)
+            );
+        }
+        assert_eq!(
+            recipient(&Pubkey::new_unique(), config.block_state_address),
+            err!(SallarError::RecipientIsProgramTokenAccount)
+        );
+        assert_eq!(
+            recipient(&stake_vault, stake_vault),
+            err!(SallarError::RecipientIsProgramTokenAccount)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_staking_tiers() {
+        let tier = |min_balance_age_seconds, multiplier_bps| FinalStakingTier {
+            min_balance_age_seconds,
+            multiplier_bps,
+        };
+
+        valid_final_staking_tiers(&[]).unwrap();
+        valid_final_staking_tiers(&[
+            tier(0, 10_000),
+            tier(86_400, 12_500),
+            tier(604_800, 100_000),
+        ])
+        .unwrap();
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(86_400, 12_500), tier(86_400, 15_000)]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(604_800, 15_000), tier(86_400, 12_500)]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(86_400, 9_999)]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(86_400, 100_001)]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(-1, 12_500)]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+        assert_eq!(
+            valid_final_staking_tiers(&[tier(0, 10_000); 9]),
+            err!(SallarError::InvalidFinalStakingTiers)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_mining_tiers() {
+        let tier = |min_final_mining_balance, reward| FinalMiningTier {
+            min_final_mining_balance,
+            reward,
+        };
+
+        valid_final_mining_tiers(&DEFAULT_FINAL_MINING_TIERS).unwrap();
+        valid_final_mining_tiers(&[tier(0, 1), tier(1, MAX_FINAL_MINING_REWARD)]).unwrap();
+        assert_eq!(
+            valid_final_mining_tiers(&[]),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+        assert_eq!(
+            valid_final_mining_tiers(&[tier(1, 2_500_000_000)]),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+        assert_eq!(
+            valid_final_mining_tiers(&[tier(0, 2_500_000_000), tier(0, 5_000_000_000)]),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+        assert_eq!(
+            valid_final_mining_tiers(&[tier(0, 0)]),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+        assert_eq!(
+            valid_final_mining_tiers(&[tier(0, MAX_FINAL_MINING_REWARD + 1)]),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+        let too_many_tiers = (0..9)
+            .map(|index| tier(index, 2_500_000_000))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            valid_final_mining_tiers(&too_many_tiers),
+            err!(SallarError::InvalidFinalMiningTiers)
+        );
+    }
+
+    #[test]
+    fn test_final_mining_tiers() {
+        assert_eq!(final_mining_tiers(None), &DEFAULT_FINAL_MINING_TIERS);
+
+        let final_mining_tiers_account = FinalMiningTiers {
+            bump: 0,
+            tiers: vec![FinalMiningTier {
+                min_final_mining_balance: 0,
+                reward: 1,
+            }],
+        };
+        assert_eq!(
+            final_mining_tiers(Some(&final_mining_tiers_account)),
+            &final_mining_tiers_account.tiers[..]
+        );
+    }
+
+    #[test]
+    fn test_final_staking_tier_multiplier_bps() {
+        let tiers = [
+            FinalStakingTier {
+                min_balance_age_seconds: 86_400,
+                multiplier_bps: 12_500,
+            },
+            FinalStakingTier {
+                min_balance_age_seconds: 604_800,
+                multiplier_bps: 15_000,
+            },
+        ];
+
+        assert_eq!(final_staking_tier_multiplier_bps(&[], 1_000_000), 10_000);
+        assert_eq!(final_staking_tier_multiplier_bps(&tiers, 0), 10_000);
+        assert_eq!(final_staking_tier_multiplier_bps(&tiers, 86_399), 10_000);
+        assert_eq!(final_staking_tier_multiplier_bps(&tiers, 86_400), 12_500);
+        assert_eq!(final_staking_tier_multiplier_bps(&tiers, 604_799), 12_500);
+        assert_eq!(final_staking_tier_multiplier_bps(&tiers, 604_800), 15_000);
+    }
+
+    #[test]
+    fn test_fail_add_to_full_denylist() {
+        let mut denylist = Denylist {
+            bump: 0,
+            token_accounts: (0..MAX_DENYLIST_SIZE)
+                .map(|_| Pubkey::new_unique())
+                .collect(),
+        };
+
+        assert_eq!(
+            add_to_denylist(&mut denylist, Pubkey::new_unique()),
+            err!(SallarError::DenylistFull)
+        );
+    }
+
+    #[test]
+    fn test_blocks_solved() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 0;
+        state.bottom_block_available_bp = 0;
+        blocks_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_blocks_solved_top_block_unlock() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 1;
+        state.bottom_block_available_bp = 0;
+        blocks_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_blocks_solved_bottom_block_unlock() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 0;
+        state.bottom_block_available_bp = 1;
+        blocks_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_blocks_solved_all_blocks_unlock() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 1;
+        state.bottom_block_available_bp = 1;
+        blocks_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_valid_owner() {
+        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let authority = Pubkey::new_unique();
+        let mut binding = 0u64;
+
+        let signer = AccountInfo {
+            key: &authority,
+            is_signer: false,
+            is_writable: false,
+            lamports: Rc::new(RefCell::new(&mut binding)),
+            data,
+            owner: &Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let state = BlocksState {
+            authority: Pubkey::new_unique(),
+            ..BlocksState::default()
+        };
+
+        valid_owner(&state, &signer).unwrap()
+    }
+
+    #[test]
+    fn test_blocks_collided() {
+        let mut state = BlocksState::default();
+        state.blocks_collided = true;
+
+        blocks_collided(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_blocks_collided() {
+        let mut state = BlocksState::default();
+        state.blocks_collided = false;
+
+        blocks_collided(&state).unwrap();
+    }
+
+    #[test]
+    fn test_update_blocks_collided() {
+        let mut state = BlocksState::default();
+        state.bottom_block_number = 1;
+        state.blocks_collided = true;
+
+        update_blocks_collided(&mut state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_update_blocks_collided() {
+        let mut state = BlocksState::default();
+        state.blocks_collided = false;
+
+        update_blocks_collided(&mut state).unwrap();
+    }
+
+    #[test]
+    fn test_initial_token_distribution_not_performed_yet() {
+        let mut state = BlocksState::default();
+        state.initial_token_distribution_already_performed = false;
+
+        initial_token_distribution_not_performed_yet(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_initial_token_distribution_not_performed_yet() {
+        let mut state = BlocksState::default();
+        state.initial_token_distribution_already_performed = true;
+
+        initial_token_distribution_not_performed_yet(&state).unwrap();
+    }
+
+    #[test]
+    fn test_can_block_be_switched() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.bottom_block_number = 2;
+
+        assert!(!can_block_be_switched(&state));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_can_block_be_switched() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.bottom_block_number = 3;
+
+        assert!(!can_block_be_switched(&state));
+    }
+
+    #[test]
+    fn test_calculate_block_state_summary() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.top_block_balance = 10;
+        state.bottom_block_number = 4;
+        state.bottom_block_balance = 20;
+
+        let summary = calculate_block_state_summary(&state).unwrap();
+
+        assert_eq!(summary.top_block_number, 1);
+        assert_eq!(
+            summary.top_block_dust_per_bp,
+            calculate_dust_per_bp(1).unwrap().to_f64()
+        );
+        assert_eq!(
+            summary.top_bp_with_boost,
+            calculate_top_bp_with_boost(1).unwrap()
+        );
+        assert_eq!(summary.bottom_block_number, 4);
+        assert_eq!(
+            summary.bottom_block_dust_per_bp,
+            calculate_dust_per_bp(4).unwrap().to_f64()
+        );
+        assert_eq!(
+            summary.bottom_block_max_boost,
+            calculate_bottom_block_max_boost(4).unwrap()
+        );
+        assert_eq!(summary.remaining_blocks_before_collision, 2);
+        assert_eq!(summary.estimated_remaining_supply, 30 + 2 * DUSTS_PER_BLOCK);
+    }
+
+    #[test]
+    fn test_calculate_block_state_summary_after_collision() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 5;
+        state.bottom_block_number = 6;
+
+        let summary = calculate_block_state_summary(&state).unwrap();
+
+        assert_eq!(summary.remaining_blocks_before_collision, 0);
+        assert_eq!(summary.estimated_remaining_supply, 0);
+    }
+
+    #[test]
+    fn test_migrate_blocks_state() {
+        let mut state = BlocksState::default();
+        state.version = 0;
+        state.top_block_number = 1;
+        state.bottom_block_number = 470_000;
+        // the fields appended after the deployed layout may hold the bytes left beyond the previous layout
+        state.top_block_rounding_remainder = u64::MAX;
+        state.bottom_block_max_bp = 1;
+        state._reserved = [0xff; 101];
+
+        migrate_blocks_state(&mut state).unwrap();
+
+        assert_eq!(state.version, BLOCKS_STATE_VERSION);
+        assert_eq!(state.top_block_rounding_remainder, 0);
+        assert_eq!(state.bottom_block_rounding_remainder, 0);
+        assert_eq!(state.top_block_max_bp, calculate_max_bp(1).unwrap());
+        assert_eq!(
+            state.bottom_block_max_bp,
+            calculate_max_bp(470_000).unwrap()
+        );
+        assert_eq!(state._reserved, [0; 101]);
+
+        assert_eq!(
+            migrate_blocks_state(&mut state),
+            err!(SallarError::BlocksStateAlreadyMigrated)
+        );
+
+        assert_eq!(state.first_top_block_number, DEFAULT_FIRST_TOP_BLOCK_NUMBER);
+        assert_eq!(
+            state.first_bottom_block_number,
+            DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER
+        );
+        assert_eq!(
+            state.initial_token_distribution_amount,
+            INITIAL_TOKEN_DISTRIBUTION_AMOUNT
+        );
+
+        // the cached parameters of a state of version 1 are kept, only the extended reserved space is zero-filled
+        state.version = 1;
+        state._reserved = [0xff; 101];
+        state.top_block_rounding_remainder = 7;
+
+        migrate_blocks_state(&mut state).unwrap();
+
+        assert_eq!(state.version, BLOCKS_STATE_VERSION);
+        assert_eq!(state.top_block_rounding_remainder, 7);
+        assert_eq!(state._reserved, [0; 101]);
+    }
+
+    #[test]
+    fn test_migrate_blocks_state_of_deployed_layout() {
+        let authority = Pubkey::new_unique();
+        let top_block_last_account_address = Pubkey::new_unique();
+        let deployed_state = BlocksStateV0 {
+            authority,
+            block_state_nonce: 255,
+            mint_nonce: 254,
+            initial_token_distribution_already_performed: true,
+            blocks_collided: false,
+            top_block_number: 44_488,
+            top_block_available_bp: 1_000,
+            top_block_solution_timestamp: 1_700_000_000,
+            top_block_balance: 50 * TOKEN_AMOUNT_SCALING_FACTOR,
+            top_block_distribution_address: Pubkey::new_unique(),
+            top_block_distribution_nonce: 253,
+            top_block_last_account_address: Some(top_block_last_account_address),
+            top_block_last_account_rest_bp: 300,
+            bottom_block_number: 469_000,
+            bottom_block_available_bp: 2_000,
+            bottom_block_solution_timestamp: 1_700_000_100,
+            bottom_block_balance: DUSTS_PER_BLOCK,
+            bottom_block_distribution_address: Pubkey::new_unique(),
+            bottom_block_distribution_nonce: 252,
+            bottom_block_last_account_address: None,
+            bottom_block_last_account_rest_bp: 0,
+            final_staking_account_nonce: 251,
+            final_staking_pool_in_round: 7_000,
+            final_staking_last_staking_timestamp: 1_699_999_000,
+            final_staking_left_reward_parts_in_round: 0.25,
+            final_staking_left_balance_in_round: 1_750,
+            final_mining_account_nonce: 250,
+        };
+
+        // the space of the deployed blocks state accounts
+        assert_eq!(BlocksStateV0::INIT_SPACE, 282);
+
+        // the account of the deployed layout
+        let mut data = BlocksState::DISCRIMINATOR.to_vec();
+        deployed_state.serialize(&mut data).unwrap();
+        data.resize(
+            SallarContext::DISCRIMINATOR_LENGTH + BlocksStateV0::INIT_SPACE,
+            0,
+        );
+
+        let mut state = deserialize_blocks_state_for_migration(&data).unwrap();
+        assert_eq!(state.version, 0);
+        migrate_blocks_state(&mut state).unwrap();
+
+        assert_eq!(state.version, BLOCKS_STATE_VERSION);
+        assert_eq!(state.authority, authority);
+        assert_eq!(state.pending_authority, None);
+        assert!(state.initial_token_distribution_already_performed);
+        assert_eq!(state.top_block_number, 44_488);
+        assert_eq!(state.top_block_available_bp, 1_000);
+        assert_eq!(state.top_block_solution_timestamp, 1_700_000_000);
+        assert_eq!(state.top_block_balance, 50 * TOKEN_AMOUNT_SCALING_FACTOR);
+        assert_eq!(
+            state.top_block_rest_carryovers,
+            vec![RestCarryover {
+                user: top_block_last_account_address,
+                rest_bp: 300,
+            }]
+        );
+        assert_eq!(state.bottom_block_number, 469_000);
+        assert_eq!(state.bottom_block_available_bp, 2_000);
+        assert_eq!(state.bottom_block_balance, DUSTS_PER_BLOCK);
+        assert!(state.bottom_block_rest_carryovers.is_empty());
+        assert_eq!(state.final_staking_pool_in_round, 7_000);
+        assert_eq!(state.final_staking_last_staking_timestamp, 1_699_999_000);
+        assert_eq!(
+            state.final_staking_left_reward_parts_in_round_scaled,
+            REWARD_PARTS_SCALING_FACTOR / 4
+        );
+        assert_eq!(state.final_staking_left_balance_in_round, 1_750);
+
+        // the fields introduced since the deployed layout are set as by initialize
+        assert_eq!(
+            state.block_solution_interval_seconds,
+            DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS
+        );
+        assert_eq!(
+            state.bottom_block_min_required_stake,
+            DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE
+        );
+        assert_eq!(state.top_block_max_bp, calculate_max_bp(44_488).unwrap());
+        assert_eq!(
+            state.bottom_block_max_bp,
+            calculate_max_bp(469_000).unwrap()
+        );
+        assert_eq!(state.first_top_block_number, DEFAULT_FIRST_TOP_BLOCK_NUMBER);
+        assert_eq!(state._reserved, [0; 101]);
+
+        // the migrated state is serialized to the account and deserialized as the current layout
+        let mut migrated_data = Vec::new();
+        state.try_serialize(&mut migrated_data).unwrap();
+        assert!(
+            migrated_data.len() <= SallarContext::DISCRIMINATOR_LENGTH + BlocksState::INIT_SPACE
+        );
+        let migrated_state = BlocksState::try_deserialize(&mut &migrated_data[..]).unwrap();
+        assert_eq!(migrated_state.authority, authority);
+        assert_eq!(migrated_state.version, BLOCKS_STATE_VERSION);
+
+        data[0] ^= 1;
+        assert!(deserialize_blocks_state_for_migration(&data).is_err());
+    }
+
+    #[test]
+    fn test_migrate_blocks_state_of_versioned_layout() {
+        // the layouts are recognized by the space of the account, the accounts of version 1 lack 64 bytes of the reserved space
+        let spaces = [
+            BlocksStateV0::INIT_SPACE,
+            BlocksStateV3::INIT_SPACE - 64,
+            BlocksStateV3::INIT_SPACE,
+            BlocksState::INIT_SPACE,
+        ];
+        for (index, space) in spaces.iter().enumerate() {
+            assert!(!spaces[index + 1..].contains(space));
+        }
+
+        let zeroed_data = vec![0; BlocksStateV3::INIT_SPACE];
+        let mut versioned_state = BlocksStateV3::deserialize(&mut &zeroed_data[..]).unwrap();
+        let authority = Pubkey::new_unique();
+        let rest_carryover = RestCarryover {
+            user: Pubkey::new_unique(),
+            rest_bp: 40,
+        };
+        versioned_state.version = 3;
+        versioned_state.authority = authority;
+        versioned_state.pending_authority = Some(Pubkey::new_unique());
+        versioned_state.block_state_nonce = 255;
+        versioned_state.mint_nonce = 254;
+        versioned_state.top_block_distribution_address = Pubkey::new_unique();
+        versioned_state.top_block_number = 120;
+        versioned_state.top_block_rest_carryovers = vec![rest_carryover];
+        versioned_state.bottom_block_number = 400_120;
+        versioned_state.final_staking_account_nonce = 253;
+        versioned_state.final_staking_round_counter = 3;
+        versioned_state.final_mining_account_nonce = 252;
+        versioned_state.top_block_max_bp = 1_234;
+        versioned_state.state_mirror_initialized = true;
+        versioned_state.first_top_block_number = 100;
+        versioned_state.first_bottom_block_number = 400_200;
+        versioned_state.initial_token_distribution_amount = 5;
+
+        let mut data = BlocksState::DISCRIMINATOR.to_vec();
+        versioned_state.serialize(&mut data).unwrap();
+        data.resize(
+            SallarContext::DISCRIMINATOR_LENGTH + BlocksStateV3::INIT_SPACE,
+            0,
+        );
+
+        let mut state = deserialize_blocks_state_for_migration(&data).unwrap();
+        assert_eq!(state.version, 3);
+        migrate_blocks_state(&mut state).unwrap();
+
+        assert_eq!(state.version, BLOCKS_STATE_VERSION);
+        assert_eq!(state.authority, authority);
+        assert_eq!(state.pending_authority, versioned_state.pending_authority);
+        assert_eq!(state.top_block_number, 120);
+        assert_eq!(state.top_block_rest_carryovers, vec![rest_carryover]);
+        assert_eq!(state.bottom_block_number, 400_120);
+        assert_eq!(state.final_staking_round_counter, 3);
+        assert_eq!(state.top_block_max_bp, 1_234);
+        assert!(state.state_mirror_initialized);
+        assert_eq!(state.first_top_block_number, 100);
+        assert_eq!(state.first_bottom_block_number, 400_200);
+        assert_eq!(state.initial_token_distribution_amount, 5);
+
+        // the migrated state fits the current layout, which is smaller by the dropped fields
+        let mut migrated_data = Vec::new();
+        state.try_serialize(&mut migrated_data).unwrap();
+        assert!(
+            migrated_data.len() <= SallarContext::DISCRIMINATOR_LENGTH + BlocksState::INIT_SPACE
+        );
+        migrated_data.resize(
+            SallarContext::DISCRIMINATOR_LENGTH + BlocksState::INIT_SPACE,
+            0,
+        );
+        assert_eq!(
+            deserialize_blocks_state_for_migration(&migrated_data)
+                .unwrap()
+                .version,
+            BLOCKS_STATE_VERSION
+        );
+
+        // the account of version 1 lacks the end of the reserved space
+        versioned_state.version = 1;
+        let mut data = BlocksState::DISCRIMINATOR.to_vec();
+        versioned_state.serialize(&mut data).unwrap();
+        data.resize(
+            SallarContext::DISCRIMINATOR_LENGTH + BlocksStateV3::INIT_SPACE - 64,
+            0,
+        );
+
+        let mut state = deserialize_blocks_state_for_migration(&data).unwrap();
+        migrate_blocks_state(&mut state).unwrap();
+
+        assert_eq!(state.version, BLOCKS_STATE_VERSION);
+        assert_eq!(state.authority, authority);
+        assert_eq!(state.top_block_max_bp, 1_234);
+        assert_eq!(state.first_top_block_number, DEFAULT_FIRST_TOP_BLOCK_NUMBER);
+    }
+
+    #[test]
+    fn test_valid_initial_schedule() {
+        valid_initial_schedule(
+            DEFAULT_FIRST_TOP_BLOCK_NUMBER,
+            DEFAULT_FIRST_BOTTOM_BLOCK_NUMBER,
+            INITIAL_TOKEN_DISTRIBUTION_AMOUNT,
+        )
+        .unwrap();
+        valid_initial_schedule(400_000, 400_002, 0).unwrap();
+
+        for (first_top_block_number, first_bottom_block_number) in [
+            (0, MAX_BLOCK_INDEX),
+            (1, MAX_BLOCK_INDEX + 1),
+            (400_000, 400_001),
+            (400_000, 400_000),
+            (400_002, 400_000),
+        ] {
+            assert_eq!(
+                valid_initial_schedule(first_top_block_number, first_bottom_block_number, 0),
+                err!(SallarError::InvalidFirstBlockNumbers)
+            );
+        }
+        assert_eq!(
+            valid_initial_schedule(1, MAX_BLOCK_INDEX, u64::MAX),
+            err!(SallarError::InvalidInitialTokenDistributionAmount)
+        );
+    }
+
+    #[test]
+    fn test_sync_state_mirror() {
+        let state = BlocksState {
+            top_block_number: 7,
+            top_block_available_bp: 100,
+            top_block_balance: 200,
+            bottom_block_number: 9,
+            bottom_block_available_bp: 300,
+            bottom_block_balance: 400,
+            blocks_collided: true,
+            final_staking_round_counter: 2,
+            final_staking_pool_in_round: 500,
+            final_staking_left_balance_in_round: 600,
+            final_staking_last_staking_timestamp: 700,
+            final_staking_finalized: true,
+            ..BlocksState::default()
+        };
+        let mut state_mirror = StateMirror {
+            bump: 5,
+            top_block_number: 0,
+            top_block_available_bp: 0,
+            top_block_balance: 0,
+            bottom_block_number: 0,
+            bottom_block_available_bp: 0,
+            bottom_block_balance: 0,
+            blocks_collided: false,
+            final_staking_round_counter: 0,
+            final_staking_pool_in_round: 0,
+            final_staking_left_balance_in_round: 0,
+            final_staking_last_staking_timestamp: 0,
+            final_staking_finalized: false,
+        };
+
+        sync_state_mirror(&mut state_mirror, &state);
+
+        assert_eq!(state_mirror.bump, 5);
+        assert_eq!(state_mirror.top_block_number, 7);
+        assert_eq!(state_mirror.top_block_available_bp, 100);
+        assert_eq!(state_mirror.top_block_balance, 200);
+        assert_eq!(state_mirror.bottom_block_number, 9);
+        assert_eq!(state_mirror.bottom_block_available_bp, 300);
+        assert_eq!(state_mirror.bottom_block_balance, 400);
+        assert!(state_mirror.blocks_collided);
+        assert_eq!(state_mirror.final_staking_round_counter, 2);
+        assert_eq!(state_mirror.final_staking_pool_in_round, 500);
+        assert_eq!(state_mirror.final_staking_left_balance_in_round, 600);
+        assert_eq!(state_mirror.final_staking_last_staking_timestamp, 700);
+        assert!(state_mirror.final_staking_finalized);
+    }
+
+    #[test]
+    fn test_record_recent_solvers() {
+        let mut recent_solvers = RecentSolvers {
+            bump: 0,
+            top_block_solvers: Vec::new(),
+            top_block_next_index: 0,
+            bottom_block_solvers: Vec::new(),
+            bottom_block_next_index: 0,
+        };
+        let users: Vec<Pubkey> = (0..RECENT_SOLVERS_CAPACITY + 2)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+        let solvers: Vec<BlockSolver> = users
+            .iter()
+            .map(|user| BlockSolver {
+                user: *user,
+                amount: 1,
+            })
+            .collect();
+
+        // the account rewarded again right after itself is recorded once
+        record_recent_solvers(&mut recent_solvers, BlockKind::Top, &solvers[..2]);
+        record_recent_solvers(&mut recent_solvers, BlockKind::Top, &solvers[1..3]);
+        assert_eq!(recent_solvers.top_block_solvers, users[..3]);
+        assert_eq!(recent_solvers.top_block_next_index, 3);
+        assert!(recent_solvers.bottom_block_solvers.is_empty());
+
+        // the oldest accounts are overwritten once the buffer is full
+        record_recent_solvers(&mut recent_solvers, BlockKind::Top, &solvers[3..]);
+        assert_eq!(
+            recent_solvers.top_block_solvers.len(),
+            RECENT_SOLVERS_CAPACITY
+        );
+        assert_eq!(recent_solvers.top_block_next_index, 2);
+        assert_eq!(
+            recent_solvers.top_block_solvers[..2],
+            users[RECENT_SOLVERS_CAPACITY..]
+        );
+        assert_eq!(
+            recent_solvers.top_block_solvers[2..],
+            users[2..RECENT_SOLVERS_CAPACITY]
+        );
+
+        record_recent_solvers(&mut recent_solvers, BlockKind::Bottom, &solvers[..1]);
+        assert_eq!(recent_solvers.bottom_block_solvers, users[..1]);
+        assert_eq!(recent_solvers.bottom_block_next_index, 1);
+    }
+
+    #[test]
+    fn test_cached_block_params() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.bottom_block_number = 470_000;
+
+        // not cached yet, calculated from the block numbers
+        assert_eq!(
+            top_block_params(&state).unwrap(),
+            (
+                calculate_top_bp_with_boost(1).unwrap(),
+                calculate_dust_per_bp(1).unwrap()
+            )
+        );
+        assert_eq!(
+            bottom_block_params(&state).unwrap(),
+            (
+                calculate_dust_per_bp(470_000).unwrap(),
+                calculate_bottom_block_max_boost(470_000).unwrap()
+            )
+        );
+
+        cache_top_block_params(&mut state).unwrap();
+        cache_bottom_block_params(&mut state).unwrap();
+        assert_eq!(state.top_block_max_bp, calculate_max_bp(1).unwrap());
+        assert_eq!(
+            state.bottom_block_max_bp,
+            calculate_max_bp(470_000).unwrap()
+        );
+
+        // the cached parameters are read until the blocks are switched
+        state.top_block_number = 2;
+        state.bottom_block_number = 469_999;
+        assert_eq!(
+            top_block_params(&state).unwrap(),
+            (
+                calculate_top_bp_with_boost(1).unwrap(),
+                calculate_dust_per_bp(1).unwrap()
+            )
+        );
+        assert_eq!(
+            bottom_block_params(&state).unwrap().0,
+            calculate_dust_per_bp(470_000).unwrap()
+        );
+
+        assert!(cache_top_block_params(&mut BlocksState::default()).is_err());
+    }
+
+    #[test]
+    fn test_calculate_invariants_report() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.top_block_balance = DUSTS_PER_BLOCK - 1_000;
+        state.top_block_available_bp = calculate_max_bp(1).unwrap()
+            - 1_000 / calculate_single_reward(1, calculate_dust_per_bp(1).unwrap()).unwrap();
+        state.top_block_pending_payouts = 1_000;
+        state.bottom_block_number = 4;
+        state.bottom_block_balance = DUSTS_PER_BLOCK;
+        state.bottom_block_available_bp = calculate_max_bp(4).unwrap();
+        state.initial_token_distribution_already_performed = true;
+        let expected_minted_amount = calculate_top_blocks_minted_amount(1, 1)
+            + calculate_bottom_blocks_minted_amount(MAX_BLOCK_INDEX, 4)
+            + INITIAL_TOKEN_DISTRIBUTION_AMOUNT;
+
+        let report = calculate_invariants_report(
+            &state,
+            expected_minted_amount - 100,
+            DUSTS_PER_BLOCK,
+            DUSTS_PER_BLOCK,
+            Some(expected_minted_amount),
+        )
+        .unwrap();
+
+        assert!(report.top_block.distribution_balance_consistent);
+        assert!(report.top_block.available_bp_consistent);
+        assert!(report.bottom_block.distribution_balance_consistent);
+        assert!(report.bottom_block.available_bp_consistent);
+        assert_eq!(report.bottom_block.available_bp_amount, DUSTS_PER_BLOCK);
+        assert_eq!(report.expected_minted_amount, expected_minted_amount);
+        assert!(report.minted_supply_consistent);
+        assert!(report.consistent);
+    }
+
+    #[test]
+    fn test_calculate_invariants_report_detects_drift() {
+        let mut state = BlocksState::default();
+        state.top_block_number = 1;
+        state.top_block_balance = DUSTS_PER_BLOCK;
+        state.top_block_available_bp = calculate_max_bp(1).unwrap() - 2;
+        state.bottom_block_number = 4;
+        state.bottom_block_balance = DUSTS_PER_BLOCK;
+        state.bottom_block_available_bp = calculate_max_bp(4).unwrap();
+        let expected_minted_amount = calculate_total_minted_amount(&state).unwrap();
+
+        let report = calculate_invariants_report(
+            &state,
+            expected_minted_amount,
+            DUSTS_PER_BLOCK,
+            DUSTS_PER_BLOCK - 1,
+            Some(expected_minted_amount + 1),
+        )
+        .unwrap();
+
+        assert!(report.top_block.distribution_balance_consistent);
+        assert!(!report.top_block.available_bp_consistent);
+        assert!(!report.bottom_block.distribution_balance_consistent);
+        assert!(report.bottom_block.available_bp_consistent);
+        assert!(!report.minted_supply_consistent);
+        assert!(!report.consistent);
+
+        let report = calculate_invariants_report(
+            &state,
+            expected_minted_amount + 1,
+            DUSTS_PER_BLOCK,
+            DUSTS_PER_BLOCK,
+            None,
+        )
+        .unwrap();
+
+        assert!(!report.minted_supply_consistent);
+        assert!(!report.consistent);
+    }
+
+    #[test]
+    fn test_allocate_reward_from_block() {
+        let dust_per_bp = calculate_dust_per_bp(MAX_BLOCK_INDEX).unwrap();
+        let block_bp = dust_per_bp.block_bp();
+
+        let (first_amount, rounding_remainder) =
+            allocate_reward_from_block(DUSTS_PER_BLOCK, block_bp, 0, 1, dust_per_bp).unwrap();
+        assert_eq!(first_amount, DUSTS_PER_BLOCK / block_bp);
+        assert_eq!(rounding_remainder, DUSTS_PER_BLOCK % block_bp);
+
+        let (second_amount, rounding_remainder) = allocate_reward_from_block(
+            DUSTS_PER_BLOCK - first_amount,
+            block_bp - 1,
+            rounding_remainder,
+            1,
+            dust_per_bp,
+        )
+        .unwrap();
+        assert_eq!(first_amount + second_amount, 2 * DUSTS_PER_BLOCK / block_bp);
+        assert_eq!(rounding_remainder, 2 * DUSTS_PER_BLOCK % block_bp);
+
+        // the reward exhausting the block is the remaining balance and the remainder is reset
+        assert_eq!(
+            allocate_reward_from_block(
+                DUSTS_PER_BLOCK - first_amount - second_amount,
+                block_bp - 2,
+                rounding_remainder,
+                block_bp - 2,
+                dust_per_bp,
+            )
+            .unwrap(),
+            (DUSTS_PER_BLOCK - first_amount - second_amount, 0)
+        );
+    }
+
+    #[test]
+    fn test_allocate_reward_from_block_exceeding_balance() {
+        let dust_per_bp = calculate_dust_per_bp(MAX_BLOCK_INDEX).unwrap();
+
+        assert_eq!(
+            allocate_reward_from_block(1_000, dust_per_bp.block_bp(), 0, 1, dust_per_bp)
+                .unwrap_err(),
+            SallarError::MismatchBetweenAvailableBlockBPAndBalance.into()
+        );
+    }
+
+    #[test]
+    fn test_simulate_block_solution() {
+        let first_user = Pubkey::new_unique();
+        let second_user = Pubkey::new_unique();
+        let third_user = Pubkey::new_unique();
+        let dust_per_bp = calculate_dust_per_bp(MAX_BLOCK_INDEX).unwrap();
+        let block_bp = dust_per_bp.block_bp();
+        let (first_amount, _) = allocate_block_reward(40, dust_per_bp, 0).unwrap();
+
+        let simulation = simulate_block_solution(
+            7,
+            block_bp,
+            DUSTS_PER_BLOCK,
+            0,
+            dust_per_bp,
+            vec![(first_user, 40), (second_user, block_bp), (third_user, 10)],
+        )
+        .unwrap();
+
+        assert_eq!(
+            simulation,
+            BlockSolutionSimulation {
+                block_number: 7,
+                users: vec![
+                    SimulatedUserReward {
+                        user: first_user,
+                        bp: 40,
+                        amount: first_amount,
+                        rest_bp: 0,
+                    },
+                    SimulatedUserReward {
+                        user: second_user,
+                        bp: block_bp - 40,
+                        amount: DUSTS_PER_BLOCK - first_amount,
+                        rest_bp: 40,
+                    },
+                    SimulatedUserReward {
+                        user: third_user,
+                        bp: 0,
+                        amount: 0,
+                        rest_bp: 10,
+                    },
+                ],
+                remaining_available_bp: 0,
+                remaining_balance: 0,
             }
-        }
+        );
+    }
+
+    #[test]
+    fn test_simulate_block_solution_not_exhausting_block() {
+        let user = Pubkey::new_unique();
+        let dust_per_bp = calculate_dust_per_bp(MAX_BLOCK_INDEX).unwrap();
+        let block_bp = dust_per_bp.block_bp();
+
+        let simulation = simulate_block_solution(
+            3,
+            block_bp,
+            DUSTS_PER_BLOCK,
+            0,
+            dust_per_bp,
+            vec![(user, 1), (user, 1), (user, 1)],
+        )
+        .unwrap();
+
+        // the rounding remainders are carried, so the rewards sum up to the reward for all BPs of the batch
+        assert_eq!(
+            simulation
+                .users
+                .iter()
+                .map(|user_reward| user_reward.amount)
+                .sum::<u64>(),
+            3 * DUSTS_PER_BLOCK / block_bp
+        );
+        assert_eq!(simulation.users[2].rest_bp, 0);
+        assert_eq!(simulation.remaining_available_bp, block_bp - 3);
+        assert_eq!(
+            simulation.remaining_balance,
+            DUSTS_PER_BLOCK - 3 * DUSTS_PER_BLOCK / block_bp
+        );
+    }
+
+    #[test]
+    fn test_top_block_not_solved() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 1;
+
+        top_block_not_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_top_block_not_solved() {
+        let mut state = BlocksState::default();
+        state.top_block_available_bp = 0;
+
+        top_block_not_solved(&state).unwrap();
+    }
+
+    #[test]
+    fn test_bottom_block_not_solved() {
+        let mut state = BlocksState::default();
+        state.bottom_block_available_bp = 1;
+
+        bottom_block_not_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_bottom_block_not_solved() {
+        let mut state = BlocksState::default();
+        state.bottom_block_available_bp = 0;
+
+        bottom_block_not_solved(&state).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_final_staking_required_interval_elapsed() {
+        final_staking_required_interval_elapsed(&0, &DEFAULT_FINAL_STAKING_INTERVAL_SECONDS)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_convert_f64_to_u64_valid() {
+        assert_eq!(convert_f64_to_u64((u64::MIN) as f64), Ok(0));
+        assert_eq!(convert_f64_to_u64(123.0), Ok(123));
+        assert_eq!(convert_f64_to_u64((u64::MAX) as f64), Ok(u64::MAX));
+
+        // This is the maximum value that can be represented by f64
+        // without losing precision
+        assert_eq!(
+            convert_f64_to_u64((1u64 << 53) as f64),
+            Ok(9007199254740992)
+        );
+
+        // This value cannot be represented by f64
+        // without losing precision but it is acceptable
+        assert_eq!(
+            convert_f64_to_u64((1u64 << 53) as f64 + 1.0),
+            Ok(9007199254740992)
+        );
+
+        // This value cannot be represented by f64
+        // without losing precision but it is acceptable
+        assert_eq!(
+            convert_f64_to_u64((1u64 << 53) as f64 + 2.0),
+            Ok(9007199254740994)
+        );
+    }
+
+    #[test]
+    fn test_convert_f64_to_u64_invalid() {
+        assert_eq!(
+            convert_f64_to_u64(f64::MIN),
+            err!(SallarError::U64ConversionError)
+        );
+        assert_eq!(
+            convert_f64_to_u64(-1.0),
+            err!(SallarError::U64ConversionError)
+        );
+        assert_eq!(
+            convert_f64_to_u64(-0.1),
+            err!(SallarError::U64ConversionError)
+        );
+        assert_eq!(
+            convert_f64_to_u64(18446744073709553665.0),
+            err!(SallarError::U64ConversionError)
+        );
+        assert_eq!(
+            convert_f64_to_u64(f64::MAX),
+            err!(SallarError::U64ConversionError)
+        );
+        assert_eq!(convert_f64_to_u64(f64::NAN), err!(SallarError::F64IsNaN));
+        assert_eq!(
+            convert_f64_to_u64(f64::INFINITY),
+            err!(SallarError::F64IsInfinite)
+        );
+    }
+
+    #[test]
+    fn test_convert_integral_f64_to_u64() {
+        assert_eq!(convert_integral_f64_to_u64(123.0), Ok(123));
+        assert_eq!(
+            convert_integral_f64_to_u64(123.5),
+            err!(SallarError::F64NotIntegral)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(f64::NAN),
+            err!(SallarError::F64IsNaN)
+        );
+        assert_eq!(
+            convert_integral_f64_to_u64(f64::NEG_INFINITY),
+            err!(SallarError::F64IsInfinite)
+        );
+    }
+
+    #[test]
+    fn test_convert_u64_to_f64_valid() {
+        assert_eq!(convert_u64_to_f64(u64::MIN), Ok(0.0));
+        assert_eq!(convert_u64_to_f64(u64::MAX), Ok(18446744073709551615.0));
+        // The same value as for u64::MAX as the precision is lost in this case
+        // but it is acceptable
+        assert_eq!(convert_u64_to_f64(u64::MAX - 1), Ok(18446744073709551615.0));
+
+        // This is the maximum value that can be represented by f64
+        // without losing precision
+        assert_eq!(convert_u64_to_f64(9007199254740992), Ok(9007199254740992.0));
+
+        // This value cannot be represented by f64
+        // without losing precision but it is acceptable
+        assert_eq!(convert_u64_to_f64(9007199254740993), Ok(9007199254740992.0));
+
+        // This value cannot be represented by f64
+        // without losing precision but it is acceptable
+        assert_eq!(convert_u64_to_f64(9007199254740994), Ok(9007199254740994.0));
+    }
+
+    #[test]
+    fn test_valid_min_payout_amount() {
+        valid_min_payout_amount(0).unwrap();
+        valid_min_payout_amount(100_000).unwrap();
+        valid_min_payout_amount(MAX_MIN_PAYOUT_AMOUNT).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fail_valid_min_payout_amount() {
+        valid_min_payout_amount(MAX_MIN_PAYOUT_AMOUNT + 1).unwrap();
+    }
+
+    #[test]
+    fn test_valid_bottom_block_min_required_stake() {
+        valid_bottom_block_min_required_stake(MIN_BOTTOM_BLOCK_MIN_REQUIRED_STAKE).unwrap();
+        valid_bottom_block_min_required_stake(DEFAULT_BOTTOM_BLOCK_MIN_REQUIRED_STAKE).unwrap();
+        valid_bottom_block_min_required_stake(MAX_BOTTOM_BLOCK_MIN_REQUIRED_STAKE).unwrap();
+        assert_eq!(
+            valid_bottom_block_min_required_stake(MIN_BOTTOM_BLOCK_MIN_REQUIRED_STAKE - 1),
+            err!(SallarError::InvalidBottomBlockMinRequiredStake)
+        );
+        assert_eq!(
+            valid_bottom_block_min_required_stake(MAX_BOTTOM_BLOCK_MIN_REQUIRED_STAKE + 1),
+            err!(SallarError::InvalidBottomBlockMinRequiredStake)
+        );
+    }
+
+    #[test]
+    fn test_stake_lock_balance_of_tokens_locked_again_within_block() {
+        let (current_block, previous_block) = (400_000, 400_001);
+        let mut first_user = StakeLock {
+            user: Pubkey::new_unique(),
+            bump: 0,
+            locked_amount: 1_000,
+            auto_restake: false,
+            vault_bump: 0,
+            locked_at_bottom_block: previous_block,
+            registered_round_index: 0,
+        };
+        assert_eq!(stake_lock_balance(&first_user, current_block), 1_000);
+
+        // the tokens counted for the first user are unlocked and locked by another token account within the block
+        first_user.locked_amount = 0;
+        let mut second_user = StakeLock {
+            user: Pubkey::new_unique(),
+            locked_amount: 1_000,
+            locked_at_bottom_block: current_block,
+            ..first_user
+        };
+        assert_eq!(stake_lock_balance(&first_user, current_block), 0);
+        assert_eq!(stake_lock_balance(&second_user, current_block), 0);
+        assert_eq!(stake_lock_balance(&second_user, current_block - 1), 1_000);
+
+        // topping up the stake defers the whole locked amount to the next block
+        second_user.locked_at_bottom_block = previous_block;
+        assert_eq!(stake_lock_balance(&second_user, current_block), 1_000);
+        second_user.locked_amount = 1_500;
+        second_user.locked_at_bottom_block = current_block;
+        assert_eq!(stake_lock_balance(&second_user, current_block), 0);
+    }
+
+    #[test]
+    fn test_apply_min_payout_threshold_accumulates_reward_below_threshold() {
+        let mut user_pending_amount = 0;
+        let mut total_pending_amount = 10;
+
+        let payout_amount = apply_min_payout_threshold(
+            100,
+            40,
+            &mut user_pending_amount,
+            &mut total_pending_amount,
+        )
+        .unwrap();
+
+        assert_eq!(payout_amount, 0);
+        assert_eq!(user_pending_amount, 40);
+        assert_eq!(total_pending_amount, 50);
+    }
+
+    #[test]
+    fn test_apply_min_payout_threshold_releases_accumulated_reward() {
+        let mut user_pending_amount = 80;
+        let mut total_pending_amount = 90;
+
+        let payout_amount = apply_min_payout_threshold(
+            100,
+            40,
+            &mut user_pending_amount,
+            &mut total_pending_amount,
+        )
+        .unwrap();
+
+        assert_eq!(payout_amount, 120);
+        assert_eq!(user_pending_amount, 0);
+        assert_eq!(total_pending_amount, 10);
+    }
+
+    #[test]
+    fn test_apply_min_payout_threshold_disabled() {
+        let mut user_pending_amount = 0;
+        let mut total_pending_amount = 0;
+
+        let payout_amount =
+            apply_min_payout_threshold(0, 1, &mut user_pending_amount, &mut total_pending_amount)
+                .unwrap();
+
+        assert_eq!(payout_amount, 1);
+        assert_eq!(user_pending_amount, 0);
+        assert_eq!(total_pending_amount, 0);
+    }
+
+    #[test]
+    fn test_program_not_paused() {
+        let state = BlocksState::default();
+        program_not_paused(&state).unwrap();
+
+        let state = BlocksState {
+            paused: true,
+            ..BlocksState::default()
+        };
+        assert_eq!(program_not_paused(&state), err!(SallarError::ProgramPaused));
     }
 
     #[test]
-    fn test_valid_signer() {
-        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
-        let mut binding = 0u64;
-        let deps = AccountInfo {
-            key: &Pubkey::new_unique(),
-            is_signer: true,
-            is_writable: false,
-            lamports: Rc::new(RefCell::new(&mut binding)),
-            data,
-            owner: &Pubkey::new_unique(),
-            executable: false,
-            rent_epoch: 0,
-        };
+    fn test_block_not_halted() {
+        let mut state = BlocksState::default();
+        block_not_halted(&state, BlockKind::Top).unwrap();
+        block_not_halted(&state, BlockKind::Bottom).unwrap();
 
-        valid_signer(&deps).unwrap();
+        set_block_halted(&mut state, BlockKind::Top, true);
+        assert_eq!(
+            block_not_halted(&state, BlockKind::Top),
+            err!(SallarError::BlockHalted)
+        );
+        block_not_halted(&state, BlockKind::Bottom).unwrap();
+
+        set_block_halted(&mut state, BlockKind::Top, false);
+        block_not_halted(&state, BlockKind::Top).unwrap();
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_valid_signer() {
+    fn test_valid_guardian() {
         let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
+        let guardian = Pubkey::new_unique();
         let mut binding = 0u64;
-        let deps = AccountInfo {
-            key: &Pubkey::new_unique(),
+
+        let signer = AccountInfo {
+            key: &guardian,
             is_signer: false,
             is_writable: false,
             lamports: Rc::new(RefCell::new(&mut binding)),
@@ -634,275 +7544,1010 @@ mod test {
             rent_epoch: 0,
         };
 
-        valid_signer(&deps).unwrap();
+        assert_eq!(
+            valid_guardian(&BlocksState::default(), &signer),
+            err!(SallarError::SignerIsNotGuardian)
+        );
+
+        let state = BlocksState {
+            guardian: Some(guardian),
+            ..BlocksState::default()
+        };
+        valid_guardian(&state, &signer).unwrap();
     }
 
     #[test]
-    fn test_valid_owner() {
-        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
-        let authority = Pubkey::new_unique();
-        let mut binding = 0u64;
+    fn test_admin_controls_not_renounced() {
+        let state = BlocksState::default();
+        admin_controls_not_renounced(&state).unwrap();
 
-        let signer = AccountInfo {
-            key: &authority,
-            is_signer: false,
-            is_writable: false,
-            lamports: Rc::new(RefCell::new(&mut binding)),
-            data,
-            owner: &Pubkey::new_unique(),
-            executable: false,
-            rent_epoch: 0,
+        let state = BlocksState {
+            admin_controls_renounced: true,
+            ..BlocksState::default()
         };
+        assert_eq!(
+            admin_controls_not_renounced(&state),
+            err!(SallarError::AdminControlsRenounced)
+        );
+    }
+
+    #[test]
+    fn test_timelock_enabled_and_disabled() {
+        let state = BlocksState::default();
+        timelock_disabled(&state).unwrap();
+        assert_eq!(
+            timelock_enabled(&state),
+            err!(SallarError::TimelockDisabled)
+        );
+
         let state = BlocksState {
-            authority,
+            timelock_delay: 86_400,
             ..BlocksState::default()
         };
+        timelock_enabled(&state).unwrap();
+        assert_eq!(
+            timelock_disabled(&state),
+            err!(SallarError::TimelockEnabled)
+        );
+    }
 
-        valid_owner(&state, &signer).unwrap()
+    #[test]
+    fn test_valid_timelock_delay() {
+        valid_timelock_delay(0).unwrap();
+        valid_timelock_delay(MAX_TIMELOCK_DELAY_SECONDS).unwrap();
+        assert_eq!(
+            valid_timelock_delay(-1),
+            err!(SallarError::InvalidTimelockDelay)
+        );
+        assert_eq!(
+            valid_timelock_delay(MAX_TIMELOCK_DELAY_SECONDS + 1),
+            err!(SallarError::InvalidTimelockDelay)
+        );
     }
 
     #[test]
-    fn test_blocks_solved() {
-        let mut state = BlocksState::default();
-        state.top_block_available_bp = 0;
-        state.bottom_block_available_bp = 0;
-        blocks_solved(&state).unwrap();
+    fn test_valid_block_solution_interval() {
+        valid_block_solution_interval(MIN_BLOCK_SOLUTION_INTERVAL_SECONDS).unwrap();
+        valid_block_solution_interval(DEFAULT_BLOCK_SOLUTION_INTERVAL_SECONDS).unwrap();
+        valid_block_solution_interval(MAX_BLOCK_SOLUTION_INTERVAL_SECONDS).unwrap();
+        assert_eq!(
+            valid_block_solution_interval(MIN_BLOCK_SOLUTION_INTERVAL_SECONDS - 1),
+            err!(SallarError::InvalidBlockSolutionInterval)
+        );
+        assert_eq!(
+            valid_block_solution_interval(MAX_BLOCK_SOLUTION_INTERVAL_SECONDS + 1),
+            err!(SallarError::InvalidBlockSolutionInterval)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_blocks_solved_top_block_unlock() {
-        let mut state = BlocksState::default();
-        state.top_block_available_bp = 1;
-        state.bottom_block_available_bp = 0;
-        blocks_solved(&state).unwrap();
+    fn test_valid_final_staking_reward_part_tolerance() {
+        valid_final_staking_reward_part_tolerance(None).unwrap();
+        valid_final_staking_reward_part_tolerance(Some(0)).unwrap();
+        valid_final_staking_reward_part_tolerance(Some(
+            MAX_FINAL_STAKING_REWARD_PART_TOLERANCE_BPS,
+        ))
+        .unwrap();
+        assert_eq!(
+            valid_final_staking_reward_part_tolerance(Some(
+                MAX_FINAL_STAKING_REWARD_PART_TOLERANCE_BPS + 1
+            )),
+            err!(SallarError::InvalidFinalStakingRewardPartTolerance)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_blocks_solved_bottom_block_unlock() {
-        let mut state = BlocksState::default();
-        state.top_block_available_bp = 0;
-        state.bottom_block_available_bp = 1;
-        blocks_solved(&state).unwrap();
+    fn test_valid_reward_fee() {
+        valid_reward_fee(0).unwrap();
+        valid_reward_fee(MAX_REWARD_FEE_BPS).unwrap();
+        assert_eq!(
+            valid_reward_fee(MAX_REWARD_FEE_BPS + 1),
+            err!(SallarError::RewardFeeTooHigh)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_blocks_solved_all_blocks_unlock() {
-        let mut state = BlocksState::default();
-        state.top_block_available_bp = 1;
-        state.bottom_block_available_bp = 1;
-        blocks_solved(&state).unwrap();
+    fn test_valid_sla_threshold() {
+        valid_sla_threshold(0).unwrap();
+        valid_sla_threshold(MAX_SLA_THRESHOLD_SECONDS).unwrap();
+        assert_eq!(
+            valid_sla_threshold(-1),
+            err!(SallarError::InvalidSlaThreshold)
+        );
+        assert_eq!(
+            valid_sla_threshold(MAX_SLA_THRESHOLD_SECONDS + 1),
+            err!(SallarError::InvalidSlaThreshold)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_valid_owner() {
-        let data: Rc<RefCell<&mut [u8]>> = Rc::new(RefCell::new(&mut [0u8; 0]));
-        let authority = Pubkey::new_unique();
-        let mut binding = 0u64;
+    fn test_record_sla_delay() {
+        let mut state = BlocksState {
+            sla_threshold_seconds: 60,
+            ..BlocksState::default()
+        };
+        let interval = state.block_solution_interval_seconds;
 
-        let signer = AccountInfo {
-            key: &authority,
-            is_signer: false,
-            is_writable: false,
-            lamports: Rc::new(RefCell::new(&mut binding)),
-            data,
-            owner: &Pubkey::new_unique(),
-            executable: false,
-            rent_epoch: 0,
+        assert_eq!(
+            record_sla_delay(&mut state, SlaSubsystem::TopBlock, 0, 1_000),
+            None
+        );
+        assert_eq!(
+            record_sla_delay(
+                &mut state,
+                SlaSubsystem::TopBlock,
+                1_000,
+                1_000 + interval + 90
+            ),
+            Some(90)
+        );
+        assert_eq!(
+            record_sla_delay(
+                &mut state,
+                SlaSubsystem::TopBlock,
+                1_000,
+                1_000 + interval + 30
+            ),
+            Some(30)
+        );
+        assert_eq!(state.top_block_max_solution_delay, 90);
+        assert_eq!(state.bottom_block_max_solution_delay, 0);
+
+        let staking_interval = state.final_staking_interval_seconds;
+        assert_eq!(
+            record_sla_delay(
+                &mut state,
+                SlaSubsystem::FinalStaking,
+                1_000,
+                1_000 + staking_interval - 10
+            ),
+            Some(0)
+        );
+        assert_eq!(state.final_staking_max_round_delay, 0);
+    }
+
+    #[test]
+    fn test_valid_final_staking_config() {
+        valid_final_staking_config(DEFAULT_FINAL_STAKING_INTERVAL_SECONDS, 1000).unwrap();
+        valid_final_staking_config(
+            MIN_FINAL_STAKING_INTERVAL_SECONDS,
+            MAX_FINAL_STAKING_POOL_DIVISION_FACTOR,
+        )
+        .unwrap();
+        assert_eq!(
+            valid_final_staking_config(MAX_FINAL_STAKING_INTERVAL_SECONDS + 1, 1000),
+            err!(SallarError::InvalidFinalStakingInterval)
+        );
+        assert_eq!(
+            valid_final_staking_config(
+                DEFAULT_FINAL_STAKING_INTERVAL_SECONDS,
+                MIN_FINAL_STAKING_POOL_DIVISION_FACTOR - 1
+            ),
+            err!(SallarError::InvalidFinalStakingPoolDivisionFactor)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_mining_config() {
+        valid_final_mining_config(
+            DEFAULT_FINAL_MINING_INTERVAL_SECONDS,
+            DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL,
+        )
+        .unwrap();
+        valid_final_mining_config(0, MAX_FINAL_MINING_REWARD).unwrap();
+        valid_final_mining_config(
+            MAX_FINAL_MINING_INTERVAL_SECONDS,
+            MAX_FINAL_MINING_MAX_PAYOUT_PER_CALL,
+        )
+        .unwrap();
+        assert_eq!(
+            valid_final_mining_config(-1, DEFAULT_FINAL_MINING_MAX_PAYOUT_PER_CALL),
+            err!(SallarError::InvalidFinalMiningConfig)
+        );
+        assert_eq!(
+            valid_final_mining_config(
+                DEFAULT_FINAL_MINING_INTERVAL_SECONDS,
+                MAX_FINAL_MINING_REWARD - 1
+            ),
+            err!(SallarError::InvalidFinalMiningConfig)
+        );
+        assert_eq!(
+            valid_final_mining_config(
+                DEFAULT_FINAL_MINING_INTERVAL_SECONDS,
+                MAX_FINAL_MINING_MAX_PAYOUT_PER_CALL + 1
+            ),
+            err!(SallarError::InvalidFinalMiningConfig)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_mining_budget() {
+        valid_final_mining_budget(None, DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS).unwrap();
+        valid_final_mining_budget(Some(MAX_FINAL_MINING_REWARD), 1).unwrap();
+        valid_final_mining_budget(Some(u64::MAX), MAX_FINAL_MINING_BUDGET_WINDOW_SECONDS).unwrap();
+        assert_eq!(
+            valid_final_mining_budget(
+                Some(MAX_FINAL_MINING_REWARD - 1),
+                DEFAULT_FINAL_MINING_BUDGET_WINDOW_SECONDS
+            ),
+            err!(SallarError::InvalidFinalMiningBudget)
+        );
+        assert_eq!(
+            valid_final_mining_budget(None, 0),
+            err!(SallarError::InvalidFinalMiningBudget)
+        );
+        assert_eq!(
+            valid_final_mining_budget(None, MAX_FINAL_MINING_BUDGET_WINDOW_SECONDS + 1),
+            err!(SallarError::InvalidFinalMiningBudget)
+        );
+    }
+
+    #[test]
+    fn test_record_final_mining_budget() {
+        let mut state = BlocksState {
+            final_mining_budget_window_seconds: 100,
+            ..BlocksState::default()
         };
+        record_final_mining_budget(&mut state, u64::MAX, 1_000).unwrap();
+        assert_eq!(state.final_mining_budget_window_distributed, 0);
+
+        state.final_mining_budget_per_window = Some(50);
+        record_final_mining_budget(&mut state, 30, 1_000).unwrap();
+        assert_eq!(state.final_mining_budget_window_start, 1_000);
+        assert_eq!(state.final_mining_budget_window_distributed, 30);
+
+        record_final_mining_budget(&mut state, 20, 1_099).unwrap();
+        assert_eq!(state.final_mining_budget_window_distributed, 50);
+        assert_eq!(
+            record_final_mining_budget(&mut state, 1, 1_099),
+            err!(SallarError::FinalMiningBudgetExhausted)
+        );
+        assert_eq!(state.final_mining_budget_window_distributed, 50);
+
+        record_final_mining_budget(&mut state, 40, 1_100).unwrap();
+        assert_eq!(state.final_mining_budget_window_start, 1_100);
+        assert_eq!(state.final_mining_budget_window_distributed, 40);
+        assert_eq!(
+            record_final_mining_budget(&mut state, 11, 1_150),
+            err!(SallarError::FinalMiningBudgetExhausted)
+        );
+    }
+
+    #[test]
+    fn test_final_mining_balances_checkpointed() {
+        let state = BlocksState::default();
+        final_mining_balances_checkpointed(&state, &[]).unwrap();
+    }
+
+    #[test]
+    fn test_final_mining_balances_match_checkpoint() {
         let state = BlocksState {
-            authority: Pubkey::new_unique(),
+            final_mining_checkpoint_required: true,
+            final_mining_checkpoint_balance: 1_000,
+            final_mining_checkpoint_slot: 100,
             ..BlocksState::default()
         };
+        let user_info = |final_mining_balance| UserInfoFinalMining {
+            user_public_key: Pubkey::new_unique(),
+            final_mining_balance,
+            wallet: None,
+        };
 
-        valid_owner(&state, &signer).unwrap()
+        final_mining_balances_match_checkpoint(&state, &[user_info(1_000), user_info(1_000)], 100)
+            .unwrap();
+        final_mining_balances_match_checkpoint(
+            &state,
+            &[user_info(1_000)],
+            100 + MAX_FINAL_MINING_CHECKPOINT_AGE_SLOTS,
+        )
+        .unwrap();
+        assert_eq!(
+            final_mining_balances_match_checkpoint(
+                &state,
+                &[user_info(1_000)],
+                101 + MAX_FINAL_MINING_CHECKPOINT_AGE_SLOTS
+            ),
+            err!(SallarError::FinalMiningCheckpointStale)
+        );
+        assert_eq!(
+            final_mining_balances_match_checkpoint(
+                &state,
+                &[user_info(1_000), user_info(999)],
+                100
+            ),
+            err!(SallarError::FinalMiningBalanceNotCheckpointed)
+        );
+        assert_eq!(
+            final_mining_balances_match_checkpoint(
+                &BlocksState {
+                    final_mining_checkpoint_slot: 0,
+                    ..state
+                },
+                &[user_info(1_000)],
+                100
+            ),
+            err!(SallarError::FinalMiningCheckpointStale)
+        );
     }
 
     #[test]
-    fn test_blocks_collided() {
+    fn test_claim_period_index_at() {
+        assert_eq!(claim_period_index_at(0, 1_700_000_000), 0);
+        assert_eq!(claim_period_index_at(86_400, 0), 0);
+        assert_eq!(claim_period_index_at(86_400, 86_399), 0);
+        assert_eq!(claim_period_index_at(86_400, 86_400), 1);
+        assert_eq!(claim_period_index_at(86_400, 1_700_000_000), 19_675);
+    }
+
+    #[test]
+    fn test_final_mining_claim_period_participants_provided() {
         let mut state = BlocksState::default();
-        state.blocks_collided = true;
+        final_mining_claim_period_participants_provided(&state, &None::<()>, &None::<()>).unwrap();
 
-        blocks_collided(&state).unwrap();
+        state.final_mining_claim_period_seconds = 86_400;
+        final_mining_claim_period_participants_provided(&state, &Some(()), &Some(())).unwrap();
+        assert_eq!(
+            final_mining_claim_period_participants_provided(&state, &None::<()>, &Some(())),
+            err!(SallarError::MissingFinalMiningClaimPeriodParticipantsAccount)
+        );
+        assert_eq!(
+            final_mining_claim_period_participants_provided(&state, &Some(()), &None::<()>),
+            err!(SallarError::MissingFinalMiningClaimPeriodParticipantsAccount)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_blocks_collided() {
+    fn test_record_final_mining_claimant() {
+        let user = Pubkey::new_unique();
+        let mut period_participants = FinalMiningClaimPeriodParticipants {
+            bump: 0,
+            period_index: 5,
+            users: Vec::new(),
+        };
+
+        record_final_mining_claimant(&mut period_participants, 5, &user).unwrap();
+        record_final_mining_claimant(&mut period_participants, 5, &Pubkey::new_unique()).unwrap();
+        assert_eq!(period_participants.users.len(), 2);
+        assert_eq!(
+            record_final_mining_claimant(&mut period_participants, 5, &user),
+            err!(SallarError::FinalMiningAlreadyClaimedInPeriod)
+        );
+        assert_eq!(
+            record_final_mining_claimant(&mut period_participants, 6, &Pubkey::new_unique()),
+            err!(SallarError::FinalMiningClaimPeriodParticipantsAccountMismatch)
+        );
+        assert_eq!(period_participants.users.len(), 2);
+    }
+
+    #[test]
+    fn test_final_mining_coordinator_inactive_at() {
         let mut state = BlocksState::default();
-        state.blocks_collided = false;
+        assert!(final_mining_coordinator_inactive_at(
+            &state,
+            FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS
+        ));
 
-        blocks_collided(&state).unwrap();
+        state.final_mining_epoch_timestamp = 1_700_000_000;
+        assert!(!final_mining_coordinator_inactive_at(
+            &state,
+            1_700_000_000 + FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS - 1
+        ));
+        assert!(final_mining_coordinator_inactive_at(
+            &state,
+            1_700_000_000 + FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS
+        ));
+
+        state.final_mining_last_timestamp = 1_700_000_100;
+        assert!(!final_mining_coordinator_inactive_at(
+            &state,
+            1_700_000_000 + FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS
+        ));
+        assert!(final_mining_coordinator_inactive_at(
+            &state,
+            1_700_000_100 + FINAL_MINING_COORDINATOR_TIMEOUT_SECONDS
+        ));
     }
 
     #[test]
-    fn test_update_blocks_collided() {
+    fn test_calculate_final_mining_participation_leaf() {
+        let user = Pubkey::new_unique();
+
+        assert_eq!(
+            calculate_final_mining_participation_leaf(&user, 1),
+            keccak::hashv(&[user.as_ref(), &1u64.to_le_bytes()]).to_bytes()
+        );
+        assert_ne!(
+            calculate_final_mining_participation_leaf(&user, 1),
+            calculate_final_mining_participation_leaf(&user, 2)
+        );
+        assert_ne!(
+            calculate_final_mining_participation_leaf(&user, 1),
+            calculate_final_mining_participation_leaf(&Pubkey::new_unique(), 1)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_mining_claim_period() {
+        valid_final_mining_claim_period(0).unwrap();
+        valid_final_mining_claim_period(MAX_FINAL_MINING_CLAIM_PERIOD_SECONDS).unwrap();
+        assert_eq!(
+            valid_final_mining_claim_period(-1),
+            err!(SallarError::InvalidFinalMiningClaimPeriod)
+        );
+        assert_eq!(
+            valid_final_mining_claim_period(MAX_FINAL_MINING_CLAIM_PERIOD_SECONDS + 1),
+            err!(SallarError::InvalidFinalMiningClaimPeriod)
+        );
+    }
+
+    #[test]
+    fn test_final_mining_payout_within_limit() {
+        let state = BlocksState {
+            final_mining_max_payout_per_call: 2 * MAX_FINAL_MINING_REWARD,
+            ..BlocksState::default()
+        };
+        let user_info = || UserInfoFinalMining {
+            user_public_key: Pubkey::new_unique(),
+            final_mining_balance: u64::MAX,
+            wallet: None,
+        };
+
+        final_mining_payout_within_limit(&state, &None, &[user_info(), user_info()]).unwrap();
+        assert_eq!(
+            final_mining_payout_within_limit(
+                &state,
+                &None,
+                &[user_info(), user_info(), user_info()]
+            ),
+            err!(SallarError::FinalMiningPayoutTooHigh)
+        );
+    }
+
+    #[test]
+    fn test_valid_final_staking_terminal_config() {
+        let state = BlocksState::default();
+        let state_with_treasury = BlocksState {
+            treasury_address: Some(Pubkey::new_unique()),
+            ..BlocksState::default()
+        };
+
+        valid_final_staking_terminal_config(
+            &state,
+            DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND,
+            FinalStakingTerminalMode::DistributeRemaining,
+        )
+        .unwrap();
+        valid_final_staking_terminal_config(
+            &state,
+            0,
+            FinalStakingTerminalMode::DistributeRemaining,
+        )
+        .unwrap();
+        valid_final_staking_terminal_config(
+            &state_with_treasury,
+            MAX_FINAL_STAKING_MIN_POOL_IN_ROUND,
+            FinalStakingTerminalMode::SweepToTreasury,
+        )
+        .unwrap();
+        assert_eq!(
+            valid_final_staking_terminal_config(
+                &state,
+                MAX_FINAL_STAKING_MIN_POOL_IN_ROUND + 1,
+                FinalStakingTerminalMode::DistributeRemaining
+            ),
+            err!(SallarError::InvalidFinalStakingTerminalConfig)
+        );
+        assert_eq!(
+            valid_final_staking_terminal_config(
+                &state,
+                DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND,
+                FinalStakingTerminalMode::SweepToTreasury
+            ),
+            err!(SallarError::InvalidFinalStakingTerminalConfig)
+        );
+    }
+
+    #[test]
+    fn test_final_staking_terminal_transition_required() {
+        let state = BlocksState::default();
+
+        assert!(final_staking_terminal_transition_required(
+            &state,
+            DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND - 1
+        ));
+        assert!(!final_staking_terminal_transition_required(
+            &state,
+            DEFAULT_FINAL_STAKING_MIN_POOL_IN_ROUND
+        ));
+
+        let state = BlocksState {
+            final_staking_min_pool_in_round: 0,
+            ..BlocksState::default()
+        };
+        assert!(!final_staking_terminal_transition_required(&state, 0));
+    }
+
+    #[test]
+    fn test_global_stats_provided() {
         let mut state = BlocksState::default();
-        state.bottom_block_number = 1;
-        state.blocks_collided = true;
+        global_stats_provided(&state, &None::<()>).unwrap();
+        global_stats_provided(&state, &Some(())).unwrap();
 
-        update_blocks_collided(&mut state).unwrap();
+        state.global_stats_initialized = true;
+        global_stats_provided(&state, &Some(())).unwrap();
+        assert_eq!(
+            global_stats_provided(&state, &None::<()>),
+            err!(SallarError::MissingGlobalStatsAccount)
+        );
+    }
+
+    #[test]
+    fn test_denylist_provided() {
+        let mut state = BlocksState::default();
+        denylist_provided(&state, &None::<()>).unwrap();
+        denylist_provided(&state, &Some(())).unwrap();
+
+        state.denylist_initialized = true;
+        denylist_provided(&state, &Some(())).unwrap();
+        assert_eq!(
+            denylist_provided(&state, &None::<()>),
+            err!(SallarError::MissingDenylistAccount)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_update_blocks_collided() {
+    fn test_final_mining_tiers_provided() {
         let mut state = BlocksState::default();
-        state.blocks_collided = false;
+        final_mining_tiers_provided(&state, &None::<()>).unwrap();
+        final_mining_tiers_provided(&state, &Some(())).unwrap();
 
-        update_blocks_collided(&mut state).unwrap();
+        state.final_mining_tiers_initialized = true;
+        final_mining_tiers_provided(&state, &Some(())).unwrap();
+        assert_eq!(
+            final_mining_tiers_provided(&state, &None::<()>),
+            err!(SallarError::MissingFinalMiningTiersAccount)
+        );
     }
 
     #[test]
-    fn test_initial_token_distribution_not_performed_yet() {
+    fn test_block_history_provided() {
         let mut state = BlocksState::default();
-        state.initial_token_distribution_already_performed = false;
+        block_history_provided(&state, &None::<()>, &None::<()>).unwrap();
 
-        initial_token_distribution_not_performed_yet(&state).unwrap();
+        state.block_history_enabled = true;
+        block_history_provided(&state, &Some(()), &Some(())).unwrap();
+        assert_eq!(
+            block_history_provided(&state, &None::<()>, &Some(())),
+            err!(SallarError::MissingBlockHistoryAccount)
+        );
+        assert_eq!(
+            block_history_provided(&state, &Some(()), &None::<()>),
+            err!(SallarError::MissingBlockHistoryAccount)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_initial_token_distribution_not_performed_yet() {
+    fn test_block_user_requests_provided() {
         let mut state = BlocksState::default();
-        state.initial_token_distribution_already_performed = true;
+        block_user_requests_provided(&state, BlockKind::Top, &None::<()>, &None::<()>).unwrap();
 
-        initial_token_distribution_not_performed_yet(&state).unwrap();
+        state.top_block_max_user_requests = 10;
+        block_user_requests_provided(&state, BlockKind::Top, &Some(()), &Some(())).unwrap();
+        block_user_requests_provided(&state, BlockKind::Bottom, &None::<()>, &None::<()>).unwrap();
+        assert_eq!(
+            block_user_requests_provided(&state, BlockKind::Top, &None::<()>, &Some(())),
+            err!(SallarError::MissingBlockUserRequestsAccount)
+        );
+        assert_eq!(
+            block_user_requests_provided(&state, BlockKind::Top, &Some(()), &None::<()>),
+            err!(SallarError::MissingBlockUserRequestsAccount)
+        );
     }
 
     #[test]
-    fn test_can_block_be_switched() {
-        let mut state = BlocksState::default();
-        state.top_block_number = 1;
-        state.bottom_block_number = 2;
+    fn test_skip_user_entry() {
+        let error: Error = SallarError::MissingRecipientAccount.into();
+        let mut skipped_user_entries = Vec::new();
+
+        skip_user_entry(
+            &mut skipped_user_entries,
+            BlockKind::Top,
+            1,
+            &Pubkey::new_unique(),
+            &error,
+            1,
+        );
+        assert_eq!(skipped_user_entries, vec![0b0000_0010]);
 
-        assert_eq!(can_block_be_switched(&state), false);
+        skip_user_entry(
+            &mut skipped_user_entries,
+            BlockKind::Top,
+            17,
+            &Pubkey::new_unique(),
+            &error,
+            1,
+        );
+        skip_user_entry(
+            &mut skipped_user_entries,
+            BlockKind::Top,
+            8,
+            &Pubkey::new_unique(),
+            &error,
+            1,
+        );
+        assert_eq!(
+            skipped_user_entries,
+            vec![0b0000_0010, 0b0000_0001, 0b0000_0010]
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_can_block_be_switched() {
-        let mut state = BlocksState::default();
-        state.top_block_number = 1;
-        state.bottom_block_number = 3;
+    fn test_record_user_requests() {
+        let user = Pubkey::new_unique();
+        let other_user = Pubkey::new_unique();
+        let mut block_user_requests = BlockUserRequests {
+            bump: 0,
+            block_kind: BlockKind::Top,
+            block_number: 5,
+            users: Vec::new(),
+        };
+
+        record_user_requests(&mut block_user_requests, 5, &user, 6, 10).unwrap();
+        record_user_requests(&mut block_user_requests, 5, &other_user, 10, 10).unwrap();
+        record_user_requests(&mut block_user_requests, 5, &user, 4, 10).unwrap();
+        assert_eq!(
+            block_user_requests.users,
+            vec![
+                UserRequests { user, requests: 10 },
+                UserRequests {
+                    user: other_user,
+                    requests: 10
+                },
+            ]
+        );
 
-        assert_eq!(can_block_be_switched(&state), false);
+        assert_eq!(
+            record_user_requests(&mut block_user_requests, 5, &user, 1, 10),
+            err!(SallarError::UserRequestsPerBlockExceeded)
+        );
+        assert_eq!(block_user_requests.users[0].requests, 10);
+        record_user_requests(&mut block_user_requests, 5, &other_user, 300, 0).unwrap();
+        assert_eq!(
+            record_user_requests(&mut block_user_requests, 6, &user, 1, 10),
+            err!(SallarError::BlockUserRequestsAccountMismatch)
+        );
     }
 
     #[test]
-    fn test_top_block_not_solved() {
+    fn test_final_staking_round_participants_provided() {
         let mut state = BlocksState::default();
-        state.top_block_available_bp = 1;
+        final_staking_round_participants_provided(&state, &Some(()), &Some(())).unwrap();
+        assert_eq!(
+            final_staking_round_participants_provided(&state, &None::<()>, &Some(())),
+            err!(SallarError::MissingFinalStakingRoundParticipantsAccount)
+        );
+        assert_eq!(
+            final_staking_round_participants_provided(&state, &Some(()), &None::<()>),
+            err!(SallarError::MissingFinalStakingRoundParticipantsAccount)
+        );
 
-        top_block_not_solved(&state).unwrap();
+        state.final_staking_snapshots_required = true;
+        final_staking_round_participants_provided(&state, &None::<()>, &None::<()>).unwrap();
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_top_block_not_solved() {
-        let mut state = BlocksState::default();
-        state.top_block_available_bp = 0;
+    fn test_record_final_staking_participant() {
+        let user = Pubkey::new_unique();
+        let other_user = Pubkey::new_unique();
+        let mut round_participants = FinalStakingRoundParticipants {
+            bump: 0,
+            round_index: 3,
+            users: Vec::new(),
+        };
 
-        top_block_not_solved(&state).unwrap();
+        record_final_staking_participant(&mut round_participants, 3, &user).unwrap();
+        record_final_staking_participant(&mut round_participants, 3, &other_user).unwrap();
+        assert_eq!(round_participants.users, vec![user, other_user]);
+
+        assert_eq!(
+            record_final_staking_participant(&mut round_participants, 3, &user),
+            err!(SallarError::FinalStakingParticipantAlreadyRewarded)
+        );
+        assert_eq!(
+            record_final_staking_participant(&mut round_participants, 4, &Pubkey::new_unique()),
+            err!(SallarError::FinalStakingRoundParticipantsAccountMismatch)
+        );
+        assert_eq!(round_participants.users, vec![user, other_user]);
     }
 
     #[test]
-    fn test_bottom_block_not_solved() {
+    fn test_final_staking_not_finalized() {
         let mut state = BlocksState::default();
-        state.bottom_block_available_bp = 1;
+        final_staking_not_finalized(&state).unwrap();
 
-        bottom_block_not_solved(&state).unwrap();
+        state.final_staking_finalized = true;
+        assert_eq!(
+            final_staking_not_finalized(&state),
+            err!(SallarError::FinalStakingFinalized)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_fail_bottom_block_not_solved() {
-        let mut state = BlocksState::default();
-        state.bottom_block_available_bp = 0;
+    fn test_calculate_final_staking_transfer() {
+        let mut state = BlocksState {
+            final_staking_pool_in_round: 1_000,
+            final_staking_left_balance_in_round: 1_000,
+            ..BlocksState::default()
+        };
 
-        bottom_block_not_solved(&state).unwrap();
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 333_333_333, 666_666_667).unwrap(),
+            (333, 0)
+        );
+
+        state.final_staking_left_balance_in_round = 667;
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 666_666_667, 0).unwrap(),
+            (666, 1)
+        );
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 666_666_666, 1).unwrap(),
+            (666, 0)
+        );
+
+        state.final_staking_final_round = true;
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 666_666_667, 0).unwrap(),
+            (667, 0)
+        );
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 666_666_666, 1).unwrap(),
+            (666, 0)
+        );
+
+        state.final_staking_final_round = false;
+        state.final_staking_left_balance_in_round = 665;
+        assert_eq!(
+            calculate_final_staking_transfer(&state, 666_666_667, 0),
+            err!(SallarError::LackOfFundsToPayTheReward)
+        );
     }
 
     #[test]
-    #[should_panic]
-    fn test_final_staking_required_interval_elapsed() {
-        final_staking_required_interval_elapsed(&0).unwrap();
+    fn test_final_staking_round_in_progress() {
+        let mut state = BlocksState::default();
+        assert_eq!(
+            final_staking_round_in_progress(&state),
+            err!(SallarError::FinalStakingRoundNotInProgress)
+        );
+
+        state.final_staking_left_balance_in_round = 1;
+        final_staking_round_in_progress(&state).unwrap();
     }
 
     #[test]
-    fn test_convert_f64_to_u64_valid() {
-        assert_eq!(convert_f64_to_u64((u64::MIN) as f64), Ok(0));
-        assert_eq!(convert_f64_to_u64(123.0), Ok(123));
-        assert_eq!(convert_f64_to_u64((u64::MAX) as f64), Ok(u64::MAX));
-
-        // This is the maximum value that can be represented by f64
-        // without losing precision
+    fn test_valid_cluster_timestamp() {
+        valid_cluster_timestamp(MIN_PLAUSIBLE_CLUSTER_TIMESTAMP).unwrap();
+        valid_cluster_timestamp(1677978061).unwrap();
+        valid_cluster_timestamp(MAX_PLAUSIBLE_CLUSTER_TIMESTAMP).unwrap();
         assert_eq!(
-            convert_f64_to_u64((1u64 << 53) as f64),
-            Ok(9007199254740992)
+            valid_cluster_timestamp(0),
+            err!(SallarError::InvalidClusterTime)
         );
+        assert_eq!(
+            valid_cluster_timestamp(MAX_PLAUSIBLE_CLUSTER_TIMESTAMP + 1),
+            err!(SallarError::InvalidClusterTime)
+        );
+    }
 
-        // This value cannot be represented by f64
-        // without losing precision but it is acceptable
+    #[test]
+    fn test_valid_config_change() {
+        let state = BlocksState::default();
+        valid_config_change(
+            &state,
+            &ConfigChange::SetMinPayoutAmount {
+                min_payout_amount: MAX_MIN_PAYOUT_AMOUNT,
+            },
+        )
+        .unwrap();
         assert_eq!(
-            convert_f64_to_u64((1u64 << 53) as f64 + 1.0),
-            Ok(9007199254740992)
+            valid_config_change(
+                &state,
+                &ConfigChange::SetMinPayoutAmount {
+                    min_payout_amount: MAX_MIN_PAYOUT_AMOUNT + 1,
+                },
+            ),
+            err!(SallarError::MinPayoutAmountTooHigh)
         );
 
-        // This value cannot be represented by f64
-        // without losing precision but it is acceptable
+        let state = BlocksState {
+            admin_controls_renounced: true,
+            ..BlocksState::default()
+        };
+        valid_config_change(
+            &state,
+            &ConfigChange::ChangeAuthority {
+                new_authority: Pubkey::new_unique(),
+            },
+        )
+        .unwrap();
         assert_eq!(
-            convert_f64_to_u64((1u64 << 53) as f64 + 2.0),
-            Ok(9007199254740994)
+            valid_config_change(
+                &state,
+                &ConfigChange::SetTimelockDelay { timelock_delay: 0 },
+            ),
+            err!(SallarError::AdminControlsRenounced)
         );
     }
 
     #[test]
-    fn test_convert_f64_to_u64_invalid() {
+    fn test_current_config_values() {
+        let treasury_address = Pubkey::new_unique();
+        let state = BlocksState {
+            min_payout_amount: 100,
+            treasury_address: Some(treasury_address),
+            ..BlocksState::default()
+        };
+
         assert_eq!(
-            convert_f64_to_u64(f64::MIN),
-            err!(SallarError::U64ConversionError)
+            current_config_values(
+                &state,
+                None,
+                None,
+                None,
+                &ConfigChange::SetMinPayoutAmount {
+                    min_payout_amount: 200,
+                },
+            ),
+            ConfigChange::SetMinPayoutAmount {
+                min_payout_amount: 100,
+            }
         );
         assert_eq!(
-            convert_f64_to_u64(-1.0),
-            err!(SallarError::U64ConversionError)
+            current_config_values(
+                &state,
+                None,
+                None,
+                None,
+                &ConfigChange::SetTreasury {
+                    treasury_address: Pubkey::new_unique(),
+                },
+            ),
+            ConfigChange::SetTreasury { treasury_address }
         );
         assert_eq!(
-            convert_f64_to_u64(-0.1),
-            err!(SallarError::U64ConversionError)
+            current_config_values(
+                &state,
+                None,
+                None,
+                None,
+                &ConfigChange::ProposeAuthority {
+                    new_authority: Pubkey::new_unique(),
+                },
+            ),
+            ConfigChange::ProposeAuthority {
+                new_authority: Pubkey::default(),
+            }
         );
+    }
+
+    #[test]
+    fn test_current_config_values_signer_set() {
+        let state = BlocksState::default();
+        let signers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let signer_set = SignerSet {
+            bump: 0,
+            threshold: 2,
+            signers: signers.clone(),
+        };
+        let change = ConfigChange::UpdateSignerSet {
+            signers: vec![Pubkey::new_unique()],
+            threshold: 1,
+        };
+
         assert_eq!(
-            convert_f64_to_u64(18446744073709553665.0),
-            err!(SallarError::U64ConversionError)
+            current_config_values(&state, None, Some(&signer_set), None, &change),
+            ConfigChange::UpdateSignerSet {
+                signers,
+                threshold: 2,
+            }
         );
         assert_eq!(
-            convert_f64_to_u64(f64::MAX),
-            err!(SallarError::U64ConversionError)
+            current_config_values(&state, None, None, None, &change),
+            ConfigChange::UpdateSignerSet {
+                signers: Vec::new(),
+                threshold: 0,
+            }
         );
     }
 
     #[test]
-    fn test_convert_u64_to_f64_valid() {
-        assert_eq!(convert_u64_to_f64(u64::MIN), Ok(0.0));
-        assert_eq!(convert_u64_to_f64(u64::MAX), Ok(18446744073709551615.0));
-        // The same value as for u64::MAX as the precision is lost in this case
-        // but it is acceptable
-        assert_eq!(convert_u64_to_f64(u64::MAX - 1), Ok(18446744073709551615.0));
+    fn test_current_config_values_final_mining_tiers() {
+        let state = BlocksState::default();
+        let final_mining_tiers_account = FinalMiningTiers {
+            bump: 0,
+            tiers: vec![FinalMiningTier {
+                min_final_mining_balance: 0,
+                reward: 1,
+            }],
+        };
+        let change = ConfigChange::SetFinalMiningTiers { tiers: Vec::new() };
 
-        // This is the maximum value that can be represented by f64
-        // without losing precision
-        assert_eq!(convert_u64_to_f64(9007199254740992), Ok(9007199254740992.0));
+        assert_eq!(
+            current_config_values(
+                &state,
+                None,
+                None,
+                Some(&final_mining_tiers_account),
+                &change
+            ),
+            ConfigChange::SetFinalMiningTiers {
+                tiers: final_mining_tiers_account.tiers.clone(),
+            }
+        );
+        assert_eq!(
+            current_config_values(&state, None, None, None, &change),
+            ConfigChange::SetFinalMiningTiers {
+                tiers: DEFAULT_FINAL_MINING_TIERS.to_vec(),
+            }
+        );
+    }
 
-        // This value cannot be represented by f64
-        // without losing precision but it is acceptable
-        assert_eq!(convert_u64_to_f64(9007199254740993), Ok(9007199254740992.0));
+    #[test]
+    fn test_current_config_values_distribution_backend() {
+        let state = BlocksState::default();
+        let mut config = Config::default();
+        config.bottom_block_distribution_backend = DistributionBackend::Escrow;
+        let change = ConfigChange::SetDistributionBackend {
+            subsystem: Subsystem::BottomBlock,
+            distribution_backend: DistributionBackend::DirectTransfer,
+        };
 
-        // This value cannot be represented by f64
-        // without losing precision but it is acceptable
-        assert_eq!(convert_u64_to_f64(9007199254740994), Ok(9007199254740994.0));
+        assert_eq!(
+            current_config_values(&state, Some(&config), None, None, &change),
+            ConfigChange::SetDistributionBackend {
+                subsystem: Subsystem::BottomBlock,
+                distribution_backend: DistributionBackend::Escrow,
+            }
+        );
+        assert_eq!(
+            current_config_values(&state, None, None, None, &change),
+            ConfigChange::SetDistributionBackend {
+                subsystem: Subsystem::BottomBlock,
+                distribution_backend: DistributionBackend::ClaimCredit,
+            }
+        );
+    }
+
+    #[test]
+    fn test_valid_distribution_backend() {
+        for distribution_backend in [
+            DistributionBackend::DirectTransfer,
+            DistributionBackend::ClaimCredit,
+            DistributionBackend::Escrow,
+        ] {
+            valid_distribution_backend(Subsystem::TopBlock, distribution_backend).unwrap();
+            valid_distribution_backend(Subsystem::BottomBlock, distribution_backend).unwrap();
+        }
+        valid_distribution_backend(Subsystem::FinalMining, DistributionBackend::DirectTransfer)
+            .unwrap();
+        valid_distribution_backend(Subsystem::FinalStaking, DistributionBackend::DirectTransfer)
+            .unwrap();
+        assert_eq!(
+            valid_distribution_backend(Subsystem::FinalMining, DistributionBackend::Escrow),
+            err!(SallarError::InvalidDistributionBackend)
+        );
+        assert_eq!(
+            valid_distribution_backend(Subsystem::FinalStaking, DistributionBackend::ClaimCredit),
+            err!(SallarError::InvalidDistributionBackend)
+        );
+    }
+
+    #[test]
+    fn test_calculate_distribution_surplus() {
+        assert_eq!(calculate_distribution_surplus(150, 100, 20), 30);
+        assert_eq!(calculate_distribution_surplus(120, 100, 20), 0);
+        assert_eq!(calculate_distribution_surplus(110, 100, 20), 0);
     }
 
     #[test]
@@ -996,4 +8641,659 @@ mod test {
 
         mint_tokens(mint, to, authority, program_account, mint_nonce, amount).unwrap();
     }
+
+    /// Phases of the program the instruction gating matrix is checked against.
+    #[derive(Clone, Copy, Debug)]
+    enum Phase {
+        PreInit,
+        Distributing,
+        Collided,
+        Finalized,
+        Paused,
+    }
+
+    const ALL_PHASES: [Phase; 5] = [
+        Phase::PreInit,
+        Phase::Distributing,
+        Phase::Collided,
+        Phase::Finalized,
+        Phase::Paused,
+    ];
+
+    /// Instructions of the program, every instruction must be listed in the gating matrix.
+    #[derive(Clone, Copy, Debug)]
+    enum ProgramInstruction {
+        Initialize,
+        InitialTokenDistribution,
+        SolveTopBlock,
+        SolveTopBlockWithRewardsRoot,
+        ClaimBlockReward,
+        SolveBottomBlock,
+        CommitBlockParticipation,
+        FinalMining,
+        PublishFinalMiningParticipationRoot,
+        OpenFinalMiningEpoch,
+        ClaimFinalMining,
+        FinalStaking,
+        CloseFinalStakingRound,
+        OpenFinalStakingClaimRound,
+        ClaimFinalStakingReward,
+        RegisterForFinalStaking,
+        SettleCollisionRemainder,
+        FinalizeMint,
+        CloseProgramAccounts,
+        RecoverForeignTokens,
+        ExportRegulatoryReport,
+        GetBlockStateSummary,
+        SimulateSolveTopBlock,
+        SimulateSolveBottomBlock,
+        GetFinalMiningEmissionProjection,
+        VerifyInvariants,
+        MigrateState,
+        InitializeConfig,
+        InitializeRecentSolvers,
+        InitializeStateMirror,
+        InitializeGlobalStats,
+        UpdateTokenMetadata,
+        ChangeAuthority,
+        InitializeSignerSet,
+        UpdateSignerSet,
+        InitializeDenylist,
+        InitializeFinalStakingTiers,
+        SetFinalStakingTiers,
+        InitializeFinalMiningTiers,
+        SetFinalMiningTiers,
+        SetFinalMiningBudget,
+        SetFinalMiningCheckpointRequired,
+        SetFinalMiningClaimPeriod,
+        CheckpointFinalMiningBalance,
+        AddToDenylist,
+        RemoveFromDenylist,
+        ProposeAuthority,
+        AcceptAuthority,
+        SetMinPayoutAmount,
+        SetTreasury,
+        Pause,
+        Unpause,
+        SetGuardian,
+        SetBlockHistoryEnabled,
+        SetSolveCoordinator,
+        SetBottomBlockMinRequiredStake,
+        SetMaxUserRequestsPerBlock,
+        SetBlockParticipationCommitmentEnabled,
+        SetBoostCreditsRequired,
+        SetFinalStakingSnapshotsRequired,
+        SetFinalStakingRewardPartTolerance,
+        HaltBlock,
+        ResumeBlock,
+        RenounceAdminControls,
+        SetTimelockDelay,
+        SetBlockSolutionInterval,
+        UpdateFinalStakingConfig,
+        UpdateFinalMiningConfig,
+        SetFinalStakingTerminalConfig,
+        SetRewardFee,
+        SetDistributionBackend,
+        SetSlaThreshold,
+        QueuePendingChange,
+        ExecutePendingChange,
+        CancelPendingChange,
+        InitializeUserClaimAccount,
+        WithdrawPendingRewards,
+        InitializeUserRewardStats,
+        LockStake,
+        UnlockStake,
+        SetFinalStakingAutoRestake,
+        BuyBoostCredits,
+        SetBlocksCollided,
+    }
+
+    const ALL_INSTRUCTIONS: [ProgramInstruction; 83] = [
+        ProgramInstruction::Initialize,
+        ProgramInstruction::InitialTokenDistribution,
+        ProgramInstruction::SolveTopBlock,
+        ProgramInstruction::SolveTopBlockWithRewardsRoot,
+        ProgramInstruction::ClaimBlockReward,
+        ProgramInstruction::SolveBottomBlock,
+        ProgramInstruction::CommitBlockParticipation,
+        ProgramInstruction::FinalMining,
+        ProgramInstruction::PublishFinalMiningParticipationRoot,
+        ProgramInstruction::OpenFinalMiningEpoch,
+        ProgramInstruction::ClaimFinalMining,
+        ProgramInstruction::FinalStaking,
+        ProgramInstruction::CloseFinalStakingRound,
+        ProgramInstruction::OpenFinalStakingClaimRound,
+        ProgramInstruction::ClaimFinalStakingReward,
+        ProgramInstruction::RegisterForFinalStaking,
+        ProgramInstruction::SettleCollisionRemainder,
+        ProgramInstruction::FinalizeMint,
+        ProgramInstruction::CloseProgramAccounts,
+        ProgramInstruction::RecoverForeignTokens,
+        ProgramInstruction::ExportRegulatoryReport,
+        ProgramInstruction::GetBlockStateSummary,
+        ProgramInstruction::SimulateSolveTopBlock,
+        ProgramInstruction::SimulateSolveBottomBlock,
+        ProgramInstruction::GetFinalMiningEmissionProjection,
+        ProgramInstruction::VerifyInvariants,
+        ProgramInstruction::MigrateState,
+        ProgramInstruction::InitializeConfig,
+        ProgramInstruction::InitializeRecentSolvers,
+        ProgramInstruction::InitializeStateMirror,
+        ProgramInstruction::InitializeGlobalStats,
+        ProgramInstruction::UpdateTokenMetadata,
+        ProgramInstruction::ChangeAuthority,
+        ProgramInstruction::InitializeSignerSet,
+        ProgramInstruction::UpdateSignerSet,
+        ProgramInstruction::InitializeDenylist,
+        ProgramInstruction::InitializeFinalStakingTiers,
+        ProgramInstruction::SetFinalStakingTiers,
+        ProgramInstruction::InitializeFinalMiningTiers,
+        ProgramInstruction::SetFinalMiningTiers,
+        ProgramInstruction::SetFinalMiningBudget,
+        ProgramInstruction::SetFinalMiningCheckpointRequired,
+        ProgramInstruction::SetFinalMiningClaimPeriod,
+        ProgramInstruction::CheckpointFinalMiningBalance,
+        ProgramInstruction::AddToDenylist,
+        ProgramInstruction::RemoveFromDenylist,
+        ProgramInstruction::ProposeAuthority,
+        ProgramInstruction::AcceptAuthority,
+        ProgramInstruction::SetMinPayoutAmount,
+        ProgramInstruction::SetTreasury,
+        ProgramInstruction::Pause,
+        ProgramInstruction::Unpause,
+        ProgramInstruction::SetGuardian,
+        ProgramInstruction::SetBlockHistoryEnabled,
+        ProgramInstruction::SetSolveCoordinator,
+        ProgramInstruction::SetBottomBlockMinRequiredStake,
+        ProgramInstruction::SetMaxUserRequestsPerBlock,
+        ProgramInstruction::SetBlockParticipationCommitmentEnabled,
+        ProgramInstruction::SetBoostCreditsRequired,
+        ProgramInstruction::SetFinalStakingSnapshotsRequired,
+        ProgramInstruction::SetFinalStakingRewardPartTolerance,
+        ProgramInstruction::HaltBlock,
+        ProgramInstruction::ResumeBlock,
+        ProgramInstruction::RenounceAdminControls,
+        ProgramInstruction::SetTimelockDelay,
+        ProgramInstruction::SetBlockSolutionInterval,
+        ProgramInstruction::UpdateFinalStakingConfig,
+        ProgramInstruction::UpdateFinalMiningConfig,
+        ProgramInstruction::SetFinalStakingTerminalConfig,
+        ProgramInstruction::SetRewardFee,
+        ProgramInstruction::SetDistributionBackend,
+        ProgramInstruction::SetSlaThreshold,
+        ProgramInstruction::QueuePendingChange,
+        ProgramInstruction::ExecutePendingChange,
+        ProgramInstruction::CancelPendingChange,
+        ProgramInstruction::InitializeUserClaimAccount,
+        ProgramInstruction::WithdrawPendingRewards,
+        ProgramInstruction::InitializeUserRewardStats,
+        ProgramInstruction::LockStake,
+        ProgramInstruction::UnlockStake,
+        ProgramInstruction::SetFinalStakingAutoRestake,
+        ProgramInstruction::BuyBoostCredits,
+        ProgramInstruction::SetBlocksCollided,
+    ];
+
+    impl ProgramInstruction {
+        fn name(&self) -> &'static str {
+            match self {
+                ProgramInstruction::Initialize => "initialize",
+                ProgramInstruction::InitialTokenDistribution => "initial_token_distribution",
+                ProgramInstruction::SolveTopBlock => "solve_top_block",
+                ProgramInstruction::SolveTopBlockWithRewardsRoot => {
+                    "solve_top_block_with_rewards_root"
+                }
+                ProgramInstruction::ClaimBlockReward => "claim_block_reward",
+                ProgramInstruction::SolveBottomBlock => "solve_bottom_block",
+                ProgramInstruction::CommitBlockParticipation => "commit_block_participation",
+                ProgramInstruction::FinalMining => "final_mining",
+                ProgramInstruction::PublishFinalMiningParticipationRoot => {
+                    "publish_final_mining_participation_root"
+                }
+                ProgramInstruction::OpenFinalMiningEpoch => "open_final_mining_epoch",
+                ProgramInstruction::ClaimFinalMining => "claim_final_mining",
+                ProgramInstruction::FinalStaking => "final_staking",
+                ProgramInstruction::CloseFinalStakingRound => "close_final_staking_round",
+                ProgramInstruction::OpenFinalStakingClaimRound => "open_final_staking_claim_round",
+                ProgramInstruction::ClaimFinalStakingReward => "claim_final_staking_reward",
+                ProgramInstruction::RegisterForFinalStaking => "register_for_final_staking",
+                ProgramInstruction::SettleCollisionRemainder => "settle_collision_remainder",
+                ProgramInstruction::FinalizeMint => "finalize_mint",
+                ProgramInstruction::CloseProgramAccounts => "close_program_accounts",
+                ProgramInstruction::RecoverForeignTokens => "recover_foreign_tokens",
+                ProgramInstruction::ExportRegulatoryReport => "export_regulatory_report",
+                ProgramInstruction::GetBlockStateSummary => "get_block_state_summary",
+                ProgramInstruction::SimulateSolveTopBlock => "simulate_solve_top_block",
+                ProgramInstruction::SimulateSolveBottomBlock => "simulate_solve_bottom_block",
+                ProgramInstruction::GetFinalMiningEmissionProjection => {
+                    "get_final_mining_emission_projection"
+                }
+                ProgramInstruction::VerifyInvariants => "verify_invariants",
+                ProgramInstruction::MigrateState => "migrate_state",
+                ProgramInstruction::InitializeConfig => "initialize_config",
+                ProgramInstruction::InitializeRecentSolvers => "initialize_recent_solvers",
+                ProgramInstruction::InitializeStateMirror => "initialize_state_mirror",
+                ProgramInstruction::InitializeGlobalStats => "initialize_global_stats",
+                ProgramInstruction::UpdateTokenMetadata => "update_token_metadata",
+                ProgramInstruction::ChangeAuthority => "change_authority",
+                ProgramInstruction::InitializeSignerSet => "initialize_signer_set",
+                ProgramInstruction::UpdateSignerSet => "update_signer_set",
+                ProgramInstruction::InitializeDenylist => "initialize_denylist",
+                ProgramInstruction::InitializeFinalStakingTiers => "initialize_final_staking_tiers",
+                ProgramInstruction::SetFinalStakingTiers => "set_final_staking_tiers",
+                ProgramInstruction::InitializeFinalMiningTiers => "initialize_final_mining_tiers",
+                ProgramInstruction::SetFinalMiningTiers => "set_final_mining_tiers",
+                ProgramInstruction::SetFinalMiningBudget => "set_final_mining_budget",
+                ProgramInstruction::SetFinalMiningCheckpointRequired => {
+                    "set_final_mining_checkpoint_required"
+                }
+                ProgramInstruction::SetFinalMiningClaimPeriod => "set_final_mining_claim_period",
+                ProgramInstruction::CheckpointFinalMiningBalance => {
+                    "checkpoint_final_mining_balance"
+                }
+                ProgramInstruction::AddToDenylist => "add_to_denylist",
+                ProgramInstruction::RemoveFromDenylist => "remove_from_denylist",
+                ProgramInstruction::ProposeAuthority => "propose_authority",
+                ProgramInstruction::AcceptAuthority => "accept_authority",
+                ProgramInstruction::SetMinPayoutAmount => "set_min_payout_amount",
+                ProgramInstruction::SetTreasury => "set_treasury",
+                ProgramInstruction::Pause => "pause",
+                ProgramInstruction::Unpause => "unpause",
+                ProgramInstruction::SetGuardian => "set_guardian",
+                ProgramInstruction::SetBlockHistoryEnabled => "set_block_history_enabled",
+                ProgramInstruction::SetSolveCoordinator => "set_solve_coordinator",
+                ProgramInstruction::SetBottomBlockMinRequiredStake => {
+                    "set_bottom_block_min_required_stake"
+                }
+                ProgramInstruction::SetMaxUserRequestsPerBlock => "set_max_user_requests_per_block",
+                ProgramInstruction::SetBlockParticipationCommitmentEnabled => {
+                    "set_block_participation_commitment_enabled"
+                }
+                ProgramInstruction::SetBoostCreditsRequired => "set_boost_credits_required",
+                ProgramInstruction::SetFinalStakingSnapshotsRequired => {
+                    "set_final_staking_snapshots_required"
+                }
+                ProgramInstruction::SetFinalStakingRewardPartTolerance => {
+                    "set_final_staking_reward_part_tolerance"
+                }
+                ProgramInstruction::HaltBlock => "halt_block",
+                ProgramInstruction::ResumeBlock => "resume_block",
+                ProgramInstruction::RenounceAdminControls => "renounce_admin_controls",
+                ProgramInstruction::SetTimelockDelay => "set_timelock_delay",
+                ProgramInstruction::SetBlockSolutionInterval => "set_block_solution_interval",
+                ProgramInstruction::UpdateFinalStakingConfig => "update_final_staking_config",
+                ProgramInstruction::UpdateFinalMiningConfig => "update_final_mining_config",
+                ProgramInstruction::SetFinalStakingTerminalConfig => {
+                    "set_final_staking_terminal_config"
+                }
+                ProgramInstruction::SetRewardFee => "set_reward_fee",
+                ProgramInstruction::SetDistributionBackend => "set_distribution_backend",
+                ProgramInstruction::SetSlaThreshold => "set_sla_threshold",
+                ProgramInstruction::QueuePendingChange => "queue_pending_change",
+                ProgramInstruction::ExecutePendingChange => "execute_pending_change",
+                ProgramInstruction::CancelPendingChange => "cancel_pending_change",
+                ProgramInstruction::InitializeUserClaimAccount => "initialize_user_claim_account",
+                ProgramInstruction::WithdrawPendingRewards => "withdraw_pending_rewards",
+                ProgramInstruction::InitializeUserRewardStats => "initialize_user_reward_stats",
+                ProgramInstruction::LockStake => "lock_stake",
+                ProgramInstruction::UnlockStake => "unlock_stake",
+                ProgramInstruction::SetFinalStakingAutoRestake => "set_final_staking_auto_restake",
+                ProgramInstruction::BuyBoostCredits => "buy_boost_credits",
+                ProgramInstruction::SetBlocksCollided => "set_blocks_collided",
+            }
+        }
+    }
+
+    /// Outcome of an instruction in a phase, as far as the phase-dependent guards are concerned
+    /// (the signer, arguments and time guards do not depend on the phase and they are assumed to pass).
+    #[derive(Debug, PartialEq)]
+    enum Outcome {
+        Allowed,
+        Rejected(u32),
+        AccountNotInitialized,
+        AccountAlreadyInitialized,
+    }
+
+    /// Accounts of the program in a given phase: the blocks state (None before initialization) and balances of the program's token accounts.
+    struct PhaseAccounts {
+        state: Option<BlocksState>,
+        distribution_top_block_account: TokenAccount,
+        distribution_bottom_block_account: TokenAccount,
+        final_staking_account: TokenAccount,
+        final_mining_account: TokenAccount,
+    }
+
+    fn token_account_with_amount(amount: u64) -> TokenAccount {
+        let mut data = [0u8; SplTokenAccount::LEN];
+        SplTokenAccount::pack(
+            SplTokenAccount {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                amount,
+                state: AccountState::Initialized,
+                ..SplTokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+
+        TokenAccount::try_deserialize(&mut data.as_slice()).unwrap()
+    }
+
+    fn phase_accounts(phase: Phase) -> PhaseAccounts {
+        let distributing = BlocksState {
+            initial_token_distribution_already_performed: true,
+            top_block_number: 1,
+            bottom_block_number: MAX_BLOCK_INDEX,
+            top_block_available_bp: 1,
+            bottom_block_available_bp: 1,
+            ..BlocksState::default()
+        };
+        let collided = BlocksState {
+            blocks_collided: true,
+            top_block_available_bp: 0,
+            bottom_block_available_bp: 0,
+            ..distributing.clone()
+        };
+
+        let (state, distribution_balance, final_pools_balance) = match phase {
+            Phase::PreInit => (None, 0, 0),
+            Phase::Distributing => (Some(distributing), DUSTS_PER_BLOCK, DUSTS_PER_BLOCK),
+            Phase::Collided => (Some(collided), 0, DUSTS_PER_BLOCK),
+            Phase::Finalized => (
+                Some(BlocksState {
+                    final_staking_finalized: true,
+                    ..collided
+                }),
+                0,
+                0,
+            ),
+            Phase::Paused => (
+                Some(BlocksState {
+                    paused: true,
+                    ..distributing
+                }),
+                DUSTS_PER_BLOCK,
+                DUSTS_PER_BLOCK,
+            ),
+        };
+
+        PhaseAccounts {
+            state,
+            distribution_top_block_account: token_account_with_amount(distribution_balance),
+            distribution_bottom_block_account: token_account_with_amount(distribution_balance),
+            final_staking_account: token_account_with_amount(final_pools_balance),
+            final_mining_account: token_account_with_amount(final_pools_balance),
+        }
+    }
+
+    /// Runs the phase-dependent guards of the instruction, in the order of its access control.
+    fn gate(instruction: ProgramInstruction, accounts: &PhaseAccounts) -> Outcome {
+        let state = match (&accounts.state, instruction) {
+            (None, ProgramInstruction::Initialize) => return Outcome::Allowed,
+            (None, _) => return Outcome::AccountNotInitialized,
+            (Some(_), ProgramInstruction::Initialize) => return Outcome::AccountAlreadyInitialized,
+            (Some(state), _) => state,
+        };
+
+        let result = match instruction {
+            ProgramInstruction::Initialize => unreachable!(),
+            ProgramInstruction::InitialTokenDistribution => program_not_paused(state)
+                .and_then(|_| initial_token_distribution_not_performed_yet(state)),
+            ProgramInstruction::SolveTopBlock
+            | ProgramInstruction::SolveTopBlockWithRewardsRoot => program_not_paused(state)
+                .and_then(|_| block_not_halted(state, BlockKind::Top))
+                .and_then(|_| top_block_not_solved(state)),
+            ProgramInstruction::ClaimBlockReward
+            | ProgramInstruction::ClaimFinalStakingReward
+            | ProgramInstruction::LockStake
+            | ProgramInstruction::UnlockStake
+            | ProgramInstruction::SetFinalStakingAutoRestake
+            | ProgramInstruction::BuyBoostCredits
+            | ProgramInstruction::WithdrawPendingRewards => program_not_paused(state),
+            ProgramInstruction::CommitBlockParticipation => {
+                program_not_paused(state).and_then(|_| block_not_halted(state, BlockKind::Top))
+            }
+            ProgramInstruction::SolveBottomBlock => program_not_paused(state)
+                .and_then(|_| block_not_halted(state, BlockKind::Bottom))
+                .and_then(|_| bottom_block_not_solved(state))
+                .and_then(|_| bottom_block_within_bounds(state)),
+            ProgramInstruction::FinalMining
+            | ProgramInstruction::PublishFinalMiningParticipationRoot
+            | ProgramInstruction::OpenFinalMiningEpoch
+            | ProgramInstruction::ClaimFinalMining
+            | ProgramInstruction::CheckpointFinalMiningBalance => program_not_paused(state)
+                .and_then(|_| blocks_collided(state))
+                .and_then(|_| blocks_solved(state)),
+            ProgramInstruction::FinalStaking | ProgramInstruction::OpenFinalStakingClaimRound => {
+                program_not_paused(state)
+                    .and_then(|_| blocks_collided(state))
+                    .and_then(|_| blocks_solved(state))
+                    .and_then(|_| final_staking_not_finalized(state))
+            }
+            ProgramInstruction::CloseFinalStakingRound => program_not_paused(state)
+                .and_then(|_| blocks_collided(state))
+                .and_then(|_| final_staking_not_finalized(state))
+                .and_then(|_| final_staking_round_in_progress(state)),
+            ProgramInstruction::RegisterForFinalStaking => {
+                program_not_paused(state).and_then(|_| final_staking_not_finalized(state))
+            }
+            ProgramInstruction::SettleCollisionRemainder => program_not_paused(state)
+                .and_then(|_| blocks_collided(state))
+                .and_then(|_| collision_remainder_exists(state)),
+            ProgramInstruction::FinalizeMint => blocks_collided(state).and_then(|_| {
+                distribution_accounts_empty(
+                    &accounts.distribution_top_block_account,
+                    &accounts.distribution_bottom_block_account,
+                )
+            }),
+            ProgramInstruction::CloseProgramAccounts => blocks_collided(state)
+                .and_then(|_| {
+                    distribution_accounts_empty(
+                        &accounts.distribution_top_block_account,
+                        &accounts.distribution_bottom_block_account,
+                    )
+                })
+                .and_then(|_| {
+                    final_pools_empty(
+                        &accounts.final_staking_account,
+                        &accounts.final_mining_account,
+                    )
+                }),
+            ProgramInstruction::RecoverForeignTokens
+            | ProgramInstruction::ExportRegulatoryReport
+            | ProgramInstruction::GetBlockStateSummary
+            | ProgramInstruction::SimulateSolveTopBlock
+            | ProgramInstruction::SimulateSolveBottomBlock
+            | ProgramInstruction::GetFinalMiningEmissionProjection
+            | ProgramInstruction::VerifyInvariants
+            | ProgramInstruction::MigrateState
+            | ProgramInstruction::InitializeConfig
+            | ProgramInstruction::InitializeRecentSolvers
+            | ProgramInstruction::InitializeStateMirror
+            | ProgramInstruction::InitializeGlobalStats
+            | ProgramInstruction::AcceptAuthority
+            | ProgramInstruction::ExecutePendingChange
+            | ProgramInstruction::CancelPendingChange
+            | ProgramInstruction::InitializeUserClaimAccount
+            | ProgramInstruction::InitializeUserRewardStats
+            | ProgramInstruction::SetBlocksCollided => Ok(()),
+            ProgramInstruction::UpdateTokenMetadata
+            | ProgramInstruction::InitializeSignerSet
+            | ProgramInstruction::InitializeDenylist
+            | ProgramInstruction::InitializeFinalStakingTiers
+            | ProgramInstruction::SetFinalStakingTiers
+            | ProgramInstruction::InitializeFinalMiningTiers
+            | ProgramInstruction::AddToDenylist
+            | ProgramInstruction::RemoveFromDenylist
+            | ProgramInstruction::Pause
+            | ProgramInstruction::Unpause
+            | ProgramInstruction::HaltBlock
+            | ProgramInstruction::ResumeBlock
+            | ProgramInstruction::SetSlaThreshold => admin_controls_not_renounced(state),
+            ProgramInstruction::ChangeAuthority | ProgramInstruction::ProposeAuthority => {
+                timelock_disabled(state)
+            }
+            ProgramInstruction::UpdateSignerSet
+            | ProgramInstruction::SetMinPayoutAmount
+            | ProgramInstruction::SetTreasury
+            | ProgramInstruction::SetGuardian
+            | ProgramInstruction::SetBlockHistoryEnabled
+            | ProgramInstruction::SetSolveCoordinator
+            | ProgramInstruction::SetBottomBlockMinRequiredStake
+            | ProgramInstruction::SetMaxUserRequestsPerBlock
+            | ProgramInstruction::SetBlockParticipationCommitmentEnabled
+            | ProgramInstruction::SetBoostCreditsRequired
+            | ProgramInstruction::SetFinalStakingSnapshotsRequired
+            | ProgramInstruction::SetFinalStakingRewardPartTolerance
+            | ProgramInstruction::SetTimelockDelay
+            | ProgramInstruction::SetBlockSolutionInterval
+            | ProgramInstruction::UpdateFinalStakingConfig
+            | ProgramInstruction::UpdateFinalMiningConfig
+            | ProgramInstruction::SetFinalMiningTiers
+            | ProgramInstruction::SetFinalMiningBudget
+            | ProgramInstruction::SetFinalMiningCheckpointRequired
+            | ProgramInstruction::SetFinalMiningClaimPeriod
+            | ProgramInstruction::SetFinalStakingTerminalConfig
+            | ProgramInstruction::SetDistributionBackend
+            | ProgramInstruction::SetRewardFee => {
+                admin_controls_not_renounced(state).and_then(|_| timelock_disabled(state))
+            }
+            ProgramInstruction::RenounceAdminControls => admin_controls_not_renounced(state)
+                .and_then(|_| program_not_paused(state))
+                .and_then(|_| block_not_halted(state, BlockKind::Top))
+                .and_then(|_| block_not_halted(state, BlockKind::Bottom)),
+            ProgramInstruction::QueuePendingChange => timelock_enabled(state),
+        };
+
+        match result {
+            Ok(()) => Outcome::Allowed,
+            Err(anchor_lang::error::Error::AnchorError(error)) => {
+                Outcome::Rejected(error.error_code_number)
+            }
+            Err(error) => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    /// The expected outcome of the instruction in the phase.
+    fn expected_outcome(instruction: ProgramInstruction, phase: Phase) -> Outcome {
+        let rejected = |error: SallarError| Outcome::Rejected(error.into());
+
+        match (phase, instruction) {
+            (Phase::PreInit, ProgramInstruction::Initialize) => Outcome::Allowed,
+            (Phase::PreInit, _) => Outcome::AccountNotInitialized,
+            (_, ProgramInstruction::Initialize) => Outcome::AccountAlreadyInitialized,
+
+            (Phase::Paused, ProgramInstruction::InitialTokenDistribution)
+            | (Phase::Paused, ProgramInstruction::SolveTopBlock)
+            | (Phase::Paused, ProgramInstruction::SolveTopBlockWithRewardsRoot)
+            | (Phase::Paused, ProgramInstruction::ClaimBlockReward)
+            | (Phase::Paused, ProgramInstruction::ClaimFinalStakingReward)
+            | (Phase::Paused, ProgramInstruction::LockStake)
+            | (Phase::Paused, ProgramInstruction::UnlockStake)
+            | (Phase::Paused, ProgramInstruction::SetFinalStakingAutoRestake)
+            | (Phase::Paused, ProgramInstruction::BuyBoostCredits)
+            | (Phase::Paused, ProgramInstruction::WithdrawPendingRewards)
+            | (Phase::Paused, ProgramInstruction::SolveBottomBlock)
+            | (Phase::Paused, ProgramInstruction::CommitBlockParticipation)
+            | (Phase::Paused, ProgramInstruction::FinalMining)
+            | (Phase::Paused, ProgramInstruction::PublishFinalMiningParticipationRoot)
+            | (Phase::Paused, ProgramInstruction::OpenFinalMiningEpoch)
+            | (Phase::Paused, ProgramInstruction::ClaimFinalMining)
+            | (Phase::Paused, ProgramInstruction::CheckpointFinalMiningBalance)
+            | (Phase::Paused, ProgramInstruction::FinalStaking)
+            | (Phase::Paused, ProgramInstruction::OpenFinalStakingClaimRound)
+            | (Phase::Paused, ProgramInstruction::CloseFinalStakingRound)
+            | (Phase::Paused, ProgramInstruction::RegisterForFinalStaking)
+            | (Phase::Paused, ProgramInstruction::SettleCollisionRemainder)
+            | (Phase::Paused, ProgramInstruction::RenounceAdminControls) => {
+                rejected(SallarError::ProgramPaused)
+            }
+            (_, ProgramInstruction::InitialTokenDistribution) => {
+                rejected(SallarError::InitialTokenDistributionAlreadyPerformed)
+            }
+
+            (Phase::Distributing, ProgramInstruction::SolveTopBlock)
+            | (Phase::Distributing, ProgramInstruction::SolveTopBlockWithRewardsRoot)
+            | (Phase::Distributing, ProgramInstruction::SolveBottomBlock) => Outcome::Allowed,
+            (_, ProgramInstruction::SolveTopBlock)
+            | (_, ProgramInstruction::SolveTopBlockWithRewardsRoot)
+            | (_, ProgramInstruction::SolveBottomBlock) => {
+                rejected(SallarError::BlockAlreadySolved)
+            }
+
+            (Phase::Distributing, ProgramInstruction::FinalMining)
+            | (Phase::Distributing, ProgramInstruction::PublishFinalMiningParticipationRoot)
+            | (Phase::Distributing, ProgramInstruction::OpenFinalMiningEpoch)
+            | (Phase::Distributing, ProgramInstruction::ClaimFinalMining)
+            | (Phase::Distributing, ProgramInstruction::CheckpointFinalMiningBalance)
+            | (Phase::Distributing, ProgramInstruction::FinalStaking)
+            | (Phase::Distributing, ProgramInstruction::OpenFinalStakingClaimRound)
+            | (Phase::Distributing, ProgramInstruction::CloseFinalStakingRound)
+            | (Phase::Distributing, ProgramInstruction::FinalizeMint)
+            | (Phase::Distributing, ProgramInstruction::CloseProgramAccounts)
+            | (Phase::Distributing, ProgramInstruction::SettleCollisionRemainder)
+            | (Phase::Paused, ProgramInstruction::FinalizeMint)
+            | (Phase::Paused, ProgramInstruction::CloseProgramAccounts) => {
+                rejected(SallarError::BlocksNotCollidedYet)
+            }
+            (Phase::Collided, ProgramInstruction::CloseProgramAccounts) => {
+                rejected(SallarError::FinalPoolsNotEmpty)
+            }
+            (Phase::Finalized, ProgramInstruction::FinalStaking)
+            | (Phase::Finalized, ProgramInstruction::OpenFinalStakingClaimRound)
+            | (Phase::Finalized, ProgramInstruction::CloseFinalStakingRound)
+            | (Phase::Finalized, ProgramInstruction::RegisterForFinalStaking) => {
+                rejected(SallarError::FinalStakingFinalized)
+            }
+            (_, ProgramInstruction::CloseFinalStakingRound) => {
+                rejected(SallarError::FinalStakingRoundNotInProgress)
+            }
+            (_, ProgramInstruction::SettleCollisionRemainder) => {
+                rejected(SallarError::NoCollisionRemainder)
+            }
+
+            (_, ProgramInstruction::QueuePendingChange) => rejected(SallarError::TimelockDisabled),
+
+            _ => Outcome::Allowed,
+        }
+    }
+
+    #[test]
+    fn test_phase_instruction_gating_matrix() {
+        for phase in ALL_PHASES {
+            let accounts = phase_accounts(phase);
+
+            for instruction in ALL_INSTRUCTIONS {
+                assert_eq!(
+                    gate(instruction, &accounts),
+                    expected_outcome(instruction, phase),
+                    "{:?} in {:?} phase",
+                    instruction,
+                    phase
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_phase_instruction_gating_matrix_covers_all_instructions() {
+        let source = include_str!("lib.rs");
+        let program_module = &source[source.find("pub mod sallar {").unwrap()..];
+        let program_module = &program_module[..program_module.find("\n}\n").unwrap()];
+
+        let mut program_instructions = program_module
+            .lines()
+            .filter_map(|line| line.strip_prefix("    pub fn "))
+            .map(|line| line.split(['<', '(']).next().unwrap())
+            .collect::<Vec<&str>>();
+        let mut matrix_instructions = ALL_INSTRUCTIONS
+            .iter()
+            .map(|instruction| instruction.name())
+            .collect::<Vec<&str>>();
+        program_instructions.sort();
+        matrix_instructions.sort();
+
+        assert_eq!(program_instructions, matrix_instructions);
+    }
 }
@@ -2,6 +2,9 @@
 programId=$(solana address -k target/deploy/sallar-keypair.json) && \
     sed -i 's/sallar = ".*/sallar = "'"$programId"'"/' Anchor.toml && \
     sed -i 's/declare_id!.*/declare_id!("'"$programId"'");/' programs/sallar/src/lib.rs && \
+    vaultProgramId=$(solana address -k target/deploy/sallar_vault-keypair.json) && \
+    sed -i 's/sallar_vault = ".*/sallar_vault = "'"$vaultProgramId"'"/' Anchor.toml && \
+    sed -i 's/declare_id!.*/declare_id!("'"$vaultProgramId"'");/' examples/sallar-vault/src/lib.rs && \
     anchor build
 
 while pkill -9 solana-test-val; do
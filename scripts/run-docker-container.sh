@@ -6,8 +6,8 @@
 # Prerequisites:
 # - Docker installed and running correctly
 
-SOLANA_VERSION="1.14.20"
-ANCHOR_VERSION="0.27.0"
+SOLANA_VERSION="1.18.17"
+ANCHOR_VERSION="0.30.1"
 IMAGE_TAG="sallar:1.0"
 CONTAINER_NAME="sallar"
 
@@ -85,8 +85,11 @@ describe("Sallar", async () => {
                     token_name,
                     token_symbol,
                     token_metadata_uri,
+                    null,
+                    null,
+                    null,
                 )
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     mint: mint_address,
                     distributionTopBlockAccount: distribution_top_block_address,
@@ -118,6 +121,9 @@ describe("Sallar", async () => {
             const blocksState = await program.account.blocksState.fetch(
                 blocks_state_address,
             );
+            const config = await program.account.config.fetch(
+                findProgramAddress("config")[0],
+            );
 
             const tokenMetadata = await programs.metadata.Metadata.findByMint(
                 connection,
@@ -130,16 +136,16 @@ describe("Sallar", async () => {
             );
 
             assert.isTrue(
-                blocksState.mintNonce === mint_bump
+                config.mintNonce === mint_bump
             );
 
             assert.isTrue(
-                blocksState.topBlockDistributionAddress.toBase58() ===
+                config.topBlockDistributionAddress.toBase58() ===
                 distribution_top_block_address.toBase58()
             );
 
             assert.isTrue(
-                blocksState.topBlockDistributionNonce === distribution_top_block_bump
+                config.topBlockDistributionNonce === distribution_top_block_bump
             );
 
             assert.isTrue(
@@ -151,12 +157,22 @@ describe("Sallar", async () => {
             );
 
             assert.isTrue(
-                blocksState.bottomBlockDistributionAddress.toBase58() ===
+                config.bottomBlockDistributionAddress.toBase58() ===
                 distribution_bottom_block_address.toBase58()
             );
 
             assert.isTrue(
-                blocksState.bottomBlockDistributionNonce === distribution_bottom_block_bump
+                config.bottomBlockDistributionNonce === distribution_bottom_block_bump
+            );
+
+            assert.isTrue(
+                config.finalStakingAccountAddress.toBase58() ===
+                final_staking_address.toBase58()
+            );
+
+            assert.isTrue(
+                config.finalMiningAccountAddress.toBase58() ===
+                final_mining_address.toBase58()
             );
 
             assert.isTrue(
@@ -201,18 +217,20 @@ describe("Sallar", async () => {
 
                 user_info_top_block.push({
                     userPublicKey: testAccounts[i],
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(1),
                     userRequestWithBoost: new anchor.BN(0),
                 });
                 user_info_bottom_block.push({
                     userPublicKey: testAccounts[i],
-                    userBalance: new anchor.BN(2_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(1),
                     userRequestWithBoost: new anchor.BN(0),
                 });
                 user_info_final_staking.push({
                     userPublicKey: testAccounts[i],
-                    rewardPart: 0.2,
+                    wallet: null,
+                    rewardPart: new anchor.BN(200_000_000),
                 });
             }
         });
@@ -221,7 +239,7 @@ describe("Sallar", async () => {
             it("Pass - (Initial token distribution)", async () => {
                 await program.methods
                     .initialTokenDistribution()
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         mint: mint_address,
                         organizationAccount: organization_account,
@@ -235,7 +253,7 @@ describe("Sallar", async () => {
                 try {
                     await program.methods
                         .initialTokenDistribution()
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             mint: mint_address,
                             organizationAccount: organization_account,
@@ -260,7 +278,7 @@ describe("Sallar", async () => {
                 try {
                     await program.methods
                         .initialTokenDistribution()
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             mint: mint_address,
                             organizationAccount: organization_account,
@@ -61,18 +61,18 @@ describe("Sallar - top block", async () => {
                 isSigner: false,
             });
 
-            user_info_top_block.push({ userPublicKey: testAccounts[i], userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(0) });
-            user_info_bottom_block.push({ userPublicKey: testAccounts[i], userBalance: new anchor.BN(20_000_000_000), userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(0) });
-            user_info_final_staking.push({ userPublicKey: testAccounts[i], rewardPart: 0.20 });
+            user_info_top_block.push({ userPublicKey: testAccounts[i], wallet: null, userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(0) });
+            user_info_bottom_block.push({ userPublicKey: testAccounts[i], wallet: null, userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(0) });
+            user_info_final_staking.push({ userPublicKey: testAccounts[i], wallet: null, rewardPart: new anchor.BN(200_000_000) });
         }
 	});
 
         describe("Solve top block", async () => {
             it("PASS - Success first solve", async () => {
                 const tx: anchor.web3.Transaction = await program.methods
-                    .solveTopBlock(user_info_top_block)
+                    .solveTopBlock(user_info_top_block, null, null, false, false, true)
                     .remainingAccounts(rem_accounts)
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         distributionTopBlockAccount: distribution_top_block_address,
                         mint: mint_address,
@@ -98,9 +98,9 @@ describe("Sallar - top block", async () => {
             it("PASS - checking the mechanism of automatic token minting", async () => {	
                 for (let i = 0; i < 5; i++) {
                     const tx: anchor.web3.Transaction = await program.methods
-                    .solveTopBlock(user_info_top_block)
+                    .solveTopBlock(user_info_top_block, null, null, false, false, true)
                     .remainingAccounts(rem_accounts)
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         distributionTopBlockAccount: distribution_top_block_address,
                         mint: mint_address,
@@ -129,10 +129,15 @@ describe("Sallar - top block", async () => {
                 try {
                     const tx: string = await program.methods
                         .solveTopBlock(
-                            user_info_top_block
+                            user_info_top_block,
+                            null,
+                            null,
+                            false,
+                            false,
+                            true
                         )
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: invalid_blocks_state_address.publicKey,
                             distributionTopBlockAccount: distribution_top_block_address,
                             mint: mint_address,
@@ -152,10 +157,15 @@ describe("Sallar - top block", async () => {
                 try {
                     const tx: string = await program.methods
                         .solveTopBlock(
-                            user_info_top_block
+                            user_info_top_block,
+                            null,
+                            null,
+                            false,
+                            false,
+                            true
                         )
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionTopBlockAccount: invalid_distribution_top_block_address.publicKey,
                             mint: mint_address,
@@ -176,10 +186,15 @@ describe("Sallar - top block", async () => {
                 try {
                     const tx: string = await program.methods
                         .solveTopBlock(
-                            user_info_top_block
+                            user_info_top_block,
+                            null,
+                            null,
+                            false,
+                            false,
+                            true
                         )
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionTopBlockAccount: distribution_top_block_address,
                             mint: invalid_mint.publicKey,
@@ -201,10 +216,15 @@ describe("Sallar - top block", async () => {
                 try {
                     const tx: string = await program.methods
                         .solveTopBlock(
-                            user_info_top_block
+                            user_info_top_block,
+                            null,
+                            null,
+                            false,
+                            false,
+                            true
                         )
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionTopBlockAccount: distribution_top_block_address,
                             mint: mint_address,
@@ -226,10 +246,15 @@ describe("Sallar - top block", async () => {
                 try {
                     const tx: string = await program.methods
                         .solveTopBlock(
-                            user_info_top_block
+                            user_info_top_block,
+                            null,
+                            null,
+                            false,
+                            false,
+                            true
                         )
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionTopBlockAccount: distribution_top_block_address,
                             mint: mint_address,
@@ -249,10 +274,15 @@ describe("Sallar - top block", async () => {
 				try {
 					const tx: string = await program.methods
 						.solveTopBlock(
-							user_info_top_block
+							user_info_top_block,
+							null,
+							null,
+							false,
+							false,
+							true
 						)
 						.remainingAccounts(rem_accounts)
-						.accounts({
+						.accountsPartial({
 							blocksStateAccount: blocks_state_address,
 							distributionTopBlockAccount: distribution_top_block_address,
 							mint: mint_address,
@@ -56,13 +56,14 @@ describe("Sallar - bottom block", async () => {
 
                 user_info_bottom_block.push({
                     userPublicKey: testAccounts[i],
-                    userBalance: new anchor.BN(2_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(1),
                     userRequestWithBoost: new anchor.BN(0),
                 });
                 user_info_final_staking.push({
                     userPublicKey: testAccounts[i],
-                    rewardPart: 0.2,
+                    wallet: null,
+                    rewardPart: new anchor.BN(200_000_000),
                 });
             }
         });
@@ -70,9 +71,9 @@ describe("Sallar - bottom block", async () => {
         describe("Solve bottom block", async () => {
             it("PASS - Success solve bottom block", async () => {
                 const tx: anchor.web3.Transaction = await program.methods
-                    .solveBottomBlock(user_info_bottom_block)
+                    .solveBottomBlock(user_info_bottom_block, null, false, true)
                     .remainingAccounts(rem_accounts)
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         distributionBottomBlockAccount:
                             distribution_bottom_block_address,
@@ -99,9 +100,9 @@ describe("Sallar - bottom block", async () => {
             it("PASS - checking the mechanism of automatic token minting", async () => {
                 for (let i = 0; i < 25; i++) {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 distribution_bottom_block_address,
@@ -136,9 +137,9 @@ describe("Sallar - bottom block", async () => {
 
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount:
                                 invalid_blocks_state_address.publicKey,
                             distributionBottomBlockAccount:
@@ -164,9 +165,9 @@ describe("Sallar - bottom block", async () => {
 
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 invalid_distribution_bottom_block_address.publicKey,
@@ -191,9 +192,9 @@ describe("Sallar - bottom block", async () => {
 
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 distribution_bottom_block_address,
@@ -218,9 +219,9 @@ describe("Sallar - bottom block", async () => {
 
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 distribution_bottom_block_address,
@@ -245,9 +246,9 @@ describe("Sallar - bottom block", async () => {
 
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 distribution_bottom_block_address,
@@ -270,9 +271,9 @@ describe("Sallar - bottom block", async () => {
             it("FAIL - (Blocks collision)", async () => {
                 try {
                     const tx: string = await program.methods
-                        .solveBottomBlock(user_info_bottom_block)
+                        .solveBottomBlock(user_info_bottom_block, null, false, true)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             distributionBottomBlockAccount:
                                 distribution_bottom_block_address,
@@ -296,20 +297,20 @@ describe("Sallar - bottom block", async () => {
                 user_info_bottom_block.pop();
                 user_info_bottom_block.push({
                     userPublicKey: testAccounts[0],
-                    userBalance: new anchor.BN(200_000_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(42),
                     userRequestWithBoost: new anchor.BN(42),
                 });
                 user_info_bottom_block.push({
                     userPublicKey: testAccounts[1],
-                    userBalance: new anchor.BN(200_000_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(42),
                     userRequestWithBoost: new anchor.BN(42),
                 });
                 const tx: anchor.web3.Transaction = await program.methods
-                    .solveBottomBlock(user_info_bottom_block)
+                    .solveBottomBlock(user_info_bottom_block, null, false, true)
                     .remainingAccounts(rem_accounts)
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         distributionBottomBlockAccount:
                             distribution_bottom_block_address,
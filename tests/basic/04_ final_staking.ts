@@ -62,7 +62,8 @@ describe("Sallar - Final staking", async () => {
 
                 user_info_final_staking.push({
                     userPublicKey: testAccounts[i],
-                    rewardPart: 0.01,
+                    wallet: null,
+                    rewardPart: new anchor.BN(10_000_000),
                 });
             }
         });
@@ -71,9 +72,9 @@ describe("Sallar - Final staking", async () => {
             it("FAIL - (Final Staking Pool In Round Is Empty.)", async () => {
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -97,9 +98,9 @@ describe("Sallar - Final staking", async () => {
             it("FAIL - (final_staking) before solve all blocks", async () => {
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -133,14 +134,15 @@ describe("Sallar - Final staking", async () => {
                 user_info_final_staking.pop();
                 user_info_final_staking.push({
                     userPublicKey: testAccounts[0],
-                    rewardPart: -1.0,
+                    wallet: null,
+                    rewardPart: new anchor.BN(0),
                 });
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -174,14 +176,15 @@ describe("Sallar - Final staking", async () => {
                 user_info_final_staking.pop();
                 user_info_final_staking.push({
                     userPublicKey: testAccounts[0],
-                    rewardPart: 1.01,
+                    wallet: null,
+                    rewardPart: new anchor.BN(1_010_000_000),
                 });
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -216,9 +219,9 @@ describe("Sallar - Final staking", async () => {
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -265,7 +268,8 @@ describe("Sallar - Final staking", async () => {
                     user_info_final_staking = [];
                     user_info_final_staking.push({
                         userPublicKey: testAccounts[i],
-                        rewardPart: 0.8,
+                        wallet: null,
+                        rewardPart: new anchor.BN(800_000_000),
                     });
                 }
             });
@@ -273,9 +277,9 @@ describe("Sallar - Final staking", async () => {
             it("FAIL - (final_staking_account feature has not yet been unlocked)", async () => {
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -306,9 +310,9 @@ describe("Sallar - Final staking", async () => {
                         anchor.web3.Keypair.generate();
 
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount:
                                 invalid_blocks_state_address.publicKey,
                             finalStakingAccount: final_staking_address,
@@ -336,9 +340,9 @@ describe("Sallar - Final staking", async () => {
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: invalid_TokenInstructions.publicKey,
@@ -373,9 +377,9 @@ describe("Sallar - Final staking", async () => {
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalStaking(user_info_final_staking)
+                        .finalStaking(user_info_final_staking, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalStakingAccount: final_staking_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -69,6 +69,7 @@ describe("Sallar - Final mining", async () => {
 
                 user_info_final_mining.push({
                     userPublicKey: testAccounts[i],
+                    wallet: null,
                     finalMiningBalance: new anchor.BN(12_500_000_000_000),
                 });
             }
@@ -78,9 +79,9 @@ describe("Sallar - Final mining", async () => {
             it("FAIL - (Lack Of Funds To Pay The Reward)", async () => {
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalMining(user_info_final_mining)
+                        .finalMining(user_info_final_mining, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalMiningAccount: final_mining_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -106,9 +107,9 @@ describe("Sallar - Final mining", async () => {
             it("Pass - (mining)", async () => {
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalMining(user_info_final_mining)
+                        .finalMining(user_info_final_mining, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalMiningAccount: final_mining_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -134,9 +135,9 @@ describe("Sallar - Final mining", async () => {
 
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalMining(user_info_final_mining)
+                        .finalMining(user_info_final_mining, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalMiningAccount: final_mining_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
@@ -169,13 +170,14 @@ describe("Sallar - Final mining", async () => {
             it("FAIL - (final Mining feature has not yet been unlocked)", async () => {
                 user_info_final_mining.push({
                     userPublicKey: testAccounts[0],
+                    wallet: null,
                     finalMiningBalance: new anchor.BN(12_500_000_000_000),
                 });
                 try {
                     const tx: anchor.web3.Transaction = await program.methods
-                        .finalMining(user_info_final_mining)
+                        .finalMining(user_info_final_mining, null)
                         .remainingAccounts(rem_accounts)
-                        .accounts({
+                        .accountsPartial({
                             blocksStateAccount: blocks_state_address,
                             finalMiningAccount: final_mining_address,
                             tokenProgram: TOKEN_PROGRAM_ID,
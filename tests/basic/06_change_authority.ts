@@ -33,7 +33,7 @@ describe("Sallar - Change Authority", async () => {
 
             await program.methods
                 .changeAuthority(new_authority)
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     signer: provider.wallet.publicKey,
                 })
@@ -53,7 +53,7 @@ describe("Sallar - Change Authority", async () => {
             try {
                 await program.methods
                     .changeAuthority(new_authority)
-                    .accounts({
+                    .accountsPartial({
                         blocksStateAccount: blocks_state_address,
                         signer: new_authority,
                     })
@@ -0,0 +1,242 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import * as mpl from "@metaplex-foundation/mpl-token-metadata";
+import { createTransferInstruction, getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import {
+    ComputeBudgetProgram,
+    Connection,
+    PublicKey,
+    Transaction,
+} from "@solana/web3.js";
+import { assert } from "chai";
+import { Sallar } from "../../target/types/sallar";
+import { SallarVault } from "../../target/types/sallar_vault";
+import { getOrCreateAssociatedTokenAccount } from "../utils/accounts";
+import { findProgramAddress } from "../utils/pda";
+
+describe("Sallar - vault example (CPI)", async () => {
+    const provider: anchor.AnchorProvider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const program: Program<Sallar> = anchor.workspace.Sallar;
+    const vaultProgram: Program<SallarVault> = anchor.workspace.SallarVault;
+    const connection = new Connection("http://localhost:8899", "confirmed");
+
+    let blocks_state_address: anchor.web3.PublicKey = null;
+    let config_address: anchor.web3.PublicKey = null;
+    let mint_address: anchor.web3.PublicKey = null;
+    let distribution_top_block_address: anchor.web3.PublicKey = null;
+    let distribution_bottom_block_address: anchor.web3.PublicKey = null;
+    let final_staking_address: anchor.web3.PublicKey = null;
+    let final_mining_address: anchor.web3.PublicKey = null;
+
+    let owner_token_account: anchor.web3.PublicKey = null;
+    let vault_address: anchor.web3.PublicKey = null;
+    let vault_token_account_address: anchor.web3.PublicKey = null;
+    let user_claim_account_address: anchor.web3.PublicKey = null;
+
+    const deposit_amount = new anchor.BN(1_000_000);
+    const reward_amount = 250_000;
+
+    before("Initialize Sallar", async () => {
+        [mint_address] = findProgramAddress("sallar");
+        [blocks_state_address] = findProgramAddress("blocks_state");
+        [config_address] = findProgramAddress("config");
+        [final_staking_address] = findProgramAddress("final_staking");
+        [final_mining_address] = findProgramAddress("final_mining");
+        [distribution_top_block_address] = findProgramAddress("distribution_top_block");
+        [distribution_bottom_block_address] = findProgramAddress("distribution_bottom_block");
+
+        const [metadataPda] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from(anchor.utils.bytes.utf8.encode("metadata")),
+                Buffer.from(mpl.PROGRAM_ID.toBytes()),
+                Buffer.from(mint_address.toBytes()),
+            ],
+            mpl.PROGRAM_ID,
+        );
+
+        const tx = await program.methods
+            .initialize("Sallar", "ALL", "http://sallar.io", null, null, null)
+            .accountsPartial({
+                blocksStateAccount: blocks_state_address,
+                mint: mint_address,
+                distributionTopBlockAccount: distribution_top_block_address,
+                distributionBottomBlockAccount: distribution_bottom_block_address,
+                finalStakingAccount: final_staking_address,
+                finalMiningAccount: final_mining_address,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                signer: provider.wallet.publicKey,
+                systemProgram: anchor.web3.SystemProgram.programId,
+                metadataPda: metadataPda,
+                metadataProgram: mpl.PROGRAM_ID,
+            })
+            .transaction();
+
+        const additionalComputeBudgetInstruction =
+            ComputeBudgetProgram.setComputeUnitLimit({
+                units: 500_000,
+            });
+        const transaction = new Transaction()
+            .add(additionalComputeBudgetInstruction)
+            .add(tx);
+
+        await provider.sendAndConfirm(transaction, [], {
+            commitment: "confirmed",
+            maxRetries: 3
+        });
+
+        owner_token_account = await getOrCreateAssociatedTokenAccount(
+            provider,
+            mint_address,
+            provider.wallet.publicKey,
+            connection,
+        );
+
+        await program.methods
+            .initialTokenDistribution()
+            .accountsPartial({
+                blocksStateAccount: blocks_state_address,
+                mint: mint_address,
+                organizationAccount: owner_token_account,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                signer: provider.wallet.publicKey,
+            })
+            .rpc({maxRetries: 3});
+
+        [vault_address] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vault"), provider.wallet.publicKey.toBuffer()],
+            vaultProgram.programId,
+        );
+        [vault_token_account_address] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vault_token_account"), vault_address.toBuffer()],
+            vaultProgram.programId,
+        );
+        [user_claim_account_address] = PublicKey.findProgramAddressSync(
+            [Buffer.from("user_claim"), vault_token_account_address.toBuffer()],
+            program.programId,
+        );
+    });
+
+    it("PASS - initialize vault creates Sallar claim account through CPI", async () => {
+        const unlock_timestamp = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+
+        await vaultProgram.methods
+            .initializeVault(unlock_timestamp)
+            .accountsPartial({
+                vault: vault_address,
+                vaultTokenAccount: vault_token_account_address,
+                mint: mint_address,
+                sallarBlocksStateAccount: blocks_state_address,
+                sallarConfigAccount: config_address,
+                sallarUserClaimAccount: user_claim_account_address,
+                sallarProgram: program.programId,
+                owner: provider.wallet.publicKey,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: anchor.web3.SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            })
+            .rpc({maxRetries: 3});
+
+        const userClaimAccount = await program.account.userClaimAccount.fetch(
+            user_claim_account_address,
+        );
+
+        assert.equal(
+            userClaimAccount.user.toBase58(),
+            vault_token_account_address.toBase58(),
+        );
+    });
+
+    it("PASS - deposit locks tokens in the vault", async () => {
+        await vaultProgram.methods
+            .deposit(deposit_amount)
+            .accountsPartial({
+                vault: vault_address,
+                vaultTokenAccount: vault_token_account_address,
+                mint: mint_address,
+                ownerTokenAccount: owner_token_account,
+                owner: provider.wallet.publicKey,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc({maxRetries: 3});
+
+        const vault = await vaultProgram.account.vault.fetch(vault_address);
+        const vaultTokenAccount = await getAccount(connection, vault_token_account_address);
+
+        assert.equal(vault.lockedAmount.toString(), deposit_amount.toString());
+        assert.equal(vaultTokenAccount.amount.toString(), deposit_amount.toString());
+    });
+
+    it("PASS - forward rewards sends only tokens above the locked amount", async () => {
+        const rewardTransaction = new Transaction().add(
+            createTransferInstruction(
+                owner_token_account,
+                vault_token_account_address,
+                provider.wallet.publicKey,
+                reward_amount,
+            ),
+        );
+        await provider.sendAndConfirm(rewardTransaction, [], {
+            commitment: "confirmed",
+        });
+
+        const ownerBalanceBefore = (await getAccount(connection, owner_token_account)).amount;
+
+        await vaultProgram.methods
+            .forwardRewards()
+            .accountsPartial({
+                vault: vault_address,
+                vaultTokenAccount: vault_token_account_address,
+                mint: mint_address,
+                ownerTokenAccount: owner_token_account,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc({maxRetries: 3});
+
+        const ownerBalanceAfter = (await getAccount(connection, owner_token_account)).amount;
+        const vaultTokenAccount = await getAccount(connection, vault_token_account_address);
+
+        assert.equal((ownerBalanceAfter - ownerBalanceBefore).toString(), reward_amount.toString());
+        assert.equal(vaultTokenAccount.amount.toString(), deposit_amount.toString());
+    });
+
+    it("FAIL - forward rewards without new rewards", async () => {
+        try {
+            await vaultProgram.methods
+                .forwardRewards()
+                .accountsPartial({
+                    vault: vault_address,
+                    vaultTokenAccount: vault_token_account_address,
+                    mint: mint_address,
+                    ownerTokenAccount: owner_token_account,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+
+            assert.fail("Transaction succeeded but was expected to fail");
+        } catch (error) {
+            assert.equal(error.error.errorMessage, "No rewards to forward");
+        }
+    });
+
+    it("FAIL - withdraw before unlock timestamp", async () => {
+        try {
+            await vaultProgram.methods
+                .withdraw()
+                .accountsPartial({
+                    vault: vault_address,
+                    vaultTokenAccount: vault_token_account_address,
+                    mint: mint_address,
+                    ownerTokenAccount: owner_token_account,
+                    owner: provider.wallet.publicKey,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+
+            assert.fail("Transaction succeeded but was expected to fail");
+        } catch (error) {
+            assert.equal(error.error.errorMessage, "Vault is still locked");
+        }
+    });
+});
@@ -75,8 +75,11 @@ describe("Sallar - user rest mechanism - initialize", async () => {
                     token_name,
                     token_symbol,
                     token_metadata_uri,
+                    null,
+                    null,
+                    null,
                 )
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     mint: mint_address,
                     distributionTopBlockAccount: distribution_top_block_address,
@@ -108,6 +111,9 @@ describe("Sallar - user rest mechanism - initialize", async () => {
             const blocksState = await program.account.blocksState.fetch(
                 blocks_state_address,
             );
+            const config = await program.account.config.fetch(
+                findProgramAddress("config")[0],
+            );
 
             const tokenMetadata = await programs.metadata.Metadata.findByMint(
                 connection,
@@ -120,16 +126,16 @@ describe("Sallar - user rest mechanism - initialize", async () => {
             );
 
             assert.isTrue(
-                blocksState.mintNonce === mint_bump
+                config.mintNonce === mint_bump
             );
 
             assert.isTrue(
-                blocksState.topBlockDistributionAddress.toBase58() ===
+                config.topBlockDistributionAddress.toBase58() ===
                 distribution_top_block_address.toBase58()
             );
 
             assert.isTrue(
-                blocksState.topBlockDistributionNonce === distribution_top_block_bump
+                config.topBlockDistributionNonce === distribution_top_block_bump
             );
 
             assert.isTrue(
@@ -141,12 +147,22 @@ describe("Sallar - user rest mechanism - initialize", async () => {
             );
 
             assert.isTrue(
-                blocksState.bottomBlockDistributionAddress.toBase58() ===
+                config.bottomBlockDistributionAddress.toBase58() ===
                 distribution_bottom_block_address.toBase58()
             );
 
             assert.isTrue(
-                blocksState.bottomBlockDistributionNonce === distribution_bottom_block_bump
+                config.bottomBlockDistributionNonce === distribution_bottom_block_bump
+            );
+
+            assert.isTrue(
+                config.finalStakingAccountAddress.toBase58() ===
+                final_staking_address.toBase58()
+            );
+
+            assert.isTrue(
+                config.finalMiningAccountAddress.toBase58() ===
+                final_mining_address.toBase58()
             );
 
             assert.isTrue(
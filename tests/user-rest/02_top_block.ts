@@ -48,7 +48,7 @@ describe("Sallar - top block - user rest mechanism tests", async () => {
 		[distribution_top_block_address, distribution_top_block_bump] = findProgramAddress("distribution_top_block");
 		[distribution_bottom_block_address, distribution_bottom_block_bump] = findProgramAddress("distribution_bottom_block");
 
-        testAccounts = await getTestAccounts(0,2, connection);
+        testAccounts = await getTestAccounts(0,9, connection);
         for (let i = 0; i < testAccounts.length; i++) {
             rem_accounts.push({
                 pubkey: testAccounts[i],
@@ -59,16 +59,18 @@ describe("Sallar - top block - user rest mechanism tests", async () => {
 	});
 
     describe("Solve top block", async () => {
-        it("FAIL - Additional user request after user request that exceeds available BPs", async () => {
+        it("FAIL - Additional user requests after user request that exceeds available BPs exceed the rest carryovers limit", async () => {
             let userInfoTopBlock = [
-                { userPublicKey: testAccounts[0], userRequestWithoutBoost: new anchor.BN(21), userRequestWithBoost: new anchor.BN(21) },
-                { userPublicKey: testAccounts[1], userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(1) }
+                { userPublicKey: testAccounts[0], wallet: null, userRequestWithoutBoost: new anchor.BN(21), userRequestWithBoost: new anchor.BN(21) },
+                ...testAccounts.slice(1).map((testAccount) => (
+                    { userPublicKey: testAccount, wallet: null, userRequestWithoutBoost: new anchor.BN(1), userRequestWithBoost: new anchor.BN(1) }
+                ))
             ];
 
             const tx: anchor.web3.Transaction = await program.methods
-                .solveTopBlock(userInfoTopBlock as [])
+                .solveTopBlock(userInfoTopBlock as [], null, null, false, false, true)
                 .remainingAccounts(rem_accounts)
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     distributionTopBlockAccount: distribution_top_block_address,
                     mint: mint_address,
@@ -92,20 +94,20 @@ describe("Sallar - top block - user rest mechanism tests", async () => {
                 });
                 assert.fail("Transaction succeeded but was expected to fail");
             } catch (error) {  
-                assert.equal(error.message, 'failed to send transaction: Transaction simulation failed: Error processing Instruction 1: custom program error: 0x177a');
+                assert.equal(error.message, 'failed to send transaction: Transaction simulation failed: Error processing Instruction 1: custom program error: 0x17c5');
                 return;
             }
         });
 
         it("PASS - Single user request that exceeds available BPs processed correctly due to user rest mechanism", async () => {
             let userInfoTopBlock = [
-                { userPublicKey: testAccounts[0], userRequestWithoutBoost: new anchor.BN(42), userRequestWithBoost: new anchor.BN(0) }
+                { userPublicKey: testAccounts[0], wallet: null, userRequestWithoutBoost: new anchor.BN(42), userRequestWithBoost: new anchor.BN(0) }
             ];
 
             const tx: anchor.web3.Transaction = await program.methods
-                .solveTopBlock(userInfoTopBlock as [])
+                .solveTopBlock(userInfoTopBlock as [], null, null, false, false, true)
                 .remainingAccounts(rem_accounts)
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     distributionTopBlockAccount: distribution_top_block_address,
                     mint: mint_address,
@@ -134,6 +136,9 @@ describe("Sallar - top block - user rest mechanism tests", async () => {
             const blocksState = await program.account.blocksState.fetch(blocks_state_address);
             assert.equal(blocksState.topBlockLastAccountAddress.toBase58(), testAccounts[0].toBase58());
             assert.equal(blocksState.topBlockLastAccountRestBp.toNumber(), 22000);
+            assert.equal(blocksState.topBlockRestCarryovers.length, 1);
+            assert.equal(blocksState.topBlockRestCarryovers[0].user.toBase58(), testAccounts[0].toBase58());
+            assert.equal(blocksState.topBlockRestCarryovers[0].restBp.toNumber(), 22000);
         });
     });
 });
@@ -42,8 +42,8 @@ describe("Sallar - bottom block - user rest mechanism", async () => {
                 findProgramAddress("authority");
         });
 
-        it("FAIL - Additional user request after user request that exceeds available BPs", async () => {
-            let testAccounts: Array<anchor.web3.PublicKey> = await getTestAccounts(0, 3, connection);
+        it("FAIL - Additional user requests after user request that exceeds available BPs exceed the rest carryovers limit", async () => {
+            let testAccounts: Array<anchor.web3.PublicKey> = await getTestAccounts(0, 10, connection);
             for (let i = 0; i < testAccounts.length; i++) {
                 rem_accounts.push({
                     pubkey: testAccounts[i],
@@ -51,30 +51,16 @@ describe("Sallar - bottom block - user rest mechanism", async () => {
                     isSigner: false,
                 });
             }
-            user_info_bottom_block = [
-                {
-                    userPublicKey: testAccounts[0],
-                    userBalance: new anchor.BN(200_000_000_000_000),
-                    userRequestWithoutBoost: new anchor.BN(255),
-                    userRequestWithBoost: new anchor.BN(255)
-                },
-                {
-                    userPublicKey: testAccounts[1],
-                    userBalance: new anchor.BN(200_000_000_000_000),
-                    userRequestWithoutBoost: new anchor.BN(255),
-                    userRequestWithBoost: new anchor.BN(255),
-                },
-                {
-                    userPublicKey: testAccounts[2],
-                    userBalance: new anchor.BN(200_000_000_000_000),
-                    userRequestWithoutBoost: new anchor.BN(255),
-                    userRequestWithBoost: new anchor.BN(255),
-                }
-            ];
+            user_info_bottom_block = testAccounts.map((testAccount) => ({
+                userPublicKey: testAccount,
+                wallet: null,
+                userRequestWithoutBoost: new anchor.BN(255),
+                userRequestWithBoost: new anchor.BN(255),
+            }));
             const tx: anchor.web3.Transaction = await program.methods
-                .solveBottomBlock(user_info_bottom_block)
+                .solveBottomBlock(user_info_bottom_block, null, false, true)
                 .remainingAccounts(rem_accounts)
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     distributionBottomBlockAccount: distribution_bottom_block_address,
                     mint: mint_address,
@@ -98,7 +84,7 @@ describe("Sallar - bottom block - user rest mechanism", async () => {
                 });
                 assert.fail("Transaction succeeded but was expected to fail");
             } catch (error) {
-                assert.equal(error.message, 'failed to send transaction: Transaction simulation failed: Error processing Instruction 1: custom program error: 0x177a');
+                assert.equal(error.message, 'failed to send transaction: Transaction simulation failed: Error processing Instruction 1: custom program error: 0x17c5');
                 return;
             }
         });
@@ -115,21 +101,21 @@ describe("Sallar - bottom block - user rest mechanism", async () => {
             user_info_bottom_block = [
                 {
                     userPublicKey: testAccounts[0],
-                    userBalance: new anchor.BN(200_000_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(255),
                     userRequestWithBoost: new anchor.BN(255)
                 },
                 {
                     userPublicKey: testAccounts[1],
-                    userBalance: new anchor.BN(200_000_000_000_000),
+                    wallet: null,
                     userRequestWithoutBoost: new anchor.BN(255),
                     userRequestWithBoost: new anchor.BN(255),
                 }
             ];
             const tx: anchor.web3.Transaction = await program.methods
-                .solveBottomBlock(user_info_bottom_block)
+                .solveBottomBlock(user_info_bottom_block, null, false, true)
                 .remainingAccounts(rem_accounts)
-                .accounts({
+                .accountsPartial({
                     blocksStateAccount: blocks_state_address,
                     distributionBottomBlockAccount: distribution_bottom_block_address,
                     mint: mint_address,
@@ -159,6 +145,9 @@ describe("Sallar - bottom block - user rest mechanism", async () => {
             const blocksState = await program.account.blocksState.fetch(blocks_state_address);
             assert.equal(blocksState.bottomBlockLastAccountAddress.toBase58(), testAccounts[testAccounts.length - 1].toBase58());
             assert.equal(blocksState.bottomBlockLastAccountRestBp.toNumber(), 9363148050);
+            assert.equal(blocksState.bottomBlockRestCarryovers.length, 1);
+            assert.equal(blocksState.bottomBlockRestCarryovers[0].user.toBase58(), testAccounts[testAccounts.length - 1].toBase58());
+            assert.equal(blocksState.bottomBlockRestCarryovers[0].restBp.toNumber(), 9363148050);
         }); 
     });
 });